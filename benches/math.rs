@@ -0,0 +1,89 @@
+// Micro-benchmarks de src/math.rs (cargo bench --bench math [--features glam]).
+// Sin dependencias extra: cada caso se repite en lotes, se queda el mejor lote y se imprime
+// el tiempo por llamada. Junto a cada función se mide la forma anterior de hacer la misma
+// cuenta con productos de matrices completos, como referencia.
+
+#[allow(dead_code)]
+#[path = "../src/math.rs"]
+mod math;
+
+use std::hint::black_box;
+use std::time::Instant;
+use nalgebra_glm::{Mat3, Mat4, Vec3, Vec4};
+use math::{Backend, MathBackend};
+
+const ITERATIONS: u32 = 200_000;
+const BATCHES: u32 = 10;
+
+fn bench(name: &str, mut work: impl FnMut(u32)) {
+    let mut best = f64::MAX;
+    for _ in 0..BATCHES {
+        let start = Instant::now();
+        for i in 0..ITERATIONS {
+            work(i);
+        }
+        best = best.min(start.elapsed().as_secs_f64());
+    }
+    println!("{:<40}{:>9.2} ns/llamada", name, best * 1.0e9 / ITERATIONS as f64);
+}
+
+// Ángulos distintos en cada iteración para que el compilador no pliegue las cuentas
+fn rotation(i: u32) -> Vec3 {
+    let t = i as f32 * 0.001;
+    Vec3::new(t, t * 0.7, t * 0.3)
+}
+
+fn composed_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
+    let rotation_x = nalgebra_glm::rotation(rotation.x, &Vec3::x());
+    let rotation_y = nalgebra_glm::rotation(rotation.y, &Vec3::y());
+    let rotation_z = nalgebra_glm::rotation(rotation.z, &Vec3::z());
+    let transform = nalgebra_glm::translation(&translation) * nalgebra_glm::scaling(&Vec3::repeat(scale));
+    transform * rotation_z * rotation_y * rotation_x
+}
+
+fn main() {
+    let translation = Vec3::new(600.0, 400.0, 0.0);
+    let viewport = math::create_viewport_matrix(2400.0, 1600.0);
+    let model = math::create_model_matrix(translation, 80.0, Vec3::new(0.4, 1.2, 0.1));
+
+    bench("create_model_matrix", |i| {
+        black_box(math::create_model_matrix(black_box(translation), 80.0, rotation(i)));
+    });
+    bench("  referencia: 4 productos Mat4", |i| {
+        black_box(composed_model_matrix(black_box(translation), 80.0, rotation(i)));
+    });
+
+    bench("create_normal_matrix", |_| {
+        black_box(math::create_normal_matrix(black_box(&model)));
+    });
+    bench("  referencia: Mat3 a mano + traspuesta", |_| {
+        let model = black_box(&model);
+        let block = Mat3::new(
+            model[0], model[1], model[2],
+            model[4], model[5], model[6],
+            model[8], model[9], model[10],
+        );
+        black_box(block.transpose().try_inverse().unwrap_or(Mat3::identity()));
+    });
+
+    bench("viewport_transform", |i| {
+        let ndc = Vec3::new(i as f32 * 1.0e-6, 0.5, 0.25);
+        black_box(math::viewport_transform(black_box(&viewport), ndc));
+    });
+    bench("  referencia: producto Mat4 * Vec4", |i| {
+        let ndc = Vec4::new(i as f32 * 1.0e-6, 0.5, 0.25, 1.0);
+        black_box(black_box(&viewport) * ndc);
+    });
+
+    // Producto del vertex shader con el backend activo (nalgebra o glam)
+    let mvp = Backend::mat4(&(nalgebra_glm::reversed_perspective_rh_zo(1.5, 0.8, 0.1, 100_000.0) * model));
+    let normal_matrix = Backend::mat3(&math::create_normal_matrix(&model));
+    bench("Backend::transform_point", |i| {
+        let point = Vec3::new(i as f32 * 1.0e-6, 0.5, 0.25);
+        black_box(Backend::transform_point(black_box(&mvp), &point));
+    });
+    bench("Backend::transform_vector", |i| {
+        let normal = Vec3::new(i as f32 * 1.0e-6, 0.5, 0.25);
+        black_box(Backend::transform_vector(black_box(&normal_matrix), &normal));
+    });
+}
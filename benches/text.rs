@@ -0,0 +1,81 @@
+// Micro-benchmarks de src/text.rs (cargo bench --bench text), con el mismo método que los de
+// math.rs: cada caso se repite en lotes, se queda el mejor lote y se imprime el tiempo por
+// llamada. Los textos son del tamaño de lo que dibuja la interfaz en cada frame: etiquetas
+// cortas, líneas de paneles y títulos grandes, con y sin contorno.
+
+#[allow(dead_code)]
+#[path = "../src/draw_state.rs"]
+mod draw_state;
+#[allow(dead_code)]
+#[path = "../src/framebuffer.rs"]
+mod framebuffer;
+#[allow(dead_code)]
+#[path = "../src/text.rs"]
+mod text;
+
+use std::hint::black_box;
+use std::time::Instant;
+use framebuffer::Framebuffer;
+use text::{Align, TextStyle};
+
+const ITERATIONS: u32 = 20_000;
+const BATCHES: u32 = 10;
+
+fn bench(name: &str, mut work: impl FnMut(u32)) {
+    let mut best = f64::MAX;
+    for _ in 0..BATCHES {
+        let start = Instant::now();
+        for i in 0..ITERATIONS {
+            work(i);
+        }
+        best = best.min(start.elapsed().as_secs_f64());
+    }
+    println!("{:<40}{:>9.2} µs/llamada", name, best * 1.0e6 / ITERATIONS as f64);
+}
+
+fn main() {
+    let mut screen = Framebuffer::new(1200, 800);
+    let label = "Júpiter";
+    let line = "Órbita: 700.0   Vel. angular: 5.73 °/s";
+    let title = "Estación espacial";
+    // El primer texto construye el atlas: fuera de la medida
+    text::draw_text(&mut screen, 0, 0, label, 0xFFFFFF, 1);
+
+    // Posición distinta en cada iteración, como las etiquetas que siguen a los cuerpos
+    let position = |i: u32| ((i % 1000) as i32, (i % 700) as i32);
+
+    bench("draw_text etiqueta (x1)", |i| {
+        let (x, y) = position(i);
+        text::draw_text(black_box(&mut screen), x, y, black_box(label), 0xDDE6FF, 1);
+    });
+    bench("draw_text línea de panel (x1)", |i| {
+        let (x, y) = position(i);
+        text::draw_text(black_box(&mut screen), x, y, black_box(line), 0xC8D2E6, 1);
+    });
+    bench("draw_text título (x2, Scale2x)", |i| {
+        let (x, y) = position(i);
+        text::draw_text(black_box(&mut screen), x, y, black_box(title), 0xFFD24A, 2);
+    });
+    bench("draw_text título (x4, bloques)", |i| {
+        let (x, y) = position(i);
+        text::draw_text(black_box(&mut screen), x, y, black_box(title), 0xFFD24A, 4);
+    });
+
+    let outlined = TextStyle::new(0xDDE6FF).with_outline(0x000000).with_align(Align::Center);
+    bench("draw_styled etiqueta con contorno", |i| {
+        let (x, y) = position(i);
+        text::draw_styled(black_box(&mut screen), x, y, black_box(label), &outlined);
+    });
+    let shadowed = TextStyle::new(0xFFD24A).with_scale(2).with_shadow(0x3A2A00);
+    bench("draw_styled título con sombra (x2)", |i| {
+        let (x, y) = position(i);
+        text::draw_styled(black_box(&mut screen), x, y, black_box(title), &shadowed);
+    });
+
+    bench("text_width línea de panel", |_| {
+        black_box(text::text_width(black_box(line), 1));
+    });
+    bench("wrap_text descripción (40 glifos)", |_| {
+        black_box(text::wrap_text(black_box("El mayor planeta del sistema solar, un gigante de gas con una tormenta más grande que la Tierra."), 40));
+    });
+}
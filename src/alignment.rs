@@ -0,0 +1,261 @@
+use minifb::{Key, KeyRepeat};
+use nalgebra_glm::Vec3;
+use crate::celestial_shaders::CelestialBody;
+use crate::ephemeris::EphemerisClock;
+use crate::events::{angle_between, Forecast};
+use crate::framebuffer::Framebuffer;
+use crate::input::Input;
+use crate::scene_graph::SceneGraph;
+use crate::text::{draw_text, text_width, GLYPH_SIZE};
+use crate::theme;
+use crate::{Camera, CelestialObject};
+
+// ============= BUSCADOR DE ALINEACIONES =============
+// Busca en el futuro de la simulación los momentos en que dos cuerpos elegidos se ven casi
+// en la misma dirección desde la Tierra o desde la cámara. Cada mínimo de separación por
+// debajo del umbral es un resultado; 1-9 o un clic sobre él adelantan el tiempo hasta ese
+// instante y encuadran la pareja desde el observador.
+// C abre y cierra el panel; ↑/↓ eligen campo, ←/→ cambian su valor y Enter busca.
+
+const SEARCH_HORIZON: f32 = 300.0;  // tiempo de simulación explorado
+const STEP: f32 = 0.05;
+const MAX_SEPARATION: f32 = 5.0;    // grados
+const MAX_RESULTS: usize = 9;
+
+const PADDING: usize = 12;
+const LINE_HEIGHT: usize = GLYPH_SIZE + 4;
+const HEADER_LINES: usize = 6; // título, tres campos, ayuda y línea en blanco
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Observer {
+    Earth,
+    Camera,
+}
+
+// Alineación encontrada, con las posiciones de ese instante para encuadrarla
+#[derive(Clone, Copy)]
+pub struct Alignment {
+    pub time: f32,
+    pub separation: f32, // grados
+    observer: Vec3,
+    first: Vec3,
+    second: Vec3,
+    observer_radius: f32,
+}
+
+impl Alignment {
+    // Coloca la cámara junto al observador mirando hacia el punto medio de la pareja
+    pub fn frame(&self, camera: &mut Camera) {
+        let to_first = (self.first - self.observer).normalize();
+        let to_second = (self.second - self.observer).normalize();
+        let direction = (to_first + to_second).normalize();
+        let distance = (self.first - self.observer).magnitude().min((self.second - self.observer).magnitude());
+
+        // Desde la Tierra: justo por encima de su polo norte para que no tape la vista
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let eye = self.observer + up * self.observer_radius * 1.5;
+        camera.place(eye, eye + direction * distance, up);
+    }
+}
+
+pub enum FinderAction {
+    Search,
+    Jump(usize),
+}
+
+pub struct AlignmentFinder {
+    open: bool,
+    field: usize, // 0 = cuerpo A, 1 = cuerpo B, 2 = observador
+    first: usize,
+    second: usize,
+    observer: Observer,
+    results: Vec<Alignment>,
+    searched: bool,
+}
+
+impl AlignmentFinder {
+    pub fn new() -> Self {
+        AlignmentFinder {
+            open: false,
+            field: 0,
+            first: 0,
+            second: 1,
+            observer: Observer::Earth,
+            results: Vec::new(),
+            searched: false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    // Al abrirse, el cuerpo A pasa a ser el seleccionado (si lo hay)
+    pub fn toggle(&mut self, selected: Option<usize>) {
+        self.open = !self.open;
+        if let Some(index) = selected.filter(|_| self.open) {
+            if index != self.first {
+                self.first = index;
+                self.clear_results();
+            }
+        }
+    }
+
+    pub fn handle_input(&mut self, input: &Input, body_count: usize) -> Option<FinderAction> {
+        if input.is_key_pressed(Key::Up, KeyRepeat::No) {
+            self.field = (self.field + 2) % 3;
+        }
+        if input.is_key_pressed(Key::Down, KeyRepeat::No) {
+            self.field = (self.field + 1) % 3;
+        }
+        let step = if input.is_key_pressed(Key::Right, KeyRepeat::Yes) {
+            1
+        } else if input.is_key_pressed(Key::Left, KeyRepeat::Yes) {
+            body_count.max(1) - 1
+        } else {
+            0
+        };
+        if step > 0 && body_count > 0 {
+            match self.field {
+                0 => self.first = (self.first + step) % body_count,
+                1 => self.second = (self.second + step) % body_count,
+                _ => {
+                    self.observer = match self.observer {
+                        Observer::Earth => Observer::Camera,
+                        Observer::Camera => Observer::Earth,
+                    }
+                }
+            }
+            self.clear_results();
+        }
+        if input.is_key_pressed(Key::Enter, KeyRepeat::No) {
+            return Some(FinderAction::Search);
+        }
+
+        let keys = [Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9];
+        keys.iter()
+            .position(|&key| input.is_key_pressed(key, KeyRepeat::No))
+            .filter(|&index| index < self.results.len())
+            .map(FinderAction::Jump)
+    }
+
+    fn clear_results(&mut self) {
+        self.results.clear();
+        self.searched = false;
+    }
+
+    pub fn result(&self, index: usize) -> Option<Alignment> {
+        self.results.get(index).copied()
+    }
+
+    // Recorre el futuro y guarda los mínimos locales de separación bajo el umbral
+    pub fn search(&mut self, objects: &[CelestialObject], graph: &SceneGraph, clock: Option<&EphemerisClock>, time: f32, camera_position: Vec3) {
+        self.results.clear();
+        self.searched = true;
+        let earth = objects.iter().position(|obj| obj.shader.body == CelestialBody::Earth);
+        let (first, second) = (self.first, self.second);
+        if first == second || first >= objects.len() || second >= objects.len() {
+            return;
+        }
+
+        let mut forecast = Forecast::new(objects, graph, clock, time);
+        let observe = |objects: &[CelestialObject], t: f32| {
+            let (observer, observer_radius) = match (self.observer, earth) {
+                (Observer::Earth, Some(earth)) => (objects[earth].transform.translation, objects[earth].transform.scale),
+                _ => (camera_position, 0.0),
+            };
+            let (a, b) = (objects[first].transform.translation, objects[second].transform.translation);
+            Alignment {
+                time: t,
+                separation: angle_between(a - observer, b - observer),
+                observer,
+                first: a,
+                second: b,
+                observer_radius,
+            }
+        };
+
+        // Ventana deslizante de tres muestras: el centro es un mínimo si no supera a sus vecinas
+        let mut previous = observe(forecast.at(time), time);
+        let mut current = observe(forecast.at(time + STEP), time + STEP);
+        let steps = (SEARCH_HORIZON / STEP) as usize;
+        for step in 2..=steps {
+            let t = time + step as f32 * STEP;
+            let next = observe(forecast.at(t), t);
+            if current.separation < MAX_SEPARATION
+                && current.separation <= previous.separation
+                && current.separation < next.separation
+            {
+                self.results.push(current);
+                if self.results.len() == MAX_RESULTS {
+                    return;
+                }
+            }
+            previous = current;
+            current = next;
+        }
+    }
+
+    // Fila de resultado bajo el cursor (para saltar con un clic)
+    pub fn result_at(&self, screen_height: usize, x: f32, y: f32, names: &[&str]) -> Option<usize> {
+        let (panel_x, panel_y, width, _) = self.layout(screen_height, names);
+        if x < panel_x as f32 || x >= (panel_x + width) as f32 {
+            return None;
+        }
+        let first_row = (panel_y + PADDING + HEADER_LINES * LINE_HEIGHT) as f32;
+        let row = ((y - first_row) / LINE_HEIGHT as f32).floor();
+        (row >= 0.0 && (row as usize) < self.results.len()).then_some(row as usize)
+    }
+
+    fn lines(&self, names: &[&str], time: f32) -> Vec<(String, u32)> {
+        let theme = theme::current();
+        let name = |index: usize| names.get(index).copied().unwrap_or("?");
+        let observer = match self.observer {
+            Observer::Earth => "Tierra",
+            Observer::Camera => "Cámara",
+        };
+        let fields = [
+            format!("Cuerpo A:    < {} >", name(self.first)),
+            format!("Cuerpo B:    < {} >", name(self.second)),
+            format!("Observador:  < {} >", observer),
+        ];
+
+        let mut lines = vec![("Buscador de alineaciones".to_string(), theme.title)];
+        for (i, field) in fields.into_iter().enumerate() {
+            let (marker, color) = if i == self.field { ("> ", theme.accent) } else { ("  ", theme.text) };
+            lines.push((format!("{}{}", marker, field), color));
+        }
+        lines.push(("Enter: buscar en el futuro".to_string(), theme.muted));
+        lines.push((String::new(), theme.text));
+
+        for (i, result) in self.results.iter().enumerate() {
+            lines.push((format!("{}  +{:<6.1} separación {:.2}°", i + 1, result.time - time, result.separation), theme.text));
+        }
+        if self.searched && self.results.is_empty() {
+            lines.push((format!("Sin alineaciones bajo {:.0}° en {:.0} de tiempo", MAX_SEPARATION, SEARCH_HORIZON), theme.muted));
+        }
+        lines.push((String::new(), theme.text));
+        lines.push(("1-9 o clic: saltar y encuadrar   C: cerrar".to_string(), theme.muted));
+        lines
+    }
+
+    // Panel a la izquierda, centrado en vertical: (x, y, ancho, alto)
+    fn layout(&self, screen_height: usize, names: &[&str]) -> (usize, usize, usize, usize) {
+        let lines = self.lines(names, 0.0);
+        let width = lines.iter().map(|(line, _)| text_width(line, 1)).max().unwrap_or(0).max(40 * GLYPH_SIZE) + PADDING * 2;
+        let height = PADDING * 2 + lines.len() * LINE_HEIGHT;
+        (PADDING, screen_height.saturating_sub(height) / 2, width, height)
+    }
+
+    pub fn draw(&self, screen: &mut Framebuffer, names: &[&str], time: f32) {
+        let theme = theme::current();
+        let (x, y, width, height) = self.layout(screen.height, names);
+        screen.blend_rect(x, y, width, height, theme.panel_background, theme.panel_opacity);
+
+        let mut text_y = (y + PADDING) as i32;
+        for (line, color) in self.lines(names, time) {
+            draw_text(screen, (x + PADDING) as i32, text_y, &line, color, 1);
+            text_y += LINE_HEIGHT as i32;
+        }
+    }
+}
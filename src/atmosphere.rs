@@ -0,0 +1,248 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use rayon::prelude::*;
+use crate::celestial_shaders::CelestialBody;
+use crate::color::Color;
+use crate::framebuffer::{Framebuffer, FAR_DEPTH};
+use crate::noise::hash3;
+use crate::weather;
+use crate::CelestialObject;
+
+// ============= ENTRADA ATMOSFÉRICA =============
+// Cuando la cámara cruza la capa atmosférica de un cuerpo, el cielo se tiñe con el color
+// de su atmósfera, aparece una bruma que crece con la profundidad, el Sol se atenúa y, si
+// la cámara desciende deprisa, estelas de plasma por el calentamiento. Enlaza la vista
+// desde el espacio con el modo terreno de cerca.
+
+pub struct AtmosphereProfile {
+    pub sky: Color,      // color del cielo a pleno día
+    pub thickness: f32,  // grosor de la capa, en radios del cuerpo
+    pub density: f32,    // 1 = tan densa como la de la Tierra
+}
+
+// Atmósfera de cada tipo de cuerpo (None si no tiene o es un gigante gaseoso)
+pub fn profile(body: CelestialBody) -> Option<AtmosphereProfile> {
+    let (sky, thickness, density) = match body {
+        CelestialBody::Earth => (Color::from_float(0.35, 0.58, 0.95), 0.5, 1.0),
+        CelestialBody::Mars => (Color::from_float(0.80, 0.58, 0.40), 0.35, 0.45),
+        CelestialBody::LavaPlanet => (Color::from_float(0.55, 0.25, 0.12), 0.4, 0.9),
+        CelestialBody::IcePlanet => (Color::from_float(0.70, 0.85, 1.0), 0.3, 0.4),
+        CelestialBody::AlienPlanet => (Color::from_float(0.65, 0.35, 0.85), 0.45, 0.8),
+        CelestialBody::Procedural(planet) => (planet.biome.palette().atmosphere, 0.45, 0.8),
+        _ => return None,
+    };
+    Some(AtmosphereProfile { sky, thickness, density })
+}
+
+// Clima que altera la atmósfera: una tormenta de polvo global espesa el aire de Marte
+// y oscurece su cielo hacia el pardo
+fn with_weather(mut profile: AtmosphereProfile, body: CelestialBody, time: f32) -> AtmosphereProfile {
+    if body == CelestialBody::Mars {
+        let dust = weather::global_dust_level(time);
+        let dusty_sky = Color::from_float(0.50, 0.34, 0.22).to_float();
+        let sky = profile.sky.to_float();
+        profile.sky = Color::from_float(
+            sky.0 + (dusty_sky.0 - sky.0) * dust,
+            sky.1 + (dusty_sky.1 - sky.1) * dust,
+            sky.2 + (dusty_sky.2 - sky.2) * dust,
+        );
+        profile.density *= 1.0 + dust * 1.5;
+    }
+    profile
+}
+
+// Estado de la cámara dentro de una atmósfera en este frame
+pub struct Entry {
+    pub profile: AtmosphereProfile,
+    pub depth: f32,     // 0 = borde superior de la capa, 1 = superficie
+    pub daylight: f32,  // 0 = noche, 1 = Sol alto en el cielo
+    pub heating: f32,   // intensidad de las estelas de plasma [0, 1]
+}
+
+// Velocidad de descenso (unidades por frame) a partir de la cual aparece el plasma
+const HEATING_MIN_SPEED: f32 = 2.0;
+const HEATING_FULL_SPEED: f32 = 10.0;
+
+// Atmósfera más profunda en la que está la cámara (None si está en el espacio)
+pub fn find_entry(
+    objects: &[CelestialObject],
+    camera_position: Vec3,
+    previous_camera_position: Vec3,
+    light_position: Vec3,
+    time: f32,
+) -> Option<Entry> {
+    objects
+        .iter()
+        .filter_map(|obj| {
+            let profile = with_weather(profile(obj.shader.body)?, obj.shader.body, time);
+            let to_camera = camera_position - obj.transform.translation;
+            let altitude = to_camera.magnitude() - obj.transform.scale;
+            let depth = 1.0 - altitude / (obj.transform.scale * profile.thickness);
+            if !(0.0..=1.5).contains(&depth) {
+                return None;
+            }
+            let depth = depth.min(1.0);
+
+            // Vertical local: de día si el Sol está sobre el horizonte de la cámara
+            let up = to_camera.normalize();
+            let to_sun = (light_position - camera_position).normalize();
+            let daylight = ((up.dot(&to_sun) + 0.15) / 0.4).clamp(0.0, 1.0);
+
+            // El plasma es más intenso en las capas medias (arriba no hay aire, abajo ya se frenó)
+            let descent = (previous_camera_position - camera_position).dot(&up);
+            let speed = ((descent - HEATING_MIN_SPEED) / (HEATING_FULL_SPEED - HEATING_MIN_SPEED)).clamp(0.0, 1.0);
+            let heating = (depth * std::f32::consts::PI).sin().max(0.0) * speed * profile.density.min(1.0);
+
+            Some(Entry { profile, depth, daylight, heating })
+        })
+        .max_by(|a, b| a.depth.total_cmp(&b.depth))
+}
+
+// Bóveda celeste vista desde la superficie: el color depende de la dirección de cada píxel
+// (cenit más oscuro, horizonte más claro, resplandor alrededor del Sol y atardecer cuando
+// el Sol está bajo). En vuelo el cielo es de un color uniforme
+pub struct SkyDome {
+    inverse_screen: Mat4, // pantalla -> mundo (inversa de viewport * proyección * vista)
+    camera_position: Vec3,
+    up: Vec3,             // vertical local en el punto de aterrizaje
+    sun_direction: Vec3,
+}
+
+impl SkyDome {
+    pub fn new(
+        view_matrix: &Mat4,
+        projection_matrix: &Mat4,
+        viewport_matrix: &Mat4,
+        camera_position: Vec3,
+        up: Vec3,
+        light_position: Vec3,
+    ) -> Option<Self> {
+        let inverse_screen = (viewport_matrix * projection_matrix * view_matrix).try_inverse()?;
+        Some(SkyDome {
+            inverse_screen,
+            camera_position,
+            up,
+            sun_direction: (light_position - camera_position).normalize(),
+        })
+    }
+
+    // Dirección del rayo que pasa por el centro del píxel
+    fn ray(&self, x: f32, y: f32) -> Vec3 {
+        let point = self.inverse_screen * Vec4::new(x + 0.5, y + 0.5, 0.5, 1.0);
+        (Vec3::new(point.x, point.y, point.z) / point.w - self.camera_position).normalize()
+    }
+
+    fn color(&self, entry: &Entry, direction: Vec3) -> (f32, f32, f32) {
+        let sky = entry.profile.sky.to_float();
+        let light = 0.12 + 0.88 * entry.daylight;
+
+        // Cerca del horizonte el aire es más espeso y el cielo más blanquecino
+        let elevation = direction.dot(&self.up).max(0.0);
+        let horizon = (1.0 - elevation).powi(3);
+        let zenith = (sky.0 * light * 0.8, sky.1 * light * 0.8, sky.2 * light);
+        let pale = (0.5 + sky.0 * 0.5, 0.5 + sky.1 * 0.5, 0.5 + sky.2 * 0.5);
+        let mut color = (
+            zenith.0 + (pale.0 * light - zenith.0) * horizon,
+            zenith.1 + (pale.1 * light - zenith.1) * horizon,
+            zenith.2 + (pale.2 * light - zenith.2) * horizon,
+        );
+
+        // Atardecer: con el Sol cerca del horizonte, el horizonte de su lado se vuelve naranja
+        let sun_elevation = self.sun_direction.dot(&self.up);
+        let toward_sun = (direction.dot(&self.sun_direction) * 0.5 + 0.5).powi(4);
+        let sunset = (1.0 - sun_elevation.abs() * 4.0).clamp(0.0, 1.0) * horizon * toward_sun;
+        color = (color.0 + sunset * 0.9, color.1 + sunset * 0.4, color.2 + sunset * 0.1);
+
+        // Resplandor alrededor del disco solar (solo con el Sol sobre el horizonte)
+        let glow = direction.dot(&self.sun_direction).max(0.0);
+        let glow = (glow.powi(64) * 0.6 + glow.powi(8) * 0.15) * entry.daylight;
+        (color.0 + glow, color.1 + glow * 0.95, color.2 + glow * 0.8)
+    }
+}
+
+// Cielo, bruma y atenuación del Sol sobre el framebuffer ya renderizado (usa el z-buffer:
+// los píxeles sin geometría son cielo). `sun_disk` = (centro x, centro y, radio) en píxeles.
+// Con `dome` (cámara posada en la superficie) el cielo y la bruma toman el color de la bóveda
+pub fn apply_sky_and_haze(
+    framebuffer: &mut Framebuffer,
+    entry: &Entry,
+    sun_disk: Option<(f32, f32, f32)>,
+    dome: Option<&SkyDome>,
+) {
+    let density = entry.profile.density;
+    let sky = entry.profile.sky * (0.12 + 0.88 * entry.daylight);
+    let sky_alpha = entry.depth.powf(0.7) * (density * 1.2).min(1.0);
+    let haze_alpha = entry.depth * density * 0.35;
+    let transmittance = 1.0 - entry.depth * density * 0.5;
+    let sky_rgb = sky.to_float();
+    let width = framebuffer.width;
+
+    framebuffer
+        .buffer
+        .par_iter_mut()
+        .zip(framebuffer.zbuffer.par_iter())
+        .enumerate()
+        .for_each(|(index, (pixel, &depth))| {
+            let mut color = Color::from_hex(*pixel).to_float();
+            let is_sky = depth == FAR_DEPTH;
+
+            // El disco del Sol pierde brillo al atravesar más aire
+            if let Some((sun_x, sun_y, sun_radius)) = sun_disk {
+                let dx = (index % width) as f32 - sun_x;
+                let dy = (index / width) as f32 - sun_y;
+                if !is_sky && dx * dx + dy * dy <= sun_radius * sun_radius {
+                    color = (color.0 * transmittance, color.1 * transmittance, color.2 * transmittance);
+                }
+            }
+
+            let alpha = if is_sky { sky_alpha } else { haze_alpha };
+            let sky_rgb = match dome {
+                Some(dome) => dome.color(entry, dome.ray((index % width) as f32, (index / width) as f32)),
+                None => sky_rgb,
+            };
+            *pixel = Color::from_float(
+                color.0 + (sky_rgb.0 - color.0) * alpha,
+                color.1 + (sky_rgb.1 - color.1) * alpha,
+                color.2 + (sky_rgb.2 - color.2) * alpha,
+            )
+            .to_hex();
+        });
+}
+
+// Estelas de plasma que fluyen desde el punto hacia el que se mueve la cámara
+pub fn draw_plasma_streaks(screen: &mut Framebuffer, entry: &Entry, time: f32, focus: (f32, f32)) {
+    const STREAK_COUNT: i32 = 70;
+    const PLASMA_COLOR: u32 = 0xFFB060;
+    const GLOW_COLOR: u32 = 0xFF6A20;
+
+    if entry.heating <= 0.01 {
+        return;
+    }
+
+    // Resplandor general del escudo térmico
+    screen.blend_rect(0, 0, screen.width, screen.height, GLOW_COLOR, entry.heating * 0.12);
+
+    let diagonal = (screen.width as f32).hypot(screen.height as f32);
+    for i in 0..STREAK_COUNT {
+        let angle = hash3(i, 7, 13) * std::f32::consts::TAU;
+        let speed = 0.8 + hash3(i, 3, 5) * 1.2;
+        let travel = (time * speed + hash3(i, 11, 17)).fract();
+
+        // Cada estela sale del foco y se alarga y acelera hacia los bordes
+        let start = diagonal * (0.08 + travel * travel * 0.6);
+        let length = diagonal * (0.03 + 0.09 * travel) * entry.heating;
+        let (sin_a, cos_a) = angle.sin_cos();
+        let alpha = entry.heating * (1.0 - travel) * (0.4 + 0.5 * hash3(i, 19, 23));
+
+        let steps = length.max(1.0) as i32;
+        for step in 0..steps {
+            let r = start + step as f32;
+            let x = focus.0 + cos_a * r;
+            let y = focus.1 + sin_a * r;
+            if x >= 0.0 && y >= 0.0 {
+                // Más brillante en la cabeza de la estela
+                let fade = 1.0 - step as f32 / steps as f32;
+                screen.blend_pixel(x as usize, y as usize, PLASMA_COLOR, alpha * (0.3 + 0.7 * fade));
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use serde::Deserialize;
+
+// ============= SONIDO =============
+// Con la feature `audio` (cargo run --features audio) el programa suena con rodio: una
+// música ambiental en bucle, el ruido de los motores de la nave (más fuerte cuanto más
+// empujan), los disparos láser y las explosiones situados en el espacio (a un lado u otro y
+// más débiles cuanto más lejos de la cámara) y un clic al cambiar la selección o abrir y
+// cerrar un panel. No hay archivos de sonido: todo se sintetiza al arrancar. Los volúmenes
+// se ajustan en la tabla [audio] de settings.toml. Sin la feature, o sin dispositivo de
+// sonido, `Audio` no hace nada y el resto del programa no cambia.
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct AudioConfig {
+    pub enabled: bool,
+    pub master_volume: f32,  // multiplica a todos los demás
+    pub music_volume: f32,
+    pub effects_volume: f32, // motores, láseres, explosiones y clics
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        AudioConfig { enabled: true, master_volume: 0.8, music_volume: 0.5, effects_volume: 1.0 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sound {
+    Laser,
+    Explosion,
+    Click,
+}
+
+#[cfg(feature = "audio")]
+pub use output::Audio;
+#[cfg(not(feature = "audio"))]
+pub use silent::Audio;
+
+// Sin la feature: la misma interfaz, sin sonido
+#[cfg(not(feature = "audio"))]
+mod silent {
+    use super::{AudioConfig, Sound};
+    use nalgebra_glm::Vec3;
+
+    pub struct Audio;
+
+    impl Audio {
+        pub fn new(_config: AudioConfig) -> Self {
+            Audio
+        }
+
+        pub fn set_listener(&mut self, _position: Vec3, _right: Vec3) {}
+
+        pub fn engine(&mut self, _throttle: f32) {}
+
+        pub fn play(&mut self, _sound: Sound, _position: Option<Vec3>) {}
+    }
+}
+
+#[cfg(feature = "audio")]
+mod output {
+    use super::{synth, AudioConfig, Sound};
+    use nalgebra_glm::Vec3;
+    use rodio::buffer::SamplesBuffer;
+    use rodio::{OutputStream, OutputStreamHandle, Sink, Source, SpatialSink};
+
+    // Distancia (unidades de la escena) hasta la que un sonido se oye a todo volumen; más
+    // lejos cae con el cuadrado de la distancia
+    const HEARING_DISTANCE: f32 = 150.0;
+    const EAR_OFFSET: f32 = 0.1; // media separación entre los oídos, en HEARING_DISTANCE
+
+    struct Device {
+        _stream: OutputStream, // el sonido se corta al soltarlo
+        handle: OutputStreamHandle,
+        engine: Sink,
+        _music: Sink,
+        laser: Vec<f32>,
+        explosion: Vec<f32>,
+        click: Vec<f32>,
+    }
+
+    pub struct Audio {
+        device: Option<Device>,
+        config: AudioConfig,
+        listener: (Vec3, Vec3), // posición de la cámara y su derecha
+    }
+
+    impl Audio {
+        // Abre el dispositivo por defecto; si no hay, sigue en silencio con un aviso
+        pub fn new(config: AudioConfig) -> Self {
+            let device = if config.enabled { Self::open(&config) } else { None };
+            Audio { device, config, listener: (Vec3::zeros(), Vec3::x()) }
+        }
+
+        fn open(config: &AudioConfig) -> Option<Device> {
+            let (stream, handle) = OutputStream::try_default()
+                .map_err(|err| eprintln!("Sin sonido: {}", err))
+                .ok()?;
+            let sink = || Sink::try_new(&handle).map_err(|err| eprintln!("Sin sonido: {}", err)).ok();
+            let music = sink()?;
+            music.set_volume(config.master_volume * config.music_volume);
+            music.append(SamplesBuffer::new(1, synth::SAMPLE_RATE, synth::music()).repeat_infinite());
+            let engine = sink()?;
+            engine.set_volume(0.0);
+            engine.append(SamplesBuffer::new(1, synth::SAMPLE_RATE, synth::engine()).repeat_infinite());
+            Some(Device {
+                _stream: stream,
+                handle,
+                engine,
+                _music: music,
+                laser: synth::laser(),
+                explosion: synth::explosion(),
+                click: synth::click(),
+            })
+        }
+
+        // Desde dónde se oye: la cámara, con los oídos sobre su eje derecho
+        pub fn set_listener(&mut self, position: Vec3, right: Vec3) {
+            self.listener = (position, right.try_normalize(1.0e-6).unwrap_or_else(Vec3::x));
+        }
+
+        // Ruido de los motores: `throttle` de 0 (parados) a 1 (turbo)
+        pub fn engine(&mut self, throttle: f32) {
+            if let Some(device) = &self.device {
+                device.engine.set_volume(self.config.master_volume * self.config.effects_volume * throttle.clamp(0.0, 1.0).sqrt());
+            }
+        }
+
+        // Un efecto: en `position` del mundo, o sin posición (interfaz) igual en los dos oídos
+        pub fn play(&mut self, sound: Sound, position: Option<Vec3>) {
+            let Some(device) = &self.device else {
+                return;
+            };
+            let samples = match sound {
+                Sound::Laser => &device.laser,
+                Sound::Explosion => &device.explosion,
+                Sound::Click => &device.click,
+            };
+            let source = SamplesBuffer::new(1, synth::SAMPLE_RATE, samples.clone());
+            let volume = self.config.master_volume * self.config.effects_volume;
+            match position {
+                Some(position) => {
+                    let (listener, right) = self.listener;
+                    let emitter = (position - listener) / HEARING_DISTANCE;
+                    let ear = right * EAR_OFFSET;
+                    if let Ok(sink) = SpatialSink::try_new(&device.handle, emitter.into(), (-ear).into(), ear.into()) {
+                        sink.set_volume(volume);
+                        sink.append(source);
+                        sink.detach();
+                    }
+                }
+                None => {
+                    if let Ok(sink) = Sink::try_new(&device.handle) {
+                        sink.set_volume(volume);
+                        sink.append(source);
+                        sink.detach();
+                    }
+                }
+            }
+        }
+    }
+}
+
+// ============= SÍNTESIS =============
+// Cada sonido es una lista de muestras mono entre -1 y 1
+#[cfg(any(feature = "audio", test))]
+mod synth {
+    use std::f32::consts::TAU;
+
+    pub const SAMPLE_RATE: u32 = 22050;
+
+    fn samples(seconds: f32, mut sample: impl FnMut(f32) -> f32) -> Vec<f32> {
+        let count = (seconds * SAMPLE_RATE as f32) as usize;
+        (0..count).map(|i| sample(i as f32 / SAMPLE_RATE as f32).clamp(-1.0, 1.0)).collect()
+    }
+
+    // Ruido blanco repetible (xorshift) para las explosiones y los motores
+    fn noise(seed: u32) -> impl FnMut() -> f32 {
+        let mut state = seed.max(1);
+        move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as f32 / u32::MAX as f32 * 2.0 - 1.0
+        }
+    }
+
+    // Láser: un silbido que cae de agudo a grave en una fracción de segundo
+    pub fn laser() -> Vec<f32> {
+        let mut phase = 0.0;
+        samples(0.18, |t| {
+            let frequency = 1400.0 * (250.0f32 / 1400.0).powf(t / 0.18);
+            phase += TAU * frequency / SAMPLE_RATE as f32;
+            (phase.sin() + 0.3 * (3.0 * phase).sin()) * 0.5 * (-t * 18.0).exp()
+        })
+    }
+
+    // Explosión: ruido que se va apagando y oscureciendo, con un golpe grave al principio
+    pub fn explosion() -> Vec<f32> {
+        let mut white = noise(0xB00A);
+        let mut filtered = 0.0;
+        samples(1.4, |t| {
+            let cutoff = 0.25 * (-t * 2.5).exp() + 0.02; // filtro paso bajo de un polo
+            filtered += (white() - filtered) * cutoff;
+            let attack = (t / 0.005).min(1.0);
+            let thump = (TAU * 50.0 * t).sin() * (-t * 6.0).exp();
+            (filtered * 2.5 + thump * 0.6) * attack * (-t * 3.0).exp()
+        })
+    }
+
+    // Clic de la interfaz
+    pub fn click() -> Vec<f32> {
+        samples(0.03, |t| (TAU * 1800.0 * t).sin() * 0.4 * (-t * 200.0).exp())
+    }
+
+    // Motores: un segundo de ruido grave con un zumbido (60 ciclos justos: el bucle no salta)
+    pub fn engine() -> Vec<f32> {
+        let mut white = noise(0xE761);
+        let mut brown = 0.0;
+        samples(1.0, |t| {
+            brown = (brown + white() * 0.05) * 0.995;
+            brown * 2.0 + (TAU * 60.0 * t).sin() * 0.15
+        })
+    }
+
+    // Música: cuatro acordes largos que se funden uno en otro, en bucle
+    pub fn music() -> Vec<f32> {
+        const CHORDS: [[f32; 3]; 4] = [
+            [220.0, 261.63, 329.63], // la menor
+            [174.61, 220.0, 261.63], // fa mayor
+            [130.81, 196.0, 261.63], // do mayor
+            [196.0, 246.94, 293.66], // sol mayor
+        ];
+        const CHORD_SECONDS: f32 = 8.0;
+        let length = CHORD_SECONDS * CHORDS.len() as f32;
+        samples(length, |t| {
+            CHORDS
+                .iter()
+                .enumerate()
+                .map(|(index, chord)| {
+                    // Envolvente de coseno alzado de dos acordes de ancho: cada acorde sube
+                    // mientras baja el anterior (y el último enlaza con el primero)
+                    let start = index as f32 * CHORD_SECONDS;
+                    let local = (t - start + CHORD_SECONDS * 0.5).rem_euclid(length);
+                    let envelope = if local < CHORD_SECONDS * 2.0 { 0.5 - 0.5 * (TAU * local / (CHORD_SECONDS * 2.0)).cos() } else { 0.0 };
+                    // Cada voz un poco desafinada con su octava baja: suena más ancha
+                    let voices: f32 = chord
+                        .iter()
+                        .map(|&frequency| (TAU * frequency * t).sin() + 0.5 * (TAU * (frequency * 0.5 + 0.3) * t).sin())
+                        .sum();
+                    voices * envelope * 0.06
+                })
+                .sum()
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn sounds_are_audible_and_in_range() {
+            for (sound, seconds) in [(laser(), 0.18), (explosion(), 1.4), (click(), 0.03), (engine(), 1.0), (music(), 32.0)] {
+                assert_eq!(sound.len(), (seconds * SAMPLE_RATE as f32) as usize);
+                assert!(sound.iter().all(|sample| (-1.0..=1.0).contains(sample)));
+                assert!(sound.iter().any(|sample| sample.abs() > 0.05));
+            }
+            // La música enlaza con su principio: el último acorde se funde en el primero
+            let music = music();
+            assert!((music[0] - music[music.len() - 1]).abs() < 0.05);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::f32::consts::TAU;
+use std::fs;
+use std::io;
+use nalgebra_glm::Vec3;
+use crate::lesson::orbit_offset;
+use crate::profiler::{FrameTiming, Profiler, STAGES};
+use crate::{Camera, CelestialObject};
+
+// ============= MODO BENCHMARK (--bench frames [--bench-out informe.json]) =============
+// Recorre siempre el mismo camino de cámara alrededor de la estrella con el paso de tiempo
+// fijo del bucle y sin esperar entre frames: una vuelta completa que se acerca hasta el
+// sistema interior y vuelve a alejarse. Al terminar escribe un informe JSON con los tiempos
+// de frame (media y percentiles), los triángulos y el desglose por etapa del perfilador,
+// para comparar el rendimiento entre commits.
+
+const WARMUP_FRAMES: usize = 10;   // se descartan (cachés, creación de buffers...)
+const FAR_DISTANCE: f32 = 3000.0;
+const NEAR_DISTANCE: f32 = 400.0;
+
+pub struct Benchmark {
+    frames: usize,
+    frame: usize,
+    output: Option<String>,
+    resolution: (usize, usize),
+    samples: Vec<FrameTiming>,
+}
+
+impl Benchmark {
+    pub fn new(frames: usize, output: Option<String>, resolution: (usize, usize)) -> Self {
+        Benchmark {
+            frames,
+            frame: 0,
+            output,
+            resolution,
+            samples: Vec::with_capacity(frames),
+        }
+    }
+
+    // Posición del camino para el frame actual: depende solo del índice del frame
+    pub fn place_camera(&self, camera: &mut Camera, objects: &[CelestialObject]) {
+        let t = self.frame as f32 / (WARMUP_FRAMES + self.frames) as f32;
+        let center = objects.first().map_or(Vec3::zeros(), |star| star.transform.translation);
+        let distance = NEAR_DISTANCE + (FAR_DISTANCE - NEAR_DISTANCE) * (0.5 + 0.5 * (t * TAU).cos());
+        let elevation = 15.0 + 10.0 * (t * 2.0 * TAU).sin();
+        camera.place(center + orbit_offset(distance, elevation, t * 360.0), center, Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    // Guarda el frame que acaba de cerrar el perfilador; true cuando ya no quedan frames
+    pub fn record(&mut self, profiler: &Profiler) -> bool {
+        if self.frame >= WARMUP_FRAMES {
+            if let Some(timing) = profiler.last_frame() {
+                self.samples.push(*timing);
+            }
+        }
+        self.frame += 1;
+        self.frame >= WARMUP_FRAMES + self.frames
+    }
+
+    pub fn report(&self) -> String {
+        let count = self.samples.len().max(1) as f32;
+        let mut frame_times: Vec<f32> = self.samples.iter().map(|sample| sample.total).collect();
+        frame_times.sort_by(f32::total_cmp);
+        let mean = frame_times.iter().sum::<f32>() / count;
+
+        let stages: Vec<String> = STAGES
+            .iter()
+            .enumerate()
+            .map(|(index, stage)| {
+                let average = self.samples.iter().map(|sample| sample.stages[index]).sum::<f32>() / count;
+                format!("    \"{}\": {:.3}", stage.column(), average)
+            })
+            .collect();
+        let triangles = self.samples.iter().map(|sample| sample.triangles).sum::<u64>() as f32 / count;
+        let drawn = self.samples.iter().map(|sample| sample.drawn).sum::<u64>() as f32 / count;
+
+        format!(
+            "{{\n  \"frames\": {},\n  \"warmup_frames\": {},\n  \"resolution\": [{}, {}],\n  \"threads\": {},\n  \
+             \"frame_ms\": {{\n    \"mean\": {:.3},\n    \"min\": {:.3},\n    \"p50\": {:.3},\n    \"p95\": {:.3},\n    \
+             \"p99\": {:.3},\n    \"max\": {:.3}\n  }},\n  \"fps\": {:.1},\n  \
+             \"triangles\": {{\n    \"submitted\": {:.0},\n    \"drawn\": {:.0}\n  }},\n  \
+             \"stages_ms\": {{\n{}\n  }}\n}}\n",
+            self.samples.len(),
+            WARMUP_FRAMES,
+            self.resolution.0,
+            self.resolution.1,
+            rayon::current_num_threads(),
+            mean,
+            frame_times.first().copied().unwrap_or(0.0),
+            percentile(&frame_times, 50.0),
+            percentile(&frame_times, 95.0),
+            percentile(&frame_times, 99.0),
+            frame_times.last().copied().unwrap_or(0.0),
+            1000.0 / mean.max(0.001),
+            triangles,
+            drawn,
+            stages.join(",\n"),
+        )
+    }
+
+    // Imprime el informe y, si se pidió, lo guarda en un archivo
+    pub fn finish(&self) -> io::Result<()> {
+        let report = self.report();
+        print!("{}", report);
+        match &self.output {
+            Some(path) => fs::write(path, report),
+            None => Ok(()),
+        }
+    }
+}
+
+// Percentil por rango más cercano sobre tiempos ya ordenados
+fn percentile(sorted: &[f32], percent: f32) -> f32 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((percent / 100.0) * sorted.len() as f32).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
@@ -0,0 +1,141 @@
+use nalgebra_glm::{Mat4, Vec3};
+use crate::draw_state::{BlendMode, CullMode, DrawState};
+use crate::framebuffer::Framebuffer;
+use crate::project_to_screen;
+
+// ============= BILLBOARDS (SPRITES ORIENTADOS A LA CÁMARA) =============
+// Un punto del mundo con un radio se dibuja como un cuadrado alineado con la pantalla: el
+// centro se proyecta como cualquier vértice y el radio se mide hacia la derecha de la cámara,
+// así que el sprite encoge con la distancia (y con el zoom en ortográfica). Cada píxel del
+// cuadrado pasa por un shader de sprite que da su cobertura en [0, 1] y se escribe con la
+// profundidad del centro, de modo que los cuerpos que están delante lo tapan.
+// Lo usan las colas de los cometas y los puntos de luz de los cuerpos lejanos.
+
+// Luz que se mezcla sobre la escena sin escribir profundidad (halos, colas, puntos de luz)
+pub const GLOW_STATE: DrawState = DrawState {
+    cull: CullMode::None,
+    depth_test: true,
+    depth_write: false,
+    blend: BlendMode::Alpha(1.0),
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpriteShader {
+    Glow,  // mancha suave que cae con el cuadrado de la distancia al centro
+    Point, // núcleo brillante y bordes que se apagan enseguida (estrellas, planetas lejanos)
+}
+
+impl SpriteShader {
+    // Cobertura a `distance` del centro (1 = borde del sprite)
+    fn coverage(self, distance: f32) -> f32 {
+        match self {
+            SpriteShader::Glow => (1.0 - distance) * (1.0 - distance),
+            SpriteShader::Point => (-distance * distance * 6.0).exp(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Billboard {
+    pub position: Vec3,
+    pub radius: f32,     // en unidades del mundo
+    pub color: u32,
+    pub intensity: f32,  // multiplica la cobertura (opacidad o brillo, según la mezcla)
+    pub shader: SpriteShader,
+    pub min_pixels: f32, // radio en pantalla mínimo y máximo
+    pub max_pixels: f32,
+}
+
+impl Billboard {
+    pub fn new(position: Vec3, radius: f32, color: u32) -> Self {
+        Billboard {
+            position,
+            radius,
+            color,
+            intensity: 1.0,
+            shader: SpriteShader::Glow,
+            min_pixels: 1.0,
+            max_pixels: 256.0,
+        }
+    }
+
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn with_shader(mut self, shader: SpriteShader) -> Self {
+        self.shader = shader;
+        self
+    }
+
+    pub fn with_pixel_range(mut self, min_pixels: f32, max_pixels: f32) -> Self {
+        self.min_pixels = min_pixels;
+        self.max_pixels = max_pixels;
+        self
+    }
+}
+
+// Cámara con la que se proyectan los billboards de una pasada
+pub struct SpriteCamera<'a> {
+    pub view_matrix: &'a Mat4,
+    pub projection_matrix: &'a Mat4,
+    pub viewport_matrix: &'a Mat4,
+}
+
+impl SpriteCamera<'_> {
+    fn right(&self) -> Vec3 {
+        Vec3::new(self.view_matrix[(0, 0)], self.view_matrix[(0, 1)], self.view_matrix[(0, 2)])
+    }
+
+    // Centro en pantalla (con profundidad) y radio en píxeles; None si queda detrás de la cámara
+    fn project(&self, billboard: &Billboard) -> Option<(Vec3, f32)> {
+        let center = project_to_screen(billboard.position, self.view_matrix, self.projection_matrix, self.viewport_matrix)?;
+        let edge = project_to_screen(billboard.position + self.right() * billboard.radius, self.view_matrix, self.projection_matrix, self.viewport_matrix)?;
+        let pixels = (edge.x - center.x).hypot(edge.y - center.y).clamp(billboard.min_pixels, billboard.max_pixels);
+        Some((center, pixels))
+    }
+}
+
+pub fn draw_billboards(framebuffer: &mut Framebuffer, camera: &SpriteCamera, state: &DrawState, billboards: &[Billboard]) {
+    for billboard in billboards {
+        if billboard.intensity <= 0.0 {
+            continue;
+        }
+        if let Some((center, pixels)) = camera.project(billboard) {
+            rasterize(framebuffer, billboard, center, pixels, state);
+        }
+    }
+}
+
+fn rasterize(framebuffer: &mut Framebuffer, billboard: &Billboard, center: Vec3, radius: f32, state: &DrawState) {
+    let min_x = (center.x - radius).floor().max(0.0) as usize;
+    let min_y = (center.y - radius).floor().max(0.0) as usize;
+    let max_x = ((center.x + radius).ceil().max(0.0) as usize).min(framebuffer.width);
+    let max_y = ((center.y + radius).ceil().max(0.0) as usize).min(framebuffer.height);
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let distance = (x as f32 + 0.5 - center.x).hypot(y as f32 + 0.5 - center.y) / radius;
+            if distance >= 1.0 {
+                continue;
+            }
+            let coverage = billboard.shader.coverage(distance) * billboard.intensity;
+            // La cobertura entra en la mezcla: opacidad en Alpha, brillo en Additive y
+            // recorte a la mitad en Opaque
+            let (color, blend) = match state.blend {
+                BlendMode::Alpha(alpha) => (billboard.color, BlendMode::Alpha(alpha * coverage)),
+                BlendMode::Additive => (scale_color(billboard.color, coverage), BlendMode::Additive),
+                BlendMode::Opaque if coverage >= 0.5 => (billboard.color, BlendMode::Opaque),
+                BlendMode::Opaque => continue,
+            };
+            framebuffer.draw_fragment(x, y, center.z, color, &DrawState { blend, ..*state });
+        }
+    }
+}
+
+fn scale_color(color: u32, factor: f32) -> u32 {
+    let factor = factor.clamp(0.0, 1.0);
+    let channel = |shift: u32| ((((color >> shift) & 0xFF) as f32 * factor) as u32) << shift;
+    channel(16) | channel(8) | channel(0)
+}
@@ -0,0 +1,133 @@
+use std::f32::consts::TAU;
+use nalgebra_glm::{Vec2, Vec3};
+use rayon::prelude::*;
+use crate::billboard::SpriteCamera;
+use crate::draw_state::{BlendMode, CullMode, DrawState};
+use crate::framebuffer::Framebuffer;
+use crate::vertex::Vertex;
+use crate::CelestialObject;
+
+// ============= AGUJERO NEGRO =============
+// El horizonte es una esfera negra (el radio del cuerpo es el de su sombra) rodeada por un
+// disco de acreción plano y muy caliente. La gravedad curva la luz que pasa cerca: tras
+// dibujar la escena, cada píxel que está detrás del agujero se reemplaza por el que se vería
+// sin él según la ecuación de una lente puntual, β = θ - θE² / θ (θ es la distancia al
+// centro en pantalla y θE el radio del anillo de Einstein). El fondo y la mitad trasera del
+// disco se ven así doblados alrededor de la sombra; lo que está delante no se deforma.
+// La parte del disco que queda justo detrás de la sombra no está en la escena (la tapa la
+// esfera), así que el disco se dibuja además solo en una capa aparte: los rayos que vienen
+// de detrás de la sombra la leen de ahí, y el disco trasero aparece arqueado por encima y
+// por debajo del agujero, como en las simulaciones.
+// En la escena: shader = "BlackHole"
+
+pub const DISC_INNER: f32 = 1.4;    // radios del disco, en radios de la sombra
+pub const DISC_OUTER: f32 = 4.0;
+const DISC_RINGS: usize = 16;
+const DISC_SECTORS: usize = 128;
+
+const EINSTEIN_RADIUS: f32 = 1.6;   // radio del anillo de Einstein, en radios de la sombra
+const LENS_EXTENT: f32 = 5.0;       // la deformación se apaga a LENS_EXTENT * θE
+const PHOTON_RING_WIDTH: f32 = 0.08; // anchura del anillo de fotones, en radios de la sombra
+const PHOTON_RING_COLOR: (f32, f32, f32) = (255.0, 214.0, 160.0);
+
+// El disco escribe profundidad: así la lente distingue su mitad delantera (que no se deforma)
+// de la trasera, que se ve doblada por encima y por debajo de la sombra
+pub const DISC_STATE: DrawState = DrawState {
+    cull: CullMode::None,
+    depth_test: true,
+    depth_write: true,
+    blend: BlendMode::Additive,
+};
+
+// Corona circular en el plano XZ del modelo (la matriz de modelo la escala con el cuerpo).
+// La coordenada de textura guarda el radio y el ángulo del centro de cada celda para el shader
+pub fn accretion_disc_mesh() -> Vec<Vertex> {
+    let point = |ring: usize, sector: usize| {
+        let r = DISC_INNER + (DISC_OUTER - DISC_INNER) * ring as f32 / DISC_RINGS as f32;
+        let theta = sector as f32 / DISC_SECTORS as f32 * TAU;
+        Vec3::new(r * theta.cos(), 0.0, r * theta.sin())
+    };
+
+    let normal = Vec3::new(0.0, 1.0, 0.0);
+    let mut vertices = Vec::with_capacity(DISC_RINGS * DISC_SECTORS * 6);
+    for ring in 0..DISC_RINGS {
+        for sector in 0..DISC_SECTORS {
+            let p00 = point(ring, sector);
+            let p01 = point(ring + 1, sector);
+            let p10 = point(ring, sector + 1);
+            let p11 = point(ring + 1, sector + 1);
+            let r = DISC_INNER + (DISC_OUTER - DISC_INNER) * (ring as f32 + 0.5) / DISC_RINGS as f32;
+            let theta = (sector as f32 + 0.5) / DISC_SECTORS as f32 * TAU;
+            for [a, b, c] in [[p00, p10, p01], [p01, p10, p11]] {
+                for position in [a, b, c] {
+                    vertices.push(Vertex::new(position, normal, Vec2::new((r - DISC_INNER) / (DISC_OUTER - DISC_INNER), theta)));
+                }
+            }
+        }
+    }
+    vertices
+}
+
+// Deforma la escena alrededor del agujero negro (después de dibujar cuerpos y partículas).
+// `disc_layer` tiene el mismo tamaño que la escena y solo el disco, sin la sombra
+pub fn apply_lensing(framebuffer: &mut Framebuffer, disc_layer: &Framebuffer, black_hole: &CelestialObject, camera: &SpriteCamera) {
+    let Some((center, radius)) = crate::picking::screen_disk(black_hole, camera.view_matrix, camera.projection_matrix, camera.viewport_matrix) else {
+        return;
+    };
+    if radius < 1.0 {
+        return;
+    }
+    let einstein = radius * EINSTEIN_RADIUS;
+    let extent = einstein * LENS_EXTENT;
+    let min_x = (center.x - extent).floor().max(0.0) as usize;
+    let max_x = ((center.x + extent).ceil().max(0.0) as usize).min(framebuffer.width);
+    let min_y = (center.y - extent).floor().max(0.0) as usize;
+    let max_y = ((center.y + extent).ceil().max(0.0) as usize).min(framebuffer.height);
+    if min_x >= max_x || min_y >= max_y {
+        return;
+    }
+
+    // Se lee de una copia: cada píxel toma el color de otro punto de la imagen sin deformar
+    let width = framebuffer.width;
+    let height = framebuffer.height;
+    let source = framebuffer.buffer.clone();
+    let rows = framebuffer.buffer[min_y * width..max_y * width]
+        .par_chunks_mut(width)
+        .zip(framebuffer.zbuffer[min_y * width..max_y * width].par_chunks(width));
+    rows.enumerate().for_each(|(row, (pixels, depths))| {
+        let dy = (min_y + row) as f32 + 0.5 - center.y;
+        for x in min_x..max_x {
+            // Reversed-Z: lo que está delante del agujero (y su sombra) no se deforma
+            if depths[x] > center.z {
+                continue;
+            }
+            let dx = x as f32 + 0.5 - center.x;
+            let theta = dx.hypot(dy);
+            if theta >= extent || theta < 1.0 {
+                continue;
+            }
+            let fade = (1.0 - (theta / extent).powi(2)).powi(2);
+            let beta = theta - einstein * einstein / theta * fade;
+            let scale = beta / theta;
+            let source_x = center.x + dx * scale;
+            let source_y = center.y + dy * scale;
+            let inside = source_x >= 0.0 && source_y >= 0.0 && (source_x as usize) < width && (source_y as usize) < height;
+            let mut color = match inside {
+                false => 0,
+                // El rayo viene de detrás de la sombra: solo puede traer luz del disco
+                true if beta.abs() < radius => disc_layer.buffer[source_y as usize * width + source_x as usize],
+                true => source[source_y as usize * width + source_x as usize],
+            };
+
+            // Anillo de fotones: luz que ha dado la vuelta al agujero, justo fuera de la sombra
+            let ring = (theta / radius - 1.0 - PHOTON_RING_WIDTH) / PHOTON_RING_WIDTH;
+            let glow = (-ring * ring).exp();
+            if glow > 0.01 {
+                let channel = |value: f32, shift: u32| ((value * glow) as u32) << shift;
+                let light = channel(PHOTON_RING_COLOR.0, 16) | channel(PHOTON_RING_COLOR.1, 8) | channel(PHOTON_RING_COLOR.2, 0);
+                color = BlendMode::Additive.apply(light, color);
+            }
+            pixels[x] = color;
+        }
+    });
+}
@@ -0,0 +1,233 @@
+use minifb::{Key, KeyRepeat};
+use nalgebra_glm::Vec3;
+use crate::framebuffer::Framebuffer;
+use crate::input::Input;
+use crate::lesson::orbit_offset;
+use crate::scene::{Scene, TourKeyframe};
+use crate::text::{draw_text, text_width, GLYPH_SIZE};
+use crate::theme::{self, HudLayout, Widget};
+use crate::{Camera, CelestialObject};
+
+// ============= MARCADORES DE CÁMARA Y VISITA GUIADA =============
+// Ctrl+1..9 guardan la posición y el objetivo de la cámara; 1..9 vuelan de vuelta con una
+// interpolación suavizada (arranca y frena despacio). V reproduce en bucle los fotogramas
+// [[tour]] de la escena para un recorrido cinematográfico automático; V otra vez lo detiene.
+
+const SLOT_KEYS: [Key; 9] = [Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9];
+const RECALL_SECONDS: f32 = 1.5;
+const NOTICE_SECONDS: f32 = 1.5;
+
+#[derive(Clone, Copy)]
+struct CameraPose {
+    position: Vec3,
+    target: Vec3,
+}
+
+impl CameraPose {
+    fn of(camera: &Camera) -> Self {
+        CameraPose {
+            position: camera.position,
+            target: camera.target,
+        }
+    }
+}
+
+// Hacia dónde vuela la cámara: un marcador fijo o un fotograma de la visita (que puede
+// seguir a un cuerpo en movimiento, así que se vuelve a calcular en cada frame)
+#[derive(Clone, Copy)]
+enum Destination {
+    Pose(CameraPose),
+    Keyframe(usize),
+}
+
+struct Flight {
+    from: CameraPose,
+    to: Destination,
+    elapsed: f32,
+    duration: f32,
+}
+
+// Fotograma con el cuerpo enfocado ya resuelto a su índice en la escena
+struct Keyframe {
+    focus: Option<usize>,
+    position: Vec3,
+    target: Vec3,
+    offset: (f32, f32, f32), // distancia (radios), elevación y azimut
+    duration: f32,
+    hold: f32,
+}
+
+impl Keyframe {
+    fn from_config(config: &TourKeyframe, scene: &Scene) -> Self {
+        Keyframe {
+            focus: config.focus.as_deref().and_then(|id| scene.body_index(id)),
+            position: config.position.map(Vec3::from).unwrap_or_default(),
+            target: Vec3::from(config.target),
+            offset: (config.distance, config.elevation, config.azimuth),
+            duration: config.duration,
+            hold: config.hold,
+        }
+    }
+
+    fn pose(&self, objects: &[CelestialObject]) -> CameraPose {
+        match self.focus.and_then(|index| objects.get(index)) {
+            Some(body) => {
+                let (distance, elevation, azimuth) = self.offset;
+                CameraPose {
+                    position: body.transform.translation + orbit_offset(distance * body.transform.scale, elevation, azimuth),
+                    target: body.transform.translation,
+                }
+            }
+            None => CameraPose {
+                position: self.position,
+                target: self.target,
+            },
+        }
+    }
+}
+
+// Suavizado "smootherstep": velocidad y aceleración nulas al salir y al llegar
+fn ease(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+pub struct CameraBookmarks {
+    slots: [Option<CameraPose>; 9],
+    keyframes: Vec<Keyframe>,
+    flight: Option<Flight>,
+    tour: Option<usize>, // fotograma actual de la visita en curso
+    hold_left: f32,
+    notice: Option<(String, f32)>,
+}
+
+impl CameraBookmarks {
+    pub fn new(scene: &Scene) -> Self {
+        CameraBookmarks {
+            slots: [None; 9],
+            keyframes: scene.tour.iter().map(|config| Keyframe::from_config(config, scene)).collect(),
+            flight: None,
+            tour: None,
+            hold_left: 0.0,
+            notice: None,
+        }
+    }
+
+    pub fn is_touring(&self) -> bool {
+        self.tour.is_some()
+    }
+
+    // Otro modo de cámara (vuelo libre, aterrizaje, salto del buscador) toma el control
+    pub fn stop(&mut self) {
+        self.tour = None;
+        self.flight = None;
+    }
+
+    // Ctrl+1..9 guardan, 1..9 recuperan y V inicia o detiene la visita
+    pub fn handle_input(&mut self, input: &Input, camera: &Camera) {
+        let ctrl = input.is_key_down(Key::LeftCtrl) || input.is_key_down(Key::RightCtrl);
+        if let Some(slot) = SLOT_KEYS.iter().position(|&key| input.is_key_pressed(key, KeyRepeat::No)) {
+            if ctrl {
+                self.slots[slot] = Some(CameraPose::of(camera));
+                self.notify(format!("Marcador {} guardado", slot + 1));
+            } else if let Some(pose) = self.slots[slot] {
+                self.tour = None;
+                self.fly(camera, Destination::Pose(pose), RECALL_SECONDS);
+                self.notify(format!("Marcador {}", slot + 1));
+            } else {
+                self.notify(format!("Marcador {} vacío (Ctrl+{} para guardarlo)", slot + 1, slot + 1));
+            }
+        }
+
+        if input.is_key_pressed(Key::V, KeyRepeat::No) {
+            if self.tour.take().is_some() {
+                self.flight = None;
+            } else if self.keyframes.is_empty() {
+                self.notify("La escena no define una visita guiada ([[tour]])".to_string());
+            } else {
+                self.tour = Some(0);
+                self.fly(camera, Destination::Keyframe(0), self.keyframes[0].duration);
+            }
+        }
+    }
+
+    fn fly(&mut self, camera: &Camera, to: Destination, duration: f32) {
+        self.flight = Some(Flight {
+            from: CameraPose::of(camera),
+            to,
+            elapsed: 0.0,
+            duration,
+        });
+    }
+
+    fn notify(&mut self, text: String) {
+        self.notice = Some((text, NOTICE_SECONDS));
+    }
+
+    fn resolve(&self, destination: Destination, objects: &[CelestialObject]) -> CameraPose {
+        match destination {
+            Destination::Pose(pose) => pose,
+            Destination::Keyframe(index) => self.keyframes[index].pose(objects),
+        }
+    }
+
+    // Avanza el vuelo en curso y la visita; mueve la cámara solo mientras hay algo activo
+    pub fn update(&mut self, camera: &mut Camera, objects: &[CelestialObject], dt: f32) {
+        if let Some((_, seconds)) = self.notice.as_mut() {
+            *seconds -= dt;
+            if *seconds <= 0.0 {
+                self.notice = None;
+            }
+        }
+
+        if let Some(flight) = self.flight.as_mut() {
+            flight.elapsed += dt;
+            let (from, to, t) = (flight.from, flight.to, ease(flight.elapsed / flight.duration));
+            let arrived = flight.elapsed >= flight.duration;
+            let to = self.resolve(to, objects);
+            camera.place(
+                from.position + (to.position - from.position) * t,
+                from.target + (to.target - from.target) * t,
+                Vec3::new(0.0, 1.0, 0.0),
+            );
+            if arrived {
+                self.flight = None;
+                if let Some(index) = self.tour {
+                    self.hold_left = self.keyframes[index].hold;
+                }
+            }
+            return;
+        }
+
+        // Parada en un fotograma: se sigue al cuerpo y después se vuela al siguiente
+        if let Some(index) = self.tour {
+            let pose = self.keyframes[index].pose(objects);
+            camera.place(pose.position, pose.target, Vec3::new(0.0, 1.0, 0.0));
+            self.hold_left -= dt;
+            if self.hold_left <= 0.0 {
+                let next = (index + 1) % self.keyframes.len();
+                self.tour = Some(next);
+                self.fly(camera, Destination::Keyframe(next), self.keyframes[next].duration);
+            }
+        }
+    }
+
+    // Aviso breve (marcadores, abajo a la izquierda por defecto) o el estado de la visita
+    pub fn draw(&self, screen: &mut Framebuffer, layout: &mut HudLayout) {
+        const PADDING: usize = 10;
+
+        let theme = theme::current();
+        let (text, color) = match (self.tour, &self.notice) {
+            (Some(index), _) => (format!("Visita guiada {}/{}   V: detener", index + 1, self.keyframes.len()), theme.title),
+            (None, Some((text, _))) => (text.clone(), theme.text),
+            (None, None) => return,
+        };
+        let width = text_width(&text, 1) + PADDING * 2;
+        let height = GLYPH_SIZE + PADDING * 2;
+        let Some((x, y)) = layout.place(Widget::Notices, width, height) else {
+            return;
+        };
+        screen.blend_rect(x, y, width, height, theme.panel_background, theme.hud_opacity);
+        draw_text(screen, (x + PADDING) as i32, (y + PADDING) as i32, &text, color, 1);
+    }
+}
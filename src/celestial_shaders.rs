@@ -1,16 +1,17 @@
 use nalgebra_glm::Vec3;
+use serde::Deserialize;
 use crate::color::Color;
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
 use crate::Uniforms;
-
-// ============= FUNCIONES DE NOISE MEJORADAS =============
-
-// Función auxiliar para ruido pseudo-aleatorio
-fn noise(x: f32, y: f32, z: f32) -> f32 {
-    let a = (x * 12.9898 + y * 78.233 + z * 45.164).sin() * 43758.5453;
-    a.fract()
-}
+use crate::noise::{fbm, hash3, turbulence, worley_noise};
+use crate::procedural::ProceduralPlanet;
+use crate::random_system::StarClass;
+use crate::black_hole::{DISC_INNER, DISC_OUTER};
+use crate::weather::{dust_coverage, global_dust_level};
+use crate::material::{body_material, Material};
+use crate::shader_params::EarthParams;
+use crate::math::{Backend, MathBackend};
 
 // Interpolación suave (smoothstep) para transiciones más naturales
 fn smoothstep(t: f32) -> f32 {
@@ -18,127 +19,6 @@ fn smoothstep(t: f32) -> f32 {
     t * t * (3.0 - 2.0 * t)
 }
 
-// Ruido interpolado linealmente para reducir pixelación
-fn noise_interpolated(x: f32, y: f32, z: f32) -> f32 {
-    let xi = x.floor();
-    let yi = y.floor();
-    let zi = z.floor();
-    
-    let xf = x - xi;
-    let yf = y - yi;
-    let zf = z - zi;
-    
-    // Interpolar con smoothstep para transiciones más suaves
-    let u = smoothstep(xf);
-    let v = smoothstep(yf);
-    let w = smoothstep(zf);
-    
-    // 8 esquinas del cubo
-    let n000 = noise(xi, yi, zi);
-    let n100 = noise(xi + 1.0, yi, zi);
-    let n010 = noise(xi, yi + 1.0, zi);
-    let n110 = noise(xi + 1.0, yi + 1.0, zi);
-    let n001 = noise(xi, yi, zi + 1.0);
-    let n101 = noise(xi + 1.0, yi, zi + 1.0);
-    let n011 = noise(xi, yi + 1.0, zi + 1.0);
-    let n111 = noise(xi + 1.0, yi + 1.0, zi + 1.0);
-    
-    // Interpolación trilinear
-    let x00 = n000 * (1.0 - u) + n100 * u;
-    let x10 = n010 * (1.0 - u) + n110 * u;
-    let x01 = n001 * (1.0 - u) + n101 * u;
-    let x11 = n011 * (1.0 - u) + n111 * u;
-    
-    let y0 = x00 * (1.0 - v) + x10 * v;
-    let y1 = x01 * (1.0 - v) + x11 * v;
-    
-    y0 * (1.0 - w) + y1 * w
-}
-
-// Función para ruido fractal (Fractal Brownian Motion) con interpolación
-fn fbm(x: f32, y: f32, z: f32, octaves: u32) -> f32 {
-    let mut value = 0.0;
-    let mut amplitude = 0.5;
-    let mut frequency = 1.0;
-    let mut max_value = 0.0;
-    
-    for _ in 0..octaves {
-        value += noise_interpolated(x * frequency, y * frequency, z * frequency) * amplitude;
-        max_value += amplitude;
-        frequency *= 2.0;
-        amplitude *= 0.5;
-    }
-    
-    // Normalizar para mantener el rango [0, 1]
-    if max_value > 0.0 {
-        value / max_value
-    } else {
-        value
-    }
-}
-
-// Worley/Cellular noise mejorado para efectos de células más suaves
-fn worley_noise(x: f32, y: f32, z: f32) -> f32 {
-    let xi = x.floor() as i32;
-    let yi = y.floor() as i32;
-    let zi = z.floor() as i32;
-    
-    let mut min_dist: f32 = 100.0;
-    let mut second_min_dist: f32 = 100.0;
-    
-    for i in -1..=1 {
-        for j in -1..=1 {
-            for k in -1..=1 {
-                let cell_x = (xi + i) as f32;
-                let cell_y = (yi + j) as f32;
-                let cell_z = (zi + k) as f32;
-                
-                let point_x = cell_x + noise(cell_x, cell_y, cell_z);
-                let point_y = cell_y + noise(cell_x + 1.0, cell_y, cell_z);
-                let point_z = cell_z + noise(cell_x, cell_y + 1.0, cell_z);
-                
-                let dx = point_x - x;
-                let dy = point_y - y;
-                let dz = point_z - z;
-                let dist = (dx*dx + dy*dy + dz*dz).sqrt();
-                
-                if dist < min_dist {
-                    second_min_dist = min_dist;
-                    min_dist = dist;
-                } else if dist < second_min_dist {
-                    second_min_dist = dist;
-                }
-            }
-        }
-    }
-    
-    // Usar la diferencia entre las dos distancias más cercanas para bordes más suaves
-    (second_min_dist - min_dist).clamp(0.0, 1.0)
-}
-
-// Turbulencia para efectos caóticos con interpolación suave
-fn turbulence(x: f32, y: f32, z: f32, octaves: u32) -> f32 {
-    let mut value = 0.0;
-    let mut amplitude = 1.0;
-    let mut frequency = 1.0;
-    let mut max_value = 0.0;
-    
-    for _ in 0..octaves {
-        let n = noise_interpolated(x * frequency, y * frequency, z * frequency);
-        value += (n - 0.5).abs() * amplitude;
-        max_value += amplitude * 0.5;
-        frequency *= 2.0;
-        amplitude *= 0.5;
-    }
-    
-    // Normalizar
-    if max_value > 0.0 {
-        value / max_value
-    } else {
-        value
-    }
-}
-
 // Helper para mezclar colores con interpolación suave
 fn mix_color(c1: Color, c2: Color, t: f32) -> Color {
     let t = smoothstep(t.clamp(0.0, 1.0)); // Usar smoothstep para transiciones más naturales
@@ -185,42 +65,98 @@ fn turbulence_adaptive(x: f32, y: f32, z: f32, base_octaves: u32, detail_level:
     turbulence(x, y, z, scale_octaves(base_octaves, detail_level))
 }
 
-// Función auxiliar para iluminación Phong
-fn calculate_phong_lighting(
-    fragment_pos: Vec3,
-    normal: Vec3,
-    light_pos: Vec3,
-    camera_pos: Vec3,
-    base_color: Color,
-    ambient_strength: f32,
-    diffuse_strength: f32,
-    specular_strength: f32,
-    shininess: f32
-) -> Color {
-    // Ambiente
-    let ambient = base_color * ambient_strength;
-    
-    // Difusa
-    let light_dir = (light_pos - fragment_pos).normalize();
-    let diff = normal.dot(&light_dir).max(0.0);
-    let diffuse = base_color * (diff * diffuse_strength);
-    
-    // Especular (Phong)
-    let view_dir = (camera_pos - fragment_pos).normalize();
-    let reflect_dir = reflect(-light_dir, normal);
-    let spec = reflect_dir.dot(&view_dir).max(0.0).powf(shininess);
-    let specular = Color::from_float(1.0, 1.0, 1.0) * (spec * specular_strength);
-    
-    ambient + diffuse + specular
+// Iluminación de la superficie con el material del cuerpo (albedo y BRDF, ver material.rs).
+// Todo en el espacio del mundo: la posición y la normal del vértice, la luz y la cámara
+fn light_surface(material: &Material, fragment_pos: Vec3, normal: Vec3, uniforms: &Uniforms, base_color: Color) -> Color {
+    let light_dir = (uniforms.light_position - fragment_pos).normalize();
+    let view_dir = (uniforms.camera_position - fragment_pos).normalize();
+    material.shade(base_color, normal, None, light_dir, view_dir)
 }
 
 fn reflect(incident: Vec3, normal: Vec3) -> Vec3 {
     incident - normal * 2.0 * incident.dot(&normal)
 }
 
+// Intensidad máxima de un relámpago: brillante pero sin quemar el canal a blanco puro,
+// para que el destello conserve el tono azulado en la salida final
+const LIGHTNING_MAX_INTENSITY: f32 = 0.85;
+
+// Relámpagos en el lado nocturno (agrupados por celdas de tormenta y sembrados por el tiempo)
+// Retorna la intensidad del destello en [0, LIGHTNING_MAX_INTENSITY]
+fn lightning_flash(pos: Vec3, normal: Vec3, light_dir: Vec3, cloud_density: f32, time: f32) -> f32 {
+    // Solo en el lado nocturno (transición suave en el terminador)
+    let night = smoothstep((-normal.dot(&light_dir) + 0.05) * 4.0);
+    if night <= 0.0 || cloud_density <= 0.0 {
+        return 0.0;
+    }
+
+    // Regiones de tormenta a baja frecuencia: los destellos aparecen agrupados
+    let storm_region = fbm(pos.x * 2.0 + 17.0, pos.y * 2.0, pos.z * 2.0 - 5.0, 2);
+    if storm_region < 0.5 {
+        return 0.0;
+    }
+
+    // Celdas de tormenta y ventanas de tiempo de medio segundo
+    let cell_x = (pos.x * 6.0).floor() as i32;
+    let cell_y = (pos.y * 6.0).floor() as i32;
+    let cell_z = (pos.z * 6.0).floor() as i32;
+    let epoch = (time * 2.0).floor() as i32;
+    let phase = (time * 2.0).fract();
+
+    // ¿Esta celda dispara en esta ventana?
+    let trigger = hash3(cell_x + epoch * 31, cell_y - epoch * 17, cell_z + epoch * 7);
+    if trigger < 0.9 {
+        return 0.0;
+    }
+
+    // Destello breve con parpadeo (primer tercio de la ventana)
+    let decay = (1.0 - phase * 3.0).max(0.0);
+    let flicker = 0.6 + 0.4 * (phase * 60.0 + trigger * 10.0).sin().abs();
+
+    // Punto de impacto aleatorio dentro de la celda con caída radial
+    let center = Vec3::new(
+        (cell_x as f32 + hash3(cell_x, epoch, cell_z)) / 6.0,
+        (cell_y as f32 + hash3(epoch, cell_y, cell_x)) / 6.0,
+        (cell_z as f32 + hash3(cell_z, cell_y, epoch)) / 6.0,
+    );
+    let falloff = (1.0 - (pos - center).magnitude() * 9.0).max(0.0);
+
+    (decay * flicker * falloff * night * cloud_density.min(1.0)).min(LIGHTNING_MAX_INTENSITY)
+}
+
 // ============= SOL (ESTRELLA) =============
-// Shader con 5+ capas: núcleo, plasma, manchas solares, llamaradas, corona
-pub fn sun_shader(_fragment: &Fragment, vertex: &Vertex, time: f32) -> Color {
+// Color de cuerpo negro a una temperatura (aproximación de Tanner Helland, 1000-40000 K)
+fn blackbody(kelvin: f32) -> Color {
+    let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+    let r = if t <= 66.0 { 255.0 } else { 329.699 * (t - 60.0).powf(-0.1332) };
+    let g = if t <= 66.0 { 99.471 * t.ln() - 161.120 } else { 288.122 * (t - 60.0).powf(-0.0755) };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.518 * (t - 10.0).ln() - 305.045
+    };
+    Color::from_float((r / 255.0).min(1.0), (g / 255.0).min(1.0), (b / 255.0).min(1.0))
+}
+
+// El cuerpo negro de una estrella como el Sol es casi blanco: se satura para que las capas
+// se distingan (naranja en el Sol, rojo en una enana roja, azul en una gigante O)
+const STAR_SATURATION: f32 = 2.5;
+
+pub fn star_tint(kelvin: f32) -> Color {
+    let (r, g, b) = blackbody(kelvin).to_float();
+    let luma = 0.3 * r + 0.59 * g + 0.11 * b;
+    let saturate = |channel: f32| (luma + (channel - luma) * STAR_SATURATION).min(1.0);
+    Color::from_float(saturate(r), saturate(g), saturate(b))
+}
+
+// Shader con 4 capas: núcleo, plasma, manchas solares, llamaradas. La corona, que sale del
+// disco, se dibuja aparte (ver corona.rs). Los colores salen de la temperatura efectiva:
+// el plasma, las llamaradas y las manchas son regiones cada vez más frías que la superficie.
+// `activity` (1 = el Sol) acelera el plasma y multiplica manchas y llamaradas
+pub fn sun_shader(_fragment: &Fragment, vertex: &Vertex, time: f32, temperature: f32, activity: f32) -> Color {
+    let time = time * (0.5 + 0.5 * activity);
     let pos = vertex.position;
     let normal = vertex.transformed_normal.normalize();
     
@@ -229,7 +165,7 @@ pub fn sun_shader(_fragment: &Fragment, vertex: &Vertex, time: f32) -> Color {
     // Capa 1: Núcleo ultra brillante con pulsación
     let pulse = (time * 2.0).sin() * 0.15 + 1.0;
     let core_intensity = (1.0 - (dist_from_center * 1.8)).max(0.0).powf(4.0) * pulse;
-    let core_color = Color::from_float(1.0, 1.0, 0.95);
+    let core_color = star_tint(temperature * 1.1);
     
     // Capa 2: Plasma interno con movimiento caótico
     let plasma_noise = turbulence(
@@ -238,12 +174,12 @@ pub fn sun_shader(_fragment: &Fragment, vertex: &Vertex, time: f32) -> Color {
         pos.z * 4.0 + time * 0.35,
         5
     );
-    let plasma_color = Color::from_float(1.0, 0.7, 0.0);
+    let plasma_color = star_tint(temperature * 0.75);
     
     // Capa 3: Manchas solares (áreas más oscuras)
     let sunspot_noise = worley_noise(pos.x * 3.0, pos.y * 3.0, pos.z * 3.0);
-    let sunspot_intensity = (sunspot_noise - 0.3).max(0.0).min(0.5);
-    let sunspot_color = Color::from_float(0.6, 0.2, 0.0);
+    let sunspot_intensity = (sunspot_noise - 0.3).max(0.0).min(0.5) * activity.min(1.5);
+    let sunspot_color = star_tint(temperature * 0.5) * 0.6;
     
     // Capa 4: Llamaradas solares en los bordes
     let flare_noise = fbm(
@@ -253,18 +189,8 @@ pub fn sun_shader(_fragment: &Fragment, vertex: &Vertex, time: f32) -> Color {
         4
     );
     let edge_dist = (dist_from_center - 0.75).max(0.0);
-    let flare_intensity = edge_dist * flare_noise * 8.0;
-    let flare_color = Color::from_float(1.0, 0.4, 0.0);
-    
-    // Capa 5: Corona brillante con partículas
-    let corona_noise = fbm(
-        pos.x * 2.5 + time * 0.15,
-        pos.y * 2.5,
-        pos.z * 2.5 - time * 0.1,
-        3
-    );
-    let corona_intensity = (dist_from_center - 0.85).max(0.0) * 6.0;
-    let corona_color = Color::from_float(1.0, 0.9, 0.5);
+    let flare_intensity = edge_dist * flare_noise * 8.0 * activity;
+    let flare_color = star_tint(temperature * 0.6);
     
     // Limb darkening mejorado
     let view_angle = normal.z.abs();
@@ -275,9 +201,11 @@ pub fn sun_shader(_fragment: &Fragment, vertex: &Vertex, time: f32) -> Color {
     final_color = mix_color(final_color, plasma_color, plasma_noise * 0.8);
     final_color = mix_color(final_color, sunspot_color, sunspot_intensity);
     final_color = mix_color(final_color, flare_color, flare_intensity.min(1.0));
-    final_color = mix_color(final_color, corona_color, corona_noise * corona_intensity);
     
-    final_color * limb_darkening * 2.5
+    // Se aclara sin saturar ningún canal por separado: así una enana roja no vira a amarillo
+    let (r, g, b) = final_color.to_float();
+    let gain = (limb_darkening * 2.5).min(1.0 / r.max(g).max(b).max(1e-3));
+    final_color * gain
 }
 
 // ============= PLANETA ROCOSO (TIPO TIERRA) =============
@@ -285,15 +213,16 @@ pub fn sun_shader(_fragment: &Fragment, vertex: &Vertex, time: f32) -> Color {
 pub fn earth_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
     let pos = vertex.position;
     let normal = vertex.transformed_normal.normalize();
-    let fragment_pos = vertex.transformed_position;
+    let fragment_pos = vertex.world_position;
+    let palette = uniforms.params.earth();
     
     // OCÉANOS REALISTAS - Colores tipo Tierra real
     let ocean_depth = fbm(pos.x * 3.5, pos.y * 3.5, pos.z * 3.5, 4);
     let ocean_waves = fbm(pos.x * 18.0, pos.y * 18.0, pos.z * 18.0, 2) * 0.1;
     
     // Océanos profundos azul oscuro, océanos poco profundos más turquesa
-    let deep_ocean = Color::from_float(0.01, 0.05, 0.15);      // Azul muy oscuro
-    let shallow_ocean = Color::from_float(0.05, 0.25, 0.45);   // Azul medio
+    let deep_ocean = palette.ocean_deep;
+    let shallow_ocean = palette.ocean_shallow;
     
     // CONTINENTES REALISTAS - Usar múltiples capas de noise para formas irregulares
     // Combinar Worley + FBM para crear continentes más naturales
@@ -302,7 +231,7 @@ pub fn earth_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifo
     let continent_variation = fbm(pos.x * 1.8, pos.y * 1.8, pos.z * 1.8, 4);
     
     // Ajustar umbral para tener ~30% de tierra (como la Tierra real)
-    let land_threshold = 0.48 + continent_variation * 0.08;
+    let land_threshold = uniforms.tweaks.land_threshold + continent_variation * 0.08;
     let is_land = (continent_base > land_threshold) || (continent_detail > 0.62 && continent_base > 0.42);
     
     // BIOMAS TERRESTRES REALISTAS - Colores tipo Tierra
@@ -310,14 +239,8 @@ pub fn earth_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifo
     let altitude = fbm(pos.x * 4.5, pos.y * 4.5, pos.z * 4.5, 3);
     let coastal_distance = fbm(pos.x * 6.0, pos.y * 6.0, pos.z * 6.0, 3);
     
-    // Colores más realistas de la Tierra
-    let forest = Color::from_float(0.13, 0.38, 0.13);        // Verde bosque oscuro
-    let plains = Color::from_float(0.42, 0.48, 0.22);        // Verde/amarillo praderas
-    let desert = Color::from_float(0.76, 0.60, 0.35);        // Arena/desierto cálido
-    let mountain = Color::from_float(0.45, 0.40, 0.35);      // Marrón/gris montaña
-    let snow = Color::from_float(0.95, 0.95, 0.98);          // Nieve brillante
-    let tundra = Color::from_float(0.55, 0.50, 0.45);        // Tundra ártica
-    let beach_sand = Color::from_float(0.88, 0.82, 0.65);    // Arena de playa
+    // Colores de los biomas (bosque, pradera, desierto, montaña, nieve, tundra, playa)
+    let EarthParams { forest, plains, desert, mountain, snow, tundra, beach: beach_sand, .. } = palette;
     
     let mut base_color = if is_land {
         // BIOMAS REALISTAS con transiciones suaves
@@ -368,35 +291,26 @@ pub fn earth_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifo
     let ice_noise = fbm(pos.x * 8.0, pos.y * 8.0, pos.z * 8.0, 2);
     base_color = mix_color(base_color, snow, (pole_intensity * ice_noise).min(1.0));
     
-    // Aplicar iluminación Phong
+    // Iluminación con el material del cuerpo
     let specular = if !is_land { 0.8 } else { 0.05 };
     let shininess = if !is_land { 64.0 } else { 4.0 };
     
-    base_color = calculate_phong_lighting(
-        fragment_pos,
-        normal,
-        uniforms.light_position,
-        uniforms.camera_position,
-        base_color,
-        0.25,
-        0.8,
-        specular,
-        shininess
-    );
+    base_color = light_surface(&Material { specular, shininess, ..body_material(CelestialBody::Earth) }, fragment_pos, normal, uniforms, base_color);
     
     // NUBES REALISTAS - Sistema de 3 capas que se mueven
+    let cloud_time = uniforms.time * uniforms.tweaks.cloud_speed;
     // Nubes grandes (sistemas climáticos)
     let cloud_large = fbm(
-        pos.x * 3.5 + uniforms.time * 0.05,
+        pos.x * 3.5 + cloud_time * 0.05,
         pos.y * 3.5,
-        pos.z * 3.5 - uniforms.time * 0.03,
+        pos.z * 3.5 - cloud_time * 0.03,
         4
     );
     // Nubes medianas (formaciones)
     let cloud_medium = fbm(
-        pos.x * 7.0 - uniforms.time * 0.07,
+        pos.x * 7.0 - cloud_time * 0.07,
         pos.y * 7.0,
-        pos.z * 7.0 + uniforms.time * 0.04,
+        pos.z * 7.0 + cloud_time * 0.04,
         3
     );
     // Detalles finos (cirrus, etc)
@@ -410,22 +324,26 @@ pub fn earth_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifo
     // Combinar capas (más nubes en zonas ecuatoriales)
     let latitude_cloud_factor = 1.0 - (pos.y.abs() * 0.5); // Más nubes cerca del ecuador
     let cloud_combined = (cloud_large * 0.5 + cloud_medium * 0.3 + cloud_fine) * latitude_cloud_factor;
-    let cloud_intensity = (cloud_combined - 0.45).max(0.0) * 2.0;
+    let cloud_intensity = (cloud_combined - uniforms.tweaks.cloud_threshold).max(0.0) * 2.0;
     
     // Iluminación de nubes (sombras realistas)
     let light_dir = (uniforms.light_position - fragment_pos).normalize();
     let cloud_lighting = (normal.dot(&light_dir).max(0.0) * 0.75 + 0.25).min(1.0);
-    let cloud_color = Color::from_float(0.98, 0.98, 1.0) * cloud_lighting;
+    let cloud_color = palette.cloud * cloud_lighting;
     
     // Aplicar nubes con transparencia variable
     base_color = mix_color(base_color, cloud_color, (cloud_intensity * 0.7).min(0.75));
     
+    // Relámpagos en las zonas nubosas del lado nocturno
+    let flash = lightning_flash(pos, normal, light_dir, cloud_intensity, uniforms.time);
+    base_color = mix_color(base_color, Color::from_float(0.85, 0.9, 1.0), flash);
+    
     // ATMÓSFERA AZUL REALISTA - Efecto Rayleigh scattering
     let view_dir = (uniforms.camera_position - fragment_pos).normalize();
     let fresnel = (1.0 - normal.dot(&view_dir).abs()).powf(2.8); // Borde atmosférico
     
-    // Color de atmósfera terrestre (azul cielo)
-    let atmosphere_color = Color::from_float(0.35, 0.55, 0.95);
+    // Color de atmósfera terrestre (azul cielo de fábrica)
+    let atmosphere_color = palette.atmosphere;
     
     // Agregar brillo atmosférico más intenso en el borde
     let atmosphere_glow = fresnel * 0.45;
@@ -433,15 +351,39 @@ pub fn earth_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifo
     mix_color(base_color, atmosphere_color, atmosphere_glow)
 }
 
+// ============= NUBES DE TORMENTA (HURACANES) =============
+// Malla de nubes sobre la Tierra: la densidad de la espiral llega en tex_coords.x
+pub fn storm_cloud_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
+    let pos = vertex.position;
+    let normal = vertex.transformed_normal.normalize();
+    let fragment_pos = vertex.world_position;
+    let density = vertex.tex_coords.x;
+
+    // Las bandas finas son grisáceas; la pared del ojo, blanca y espesa
+    let thin_cloud = Color::from_float(0.72, 0.74, 0.80);
+    let thick_cloud = Color::from_float(0.98, 0.98, 1.0);
+    let base_color = mix_color(thin_cloud, thick_cloud, smoothstep(density));
+
+    // Misma iluminación que la capa de nubes de earth_like_shader
+    let light_dir = (uniforms.light_position - fragment_pos).normalize();
+    let cloud_lighting = (normal.dot(&light_dir).max(0.0) * 0.75 + 0.25).min(1.0);
+    let lit_color = base_color * cloud_lighting;
+
+    // Tormentas eléctricas en el lado nocturno
+    let flash = lightning_flash(pos, normal, light_dir, density, uniforms.time);
+    mix_color(lit_color, Color::from_float(0.85, 0.9, 1.0), flash)
+}
+
 // ============= GIGANTE GASEOSO (TIPO JÚPITER) =============
 // Shader con 7+ capas: atmósfera profunda, bandas en múltiples alturas, turbulencias,
 // gran mancha roja, tormentas secundarias, scattering, brillo volumétrico
 pub fn gas_giant_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
     let pos = vertex.position;
     let normal = vertex.transformed_normal.normalize();
-    let fragment_pos = vertex.transformed_position;
+    let fragment_pos = vertex.world_position;
     let view_dir = (uniforms.camera_position - fragment_pos).normalize();
     let detail = uniforms.detail_level;
+    let palette = uniforms.params.jupiter();
 
     // Calcular profundidad atmosférica (más denso en el centro, menos en los bordes)
     let edge_factor = normal.dot(&view_dir).abs();
@@ -450,13 +392,12 @@ pub fn gas_giant_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifor
     // ===== CAPA 1: Atmósfera profunda base (colores más precisos de Júpiter) =====
     // Júpiter tiene tonos naranjas, cremas y marrones
     let deep_atm_noise = fbm_adaptive(pos.x * 2.5, pos.y * 2.5, pos.z * 2.5, 4, detail);
-    let deep_color1 = Color::from_float(0.82, 0.58, 0.35); // Naranja cálido
-    let deep_color2 = Color::from_float(0.68, 0.45, 0.28); // Marrón dorado
+    let [deep_color1, deep_color2] = palette.deep; // Naranja cálido y marrón dorado
     let deep_layer = mix_color(deep_color1, deep_color2, deep_atm_noise);
 
     // ===== CAPA 2: Bandas atmosféricas horizontales (como en la referencia de Three.js) =====
     // Júpiter tiene bandas muy pronunciadas con mucha turbulencia
-    let band_freq = 14.0; // Más bandas para mayor realismo
+    let band_freq = uniforms.tweaks.band_frequency; // 14 de fábrica: más bandas para mayor realismo
     
     // Banda lenta (ecuatorial)
     let slow_distortion = turbulence_adaptive(
@@ -488,11 +429,9 @@ pub fn gas_giant_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifor
     ) * 0.8;
     let fast_band = ((pos.y + fast_distortion) * band_freq * 1.3).sin();
 
-    // Colores más precisos de Júpiter (inspirados en imágenes reales)
-    let band_color1 = Color::from_float(0.98, 0.88, 0.72); // Zona clara (crema brillante)
-    let band_color2 = Color::from_float(0.75, 0.52, 0.32); // Cinturón oscuro (marrón rojizo)
-    let band_color3 = Color::from_float(0.92, 0.78, 0.58); // Zona intermedia (naranja suave)
-    let band_color4 = Color::from_float(0.68, 0.45, 0.28); // Cinturón profundo (marrón oscuro)
+    // Colores de Júpiter (inspirados en imágenes reales): zona clara crema, zona intermedia
+    // naranja suave, cinturón oscuro marrón rojizo y cinturón profundo marrón oscuro
+    let [band_color1, band_color3, band_color2, band_color4] = palette.bands;
 
     let combined_band = slow_band * 0.4 + mid_band * 0.35 + fast_band * 0.25;
     let band_value = (combined_band + 1.0) / 2.0;
@@ -533,18 +472,18 @@ pub fn gas_giant_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifor
         detail,
     );
     let vortex_combined = large_vortex * 0.5 + medium_vortex * 0.3 + small_vortex * 0.2;
-    let vortex_color = Color::from_float(0.85, 0.65, 0.45); // Naranja turbulento
+    let vortex_color = palette.vortex; // Naranja turbulento
     base_color = mix_color(base_color, vortex_color, vortex_combined * 0.4);
 
     // ===== CAPA 4: Gran Mancha Roja (Great Red Spot) =====
     // La tormenta más famosa del sistema solar - tiene que verse BIEN
-    let storm_center = Vec3::new(0.3, -0.12, 0.65);
+    let storm_center = uniforms.tweaks.place_storm(Vec3::new(0.3, -0.12, 0.65));
     let dx = pos.x - storm_center.x;
     let dy = (pos.y - storm_center.y) * 1.8; // Elíptica (más ancha que alta)
     let dz = pos.z - storm_center.z;
     let dist_to_storm = (dx * dx + dy * dy + dz * dz).sqrt();
 
-    let storm_radius = 0.38; // Más grande
+    let storm_radius = 0.38 * uniforms.tweaks.storm_size; // Más grande
     let storm_intensity = (1.0 - (dist_to_storm / storm_radius)).max(0.0).powf(1.3);
     
     // Rotación de la tormenta (anti-ciclónica)
@@ -558,9 +497,7 @@ pub fn gas_giant_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifor
     );
 
     // Colores de la Gran Mancha Roja (rojo ladrillo con bordes naranjas)
-    let storm_center_color = Color::from_float(0.92, 0.22, 0.12); // Rojo intenso
-    let storm_mid_color = Color::from_float(0.88, 0.35, 0.18);    // Rojo anaranjado
-    let storm_edge_color = Color::from_float(0.82, 0.48, 0.28);   // Naranja
+    let [storm_edge_color, storm_mid_color, storm_center_color] = palette.storm;
     
     let storm_color = if storm_intensity > 0.6 {
         mix_color(storm_mid_color, storm_center_color, (storm_intensity - 0.6) * 2.5)
@@ -574,13 +511,13 @@ pub fn gas_giant_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifor
     let white_spot_center = Vec3::new(-0.35, 0.35, 0.5);
     let dist_white = ((pos - white_spot_center).magnitude() * 7.0 - 1.0).max(0.0);
     let white_spot_intensity = (1.0 - dist_white).max(0.0).powf(2.0);
-    let white_storm_color = Color::from_float(0.95, 0.85, 0.70);
+    let white_storm_color = palette.white_storm;
     base_color = mix_color(base_color, white_storm_color, white_spot_intensity * 0.5);
 
     let brown_spot_center = Vec3::new(0.4, 0.25, -0.4);
     let dist_brown = ((pos - brown_spot_center).magnitude() * 9.0 - 1.0).max(0.0);
     let brown_spot_intensity = (1.0 - dist_brown).max(0.0).powf(2.5);
-    let brown_storm_color = Color::from_float(0.65, 0.45, 0.30);
+    let brown_storm_color = palette.brown_storm;
     base_color = mix_color(base_color, brown_storm_color, brown_spot_intensity * 0.4);
 
     // ===== CAPA 6: Nubes de alta altitud =====
@@ -591,8 +528,8 @@ pub fn gas_giant_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifor
         3,
         detail,
     );
-    let cloud_intensity = ((high_clouds - 0.55).max(0.0) * 3.0).min(1.0);
-    let high_cloud_color = Color::from_float(0.98, 0.90, 0.75);
+    let cloud_intensity = ((high_clouds - uniforms.tweaks.cloud_threshold).max(0.0) * 3.0).min(1.0);
+    let high_cloud_color = palette.high_cloud;
     base_color = mix_color(base_color, high_cloud_color, cloud_intensity * 0.25);
 
     // ===== CAPA 7: Iluminación atmosférica realista (inspirada en Three.js) =====
@@ -617,14 +554,19 @@ pub fn gas_giant_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifor
     let lighting = ambient + diffuse_factor * 0.85 + subsurface + spec + fresnel;
     base_color = base_color * lighting.clamp(0.3, 1.8);
 
+    // Relámpagos en tormentas del lado nocturno (nubes altas y vórtices)
+    let storm_clouds = cloud_intensity.max(vortex_combined * 0.6);
+    let flash = lightning_flash(pos, normal, light_dir, storm_clouds, uniforms.time);
+    base_color = mix_color(base_color, Color::from_float(0.9, 0.88, 1.0), flash);
+
     // ===== CAPA 8: Scattering atmosférico (rayos de luz dispersándose) =====
     let scatter_intensity = (1.0 - edge_factor).powf(2.8);
-    let scatter_color = Color::from_float(0.92, 0.78, 0.62); // Naranja dorado cálido
+    let scatter_color = palette.scatter; // Naranja dorado cálido
     base_color = mix_color(base_color, scatter_color, scatter_intensity * 0.25);
 
     // ===== CAPA 9: Rim Light volumétrico (brillo atmosférico en los bordes) =====
     let rim_light = (1.0 - edge_factor).powf(2.2);
-    let rim_color = Color::from_float(0.98, 0.82, 0.62);
+    let rim_color = palette.rim;
     base_color = mix_color(base_color, rim_color, rim_light * 0.35);
 
     // ===== CAPA 10: Variación de densidad =====
@@ -646,13 +588,12 @@ pub fn gas_giant_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifor
 pub fn mars_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
     let pos = vertex.position;
     let normal = vertex.transformed_normal.normalize();
-    let fragment_pos = vertex.transformed_position;
+    let fragment_pos = vertex.world_position;
+    let palette = uniforms.params.mars();
     
     // Capa 1: Superficie oxidada con variación
     let base_noise = fbm(pos.x * 3.0, pos.y * 3.0, pos.z * 3.0, 4);
-    let rust_color1 = Color::from_float(0.8, 0.3, 0.1);
-    let rust_color2 = Color::from_float(0.6, 0.25, 0.15);
-    let rust_color3 = Color::from_float(0.7, 0.35, 0.2);
+    let [rust_color2, rust_color3, rust_color1] = palette.rust;
     
     let mut base_color = if base_noise > 0.6 {
         rust_color1
@@ -666,39 +607,32 @@ pub fn mars_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifor
     let crater_noise = worley_noise(pos.x * 5.0, pos.y * 5.0, pos.z * 5.0);
     let crater_depth = fbm(pos.x * 12.0, pos.y * 12.0, pos.z * 12.0, 2);
     let crater_intensity = ((crater_noise - 0.4).max(0.0) * crater_depth).min(1.0);
-    let crater_color = Color::from_float(0.3, 0.15, 0.1);
+    let crater_color = palette.crater;
     base_color = mix_color(base_color, crater_color, crater_intensity * 0.6);
     
     // Capa 3: Polos de hielo (CO2)
     let pole_intensity = (pos.y.abs() - 0.65).max(0.0) * 6.0;
     let ice_noise = fbm(pos.x * 10.0, pos.y * 10.0, pos.z * 10.0, 3);
-    let ice_color = Color::from_float(0.9, 0.95, 1.0);
+    let ice_color = palette.ice;
     base_color = mix_color(base_color, ice_color, (pole_intensity * ice_noise).min(1.0));
+
+    // Tormentas de polvo (regionales y globales): tapan cráteres y casquetes con un velo ocre
+    let dust_cover = dust_coverage(pos, uniforms.time);
+    let dust_color = palette.dust;
+    base_color = mix_color(base_color, dust_color, dust_cover * 0.85);
     
-    // Aplicar iluminación Phong
-    base_color = calculate_phong_lighting(
-        fragment_pos,
-        normal,
-        uniforms.light_position,
-        uniforms.camera_position,
-        base_color,
-        0.3,
-        0.75,
-        0.08,
-        4.0
-    );
+    // Iluminación con el material del cuerpo
+    base_color = light_surface(&body_material(CelestialBody::Mars), fragment_pos, normal, uniforms, base_color);
     
-    // Capa 4: Atmósfera tenue con tormentas de polvo
+    // Capa 4: Atmósfera tenue con tormentas de polvo (más espesa y rojiza durante una tormenta global)
     let view_dir = (uniforms.camera_position - fragment_pos).normalize();
     let atmosphere = (1.0 - normal.dot(&view_dir).abs()).powf(4.0);
     let dust_storm = fbm(pos.x * 4.0 + uniforms.time * 0.1, pos.y * 4.0, pos.z * 4.0, 2);
-    let atm_color = mix_color(
-        Color::from_float(0.9, 0.6, 0.4),
-        Color::from_float(0.8, 0.5, 0.3),
-        dust_storm
-    );
+    let atm_color = mix_color(palette.haze[0], palette.haze[1], dust_storm);
+    let global_dust = global_dust_level(uniforms.time);
+    let haze = (atmosphere * (1.0 + global_dust * 3.0)).min(1.0) * (0.2 + global_dust * 0.3);
     
-    mix_color(base_color, atm_color, atmosphere * 0.2)
+    mix_color(base_color, atm_color, haze)
 }
 
 // ============= GIGANTE GASEOSO CON ANILLOS (TIPO SATURNO) =============
@@ -707,9 +641,10 @@ pub fn mars_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifor
 pub fn saturn_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
     let pos = vertex.position;
     let normal = vertex.transformed_normal.normalize();
-    let fragment_pos = vertex.transformed_position;
+    let fragment_pos = vertex.world_position;
     let view_dir = (uniforms.camera_position - fragment_pos).normalize();
     let detail = uniforms.detail_level;
+    let palette = uniforms.params.saturn();
     
     // Calcular profundidad atmosférica
     let edge_factor = normal.dot(&view_dir).abs();
@@ -717,12 +652,11 @@ pub fn saturn_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unif
     
     // ===== CAPA 1: Atmósfera profunda base (tonos crema/beige) =====
     let deep_atm_noise = fbm_adaptive(pos.x * 1.8, pos.y * 1.8, pos.z * 1.8, 3, detail);
-    let deep_color1 = Color::from_float(0.90, 0.85, 0.68);
-    let deep_color2 = Color::from_float(0.85, 0.80, 0.63);
+    let [deep_color1, deep_color2] = palette.deep;
     let deep_layer = mix_color(deep_color1, deep_color2, deep_atm_noise);
     
     // ===== CAPA 2: Bandas atmosféricas en múltiples altitudes =====
-    let band_freq = 9.0;
+    let band_freq = uniforms.tweaks.band_frequency;
     
     // Banda lenta (capa profunda) - movimiento lento hacia el este
     let slow_distortion = fbm_adaptive(
@@ -754,11 +688,8 @@ pub fn saturn_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unif
     ) * 0.3;
     let fast_band = ((pos.y + fast_distortion) * band_freq * 1.1).sin();
     
-    // Colores de bandas (tonos pastel suaves)
-    let band_color1 = Color::from_float(0.98, 0.94, 0.78);  // Crema muy claro
-    let band_color2 = Color::from_float(0.88, 0.84, 0.68);  // Beige
-    let band_color3 = Color::from_float(0.93, 0.89, 0.73);  // Intermedio
-    let band_color4 = Color::from_float(0.84, 0.80, 0.65);  // Beige oscuro
+    // Colores de bandas (tonos pastel suaves): crema muy claro, intermedio, beige y beige oscuro
+    let [band_color1, band_color3, band_color2, band_color4] = palette.bands;
     
     // Combinar bandas
     let combined_band = slow_band * 0.4 + mid_band * 0.4 + fast_band * 0.2;
@@ -785,7 +716,7 @@ pub fn saturn_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unif
         4,
         detail,
     );
-    let turb_color = Color::from_float(0.91, 0.87, 0.71);
+    let turb_color = palette.turbulence;
     base_color = mix_color(base_color, turb_color, gentle_turbulence * 0.25);
     
     // ===== CAPA 4: Corrientes de viento (jet streams) =====
@@ -804,7 +735,7 @@ pub fn saturn_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unif
         2,
         detail,
     );
-    let wind_color = Color::from_float(0.96, 0.92, 0.76);
+    let wind_color = palette.wind;
     base_color = mix_color(base_color, wind_color, wind_pattern * wind_strength * 0.3);
     
     // ===== CAPA 5: Hexágono en polo norte (característica real única de Saturno) =====
@@ -821,7 +752,7 @@ pub fn saturn_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unif
         let hex_intensity = hex_pattern * lat_factor;
         
         // Color del hexágono (más oscuro)
-        let hex_color = Color::from_float(0.78, 0.74, 0.60);
+        let [hex_color, hex_turb_color] = palette.hexagon;
         base_color = mix_color(base_color, hex_color, hex_intensity.abs() * 0.4);
         
         // Agregar turbulencia dentro del hexágono
@@ -832,7 +763,6 @@ pub fn saturn_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unif
             3,
             detail,
         );
-        let hex_turb_color = Color::from_float(0.82, 0.78, 0.64);
         base_color = mix_color(base_color, hex_turb_color, hex_turb * lat_factor * 0.3);
     }
     
@@ -845,7 +775,7 @@ pub fn saturn_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unif
         detail,
     );
     let cloud_intensity = ((high_clouds - 0.6).max(0.0) * 3.5).min(1.0);
-    let wispy_color = Color::from_float(0.99, 0.96, 0.82);
+    let wispy_color = palette.high_cloud;
     base_color = mix_color(base_color, wispy_color, cloud_intensity * 0.2);
     
     // ===== CAPA 7: Iluminación atmosférica (gas dispersa luz suavemente) =====
@@ -861,14 +791,18 @@ pub fn saturn_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unif
     let lighting = ambient + diffuse_factor * 0.65 + subsurface + spec;
     base_color = base_color * lighting.min(1.4);
     
+    // Relámpagos en las nubes altas del lado nocturno
+    let flash = lightning_flash(pos, normal, light_dir, cloud_intensity * 1.5, uniforms.time);
+    base_color = mix_color(base_color, Color::from_float(0.92, 0.9, 1.0), flash);
+    
     // ===== CAPA 8: Scattering atmosférico (tonos dorados) =====
     let scatter_intensity = (1.0 - edge_factor).powf(3.5);
-    let scatter_color = Color::from_float(0.95, 0.91, 0.75);
+    let scatter_color = palette.scatter;
     base_color = mix_color(base_color, scatter_color, scatter_intensity * 0.18);
     
     // ===== CAPA 9: Brillo volumétrico suave en los bordes =====
     let rim_light = (1.0 - edge_factor).powf(2.2);
-    let rim_color = Color::from_float(0.99, 0.95, 0.80);
+    let rim_color = palette.rim;
     base_color = mix_color(base_color, rim_color, rim_light * 0.25);
     
     // ===== CAPA 10: Variación de densidad (atmósfera menos densa en los bordes) =====
@@ -890,7 +824,7 @@ pub fn saturn_like_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unif
 pub fn ring_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
     let pos = vertex.position;
     let normal = vertex.transformed_normal.normalize();
-    let fragment_pos = vertex.transformed_position;
+    let fragment_pos = vertex.world_position;
     
     // Distancia radial desde el centro (en el plano XZ)
     let radial_dist = (pos.x * pos.x + pos.z * pos.z).sqrt();
@@ -941,33 +875,58 @@ pub fn ring_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -
     let density = (radial_dist * 15.0).sin() * 0.5 + 0.5;
     base_color = base_color * (0.7 + density * 0.3);
     
-    // Aplicar iluminación Phong
-    base_color = calculate_phong_lighting(
-        fragment_pos,
-        normal,
-        uniforms.light_position,
-        uniforms.camera_position,
-        base_color,
-        0.3,
-        0.7,
-        0.25,
-        8.0
-    );
+    // Iluminación: brillo anisótropo a lo largo de la órbita de las partículas
+    let tangent = Backend::transform_vector(&uniforms.normal_matrix, &Vec3::new(-pos.z, 0.0, pos.x)).normalize();
+    let light_dir = (uniforms.light_position - fragment_pos).normalize();
+    let view_dir = (uniforms.camera_position - fragment_pos).normalize();
+    base_color = body_material(CelestialBody::Ring).shade(base_color, normal, Some(tangent), light_dir, view_dir);
     
     // Capa 4: Efecto de translucidez cuando el sol está detrás
-    let light_dir = (uniforms.light_position - fragment_pos).normalize();
     let backlight = (-normal.dot(&light_dir)).max(0.0);
     let glow_color = Color::from_float(1.0, 0.95, 0.85);
     
     mix_color(base_color, glow_color, backlight * 0.3)
 }
 
+// ============= DISCO DE ACRECIÓN =============
+// Gas que cae en espiral hacia el agujero negro (ver black_hole.rs). Emite su propia luz:
+// la temperatura cae con el radio como r^-3/4 (blanco azulado por dentro, naranja por
+// fuera), el gas gira más rápido cuanto más cerca está (rotación kepleriana, ω ~ r^-3/2)
+// y el lado que se acerca a la cámara brilla más por el efecto Doppler relativista.
+// tex_coords = (radio normalizado en el disco, ángulo del centro de la celda)
+const DISC_TEMPERATURE: f32 = 9000.0; // kelvin en el borde interior
+const DISC_SPIN: f32 = 0.6;           // radianes por segundo en el borde interior
+
+pub fn accretion_disc_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
+    let radius = 1.0 + vertex.tex_coords.x * (DISC_OUTER / DISC_INNER - 1.0); // en múltiplos del radio interior
+    let theta = vertex.tex_coords.y;
+
+    // Capa 1: color de cuerpo negro según la temperatura del anillo
+    let temperature = DISC_TEMPERATURE * radius.powf(-0.75);
+    let base_color = star_tint(temperature);
+
+    // Capa 2: filamentos de gas arrastrados por la rotación diferencial
+    let phase = theta - uniforms.time * DISC_SPIN * radius.powf(-1.5);
+    let filaments = fbm(phase.cos() * 3.0 * radius, phase.sin() * 3.0 * radius, radius * 6.0, 4);
+    let brightness = radius.powf(-1.5) * (0.45 + 0.9 * filaments);
+
+    // Capa 3: Doppler, según la velocidad orbital (tangente al anillo) hacia la cámara
+    let velocity = Backend::transform_vector(&uniforms.normal_matrix, &Vec3::new(-theta.sin(), 0.0, theta.cos())).normalize();
+    let toward_camera = Vec3::new(uniforms.view_matrix[(2, 0)], uniforms.view_matrix[(2, 1)], uniforms.view_matrix[(2, 2)]);
+    let doppler = 1.0 + 0.6 * velocity.dot(&toward_camera);
+
+    // Capa 4: el borde exterior se difumina en el vacío
+    let edge = 1.0 - smoothstep((vertex.tex_coords.x - 0.75) / 0.25);
+
+    base_color * (brightness * doppler * edge * 1.6)
+}
+
 // ============= LUNA =============
 // Shader con 4 capas: superficie, cráteres, mares, rayos de eyección
 pub fn moon_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
     let pos = vertex.position;
     let normal = vertex.transformed_normal.normalize();
-    let fragment_pos = vertex.transformed_position;
+    let fragment_pos = vertex.world_position;
     
     // Capa 1: Superficie lunar (gris con variación)
     let surface_noise = fbm(pos.x * 5.0, pos.y * 5.0, pos.z * 5.0, 3);
@@ -1002,29 +961,183 @@ pub fn moon_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -
         base_color = mix_color(base_color, ray_color, 0.4);
     }
     
-    // Aplicar iluminación Phong
-    base_color = calculate_phong_lighting(
-        fragment_pos,
-        normal,
-        uniforms.light_position,
-        uniforms.camera_position,
-        base_color,
-        0.2,
-        0.85,
-        0.03,
-        2.0
-    );
+    // Iluminación con el material del cuerpo
+    base_color = light_surface(&body_material(CelestialBody::Moon), fragment_pos, normal, uniforms, base_color);
     
     base_color
 }
 
+// ============= LUNAS GALILEANAS (JÚPITER) =============
+
+// IO - 3 capas: azufre amarillo-naranja, calderas volcánicas oscuras con halos rojizos, escarcha de SO2
+pub fn io_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
+    let pos = vertex.position;
+    let normal = vertex.transformed_normal.normalize();
+    let fragment_pos = vertex.world_position;
+
+    // Capa 1: Llanuras de azufre con manchas naranjas
+    let sulfur_noise = fbm(pos.x * 4.0, pos.y * 4.0, pos.z * 4.0, 3);
+    let sulfur = Color::from_float(0.88, 0.80, 0.35);
+    let orange = Color::from_float(0.85, 0.50, 0.18);
+    let mut base_color = mix_color(sulfur, orange, (sulfur_noise - 0.4) * 2.5);
+
+    // Capa 2: Calderas (centro negro, halo rojo de depósitos de azufre)
+    let caldera = worley_noise(pos.x * 5.0, pos.y * 5.0, pos.z * 5.0);
+    let halo_color = Color::from_float(0.70, 0.25, 0.08);
+    let caldera_color = Color::from_float(0.12, 0.06, 0.04);
+    base_color = mix_color(base_color, halo_color, (1.0 - caldera * 5.0).clamp(0.0, 1.0) * 0.7);
+    base_color = mix_color(base_color, caldera_color, (1.0 - caldera * 12.0).clamp(0.0, 1.0));
+
+    // Capa 3: Escarcha blanca de dióxido de azufre
+    let frost = fbm(pos.x * 9.0, pos.y * 9.0, pos.z * 9.0, 2);
+    base_color = mix_color(base_color, Color::from_float(0.95, 0.93, 0.85), (frost - 0.65).max(0.0) * 3.0);
+
+    light_surface(&body_material(CelestialBody::Io), fragment_pos, normal, uniforms, base_color)
+}
+
+// EUROPA - 3 capas: hielo blanco, líneas (grietas) pardo-rojizas, terreno caótico
+pub fn europa_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
+    let pos = vertex.position;
+    let normal = vertex.transformed_normal.normalize();
+    let fragment_pos = vertex.world_position;
+
+    // Capa 1: Corteza de hielo
+    let ice_noise = fbm(pos.x * 3.0, pos.y * 3.0, pos.z * 3.0, 3);
+    let mut base_color = mix_color(
+        Color::from_float(0.82, 0.80, 0.74),
+        Color::from_float(0.95, 0.94, 0.90),
+        ice_noise,
+    );
+
+    // Capa 2: Lineae - grietas largas a partir de isolíneas de ruido deformado
+    let warp = fbm(pos.x * 2.0, pos.y * 2.0, pos.z * 2.0, 2) * 6.0;
+    let lines_a = ((pos.x * 7.0 + pos.y * 3.0 + warp).sin()).abs();
+    let lines_b = ((pos.z * 8.0 - pos.y * 4.0 + warp * 0.7).sin()).abs();
+    let crack = (1.0 - lines_a.min(lines_b) * 12.0).clamp(0.0, 1.0);
+    let crack_color = Color::from_float(0.55, 0.30, 0.18);
+    base_color = mix_color(base_color, crack_color, crack * 0.8);
+
+    // Capa 3: Terreno caótico (bloques de hielo removidos, más oscuros)
+    let chaos = fbm(pos.x * 6.0 + 20.0, pos.y * 6.0, pos.z * 6.0, 3);
+    base_color = mix_color(base_color, Color::from_float(0.60, 0.45, 0.35), (chaos - 0.62).max(0.0) * 2.5);
+
+    light_surface(&body_material(CelestialBody::Europa), fragment_pos, normal, uniforms, base_color)
+}
+
+// GANÍMEDES - 3 capas: regiones oscuras antiguas, terreno claro con surcos, escarcha polar
+pub fn ganymede_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
+    let pos = vertex.position;
+    let normal = vertex.transformed_normal.normalize();
+    let fragment_pos = vertex.world_position;
+
+    // Capa 1: Regiones oscuras (Galileo Regio) frente a terreno claro
+    let region = fbm(pos.x * 2.2, pos.y * 2.2, pos.z * 2.2, 4);
+    let dark = Color::from_float(0.33, 0.30, 0.26);
+    let bright = Color::from_float(0.66, 0.63, 0.58);
+    let bright_amount = ((region - 0.45) * 6.0).clamp(0.0, 1.0);
+    let mut base_color = mix_color(dark, bright, bright_amount);
+
+    // Capa 2: Surcos paralelos en el terreno claro
+    let groove_warp = fbm(pos.x * 3.0, pos.y * 3.0, pos.z * 3.0, 2) * 4.0;
+    let grooves = ((pos.x * 30.0 + pos.z * 18.0 + groove_warp).sin() * 0.5 + 0.5) * bright_amount;
+    base_color = base_color * (1.0 - grooves * 0.18);
+
+    // Capa 3: Casquetes de escarcha
+    let pole = (pos.y.abs() - 0.7).max(0.0) * 4.0;
+    base_color = mix_color(base_color, Color::from_float(0.85, 0.86, 0.88), pole.min(0.7));
+
+    light_surface(&body_material(CelestialBody::Ganymede), fragment_pos, normal, uniforms, base_color)
+}
+
+// CALISTO - 3 capas: superficie muy oscura, cráteres brillantes, cuenca multianillo (Valhalla)
+pub fn callisto_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
+    let pos = vertex.position;
+    let normal = vertex.transformed_normal.normalize();
+    let fragment_pos = vertex.world_position;
+
+    // Capa 1: Superficie oscura y antigua
+    let surface = fbm(pos.x * 5.0, pos.y * 5.0, pos.z * 5.0, 3);
+    let mut base_color = mix_color(
+        Color::from_float(0.18, 0.16, 0.14),
+        Color::from_float(0.30, 0.27, 0.23),
+        surface,
+    );
+
+    // Capa 2: Cráteres pequeños y brillantes (hielo expuesto) por todas partes
+    let craters = worley_noise(pos.x * 10.0, pos.y * 10.0, pos.z * 10.0);
+    base_color = mix_color(base_color, Color::from_float(0.75, 0.73, 0.70), (1.0 - craters * 10.0).clamp(0.0, 1.0));
+
+    // Capa 3: Valhalla - anillos concéntricos alrededor de un punto fijo
+    let basin_center = Vec3::new(0.6, 0.3, 0.74).normalize();
+    let basin_distance = pos.normalize().dot(&basin_center).clamp(-1.0, 1.0).acos();
+    if basin_distance < 0.9 {
+        let rings = (basin_distance * 40.0).sin() * 0.5 + 0.5;
+        let fade = 1.0 - basin_distance / 0.9;
+        base_color = mix_color(base_color, Color::from_float(0.55, 0.52, 0.48), rings * fade * 0.5);
+    }
+
+    light_surface(&body_material(CelestialBody::Callisto), fragment_pos, normal, uniforms, base_color)
+}
+
+// ============= LUNAS DE MARTE =============
+
+// FOBOS - 3 capas: regolito gris oscuro, estrías paralelas, cráter Stickney
+pub fn phobos_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
+    let pos = vertex.position;
+    let normal = vertex.transformed_normal.normalize();
+    let fragment_pos = vertex.world_position;
+
+    // Capa 1: Regolito oscuro
+    let regolith = fbm(pos.x * 6.0, pos.y * 6.0, pos.z * 6.0, 3);
+    let mut base_color = mix_color(
+        Color::from_float(0.22, 0.20, 0.19),
+        Color::from_float(0.36, 0.33, 0.30),
+        regolith,
+    );
+
+    // Capa 2: Estrías (cadenas de cráteres paralelas)
+    let grooves = ((pos.y * 45.0 + pos.x * 10.0).sin()).abs();
+    base_color = base_color * (0.85 + grooves * 0.15);
+
+    // Capa 3: Stickney, el gran cráter de impacto
+    let stickney_center = Vec3::new(1.0, 0.1, 0.2).normalize();
+    let crater_distance = pos.normalize().dot(&stickney_center).clamp(-1.0, 1.0).acos();
+    let crater_floor = (1.0 - crater_distance / 0.45).clamp(0.0, 1.0);
+    let crater_rim = (1.0 - (crater_distance - 0.5).abs() * 12.0).clamp(0.0, 1.0);
+    base_color = mix_color(base_color, Color::from_float(0.14, 0.13, 0.12), crater_floor * 0.8);
+    base_color = mix_color(base_color, Color::from_float(0.48, 0.44, 0.40), crater_rim * 0.6);
+
+    light_surface(&body_material(CelestialBody::Phobos), fragment_pos, normal, uniforms, base_color)
+}
+
+// DEIMOS - 2 capas: polvo liso ocre-grisáceo, pocos cráteres suavizados por el polvo
+pub fn deimos_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
+    let pos = vertex.position;
+    let normal = vertex.transformed_normal.normalize();
+    let fragment_pos = vertex.world_position;
+
+    // Capa 1: Superficie cubierta de polvo fino
+    let dust = fbm(pos.x * 3.0, pos.y * 3.0, pos.z * 3.0, 2);
+    let mut base_color = mix_color(
+        Color::from_float(0.40, 0.35, 0.30),
+        Color::from_float(0.52, 0.46, 0.39),
+        dust,
+    );
+
+    // Capa 2: Cráteres rellenos de polvo (contraste bajo)
+    let craters = worley_noise(pos.x * 4.0, pos.y * 4.0, pos.z * 4.0);
+    base_color = mix_color(base_color, Color::from_float(0.30, 0.27, 0.24), (1.0 - craters * 6.0).clamp(0.0, 1.0) * 0.4);
+
+    light_surface(&body_material(CelestialBody::Deimos), fragment_pos, normal, uniforms, base_color)
+}
+
 // ============= PLANETAS EXTRAS PARA BONIFICACIÓN =============
 
 // PLANETA DE LAVA VOLCÁNICO - 4 capas
 pub fn lava_planet_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
     let pos = vertex.position;
     let normal = vertex.transformed_normal.normalize();
-    let fragment_pos = vertex.transformed_position;
+    let fragment_pos = vertex.world_position;
     
     // Capa 1: Corteza oscura (roca volcánica)
     let crust_noise = fbm(pos.x * 4.0, pos.y * 4.0, pos.z * 4.0, 3);
@@ -1065,17 +1178,7 @@ pub fn lava_planet_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unif
     if is_crack {
         base_color = base_color * (1.5 + lava_intensity * 0.5);
     } else {
-        base_color = calculate_phong_lighting(
-            fragment_pos,
-            normal,
-            uniforms.light_position,
-            uniforms.camera_position,
-            base_color,
-            0.2,
-            0.6,
-            0.1,
-            4.0
-        );
+        base_color = light_surface(&body_material(CelestialBody::LavaPlanet), fragment_pos, normal, uniforms, base_color);
     }
     
     // Capa 4: Atmósfera volcánica (ceniza y gases)
@@ -1090,7 +1193,7 @@ pub fn lava_planet_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unif
 pub fn ice_planet_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
     let pos = vertex.position;
     let normal = vertex.transformed_normal.normalize();
-    let fragment_pos = vertex.transformed_position;
+    let fragment_pos = vertex.world_position;
     
     // Capa 1: Hielo base (azul cristalino)
     let ice_noise = fbm(pos.x * 3.0, pos.y * 3.0, pos.z * 3.0, 4);
@@ -1119,18 +1222,8 @@ pub fn ice_planet_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifo
     let aurora_color = Color::from_float(0.3, 0.8, 0.9);
     base_color = mix_color(base_color, aurora_color, aurora_intensity * 0.3);
     
-    // Aplicar iluminación Phong (hielo es muy reflectante)
-    base_color = calculate_phong_lighting(
-        fragment_pos,
-        normal,
-        uniforms.light_position,
-        uniforms.camera_position,
-        base_color,
-        0.4,
-        0.6,
-        0.9,
-        128.0
-    );
+    // Iluminación (hielo es muy reflectante)
+    base_color = light_surface(&body_material(CelestialBody::IcePlanet), fragment_pos, normal, uniforms, base_color);
     
     // Capa 5: Atmósfera cristalina
     let view_dir = (uniforms.camera_position - fragment_pos).normalize();
@@ -1144,7 +1237,7 @@ pub fn ice_planet_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Unifo
 pub fn alien_planet_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
     let pos = vertex.position;
     let normal = vertex.transformed_normal.normalize();
-    let fragment_pos = vertex.transformed_position;
+    let fragment_pos = vertex.world_position;
     
     // Capa 1: Superficie base alienígena (púrpura/magenta)
     let surface_noise = fbm(pos.x * 3.0, pos.y * 3.0, pos.z * 3.0, 4);
@@ -1165,7 +1258,7 @@ pub fn alien_planet_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uni
         pos.z * 8.0 - uniforms.time * 0.08,
         3
     );
-    let bio_spots = (bio_pattern - 0.6).max(0.0) * 4.0;
+    let bio_spots = (bio_pattern - 0.6).max(0.0) * 4.0 * uniforms.tweaks.glow;
     let bio_color = Color::from_float(0.0, 1.0, 0.8);
     base_color = mix_color(base_color, bio_color * pulse, bio_spots.min(1.0));
     
@@ -1176,17 +1269,7 @@ pub fn alien_planet_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uni
     base_color = mix_color(base_color, vein_color, vein_intensity.min(1.0) * 0.6);
     
     // Aplicar iluminación
-    base_color = calculate_phong_lighting(
-        fragment_pos,
-        normal,
-        uniforms.light_position,
-        uniforms.camera_position,
-        base_color,
-        0.35,
-        0.7,
-        0.4,
-        16.0
-    );
+    base_color = light_surface(&body_material(CelestialBody::AlienPlanet), fragment_pos, normal, uniforms, base_color);
     
     // Capa 5: Atmósfera extraña (gradiente multicolor)
     let view_dir = (uniforms.camera_position - fragment_pos).normalize();
@@ -1200,11 +1283,291 @@ pub fn alien_planet_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uni
     mix_color(base_color, atm_color, atmosphere * 0.5)
 }
 
+// ============= GIGANTE HELADO (TIPO URANO) =============
+// Shader con 5 capas: atmósfera cian pálida, bandas muy sutiles sobre el eje inclinado,
+// capucha polar brillante, neblina de metano, iluminación suave
+pub fn uranus_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
+    let pos = vertex.position;
+    let normal = vertex.transformed_normal.normalize();
+    let fragment_pos = vertex.world_position;
+    let view_dir = (uniforms.camera_position - fragment_pos).normalize();
+    let detail = uniforms.detail_level;
+
+    // Urano rota "acostado": el eje de las bandas está inclinado ~98° respecto al eje Y
+    let tilt = 97.77_f32.to_radians();
+    let (sin_t, cos_t) = tilt.sin_cos();
+    let axis_coord = pos.x * sin_t + pos.y * cos_t; // latitud sobre el eje inclinado
+
+    // Capa 1: Atmósfera base casi sin rasgos (cian pálido)
+    let haze_noise = fbm_adaptive(pos.x * 1.5, pos.y * 1.5, pos.z * 1.5, 3, detail);
+    let palette = uniforms.params.uranus();
+    let [cyan_deep, cyan_light] = palette.base;
+    let mut base_color = mix_color(cyan_deep, cyan_light, haze_noise);
+
+    // Capa 2: Bandas MUY sutiles (apenas visibles, como en imágenes reales)
+    let band_distortion = fbm_adaptive(
+        pos.x * 2.0 + uniforms.time * 0.01,
+        pos.y * 2.0,
+        pos.z * 2.0 - uniforms.time * 0.008,
+        2,
+        detail,
+    ) * 0.25;
+    let band = ((axis_coord + band_distortion) * uniforms.tweaks.band_frequency).sin() * 0.5 + 0.5;
+    let band_color = palette.band;
+    base_color = mix_color(base_color, band_color, band * 0.12);
+
+    // Capa 3: Capucha polar brillante (el polo apunta casi hacia el Sol)
+    let polar_hood = (axis_coord.abs() - 0.7).max(0.0) * 3.0;
+    let hood_color = palette.hood;
+    base_color = mix_color(base_color, hood_color, polar_hood.min(1.0) * 0.5);
+
+    // Capa 4: Iluminación suave (atmósfera densa, wrap lighting)
+    let light_dir = (uniforms.light_position - fragment_pos).normalize();
+    let diffuse_factor = (normal.dot(&light_dir) * 0.55 + 0.45).max(0.0);
+    let spec = reflect(-light_dir, normal).dot(&view_dir).max(0.0).powf(8.0) * 0.08;
+    let lighting = 0.3 + diffuse_factor * 0.75 + spec;
+    base_color = base_color * lighting.clamp(0.3, 1.3);
+
+    // Capa 5: Neblina de metano en el limbo
+    let edge_factor = normal.dot(&view_dir).abs();
+    let limb_haze = (1.0 - edge_factor).powf(2.5);
+    let haze_color = palette.haze;
+    mix_color(base_color, haze_color, limb_haze * 0.45)
+}
+
+// ============= GIGANTE HELADO (TIPO NEPTUNO) =============
+// Shader con 6 capas: azul profundo, bandas, Gran Mancha Oscura, nubes compañeras,
+// vetas de nubes de metano, neblina atmosférica
+pub fn neptune_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
+    let pos = vertex.position;
+    let normal = vertex.transformed_normal.normalize();
+    let fragment_pos = vertex.world_position;
+    let view_dir = (uniforms.camera_position - fragment_pos).normalize();
+    let detail = uniforms.detail_level;
+
+    // Capa 1: Atmósfera base azul profundo
+    let deep_noise = fbm_adaptive(pos.x * 2.0, pos.y * 2.0, pos.z * 2.0, 3, detail);
+    let palette = uniforms.params.neptune();
+    let [deep_blue, mid_blue] = palette.base;
+    let mut base_color = mix_color(deep_blue, mid_blue, deep_noise);
+
+    // Capa 2: Bandas (vientos más rápidos del sistema solar)
+    let band_distortion = turbulence_adaptive(
+        pos.x * 3.0 + uniforms.time * 0.05,
+        pos.y * 2.0,
+        pos.z * 3.0 - uniforms.time * 0.04,
+        3,
+        detail,
+    ) * 0.4;
+    let band = ((pos.y + band_distortion) * uniforms.tweaks.band_frequency).sin() * 0.5 + 0.5;
+    let band_color = palette.band;
+    base_color = mix_color(base_color, band_color, band * 0.35);
+
+    // Capa 3: Gran Mancha Oscura (tormenta anticiclónica, elíptica)
+    let storm_size = uniforms.tweaks.storm_size;
+    let spot_center = uniforms.tweaks.place_storm(Vec3::new(-0.25, -0.3, 0.6));
+    let dx = pos.x - spot_center.x;
+    let dy = (pos.y - spot_center.y) * 1.6;
+    let dz = pos.z - spot_center.z;
+    let dist_to_spot = (dx * dx + dy * dy + dz * dz).sqrt();
+    let spot_intensity = (1.0 - dist_to_spot / (0.28 * storm_size)).max(0.0).powf(1.5);
+    let spot_color = palette.spot;
+    base_color = mix_color(base_color, spot_color, spot_intensity * 0.85);
+
+    // Capa 4: Nubes compañeras brillantes al borde de la mancha
+    let companion_center = uniforms.tweaks.place_storm(Vec3::new(-0.25, -0.12, 0.65));
+    let dist_companion = (pos - companion_center).magnitude() * 8.0 / storm_size;
+    let companion_intensity = (1.0 - dist_companion).max(0.0).powf(2.0);
+    let companion_color = palette.companion;
+    base_color = mix_color(base_color, companion_color, companion_intensity * 0.7);
+
+    // Capa 5: Vetas de nubes de metano (alargadas en longitud, muy blancas)
+    let streak_noise = fbm_adaptive(
+        pos.x * 3.0 + uniforms.time * 0.12,
+        pos.y * 22.0,
+        pos.z * 3.0 - uniforms.time * 0.1,
+        3,
+        detail,
+    );
+    let streak_intensity = ((streak_noise - 0.62).max(0.0) * 4.0).min(1.0);
+    let streak_color = palette.streak;
+    base_color = mix_color(base_color, streak_color, streak_intensity * 0.6);
+
+    // Iluminación (wrap lighting para atmósfera densa)
+    let light_dir = (uniforms.light_position - fragment_pos).normalize();
+    let diffuse_factor = (normal.dot(&light_dir) * 0.6 + 0.4).max(0.0);
+    let spec = reflect(-light_dir, normal).dot(&view_dir).max(0.0).powf(10.0) * 0.15;
+    let lighting = 0.28 + diffuse_factor * 0.8 + spec;
+    base_color = base_color * lighting.clamp(0.3, 1.4);
+
+    // Capa 6: Neblina atmosférica azul en los bordes
+    let edge_factor = normal.dot(&view_dir).abs();
+    let rim = (1.0 - edge_factor).powf(2.2);
+    let rim_color = palette.rim;
+    mix_color(base_color, rim_color, rim * 0.4)
+}
+
+// ============= PLANETA PROCEDURAL (SEMILLA) =============
+// Shader con 4 capas parametrizado por ProceduralPlanet: relieve con nivel del mar y
+// paleta de bioma, casquetes polares, nubes, atmósfera
+pub fn procedural_planet_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms, planet: &ProceduralPlanet) -> Color {
+    let pos = vertex.position;
+    let normal = vertex.transformed_normal.normalize();
+    let fragment_pos = vertex.world_position;
+    let palette = planet.biome.palette();
+
+    // El desplazamiento de la semilla hace que cada planeta use otra región del ruido
+    let [ox, oy, oz] = planet.terrain_offset;
+    let scale = planet.terrain_scale;
+    let p = Vec3::new(pos.x * scale + ox, pos.y * scale + oy, pos.z * scale + oz);
+
+    // Capa 1: Relieve (la mayor parte del fbm cae en [0.3, 0.7]; el nivel del mar se
+    // reescala a ese rango para que ocean_level aproxime la fracción de océano)
+    let height = fbm_adaptive(p.x, p.y, p.z, 5, uniforms.detail_level);
+    let sea_level = 0.5 + (planet.ocean_level - 0.5) * 0.35;
+    let is_ocean = height < sea_level;
+
+    let mut base_color = if is_ocean {
+        let depth = ((sea_level - height) / 0.12).clamp(0.0, 1.0);
+        mix_color(palette.shallow_ocean, palette.deep_ocean, depth)
+    } else {
+        let elevation = ((height - sea_level) / (1.0 - sea_level).max(0.01) * 2.5).clamp(0.0, 1.0);
+        if elevation < 0.5 {
+            mix_color(palette.lowland, palette.highland, elevation * 2.0)
+        } else {
+            mix_color(palette.highland, palette.peak, (elevation - 0.5) * 2.0)
+        }
+    };
+
+    // Capa 2: Casquetes polares
+    let ice_noise = fbm(p.x * 3.0, p.y * 3.0, p.z * 3.0, 2);
+    let pole_intensity = (pos.y.abs() - planet.ice_caps).max(0.0) * 8.0;
+    base_color = mix_color(base_color, palette.peak, (pole_intensity * ice_noise).min(1.0));
+
+    // Iluminación: océanos brillantes, tierra mate
+    let (specular, shininess) = if is_ocean { (0.6, 48.0) } else { (0.05, 4.0) };
+    base_color = light_surface(&Material { specular, shininess, ..body_material(CelestialBody::Procedural(*planet)) }, fragment_pos, normal, uniforms, base_color);
+
+    // Capa 3: Nubes (la densidad desplaza el umbral de cobertura)
+    let cloud_noise = fbm(
+        p.x * 1.5 + uniforms.time * 0.04,
+        p.y * 1.5,
+        p.z * 1.5 - uniforms.time * 0.03,
+        4
+    );
+    let cloud_threshold = 0.62 - planet.cloud_density * 0.25;
+    let cloud_intensity = ((cloud_noise - cloud_threshold) * 3.0).clamp(0.0, 1.0);
+    let light_dir = (uniforms.light_position - fragment_pos).normalize();
+    let cloud_lighting = (normal.dot(&light_dir).max(0.0) * 0.75 + 0.25).min(1.0);
+    base_color = mix_color(base_color, palette.cloud * cloud_lighting, cloud_intensity * 0.8);
+
+    // Capa 4: Atmósfera en el borde
+    let view_dir = (uniforms.camera_position - fragment_pos).normalize();
+    let fresnel = (1.0 - normal.dot(&view_dir).abs()).powf(2.8);
+
+    mix_color(base_color, palette.atmosphere, fresnel * 0.4)
+}
+
+// ============= RELIEVE (MODO TERRENO) =============
+// Altura de la superficie en [0, 1] para un punto de la esfera unitaria, con el mismo
+// ruido que usa el shader del cuerpo (las montañas coinciden con sus colores).
+// None para los cuerpos sin superficie sólida (Sol, gigantes gaseosos, anillos).
+pub fn surface_height(body: CelestialBody, pos: Vec3) -> Option<f32> {
+    // Cráteres: borde elevado y fondo hundido a partir de la distancia de Worley
+    let craters = |frequency: f32| {
+        let cell = worley_noise(pos.x * frequency, pos.y * frequency, pos.z * frequency);
+        let rim = (1.0 - (cell - 0.35).abs() * 8.0).max(0.0) * 0.25;
+        let bowl = (0.3 - cell).max(0.0) * 1.5;
+        rim - bowl
+    };
+
+    let height = match body {
+        CelestialBody::Earth => {
+            // Mismo criterio de tierra firme que earth_like_shader: los océanos quedan planos
+            let continent_base = worley_noise(pos.x * 1.2, pos.y * 1.2, pos.z * 1.2);
+            let continent_detail = fbm(pos.x * 2.5, pos.y * 2.5, pos.z * 2.5, 5);
+            let continent_variation = fbm(pos.x * 1.8, pos.y * 1.8, pos.z * 1.8, 4);
+            let land_threshold = 0.48 + continent_variation * 0.08;
+            let is_land = (continent_base > land_threshold) || (continent_detail > 0.62 && continent_base > 0.42);
+            if is_land {
+                let altitude = fbm(pos.x * 4.5, pos.y * 4.5, pos.z * 4.5, 5);
+                0.1 + ((altitude - 0.35) * 2.0).clamp(0.0, 0.9)
+            } else {
+                0.0
+            }
+        }
+        CelestialBody::Mars => {
+            let base = fbm(pos.x * 3.0, pos.y * 3.0, pos.z * 3.0, 5);
+            base * 0.8 + craters(5.0)
+        }
+        CelestialBody::Moon | CelestialBody::Callisto | CelestialBody::Ganymede => {
+            let base = fbm(pos.x * 5.0, pos.y * 5.0, pos.z * 5.0, 4);
+            base * 0.6 + craters(6.0)
+        }
+        CelestialBody::Phobos | CelestialBody::Deimos => {
+            let base = fbm(pos.x * 2.0, pos.y * 2.0, pos.z * 2.0, 4);
+            base + craters(4.0)
+        }
+        CelestialBody::Io | CelestialBody::LavaPlanet => {
+            let base = fbm(pos.x * 4.0, pos.y * 4.0, pos.z * 4.0, 5);
+            base * 0.7 + turbulence(pos.x * 2.0, pos.y * 2.0, pos.z * 2.0, 3) * 0.3
+        }
+        CelestialBody::Europa | CelestialBody::IcePlanet => {
+            // Hielo casi liso con crestas entre placas
+            let ridges = 1.0 - (fbm(pos.x * 6.0, pos.y * 6.0, pos.z * 6.0, 4) - 0.5).abs() * 4.0;
+            ridges.max(0.0) * 0.4
+        }
+        CelestialBody::AlienPlanet => fbm(pos.x * 4.0, pos.y * 4.0, pos.z * 4.0, 5),
+        CelestialBody::Procedural(planet) => {
+            let [ox, oy, oz] = planet.terrain_offset;
+            let scale = planet.terrain_scale;
+            let height = fbm(pos.x * scale + ox, pos.y * scale + oy, pos.z * scale + oz, 5);
+            let sea_level = 0.5 + (planet.ocean_level - 0.5) * 0.35;
+            ((height - sea_level) / (1.0 - sea_level).max(0.01) * 2.5).max(0.0)
+        }
+        CelestialBody::Sun
+        | CelestialBody::Star(_)
+        | CelestialBody::BlackHole
+        | CelestialBody::AccretionDisc
+        | CelestialBody::MeshMaterial
+        | CelestialBody::Station
+        | CelestialBody::Jupiter
+        | CelestialBody::Saturn
+        | CelestialBody::Ring
+        | CelestialBody::StormClouds
+        | CelestialBody::Uranus
+        | CelestialBody::Neptune => return None,
+    };
+
+    Some(height.clamp(0.0, 1.0))
+}
+
+// MATERIAL DE MALLA - color plano del material de cada cara (la nave y otros modelos con .mtl).
+// El shader recibe el primer vértice de la primitiva, así que el material es el de la cara
+pub fn mesh_material_shader(_fragment: &Fragment, vertex: &Vertex, uniforms: &Uniforms) -> Color {
+    let normal = vertex.transformed_normal.normalize();
+    light_surface(&body_material(CelestialBody::MeshMaterial), vertex.transformed_position, normal, uniforms, vertex.color)
+}
+
+// LUZ PROPIA - caras con Ke en el .mtl: su color tal cual, sin iluminar. En la estación son
+// balizas que destellan todas a la vez, encendidas una fracción corta de cada periodo
+const BEACON_PERIOD: f32 = 1.5; // segundos
+const BEACON_ON: f32 = 0.2;     // fracción del periodo encendidas
+const BEACON_OFF: f32 = 0.12;   // brillo apagadas
+
+pub fn emission_shader(body: CelestialBody, vertex: &Vertex, uniforms: &Uniforms) -> Color {
+    if body != CelestialBody::Station || (uniforms.time / BEACON_PERIOD).fract() < BEACON_ON {
+        return vertex.color;
+    }
+    vertex.color * BEACON_OFF
+}
+
 // ============= ENUM Y FUNCIÓN DE SELECCIÓN =============
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Deserialize)]
 pub enum CelestialBody {
     Sun,
+    Star(StarClass), // otra estrella según su clase espectral (el Sol es una G)
     Earth,
     Jupiter,
     Mars,
@@ -1214,6 +1577,66 @@ pub enum CelestialBody {
     LavaPlanet,
     IcePlanet,
     AlienPlanet,
+    Uranus,
+    Neptune,
+    Procedural(ProceduralPlanet),
+    Io,
+    Europa,
+    Ganymede,
+    Callisto,
+    Phobos,
+    Deimos,
+    StormClouds,
+    BlackHole,     // sombra negra; el disco y la lente se dibujan aparte (ver black_hole.rs)
+    AccretionDisc,
+    MeshMaterial,  // mallas importadas: cada cara con el color difuso de su material (ver obj.rs)
+    Station,       // como MeshMaterial, con las caras de luz propia parpadeando (balizas)
+}
+
+impl CelestialBody {
+    // Estrellas: emiten la luz de la escena en lugar de recibirla
+    pub fn is_star(self) -> bool {
+        self.star_class().is_some()
+    }
+
+    pub fn star_class(self) -> Option<StarClass> {
+        match self {
+            CelestialBody::Sun => Some(StarClass::G),
+            CelestialBody::Star(class) => Some(class),
+            _ => None,
+        }
+    }
+
+    // Nombre del shader tal como se escribe en la escena (para el panel de información)
+    pub fn name(self) -> &'static str {
+        match self {
+            CelestialBody::Sun => "Sun",
+            CelestialBody::Star(_) => "Star",
+            CelestialBody::Earth => "Earth",
+            CelestialBody::Jupiter => "Jupiter",
+            CelestialBody::Mars => "Mars",
+            CelestialBody::Saturn => "Saturn",
+            CelestialBody::Ring => "Ring",
+            CelestialBody::Moon => "Moon",
+            CelestialBody::LavaPlanet => "LavaPlanet",
+            CelestialBody::IcePlanet => "IcePlanet",
+            CelestialBody::AlienPlanet => "AlienPlanet",
+            CelestialBody::Uranus => "Uranus",
+            CelestialBody::Neptune => "Neptune",
+            CelestialBody::Procedural(_) => "Procedural",
+            CelestialBody::Io => "Io",
+            CelestialBody::Europa => "Europa",
+            CelestialBody::Ganymede => "Ganymede",
+            CelestialBody::Callisto => "Callisto",
+            CelestialBody::Phobos => "Phobos",
+            CelestialBody::Deimos => "Deimos",
+            CelestialBody::StormClouds => "StormClouds",
+            CelestialBody::BlackHole => "BlackHole",
+            CelestialBody::AccretionDisc => "AccretionDisc",
+            CelestialBody::MeshMaterial => "MeshMaterial",
+            CelestialBody::Station => "Station",
+        }
+    }
 }
 
 pub fn get_celestial_shader(
@@ -1222,8 +1645,9 @@ pub fn get_celestial_shader(
     vertex: &Vertex,
     uniforms: &Uniforms
 ) -> Color {
-    match body {
-        CelestialBody::Sun => sun_shader(fragment, vertex, uniforms.time),
+    let lit = match body {
+        CelestialBody::Sun => return sun_shader(fragment, vertex, uniforms.time, StarClass::G.temperature(), 1.0),
+        CelestialBody::Star(class) => return sun_shader(fragment, vertex, uniforms.time, class.temperature(), class.activity()),
         CelestialBody::Earth => earth_like_shader(fragment, vertex, uniforms),
         CelestialBody::Jupiter => gas_giant_shader(fragment, vertex, uniforms),
         CelestialBody::Mars => mars_like_shader(fragment, vertex, uniforms),
@@ -1233,5 +1657,23 @@ pub fn get_celestial_shader(
         CelestialBody::LavaPlanet => lava_planet_shader(fragment, vertex, uniforms),
         CelestialBody::IcePlanet => ice_planet_shader(fragment, vertex, uniforms),
         CelestialBody::AlienPlanet => alien_planet_shader(fragment, vertex, uniforms),
-    }
+        CelestialBody::Uranus => uranus_shader(fragment, vertex, uniforms),
+        CelestialBody::Neptune => neptune_shader(fragment, vertex, uniforms),
+        CelestialBody::Procedural(planet) => procedural_planet_shader(fragment, vertex, uniforms, &planet),
+        CelestialBody::Io => io_shader(fragment, vertex, uniforms),
+        CelestialBody::Europa => europa_shader(fragment, vertex, uniforms),
+        CelestialBody::Ganymede => ganymede_shader(fragment, vertex, uniforms),
+        CelestialBody::Callisto => callisto_shader(fragment, vertex, uniforms),
+        CelestialBody::Phobos => phobos_shader(fragment, vertex, uniforms),
+        CelestialBody::Deimos => deimos_shader(fragment, vertex, uniforms),
+        CelestialBody::StormClouds => storm_cloud_shader(fragment, vertex, uniforms),
+        // Ni la sombra ni el disco reciben la luz de la estrella
+        CelestialBody::BlackHole => return Color::black(),
+        CelestialBody::AccretionDisc => return accretion_disc_shader(fragment, vertex, uniforms),
+        // La luz propia no depende de la distancia al Sol
+        CelestialBody::MeshMaterial | CelestialBody::Station if vertex.emissive => return emission_shader(body, vertex, uniforms),
+        CelestialBody::MeshMaterial | CelestialBody::Station => mesh_material_shader(fragment, vertex, uniforms),
+    };
+    // Atenuación con la distancia al Sol (ver lighting.rs); el Sol emite su propia luz
+    lit * uniforms.light_intensity
 }
@@ -0,0 +1,125 @@
+use clap::{Parser, ValueEnum};
+use crate::watchdog::Quality;
+
+// ============= LÍNEA DE COMANDOS =============
+// Todo lo que antes eran constantes de `main()` (tamaño de ventana, supersampling, calidad)
+// y los modos de arranque (escena, lección, sistema aleatorio, benchmark...) se eligen al
+// lanzar el programa, sin recompilar. `--help` lista las opciones.
+
+#[derive(Parser)]
+#[command(name = "spaceship", version, about = "Sistema solar con rasterizador por software")]
+pub struct Cli {
+    /// Escena TOML (como --scene; se mantiene por compatibilidad)
+    #[arg(value_name = "ESCENA")]
+    pub scene_file: Option<String>,
+
+    /// Escena TOML con los cuerpos, órbitas y metadatos
+    #[arg(long, value_name = "ARCHIVO")]
+    pub scene: Option<String>,
+
+    /// Ancho de la ventana en píxeles
+    #[arg(long, default_value_t = 1200, value_parser = clap::value_parser!(u32).range(64..))]
+    pub width: u32,
+
+    /// Alto de la ventana en píxeles
+    #[arg(long, default_value_t = 800, value_parser = clap::value_parser!(u32).range(64..))]
+    pub height: u32,
+
+    /// Calidad inicial (sin ella se elige con el historial de rendimiento de esta máquina)
+    #[arg(long, value_enum)]
+    pub quality: Option<QualityLevel>,
+
+    /// Factor de supersampling fijo (sin él la escala sigue al regulador del tiempo de frame, target_fps en [render])
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=4))]
+    pub supersample: Option<u32>,
+
+    /// Sin ventana: recorre el benchmark y escribe el informe (requiere --bench)
+    #[arg(long, requires = "bench")]
+    pub headless: bool,
+
+    /// Semilla de lo aleatorio de la sesión (planetas con P, partículas) para repetirla igual
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Lección guiada TOML
+    #[arg(long, value_name = "ARCHIVO")]
+    pub lesson: Option<String>,
+
+    /// Genera un sistema estelar aleatorio a partir de la semilla
+    #[arg(long, value_name = "SEMILLA")]
+    pub random_system: Option<u64>,
+
+    /// Narración de la visita: archivo de texto o '-' para la salida estándar
+    #[arg(long, value_name = "ARCHIVO|-")]
+    pub narrate: Option<String>,
+
+    /// Fecha de las posiciones de los planetas (AAAA-MM-DD)
+    #[arg(long, value_name = "AAAA-MM-DD")]
+    pub date: Option<String>,
+
+    /// Importa un sistema real de un catálogo CSV de exoplanetas
+    #[arg(long, value_name = "CATALOGO.CSV")]
+    pub exoplanets: Option<String>,
+
+    /// Sistema del catálogo de exoplanetas (por defecto el primero)
+    #[arg(long, value_name = "NOMBRE", requires = "exoplanets")]
+    pub system: Option<String>,
+
+    /// Exporta los tiempos por etapa a un CSV
+    #[arg(long, value_name = "TIEMPOS.CSV")]
+    pub profile: Option<String>,
+
+    /// Archivo de ajustes (por defecto settings.toml)
+    #[arg(long, value_name = "ARCHIVO")]
+    pub settings: Option<String>,
+
+    /// Parámetros de los shaders por cuerpo (por defecto shader_params.toml); se recarga al guardarlo
+    #[arg(long, value_name = "ARCHIVO")]
+    pub params: Option<String>,
+
+    /// Benchmark de N frames con un camino de cámara fijo
+    #[arg(long, value_name = "FRAMES")]
+    pub bench: Option<usize>,
+
+    /// Informe JSON del benchmark
+    #[arg(long, value_name = "INFORME.JSON", requires = "bench")]
+    pub bench_out: Option<String>,
+
+    /// Graba la entrada de cada frame para repetirla con --replay
+    #[arg(long, value_name = "ARCHIVO")]
+    pub record: Option<String>,
+
+    /// Repite una entrada grabada con --record (después los controles vuelven a la ventana)
+    #[arg(long, value_name = "ARCHIVO", conflicts_with = "bench")]
+    pub replay: Option<String>,
+
+    /// Autoprueba sin ventana: rasterizador, shaders y capacidades de la máquina
+    #[arg(long)]
+    pub selftest: bool,
+}
+
+impl Cli {
+    // --scene tiene prioridad sobre la escena posicional
+    pub fn scene_path(&self) -> &str {
+        self.scene.as_deref().or(self.scene_file.as_deref()).unwrap_or("scenes/solar_system.toml")
+    }
+}
+
+// Niveles de --quality (la calidad mínima la reserva el vigilante de frames lentos)
+#[derive(Clone, Copy, ValueEnum)]
+pub enum QualityLevel {
+    Low,
+    #[value(alias = "medium")]
+    Med,
+    High,
+}
+
+impl From<QualityLevel> for Quality {
+    fn from(level: QualityLevel) -> Self {
+        match level {
+            QualityLevel::Low => Quality::Low,
+            QualityLevel::Med => Quality::Medium,
+            QualityLevel::High => Quality::High,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use nalgebra_glm::{Mat4, Vec3};
+use crate::framebuffer::Framebuffer;
+use crate::free_flight::FreeFlight;
+use crate::line::{draw_line, LineStyle};
+use crate::picking::screen_disk;
+use crate::text::{draw_styled, text_width, Align, TextStyle, GLYPH_SIZE};
+use crate::theme;
+use crate::{project_to_screen, CelestialObject};
+
+// ============= HUD DE LA CABINA =============
+// Lo que ve el piloto en la vista de cabina del vuelo libre (V), dibujado sobre la escena:
+// - Retícula en el centro: hacia donde apunta la nave.
+// - Vector de velocidad: el punto del cielo hacia el que se mueve la nave (un círculo con
+//   alas, como en los aviones); solo mientras se mueve.
+// - Corchetes alrededor del cuerpo seleccionado con su nombre y la distancia a su superficie.
+//   Si queda fuera de la vista, una flecha en el borde indica hacia dónde girar.
+
+const RETICLE_GAP: f32 = 5.0;
+const RETICLE_SIZE: f32 = 14.0;
+const MARKER_RADIUS: f32 = 7.0;
+const MIN_BRACKET: f32 = 14.0; // medio lado mínimo de los corchetes, en píxeles
+const EDGE_MARGIN: f32 = 28.0;
+// Distancia a la que se proyecta la dirección de la velocidad (mucho más lejos que el
+// cuerpo más cercano, así el marcador no se mueve con la posición de la nave)
+const MARKER_DISTANCE: f32 = 100_000.0;
+
+pub fn draw_hud(
+    screen: &mut Framebuffer,
+    ship: &FreeFlight,
+    target: Option<&CelestialObject>,
+    view_matrix: &Mat4,
+    projection_matrix: &Mat4,
+    viewport_matrix: &Mat4,
+) {
+    let theme = theme::current();
+    let style = if theme.smooth_lines { LineStyle::AntiAliased } else { LineStyle::Aliased };
+    let center = (screen.width as f32 / 2.0, screen.height as f32 / 2.0);
+
+    // Retícula: cuatro trazos con un hueco en el centro
+    for (dx, dy) in [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)] {
+        let from = (center.0 + dx * RETICLE_GAP, center.1 + dy * RETICLE_GAP);
+        let to = (center.0 + dx * RETICLE_SIZE, center.1 + dy * RETICLE_SIZE);
+        draw_line(screen, from, to, theme.accent, 0.9, style);
+    }
+
+    // Vector de velocidad
+    if let Some(direction) = ship.velocity().try_normalize(1.0e-6) {
+        let far = ship.position() + direction * MARKER_DISTANCE;
+        if let Some(marker) = project_to_screen(far, view_matrix, projection_matrix, viewport_matrix) {
+            draw_circle(screen, (marker.x, marker.y), MARKER_RADIUS, theme.accent, style);
+            let wing = MARKER_RADIUS * 2.0;
+            draw_line(screen, (marker.x - MARKER_RADIUS, marker.y), (marker.x - wing, marker.y), theme.accent, 0.9, style);
+            draw_line(screen, (marker.x + MARKER_RADIUS, marker.y), (marker.x + wing, marker.y), theme.accent, 0.9, style);
+            draw_line(screen, (marker.x, marker.y - MARKER_RADIUS), (marker.x, marker.y - MARKER_RADIUS * 1.6), theme.accent, 0.9, style);
+        }
+    }
+
+    let Some(target) = target else {
+        return;
+    };
+    let distance = ((target.transform.translation - ship.position()).magnitude() - target.transform.scale).max(0.0);
+    let label = format!("{}  {:.0} u", target.display_name(), distance);
+    let text_style = TextStyle::new(theme.label_selected).with_outline(theme.label_outline);
+
+    let on_screen = screen_disk(target, view_matrix, projection_matrix, viewport_matrix)
+        .filter(|(disk, _)| disk.x >= 0.0 && disk.y >= 0.0 && disk.x < screen.width as f32 && disk.y < screen.height as f32);
+    match on_screen {
+        Some((disk, radius)) => {
+            // Corchetes en las cuatro esquinas del cuadrado que rodea el disco
+            let half = (radius + 6.0).max(MIN_BRACKET);
+            let arm = half * 0.4;
+            for (sx, sy) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+                let corner = (disk.x + sx * half, disk.y + sy * half);
+                draw_line(screen, corner, (corner.0 - sx * arm, corner.1), theme.label_selected, 1.0, style);
+                draw_line(screen, corner, (corner.0, corner.1 - sy * arm), theme.label_selected, 1.0, style);
+            }
+            draw_styled(screen, disk.x as i32, (disk.y + half) as i32 + 6, &label, &text_style.with_align(Align::Center));
+        }
+        None => draw_edge_arrow(screen, target.transform.translation, &label, theme.label_selected, view_matrix),
+    }
+}
+
+// Flecha en el borde de la pantalla hacia un punto fuera de la vista (o detrás), en su
+// dirección vista desde la cámara sobre el plano de la pantalla, con su etiqueta
+pub fn draw_edge_arrow(screen: &mut Framebuffer, point: Vec3, label: &str, color: u32, view_matrix: &Mat4) {
+    let theme = theme::current();
+    let style = if theme.smooth_lines { LineStyle::AntiAliased } else { LineStyle::Aliased };
+    let center = (screen.width as f32 / 2.0, screen.height as f32 / 2.0);
+    let text_style = TextStyle::new(color).with_outline(theme.label_outline);
+
+    let relative = view_matrix * point.push(1.0);
+    let Some(direction) = nalgebra_glm::vec2(relative.x, -relative.y).try_normalize(1.0e-6) else {
+        return;
+    };
+    let reach = (center.0 - EDGE_MARGIN) / direction.x.abs().max(1.0e-6);
+    let reach = reach.min((center.1 - EDGE_MARGIN) / direction.y.abs().max(1.0e-6));
+    let tip = (center.0 + direction.x * reach, center.1 + direction.y * reach);
+    let back = (tip.0 - direction.x * 12.0, tip.1 - direction.y * 12.0);
+    let side = (-direction.y * 7.0, direction.x * 7.0);
+    draw_line(screen, tip, (back.0 + side.0, back.1 + side.1), color, 1.0, style);
+    draw_line(screen, tip, (back.0 - side.0, back.1 - side.1), color, 1.0, style);
+    let x = (back.0 - text_width(label, 1) as f32 / 2.0).min(screen.width as f32 - text_width(label, 1) as f32 - 4.0).max(4.0);
+    let y = (back.1 - direction.y * GLYPH_SIZE as f32 * 1.5).min(screen.height as f32 - GLYPH_SIZE as f32 - 4.0).max(4.0);
+    draw_styled(screen, x as i32, y as i32, label, &text_style);
+}
+
+fn draw_circle(screen: &mut Framebuffer, center: (f32, f32), radius: f32, color: u32, style: LineStyle) {
+    let segments = 24;
+    let point = |i: usize| {
+        let angle = i as f32 / segments as f32 * std::f32::consts::TAU;
+        (center.0 + angle.cos() * radius, center.1 + angle.sin() * radius)
+    };
+    for i in 0..segments {
+        draw_line(screen, point(i), point(i + 1), color, 0.9, style);
+    }
+}
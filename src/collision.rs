@@ -0,0 +1,192 @@
+use nalgebra_glm::Vec3;
+use crate::celestial_shaders::CelestialBody;
+use crate::flight_model::ShipBody;
+use crate::particles::{Emission, EmitterConfig, ParticleSystem};
+use crate::CelestialObject;
+
+// ============= COLISIONES DE LA NAVE =============
+// En vuelo libre la nave es una esfera y cada cuerpo (planetas, lunas, asteroides...) otra,
+// con el radio de su escala (las mallas se normalizan a radio 1). En cada frame se lanza un
+// rayo desde donde estaba la nave hasta donde ha llegado contra las esferas de los cuerpos
+// agrandadas con el radio de la nave (esfera contra rayo): así no atraviesa un asteroide
+// aunque en un frame avance más que su tamaño. Si un cuerpo se le echa encima estando
+// quieta, la nave ya empieza dentro de su esfera (esfera contra esfera) y se la saca fuera.
+// Un choque suave rebota; uno fuerte, o contra una estrella o un agujero negro, destruye la
+// nave, que explota y reaparece en el punto de despegue.
+
+pub const CRASH_SPEED: f32 = 60.0; // unidades/s de acercamiento a partir de las que la nave explota
+const RESTITUTION: f32 = 0.5;      // fracción de la velocidad de choque que conserva el rebote
+const SKIN: f32 = 0.05;            // margen con la superficie tras el rebote
+
+const EXPLOSION_RAMP: [(f32, u32); 5] = [(0.0, 0xFFFFE0), (0.1, 0xFFD060), (0.35, 0xFF6A20), (0.7, 0x602010), (1.0, 0x000000)];
+const BURST_TIME: f32 = 0.05; // segundos de chispas que se sueltan de golpe
+
+// Bola de fuego de la nave: chispas en todas direcciones que se apagan en un par de segundos
+pub const EXPLOSION: EmitterConfig = EmitterConfig {
+    spawn_rate: 8000.0, // × BURST_TIME = 400 chispas por explosión
+    max_particles: 1024,
+    lifetime: (0.6, 1.8),
+    speed: (8.0, 45.0),
+    spread: std::f32::consts::PI,
+    size: (1.5, 4.0),
+    colors: &EXPLOSION_RAMP,
+    max_pixels: 40.0,
+};
+
+// Esfera de un cuerpo en este frame
+#[derive(Clone, Copy, Debug)]
+pub struct Collider {
+    pub center: Vec3,
+    pub radius: f32,
+    pub velocity: Vec3, // del cuerpo (su órbita), unidades/s
+    pub fatal: bool,    // estrellas y agujeros negros: cualquier contacto destruye la nave
+}
+
+impl Collider {
+    // El movimiento del cuerpo sale de su matriz del frame anterior. Un salto mayor que el
+    // propio cuerpo (al mover el reloj o cambiar de escena) no es movimiento y no cuenta
+    pub fn of(obj: &CelestialObject, dt: f32) -> Self {
+        let previous = obj.transform.previous_model_matrix().column(3).xyz();
+        let displacement = obj.transform.translation - previous;
+        let velocity = if displacement.magnitude() < obj.transform.scale { displacement / dt } else { Vec3::zeros() };
+        Collider {
+            center: obj.transform.translation,
+            radius: obj.transform.scale,
+            velocity,
+            fatal: obj.shader.body.is_star() || obj.shader.body == CelestialBody::BlackHole,
+        }
+    }
+}
+
+// Primer punto de contacto de una esfera de radio `radius` que va de `from` a `to`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+    pub time: f32,    // fracción del recorrido (0 si ya empezaba dentro)
+    pub normal: Vec3, // del cuerpo hacia la nave
+}
+
+// ¿Se tocan dos esferas?
+pub fn spheres_overlap(a: Vec3, a_radius: f32, b: Vec3, b_radius: f32) -> bool {
+    (a - b).magnitude_squared() < (a_radius + b_radius) * (a_radius + b_radius)
+}
+
+// Primera fracción t de [0, 1] en la que el rayo `origin + t·direction` entra en la esfera
+pub fn ray_sphere(origin: Vec3, direction: Vec3, center: Vec3, radius: f32) -> Option<f32> {
+    let offset = origin - center;
+    let a = direction.magnitude_squared();
+    let b = offset.dot(&direction);
+    let c = offset.magnitude_squared() - radius * radius;
+    if a <= 0.0 || b > 0.0 {
+        return None; // quieta o alejándose
+    }
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / a;
+    (0.0..=1.0).contains(&t).then_some(t)
+}
+
+// Contacto de la nave (esfera de radio `radius` de `from` a `to`) con un cuerpo
+pub fn sweep(from: Vec3, to: Vec3, radius: f32, collider: &Collider) -> Option<Contact> {
+    let reach = radius + collider.radius;
+    let contact = |time: f32, point: Vec3| {
+        let normal = (point - collider.center).try_normalize(1.0e-6).unwrap_or_else(Vec3::y);
+        Contact { time, normal }
+    };
+    if spheres_overlap(from, radius, collider.center, collider.radius) {
+        return Some(contact(0.0, from));
+    }
+    ray_sphere(from, to - from, collider.center, reach).map(|time| contact(time, from + (to - from) * time))
+}
+
+// Qué pasó al chocar
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Impact {
+    Bounce,
+    Crash { position: Vec3, velocity: Vec3 }, // dónde explota y con qué velocidad salen las chispas
+}
+
+// Choca la nave, que acaba de moverse desde `from`, con el primer cuerpo que encuentre. En un
+// rebote la deja sobre la superficie con la velocidad reflejada; en un choque fuerte no la toca
+pub fn resolve(ship: &mut ShipBody, from: Vec3, radius: f32, colliders: &[Collider]) -> Option<Impact> {
+    let (collider, contact) = colliders
+        .iter()
+        .filter_map(|collider| sweep(from, ship.position, radius, collider).map(|contact| (collider, contact)))
+        .min_by(|(_, a), (_, b)| a.time.total_cmp(&b.time))?;
+
+    // Velocidad respecto al cuerpo: lo que importa es cómo se acercan
+    let relative = ship.velocity - collider.velocity;
+    let approach = -relative.dot(&contact.normal);
+    if collider.fatal || approach > CRASH_SPEED {
+        let position = from + (ship.position - from) * contact.time;
+        return Some(Impact::Crash { position, velocity: collider.velocity });
+    }
+    ship.position = collider.center + contact.normal * (collider.radius + radius + SKIN);
+    if approach > 0.0 {
+        ship.velocity = collider.velocity + relative + contact.normal * approach * (1.0 + RESTITUTION);
+    }
+    Some(Impact::Bounce)
+}
+
+// Suelta de golpe la explosión de la nave
+pub fn explode(explosions: &mut ParticleSystem, position: Vec3, velocity: Vec3) {
+    explosions.emit(BURST_TIME, 1.0, |_| Emission {
+        position,
+        direction: Vec3::y(),
+        velocity,
+        speed_scale: 1.0,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rock(center: Vec3, radius: f32) -> Collider {
+        Collider { center, radius, velocity: Vec3::zeros(), fatal: false }
+    }
+
+    fn ship_at(position: Vec3, velocity: Vec3) -> ShipBody {
+        ShipBody { velocity, ..ShipBody::new(position, nalgebra_glm::quat_identity()) }
+    }
+
+    #[test]
+    fn fast_ships_do_not_tunnel_through_small_bodies() {
+        // En un frame la nave salta de un lado al otro de la roca sin tocarla en los extremos
+        let collider = rock(Vec3::zeros(), 3.0);
+        let contact = sweep(Vec3::new(-20.0, 0.0, 0.0), Vec3::new(20.0, 0.0, 0.0), 2.0, &collider).unwrap();
+        assert!((contact.time - 15.0 / 40.0).abs() < 1.0e-5);
+        assert!((contact.normal - Vec3::new(-1.0, 0.0, 0.0)).magnitude() < 1.0e-5);
+
+        // Pasando de largo o alejándose no hay contacto
+        assert_eq!(sweep(Vec3::new(-20.0, 6.0, 0.0), Vec3::new(20.0, 6.0, 0.0), 2.0, &collider), None);
+        assert_eq!(sweep(Vec3::new(6.0, 0.0, 0.0), Vec3::new(20.0, 0.0, 0.0), 2.0, &collider), None);
+        // Empezando dentro, contacto inmediato
+        assert_eq!(sweep(Vec3::new(4.0, 0.0, 0.0), Vec3::new(4.0, 0.0, 0.0), 2.0, &collider).map(|contact| contact.time), Some(0.0));
+    }
+
+    #[test]
+    fn gentle_hits_bounce_and_hard_hits_crash() {
+        let colliders = [rock(Vec3::new(0.0, 0.0, -20.0), 10.0)];
+
+        // Despacio: rebota fuera del cuerpo alejándose de él
+        let mut ship = ship_at(Vec3::new(0.0, 0.0, -8.5), Vec3::new(0.0, 0.0, -20.0));
+        assert_eq!(resolve(&mut ship, Vec3::new(0.0, 0.0, -7.0), 2.0, &colliders), Some(Impact::Bounce));
+        assert!(ship.position.z > -8.0);
+        assert!((ship.velocity.z - 10.0).abs() < 1.0e-4, "{:?}", ship.velocity);
+
+        // Deprisa: explota en el punto de contacto y la nave no se mueve
+        let mut ship = ship_at(Vec3::new(0.0, 0.0, -9.0), Vec3::new(0.0, 0.0, -200.0));
+        let Some(Impact::Crash { position, .. }) = resolve(&mut ship, Vec3::new(0.0, 0.0, -4.0), 2.0, &colliders) else {
+            panic!("tenía que explotar");
+        };
+        assert!((position - Vec3::new(0.0, 0.0, -8.0)).magnitude() < 1.0e-4);
+        assert_eq!(ship.position, Vec3::new(0.0, 0.0, -9.0));
+
+        // Contra una estrella cualquier contacto es fatal
+        let star = [Collider { fatal: true, ..colliders[0] }];
+        let mut ship = ship_at(Vec3::new(0.0, 0.0, -8.5), Vec3::new(0.0, 0.0, -1.0));
+        assert!(matches!(resolve(&mut ship, Vec3::new(0.0, 0.0, -7.0), 2.0, &star), Some(Impact::Crash { .. })));
+    }
+}
@@ -1,29 +1,41 @@
 use std::fmt;
+use serde::Deserialize;
 
-#[derive(Debug, Clone, Copy)]
+// Los canales se guardan como f32 lineales de 0.0 a 1.0 y sin tope por arriba (HDR): la suma
+// de varias luces o un brillo multiplicado por 2.5 no se recortan ni dan la vuelta a mitad
+// del shader. Solo se cuantizan a 8 bits al escribir en el framebuffer (to_hex).
+// En los archivos TOML un color se escribe como [r, g, b] con valores de 0.0 a 1.0
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(from = "[f32; 3]")]
 pub struct Color {
-  r: u8,
-  g: u8,
-  b: u8,
+  r: f32,
+  g: f32,
+  b: f32,
+}
+
+// Canal HDR a 8 bits (el brillo por encima de 1.0 se recorta aquí y en ningún otro sitio)
+fn quantize(channel: f32) -> u8 {
+  (channel.clamp(0.0, 1.0) * 255.0).round() as u8
 }
 
 impl Color {
   // Constructor to initialize the color using r, g, b values as u8
   pub fn new(r: u8, g: u8, b: u8) -> Self {
-    Color { r, g, b }
+    Color::from_float(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
   }
 
   // default color
   pub fn black() -> Self {
-    Color { r: 0, g: 0, b: 0 }
+    Color { r: 0.0, g: 0.0, b: 0.0 }
   }
 
-  // New constructor to initialize the color using r, g, b values as f32 (0.0 to 1.0)
+  // Color a partir de canales f32 (1.0 es el blanco de la pantalla; se admite más brillo,
+  // pero no valores negativos)
   pub fn from_float(r: f32, g: f32, b: f32) -> Self {
     Color {
-      r: (r.clamp(0.0, 1.0) * 255.0) as u8,
-      g: (g.clamp(0.0, 1.0) * 255.0) as u8,
-      b: (b.clamp(0.0, 1.0) * 255.0) as u8,
+      r: r.max(0.0),
+      g: g.max(0.0),
+      b: b.max(0.0),
     }
   }
 
@@ -32,27 +44,29 @@ impl Color {
     let r = ((hex >> 16) & 0xFF) as u8;
     let g = ((hex >> 8) & 0xFF) as u8;
     let b = (hex & 0xFF) as u8;
-    Color { r, g, b }
+    Color::new(r, g, b)
   }
 
-  // Function to return the color as a hex value
+  // Valor hexadecimal para el framebuffer: aquí se cuantiza cada canal
   pub fn to_hex(&self) -> u32 {
-    ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
+    ((quantize(self.r) as u32) << 16) | ((quantize(self.g) as u32) << 8) | (quantize(self.b) as u32)
   }
 
-  // Convert to float tuple
+  // Canales sin recortar (pueden pasar de 1.0)
   pub fn to_float(&self) -> (f32, f32, f32) {
-    (
-      self.r as f32 / 255.0,
-      self.g as f32 / 255.0,
-      self.b as f32 / 255.0,
-    )
+    (self.r, self.g, self.b)
   }
 
-  // Getter methods
-  pub fn r(&self) -> u8 { self.r }
-  pub fn g(&self) -> u8 { self.g }
-  pub fn b(&self) -> u8 { self.b }
+  // Getter methods (ya cuantizados)
+  pub fn r(&self) -> u8 { quantize(self.r) }
+  pub fn g(&self) -> u8 { quantize(self.g) }
+  pub fn b(&self) -> u8 { quantize(self.b) }
+}
+
+impl From<[f32; 3]> for Color {
+  fn from([r, g, b]: [f32; 3]) -> Self {
+    Color::from_float(r, g, b)
+  }
 }
 
 // Implement addition for Color
@@ -63,31 +77,97 @@ impl Add for Color {
 
   fn add(self, other: Color) -> Color {
     Color {
-      r: self.r.saturating_add(other.r),
-      g: self.g.saturating_add(other.g),
-      b: self.b.saturating_add(other.b),
+      r: self.r + other.r,
+      g: self.g + other.g,
+      b: self.b + other.b,
     }
   }
 }
 
-// Implement multiplication by a constant for Color
+// Implement multiplication by a constant for Color (un factor negativo da negro)
 use std::ops::Mul;
 
 impl Mul<f32> for Color {
   type Output = Color;
 
   fn mul(self, scalar: f32) -> Color {
-    Color {
-      r: (self.r as f32 * scalar).clamp(0.0, 255.0) as u8,
-      g: (self.g as f32 * scalar).clamp(0.0, 255.0) as u8,
-      b: (self.b as f32 * scalar).clamp(0.0, 255.0) as u8,
-    }
+    Color::from_float(self.r * scalar, self.g * scalar, self.b * scalar)
   }
 }
 
 // Implement display formatting for Color
 impl fmt::Display for Color {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "Color(r: {}, g: {}, b: {})", self.r, self.g, self.b)
+    write!(f, "Color(r: {:.3}, g: {:.3}, b: {:.3})", self.r, self.g, self.b)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::procedural::SeededRng;
+
+  fn random_color(rng: &mut SeededRng) -> Color {
+    Color::from_hex((rng.next_u64() & 0xFF_FFFF) as u32)
+  }
+
+  #[test]
+  fn addition_keeps_hdr_and_saturates_on_write() {
+    let mut rng = SeededRng::new(0xADD5);
+    for _ in 0..500 {
+      let (a, b) = (random_color(&mut rng), random_color(&mut rng));
+      let sum = a + b;
+      assert_eq!(sum.r(), (a.r() as u16 + b.r() as u16).min(255) as u8);
+      assert_eq!(sum.g(), (a.g() as u16 + b.g() as u16).min(255) as u8);
+      assert_eq!(sum.b(), (a.b() as u16 + b.b() as u16).min(255) as u8);
+      // Lo que pasa de 1.0 sigue ahí hasta cuantizar: la media de dos colores claros no se oscurece
+      let mean = (a + b) * 0.5;
+      for (mean, a, b) in [(mean.r(), a.r(), b.r()), (mean.g(), a.g(), b.g()), (mean.b(), a.b(), b.b())] {
+        assert!((mean as i32 * 2 - (a as i32 + b as i32)).abs() <= 1);
+      }
+    }
+    assert_eq!((Color::new(200, 255, 0) + Color::new(100, 1, 0)).to_hex(), 0xFFFF00);
+  }
+
+  #[test]
+  fn scaling_keeps_brightness_above_white() {
+    let mut rng = SeededRng::new(0x5CA1);
+    for _ in 0..500 {
+      let color = random_color(&mut rng);
+      assert_eq!((color * 1.0).to_hex(), color.to_hex());
+      assert_eq!((color * 0.0).to_hex(), 0);
+      assert_eq!((color * -3.0).to_hex(), 0);
+      let bright = color * 1000.0;
+      for (channel, scaled) in [(color.r(), bright.r()), (color.g(), bright.g()), (color.b(), bright.b())] {
+        assert_eq!(scaled, if channel == 0 { 0 } else { 255 });
+      }
+      // Un brillo de 2.5 seguido de una atenuación no pierde el color por el camino
+      assert_eq!((color * 2.5 * 0.4).to_hex(), color.to_hex());
+    }
+  }
+
+  #[test]
+  fn from_float_rejects_negative_values() {
+    assert_eq!(Color::from_float(-0.5, 2.0, 1.0).to_hex(), 0x00FFFF);
+    assert_eq!(Color::from_float(-0.5, 2.0, 1.0).to_float(), (0.0, 2.0, 1.0));
+    assert_eq!(Color::from_float(0.0, 0.0, 0.0).to_hex(), 0);
+    let mut rng = SeededRng::new(0xF10A);
+    for _ in 0..500 {
+      let (r, g, b) = (rng.next_f32(), rng.next_f32(), rng.next_f32());
+      let color = Color::from_float(r, g, b);
+      // Se cuantiza al paso de 8 bits más cercano
+      for (original, stored) in [(r, color.r()), (g, color.g()), (b, color.b())] {
+        assert!((stored as f32 / 255.0 - original).abs() <= 0.5 / 255.0 + 1e-6);
+      }
+    }
+  }
+
+  #[test]
+  fn hex_round_trips() {
+    let mut rng = SeededRng::new(0x4E70);
+    for _ in 0..500 {
+      let hex = (rng.next_u64() & 0xFF_FFFF) as u32;
+      assert_eq!(Color::from_hex(hex).to_hex(), hex);
+    }
   }
 }
@@ -0,0 +1,214 @@
+use std::f32::consts::{PI, TAU};
+use nalgebra_glm::{Mat4, Vec3};
+use crate::billboard::{draw_billboards, Billboard, SpriteCamera, GLOW_STATE};
+use crate::framebuffer::Framebuffer;
+use crate::particles::{Emission, EmitterConfig, ParticleSystem};
+use crate::scene::CometConfig;
+
+// ============= COMETAS =============
+// Órbita kepleriana elíptica con foco en el Sol: posición y velocidad salen de la ecuación
+// de Kepler en cada frame. Las dos colas se separan como en los cometas reales:
+// - Cola de iones (gas ionizado arrastrado por el viento solar): recta, azulada y
+//   estrictamente en dirección opuesta al Sol.
+// - Cola de polvo (granos empujados por la presión de radiación): amarillenta, ancha y
+//   curvada a lo largo de la órbita, porque cada grano se queda donde lo soltó el núcleo y
+//   desde ahí la radiación lo aleja del Sol mientras el núcleo sigue su camino.
+// Ambas crecen al acercarse al perihelio (actividad ~ 1/r²). Las colas son sistemas de
+// partículas (ver particles.rs) que avanzan con el tiempo de la simulación.
+
+const COMA_COLOR: u32 = 0xD8ECFF;
+const ION_RAMP: [(f32, u32); 3] = [(0.0, 0x7FB2FF), (0.6, 0x3A5A90), (1.0, 0x000000)];
+const DUST_RAMP: [(f32, u32); 3] = [(0.0, 0xFFE2A8), (0.5, 0x8C7450), (1.0, 0x000000)];
+const ION_LIFETIME: f32 = 1.0;   // segundos que tarda el gas en recorrer toda la cola
+const DUST_LAG: f32 = 0.008;     // antigüedad del grano más viejo (fracción del periodo)
+const DUST_PUSH: f32 = 0.9;      // empuje de la radiación frente al del viento solar
+const MIN_ACTIVITY: f32 = 0.02;  // por debajo (cerca del afelio) no se emiten colas
+const MAX_STEP: f32 = 0.5;       // un salto mayor en el tiempo reconstruye las colas
+const PREWARM_STEP: f32 = 0.016;
+
+// Tamaños en múltiplos de la escala del núcleo. La rapidez de los iones es una fracción de
+// la longitud de la cola por ION_LIFETIME; la del polvo, múltiplos de la escala
+const ION_TAIL: EmitterConfig = EmitterConfig {
+    spawn_rate: 420.0,
+    max_particles: 1024,
+    lifetime: (0.6 * ION_LIFETIME, ION_LIFETIME),
+    speed: (0.85, 1.0),
+    spread: 0.015,
+    size: (1.4, 4.0),
+    colors: &ION_RAMP,
+    max_pixels: 60.0,
+};
+
+const DUST_TAIL: EmitterConfig = EmitterConfig {
+    spawn_rate: 300.0,
+    max_particles: 2048,
+    lifetime: (0.5, 1.0), // fracciones de DUST_LAG * periodo
+    speed: (0.0, 0.8),
+    spread: PI,
+    size: (1.5, 7.5),
+    colors: &DUST_RAMP,
+    max_pixels: 60.0,
+};
+
+pub struct Comet {
+    pub name: String,
+    pub scale: f32,
+    perihelion: f32,
+    semi_major_axis: f32,
+    eccentricity: f32,
+    period: f32,
+    phase: f32,
+    inclination: f32,          // radianes
+    perihelion_longitude: f32, // radianes, en el plano de la escena
+    tail_length: f32,
+}
+
+// Estado físico en un instante (mundo)
+pub struct CometState {
+    pub position: Vec3,
+    pub activity: f32,       // 1 en el perihelio, cae con 1/r²
+}
+
+impl Comet {
+    pub fn from_config(config: &CometConfig) -> Self {
+        Comet {
+            name: config.name.clone(),
+            scale: config.scale,
+            perihelion: config.perihelion,
+            semi_major_axis: (config.perihelion + config.aphelion) / 2.0,
+            eccentricity: (config.aphelion - config.perihelion) / (config.aphelion + config.perihelion),
+            period: config.period,
+            phase: config.phase,
+            inclination: config.inclination.to_radians(),
+            perihelion_longitude: config.perihelion_longitude.to_radians(),
+            tail_length: config.tail_length,
+        }
+    }
+
+    // Posición relativa al Sol en un instante (ecuación de Kepler resuelta por Newton)
+    fn offset_from_sun(&self, time: f32) -> Vec3 {
+        let e = self.eccentricity;
+        let mean_anomaly = (TAU * (time / self.period + self.phase)).rem_euclid(TAU);
+        let mut eccentric = if e > 0.8 { std::f32::consts::PI } else { mean_anomaly };
+        for _ in 0..12 {
+            eccentric -= (eccentric - e * eccentric.sin() - mean_anomaly) / (1.0 - e * eccentric.cos());
+        }
+
+        // Plano de la órbita (perihelio sobre +x), inclinado y girado hacia su longitud
+        let x = self.semi_major_axis * (eccentric.cos() - e);
+        let z = self.semi_major_axis * (1.0 - e * e).sqrt() * eccentric.sin();
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        let (y, z) = (z * sin_i, z * cos_i);
+        let (sin_l, cos_l) = self.perihelion_longitude.sin_cos();
+        Vec3::new(x * cos_l - z * sin_l, y, x * sin_l + z * cos_l)
+    }
+
+    // Siguiente paso por el perihelio a partir de `time`
+    pub fn next_perihelion(&self, time: f32) -> f32 {
+        ((time / self.period + self.phase).ceil() - self.phase) * self.period
+    }
+
+    pub fn state(&self, time: f32, sun: Vec3) -> CometState {
+        let offset = self.offset_from_sun(time);
+        CometState {
+            position: sun + offset,
+            activity: (self.perihelion / offset.magnitude()).powi(2).min(1.0),
+        }
+    }
+
+    // El núcleo gira despacio sobre sí mismo
+    pub fn model_matrix(&self, state: &CometState, time: f32) -> Mat4 {
+        crate::math::create_model_matrix(state.position, self.scale, Vec3::new(0.3, time * 0.4, 0.0))
+    }
+
+    // Coma: halo de luz alrededor del núcleo con prueba de profundidad (los planetas lo tapan)
+    pub fn draw_coma(&self, framebuffer: &mut Framebuffer, state: &CometState, camera: &SpriteCamera) {
+        if state.activity < MIN_ACTIVITY {
+            return;
+        }
+        let coma = Billboard::new(state.position, self.scale * (2.0 + 4.0 * state.activity), COMA_COLOR)
+            .with_intensity(0.6 * state.activity)
+            .with_pixel_range(1.0, 60.0);
+        draw_billboards(framebuffer, camera, &GLOW_STATE, &[coma]);
+    }
+}
+
+// Colas de iones y de polvo de un cometa
+pub struct CometTails {
+    ion: ParticleSystem,
+    dust: ParticleSystem,
+    last_time: Option<f32>,
+}
+
+impl CometTails {
+    pub fn new(comet: &Comet, seed: u64) -> Self {
+        let scaled = |config: EmitterConfig| EmitterConfig {
+            size: (config.size.0 * comet.scale, config.size.1 * comet.scale),
+            ..config
+        };
+        let dust_age = DUST_LAG * comet.period;
+        let dust = EmitterConfig {
+            lifetime: (DUST_TAIL.lifetime.0 * dust_age, DUST_TAIL.lifetime.1 * dust_age),
+            speed: (DUST_TAIL.speed.0 * comet.scale, DUST_TAIL.speed.1 * comet.scale),
+            ..scaled(DUST_TAIL)
+        };
+        CometTails {
+            ion: ParticleSystem::new(scaled(ION_TAIL), seed.wrapping_mul(2)),
+            dust: ParticleSystem::new(dust, seed.wrapping_mul(2) + 1),
+            last_time: None,
+        }
+    }
+
+    // Avanza las colas hasta `time`. En el primer frame o tras un salto en el tiempo (buscador
+    // de alineaciones, línea temporal) se vacían y se simula de nuevo la vida del grano más viejo
+    pub fn update(&mut self, comet: &Comet, sun: Vec3, time: f32) {
+        let dt = self.last_time.map(|last| time - last);
+        self.last_time = Some(time);
+        match dt {
+            Some(dt) if (0.0..=MAX_STEP).contains(&dt) => self.step(comet, sun, time, dt),
+            _ => {
+                self.ion.clear();
+                self.dust.clear();
+                let steps = (DUST_LAG * comet.period / PREWARM_STEP).ceil() as usize;
+                for i in (0..steps).rev() {
+                    self.step(comet, sun, time - i as f32 * PREWARM_STEP, PREWARM_STEP);
+                }
+            }
+        }
+    }
+
+    fn step(&mut self, comet: &Comet, sun: Vec3, time: f32, dt: f32) {
+        let state = comet.state(time, sun);
+        let active = if state.activity < MIN_ACTIVITY { 0.0 } else { 1.0 };
+        let anti_sun = (state.position - sun).normalize();
+        let length = comet.tail_length * state.activity.sqrt();
+        self.ion.intensity = 0.3 * state.activity;
+        self.dust.intensity = 0.22 * state.activity;
+
+        // Iones: salen disparados en dirección opuesta al Sol y recorren la cola en ION_LIFETIME
+        self.ion.emit(dt, active, |_| Emission {
+            position: state.position,
+            direction: anti_sun,
+            velocity: Vec3::zeros(),
+            speed_scale: length / ION_LIFETIME,
+        });
+        self.ion.update(dt, |_| Vec3::zeros());
+
+        // Polvo: parte casi en reposo y la radiación lo acelera hacia fuera; con aceleración
+        // constante el grano más viejo llega a DUST_PUSH veces la longitud de la cola
+        let dust_age = DUST_LAG * comet.period;
+        let push = 2.0 * length * DUST_PUSH / (dust_age * dust_age);
+        self.dust.emit(dt, active, |_| Emission {
+            position: state.position,
+            direction: anti_sun,
+            velocity: Vec3::zeros(),
+            speed_scale: 1.0,
+        });
+        self.dust.update(dt, |position| (position - sun).normalize() * push);
+    }
+
+    pub fn draw(&self, framebuffer: &mut Framebuffer, camera: &SpriteCamera) {
+        self.dust.draw(framebuffer, camera);
+        self.ion.draw(framebuffer, camera);
+    }
+}
@@ -0,0 +1,128 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+use nalgebra_glm::{Mat4, Vec3};
+use crate::celestial_shaders::CelestialBody;
+use crate::draw_state::CullMode;
+use crate::math::create_model_matrix;
+use crate::scene::RingStyle;
+use crate::scene_graph::NodeId;
+use crate::shader_params::ShaderParams;
+use crate::tweaks::ShaderTweaks;
+use crate::vertex::Vertex;
+
+// ============= COMPONENTES DE LOS CUERPOS =============
+// Un cuerpo de la escena (CelestialObject) es una entidad ligera: su id, sus metadatos y
+// unos pocos componentes. Los obligatorios dicen dónde está y cómo se dibuja (Transform,
+// Spin, Renderable, Shader); los opcionales (Orbit, RingAttachment, LightEmitter) solo los
+// llevan los cuerpos que los necesitan. Los sistemas del bucle principal (systems.rs)
+// recorren los cuerpos mirando solo los componentes que les importan, así que un
+// comportamiento nuevo es un componente y un sistema más en lugar de otro campo suelto.
+
+// Posición, orientación y tamaño
+#[derive(Clone)]
+pub struct Transform {
+    pub translation: Vec3,       // posición de mundo (la calcula el grafo de escena)
+    pub local_translation: Vec3, // posición relativa al padre en el grafo de escena (o al mundo)
+    pub rotation: Vec3,
+    pub scale: f32,
+    pub axial_tilt: Vec3,        // inclinación del eje de giro (radianes), fija respecto a la órbita
+    pub node: Option<NodeId>,    // nodo en el grafo de escena (None hasta registrarlo)
+    pub previous_model: Option<Mat4>, // matriz de modelo del frame anterior (vectores de movimiento)
+}
+
+impl Transform {
+    pub fn new(translation: Vec3, scale: f32) -> Self {
+        Transform {
+            translation,
+            local_translation: translation,
+            rotation: Vec3::zeros(),
+            scale,
+            axial_tilt: Vec3::zeros(),
+            node: None,
+            previous_model: None,
+        }
+    }
+
+    // Matriz de modelo: el giro propio ocurre alrededor del eje ya inclinado
+    pub fn model_matrix(&self) -> Mat4 {
+        create_model_matrix(self.translation, self.scale, self.axial_tilt)
+            * create_model_matrix(Vec3::zeros(), 1.0, self.rotation)
+    }
+
+    // La del frame anterior, o la actual si el cuerpo acaba de aparecer
+    pub fn previous_model_matrix(&self) -> Mat4 {
+        self.previous_model.unwrap_or_else(|| self.model_matrix())
+    }
+}
+
+// Órbita circular alrededor de `center` (el origen del padre si es una luna)
+#[derive(Clone, Copy)]
+pub struct Orbit {
+    pub radius: f32,
+    pub speed: f32,
+    pub phase: f32, // ángulo inicial en la órbita (radianes)
+    pub center: Vec3,
+}
+
+impl Orbit {
+    pub fn new(radius: f32, speed: f32) -> Self {
+        Orbit { radius, speed, phase: 0.0, center: Vec3::new(400.0, 300.0, 0.0) }
+    }
+
+    pub fn angle(&self, time: f32) -> f32 {
+        time * self.speed + self.phase
+    }
+}
+
+// Giro propio por frame
+#[derive(Clone, Copy)]
+pub struct Spin {
+    pub speed: Vec3,
+    pub tidally_locked: bool, // rotación sincronizada con la órbita: siempre la misma cara al padre
+}
+
+impl Spin {
+    // Acoplamiento de marea: el giro sigue a la órbita y la cara +X mira al centro
+    pub fn lock_to_orbit(&self, transform: &mut Transform, angle: f32) {
+        if self.tidally_locked {
+            transform.rotation.y = PI - angle;
+        }
+    }
+}
+
+impl Default for Spin {
+    fn default() -> Self {
+        Spin { speed: Vec3::new(0.0, 0.01, 0.0), tidally_locked: false }
+    }
+}
+
+// Geometría: la esfera compartida o una malla importada (radio 1), y qué caras se descartan
+#[derive(Clone, Default)]
+pub struct Renderable {
+    pub model: Option<Arc<Vec<Vertex>>>,
+    pub cull: CullMode,
+}
+
+// Shader de la superficie con sus constantes y sus colores
+#[derive(Clone, Copy)]
+pub struct Shader {
+    pub body: CelestialBody,
+    pub tweaks: ShaderTweaks, // constantes del shader, ajustables con el panel F2
+    pub params: ShaderParams, // colores del shader (tabla `palette` de la escena)
+}
+
+impl Shader {
+    pub fn new(body: CelestialBody) -> Self {
+        Shader { body, tweaks: ShaderTweaks::for_body(body), params: ShaderParams::for_body(body) }
+    }
+}
+
+// Anillos que se dibujan alrededor del cuerpo y heredan su inclinación
+#[derive(Clone, Copy)]
+pub struct RingAttachment {
+    pub style: RingStyle,
+}
+
+// Fuente de luz de la escena (las estrellas); el primer cuerpo que la lleva ilumina a los demás
+#[derive(Clone, Copy)]
+pub struct LightEmitter;
@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use minifb::{Key, KeyRepeat};
+use crate::input::Input;
+
+// ============= ASIGNACIÓN DE TECLAS (controls.toml) =============
+// Las acciones de la cámara (mover, orbitar, zoom), la captura de pantalla y la pausa se leen
+// de controls.toml al arrancar, así quien no usa QWERTY puede reasignarlas (ZQSD en AZERTY).
+// Si el archivo no existe se escribe con las teclas de fábrica. Cada línea es
+// `accion = "Tecla"`, con el nombre de la tecla de minifb ("W", "Left", "F12", "Space",
+// "Key1", "NumPad8"...); las acciones que falten conservan su tecla de fábrica.
+// El vuelo libre y el aterrizaje usan las mismas teclas de movimiento; en vuelo, las de
+// orbitar cabecean y guiñan y las de zoom alabean.
+
+pub const DEFAULT_PATH: &str = "controls.toml";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    OrbitLeft,
+    OrbitRight,
+    OrbitUp,
+    OrbitDown,
+    ZoomIn,
+    ZoomOut,
+    Screenshot,
+    Pause,
+}
+
+pub const ACTIONS: [Action; 14] = [
+    Action::MoveForward,
+    Action::MoveBack,
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::OrbitLeft,
+    Action::OrbitRight,
+    Action::OrbitUp,
+    Action::OrbitDown,
+    Action::ZoomIn,
+    Action::ZoomOut,
+    Action::Screenshot,
+    Action::Pause,
+];
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveForward => "move_forward",
+            Action::MoveBack => "move_back",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::OrbitLeft => "orbit_left",
+            Action::OrbitRight => "orbit_right",
+            Action::OrbitUp => "orbit_up",
+            Action::OrbitDown => "orbit_down",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+            Action::Screenshot => "screenshot",
+            Action::Pause => "pause",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Action::MoveForward => "avanzar",
+            Action::MoveBack => "retroceder",
+            Action::MoveLeft => "desplazarse a la izquierda",
+            Action::MoveRight => "desplazarse a la derecha",
+            Action::MoveUp => "subir",
+            Action::MoveDown => "bajar",
+            Action::OrbitLeft => "orbitar a la izquierda (en vuelo, guiñada)",
+            Action::OrbitRight => "orbitar a la derecha (en vuelo, guiñada)",
+            Action::OrbitUp => "orbitar hacia arriba (en vuelo, cabeceo)",
+            Action::OrbitDown => "orbitar hacia abajo (en vuelo, cabeceo)",
+            Action::ZoomIn => "acercar (en vuelo, alabeo)",
+            Action::ZoomOut => "alejar (en vuelo, alabeo)",
+            Action::Screenshot => "guardar una captura PNG",
+            Action::Pause => "pausar la simulación",
+        }
+    }
+
+    fn default_key(self) -> Key {
+        match self {
+            Action::MoveForward => Key::W,
+            Action::MoveBack => Key::S,
+            Action::MoveLeft => Key::A,
+            Action::MoveRight => Key::D,
+            Action::MoveUp => Key::Q,
+            Action::MoveDown => Key::E,
+            Action::OrbitLeft => Key::Left,
+            Action::OrbitRight => Key::Right,
+            Action::OrbitUp => Key::Up,
+            Action::OrbitDown => Key::Down,
+            Action::ZoomIn => Key::Z,
+            Action::ZoomOut => Key::X,
+            Action::Screenshot => Key::F12,
+            Action::Pause => Key::B,
+        }
+    }
+}
+
+// Teclas que se pueden asignar (su nombre es el de minifb)
+const KEYS: [Key; 106] = [
+    Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9,
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+    Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9, Key::F10, Key::F11, Key::F12,
+    Key::F13, Key::F14, Key::F15,
+    Key::Down, Key::Left, Key::Right, Key::Up,
+    Key::Apostrophe, Key::Backquote, Key::Backslash, Key::Comma, Key::Equal, Key::LeftBracket, Key::Minus,
+    Key::Period, Key::RightBracket, Key::Semicolon, Key::Slash,
+    Key::Backspace, Key::Delete, Key::End, Key::Enter, Key::Escape, Key::Home, Key::Insert, Key::Menu,
+    Key::PageDown, Key::PageUp, Key::Pause, Key::Space, Key::Tab, Key::NumLock, Key::CapsLock, Key::ScrollLock,
+    Key::LeftShift, Key::RightShift, Key::LeftCtrl, Key::RightCtrl,
+    Key::NumPad0, Key::NumPad1, Key::NumPad2, Key::NumPad3, Key::NumPad4, Key::NumPad5, Key::NumPad6,
+    Key::NumPad7, Key::NumPad8, Key::NumPad9, Key::NumPadDot, Key::NumPadSlash, Key::NumPadAsterisk,
+    Key::NumPadMinus, Key::NumPadPlus, Key::NumPadEnter,
+    Key::LeftAlt, Key::RightAlt, Key::LeftSuper, Key::RightSuper,
+];
+
+pub fn key_name(key: Key) -> String {
+    format!("{:?}", key)
+}
+
+// Sin distinguir mayúsculas: "left" y "Left" son la misma tecla
+pub fn parse_key(name: &str) -> Option<Key> {
+    KEYS.iter().copied().find(|&key| key_name(key).eq_ignore_ascii_case(name.trim()))
+}
+
+#[derive(Debug)]
+pub enum ControlsError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    UnknownAction(String),
+    UnknownKey { action: String, key: String },
+    Duplicate { key: String, first: &'static str, second: &'static str },
+}
+
+impl fmt::Display for ControlsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ControlsError::Io(err) => write!(f, "no se pudieron leer los controles: {}", err),
+            ControlsError::Parse(err) => write!(f, "controles inválidos: {}", err),
+            ControlsError::UnknownAction(action) => write!(f, "acción desconocida: '{}'", action),
+            ControlsError::UnknownKey { action, key } => write!(f, "'{}' usa una tecla desconocida: '{}'", action, key),
+            ControlsError::Duplicate { key, first, second } => {
+                write!(f, "la tecla {} está asignada a '{}' y a '{}'", key, first, second)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Controls {
+    keys: [Key; ACTIONS.len()],
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Controls { keys: ACTIONS.map(Action::default_key) }
+    }
+}
+
+impl Controls {
+    // Sin archivo se escriben las teclas de fábrica (si no se puede, se avisa y se usan igual)
+    pub fn load(path: &str) -> Result<Self, ControlsError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                if let Err(err) = fs::write(path, Self::default().to_file()) {
+                    eprintln!("Controles: no se pudo escribir {} ({})", path, err);
+                }
+                Ok(Self::default())
+            }
+            Err(err) => Err(ControlsError::Io(err)),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, ControlsError> {
+        let table: BTreeMap<String, String> = toml::from_str(contents).map_err(ControlsError::Parse)?;
+        let mut controls = Self::default();
+        for (action_name, key_name) in &table {
+            let index = ACTIONS
+                .iter()
+                .position(|action| action.name() == action_name)
+                .ok_or_else(|| ControlsError::UnknownAction(action_name.clone()))?;
+            controls.keys[index] = parse_key(key_name).ok_or_else(|| ControlsError::UnknownKey {
+                action: action_name.clone(),
+                key: key_name.clone(),
+            })?;
+        }
+
+        for (i, first) in ACTIONS.iter().enumerate() {
+            if let Some(second) = ACTIONS[i + 1..].iter().find(|&&second| controls.key(second) == controls.key(*first)) {
+                return Err(ControlsError::Duplicate {
+                    key: key_name(controls.key(*first)),
+                    first: first.name(),
+                    second: second.name(),
+                });
+            }
+        }
+        Ok(controls)
+    }
+
+    pub fn key(&self, action: Action) -> Key {
+        self.keys[action as usize]
+    }
+
+    // Tecla de la acción para las ayudas en pantalla: el nombre de minifb, con las flechas
+    // dibujadas como flechas
+    pub fn key_name(&self, action: Action) -> String {
+        match self.key(action) {
+            Key::Left => "←".to_string(),
+            Key::Up => "↑".to_string(),
+            Key::Right => "→".to_string(),
+            Key::Down => "↓".to_string(),
+            key => key_name(key),
+        }
+    }
+
+    // Dos acciones opuestas en una ayuda: "W/S"
+    pub fn pair_name(&self, first: Action, second: Action) -> String {
+        format!("{}/{}", self.key_name(first), self.key_name(second))
+    }
+
+    // Mientras se mantiene pulsada (movimientos continuos)
+    pub fn is_down(&self, input: &Input, action: Action) -> bool {
+        input.is_key_down(self.key(action))
+    }
+
+    // Solo el frame en que se pulsa (acciones sueltas)
+    pub fn is_pressed(&self, input: &Input, action: Action) -> bool {
+        input.is_key_pressed(self.key(action), KeyRepeat::No)
+    }
+
+    // Archivo comentado con una línea por acción
+    fn to_file(self) -> String {
+        let mut file = String::from(
+            "# Teclas de cada acción (nombres de minifb: \"W\", \"Left\", \"F12\", \"Space\", \"Key1\", \"NumPad8\"...)\n\
+             # Las acciones que se borren conservan su tecla de fábrica.\n\n",
+        );
+        for action in ACTIONS {
+            file.push_str(&format!("{} = \"{}\"  # {}\n", action.name(), key_name(self.key(action)), action.description()));
+        }
+        file
+    }
+}
@@ -0,0 +1,167 @@
+use std::f32::consts::{PI, TAU};
+use nalgebra_glm::Vec3;
+use rayon::prelude::*;
+use crate::billboard::{draw_billboards, Billboard, SpriteCamera};
+use crate::draw_state::DrawState;
+use crate::framebuffer::Framebuffer;
+use crate::celestial_shaders::star_tint;
+use crate::noise::{fbm, hash3};
+use crate::random_system::StarClass;
+use crate::CelestialObject;
+
+// ============= CORONA Y PROTUBERANCIAS DEL SOL =============
+// La esfera del Sol termina en un borde nítido; la corona se dibuja aparte, sumando luz en
+// la escena alrededor del disco proyectado:
+// - Corona: un anillo en pantalla con lenguas de fuego animadas. El alcance de cada lengua
+//   (entre 1.5 y 2 radios) sale de ruido fractal según el ángulo, y las vetas se desplazan
+//   hacia fuera con el tiempo. Se escribe con la profundidad del centro del Sol, así que la
+//   superficie de la esfera tapa su parte interior y los planetas de delante la cortan.
+//   Cerca del disco tiene el color de la superficie y hacia fuera el de un gas más frío.
+// - Protuberancias: de vez en cuando un arco de plasma se levanta entre dos puntos de la
+//   superficie, se sostiene unos segundos y se apaga. Son arcos en el mundo hechos de
+//   billboards, de modo que giran con la cámara y el Sol tapa los que quedan detrás.
+
+const INNER_EDGE: f32 = 0.92;          // la corona empieza algo dentro del borde del disco
+const MIN_REACH: f32 = 1.5;            // alcance de las lenguas, en radios del disco
+const MAX_REACH: f32 = 2.0;
+const REACH_SAMPLES: usize = 256;      // alcance precalculado por ángulo en cada frame
+const CORONA_INTENSITY: f32 = 0.55;
+
+const PROMINENCE_SLOTS: i32 = 3;       // protuberancias que pueden coincidir
+const PROMINENCE_CYCLE: f32 = 12.0;    // segundos de cada ventana de una ranura
+const PROMINENCE_CHANCE: f32 = 0.55;   // probabilidad de que una ventana tenga protuberancia
+const PROMINENCE_SEGMENTS: usize = 40; // billboards a lo largo del arco
+
+// Corona alrededor del disco del Sol en el framebuffer de la escena
+pub fn draw_corona(framebuffer: &mut Framebuffer, sun: &CelestialObject, camera: &SpriteCamera, time: f32) {
+    let Some((center, radius)) = crate::picking::screen_disk(sun, camera.view_matrix, camera.projection_matrix, camera.viewport_matrix) else {
+        return;
+    };
+    if radius < 1.0 {
+        return;
+    }
+    let outer = radius * MAX_REACH;
+    let min_x = (center.x - outer).floor().max(0.0) as usize;
+    let max_x = ((center.x + outer).ceil().max(0.0) as usize).min(framebuffer.width);
+    let min_y = (center.y - outer).floor().max(0.0) as usize;
+    let max_y = ((center.y + outer).ceil().max(0.0) as usize).min(framebuffer.height);
+    if min_x >= max_x || min_y >= max_y {
+        return;
+    }
+
+    // El alcance solo depende del ángulo: se calcula una vez por frame en una tabla
+    let reach: Vec<f32> = (0..REACH_SAMPLES)
+        .map(|i| {
+            let (sin, cos) = (i as f32 / REACH_SAMPLES as f32 * TAU).sin_cos();
+            MIN_REACH + (MAX_REACH - MIN_REACH) * fbm(cos * 2.5, sin * 2.5, time * 0.08, 3)
+        })
+        .collect();
+
+    // Colores según la temperatura de la estrella (el Sol por defecto)
+    let temperature = sun.shader.body.star_class().unwrap_or(StarClass::G).temperature();
+    let inner_color = star_tint(temperature * 1.05).to_float();
+    let outer_color = star_tint(temperature * 0.6).to_float();
+
+    let width = framebuffer.width;
+    let rows = framebuffer.buffer[min_y * width..max_y * width]
+        .par_chunks_mut(width)
+        .zip(framebuffer.zbuffer[min_y * width..max_y * width].par_chunks(width));
+    rows.enumerate().for_each(|(row, (pixels, depths))| {
+        let dy = (min_y + row) as f32 + 0.5 - center.y;
+        for x in min_x..max_x {
+            // Reversed-Z: algo más cercano que el centro del Sol tapa la corona
+            if depths[x] > center.z {
+                continue;
+            }
+            let dx = x as f32 + 0.5 - center.x;
+            let distance = dx.hypot(dy) / radius;
+            if !(INNER_EDGE..MAX_REACH).contains(&distance) {
+                continue;
+            }
+            let light = corona_light(dy.atan2(dx), distance, &reach, time);
+            if light <= 1.0 / 255.0 {
+                continue;
+            }
+            let heat = ((distance - 1.0) / (MAX_REACH - 1.0)).clamp(0.0, 1.0);
+            let channel = |inner: f32, outer: f32, shift: u32| (((inner + (outer - inner) * heat) * light * 255.0) as u32) << shift;
+            let color = channel(inner_color.0, outer_color.0, 16)
+                | channel(inner_color.1, outer_color.1, 8)
+                | channel(inner_color.2, outer_color.2, 0);
+            pixels[x] = DrawState::ADDITIVE.blend.apply(color, pixels[x]);
+        }
+    });
+}
+
+// Brillo de la corona en un ángulo y a una distancia del centro (en radios del disco)
+fn corona_light(angle: f32, distance: f32, reach_table: &[f32], time: f32) -> f32 {
+    // Alcance de la lengua en esta dirección, interpolado en la tabla
+    let position = angle.rem_euclid(TAU) / TAU * REACH_SAMPLES as f32;
+    let index = position as usize % REACH_SAMPLES;
+    let t = position.fract();
+    let reach = reach_table[index] * (1.0 - t) + reach_table[(index + 1) % REACH_SAMPLES] * t;
+    if distance >= reach {
+        return 0.0;
+    }
+    // Vetas finas que se alejan del Sol
+    let (sin, cos) = angle.sin_cos();
+    let streaks = fbm(cos * 9.0, sin * 9.0, (distance - time * 0.12) * 3.0, 2);
+    let falloff = ((reach - distance) / (reach - 1.0)).min(1.0).powi(2);
+    let fade_in = ((distance - INNER_EDGE) / (1.0 - INNER_EDGE)).clamp(0.0, 1.0);
+    falloff * fade_in * (0.35 + 0.9 * streaks) * CORONA_INTENSITY
+}
+
+// Protuberancias activas en `time`, dibujadas con prueba de profundidad contra la escena
+pub fn draw_prominences(framebuffer: &mut Framebuffer, sun: &CelestialObject, camera: &SpriteCamera, time: f32) {
+    let temperature = sun.shader.body.star_class().unwrap_or(StarClass::G).temperature();
+    let color = star_tint(temperature * 0.55).to_hex();
+    let mut billboards = Vec::new();
+    for slot in 0..PROMINENCE_SLOTS {
+        // Las ranuras están desfasadas para que no aparezcan todas a la vez
+        let cycle = time / PROMINENCE_CYCLE + slot as f32 / PROMINENCE_SLOTS as f32;
+        let epoch = cycle.floor() as i32;
+        let random = |k: i32| hash3(slot, epoch, k);
+        if random(0) > PROMINENCE_CHANCE {
+            continue;
+        }
+        // Crece, se sostiene y se apaga dentro de su ventana
+        let phase = cycle.fract();
+        let strength = (phase / 0.2).min(1.0) * ((1.0 - phase) / 0.3).min(1.0);
+        if strength <= 0.0 {
+            continue;
+        }
+
+        // Pie del arco: dirección al azar sobre la esfera y un eje tangente para separar los pies
+        let z = random(1) * 2.0 - 1.0;
+        let around = random(2) * TAU;
+        let ring = (1.0 - z * z).sqrt();
+        let base = Vec3::new(ring * around.cos(), z, ring * around.sin());
+        let helper = if base.y.abs() < 0.9 { Vec3::y() } else { Vec3::x() };
+        let tangent = base.cross(&helper).normalize();
+        let tangent = rotate_about(tangent, base, random(3) * TAU);
+        let span = 0.3 + 0.35 * random(4);  // radianes entre los pies
+        let height = 0.2 + 0.3 * random(5);  // altura máxima, en radios
+        let thickness = 0.035 + 0.025 * random(6);
+
+        let rise = 0.3 + 0.7 * (phase / 0.35).min(1.0);
+        for i in 0..PROMINENCE_SEGMENTS {
+            let u = i as f32 / (PROMINENCE_SEGMENTS - 1) as f32;
+            let lift = (u * PI).sin();
+            let direction = rotate_about(base, base.cross(&tangent).normalize(), (u - 0.5) * span);
+            let position = sun.transform.translation + direction * sun.transform.scale * (1.0 + height * rise * lift);
+            // Los pies, más densos, brillan más que la cima
+            let glow = strength * (0.55 + 0.45 * (1.0 - lift));
+            billboards.push(
+                Billboard::new(position, sun.transform.scale * thickness * (1.0 + 0.6 * lift), color)
+                    .with_intensity(glow)
+                    .with_pixel_range(1.0, 48.0),
+            );
+        }
+    }
+    draw_billboards(framebuffer, camera, &DrawState::ADDITIVE, &billboards);
+}
+
+// Giro de `v` alrededor del eje unitario `axis` (fórmula de Rodrigues)
+fn rotate_about(v: Vec3, axis: Vec3, angle: f32) -> Vec3 {
+    let (sin, cos) = angle.sin_cos();
+    v * cos + axis.cross(&v) * sin + axis * axis.dot(&v) * (1.0 - cos)
+}
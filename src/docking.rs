@@ -0,0 +1,110 @@
+use nalgebra_glm::Vec3;
+use crate::celestial_shaders::CelestialBody;
+use crate::collision::Collider;
+use crate::CelestialObject;
+
+// ============= ATRAQUE EN LA ESTACIÓN =============
+// Una estación es un cuerpo de la escena con el shader Station (la de la Tierra lleva su
+// propia malla, models/station.obj). Si la nave del vuelo libre se acerca a menos de
+// DOCK_RANGE radios de su centro y va despacio respecto a ella (DOCK_SPEED), atraca: queda
+// amarrada donde llegó, guardada en el espacio del modelo de la estación, así que gira y
+// orbita con ella y la cámara de persecución la acompaña. Cualquier empuje suelta las
+// amarras con un pequeño impulso hacia fuera; para volver a atracar hay que alejarse antes.
+
+pub const DOCK_RANGE: f32 = 2.5;  // radios de la estación
+pub const DOCK_SPEED: f32 = 20.0; // unidades/s respecto a la estación
+const BERTH: f32 = 1.8;           // distancia del amarre al centro, en radios
+const UNDOCK_PUSH: f32 = 15.0;    // unidades/s hacia fuera al soltar amarras
+
+pub struct Dock {
+    pub body: usize, // índice de la estación en la lista de la escena
+    id: String,      // para soltarla si la escena cambia
+    pub name: String,
+    berth: Vec3,     // punto de amarre en el espacio del modelo de la estación
+}
+
+impl Dock {
+    // La estación en la que puede atracar una nave en `position` con `velocity`, si hay alguna
+    pub fn find(objects: &[CelestialObject], bodies: &[Collider], position: Vec3, velocity: Vec3) -> Option<Dock> {
+        let (body, obj) = objects.iter().enumerate().find(|(index, obj)| {
+            obj.shader.body == CelestialBody::Station
+                && (position - obj.transform.translation).magnitude() < obj.transform.scale * DOCK_RANGE
+                && (velocity - bodies[*index].velocity).magnitude() < DOCK_SPEED
+        })?;
+        let outward = (position - obj.transform.translation).try_normalize(1.0e-6).unwrap_or_else(Vec3::y);
+        let inverse_model = obj.transform.model_matrix().try_inverse()?;
+        // El modelo ya escala por el radio: el amarre queda a BERTH en su espacio
+        let local = (inverse_model * (obj.transform.translation + outward * obj.transform.scale).push(1.0)).xyz();
+        Some(Dock {
+            body,
+            id: obj.id.clone(),
+            name: obj.display_name().to_string(),
+            berth: local.try_normalize(1.0e-6).unwrap_or_else(Vec3::y) * BERTH,
+        })
+    }
+
+    // Dónde está el amarre en este frame (None si la estación ya no existe)
+    pub fn berth(&self, objects: &[CelestialObject]) -> Option<Vec3> {
+        let station = objects.get(self.body).filter(|obj| obj.id == self.id)?;
+        Some((station.transform.model_matrix() * self.berth.push(1.0)).xyz())
+    }
+
+    // Velocidad con la que sale la nave al soltar amarras
+    pub fn release_velocity(&self, objects: &[CelestialObject], bodies: &[Collider], position: Vec3) -> Vec3 {
+        let Some(station) = objects.get(self.body).filter(|obj| obj.id == self.id) else {
+            return Vec3::zeros();
+        };
+        let outward = (position - station.transform.translation).try_normalize(1.0e-6).unwrap_or_else(Vec3::y);
+        bodies[self.body].velocity + outward * UNDOCK_PUSH
+    }
+}
+
+// ¿Está la nave en `position` fuera del alcance de todas las estaciones? (para poder volver a atracar)
+pub fn clear_of_stations(objects: &[CelestialObject], position: Vec3) -> bool {
+    objects
+        .iter()
+        .filter(|obj| obj.shader.body == CelestialBody::Station)
+        .all(|obj| (position - obj.transform.translation).magnitude() > obj.transform.scale * DOCK_RANGE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn station() -> CelestialObject {
+        let mut station = CelestialObject::new(CelestialBody::Station, Vec3::new(100.0, 0.0, 0.0), 6.0);
+        station.id = "station".to_string();
+        station
+    }
+
+    #[test]
+    fn slow_ships_dock_and_ride_with_the_station() {
+        let mut objects = vec![station()];
+        let bodies = [Collider { center: objects[0].transform.translation, radius: 6.0, velocity: Vec3::new(5.0, 0.0, 0.0), fatal: false }];
+        let near = Vec3::new(100.0, 12.0, 0.0);
+
+        // Deprisa o lejos no atraca
+        assert!(Dock::find(&objects, &bodies, near, Vec3::new(0.0, -40.0, 0.0)).is_none());
+        assert!(Dock::find(&objects, &bodies, Vec3::new(100.0, 30.0, 0.0), Vec3::zeros()).is_none());
+        assert!(!clear_of_stations(&objects, near));
+
+        let dock = Dock::find(&objects, &bodies, near, Vec3::new(5.0, -3.0, 0.0)).unwrap();
+        let berth = dock.berth(&objects).unwrap();
+        assert!((berth - Vec3::new(100.0, 6.0 * BERTH, 0.0)).magnitude() < 1.0e-3, "{:?}", berth);
+
+        // La estación se mueve y gira: el amarre va con ella
+        objects[0].transform.translation = Vec3::new(110.0, 0.0, 0.0);
+        objects[0].transform.rotation = Vec3::new(0.0, 0.0, std::f32::consts::FRAC_PI_2);
+        let moved = dock.berth(&objects).unwrap();
+        assert!(((moved - objects[0].transform.translation).magnitude() - 6.0 * BERTH).abs() < 1.0e-3);
+        assert!((moved - Vec3::new(110.0, 6.0 * BERTH, 0.0)).magnitude() > 1.0);
+
+        // Al soltar amarras sale hacia fuera con la velocidad de la estación
+        let release = dock.release_velocity(&objects, &bodies, moved);
+        assert!((release - bodies[0].velocity).dot(&(moved - objects[0].transform.translation)) > 0.0);
+
+        // Si la estación desaparece, no hay amarre
+        objects[0].id = "other".to_string();
+        assert!(dock.berth(&objects).is_none());
+    }
+}
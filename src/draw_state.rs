@@ -0,0 +1,88 @@
+// ============= ESTADO FIJO DE CADA LLAMADA DE DIBUJO =============
+// Lo que no decide el shader sino la llamada: qué caras se descartan, si se prueba y se
+// escribe la profundidad y cómo se mezcla el color con lo que ya hay en el framebuffer.
+// render() lo recibe junto a las uniformes; los cuerpos usan OPAQUE y cada efecto
+// (anillos, nubes, partículas...) elige el suyo.
+
+use serde::Deserialize;
+
+// También es la clave `cull` de los cuerpos de la escena ("back", "front" o "none")
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CullMode {
+    #[default]
+    Back,  // descarta las caras de espaldas a la cámara
+    Front, // descarta las de cara: mallas con el orden de los vértices al revés
+    None,  // ambas caras (geometría fina vista desde los dos lados)
+}
+
+impl CullMode {
+    // Si se dibuja un triángulo según el signo de su área en pantalla (positiva = de cara a
+    // la cámara); los degenerados nunca
+    pub fn keeps(self, signed_area: f32) -> bool {
+        match self {
+            CullMode::Back => signed_area > 0.0,
+            CullMode::Front => signed_area < 0.0,
+            CullMode::None => signed_area != 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BlendMode {
+    Opaque,
+    Alpha(f32), // opacidad de toda la llamada, en [0, 1]
+    Additive,   // suma saturada: el negro no cambia nada (brillos, anillos, partículas)
+}
+
+impl BlendMode {
+    pub fn apply(self, src: u32, dst: u32) -> u32 {
+        match self {
+            BlendMode::Opaque => src,
+            BlendMode::Alpha(alpha) => {
+                let alpha = alpha.clamp(0.0, 1.0);
+                let mix = |shift: u32| {
+                    let s = ((src >> shift) & 0xFF) as f32;
+                    let d = ((dst >> shift) & 0xFF) as f32;
+                    ((s * alpha + d * (1.0 - alpha)) as u32) << shift
+                };
+                mix(16) | mix(8) | mix(0)
+            }
+            BlendMode::Additive => {
+                let add = |shift: u32| (((src >> shift) & 0xFF) + ((dst >> shift) & 0xFF)).min(0xFF) << shift;
+                add(16) | add(8) | add(0)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DrawState {
+    pub cull: CullMode,
+    pub depth_test: bool,
+    pub depth_write: bool,
+    pub blend: BlendMode,
+}
+
+impl DrawState {
+    // Superficies sólidas: lo que se ha dibujado hasta ahora
+    pub const OPAQUE: DrawState = DrawState {
+        cull: CullMode::Back,
+        depth_test: true,
+        depth_write: true,
+        blend: BlendMode::Opaque,
+    };
+
+    // Luz que se suma a la escena: la tapan los cuerpos pero no tapa nada
+    pub const ADDITIVE: DrawState = DrawState {
+        cull: CullMode::None,
+        depth_test: true,
+        depth_write: false,
+        blend: BlendMode::Additive,
+    };
+
+    // Capa semitransparente sobre una superficie (nubes): deja ver lo de debajo
+    pub fn translucent(opacity: f32) -> DrawState {
+        DrawState { blend: BlendMode::Alpha(opacity), ..DrawState::OPAQUE }
+    }
+}
@@ -0,0 +1,348 @@
+use nalgebra_glm::{Mat3, Mat4, Vec3};
+use crate::collision::{self, Collider, Impact};
+use crate::flight_model::ShipBody;
+use crate::free_flight::ship_model_matrix;
+use crate::particles::random_unit_vector;
+use crate::procedural::SeededRng;
+use crate::weapons::{Weapons, BOLT_SPEED};
+
+// ============= DRONES ENEMIGOS =============
+// En vuelo libre unas pocas naves controladas por el programa patrullan entre los planetas.
+// Se mueven con comportamientos de dirección (steering behaviors): cada uno dice a qué
+// velocidad querría ir el dron y la diferencia con la que lleva, limitada, es su aceleración.
+// - Patrulla: busca (seek) un punto junto a un planeta, le da unas vueltas en órbita y sigue
+//   con otro.
+// - Persecución: si el jugador se acerca, lo busca y le dispara cuando lo tiene delante.
+// - Evasión: si un láser del jugador viene hacia él, huye (flee) hacia un lado de su línea.
+// Además se apartan de los cuerpos que tienen cerca; si aun así chocan, rebotan o explotan
+// como la nave del jugador (ver collision.rs). Usan la malla de la nave, y sus disparos son
+// proyectiles del mismo sistema de armas (weapons.rs) que los del jugador.
+
+pub const DRONE_COUNT: usize = 3;
+pub const DRONE_POINTS: u32 = 100;
+const DRONE_SCALE: f32 = 1.6;     // radio, como SHIP_SCALE en la nave del jugador
+const HEALTH: u32 = 3;            // impactos de láser que aguanta
+const MAX_SPEED: f32 = 160.0;     // unidades/s: más lento que el jugador con el limitador
+const MAX_FORCE: f32 = 140.0;     // aceleración máxima de la dirección, unidades/s²
+const TURN_RATE: f32 = 4.0;       // por segundo: lo que tarda el morro en seguir a la velocidad
+const DETECT_RANGE: f32 = 700.0;  // empieza a perseguir al jugador
+const LOSE_RANGE: f32 = 1100.0;   // y lo deja
+const FIRE_RANGE: f32 = 450.0;
+const FIRE_CONE: f32 = 0.3;       // radianes entre el morro y el jugador para disparar
+const AIM_ERROR: f32 = 0.01;      // radianes de puntería al azar, para que se pueda esquivar
+const FIRE_INTERVAL: f32 = 1.4;   // segundos entre disparos de cada dron
+const EVADE_RANGE: f32 = 120.0;   // láseres del jugador más cerca que esto asustan
+const PATROL_DISTANCE: f32 = 3.0; // radio de la órbita de patrulla, en radios del planeta
+const PATROL_MARGIN: f32 = 40.0;  // unidades más allá de esos radios
+const PATROL_TIME: f32 = 12.0;    // segundos de órbita antes de ir al siguiente planeta
+const AVOID_MARGIN: f32 = 30.0;   // distancia a la superficie a la que empieza a apartarse
+const RESPAWN_TIME: f32 = 8.0;
+const BOLT_COLOR: u32 = 0x50FF70;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+    Patrol,
+    Pursue,
+    Evade,
+}
+
+// Lo que un dron sabe del jugador en este frame
+#[derive(Clone, Copy, Debug)]
+pub struct Player {
+    pub position: Vec3,
+    pub velocity: Vec3,
+}
+
+struct Drone {
+    body: ShipBody,
+    mode: Mode,
+    waypoint: usize,   // planeta de la patrulla (índice en los cuerpos)
+    orbiting: f32,     // segundos que lleva en órbita alrededor de él
+    health: u32,
+    cooldown: f32,
+    wreck: f32,        // segundos para reaparecer (0 mientras vuela)
+}
+
+impl Drone {
+    fn alive(&self) -> bool {
+        self.wreck <= 0.0
+    }
+}
+
+pub struct Squadron {
+    drones: Vec<Drone>,
+    rng: SeededRng,
+    pub weapons: Weapons, // los disparos de todos los drones
+}
+
+impl Squadron {
+    pub fn new(seed: u64) -> Self {
+        Squadron { drones: Vec::new(), rng: SeededRng::new(seed), weapons: Weapons::new().with_color(BOLT_COLOR) }
+    }
+
+    // Los drones desaparecen al salir del vuelo libre (y vuelven a salir al entrar)
+    pub fn clear(&mut self) {
+        self.drones.clear();
+        self.weapons.clear();
+    }
+
+    // Un frame: elige el comportamiento de cada dron (patrulla entre `bodies`, `threats` son los
+    // láseres del jugador), lo mueve y lo choca con los cuerpos y las `rocks`. Devuelve dónde
+    // han explotado los que se estrellaron
+    pub fn update(&mut self, dt: f32, bodies: &[Collider], rocks: &[Collider], player: Option<Player>, threats: &[(Vec3, Vec3)]) -> Vec<Vec3> {
+        let obstacles: Vec<Collider> = bodies.iter().chain(rocks).copied().collect();
+        let planets: Vec<usize> = (0..bodies.len()).filter(|&index| !bodies[index].fatal).collect();
+        if planets.is_empty() {
+            return Vec::new();
+        }
+        if self.drones.is_empty() {
+            for i in 0..DRONE_COUNT {
+                let waypoint = planets[(i * 3 + 1) % planets.len()];
+                let drone = spawn(&mut self.rng, &bodies[waypoint], waypoint);
+                self.drones.push(drone);
+            }
+        }
+
+        let mut crashes = Vec::new();
+        for drone in &mut self.drones {
+            if !drone.alive() {
+                drone.wreck -= dt;
+                if drone.wreck <= 0.0 {
+                    let waypoint = *self.rng.pick(&planets);
+                    *drone = spawn(&mut self.rng, &bodies[waypoint], waypoint);
+                }
+                continue;
+            }
+            if !planets.contains(&drone.waypoint) {
+                drone.waypoint = planets[0];
+            }
+            drone.cooldown = (drone.cooldown - dt).max(0.0);
+
+            let position = drone.body.position;
+            let threat = threats
+                .iter()
+                .filter(|(bolt, velocity)| (position - bolt).magnitude() < EVADE_RANGE && velocity.dot(&(position - bolt)) > 0.0)
+                .min_by(|(a, _), (b, _)| (position - a).magnitude().total_cmp(&(position - b).magnitude()));
+            let distance = player.map(|player| (player.position - position).magnitude());
+            drone.mode = match (threat, distance) {
+                (Some(_), _) => Mode::Evade,
+                (None, Some(distance)) if distance < DETECT_RANGE => Mode::Pursue,
+                (None, Some(distance)) if drone.mode == Mode::Pursue && distance < LOSE_RANGE => Mode::Pursue,
+                _ => Mode::Patrol,
+            };
+
+            let mut steering = match (drone.mode, threat, player) {
+                (Mode::Evade, Some(&(bolt, velocity)), _) => {
+                    // Hacia un lado de la trayectoria del láser, lejos de ella
+                    let line = velocity.try_normalize(1.0e-6).unwrap_or_else(Vec3::z);
+                    let away = position - bolt;
+                    let side = (away - line * away.dot(&line)).try_normalize(1.0e-6).unwrap_or_else(|| any_perpendicular(line));
+                    flee(&drone.body, position - side * EVADE_RANGE)
+                }
+                (Mode::Pursue, _, Some(player)) => {
+                    // Apunta a donde estará el jugador cuando llegue (aprox.)
+                    let lead = (player.position - position).magnitude() / MAX_SPEED.max(1.0);
+                    seek(&drone.body, player.position + player.velocity * lead.min(2.0))
+                }
+                _ => {
+                    let planet = &bodies[drone.waypoint];
+                    let radius = planet.radius * PATROL_DISTANCE + PATROL_MARGIN;
+                    if (position - planet.center).magnitude() < radius * 1.5 {
+                        drone.orbiting += dt;
+                        if drone.orbiting > PATROL_TIME {
+                            drone.orbiting = 0.0;
+                            drone.waypoint = planets[(planets.iter().position(|&p| p == drone.waypoint).unwrap_or(0) + 1) % planets.len()];
+                        }
+                        orbit(&drone.body, planet.center, radius, planet.velocity)
+                    } else {
+                        seek(&drone.body, planet.center)
+                    }
+                }
+            };
+            steering += avoid(&drone.body, &obstacles);
+
+            let from = drone.body.position;
+            steer(&mut drone.body, steering, dt);
+            if let Some(Impact::Crash { position, .. }) = collision::resolve(&mut drone.body, from, DRONE_SCALE, &obstacles) {
+                drone.wreck = RESPAWN_TIME;
+                crashes.push(position);
+                continue;
+            }
+
+            // Dispara si tiene al jugador más o menos delante y cerca, apuntando a donde estará
+            // cuando llegue el rayo (que también lleva la velocidad del dron)
+            if let (Mode::Pursue, Some(player)) = (drone.mode, player) {
+                let forward = drone.body.axis(Vec3::new(0.0, 0.0, -1.0));
+                let muzzle = drone.body.position + forward * DRONE_SCALE;
+                let to_player = player.position - muzzle;
+                let flight_time = to_player.magnitude() / BOLT_SPEED;
+                let aim = to_player + (player.velocity - drone.body.velocity) * flight_time;
+                if drone.cooldown == 0.0 && to_player.magnitude() < FIRE_RANGE && forward.angle(&to_player) < FIRE_CONE {
+                    drone.cooldown = FIRE_INTERVAL;
+                    let direction = aim.normalize() + random_unit_vector(&mut self.rng) * AIM_ERROR;
+                    self.weapons.shoot(muzzle, direction, drone.body.velocity);
+                }
+            }
+        }
+        crashes
+    }
+
+    // Esferas de los drones en vuelo, en el orden que usa `hit`
+    pub fn colliders(&self) -> Vec<Collider> {
+        self.drones
+            .iter()
+            .filter(|drone| drone.alive())
+            .map(|drone| Collider { center: drone.body.position, radius: DRONE_SCALE, velocity: drone.body.velocity, fatal: false })
+            .collect()
+    }
+
+    // Un láser alcanza al dron `index` de `colliders`. Si se queda sin vida explota: devuelve
+    // dónde y con qué velocidad
+    pub fn hit(&mut self, index: usize) -> Option<(Vec3, Vec3)> {
+        let drone = self.drones.iter_mut().filter(|drone| drone.alive()).nth(index)?;
+        drone.health = drone.health.saturating_sub(1);
+        if drone.health > 0 {
+            return None;
+        }
+        drone.wreck = RESPAWN_TIME;
+        Some((drone.body.position, drone.body.velocity))
+    }
+
+    // Matrices de modelo de los drones en vuelo (la malla de la nave)
+    pub fn model_matrices(&self) -> Vec<Mat4> {
+        self.drones.iter().filter(|drone| drone.alive()).map(|drone| ship_model_matrix(&drone.body, DRONE_SCALE)).collect()
+    }
+}
+
+// Aparece cerca del planeta de su patrulla, parado y mirando hacia él
+fn spawn(rng: &mut SeededRng, planet: &Collider, waypoint: usize) -> Drone {
+    let position = planet.center + random_unit_vector(rng) * (planet.radius * PATROL_DISTANCE + PATROL_MARGIN);
+    let mut body = ShipBody::new(position, nalgebra_glm::quat_identity());
+    face(&mut body, planet.center - position, 1.0);
+    Drone { body, mode: Mode::Patrol, waypoint, orbiting: 0.0, health: HEALTH, cooldown: FIRE_INTERVAL, wreck: 0.0 }
+}
+
+// ============= COMPORTAMIENTOS DE DIRECCIÓN =============
+
+// Hacia `target` a toda velocidad
+fn seek(body: &ShipBody, target: Vec3) -> Vec3 {
+    let desired = (target - body.position).try_normalize(1.0e-6).unwrap_or_else(Vec3::zeros) * MAX_SPEED;
+    desired - body.velocity
+}
+
+// Lejos de `threat` a toda velocidad
+fn flee(body: &ShipBody, threat: Vec3) -> Vec3 {
+    let desired = (body.position - threat).try_normalize(1.0e-6).unwrap_or_else(Vec3::zeros) * MAX_SPEED;
+    desired - body.velocity
+}
+
+// Dar vueltas a `radius` de `center` (que se mueve con `center_velocity`): tangente a la
+// órbita más una corrección hacia el radio
+fn orbit(body: &ShipBody, center: Vec3, radius: f32, center_velocity: Vec3) -> Vec3 {
+    let radial = body.position - center;
+    let distance = radial.magnitude().max(1.0e-3);
+    let outward = radial / distance;
+    let tangent = Vec3::y().cross(&outward).try_normalize(1.0e-6).unwrap_or_else(|| any_perpendicular(outward));
+    let correction = outward * (radius - distance).clamp(-MAX_SPEED, MAX_SPEED);
+    let desired = center_velocity + (tangent * MAX_SPEED * 0.6 + correction).cap_magnitude(MAX_SPEED);
+    desired - body.velocity
+}
+
+// Se aparta de los cuerpos cuya superficie tiene a menos de AVOID_MARGIN, más cuanto más cerca
+fn avoid(body: &ShipBody, bodies: &[Collider]) -> Vec3 {
+    bodies
+        .iter()
+        .map(|collider| {
+            let away = body.position - collider.center;
+            let gap = away.magnitude() - collider.radius - DRONE_SCALE;
+            if gap < AVOID_MARGIN {
+                away.try_normalize(1.0e-6).unwrap_or_else(Vec3::y) * MAX_FORCE * 2.0 * (1.0 - gap.max(0.0) / AVOID_MARGIN)
+            } else {
+                Vec3::zeros()
+            }
+        })
+        .sum()
+}
+
+// Aplica la dirección (limitada a MAX_FORCE) y gira el morro hacia la velocidad
+fn steer(body: &mut ShipBody, steering: Vec3, dt: f32) {
+    body.velocity = (body.velocity + steering.cap_magnitude(MAX_FORCE) * dt).cap_magnitude(MAX_SPEED);
+    body.position += body.velocity * dt;
+    let velocity = body.velocity;
+    face(body, velocity, 1.0 - (-TURN_RATE * dt).exp());
+}
+
+// Gira la nave hacia `direction` una fracción `amount` del camino (1: del todo)
+fn face(body: &mut ShipBody, direction: Vec3, amount: f32) {
+    let Some(forward) = direction.try_normalize(1.0e-3) else {
+        return;
+    };
+    let right = forward.cross(&Vec3::y()).try_normalize(1.0e-6).unwrap_or_else(|| any_perpendicular(forward));
+    let up = right.cross(&forward);
+    let target = nalgebra_glm::mat3_to_quat(&Mat3::from_columns(&[right, up, -forward]));
+    body.orientation = nalgebra_glm::quat_normalize(&nalgebra_glm::quat_slerp(&body.orientation, &target, amount));
+}
+
+fn any_perpendicular(axis: Vec3) -> Vec3 {
+    let helper = if axis.x.abs() < 0.9 { Vec3::x() } else { Vec3::y() };
+    axis.cross(&helper).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn planet(center: Vec3) -> Collider {
+        Collider { center, radius: 20.0, velocity: Vec3::zeros(), fatal: false }
+    }
+
+    fn run(squadron: &mut Squadron, bodies: &[Collider], player: Option<Player>, seconds: f32) {
+        for _ in 0..(seconds / 0.016) as usize {
+            squadron.update(0.016, bodies, &[], player, &[]);
+        }
+    }
+
+    #[test]
+    fn drones_patrol_then_pursue_and_shoot_the_player() {
+        let bodies = [planet(Vec3::new(0.0, 0.0, 0.0)), planet(Vec3::new(3000.0, 0.0, 0.0))];
+        let mut squadron = Squadron::new(7);
+        run(&mut squadron, &bodies, None, 5.0);
+        assert_eq!(squadron.drones.len(), DRONE_COUNT);
+        for drone in &squadron.drones {
+            assert_eq!(drone.mode, Mode::Patrol);
+            // Sin chocar con el planeta: siguen fuera de su superficie
+            let distance = (drone.body.position - bodies[drone.waypoint].center).magnitude();
+            assert!(distance > 20.0 + DRONE_SCALE, "{}", distance);
+        }
+
+        // Un jugador quieto cerca de un dron: lo persigue y le dispara
+        let near = squadron.drones[0].body.position + Vec3::new(0.0, 300.0, 0.0);
+        let player = Player { position: near, velocity: Vec3::zeros() };
+        squadron.weapons.clear();
+        run(&mut squadron, &bodies, Some(player), 4.0);
+        assert_eq!(squadron.drones[0].mode, Mode::Pursue);
+        let targets = [Collider { center: near, radius: 2.0, velocity: Vec3::zeros(), fatal: false }];
+        let mut hit = false;
+        for _ in 0..200 {
+            squadron.update(0.016, &bodies, &[], Some(player), &[]);
+            hit |= !squadron.weapons.update(0.016, &[], &targets).is_empty();
+        }
+        assert!(hit);
+    }
+
+    #[test]
+    fn drones_dodge_incoming_fire_and_die_after_three_hits() {
+        let bodies = [planet(Vec3::zeros())];
+        let mut squadron = Squadron::new(3);
+        squadron.update(0.016, &bodies, &[], None, &[]);
+        let position = squadron.drones[0].body.position;
+        let bolt = (position - Vec3::new(50.0, 0.0, 0.0), Vec3::new(900.0, 0.0, 0.0));
+        squadron.update(0.016, &bodies, &[], None, &[bolt]);
+        assert_eq!(squadron.drones[0].mode, Mode::Evade);
+
+        assert_eq!(squadron.hit(0), None);
+        assert_eq!(squadron.hit(0), None);
+        assert!(squadron.hit(0).is_some());
+        assert_eq!(squadron.colliders().len(), DRONE_COUNT - 1);
+    }
+}
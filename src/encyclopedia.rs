@@ -0,0 +1,273 @@
+use nalgebra_glm::{Vec2, Vec3};
+use crate::draw_state::DrawState;
+use crate::framebuffer::{Framebuffer, NO_OBJECT};
+use crate::scene::RingStyle;
+use crate::text::{draw_styled, draw_text, wrap_text, Align, TextStyle, GLYPH_SIZE};
+use crate::theme;
+use crate::vertex::Vertex;
+use crate::math::{create_normal_matrix, create_viewport_matrix, Backend, MathBackend};
+use crate::{
+    render, render_alien_rings, render_saturn_rings, CelestialObject, Projection, Uniforms,
+};
+
+// ============= ENCICLOPEDIA =============
+// Una página por cuerpo con el texto de los metadatos de la escena y una miniatura
+// renderizada en vivo. Se abre desde el panel de información (Enter).
+
+const THUMBNAIL_SIZE: usize = 280;
+const PANEL_MARGIN_X: usize = 100;
+const PANEL_MARGIN_Y: usize = 70;
+const PADDING: usize = 24;
+const LINE_HEIGHT: usize = GLYPH_SIZE + 5;
+const MIN_TEXT_CHARS: usize = 24;
+
+// Pantalla más pequeña en la que cabe la página: la miniatura con sus dos líneas de datos y
+// una columna de MIN_TEXT_CHARS glifos. En ventanas menores (--width/--height) solo se avisa
+const MIN_SCREEN: (usize, usize) = (
+    PANEL_MARGIN_X * 2 + PADDING * 3 + THUMBNAIL_SIZE + MIN_TEXT_CHARS * GLYPH_SIZE,
+    PANEL_MARGIN_Y * 2 + PADDING * 2 + THUMBNAIL_SIZE + 16 + LINE_HEIGHT * 2,
+);
+
+const THUMBNAIL_BACKGROUND: u32 = 0x000011;
+const TITLE_SHADOW: u32 = 0x3A2A00;
+const HEADING_COLOR: u32 = 0x8FC8FF;
+
+pub struct Encyclopedia {
+    page: Option<usize>,
+    scroll: usize,
+    thumbnail: Framebuffer,
+}
+
+impl Encyclopedia {
+    pub fn new() -> Self {
+        let mut thumbnail = Framebuffer::new(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+        thumbnail.set_background_color(THUMBNAIL_BACKGROUND);
+        Encyclopedia {
+            page: None,
+            scroll: 0,
+            thumbnail,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.page.is_some()
+    }
+
+    pub fn page(&self) -> Option<usize> {
+        self.page
+    }
+
+    pub fn open(&mut self, page: usize) {
+        self.page = Some(page);
+        self.scroll = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.page = None;
+    }
+
+    pub fn next_page(&mut self, page_count: usize) {
+        if let Some(page) = self.page {
+            self.open((page + 1) % page_count.max(1));
+        }
+    }
+
+    pub fn previous_page(&mut self, page_count: usize) {
+        if let Some(page) = self.page {
+            self.open((page + page_count.max(1) - 1) % page_count.max(1));
+        }
+    }
+
+    pub fn scroll_by(&mut self, lines: i32) {
+        self.scroll = (self.scroll as i32 + lines).max(0) as usize;
+    }
+
+    pub fn draw(
+        &mut self,
+        screen: &mut Framebuffer,
+        objects: &[&CelestialObject],
+        light_position: Vec3,
+        time: f32,
+        vertex_array: &[Vertex],
+    ) {
+        let theme = theme::current();
+        let Some(obj) = self.page.and_then(|page| objects.get(page)) else {
+            return;
+        };
+
+        // Oscurecer la escena detrás del panel
+        screen.blend_rect(0, 0, screen.width, screen.height, 0x000000, 0.55);
+
+        if !page_fits(screen.width, screen.height) {
+            let note = format!("Ventana demasiado pequeña para la enciclopedia (mínimo {}x{})", MIN_SCREEN.0, MIN_SCREEN.1);
+            let style = TextStyle::new(theme.text).with_align(Align::Center);
+            draw_styled(screen, screen.width as i32 / 2, (screen.height / 2) as i32, &note, &style);
+            return;
+        }
+
+        let panel_width = screen.width - PANEL_MARGIN_X * 2;
+        let panel_height = screen.height - PANEL_MARGIN_Y * 2;
+        screen.blend_rect(PANEL_MARGIN_X, PANEL_MARGIN_Y, panel_width, panel_height, theme.panel_background, theme.panel_opacity);
+
+        // Miniatura en vivo (columna izquierda)
+        self.render_thumbnail(obj, light_position, time, vertex_array);
+        let thumb_x = PANEL_MARGIN_X + PADDING;
+        let thumb_y = PANEL_MARGIN_Y + PADDING;
+        screen.blit(&self.thumbnail, thumb_x, thumb_y);
+
+        let info_y = (thumb_y + THUMBNAIL_SIZE + 16) as i32;
+        draw_text(screen, thumb_x as i32, info_y, &format!("Radio: {:.0} u", obj.transform.scale), theme.muted, 1);
+        if let Some(orbit) = &obj.orbit {
+            let orbit_text = format!("Órbita: {:.0} u", orbit.radius);
+            draw_text(screen, thumb_x as i32, info_y + LINE_HEIGHT as i32, &orbit_text, theme.muted, 1);
+        }
+
+        // Texto de la página (columna derecha, con scroll)
+        let text_x = thumb_x + THUMBNAIL_SIZE + PADDING;
+        let text_width = PANEL_MARGIN_X + panel_width - PADDING - text_x;
+        let max_chars = text_width / GLYPH_SIZE;
+
+        let mut y = (PANEL_MARGIN_Y + PADDING) as i32;
+        draw_styled(screen, text_x as i32, y, obj.display_name(), &TextStyle::new(theme.title).with_scale(3).with_shadow(TITLE_SHADOW));
+        y += (GLYPH_SIZE * 3 + 14) as i32;
+
+        let lines = page_lines(obj, max_chars);
+        let footer_y = (PANEL_MARGIN_Y + panel_height - PADDING - GLYPH_SIZE) as i32;
+        let visible_lines = ((footer_y - y) as usize / LINE_HEIGHT).saturating_sub(1);
+        self.scroll = self.scroll.min(lines.len().saturating_sub(visible_lines));
+
+        for (line, color) in lines.iter().skip(self.scroll).take(visible_lines) {
+            draw_text(screen, text_x as i32, y, line, *color, 1);
+            y += LINE_HEIGHT as i32;
+        }
+
+        let footer = format!(
+            "<- ->: anterior/siguiente   Arriba/Abajo: desplazar   Enter: cerrar   ({}/{})",
+            self.page.unwrap_or(0) + 1,
+            objects.len()
+        );
+        draw_text(screen, (PANEL_MARGIN_X + PADDING) as i32, footer_y, &footer, theme.muted, 1);
+    }
+
+    // Renderiza el cuerpo solo, visto desde el lado iluminado y un poco de costado
+    fn render_thumbnail(&mut self, obj: &CelestialObject, light_position: Vec3, time: f32, vertex_array: &[Vertex]) {
+        self.thumbnail.clear();
+
+        let to_light = light_position - obj.transform.translation;
+        let base_dir = if to_light.magnitude() > 1e-3 {
+            to_light.normalize()
+        } else {
+            Vec3::new(0.0, 0.0, 1.0) // El propio Sol
+        };
+        let (sin_a, cos_a) = 0.6f32.sin_cos();
+        let view_dir = Vec3::new(
+            base_dir.x * cos_a - base_dir.z * sin_a,
+            0.35,
+            base_dir.x * sin_a + base_dir.z * cos_a,
+        )
+        .normalize();
+
+        let framing = match obj.rings.map(|rings| rings.style) {
+            Some(RingStyle::Saturn) => 7.0,
+            Some(RingStyle::Wide) => 10.0,
+            None => 3.2,
+        };
+        let camera_position = obj.transform.translation + view_dir * obj.transform.scale * framing;
+        let view_matrix = nalgebra_glm::look_at(&camera_position, &obj.transform.translation, &Vec3::new(0.0, 1.0, 0.0));
+
+        let model_matrix = obj.transform.model_matrix();
+        let projection_matrix = Projection::default().matrix(THUMBNAIL_SIZE as f32, THUMBNAIL_SIZE as f32);
+        let uniforms = Uniforms {
+            view_matrix,
+            projection_matrix,
+            viewport_matrix: create_viewport_matrix(THUMBNAIL_SIZE as f32, THUMBNAIL_SIZE as f32),
+            time,
+            current_shader: obj.shader.body,
+            light_position,
+            camera_position,
+            detail_level: 1.0,
+            // Miniatura de catálogo: todos los cuerpos con la luz de referencia
+            light_intensity: 1.0,
+            model_matrix: Backend::mat4(&model_matrix),
+            mvp_matrix: Backend::mat4(&(projection_matrix * view_matrix * model_matrix)),
+            normal_matrix: Backend::mat3(&create_normal_matrix(&model_matrix)),
+            previous_view_projection: None,
+            previous_mvp_matrix: None,
+            jitter: Vec2::zeros(),
+            object_id: NO_OBJECT,
+            tweaks: obj.shader.tweaks,
+            params: obj.shader.params,
+        };
+
+        render(&mut self.thumbnail, &uniforms, &DrawState::OPAQUE, vertex_array);
+        match obj.rings.map(|rings| rings.style) {
+            Some(RingStyle::Saturn) => render_saturn_rings(&mut self.thumbnail, obj, &uniforms, vertex_array),
+            Some(RingStyle::Wide) => render_alien_rings(&mut self.thumbnail, obj, &uniforms, vertex_array),
+            None => {}
+        }
+    }
+}
+
+fn page_fits(width: usize, height: usize) -> bool {
+    width >= MIN_SCREEN.0 && height >= MIN_SCREEN.1
+}
+
+// Líneas de texto de la página: descripción, artículo, datos curiosos y campos libres
+fn page_lines(obj: &CelestialObject, max_chars: usize) -> Vec<(String, u32)> {
+    let theme = theme::current();
+    let metadata = &obj.metadata;
+    let mut lines: Vec<(String, u32)> = Vec::new();
+
+    for line in wrap_text(&metadata.description, max_chars) {
+        lines.push((line, theme.text));
+    }
+
+    for paragraph in metadata.article.split("\n\n").filter(|p| !p.trim().is_empty()) {
+        lines.push((String::new(), theme.text));
+        for line in wrap_text(paragraph, max_chars) {
+            lines.push((line, theme.text));
+        }
+    }
+
+    if !metadata.facts.is_empty() {
+        lines.push((String::new(), theme.text));
+        lines.push(("Datos curiosos".to_string(), HEADING_COLOR));
+        for fact in &metadata.facts {
+            for (i, line) in wrap_text(fact, max_chars.saturating_sub(2)).into_iter().enumerate() {
+                let prefix = if i == 0 { "- " } else { "  " };
+                lines.push((format!("{}{}", prefix, line), theme.text));
+            }
+        }
+    }
+
+    if !metadata.custom.is_empty() {
+        lines.push((String::new(), theme.text));
+        for (key, value) in &metadata.custom {
+            for line in wrap_text(&format!("{}: {}", key, value), max_chars) {
+                lines.push((line, theme.muted));
+            }
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_needs_room_for_the_thumbnail_and_the_text() {
+        assert!(page_fits(1200, 800));
+        assert!(!page_fits(500, 800));
+        assert!(!page_fits(1200, 200));
+        // Justo en el mínimo las cuentas de draw no se quedan sin sitio
+        let (width, height) = MIN_SCREEN;
+        assert!(page_fits(width, height));
+        let panel_width = width - PANEL_MARGIN_X * 2;
+        let text_x = PANEL_MARGIN_X + PADDING + THUMBNAIL_SIZE + PADDING;
+        assert!((PANEL_MARGIN_X + panel_width - PADDING - text_x) / GLYPH_SIZE >= MIN_TEXT_CHARS);
+        let panel_height = height - PANEL_MARGIN_Y * 2;
+        assert!(PANEL_MARGIN_Y + panel_height - PADDING - GLYPH_SIZE > PANEL_MARGIN_Y + PADDING + GLYPH_SIZE * 3 + 14);
+    }
+}
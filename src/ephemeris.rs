@@ -0,0 +1,222 @@
+use std::f64::consts::PI;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::framebuffer::Framebuffer;
+use crate::text::{draw_text, GLYPH_SIZE};
+use crate::theme::{self, HudLayout, Widget};
+
+// ============= EFEMÉRIDES (--date AAAA-MM-DD) =============
+// Posiciones reales de los planetas a partir de elementos keplerianos aproximados
+// (Standish, JPL: válidos entre 1800 y 2050 con error de minutos de arco). Las distancias
+// de la escena no están a escala, así que solo se usa la longitud eclíptica heliocéntrica:
+// cada planeta se coloca en su órbita de la escena en la dirección real respecto al Sol.
+
+const J2000: f64 = 2_451_545.0;
+const DAYS_PER_CENTURY: f64 = 36_525.0;
+const MIN_DAYS_PER_SECOND: f64 = 1.0 / 16.0;
+const MAX_DAYS_PER_SECOND: f64 = 1024.0;
+
+// Elementos en J2000 y su variación por siglo juliano:
+// a (UA), e, I (°), L longitud media (°), ϖ longitud del perihelio (°), Ω nodo ascendente (°)
+struct Elements {
+    id: &'static str,
+    base: [f64; 6],
+    rate: [f64; 6],
+}
+
+const PLANETS: [Elements; 8] = [
+    Elements {
+        id: "mercury",
+        base: [0.38709927, 0.20563593, 7.00497902, 252.25032350, 77.45779628, 48.33076593],
+        rate: [0.00000037, 0.00001906, -0.00594749, 149472.67411175, 0.16047689, -0.12534081],
+    },
+    Elements {
+        id: "venus",
+        base: [0.72333566, 0.00677672, 3.39467605, 181.97909950, 131.60246718, 76.67984255],
+        rate: [0.00000390, -0.00004107, -0.00078890, 58517.81538729, 0.00268329, -0.27769418],
+    },
+    Elements {
+        id: "earth",
+        base: [1.00000261, 0.01671123, -0.00001531, 100.46457166, 102.93768193, 0.0],
+        rate: [0.00000562, -0.00004392, -0.01294668, 35999.37244981, 0.32327364, 0.0],
+    },
+    Elements {
+        id: "mars",
+        base: [1.52371034, 0.09339410, 1.84969142, -4.55343205, -23.94362959, 49.55953891],
+        rate: [0.00001847, 0.00007882, -0.00813131, 19140.30268499, 0.44441088, -0.29257343],
+    },
+    Elements {
+        id: "jupiter",
+        base: [5.20288700, 0.04838624, 1.30439695, 34.39644051, 14.72847983, 100.47390909],
+        rate: [-0.00011607, -0.00013253, -0.00183714, 3034.74612775, 0.21252668, 0.20469106],
+    },
+    Elements {
+        id: "saturn",
+        base: [9.53667594, 0.05386179, 2.48599187, 49.95424423, 92.59887831, 113.66242448],
+        rate: [-0.00125060, -0.00050991, 0.00193609, 1222.49362201, -0.41897216, -0.28867794],
+    },
+    Elements {
+        id: "uranus",
+        base: [19.18916464, 0.04725744, 0.77263783, 313.23810451, 170.95427630, 74.01692503],
+        rate: [-0.00196176, -0.00004397, -0.00242939, 428.48202785, 0.40805281, 0.04240589],
+    },
+    Elements {
+        id: "neptune",
+        base: [30.06992276, 0.00859048, 1.77004347, -55.12002969, 44.96476227, 131.78422574],
+        rate: [0.00026291, 0.00005105, 0.00035372, 218.45945325, -0.32241464, -0.00508664],
+    },
+];
+
+#[derive(Debug)]
+pub enum DateError {
+    Format(String),
+    OutOfRange(String),
+}
+
+impl fmt::Display for DateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DateError::Format(text) => write!(f, "fecha '{}' inválida: se esperaba AAAA-MM-DD", text),
+            DateError::OutOfRange(text) => write!(f, "la fecha '{}' no existe en el calendario", text),
+        }
+    }
+}
+
+// Día juliano a partir de una fecha "AAAA-MM-DD" del calendario gregoriano (a las 0 h TT)
+pub fn parse_date(text: &str) -> Result<f64, DateError> {
+    let parts: Vec<&str> = text.trim().split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return Err(DateError::Format(text.to_string()));
+    };
+    let parse = |part: &str| part.parse::<i32>().map_err(|_| DateError::Format(text.to_string()));
+    let (year, month, day) = (parse(year)?, parse(month)?, parse(day)?);
+
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return Err(DateError::OutOfRange(text.to_string())),
+    };
+    if !(1..=days_in_month).contains(&day) {
+        return Err(DateError::OutOfRange(text.to_string()));
+    }
+    Ok(julian_day(year, month, day as f64))
+}
+
+// Algoritmo de Meeus (calendario gregoriano)
+fn julian_day(year: i32, month: i32, day: f64) -> f64 {
+    let (year, month) = if month <= 2 { (year - 1, month + 12) } else { (year, month) };
+    let a = (year as f64 / 100.0).floor();
+    let b = 2.0 - a + (a / 4.0).floor();
+    (365.25 * (year as f64 + 4716.0)).floor() + (30.6001 * (month as f64 + 1.0)).floor() + day + b - 1524.5
+}
+
+// Inverso de julian_day: (año, mes, día)
+fn calendar_date(julian_day: f64) -> (i32, i32, i32) {
+    let z = (julian_day + 0.5).floor();
+    let fraction = julian_day + 0.5 - z;
+    let alpha = ((z - 1_867_216.25) / 36_524.25).floor();
+    let a = z + 1.0 + alpha - (alpha / 4.0).floor();
+    let b = a + 1524.0;
+    let c = ((b - 122.1) / 365.25).floor();
+    let d = (365.25 * c).floor();
+    let e = ((b - d) / 30.6001).floor();
+
+    let day = (b - d - (30.6001 * e).floor() + fraction).floor() as i32;
+    let month = if e < 14.0 { e - 1.0 } else { e - 13.0 } as i32;
+    let year = if month > 2 { c - 4716.0 } else { c - 4715.0 } as i32;
+    (year, month, day)
+}
+
+// Posición heliocéntrica eclíptica (UA) de un planeta en un día juliano
+fn heliocentric_position(elements: &Elements, julian_day: f64) -> (f64, f64, f64) {
+    let centuries = (julian_day - J2000) / DAYS_PER_CENTURY;
+    let value = |i: usize| elements.base[i] + elements.rate[i] * centuries;
+    let (a, e) = (value(0), value(1));
+    let inclination = value(2).to_radians();
+    let mean_longitude = value(3);
+    let perihelion = value(4);
+    let node = value(5);
+
+    // Anomalía media en [-180°, 180°] y ecuación de Kepler por Newton
+    let mean_anomaly = ((mean_longitude - perihelion + 180.0).rem_euclid(360.0) - 180.0).to_radians();
+    let mut eccentric = mean_anomaly + e * mean_anomaly.sin();
+    for _ in 0..6 {
+        eccentric -= (eccentric - e * eccentric.sin() - mean_anomaly) / (1.0 - e * eccentric.cos());
+    }
+
+    // Coordenadas en el plano de la órbita y rotación al plano de la eclíptica
+    let x_orbit = a * (eccentric.cos() - e);
+    let y_orbit = a * (1.0 - e * e).sqrt() * eccentric.sin();
+    let (sin_w, cos_w) = (perihelion - node).to_radians().sin_cos();
+    let (sin_node, cos_node) = node.to_radians().sin_cos();
+    let (sin_i, cos_i) = inclination.sin_cos();
+
+    let x = (cos_w * cos_node - sin_w * sin_node * cos_i) * x_orbit
+        + (-sin_w * cos_node - cos_w * sin_node * cos_i) * y_orbit;
+    let y = (cos_w * sin_node + sin_w * cos_node * cos_i) * x_orbit
+        + (-sin_w * sin_node + cos_w * cos_node * cos_i) * y_orbit;
+    let z = sin_w * sin_i * x_orbit + cos_w * sin_i * y_orbit;
+    (x, y, z)
+}
+
+// Reloj de la simulación en días reales: [ y ] cambian cuántos días pasan por segundo
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EphemerisClock {
+    julian_day: f64,
+    days_per_second: f64,
+}
+
+impl EphemerisClock {
+    pub fn new(julian_day: f64) -> Self {
+        EphemerisClock {
+            julian_day,
+            days_per_second: 1.0,
+        }
+    }
+
+    pub fn advance(&mut self, seconds: f32) {
+        self.julian_day += seconds as f64 * self.days_per_second;
+    }
+
+    pub fn faster(&mut self) {
+        self.days_per_second = (self.days_per_second * 2.0).min(MAX_DAYS_PER_SECOND);
+    }
+
+    pub fn slower(&mut self) {
+        self.days_per_second = (self.days_per_second / 2.0).max(MIN_DAYS_PER_SECOND);
+    }
+
+    // Ángulo en la órbita de la escena (plano XZ, eclíptica norte = +Y) para un cuerpo de
+    // la escena, o None si su id no es un planeta con efemérides
+    pub fn orbit_angle(&self, id: &str) -> Option<f32> {
+        let elements = PLANETS.iter().find(|elements| elements.id == id)?;
+        let (x, y, _) = heliocentric_position(elements, self.julian_day);
+        Some((-y).atan2(x).rem_euclid(2.0 * PI) as f32)
+    }
+
+    // Fecha actual y velocidad del reloj (esquina inferior derecha por defecto)
+    pub fn draw(&self, screen: &mut Framebuffer, layout: &mut HudLayout) {
+        const PADDING: usize = 10;
+
+        let (year, month, day) = calendar_date(self.julian_day);
+        let date = format!("{:04}-{:02}-{:02}", year, month, day);
+        let speed = if self.days_per_second >= 1.0 {
+            format!("{:.0} días/s   [ ]: velocidad", self.days_per_second)
+        } else {
+            format!("1/{:.0} día/s   [ ]: velocidad", 1.0 / self.days_per_second)
+        };
+
+        let width = (date.chars().count() * 2).max(speed.chars().count()) * GLYPH_SIZE + PADDING * 2;
+        let height = PADDING * 2 + GLYPH_SIZE * 3 + 6;
+        let Some((x, y)) = layout.place(Widget::Clock, width, height) else {
+            return;
+        };
+        let theme = theme::current();
+        screen.blend_rect(x, y, width, height, theme.panel_background, theme.hud_opacity);
+        draw_text(screen, (x + PADDING) as i32, (y + PADDING) as i32, &date, theme.title, 2);
+        draw_text(screen, (x + PADDING) as i32, (y + PADDING + GLYPH_SIZE * 2 + 6) as i32, &speed, theme.text, 1);
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+use nalgebra_glm::Vec3;
+use crate::celestial_shaders::CelestialBody;
+use crate::comet::Comet;
+use crate::ephemeris::EphemerisClock;
+use crate::framebuffer::Framebuffer;
+use crate::scene::EventConfig;
+use crate::scene_graph::SceneGraph;
+use crate::systems;
+use crate::text::{draw_text, text_width, GLYPH_SIZE};
+use crate::theme;
+use crate::weather::{active_hurricanes, global_dust_level};
+use crate::CelestialObject;
+
+// ============= REGISTRO DE EVENTOS Y LÍNEA DE TIEMPO =============
+// Las órbitas son funciones del tiempo, así que los eventos astronómicos (eclipses,
+// conjunciones, perihelios de cometas) se predicen simulando por adelantado una copia de
+// los cuerpos. Al llegar su momento pasan al registro y aparecen como avisos breves; la
+// línea de tiempo (T) lista los próximos y 1-9 saltan hasta ellos. Las tormentas no se
+// predicen: se registran cuando se forman. Qué se registra se configura en [events].
+
+const HORIZON: f32 = 60.0;        // cuánto tiempo de simulación se predice por adelantado
+const STEP: f32 = 0.05;           // paso de la búsqueda
+const JUMP_LEAD: f32 = 0.3;       // el salto deja un margen para ver el evento empezar
+const HISTORY_SIZE: usize = 50;
+const MAX_TOASTS: usize = 3;
+const TIMELINE_ROWS: usize = 9;
+
+const PADDING: usize = 12;
+const LINE_HEIGHT: usize = GLYPH_SIZE + 4;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum EventKind {
+    Eclipse,
+    Conjunction,
+    Perihelion,
+    Storm,
+}
+
+impl EventKind {
+    fn color(self) -> u32 {
+        match self {
+            EventKind::Eclipse => 0xB8A0FF,
+            EventKind::Conjunction => 0x7FE0FF,
+            EventKind::Perihelion => 0x9FC8FF,
+            EventKind::Storm => 0xFFB070,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SimEvent {
+    pub time: f32,
+    pub kind: EventKind,
+    pub text: String,
+    pub focus: Option<usize>, // cuerpo que se selecciona al saltar al evento
+}
+
+pub struct EventLog {
+    config: EventConfig,
+    upcoming: Vec<SimEvent>, // predichos, ordenados por tiempo
+    predicted_until: f32,
+    history: VecDeque<SimEvent>,
+    toasts: VecDeque<(SimEvent, Instant)>,
+    active_storms: Vec<&'static str>,
+    global_dust: bool,
+    timeline_open: bool,
+}
+
+impl EventLog {
+    pub fn new(config: EventConfig) -> Self {
+        EventLog {
+            config,
+            upcoming: Vec::new(),
+            predicted_until: f32::NEG_INFINITY,
+            history: VecDeque::new(),
+            toasts: VecDeque::new(),
+            active_storms: Vec::new(),
+            global_dust: false,
+            timeline_open: false,
+        }
+    }
+
+    pub fn toggle_timeline(&mut self) {
+        self.timeline_open = !self.timeline_open;
+    }
+
+    pub fn timeline_open(&self) -> bool {
+        self.timeline_open
+    }
+
+    // Cada frame: amplía la predicción si hace falta y registra lo que ya ocurrió
+    pub fn update(&mut self, time: f32, objects: &[CelestialObject], graph: &SceneGraph, comets: &[Comet], clock: Option<&EphemerisClock>) {
+        if time + HORIZON / 2.0 > self.predicted_until {
+            let start = self.predicted_until.max(time);
+            let end = time + HORIZON;
+            let mut forecast = Forecast::new(objects, graph, clock, time);
+            self.upcoming.extend(self.predict(start..end, &mut forecast, comets));
+            self.upcoming.sort_by(|a, b| a.time.total_cmp(&b.time));
+            self.predicted_until = end;
+        }
+
+        let due = self.upcoming.iter().take_while(|event| event.time <= time).count();
+        let happened: Vec<SimEvent> = self.upcoming.drain(..due).collect();
+        for event in happened {
+            self.record(event);
+        }
+
+        if self.config.storms {
+            self.record_storms(time, objects);
+        }
+    }
+
+    // Índice i (0-8) de la línea de tiempo: devuelve el evento y el tiempo al que saltar.
+    // Los eventos intermedios se descartan sin registrarse
+    pub fn jump_to(&mut self, index: usize) -> Option<(SimEvent, f32)> {
+        let event = self.upcoming.get(index)?.clone();
+        let target = event.time - JUMP_LEAD;
+        self.skip_to(target);
+        Some((event, target))
+    }
+
+    // Tras un salto en el tiempo, lo que quedó atrás no se registra
+    pub fn skip_to(&mut self, time: f32) {
+        self.upcoming.retain(|event| event.time > time);
+    }
+
+    // Al cargar una partida el tiempo puede ir hacia atrás: se vuelve a predecir desde `time`
+    pub fn resume_at(&mut self, time: f32) {
+        self.upcoming.clear();
+        self.predicted_until = time;
+    }
+
+    fn record(&mut self, event: SimEvent) {
+        if self.config.toasts {
+            self.toasts.push_back((event.clone(), Instant::now()));
+            while self.toasts.len() > MAX_TOASTS {
+                self.toasts.pop_front();
+            }
+        }
+        self.history.push_back(event);
+        while self.history.len() > HISTORY_SIZE {
+            self.history.pop_front();
+        }
+    }
+
+    // Huracanes que acaban de formarse y comienzo de una tormenta de polvo global en Marte
+    fn record_storms(&mut self, time: f32, objects: &[CelestialObject]) {
+        let find = |body: CelestialBody| objects.iter().position(|obj| obj.shader.body == body);
+
+        let storms = active_hurricanes(time);
+        let names: Vec<&'static str> = storms.iter().map(|storm| storm.name).collect();
+        let previous = std::mem::replace(&mut self.active_storms, names);
+        for storm in storms.iter().filter(|storm| !previous.contains(&storm.name)) {
+            self.record(SimEvent {
+                time,
+                kind: EventKind::Storm,
+                text: format!("Se forma el huracán {} en la Tierra", storm.name),
+                focus: find(CelestialBody::Earth),
+            });
+        }
+
+        let global_dust = global_dust_level(time) > 0.0;
+        if global_dust && !self.global_dust {
+            self.record(SimEvent {
+                time,
+                kind: EventKind::Storm,
+                text: "Comienza una tormenta de polvo global en Marte".to_string(),
+                focus: find(CelestialBody::Mars),
+            });
+        }
+        self.global_dust = global_dust;
+    }
+
+    // Simula la copia de los cuerpos entre `start` y `end` buscando el inicio de cada evento
+    fn predict(&self, window: Range<f32>, forecast: &mut Forecast, comets: &[Comet]) -> Vec<SimEvent> {
+        let Range { start, end } = window;
+        let mut events = Vec::new();
+
+        let objects = forecast.objects();
+        let sun = objects
+            .iter()
+            .position(|obj| obj.shader.body.is_star())
+            .unwrap_or(0);
+        // Lunas con su planeta y planetas que orbitan el centro del sistema
+        let moons: Vec<(usize, usize)> = (0..objects.len())
+            .filter_map(|index| Some((index, forecast.parent_of(index)?)))
+            .filter(|&(_, planet)| planet != sun)
+            .collect();
+        let planets: Vec<usize> = (0..objects.len())
+            .filter(|&index| index != sun && objects[index].orbit.is_some())
+            .filter(|&index| !moons.iter().any(|&(moon, _)| moon == index))
+            .collect();
+        let pairs: Vec<(usize, usize)> = planets
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &a)| planets[i + 1..].iter().map(move |&b| (a, b)))
+            .collect();
+
+        let mut eclipsed = vec![false; moons.len()];
+        let mut aligned = vec![false; pairs.len()];
+        let steps = ((end - start) / STEP).ceil() as usize;
+        for step in 0..=steps {
+            // El primer paso solo fija el estado de partida (un evento en curso no se repite)
+            let t = start - STEP + step as f32 * STEP;
+            let objects = forecast.at(t);
+            let sun_position = objects[sun].transform.translation;
+
+            for (i, &(moon, planet)) in moons.iter().enumerate() {
+                let (moon, planet) = (&objects[moon], &objects[planet]);
+                let shadow = (planet.transform.translation - sun_position).normalize();
+                let offset = moon.transform.translation - planet.transform.translation;
+                let along = offset.dot(&shadow);
+                let inside = (offset - shadow * along).magnitude() < planet.transform.scale + moon.transform.scale;
+                if inside && !eclipsed[i] && step > 0 && self.config.eclipses {
+                    let text = if along < 0.0 {
+                        format!("Eclipse solar en {} (lo cubre {})", planet.display_name(), moon.display_name())
+                    } else {
+                        format!("Eclipse de {} en la sombra de {}", moon.display_name(), planet.display_name())
+                    };
+                    events.push(SimEvent { time: t, kind: EventKind::Eclipse, text, focus: Some(moons[i].1) });
+                }
+                eclipsed[i] = inside;
+            }
+
+            for (i, &(a, b)) in pairs.iter().enumerate() {
+                let separation = angle_between(objects[a].transform.translation - sun_position, objects[b].transform.translation - sun_position);
+                let inside = separation < self.config.conjunction_angle;
+                if inside && !aligned[i] && step > 0 && self.config.conjunctions {
+                    let text = format!(
+                        "Conjunción de {} y {} vistos desde el Sol",
+                        objects[a].display_name(),
+                        objects[b].display_name()
+                    );
+                    events.push(SimEvent { time: t, kind: EventKind::Conjunction, text, focus: Some(a) });
+                }
+                aligned[i] = inside;
+            }
+        }
+
+        if self.config.perihelia {
+            for comet in comets {
+                let mut t = comet.next_perihelion(start);
+                while t < end {
+                    let text = format!("El cometa {} pasa por el perihelio", comet.name);
+                    events.push(SimEvent { time: t, kind: EventKind::Perihelion, text, focus: None });
+                    t = comet.next_perihelion(t + STEP);
+                }
+            }
+        }
+        events
+    }
+
+    // Avisos de los últimos eventos, centrados arriba; se desvanecen al expirar. Devuelve la
+    // altura a la que puede ir el siguiente aviso
+    pub fn draw_toasts(&mut self, screen: &mut Framebuffer) -> usize {
+        let theme = theme::current();
+        let lifetime = Duration::from_secs_f32(self.config.toast_seconds);
+        self.toasts.retain(|(_, shown)| shown.elapsed() < lifetime);
+        if !theme.toasts {
+            return PADDING;
+        }
+
+        let mut y = PADDING;
+        for (event, shown) in &self.toasts {
+            // Opaco casi todo el tiempo; el último cuarto se desvanece
+            let remaining = 1.0 - shown.elapsed().as_secs_f32() / self.config.toast_seconds;
+            let alpha = (remaining * 4.0).min(1.0);
+            let width = text_width(&event.text, 1) + PADDING * 2;
+            let x = screen.width.saturating_sub(width) / 2;
+            screen.blend_rect(x, y, width, LINE_HEIGHT + PADDING, theme.panel_background, theme.hud_opacity * alpha);
+            screen.blend_rect(x, y, 3, LINE_HEIGHT + PADDING, event.kind.color(), alpha);
+            draw_text(screen, (x + PADDING) as i32, (y + PADDING / 2 + 2) as i32, &event.text, event.kind.color(), 1);
+            y += LINE_HEIGHT + PADDING + 6;
+        }
+        y
+    }
+
+    // Panel a la derecha: próximos eventos numerados y los últimos registrados
+    pub fn draw_timeline(&self, screen: &mut Framebuffer, time: f32) {
+        let theme = theme::current();
+        let upcoming: Vec<(String, u32)> = self
+            .upcoming
+            .iter()
+            .take(TIMELINE_ROWS)
+            .enumerate()
+            .map(|(i, event)| (format!("{}  +{:<5.1} {}", i + 1, event.time - time, event.text), event.kind.color()))
+            .collect();
+        let recent: Vec<(String, u32)> = self
+            .history
+            .iter()
+            .rev()
+            .take(5)
+            .map(|event| (format!("   -{:<5.1} {}", (time - event.time).max(0.0), event.text), theme.muted))
+            .collect();
+
+        let mut lines: Vec<(String, u32)> = vec![("Próximos eventos".to_string(), theme.title)];
+        if upcoming.is_empty() {
+            lines.push(("Nada en el horizonte de predicción".to_string(), theme.muted));
+        }
+        lines.extend(upcoming);
+        if !recent.is_empty() {
+            lines.push((String::new(), theme.text));
+            lines.push(("Registro".to_string(), theme.title));
+            lines.extend(recent);
+        }
+        lines.push((String::new(), theme.text));
+        lines.push(("1-9: saltar al evento   T: cerrar".to_string(), theme.text));
+
+        let width = lines.iter().map(|(line, _)| text_width(line, 1)).max().unwrap_or(0) + PADDING * 2;
+        let height = PADDING * 2 + lines.len() * LINE_HEIGHT;
+        let x = screen.width.saturating_sub(width + PADDING);
+        let y = screen.height.saturating_sub(height) / 2;
+        screen.blend_rect(x, y, width, height, theme.panel_background, theme.hud_opacity);
+
+        let mut text_y = (y + PADDING) as i32;
+        for (line, color) in &lines {
+            draw_text(screen, (x + PADDING) as i32, text_y, line, *color, 1);
+            text_y += LINE_HEIGHT as i32;
+        }
+    }
+}
+
+// Copia de los cuerpos que se puede llevar a cualquier instante (predicciones y búsquedas)
+pub struct Forecast {
+    objects: Vec<CelestialObject>,
+    graph: SceneGraph,
+    clock: Option<EphemerisClock>, // reloj en el instante `now`
+    now: f32,
+}
+
+impl Forecast {
+    pub fn new(objects: &[CelestialObject], graph: &SceneGraph, clock: Option<&EphemerisClock>, now: f32) -> Self {
+        Forecast {
+            objects: objects.to_vec(),
+            graph: graph.clone(),
+            clock: clock.cloned(),
+            now,
+        }
+    }
+
+    pub fn objects(&self) -> &[CelestialObject] {
+        &self.objects
+    }
+
+    // Índice del cuerpo alrededor del que orbita (lunas)
+    pub fn parent_of(&self, index: usize) -> Option<usize> {
+        let parent = self.graph.parent(self.objects[index].transform.node?)?;
+        self.objects.iter().position(|obj| obj.transform.node == Some(parent))
+    }
+
+    // Mismo recorrido que el bucle principal: órbitas locales -> grafo -> posiciones de mundo
+    pub fn at(&mut self, time: f32) -> &[CelestialObject] {
+        let clock = self.clock.clone().map(|mut clock| {
+            clock.advance(time - self.now);
+            clock
+        });
+        systems::spin(&mut self.objects);
+        systems::orbits(&mut self.objects, time, clock.as_ref());
+        systems::write_graph(&self.objects, &mut self.graph);
+        self.graph.update_world_transforms();
+        systems::read_graph(&mut self.objects, &self.graph);
+        &self.objects
+    }
+}
+
+// Separación en grados entre dos direcciones
+pub fn angle_between(a: Vec3, b: Vec3) -> f32 {
+    a.normalize().dot(&b.normalize()).clamp(-1.0, 1.0).acos().to_degrees()
+}
@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use crate::celestial_shaders::CelestialBody;
+use crate::draw_state::CullMode;
+use crate::procedural::{kepler_orbit_speed, Biome, ProceduralPlanet, SeededRng};
+use crate::random_system::StarClass;
+use crate::scene::{default_position, BodyConfig, BodyMetadata, EventConfig, Scene};
+
+// ============= SISTEMAS DE EXOPLANETAS (--exoplanets catalogo.csv [--system nombre]) =============
+// Lee un catálogo CSV sencillo con una fila por planeta:
+//   system,star_type,planet,radius_earth,period_days,eq_temp_k
+// (el orden de las columnas lo marca la cabecera; las líneas vacías o que empiezan por '#'
+// se ignoran). Cada sistema se convierte en una escena: la estrella según su tipo espectral
+// y cada planeta con el generador procedural, eligiendo el bioma por su temperatura.
+// Las distancias no están a escala, pero conservan la proporción de la tercera ley de
+// Kepler entre los periodos (a ∝ T^(2/3)).
+
+const COLUMNS: [&str; 6] = ["system", "star_type", "planet", "radius_earth", "period_days", "eq_temp_k"];
+const EARTH_SCALE: f32 = 28.0; // tamaño de la Tierra en la escena
+const MIN_GAP: f32 = 1.5;      // separación mínima entre órbitas vecinas (en suma de radios)
+
+pub struct ExoplanetRow {
+    pub name: String,
+    pub radius: f32, // radios terrestres
+    pub period: f32, // días
+    pub temperature: f32, // temperatura de equilibrio (K)
+}
+
+pub struct ExoplanetSystem {
+    pub name: String,
+    pub star_type: String,
+    pub planets: Vec<ExoplanetRow>,
+}
+
+#[derive(Debug)]
+pub enum CatalogError {
+    Io(std::io::Error),
+    MissingColumn(&'static str),
+    Row { line: usize, message: String },
+    UnknownSystem { name: String, available: Vec<String> },
+    Empty,
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CatalogError::Io(err) => write!(f, "no se pudo leer el catálogo: {}", err),
+            CatalogError::MissingColumn(column) => write!(f, "falta la columna '{}' en la cabecera", column),
+            CatalogError::Row { line, message } => write!(f, "línea {}: {}", line, message),
+            CatalogError::UnknownSystem { name, available } => write!(
+                f,
+                "el catálogo no tiene el sistema '{}' (disponibles: {})",
+                name,
+                available.join(", ")
+            ),
+            CatalogError::Empty => write!(f, "el catálogo no contiene ningún planeta"),
+        }
+    }
+}
+
+// Sistemas del catálogo en el orden en que aparecen
+pub fn load(filename: &str) -> Result<Vec<ExoplanetSystem>, CatalogError> {
+    let contents = fs::read_to_string(filename).map_err(CatalogError::Io)?;
+    parse(&contents)
+}
+
+pub fn parse(contents: &str) -> Result<Vec<ExoplanetSystem>, CatalogError> {
+    let mut rows = contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    let (_, header) = rows.next().ok_or(CatalogError::Empty)?;
+    let header: Vec<&str> = header.split(',').map(str::trim).collect();
+    let mut columns = [0; COLUMNS.len()];
+    for (slot, name) in columns.iter_mut().zip(COLUMNS) {
+        *slot = header
+            .iter()
+            .position(|column| column.eq_ignore_ascii_case(name))
+            .ok_or(CatalogError::MissingColumn(name))?;
+    }
+    let [system, star_type, planet, radius, period, temperature] = columns;
+
+    let mut systems: Vec<ExoplanetSystem> = Vec::new();
+    for (line, row) in rows {
+        let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+        let field = |index: usize| {
+            fields.get(index).copied().filter(|value| !value.is_empty()).ok_or_else(|| CatalogError::Row {
+                line,
+                message: format!("falta el valor de '{}'", header[index]),
+            })
+        };
+        let number = |index: usize| {
+            let value = field(index)?;
+            match value.parse::<f32>() {
+                Ok(number) if number > 0.0 => Ok(number),
+                _ => Err(CatalogError::Row {
+                    line,
+                    message: format!("'{}' debe ser un número positivo (se leyó '{}')", header[index], value),
+                }),
+            }
+        };
+
+        let row = ExoplanetRow {
+            name: field(planet)?.to_string(),
+            radius: number(radius)?,
+            period: number(period)?,
+            temperature: number(temperature)?,
+        };
+        let (system_name, star) = (field(system)?, field(star_type)?);
+        if StarClass::from_spectral_type(star).is_none() {
+            return Err(CatalogError::Row { line, message: format!("tipo espectral desconocido '{}'", star) });
+        }
+
+        match systems.iter_mut().find(|existing| existing.name == system_name) {
+            Some(existing) => existing.planets.push(row),
+            None => systems.push(ExoplanetSystem {
+                name: system_name.to_string(),
+                star_type: star.to_string(),
+                planets: vec![row],
+            }),
+        }
+    }
+
+    if systems.is_empty() {
+        return Err(CatalogError::Empty);
+    }
+    Ok(systems)
+}
+
+// Escena de un sistema del catálogo (el primero si no se indica nombre)
+pub fn import(filename: &str, system: Option<&str>) -> Result<Scene, CatalogError> {
+    let mut systems = load(filename)?;
+    let index = match system {
+        None => 0,
+        Some(name) => systems
+            .iter()
+            .position(|candidate| candidate.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| CatalogError::UnknownSystem {
+                name: name.to_string(),
+                available: systems.iter().map(|candidate| candidate.name.clone()).collect(),
+            })?,
+    };
+    Ok(systems.swap_remove(index).into_scene())
+}
+
+// Semilla estable a partir del nombre (FNV-1a): el mismo planeta sale siempre igual
+fn name_seed(name: &str) -> u64 {
+    name.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3))
+}
+
+// Bioma plausible para una temperatura de equilibrio
+fn biome_for(temperature: f32) -> Biome {
+    match temperature {
+        t if t >= 1000.0 => Biome::Volcanic,
+        t if t >= 500.0 => Biome::Desert,
+        t if t >= 350.0 => Biome::Toxic,
+        t if t >= 200.0 => Biome::Temperate,
+        _ => Biome::Frozen,
+    }
+}
+
+impl ExoplanetRow {
+    // Radio en la escena: comprimido para que los gigantes no tapen el sistema
+    fn scale(&self) -> f32 {
+        (EARTH_SCALE * self.radius.powf(0.35)).clamp(10.0, 60.0)
+    }
+
+    // Shader y anillos: gigantes gaseosos y neptunos usan los shaders del Sistema Solar,
+    // los planetas rocosos salen del generador procedural
+    fn shader(&self, seed: u64) -> (CelestialBody, Option<BodyMetadata>) {
+        if self.radius >= 6.0 {
+            let shader = if seed.is_multiple_of(2) { CelestialBody::Jupiter } else { CelestialBody::Saturn };
+            (shader, None)
+        } else if self.radius >= 2.5 {
+            let shader = if self.temperature < 100.0 { CelestialBody::Uranus } else { CelestialBody::Neptune };
+            (shader, None)
+        } else {
+            let planet = ProceduralPlanet::with_biome(seed, biome_for(self.temperature));
+            (CelestialBody::Procedural(planet), Some(planet.metadata()))
+        }
+    }
+
+    fn metadata(&self, system: &str, procedural: Option<BodyMetadata>) -> BodyMetadata {
+        let kind = match self.radius {
+            r if r >= 6.0 => "Gigante gaseoso",
+            r if r >= 2.5 => "Neptuno templado o subneptuno",
+            r if r >= 1.25 => "Supertierra",
+            _ => "Planeta rocoso",
+        };
+        let mut metadata = procedural.unwrap_or_default();
+        metadata.name = self.name.clone();
+        metadata.description = format!("{} del sistema {}, importado de un catálogo de exoplanetas.", kind, system);
+        metadata.custom.insert("Radio".to_string(), format!("{:.2} R⊕", self.radius));
+        metadata.custom.insert("Periodo orbital".to_string(), format!("{:.2} días", self.period));
+        metadata.custom.insert("Temperatura de equilibrio".to_string(), format!("{:.0} K", self.temperature));
+        metadata
+    }
+}
+
+impl ExoplanetSystem {
+    pub fn into_scene(mut self) -> Scene {
+        let star_class = StarClass::from_spectral_type(&self.star_type).unwrap_or(StarClass::G);
+        let star_scale = star_class.scale();
+        let mut rng = SeededRng::new(name_seed(&self.name));
+
+        let mut star_custom = BTreeMap::new();
+        star_custom.insert("Tipo espectral".to_string(), self.star_type.clone());
+        star_custom.insert("Planetas conocidos".to_string(), self.planets.len().to_string());
+        let mut bodies = vec![BodyConfig {
+            id: "star".to_string(),
+            shader: CelestialBody::Star(star_class),
+            parent: None,
+            position: default_position(),
+            scale: star_scale,
+            orbit_radius: 0.0,
+            orbit_speed: 0.0,
+            orbit_phase: 0.0,
+            rotation_speed: [0.0, 0.005, 0.0],
+            axial_tilt: [0.0; 3],
+            tidally_locked: false,
+            rings: None,
+            model: None,
+            cull: CullMode::default(),
+            metadata: BodyMetadata {
+                name: self.name.clone(),
+                description: format!("Estrella de tipo {}: {}.", self.star_type, star_class.description()),
+                custom: star_custom,
+                ..BodyMetadata::default()
+            },
+            palette: None,
+            script: None,
+        }];
+
+        // El planeta más interior queda justo fuera de la estrella y el resto a la distancia
+        // que marca su periodo; si dos órbitas quedan demasiado juntas se separan
+        self.planets.sort_by(|a, b| a.period.total_cmp(&b.period));
+        let innermost = self.planets[0].period;
+        let base_radius = star_scale + 80.0 * star_class.orbit_spread();
+        let (mut previous_orbit, mut previous_scale) = (0.0f32, 0.0f32);
+        for (index, row) in self.planets.iter().enumerate() {
+            let seed = name_seed(&row.name);
+            let scale = row.scale();
+            let kepler_radius = base_radius * (row.period / innermost).powf(2.0 / 3.0);
+            let orbit_radius = kepler_radius.max(previous_orbit + (previous_scale + scale) * MIN_GAP);
+            (previous_orbit, previous_scale) = (orbit_radius, scale);
+
+            let (shader, procedural) = row.shader(seed);
+            let rings = match shader {
+                CelestialBody::Procedural(planet) => planet.rings,
+                _ => None,
+            };
+            bodies.push(BodyConfig {
+                id: format!("planet-{}", index + 1),
+                shader,
+                parent: None,
+                position: default_position(),
+                scale,
+                orbit_radius,
+                orbit_speed: kepler_orbit_speed(orbit_radius),
+                orbit_phase: rng.range(0.0, std::f32::consts::TAU),
+                rotation_speed: [0.0, rng.range(0.005, 0.03), 0.0],
+                axial_tilt: [0.0; 3],
+                tidally_locked: false,
+                rings,
+                model: None,
+                cull: CullMode::default(),
+                metadata: row.metadata(&self.name, procedural),
+                palette: None,
+                script: None,
+            });
+        }
+
+        Scene { bodies, belts: Vec::new(), comets: Vec::new(), events: EventConfig::default(), tour: Vec::new(), mission: None, nebula: None, skybox: None, star_catalog: None, post: Vec::new() }
+    }
+}
@@ -0,0 +1,142 @@
+use nalgebra_glm::{Qua, Vec3};
+
+// ============= MODELO DE VUELO NEWTONIANO =============
+// La nave del vuelo libre es un cuerpo rígido con seis grados de libertad: los mandos no
+// mueven la nave sino que la empujan. El motor principal acelera hacia delante, los
+// propulsores de maniobra (RCS, más débiles) hacia atrás, a los lados y en vertical, y los
+// pares de giro hacen cabecear, guiñar y alabear. Sin mandos la nave sigue a la deriva con
+// la velocidad que llevaba, frenada solo por un amortiguamiento suave (como el "flight
+// assist" de los simuladores); el giro se amortigua más, así deja de girar al soltar.
+// El limitador de velocidad (K) recorta la velocidad a un máximo, mayor con el turbo.
+//
+// Se integra con pasos de como mucho MAX_STEP segundos (Euler semiimplícito y los
+// amortiguamientos con su exponencial exacta), así el manejo no depende de los FPS.
+
+pub const MAIN_THRUST: f32 = 400.0;    // unidades/s² del motor principal
+pub const RCS_THRUST: f32 = 150.0;     // unidades/s² de los propulsores de maniobra
+pub const BOOST: f32 = 5.0;            // multiplicador del empuje y del límite con Shift
+pub const MAX_SPEED: f32 = 250.0;      // unidades/s con el limitador
+const LINEAR_DAMPING: f32 = 0.35;      // por segundo
+const PITCH_YAW_TORQUE: f32 = 6.0;     // rad/s²
+const ROLL_TORQUE: f32 = 7.0;
+const ANGULAR_DAMPING: f32 = 3.5;      // por segundo: giro máximo ≈ par / amortiguamiento
+const MAX_STEP: f32 = 1.0 / 120.0;
+
+// Mandos de un frame, en ejes locales de la nave (+X derecha, +Y arriba, -Z adelante) y
+// entre -1 y 1 en cada eje
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ShipControls {
+    pub thrust: Vec3,
+    pub torque: Vec3, // cabeceo (X), guiñada (Y), alabeo (Z)
+    pub boost: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ShipBody {
+    pub position: Vec3,
+    pub orientation: Qua<f32>,
+    pub velocity: Vec3,         // mundo, unidades/s
+    pub angular_velocity: Vec3, // ejes locales, rad/s
+    pub limiter: bool,
+}
+
+impl ShipBody {
+    pub fn new(position: Vec3, orientation: Qua<f32>) -> Self {
+        ShipBody {
+            position,
+            orientation,
+            velocity: Vec3::zeros(),
+            angular_velocity: Vec3::zeros(),
+            limiter: true,
+        }
+    }
+
+    // Un eje local en el mundo
+    pub fn axis(&self, local: Vec3) -> Vec3 {
+        nalgebra_glm::quat_rotate_vec3(&self.orientation, &local)
+    }
+
+    // Avanza `dt` segundos con los mandos dados
+    pub fn update(&mut self, controls: &ShipControls, dt: f32) {
+        let steps = (dt / MAX_STEP).ceil().max(1.0);
+        let step = dt / steps;
+        for _ in 0..steps as usize {
+            self.step(controls, step);
+        }
+    }
+
+    fn step(&mut self, controls: &ShipControls, dt: f32) {
+        let boost = if controls.boost { BOOST } else { 1.0 };
+        let thrust = controls.thrust.map(|axis| axis.clamp(-1.0, 1.0));
+        // Hacia delante empuja el motor principal; el resto, los propulsores de maniobra
+        let forward = if thrust.z < 0.0 { MAIN_THRUST } else { RCS_THRUST };
+        let local = Vec3::new(thrust.x * RCS_THRUST, thrust.y * RCS_THRUST, thrust.z * forward) * boost;
+        self.velocity += self.axis(local) * dt;
+        self.velocity *= (-LINEAR_DAMPING * dt).exp();
+        if self.limiter {
+            let limit = MAX_SPEED * boost;
+            if self.velocity.magnitude() > limit {
+                self.velocity = self.velocity.normalize() * limit;
+            }
+        }
+        self.position += self.velocity * dt;
+
+        let torque = controls.torque.map(|axis| axis.clamp(-1.0, 1.0));
+        self.angular_velocity += Vec3::new(torque.x * PITCH_YAW_TORQUE, torque.y * PITCH_YAW_TORQUE, torque.z * ROLL_TORQUE) * dt;
+        self.angular_velocity *= (-ANGULAR_DAMPING * dt).exp();
+        self.rotate(self.angular_velocity * dt);
+    }
+
+    // Gira la nave un vector de rotación en sus ejes locales
+    pub fn rotate(&mut self, rotation: Vec3) {
+        let angle = rotation.magnitude();
+        if angle > 0.0 {
+            let turn = nalgebra_glm::quat_angle_axis(angle, &(rotation / angle));
+            self.orientation = nalgebra_glm::quat_normalize(&(self.orientation * turn));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ship() -> ShipBody {
+        ShipBody::new(Vec3::zeros(), nalgebra_glm::quat_identity())
+    }
+
+    fn fly(dt: f32, seconds: f32, controls: &ShipControls) -> ShipBody {
+        let mut ship = ship();
+        for _ in 0..(seconds / dt).round() as usize {
+            ship.update(controls, dt);
+        }
+        ship
+    }
+
+    #[test]
+    fn handling_does_not_depend_on_the_frame_rate() {
+        let controls = ShipControls { thrust: Vec3::new(0.5, 0.0, -1.0), torque: Vec3::new(0.3, -1.0, 0.5), boost: false };
+        let slow = fly(1.0 / 20.0, 2.0, &controls);
+        let fast = fly(1.0 / 144.0, 2.0, &controls);
+        assert!((slow.position - fast.position).magnitude() < 1.0, "{:?} / {:?}", slow.position, fast.position);
+        assert!((slow.axis(Vec3::z()) - fast.axis(Vec3::z())).magnitude() < 0.01);
+    }
+
+    #[test]
+    fn ship_drifts_and_the_limiter_caps_the_speed() {
+        let mut ship = fly(0.016, 5.0, &ShipControls { thrust: Vec3::new(0.0, 0.0, -1.0), ..ShipControls::default() });
+        assert!((ship.velocity.magnitude() - MAX_SPEED).abs() < 0.5);
+        assert!(ship.velocity.z < 0.0);
+
+        // Sin mandos sigue moviéndose y el giro se para
+        ship.angular_velocity = Vec3::new(0.0, 1.0, 0.0);
+        ship.update(&ShipControls::default(), 2.0);
+        assert!(ship.velocity.magnitude() > MAX_SPEED * 0.4);
+        assert!(ship.angular_velocity.magnitude() < 0.01);
+
+        // Sin limitador el empuje sigue acelerando
+        let mut free = ShipBody { limiter: false, ..ShipBody::new(Vec3::zeros(), nalgebra_glm::quat_identity()) };
+        free.update(&ShipControls { thrust: Vec3::new(0.0, 0.0, -1.0), ..ShipControls::default() }, 5.0);
+        assert!(free.velocity.magnitude() > MAX_SPEED);
+    }
+}
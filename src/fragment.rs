@@ -1,10 +1,12 @@
 use nalgebra_glm::Vec2;
 use crate::color::Color;
+use crate::framebuffer::FULL_COVERAGE;
 
 pub struct Fragment {
     pub position: Vec2,
     pub color: Color,
     pub depth: f32,
+    pub coverage: u8, // muestras del píxel que cubre el triángulo (ver framebuffer.rs)
 }
 
 impl Fragment {
@@ -13,6 +15,12 @@ impl Fragment {
             position: Vec2::new(x, y),
             color,
             depth,
+            coverage: FULL_COVERAGE,
         }
     }
+
+    pub fn with_coverage(mut self, coverage: u8) -> Self {
+        self.coverage = coverage;
+        self
+    }
 }
\ No newline at end of file
@@ -1,12 +1,68 @@
 // framebuffer.rs
 
+use nalgebra_glm::{Vec2, Vec3};
+use crate::draw_state::DrawState;
+
+// Profundidad invertida (reverse-Z): 1 en el plano cercano y 0 en el lejano. El float tiene
+// más precisión cerca de 0, justo donde la perspectiva comprime los objetos lejanos, así que
+// planetas y anillos a miles de unidades no se pelean por la profundidad.
+// Un píxel sin geometría conserva FAR_DEPTH; más cerca = mayor profundidad.
+pub const FAR_DEPTH: f32 = 0.0;
+
+// Antialiasing de bordes (MSAA 4x): el rasterizador prueba cada píxel en cuatro puntos y el
+// fragmento lleva en `coverage` un bit por muestra cubierta, pero el shader corre una sola
+// vez por píxel. Solo los píxeles de borde (cobertura parcial) guardan color y profundidad
+// por muestra; en ellos `buffer` tiene la media de las cuatro, que se recalcula al escribir,
+// y `zbuffer` la más lejana, así los pasos que leen la profundidad directamente (cielo,
+// corona, haz de luz) tratan el borde como cielo. El resto de píxeles va por el camino normal
+pub const SAMPLES: usize = 4;
+pub const FULL_COVERAGE: u8 = (1 << SAMPLES) - 1;
+
+// Canales auxiliares (G-buffer) que se escriben junto al color y la profundidad, cada uno
+// solo si alguien lo pide: qué objeto quedó delante en cada píxel (picking, contornos), su
+// normal y su posición en el mundo (efectos que necesitan la geometría de la imagen). Los
+// rellena lo que escribe profundidad; el sombreado es plano, así que son los del triángulo
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Channels {
+    pub object_id: bool,
+    pub normal: bool,
+    pub world_position: bool,
+}
+
+// Identificador de los píxeles sin objeto (el cielo, o lo que no se puede seleccionar)
+pub const NO_OBJECT: u32 = 0;
+
+// Lo que el G-buffer guarda de un fragmento
+#[derive(Clone, Copy)]
+pub struct Surface {
+    pub object: u32,
+    pub normal: Vec3,
+    pub world_position: Vec3,
+}
+
+#[derive(Clone, Copy)]
+struct PixelSamples {
+    color: [u32; SAMPLES],
+    depth: [f32; SAMPLES],
+}
+
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
     pub buffer: Vec<u32>,
     pub zbuffer: Vec<f32>,
     background_color: u32,
-    current_color: u32,
+    multisample: bool,
+    samples: Vec<PixelSamples>, // se reserva con el primer píxel de borde
+    multisampled: Vec<bool>,    // píxeles con muestras propias en este frame
+    // Cuánto se movió en pantalla desde el frame anterior la geometría de cada píxel (para el
+    // antialiasing temporal y las estelas, ver motion.rs); None donde nada escribió profundidad.
+    // Vacío si nadie lo usa
+    velocity: Vec<Option<Vec2>>,
+    // G-buffer: cada canal está vacío mientras no se pida
+    object_ids: Vec<u32>,
+    normals: Vec<Option<Vec3>>,
+    world_positions: Vec<Option<Vec3>>,
 }
 
 impl Framebuffer {
@@ -15,9 +71,15 @@ impl Framebuffer {
             width,
             height,
             buffer: vec![0; width * height],
-            zbuffer: vec![f32::INFINITY; width * height],
+            zbuffer: vec![FAR_DEPTH; width * height],
             background_color: 0x000000,
-            current_color: 0xFFFFFF,
+            multisample: false,
+            samples: Vec::new(),
+            multisampled: Vec::new(),
+            velocity: Vec::new(),
+            object_ids: Vec::new(),
+            normals: Vec::new(),
+            world_positions: Vec::new(),
         }
     }
 
@@ -26,25 +88,212 @@ impl Framebuffer {
             *pixel = self.background_color;
         }
         for depth in self.zbuffer.iter_mut() {
-            *depth = f32::INFINITY;
+            *depth = FAR_DEPTH;
+        }
+        self.multisampled.fill(false);
+        self.velocity.fill(None);
+        self.object_ids.fill(NO_OBJECT);
+        self.normals.fill(None);
+        self.world_positions.fill(None);
+    }
+
+    // Con el antialiasing activado el rasterizador calcula la cobertura de cada muestra
+    pub fn set_multisample(&mut self, enabled: bool) {
+        self.multisample = enabled;
+    }
+
+    pub fn multisample(&self) -> bool {
+        self.multisample
+    }
+
+    // Con el antialiasing temporal o las estelas se guarda la velocidad de cada píxel
+    pub fn set_motion_vectors(&mut self, enabled: bool) {
+        if !enabled {
+            self.velocity = Vec::new();
+        } else if self.velocity.len() != self.buffer.len() {
+            self.velocity = vec![None; self.buffer.len()];
+        }
+    }
+
+    pub fn motion_vectors(&self) -> bool {
+        !self.velocity.is_empty()
+    }
+
+    pub fn velocity(&self, x: usize, y: usize) -> Option<Vec2> {
+        self.velocity.get(y * self.width + x).copied().flatten()
+    }
+
+    pub fn set_velocity(&mut self, x: usize, y: usize, velocity: Vec2) {
+        if let Some(pixel) = self.velocity.get_mut(y * self.width + x) {
+            *pixel = Some(velocity);
+        }
+    }
+
+    // Reserva los canales pedidos y libera los demás
+    pub fn set_channels(&mut self, channels: Channels) {
+        let size = self.buffer.len();
+        resize_channel(&mut self.object_ids, channels.object_id, size, NO_OBJECT);
+        resize_channel(&mut self.normals, channels.normal, size, None);
+        resize_channel(&mut self.world_positions, channels.world_position, size, None);
+    }
+
+    pub fn channels(&self) -> Channels {
+        Channels {
+            object_id: !self.object_ids.is_empty(),
+            normal: !self.normals.is_empty(),
+            world_position: !self.world_positions.is_empty(),
+        }
+    }
+
+    // Objeto delante en el píxel (NO_OBJECT sin canal o fuera de la imagen)
+    pub fn object_id(&self, x: usize, y: usize) -> u32 {
+        self.object_ids.get(y * self.width + x).copied().unwrap_or(NO_OBJECT)
+    }
+
+    pub fn normal(&self, x: usize, y: usize) -> Option<Vec3> {
+        self.normals.get(y * self.width + x).copied().flatten()
+    }
+
+    pub fn world_position(&self, x: usize, y: usize) -> Option<Vec3> {
+        self.world_positions.get(y * self.width + x).copied().flatten()
+    }
+
+    // Guarda la superficie del píxel en los canales que estén activos
+    pub fn set_surface(&mut self, x: usize, y: usize, surface: &Surface) {
+        let index = y * self.width + x;
+        if let Some(object) = self.object_ids.get_mut(index) {
+            *object = surface.object;
+        }
+        if let Some(normal) = self.normals.get_mut(index) {
+            *normal = Some(surface.normal);
+        }
+        if let Some(position) = self.world_positions.get_mut(index) {
+            *position = Some(surface.world_position);
+        }
+    }
+
+    // Escribe un fragmento con la prueba de profundidad, la mezcla y la escritura de
+    // profundidad que indique el estado de la llamada de dibujo
+    pub fn draw_fragment(&mut self, x: usize, y: usize, depth: f32, color: u32, state: &DrawState) {
+        self.draw_coverage(x, y, depth, color, FULL_COVERAGE, state);
+    }
+
+    // Igual que draw_fragment, solo en las muestras de `coverage`. Devuelve si alguna muestra
+    // pasó la prueba de profundidad
+    pub fn draw_coverage(&mut self, x: usize, y: usize, depth: f32, color: u32, coverage: u8, state: &DrawState) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let index = y * self.width + x;
+        // Por encima de 1 el fragmento queda delante del plano cercano (o detrás de la cámara)
+        if depth > 1.0 {
+            return false;
+        }
+        if coverage != FULL_COVERAGE || self.multisampled.get(index).copied().unwrap_or(false) {
+            return self.draw_samples(index, depth, color, coverage, state);
+        }
+        if state.depth_test && depth <= self.zbuffer[index] {
+            return false;
+        }
+        self.buffer[index] = state.blend.apply(color, self.buffer[index]);
+        if state.depth_write {
+            self.zbuffer[index] = depth;
+        }
+        true
+    }
+
+    // Píxel de borde: prueba y mezcla por muestra y vuelve a resolver el píxel
+    fn draw_samples(&mut self, index: usize, depth: f32, color: u32, coverage: u8, state: &DrawState) -> bool {
+        if self.multisampled.len() != self.buffer.len() {
+            self.samples = vec![PixelSamples { color: [0; SAMPLES], depth: [FAR_DEPTH; SAMPLES] }; self.buffer.len()];
+            self.multisampled = vec![false; self.buffer.len()];
+        }
+        // La primera vez en el frame, las cuatro muestras parten de lo que ya tenía el píxel
+        if !self.multisampled[index] {
+            self.multisampled[index] = true;
+            self.samples[index] = PixelSamples { color: [self.buffer[index]; SAMPLES], depth: [self.zbuffer[index]; SAMPLES] };
+        }
+
+        let pixel = &mut self.samples[index];
+        let mut written = false;
+        for sample in 0..SAMPLES {
+            if coverage & (1 << sample) == 0 || (state.depth_test && depth <= pixel.depth[sample]) {
+                continue;
+            }
+            pixel.color[sample] = state.blend.apply(color, pixel.color[sample]);
+            if state.depth_write {
+                pixel.depth[sample] = depth;
+            }
+            written = true;
         }
+        if written {
+            self.buffer[index] = average_color(&pixel.color);
+            self.zbuffer[index] = pixel.depth.iter().copied().fold(f32::INFINITY, f32::min);
+        }
+        written
     }
 
-    pub fn point(&mut self, x: usize, y: usize, depth: f32) {
+    // Escritura directa sin prueba de profundidad (para overlays: texto, paneles)
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: u32) {
+        if x < self.width && y < self.height {
+            self.buffer[y * self.width + x] = color;
+        }
+    }
+
+    // Mezcla alfa de un color sobre el píxel existente (alpha en [0, 1])
+    pub fn blend_pixel(&mut self, x: usize, y: usize, color: u32, alpha: f32) {
         if x < self.width && y < self.height {
             let index = y * self.width + x;
-            if self.zbuffer[index] > depth {
-                self.buffer[index] = self.current_color;
-                self.zbuffer[index] = depth;
+            let dst = self.buffer[index];
+            let a = alpha.clamp(0.0, 1.0);
+            let mix = |shift: u32| {
+                let s = ((color >> shift) & 0xFF) as f32;
+                let d = ((dst >> shift) & 0xFF) as f32;
+                ((s * a + d * (1.0 - a)) as u32) << shift
+            };
+            self.buffer[index] = mix(16) | mix(8) | mix(0);
+        }
+    }
+
+    pub fn blend_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: u32, alpha: f32) {
+        let x_end = (x + width).min(self.width);
+        let y_end = (y + height).min(self.height);
+        for py in y..y_end {
+            for px in x..x_end {
+                self.blend_pixel(px, py, color, alpha);
             }
         }
     }
 
+    // Copiar otro framebuffer (p. ej. una miniatura renderizada aparte) en la posición (x, y)
+    pub fn blit(&mut self, src: &Framebuffer, x: usize, y: usize) {
+        for sy in 0..src.height {
+            let dy = y + sy;
+            if dy >= self.height {
+                break;
+            }
+            let row_width = src.width.min(self.width.saturating_sub(x));
+            let dst_start = dy * self.width + x;
+            let src_start = sy * src.width;
+            self.buffer[dst_start..dst_start + row_width]
+                .copy_from_slice(&src.buffer[src_start..src_start + row_width]);
+        }
+    }
+
     pub fn set_background_color(&mut self, color: u32) {
         self.background_color = color;
     }
+}
 
-    pub fn set_current_color(&mut self, color: u32) {
-        self.current_color = color;
+fn resize_channel<T: Clone>(channel: &mut Vec<T>, enabled: bool, size: usize, empty: T) {
+    if !enabled {
+        *channel = Vec::new();
+    } else if channel.len() != size {
+        *channel = vec![empty; size];
     }
 }
+
+fn average_color(colors: &[u32; SAMPLES]) -> u32 {
+    let channel = |shift: u32| (colors.iter().map(|color| (color >> shift) & 0xFF).sum::<u32>() / SAMPLES as u32) << shift;
+    channel(16) | channel(8) | channel(0)
+}
@@ -0,0 +1,523 @@
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode};
+use crate::collision::{self, Collider, Impact};
+use crate::controls::{Action, Controls};
+use crate::docking::{self, Dock};
+use nalgebra_glm::{Mat3, Mat4, Qua, Vec3};
+use serde::Deserialize;
+use crate::flight_model::{ShipBody, ShipControls, BOOST, MAX_SPEED};
+use crate::framebuffer::Framebuffer;
+use crate::input::Input;
+use crate::particles::{Emission, EmitterConfig, ParticleSystem};
+use crate::ship_systems::{ShipSystems, BAR_HEIGHT};
+use crate::spring::Spring;
+use crate::text::{draw_text, GLYPH_SIZE};
+use crate::theme::{self, HudLayout, Widget};
+use crate::weapons::Weapons;
+use crate::{Camera, CelestialObject};
+
+// ============= VUELO LIBRE (F) =============
+// La cámara orbital limita la inclinación y no puede alabear. En vuelo libre la nave es un
+// cuerpo con inercia (ver flight_model.rs): los mandos la empujan y la hacen girar. La orientación
+// es un cuaternión y cada giro se aplica sobre los ejes locales de la nave, así no hay
+// bloqueo de cardán: se puede dar la vuelta completa en cualquier eje y atravesar el
+// cinturón de asteroides como un piloto.
+// Ejes locales (como la cámara de OpenGL): +X derecha, +Y arriba, -Z adelante.
+// La nave (models/airwing.obj, con los colores de su .mtl) va delante de la cámara, que la
+// sigue desde atrás y algo por encima. La cámara no va pegada: su posición, el punto al que
+// mira y su vertical persiguen a los de la nave con muelles críticos (ver spring.rs), así
+// que en los giros bruscos se queda un poco atrás y la alcanza sin oscilar. Los muelles
+// trabajan en coordenadas relativas a la nave para que la velocidad no la deje atrás.
+// Con V la cámara pasa a la cabina (primera persona, fija a la nave y sin dibujar la nave) con
+// el HUD de cockpit.rs; al volver, la cámara sale de la cabina con los mismos muelles.
+// La nave choca con los cuerpos (ver collision.rs): rebota o, si el golpe es fuerte, explota
+// y al poco reaparece parada en el punto de despegue. Espacio dispara los láseres de las alas
+// (ver weapons.rs). Despacio junto a una estación la nave atraca (ver docking.rs). Los
+// motores gastan combustible y los golpes el escudo y el casco (ver ship_systems.rs); sin
+// combustible, al poco la remolcan al punto de despegue.
+
+const MOUSE_SENSITIVITY: f32 = 0.004; // radianes por píxel
+const LOOK_DISTANCE: f32 = 100.0;    // el objetivo de la cámara va por delante de la nave
+const SHIP_SCALE: f32 = 2.0;         // radio de la nave (la malla se normaliza a radio 1)
+const CHASE_DISTANCE: f32 = 7.0;     // la cámara va detrás de la nave...
+const CHASE_HEIGHT: f32 = 2.0;       // ...y algo por encima para verla desde arriba
+const COCKPIT: (f32, f32, f32) = (0.0, 0.5, -1.0); // ojos del piloto en ejes locales
+const RESPAWN_TIME: f32 = 2.0;       // segundos entre la explosión y la nave nueva
+const TOW_TIME: f32 = 5.0;           // segundos a la deriva sin combustible antes del remolque
+
+// Toberas en ejes locales: la parte trasera de las dos góndolas azules de los motores
+const NOZZLES: [(f32, f32, f32); 2] = [(-0.85, 0.0, 0.6), (0.85, 0.0, 0.6)];
+// Cañones láser en ejes locales: bajo la punta de cada ala
+const GUNS: [(f32, f32, f32); 2] = [(-1.3, -0.1, -0.8), (1.3, -0.1, -0.8)];
+const EXHAUST_RAMP: [(f32, u32); 4] = [(0.0, 0xE8F4FF), (0.15, 0x6FA8FF), (0.5, 0x3040A0), (1.0, 0x000000)];
+
+// Escape de los motores: chorro estrecho que sale hacia atrás y se apaga en menos de un segundo
+pub const EXHAUST: EmitterConfig = EmitterConfig {
+    spawn_rate: 240.0, // por tobera a velocidad normal
+    max_particles: 2048,
+    lifetime: (0.3, 0.6),
+    speed: (40.0, 60.0),
+    spread: 0.12,
+    size: (0.35, 1.2),
+    colors: &EXHAUST_RAMP,
+    max_pixels: 24.0,
+};
+
+// Rigidez de los muelles de la cámara de persecución (tabla [flight] de settings.toml): más
+// alta, más pegada a la nave
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct FlightConfig {
+    pub chase_stiffness: f32, // posición de la cámara
+    pub look_stiffness: f32,  // punto al que mira y vertical
+}
+
+impl Default for FlightConfig {
+    fn default() -> Self {
+        FlightConfig { chase_stiffness: 60.0, look_stiffness: 120.0 }
+    }
+}
+
+// Muelles de la cámara, relativos a la posición de la nave
+struct Chase {
+    offset: Spring, // de la nave a la cámara
+    look: Spring,   // de la nave al punto al que mira la cámara
+    up: Spring,
+}
+
+pub struct FreeFlight {
+    ship: ShipBody,
+    previous_position: Vec3,       // antes del último paso, de donde sale el rayo de las colisiones
+    spawn: (Vec3, Qua<f32>),       // punto de despegue, donde reaparece tras explotar
+    wreck: f32,                    // segundos que faltan para reaparecer (0 mientras vuela)
+    tow: Option<f32>,              // sin combustible: segundos que faltan para el remolque
+    respawned: bool,               // ha reaparecido en el último paso
+    systems: ShipSystems,          // combustible, casco y escudo
+    controls: ShipControls,        // mandos de este frame (se sueltan al integrarlos)
+    main_engine: f32,              // empuje del motor principal en el último paso (0, 1 o BOOST)
+    trigger: bool,                 // Espacio pulsado este frame
+    firing: bool,                  // dispara en este frame (el gatillo del último paso)
+    last_mouse: Option<(f32, f32)>, // posición del ratón con el botón derecho pulsado
+    chase: Chase,
+    cockpit: bool,
+    dock: Option<Dock>,            // estación en la que está atracada
+    release: bool,                 // empuje con la nave atracada: suelta amarras
+    may_dock: bool,                // falso tras soltar amarras hasta alejarse de la estación
+}
+
+impl FreeFlight {
+    // Despega desde la vista actual, conservando la dirección de la mirada y la vertical. La
+    // nave aparece delante para que la cámara de persecución arranque donde estaba la orbital
+    pub fn from_camera(camera: &Camera) -> Self {
+        let forward = (camera.target - camera.position).normalize();
+        let right = forward.cross(&camera.up).try_normalize(1.0e-6).unwrap_or_else(|| Vec3::new(1.0, 0.0, 0.0));
+        let up = right.cross(&forward);
+        let basis = Mat3::from_columns(&[right, up, -forward]);
+
+        // La cámara arranca donde estaba la orbital y se coloca detrás de la nave con los muelles
+        let position = camera.position + forward * CHASE_DISTANCE - up * CHASE_HEIGHT;
+        let orientation = nalgebra_glm::mat3_to_quat(&basis);
+        FreeFlight {
+            ship: ShipBody::new(position, orientation),
+            previous_position: position,
+            spawn: (position, orientation),
+            wreck: 0.0,
+            tow: None,
+            respawned: false,
+            systems: ShipSystems::new(),
+            controls: ShipControls::default(),
+            main_engine: 0.0,
+            trigger: false,
+            firing: false,
+            last_mouse: None,
+            chase: Chase {
+                offset: Spring::new(camera.position - position),
+                look: Spring::new(camera.target - position),
+                up: Spring::new(up),
+            },
+            cockpit: false,
+            dock: None,
+            release: false,
+            may_dock: true,
+        }
+    }
+
+    fn axis(&self, local: Vec3) -> Vec3 {
+        self.ship.axis(local)
+    }
+
+    // Hacia donde apunta la proa
+    pub fn forward(&self) -> Vec3 {
+        self.axis(Vec3::new(0.0, 0.0, -1.0))
+    }
+
+    // W/S motor principal y retroceso, A/D y Q/E propulsores laterales y verticales (ejes
+    // locales), flechas cabeceo y guiñada, Z/X alabeo (teclas de controls.toml), Shift turbo,
+    // K limitador de velocidad, Espacio disparar y botón derecho + ratón para mirar
+    pub fn handle_input(&mut self, input: &Input, controls: &Controls) {
+        if input.is_key_pressed(Key::V, KeyRepeat::No) {
+            self.cockpit = !self.cockpit;
+        }
+        if input.is_key_pressed(Key::K, KeyRepeat::No) {
+            self.ship.limiter = !self.ship.limiter;
+        }
+
+        let mut thrust = Vec3::zeros();
+        if controls.is_down(input, Action::MoveForward) {
+            thrust.z -= 1.0;
+        }
+        if controls.is_down(input, Action::MoveBack) {
+            thrust.z += 1.0;
+        }
+        if controls.is_down(input, Action::MoveLeft) {
+            thrust.x -= 1.0;
+        }
+        if controls.is_down(input, Action::MoveRight) {
+            thrust.x += 1.0;
+        }
+        if controls.is_down(input, Action::MoveUp) {
+            thrust.y += 1.0;
+        }
+        if controls.is_down(input, Action::MoveDown) {
+            thrust.y -= 1.0;
+        }
+
+        // Pares de giro: cabeceo (X), guiñada (Y) y alabeo (sobre -Z, la proa)
+        let mut torque = Vec3::zeros();
+        if controls.is_down(input, Action::OrbitUp) {
+            torque.x += 1.0;
+        }
+        if controls.is_down(input, Action::OrbitDown) {
+            torque.x -= 1.0;
+        }
+        if controls.is_down(input, Action::OrbitLeft) {
+            torque.y += 1.0;
+        }
+        if controls.is_down(input, Action::OrbitRight) {
+            torque.y -= 1.0;
+        }
+        if controls.is_down(input, Action::ZoomIn) {
+            torque.z -= 1.0;
+        }
+        if controls.is_down(input, Action::ZoomOut) {
+            torque.z += 1.0;
+        }
+        self.controls = ShipControls { thrust, torque, boost: input.is_key_down(Key::LeftShift) };
+        self.trigger = input.is_key_down(Key::Space);
+
+        // Mirar con el ratón mientras se mantiene el botón derecho (el izquierdo sigue
+        // seleccionando): gira la nave directamente, sin inercia
+        let mouse = input
+            .get_mouse_pos(MouseMode::Pass)
+            .filter(|_| input.get_mouse_down(MouseButton::Right));
+        if let (Some((x, y)), Some((last_x, last_y))) = (mouse, self.last_mouse) {
+            self.ship.rotate(Vec3::new(-(y - last_y), -(x - last_x), 0.0) * MOUSE_SENSITIVITY);
+        }
+        self.last_mouse = mouse;
+    }
+
+    // Integra el vuelo `dt` segundos con los mandos del frame, que después se sueltan (con un
+    // menú abierto no llegan mandos y la nave sigue a la deriva). Tras una explosión solo
+    // corre la cuenta atrás hasta reaparecer
+    pub fn update(&mut self, dt: f32) {
+        self.respawned = false;
+        if self.wrecked() {
+            self.wreck = (self.wreck - dt).max(0.0);
+            if self.wreck == 0.0 {
+                self.respawn();
+            }
+            self.controls = ShipControls::default();
+            self.trigger = false;
+            self.firing = false;
+            return;
+        }
+        // Atracada no se mueve por sí misma: la lleva la estación (ver `moor`), que la reposta
+        if self.dock.is_some() {
+            self.systems.service(dt);
+            self.release = self.controls.thrust != Vec3::zeros();
+            self.controls = ShipControls::default();
+            self.trigger = false;
+            self.firing = false;
+            self.main_engine = 0.0;
+            return;
+        }
+        self.systems.update(dt);
+        self.controls = self.systems.burn(self.controls, dt);
+        if self.systems.out_of_fuel() {
+            let tow = self.tow.unwrap_or(TOW_TIME) - dt;
+            self.tow = Some(tow);
+            if tow <= 0.0 {
+                self.respawn();
+                return;
+            }
+        } else {
+            self.tow = None;
+        }
+        self.previous_position = self.ship.position;
+        self.ship.update(&self.controls, dt);
+        self.main_engine = match (self.controls.thrust.z < 0.0, self.controls.boost) {
+            (false, _) => 0.0,
+            (true, false) => 1.0,
+            (true, true) => BOOST,
+        };
+        self.firing = self.trigger;
+        self.controls = ShipControls::default();
+        self.trigger = false;
+    }
+
+    // Vuelve parada al punto de despegue con los depósitos llenos y el casco reparado
+    fn respawn(&mut self) {
+        let (position, orientation) = self.spawn;
+        self.ship = ShipBody { limiter: self.ship.limiter, ..ShipBody::new(position, orientation) };
+        self.previous_position = position;
+        self.systems = ShipSystems::new();
+        self.tow = None;
+        self.respawned = true;
+    }
+
+    // Choca el último paso con los cuerpos. Un rebote daña el escudo y el casco según cuánto
+    // cambia la velocidad; si la nave explota se queda donde chocó (la cámara mira la
+    // explosión) hasta que reaparece
+    pub fn collide(&mut self, colliders: &[Collider]) -> Option<Impact> {
+        if self.wrecked() || self.dock.is_some() {
+            return None;
+        }
+        let velocity = self.ship.velocity;
+        let impact = match collision::resolve(&mut self.ship, self.previous_position, SHIP_SCALE, colliders) {
+            Some(Impact::Bounce) if self.systems.bump((self.ship.velocity - velocity).magnitude()) => {
+                Some(Impact::Crash { position: self.ship.position, velocity })
+            }
+            impact => impact,
+        };
+        if let Some(Impact::Crash { position, .. }) = impact {
+            self.ship.position = position;
+            self.destroy();
+        }
+        impact
+    }
+
+    // Un láser enemigo: si rompe el casco la nave explota. Devuelve true en ese caso
+    pub fn hit(&mut self, damage: f32) -> bool {
+        if self.wrecked() || !self.systems.damage(damage) {
+            return false;
+        }
+        self.destroy();
+        true
+    }
+
+    // Sin combustible (a la deriva hasta el remolque)
+    pub fn out_of_fuel(&self) -> bool {
+        self.systems.out_of_fuel()
+    }
+
+    // La nave ha vuelto al punto de despegue en este frame (tras explotar o ser remolcada)
+    pub fn respawned(&self) -> bool {
+        self.respawned
+    }
+
+    pub fn wrecked(&self) -> bool {
+        self.wreck > 0.0
+    }
+
+    // Atraque: amarrada, la nave sigue al amarre de la estación hasta que un empuje la suelta;
+    // suelta, atraca si llega despacio junto a una. Devuelve la estación si acaba de atracar
+    pub fn moor(&mut self, objects: &[CelestialObject], bodies: &[Collider]) -> Option<usize> {
+        if let Some(dock) = &self.dock {
+            match dock.berth(objects) {
+                Some(berth) if !self.release => {
+                    self.ship.position = berth;
+                    self.ship.velocity = bodies[dock.body].velocity;
+                    self.previous_position = berth;
+                }
+                berth => {
+                    let position = berth.unwrap_or(self.ship.position);
+                    self.ship.velocity = dock.release_velocity(objects, bodies, position);
+                    self.dock = None;
+                    self.release = false;
+                    self.may_dock = false;
+                }
+            }
+            return None;
+        }
+        if !self.may_dock {
+            self.may_dock = docking::clear_of_stations(objects, self.ship.position);
+            return None;
+        }
+        if self.wrecked() {
+            return None;
+        }
+        let dock = Dock::find(objects, bodies, self.ship.position, self.ship.velocity)?;
+        self.ship.angular_velocity = Vec3::zeros();
+        let body = dock.body;
+        self.dock = Some(dock);
+        Some(body)
+    }
+
+    // La cámara persigue su sitio detrás y por encima de la nave (en sus ejes locales), mirando
+    // por delante de ella; los muelles avanzan `dt` segundos, como el vuelo
+    pub fn place_camera(&mut self, camera: &mut Camera, config: &FlightConfig, dt: f32) {
+        // En la cabina la cámara va fija y los muelles se quedan en ella para salir suavemente
+        if self.cockpit {
+            let (x, y, z) = COCKPIT;
+            let eye = self.axis(Vec3::new(x, y, z));
+            let up = self.axis(Vec3::new(0.0, 1.0, 0.0));
+            let look = eye + self.forward() * LOOK_DISTANCE;
+            self.chase = Chase { offset: Spring::new(eye), look: Spring::new(look), up: Spring::new(up) };
+            camera.place(self.ship.position + eye, self.ship.position + look, up);
+            return;
+        }
+        let offset = self.chase.offset.update(self.axis(Vec3::new(0.0, CHASE_HEIGHT, CHASE_DISTANCE)), config.chase_stiffness, dt);
+        let look = self.chase.look.update(self.forward() * LOOK_DISTANCE, config.look_stiffness, dt);
+        let up = self.chase.up.update(self.axis(Vec3::new(0.0, 1.0, 0.0)), config.look_stiffness, dt);
+        camera.place(self.ship.position + offset, self.ship.position + look, up.try_normalize(1.0e-6).unwrap_or_else(Vec3::y));
+    }
+
+    // Al salir la cámara orbital se queda donde estaba y recupera la vertical del mundo
+    pub fn land_camera(&self, camera: &mut Camera) {
+        camera.place(self.ship.position + self.chase.offset.value(), self.ship.position + self.chase.look.value(), Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    // Estación en la que está atracada (índice en la escena)
+    pub fn docked_at(&self) -> Option<usize> {
+        self.dock.as_ref().map(|dock| dock.body)
+    }
+
+    pub fn cockpit(&self) -> bool {
+        self.cockpit
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.ship.position
+    }
+
+    // Unidades por segundo, en el mundo
+    pub fn velocity(&self) -> Vec3 {
+        self.ship.velocity
+    }
+
+    // Empuje del motor principal de 0 (parado) a 1 (turbo), para el sonido
+    pub fn throttle(&self) -> f32 {
+        self.main_engine / BOOST
+    }
+
+    pub fn ship_matrix(&self) -> Mat4 {
+        ship_model_matrix(&self.ship, SHIP_SCALE)
+    }
+
+    // Suelta el escape de `dt` segundos por las toberas mientras empuja el motor principal (más
+    // con Shift). Las partículas heredan la velocidad de la nave y salen hacia atrás desde ahí
+    pub fn emit_exhaust(&self, exhaust: &mut ParticleSystem, dt: f32) {
+        if self.main_engine == 0.0 {
+            return;
+        }
+        let backward = -self.forward();
+        let mut nozzle = 0;
+        exhaust.emit(dt, NOZZLES.len() as f32 * self.main_engine, |_| {
+            let (x, y, z) = NOZZLES[nozzle % NOZZLES.len()];
+            nozzle += 1;
+            Emission {
+                position: self.ship.position + self.axis(Vec3::new(x, y, z)),
+                direction: backward,
+                velocity: self.ship.velocity,
+                speed_scale: 1.0,
+            }
+        });
+    }
+
+    // La nave explota donde está y reaparece en el punto de despegue tras RESPAWN_TIME
+    pub fn destroy(&mut self) {
+        self.tow = None;
+        self.ship.velocity = Vec3::zeros();
+        self.ship.angular_velocity = Vec3::zeros();
+        self.main_engine = 0.0;
+        self.firing = false;
+        self.wreck = RESPAWN_TIME;
+    }
+
+    // Esfera de la nave para los disparos de los drones (ninguna mientras espera a reaparecer
+    // ni atracada: la estación la protege)
+    pub fn collider(&self) -> Option<Collider> {
+        (!self.wrecked() && self.dock.is_none()).then_some(Collider { center: self.ship.position, radius: SHIP_SCALE, velocity: self.ship.velocity, fatal: false })
+    }
+
+    // Dispara por los cañones mientras se mantiene Espacio (al ritmo de recarga de las armas)
+    pub fn fire(&self, weapons: &mut Weapons) {
+        if self.firing {
+            let muzzles = GUNS.map(|(x, y, z)| self.ship.position + self.axis(Vec3::new(x, y, z)));
+            weapons.fire(&muzzles, self.forward(), self.ship.velocity);
+        }
+    }
+
+    // Cartel con el combustible, el casco y el escudo, la velocidad, los puntos y los controles
+    // (con las teclas de controls.toml)
+    pub fn draw_hud(&self, screen: &mut Framebuffer, layout: &mut HudLayout, score: u32, controls: &Controls) {
+        const PADDING: usize = 10;
+        const LINE_HEIGHT: usize = GLYPH_SIZE + 4;
+
+        let title = "Vuelo libre";
+        let limiter = if self.ship.limiter { format!("límite {:.0}", MAX_SPEED) } else { "sin límite".to_string() };
+        let speed = if self.wrecked() {
+            format!("Nave destruida: reaparece en {:.1} s", self.wreck)
+        } else if let Some(dock) = &self.dock {
+            let service = if self.systems.topped_up() { "lista" } else { "repostando" };
+            format!("Atracada en {}, {} (empuje para soltar amarras)", dock.name, service)
+        } else if let Some(tow) = self.tow {
+            format!("Sin combustible: remolque al despegue en {:.1} s", tow.max(0.0))
+        } else {
+            format!("Velocidad: {:.0} u/s ({}, K)", self.ship.velocity.magnitude(), limiter)
+        };
+        let score = format!("Puntos: {}", score);
+        let thrust = format!(
+            "{} empuje   {} lateral   {} subir/bajar",
+            controls.pair_name(Action::MoveForward, Action::MoveBack),
+            controls.pair_name(Action::MoveLeft, Action::MoveRight),
+            controls.pair_name(Action::MoveUp, Action::MoveDown),
+        );
+        let rotate = format!(
+            "{} cabeceo   {} guiñada   {} alabeo",
+            controls.pair_name(Action::OrbitUp, Action::OrbitDown),
+            controls.pair_name(Action::OrbitLeft, Action::OrbitRight),
+            controls.pair_name(Action::ZoomIn, Action::ZoomOut),
+        );
+        let lines = [
+            speed.as_str(),
+            score.as_str(),
+            thrust.as_str(),
+            rotate.as_str(),
+            "Shift turbo   Espacio disparar   Botón derecho + ratón mirar",
+            "V cabina / tercera persona   F cámara orbital",
+        ];
+
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0).max(title.chars().count() * 2)
+            * GLYPH_SIZE
+            + PADDING * 2;
+        let bars = 3 * BAR_HEIGHT + 6;
+        let height = PADDING * 2 + GLYPH_SIZE * 2 + 8 + bars + lines.len() * LINE_HEIGHT;
+        let Some((x, panel_y)) = layout.place(Widget::Flight, width, height) else {
+            return;
+        };
+        let theme = theme::current();
+        screen.blend_rect(x, panel_y, width, height, theme.panel_background, theme.hud_opacity);
+
+        let text_x = (x + PADDING) as i32;
+        let mut y = (panel_y + PADDING) as i32;
+        draw_text(screen, text_x, y, title, theme.title, 2);
+        y += (GLYPH_SIZE * 2 + 8) as i32;
+        self.systems.draw_bars(screen, x + PADDING, y as usize, width - PADDING * 2);
+        y += bars as i32;
+        for line in lines {
+            draw_text(screen, text_x, y, line, theme.text, 1);
+            y += LINE_HEIGHT as i32;
+        }
+    }
+}
+
+// Matriz de modelo de una nave con la malla de airwing.obj (la del jugador o un dron). Al
+// cargarla, la malla queda con la proa hacia +Z y boca abajo (ver `to_scene` en obj.rs): medio
+// giro sobre X la deja mirando hacia -Z local
+pub fn ship_model_matrix(ship: &ShipBody, scale: f32) -> Mat4 {
+    let model_fix = nalgebra_glm::rotation(std::f32::consts::PI, &Vec3::new(1.0, 0.0, 0.0));
+    nalgebra_glm::translation(&ship.position)
+        * nalgebra_glm::quat_to_mat4(&ship.orientation)
+        * model_fix
+        * nalgebra_glm::scaling(&Vec3::new(scale, scale, scale))
+}
@@ -0,0 +1,124 @@
+use rayon::prelude::*;
+use crate::framebuffer::{Channels, Framebuffer, NO_OBJECT};
+
+// ============= G-BUFFER =============
+// Además del color y la profundidad, el rasterizador puede dejar por píxel qué objeto quedó
+// delante, su normal y su posición en el mundo (ver framebuffer.rs). El canal de objetos está
+// siempre activo: el clic selecciona el cuerpo que se ve en el píxel (picking.rs) en lugar
+// del disco más cercano. Con F4 se ven los canales en lugar de la imagen, uno tras otro.
+
+// Lo que enseña la escena con F4
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum GBufferView {
+    #[default]
+    Off,
+    Objects,
+    Normals,
+    Positions,
+}
+
+// Tamaño de las franjas de la vista de posiciones, en unidades del mundo
+const POSITION_BAND: f32 = 50.0;
+
+impl GBufferView {
+    pub fn next(self) -> Self {
+        match self {
+            GBufferView::Off => GBufferView::Objects,
+            GBufferView::Objects => GBufferView::Normals,
+            GBufferView::Normals => GBufferView::Positions,
+            GBufferView::Positions => GBufferView::Off,
+        }
+    }
+
+    // Canales que hay que escribir este frame (el de objetos, siempre)
+    pub fn channels(self) -> Channels {
+        Channels {
+            object_id: true,
+            normal: self == GBufferView::Normals,
+            world_position: self == GBufferView::Positions,
+        }
+    }
+
+    pub fn label(self) -> Option<&'static str> {
+        match self {
+            GBufferView::Off => None,
+            GBufferView::Objects => Some("G-BUFFER: OBJETOS"),
+            GBufferView::Normals => Some("G-BUFFER: NORMALES"),
+            GBufferView::Positions => Some("G-BUFFER: POSICIONES"),
+        }
+    }
+
+    // Reemplaza la imagen de la escena por el canal elegido (negro donde no hay nada)
+    pub fn draw(self, scene: &mut Framebuffer) {
+        if self == GBufferView::Off {
+            return;
+        }
+        let width = scene.width;
+        let mut buffer = std::mem::take(&mut scene.buffer);
+        let source = &*scene;
+        buffer.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = match self {
+                    GBufferView::Objects => object_color(source.object_id(x, y)),
+                    // Cada eje a un canal: (0.5, 0.5, 0.5) es la normal nula
+                    GBufferView::Normals => source.normal(x, y).map_or(0, |normal| pack(normal.map(|axis| axis * 0.5 + 0.5).into())),
+                    // Franjas cada POSITION_BAND unidades en cada eje
+                    GBufferView::Positions => source
+                        .world_position(x, y)
+                        .map_or(0, |position| pack(position.map(|axis| (axis / POSITION_BAND).rem_euclid(1.0)).into())),
+                    GBufferView::Off => *pixel,
+                };
+            }
+        });
+        scene.buffer = buffer;
+    }
+}
+
+// Un color saturado y estable por objeto (el identificador mezclado con un hash multiplicativo)
+fn object_color(object: u32) -> u32 {
+    if object == NO_OBJECT {
+        return 0x000000;
+    }
+    (object.wrapping_mul(0x9E37_79B9).rotate_left(11) | 0x40_4040) & 0xFF_FFFF
+}
+
+fn pack(color: [f32; 3]) -> u32 {
+    let [r, g, b] = color.map(|channel| (channel.clamp(0.0, 1.0) * 255.0 + 0.5) as u32);
+    (r << 16) | (g << 8) | b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::Surface;
+    use nalgebra_glm::Vec3;
+
+    #[test]
+    fn channels_follow_the_view_and_clear_with_the_frame() {
+        let mut scene = Framebuffer::new(4, 4);
+        scene.set_channels(GBufferView::Normals.channels());
+        let surface = Surface { object: 3, normal: Vec3::new(0.0, 1.0, 0.0), world_position: Vec3::new(1.0, 2.0, 3.0) };
+        scene.set_surface(1, 2, &surface);
+        assert_eq!(scene.object_id(1, 2), 3);
+        assert_eq!(scene.normal(1, 2), Some(surface.normal));
+        // Sin canal de posiciones no se guarda nada
+        assert_eq!(scene.world_position(1, 2), None);
+
+        GBufferView::Normals.draw(&mut scene);
+        assert_eq!(scene.buffer[2 * 4 + 1], 0x80FF80);
+        assert_eq!(scene.buffer[0], 0x000000);
+
+        scene.clear();
+        assert_eq!(scene.object_id(1, 2), NO_OBJECT);
+        assert_eq!(scene.normal(1, 2), None);
+    }
+
+    #[test]
+    fn every_object_is_visible_and_the_sky_is_black() {
+        assert_eq!(object_color(NO_OBJECT), 0x000000);
+        for object in 1..64 {
+            assert_ne!(object_color(object), 0x000000);
+            assert!(object_color(object) <= 0xFF_FFFF);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+// ============= RECARGA EN CALIENTE =============
+// Vigila la escena y el archivo de parámetros de los shaders para volver a aplicarlos sin
+// reiniciar: la cámara y el tiempo de la simulación se conservan. Se vigila la carpeta de
+// cada archivo y no el archivo en sí, porque muchos editores guardan escribiendo un archivo
+// nuevo y renombrándolo encima del viejo (y la vigilancia del original se pierde).
+// Un guardado suele llegar como varios eventos seguidos: poll() los junta en uno por archivo.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Reload {
+    Scene,
+    Params,
+}
+
+struct WatchedFile {
+    directory: PathBuf, // carpeta canónica
+    name: OsString,
+    reload: Reload,
+}
+
+pub struct FileWatcher {
+    _watcher: RecommendedWatcher, // al soltarlo deja de vigilar
+    events: Receiver<notify::Result<Event>>,
+    files: Vec<WatchedFile>,
+}
+
+// Carpeta canónica de un archivo (el archivo puede no existir todavía)
+fn canonical_directory(path: &Path) -> std::io::Result<PathBuf> {
+    match path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        Some(parent) => fs::canonicalize(parent),
+        None => std::env::current_dir(),
+    }
+}
+
+impl FileWatcher {
+    pub fn new(paths: &[(&str, Reload)]) -> notify::Result<Self> {
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+
+        let mut files: Vec<WatchedFile> = Vec::new();
+        for &(path, reload) in paths {
+            let path = Path::new(path);
+            let directory = canonical_directory(path)?;
+            let Some(name) = path.file_name() else {
+                continue;
+            };
+            if !files.iter().any(|file| file.directory == directory) {
+                watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+            }
+            files.push(WatchedFile { directory, name: name.to_os_string(), reload });
+        }
+
+        Ok(FileWatcher { _watcher: watcher, events, files })
+    }
+
+    // Archivos cambiados desde la última llamada (cada uno una sola vez)
+    pub fn poll(&self) -> Vec<Reload> {
+        let mut reloads = Vec::new();
+        for event in self.events.try_iter() {
+            let event = match event {
+                Ok(event) => event,
+                Err(err) => {
+                    eprintln!("Recarga en caliente: {}", err);
+                    continue;
+                }
+            };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                continue;
+            }
+            for path in &event.paths {
+                for file in self.files.iter().filter(|file| path.file_name() == Some(file.name.as_os_str())) {
+                    let same_directory = canonical_directory(path).is_ok_and(|directory| directory == file.directory);
+                    if same_directory && !reloads.contains(&file.reload) {
+                        reloads.push(file.reload);
+                    }
+                }
+            }
+        }
+        reloads
+    }
+}
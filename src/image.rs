@@ -0,0 +1,646 @@
+use std::fmt;
+use std::fs;
+
+// ============= CARGA DE IMÁGENES (PNG Y RADIANCE HDR) =============
+// Decodificadores mínimos para los fondos de la escena:
+// - PNG: escala de grises, RGB, paleta y sus variantes con alfa, de 8 o 16 bits por canal
+//   (sin entrelazado). Los datos comprimidos se descomprimen con un inflate propio.
+// - Radiance HDR (.hdr / .pic): RGBE plano o con la compresión RLE por canales, orientación
+//   estándar "-Y alto +X ancho". Los valores pueden pasar de 1 (luz por encima del blanco).
+// Los píxeles se guardan como RGB en coma flotante; el alfa se descarta.
+// Para las capturas de pantalla también se escriben PNG RGB de 8 bits (deflate sin comprimir).
+
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<(f32, f32, f32)>,
+}
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(std::io::Error),
+    UnknownFormat,
+    Unsupported(String),
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Io(err) => write!(f, "no se pudo leer la imagen: {}", err),
+            ImageError::UnknownFormat => write!(f, "formato de imagen desconocido (se admiten PNG y Radiance HDR)"),
+            ImageError::Unsupported(what) => write!(f, "imagen no admitida: {}", what),
+            ImageError::Corrupt(what) => write!(f, "imagen dañada: {}", what),
+        }
+    }
+}
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+impl Image {
+    pub fn load(filename: &str) -> Result<Self, ImageError> {
+        let data = fs::read(filename).map_err(ImageError::Io)?;
+        if data.starts_with(&PNG_SIGNATURE) {
+            decode_png(&data)
+        } else if data.starts_with(b"#?") {
+            decode_hdr(&data)
+        } else {
+            Err(ImageError::UnknownFormat)
+        }
+    }
+
+    // Muestra con interpolación bilineal; `u` da la vuelta (panoramas) y `v` se recorta.
+    // Las coordenadas van de 0 a 1 sobre toda la imagen
+    pub fn sample(&self, u: f32, v: f32, wrap_u: bool) -> (f32, f32, f32) {
+        let x = u * self.width as f32 - 0.5;
+        let y = (v * self.height as f32 - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let x = if wrap_u { x.rem_euclid(self.width as f32) } else { x.clamp(0.0, (self.width - 1) as f32) };
+        // Justo por debajo de 0, rem_euclid puede redondear a `width`: sigue siendo el último píxel
+        let x0 = (x as usize).min(self.width - 1);
+        let y0 = y as usize;
+        let x1 = if wrap_u { (x0 + 1) % self.width } else { (x0 + 1).min(self.width - 1) };
+        let y1 = (y0 + 1).min(self.height - 1);
+        let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+        let pixel = |px: usize, py: usize| self.pixels[py * self.width + px];
+        let mix = |a: (f32, f32, f32), b: (f32, f32, f32), t: f32| {
+            (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t, a.2 + (b.2 - a.2) * t)
+        };
+        mix(mix(pixel(x0, y0), pixel(x1, y0), tx), mix(pixel(x0, y1), pixel(x1, y1), tx), ty)
+    }
+}
+
+// ============= PNG =============
+
+fn decode_png(data: &[u8]) -> Result<Image, ImageError> {
+    let mut offset = PNG_SIGNATURE.len();
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut compressed = Vec::new();
+
+    // Trozos: longitud, tipo, datos y CRC (el CRC no se comprueba)
+    while offset + 8 <= data.len() {
+        let length = u32::from_be_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]) as usize;
+        let kind = &data[offset + 4..offset + 8];
+        let body = data.get(offset + 8..offset + 8 + length).ok_or(ImageError::Corrupt("trozo PNG truncado"))?;
+        match kind {
+            b"IHDR" if length >= 13 => header = Some(body),
+            b"PLTE" => palette = body,
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        offset += 12 + length;
+    }
+
+    let header = header.ok_or(ImageError::Corrupt("falta la cabecera IHDR"))?;
+    let width = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let height = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    let (bit_depth, color_type, interlaced) = (header[8], header[9], header[12] != 0);
+    if width == 0 || height == 0 {
+        return Err(ImageError::Corrupt("tamaño cero"));
+    }
+    if interlaced {
+        return Err(ImageError::Unsupported("PNG entrelazado (Adam7)".to_string()));
+    }
+    let channels = match color_type {
+        0 => 1, // gris
+        2 => 3, // RGB
+        3 => 1, // índice de paleta
+        4 => 2, // gris + alfa
+        6 => 4, // RGBA
+        _ => return Err(ImageError::Corrupt("tipo de color PNG desconocido")),
+    };
+    if !(bit_depth == 8 || (bit_depth == 16 && color_type != 3)) {
+        return Err(ImageError::Unsupported(format!("PNG de {} bits por canal", bit_depth)));
+    }
+
+    let bytes_per_pixel = channels * bit_depth as usize / 8;
+    let stride = width * bytes_per_pixel;
+    let raw = inflate_zlib(&compressed)?;
+    if raw.len() < (stride + 1) * height {
+        return Err(ImageError::Corrupt("faltan datos de imagen"));
+    }
+
+    // Deshacer el filtro de cada fila (cada una empieza con su tipo de filtro)
+    let mut rows = vec![0u8; stride * height];
+    for y in 0..height {
+        let filter = raw[y * (stride + 1)];
+        let source = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (done, current) = rows.split_at_mut(y * stride);
+        let previous = if y > 0 { &done[(y - 1) * stride..] } else { &[][..] };
+        let current = &mut current[..stride];
+        for x in 0..stride {
+            let left = if x >= bytes_per_pixel { current[x - bytes_per_pixel] } else { 0 };
+            let up = previous.get(x).copied().unwrap_or(0);
+            let up_left = if x >= bytes_per_pixel { previous.get(x - bytes_per_pixel).copied().unwrap_or(0) } else { 0 };
+            let predictor = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(ImageError::Corrupt("filtro PNG desconocido")),
+            };
+            current[x] = source[x].wrapping_add(predictor);
+        }
+    }
+
+    // Canal i del píxel en [0, 1] (en 16 bits se usan los dos bytes)
+    let sample = |pixel: &[u8], i: usize| match bit_depth {
+        16 => u16::from_be_bytes([pixel[i * 2], pixel[i * 2 + 1]]) as f32 / 65535.0,
+        _ => pixel[i] as f32 / 255.0,
+    };
+    let mut pixels = Vec::with_capacity(width * height);
+    for pixel in rows.chunks_exact(bytes_per_pixel) {
+        pixels.push(match color_type {
+            0 | 4 => {
+                let gray = sample(pixel, 0);
+                (gray, gray, gray)
+            }
+            3 => {
+                let entry = palette.get(pixel[0] as usize * 3..pixel[0] as usize * 3 + 3).ok_or(ImageError::Corrupt("índice fuera de la paleta"))?;
+                (entry[0] as f32 / 255.0, entry[1] as f32 / 255.0, entry[2] as f32 / 255.0)
+            }
+            _ => (sample(pixel, 0), sample(pixel, 1), sample(pixel, 2)),
+        });
+    }
+    Ok(Image { width, height, pixels })
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// ============= INFLATE (ZLIB / DEFLATE) =============
+// Bloques sin comprimir, con Huffman fijo y con Huffman dinámico (RFC 1950 y 1951).
+// Los códigos se decodifican de forma canónica, bit a bit, contando cuántos hay de cada longitud
+
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+// Orden en que llegan las longitudes del código de longitudes en un bloque dinámico
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize, // en bits
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: u32) -> Result<u32, ImageError> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = *self.data.get(self.position / 8).ok_or(ImageError::Corrupt("datos comprimidos truncados"))?;
+            value |= (((byte >> (self.position % 8)) & 1) as u32) << i;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+}
+
+struct Huffman {
+    counts: [u16; 16],  // cuántos códigos hay de cada longitud
+    symbols: Vec<u16>,  // símbolos ordenados por código
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, ImageError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(ImageError::Corrupt("código Huffman inválido"))
+    }
+}
+
+fn inflate_zlib(data: &[u8]) -> Result<Vec<u8>, ImageError> {
+    // Cabecera zlib: método 8 (deflate) y sin diccionario predefinido
+    if data.len() < 2 || data[0] & 0x0F != 8 || data[1] & 0x20 != 0 {
+        return Err(ImageError::Corrupt("cabecera zlib inválida"));
+    }
+    let mut reader = BitReader { data: &data[2..], position: 0 };
+    let mut output = Vec::new();
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align_to_byte();
+                let length = reader.bits(16)? as usize;
+                reader.bits(16)?; // complemento de la longitud
+                let start = reader.position / 8;
+                let block = reader.data.get(start..start + length).ok_or(ImageError::Corrupt("bloque sin comprimir truncado"))?;
+                output.extend_from_slice(block);
+                reader.position += length * 8;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                inflate_block(&mut reader, &mut output, &Huffman::new(&lengths), &Huffman::new(&[5; 30]))?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_tables(&mut reader)?;
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            _ => return Err(ImageError::Corrupt("tipo de bloque deflate inválido")),
+        }
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+fn read_dynamic_tables(reader: &mut BitReader) -> Result<(Huffman, Huffman), ImageError> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+    let mut code_lengths = [0u8; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[index] = reader.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths);
+
+    // Longitudes de los dos códigos, con repeticiones (16) y rachas de ceros (17 y 18)
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match code_length_code.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last().ok_or(ImageError::Corrupt("repetición sin longitud previa"))?, 3 + reader.bits(2)?),
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > literal_count + distance_count {
+        return Err(ImageError::Corrupt("demasiadas longitudes de código"));
+    }
+    Ok((Huffman::new(&lengths[..literal_count]), Huffman::new(&lengths[literal_count..])))
+}
+
+fn inflate_block(reader: &mut BitReader, output: &mut Vec<u8>, literals: &Huffman, distances: &Huffman) -> Result<(), ImageError> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let index = symbol - 257;
+                if index >= LENGTH_BASE.len() {
+                    return Err(ImageError::Corrupt("longitud deflate inválida"));
+                }
+                let length = LENGTH_BASE[index] as usize + reader.bits(LENGTH_EXTRA[index] as u32)? as usize;
+                let index = distances.decode(reader)? as usize;
+                if index >= DISTANCE_BASE.len() {
+                    return Err(ImageError::Corrupt("distancia deflate inválida"));
+                }
+                let distance = DISTANCE_BASE[index] as usize + reader.bits(DISTANCE_EXTRA[index] as u32)? as usize;
+                if distance > output.len() {
+                    return Err(ImageError::Corrupt("distancia fuera de los datos"));
+                }
+                // Copia byte a byte: la copia puede solaparse con lo que va escribiendo
+                let start = output.len() - distance;
+                for i in 0..length {
+                    output.push(output[start + i]);
+                }
+            }
+        }
+    }
+}
+
+// ============= ESCRITURA DE PNG (CAPTURAS) =============
+// Sin compresor: los datos van en bloques deflate "almacenados" de hasta 65535 bytes, que
+// cualquier lector de PNG acepta. Una captura de 1200x800 ocupa unos 2.9 MB.
+
+const STORED_BLOCK: usize = 65535;
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+// Guarda un buffer 0xRRGGBB (como el del framebuffer) en un PNG RGB
+pub fn save_png(filename: &str, width: usize, height: usize, pixels: &[u32]) -> std::io::Result<()> {
+    fs::write(filename, encode_png(width, height, pixels))
+}
+
+fn encode_png(width: usize, height: usize, pixels: &[u32]) -> Vec<u8> {
+    // Cada fila empieza con su filtro (0 = ninguno)
+    let mut raw = Vec::with_capacity(height * (width * 3 + 1));
+    for row in pixels.chunks(width).take(height) {
+        raw.push(0);
+        for &pixel in row {
+            raw.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8]);
+        }
+    }
+    png_from_rows(width, height, &raw)
+}
+
+// PNG RGB de 8 bits con las filas ya filtradas
+fn png_from_rows(width: usize, height: usize, raw: &[u8]) -> Vec<u8> {
+    let mut zlib = vec![0x78, 0x01];
+    let blocks = raw.chunks(STORED_BLOCK).count().max(1);
+    for (index, block) in raw.chunks(STORED_BLOCK).enumerate() {
+        zlib.push((index + 1 == blocks) as u8);
+        zlib.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        zlib.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]); // 8 bits, RGB, deflate, filtro por filas, sin entrelazado
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    push_chunk(&mut png, b"IHDR", &header);
+    push_chunk(&mut png, b"IDAT", &zlib);
+    push_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+// ============= RADIANCE HDR (RGBE) =============
+
+fn decode_hdr(data: &[u8]) -> Result<Image, ImageError> {
+    // Cabecera de texto hasta una línea vacía, y después la línea de resolución
+    let mut offset = 0;
+    let next_line = |offset: &mut usize| -> Result<String, ImageError> {
+        let end = data[*offset..].iter().position(|&b| b == b'\n').ok_or(ImageError::Corrupt("cabecera HDR incompleta"))?;
+        let line = String::from_utf8_lossy(&data[*offset..*offset + end]).trim().to_string();
+        *offset += end + 1;
+        Ok(line)
+    };
+    loop {
+        let line = next_line(&mut offset)?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some(format) = line.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(ImageError::Unsupported(format!("HDR en formato {}", format)));
+            }
+        }
+    }
+    let resolution = next_line(&mut offset)?;
+    let parts: Vec<&str> = resolution.split_whitespace().collect();
+    let (height, width) = match parts.as_slice() {
+        ["-Y", height, "+X", width] => (
+            height.parse::<usize>().map_err(|_| ImageError::Corrupt("resolución HDR inválida"))?,
+            width.parse::<usize>().map_err(|_| ImageError::Corrupt("resolución HDR inválida"))?,
+        ),
+        _ => return Err(ImageError::Unsupported(format!("orientación HDR '{}'", resolution))),
+    };
+    if width == 0 || height == 0 {
+        return Err(ImageError::Corrupt("tamaño cero"));
+    }
+
+    let mut pixels = Vec::with_capacity(width * height);
+    let mut scanline = vec![[0u8; 4]; width];
+    for _ in 0..height {
+        let start = data.get(offset..offset + 4).ok_or(ImageError::Corrupt("faltan filas HDR"))?;
+        let run_length = (8..0x8000).contains(&width) && start[0] == 2 && start[1] == 2 && start[2] & 0x80 == 0;
+        if run_length {
+            // RLE por canales: primero todos los R de la fila, luego G, B y el exponente
+            offset += 4;
+            for channel in 0..4 {
+                let mut x = 0;
+                while x < width {
+                    let count = *data.get(offset).ok_or(ImageError::Corrupt("fila HDR truncada"))? as usize;
+                    offset += 1;
+                    if count > 128 {
+                        let value = *data.get(offset).ok_or(ImageError::Corrupt("fila HDR truncada"))?;
+                        offset += 1;
+                        for pixel in scanline.iter_mut().skip(x).take(count - 128) {
+                            pixel[channel] = value;
+                        }
+                        x += count - 128;
+                    } else {
+                        let values = data.get(offset..offset + count).ok_or(ImageError::Corrupt("fila HDR truncada"))?;
+                        for (pixel, &value) in scanline.iter_mut().skip(x).zip(values) {
+                            pixel[channel] = value;
+                        }
+                        offset += count;
+                        x += count;
+                    }
+                }
+            }
+        } else {
+            let row = data.get(offset..offset + width * 4).ok_or(ImageError::Corrupt("fila HDR truncada"))?;
+            for (pixel, rgbe) in scanline.iter_mut().zip(row.chunks_exact(4)) {
+                pixel.copy_from_slice(rgbe);
+            }
+            offset += width * 4;
+        }
+        pixels.extend(scanline.iter().map(|&[r, g, b, e]| {
+            if e == 0 {
+                return (0.0, 0.0, 0.0);
+            }
+            // Mantisas de 8 bits con un exponente compartido
+            let scale = 2f32.powi(e as i32 - 136);
+            (r as f32 * scale, g as f32 * scale, b as f32 * scale)
+        }));
+    }
+    Ok(Image { width, height, pixels })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Imagen de prueba con los tres canales distintos en cada píxel
+    fn test_pixels(width: usize, height: usize) -> Vec<u32> {
+        (0..width * height).map(|i| ((i * 37 % 256) << 16 | (i * 91 % 256) << 8 | (255 - i % 256)) as u32).collect()
+    }
+
+    fn to_rgb(pixel: u32) -> (f32, f32, f32) {
+        ((pixel >> 16 & 0xFF) as f32 / 255.0, (pixel >> 8 & 0xFF) as f32 / 255.0, (pixel & 0xFF) as f32 / 255.0)
+    }
+
+    #[test]
+    fn saved_png_decodes_to_the_same_pixels() {
+        // Más de un bloque almacenado (65535 bytes) para probar también el enlace entre bloques
+        let (width, height) = (150, 160);
+        let pixels = test_pixels(width, height);
+        let image = decode_png(&encode_png(width, height, &pixels)).unwrap();
+        assert_eq!((image.width, image.height), (width, height));
+        assert!(image.pixels.iter().zip(&pixels).all(|(&decoded, &pixel)| decoded == to_rgb(pixel)));
+    }
+
+    #[test]
+    fn png_filters_are_undone() {
+        // Cada fila con un filtro distinto (0 a 4), calculado como lo haría un codificador
+        let (width, height) = (7, 10);
+        let pixels = test_pixels(width, height);
+        let rows: Vec<Vec<u8>> = pixels
+            .chunks(width)
+            .map(|row| row.iter().flat_map(|&pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8]).collect())
+            .collect();
+        let mut raw = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            let filter = (y % 5) as u8;
+            raw.push(filter);
+            let zero = vec![0; row.len()];
+            let previous = if y > 0 { &rows[y - 1] } else { &zero };
+            for x in 0..row.len() {
+                let left = if x >= 3 { row[x - 3] } else { 0 };
+                let up_left = if x >= 3 { previous[x - 3] } else { 0 };
+                let predictor = match filter {
+                    0 => 0,
+                    1 => left,
+                    2 => previous[x],
+                    3 => ((left as u16 + previous[x] as u16) / 2) as u8,
+                    _ => paeth(left, previous[x], up_left),
+                };
+                raw.push(row[x].wrapping_sub(predictor));
+            }
+        }
+        let image = decode_png(&png_from_rows(width, height, &raw)).unwrap();
+        assert!(image.pixels.iter().zip(&pixels).all(|(&decoded, &pixel)| decoded == to_rgb(pixel)));
+    }
+
+    #[test]
+    fn inflate_decodes_fixed_and_dynamic_huffman_blocks() {
+        // Comprimidos con zlib (nivel 9): el primero con Huffman fijo y copias hacia atrás,
+        // el segundo con un bloque de Huffman dinámico
+        let fixed = [120, 218, 75, 76, 74, 78, 132, 33, 133, 140, 252, 156, 68, 48, 1, 0, 94, 58, 8, 33];
+        assert_eq!((fixed[2] >> 1) & 3, 1);
+        assert_eq!(inflate_zlib(&fixed).unwrap(), b"abcabcabcabc hola hola");
+
+        let dynamic = [120, 218, 5, 193, 1, 1, 0, 0, 0, 64, 160, 173, 204, 176, 94, 21, 20, 106, 153, 130, 12, 89, 63, 8, 75];
+        assert_eq!((dynamic[2] >> 1) & 3, 2);
+        assert_eq!(inflate_zlib(&dynamic).unwrap(), b"zzaazzabbbzzbzbbaaba");
+    }
+
+    #[test]
+    fn corrupt_images_are_errors() {
+        let fixed = [120, 218, 75, 76, 74, 78, 132, 33, 133, 140, 252, 156, 68, 48, 1, 0, 94, 58, 8, 33];
+        for length in 0..fixed.len() - 4 {
+            assert!(inflate_zlib(&fixed[..length]).is_err(), "{} bytes", length);
+        }
+        // Tipo de bloque 3 (reservado) y cabecera que no es deflate
+        assert!(inflate_zlib(&[0x78, 0x01, 0b111]).is_err());
+        assert!(inflate_zlib(&[0x79, 0x01, 0x01, 0x00, 0x00, 0xFF, 0xFF]).is_err());
+
+        let png = encode_png(4, 4, &test_pixels(4, 4));
+        for length in [PNG_SIGNATURE.len(), 30, png.len() / 2, png.len() - 20] {
+            assert!(decode_png(&png[..length]).is_err(), "{} bytes", length);
+        }
+        // Filtro de la primera fila: tras la firma, IHDR, la cabecera de IDAT, la de zlib y
+        // la del bloque almacenado
+        let mut bad_filter = png.clone();
+        bad_filter[PNG_SIGNATURE.len() + 25 + 8 + 2 + 5] = 7;
+        assert!(matches!(decode_png(&bad_filter), Err(ImageError::Corrupt("filtro PNG desconocido"))));
+
+        let hdr = hdr_rle_image();
+        for length in [3, 20, hdr.len() - 1] {
+            assert!(decode_hdr(&hdr[..length]).is_err(), "{} bytes", length);
+        }
+    }
+
+    // 8x1 en RLE por canales: R en racha, G literal, B en racha y exponente 128 (escala 1/256)
+    fn hdr_rle_image() -> Vec<u8> {
+        let mut data = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 8\n".to_vec();
+        data.extend_from_slice(&[2, 2, 0, 8]);
+        data.extend_from_slice(&[128 + 8, 128]);
+        data.push(8);
+        data.extend((0..8).map(|x| x * 32));
+        data.extend_from_slice(&[128 + 8, 64]);
+        data.extend_from_slice(&[128 + 8, 128]);
+        data
+    }
+
+    #[test]
+    fn hdr_decodes_run_length_and_flat_scanlines() {
+        let image = decode_hdr(&hdr_rle_image()).unwrap();
+        assert_eq!((image.width, image.height), (8, 1));
+        for (x, &pixel) in image.pixels.iter().enumerate() {
+            assert_eq!(pixel, (0.5, x as f32 * 32.0 / 256.0, 0.25));
+        }
+
+        // Menos de 8 de ancho: sin RLE. Un exponente más alto pasa de 1 (más que blanco)
+        let mut flat = b"#?RADIANCE\n\n-Y 2 +X 1\n".to_vec();
+        flat.extend_from_slice(&[128, 64, 0, 130, 0, 0, 0, 0]);
+        let image = decode_hdr(&flat).unwrap();
+        assert_eq!(image.pixels, vec![(2.0, 1.0, 0.0), (0.0, 0.0, 0.0)]);
+    }
+
+    #[test]
+    fn wrapped_sample_just_below_zero_stays_in_the_image() {
+        let image = Image { width: 4, height: 2, pixels: (0..8).map(|i| (i as f32, 0.0, 0.0)).collect() };
+        // u * 4 - 0.5 queda a unos 3e-8 por debajo de 0 y rem_euclid(4) redondea a 4
+        let u = f32::from_bits(0.125f32.to_bits() - 1);
+        let (red, _, _) = image.sample(u, 1.0, true);
+        // Fila de abajo, entre el último píxel (7) y el primero (4): prácticamente el primero
+        assert!((red - 4.0).abs() < 1e-3, "{}", red);
+    }
+}
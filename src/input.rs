@@ -0,0 +1,64 @@
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window};
+
+// ============= ENTRADA DEL FRAME =============
+// Foto del teclado y del ratón tomada una vez por frame. Los paneles, la cámara y los modos
+// de vuelo la leen con las mismas preguntas que harían a la ventana de minifb, así la foto
+// puede venir de la ventana o de una repetición grabada (ver replay.rs) sin que lo noten.
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Input {
+    pub down: Vec<Key>,               // mantenidas
+    pub pressed: Vec<Key>,            // pulsadas en este frame
+    pub repeated: Vec<Key>,           // pulsadas o repetidas por el teclado en este frame
+    pub mouse: Option<(f32, f32)>,    // posición sin recortar (None si el sistema no la da)
+    pub buttons: [bool; 3],           // izquierdo, central y derecho
+    pub size: (usize, usize),         // tamaño de la ventana
+}
+
+impl Input {
+    pub fn from_window(window: &Window) -> Self {
+        Input {
+            down: window.get_keys(),
+            pressed: window.get_keys_pressed(KeyRepeat::No),
+            repeated: window.get_keys_pressed(KeyRepeat::Yes),
+            mouse: window.get_mouse_pos(MouseMode::Pass),
+            buttons: [MouseButton::Left, MouseButton::Middle, MouseButton::Right].map(|button| window.get_mouse_down(button)),
+            size: window.get_size(),
+        }
+    }
+
+    pub fn is_key_down(&self, key: Key) -> bool {
+        self.down.contains(&key)
+    }
+
+    pub fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        match repeat {
+            KeyRepeat::No => self.pressed.contains(&key),
+            KeyRepeat::Yes => self.repeated.contains(&key),
+        }
+    }
+
+    pub fn get_mouse_down(&self, button: MouseButton) -> bool {
+        match button {
+            MouseButton::Left => self.buttons[0],
+            MouseButton::Middle => self.buttons[1],
+            MouseButton::Right => self.buttons[2],
+        }
+    }
+
+    // Los mismos modos que minifb: Pass sin tocar, Clamp recortada a la ventana y Discard
+    // solo si está dentro
+    pub fn get_mouse_pos(&self, mode: MouseMode) -> Option<(f32, f32)> {
+        let (x, y) = self.mouse?;
+        let (width, height) = (self.size.0 as f32, self.size.1 as f32);
+        match mode {
+            MouseMode::Pass => Some((x, y)),
+            MouseMode::Clamp => Some((x.clamp(0.0, width - 1.0), y.clamp(0.0, height - 1.0))),
+            MouseMode::Discard => (x >= 0.0 && x < width && y >= 0.0 && y < height).then_some((x, y)),
+        }
+    }
+
+    pub fn get_size(&self) -> (usize, usize) {
+        self.size
+    }
+}
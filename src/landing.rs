@@ -0,0 +1,184 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use crate::celestial_shaders::surface_height;
+use crate::controls::{Action, Controls};
+use crate::framebuffer::Framebuffer;
+use crate::input::Input;
+use crate::terrain::{ground_radius, TERRAIN_DISTANCE};
+use crate::text::{draw_text, text_width, GLYPH_SIZE};
+use crate::theme::{self, HudLayout, Widget};
+use crate::{Camera, CelestialObject};
+
+// ============= ATERRIZAJE Y PASEO POR LA SUPERFICIE =============
+// G posa la nave sobre un cuerpo rocoso (el seleccionado o el más cercano en modo terreno).
+// La posición se guarda en el espacio del modelo del cuerpo, así la cámara gira y orbita
+// con él; el suelo sale del mismo mapa de alturas que la malla del modo terreno.
+
+const WALK_SPEED: f32 = 0.25;        // unidades por frame
+const TURN_SPEED: f32 = 0.03;        // radianes por frame
+const LOOK_SPEED: f32 = 0.02;
+const MIN_EYE_HEIGHT: f32 = 0.4;     // altura de los ojos sobre el suelo (unidades)
+const MAX_HOVER: f32 = 0.3;          // altura máxima de vuelo estacionario (en radios)
+const HOVER_SPEED: f32 = 0.1;
+const MAX_PITCH: f32 = 1.4;
+
+pub struct Landing {
+    pub body: usize,     // índice del cuerpo en la lista de la escena
+    position: Vec3,      // punto de la superficie bajo la cámara (esfera unitaria del modelo)
+    heading: Vec3,       // hacia dónde se camina, tangente a la superficie (espacio del modelo)
+    pitch: f32,          // mirada sobre el horizonte (radianes)
+    eye_height: f32,     // altura de la cámara sobre el suelo (unidades)
+}
+
+impl Landing {
+    // Aterriza bajo la cámara en el cuerpo elegido, o None si no hay superficie a la que bajar
+    pub fn touch_down(objects: &[CelestialObject], selected: Option<usize>, camera: &Camera) -> Option<Self> {
+        let has_surface = |obj: &CelestialObject| surface_height(obj.shader.body, Vec3::new(0.0, 1.0, 0.0)).is_some();
+        let body = selected
+            .filter(|&index| has_surface(&objects[index]))
+            .or_else(|| {
+                objects
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, obj)| has_surface(obj))
+                    .filter(|(_, obj)| (camera.position - obj.transform.translation).magnitude() < obj.transform.scale * TERRAIN_DISTANCE)
+                    .min_by(|(_, a), (_, b)| {
+                        let distance_a = (camera.position - a.transform.translation).magnitude() / a.transform.scale;
+                        let distance_b = (camera.position - b.transform.translation).magnitude() / b.transform.scale;
+                        distance_a.total_cmp(&distance_b)
+                    })
+                    .map(|(index, _)| index)
+            })?;
+
+        // Punto bajo la cámara y rumbo según la dirección de la mirada, en el espacio del modelo
+        let inverse_model = objects[body].transform.model_matrix().try_inverse()?;
+        let position = transform_point(&inverse_model, camera.position).try_normalize(1.0e-6)?;
+        let forward = transform_vector(&inverse_model, camera.target - camera.position);
+        let heading = (forward - position * forward.dot(&position))
+            .try_normalize(1.0e-6)
+            .unwrap_or_else(|| any_tangent(position));
+
+        Some(Landing {
+            body,
+            position,
+            heading,
+            pitch: 0.0,
+            eye_height: MIN_EYE_HEIGHT,
+        })
+    }
+
+    // WASD caminar y girar, flechas mirar, Q/E subir o bajar sobre el suelo (o lo asignado
+    // en controls.toml)
+    pub fn handle_input(&mut self, input: &Input, controls: &Controls, obj: &CelestialObject) {
+        let step = WALK_SPEED / obj.transform.scale;
+        if controls.is_down(input, Action::MoveForward) {
+            self.walk(step);
+        }
+        if controls.is_down(input, Action::MoveBack) {
+            self.walk(-step);
+        }
+        if controls.is_down(input, Action::MoveLeft) || controls.is_down(input, Action::OrbitLeft) {
+            self.turn(TURN_SPEED);
+        }
+        if controls.is_down(input, Action::MoveRight) || controls.is_down(input, Action::OrbitRight) {
+            self.turn(-TURN_SPEED);
+        }
+        if controls.is_down(input, Action::OrbitUp) {
+            self.pitch = (self.pitch + LOOK_SPEED).min(MAX_PITCH);
+        }
+        if controls.is_down(input, Action::OrbitDown) {
+            self.pitch = (self.pitch - LOOK_SPEED).max(-MAX_PITCH);
+        }
+        if controls.is_down(input, Action::MoveUp) {
+            self.eye_height = (self.eye_height + HOVER_SPEED).min(obj.transform.scale * MAX_HOVER);
+        }
+        if controls.is_down(input, Action::MoveDown) {
+            self.eye_height = (self.eye_height - HOVER_SPEED).max(MIN_EYE_HEIGHT);
+        }
+    }
+
+    // Avanza sobre un círculo máximo; el rumbo se reajusta para seguir tangente
+    fn walk(&mut self, angle: f32) {
+        self.position = (self.position + self.heading * angle).normalize();
+        self.heading = (self.heading - self.position * self.heading.dot(&self.position)).normalize();
+    }
+
+    // Gira el rumbo alrededor de la vertical local
+    fn turn(&mut self, angle: f32) {
+        let (sin_a, cos_a) = angle.sin_cos();
+        self.heading = (self.heading * cos_a + self.position.cross(&self.heading) * sin_a).normalize();
+    }
+
+    // Vertical local en el mundo (para el cielo y la orientación de la cámara)
+    pub fn up(&self, obj: &CelestialObject) -> Vec3 {
+        transform_vector(&obj.transform.model_matrix(), self.position).normalize()
+    }
+
+    // Coloca la cámara en el cuerpo: sobre el suelo, mirando según el rumbo y la inclinación
+    pub fn place_camera(&self, camera: &mut Camera, obj: &CelestialObject) {
+        let model_matrix = obj.transform.model_matrix();
+        let up = self.up(obj);
+        let ground = transform_point(&model_matrix, self.position * ground_radius(obj.shader.body, self.position));
+        let eye = ground + up * self.eye_height;
+
+        let forward = transform_vector(&model_matrix, self.heading).normalize();
+        let look = forward * self.pitch.cos() + up * self.pitch.sin();
+        camera.place(eye, eye + look, up);
+    }
+
+    // Al despegar, la cámara vuelve a la vista orbital por encima del punto de aterrizaje
+    pub fn take_off(&self, camera: &mut Camera, obj: &CelestialObject) {
+        let up = self.up(obj);
+        camera.place(obj.transform.translation + up * obj.transform.scale * TERRAIN_DISTANCE, obj.transform.translation, Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    // Cartel con el cuerpo, la altura y los controles (con las teclas de controls.toml)
+    pub fn draw_hud(&self, screen: &mut Framebuffer, layout: &mut HudLayout, obj: &CelestialObject, controls: &Controls) {
+        const PADDING: usize = 10;
+        const LINE_HEIGHT: usize = GLYPH_SIZE + 4;
+
+        let title = format!("En la superficie de {}", obj.display_name());
+        let altitude = format!("Altura sobre el suelo: {:.1}", self.eye_height);
+        let walk = format!(
+            "{} caminar   {} o {} girar   {} mirar",
+            controls.pair_name(Action::MoveForward, Action::MoveBack),
+            controls.pair_name(Action::MoveLeft, Action::MoveRight),
+            controls.pair_name(Action::OrbitLeft, Action::OrbitRight),
+            controls.pair_name(Action::OrbitUp, Action::OrbitDown),
+        );
+        let float = format!("{} flotar   G despegar", controls.pair_name(Action::MoveUp, Action::MoveDown));
+        let lines = [altitude.as_str(), walk.as_str(), float.as_str()];
+
+        let width = lines.iter().map(|line| text_width(line, 1)).max().unwrap_or(0).max(text_width(&title, 2)) + PADDING * 2;
+        let height = PADDING * 2 + GLYPH_SIZE * 2 + 8 + lines.len() * LINE_HEIGHT;
+        let Some((x, panel_y)) = layout.place(Widget::Landing, width, height) else {
+            return;
+        };
+        let theme = theme::current();
+        screen.blend_rect(x, panel_y, width, height, theme.panel_background, theme.hud_opacity);
+
+        let text_x = (x + PADDING) as i32;
+        let mut y = (panel_y + PADDING) as i32;
+        draw_text(screen, text_x, y, &title, theme.title, 2);
+        y += (GLYPH_SIZE * 2 + 8) as i32;
+        for line in lines {
+            draw_text(screen, text_x, y, line, theme.text, 1);
+            y += LINE_HEIGHT as i32;
+        }
+    }
+}
+
+fn transform_point(matrix: &Mat4, point: Vec3) -> Vec3 {
+    let result = matrix * Vec4::new(point.x, point.y, point.z, 1.0);
+    Vec3::new(result.x, result.y, result.z)
+}
+
+fn transform_vector(matrix: &Mat4, vector: Vec3) -> Vec3 {
+    let result = matrix * Vec4::new(vector.x, vector.y, vector.z, 0.0);
+    Vec3::new(result.x, result.y, result.z)
+}
+
+// Un rumbo cualquiera si la cámara miraba justo hacia el centro del cuerpo
+fn any_tangent(normal: Vec3) -> Vec3 {
+    let helper = if normal.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+    normal.cross(&helper).normalize()
+}
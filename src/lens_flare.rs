@@ -0,0 +1,129 @@
+use nalgebra_glm::{Mat4, Vec3};
+use crate::celestial_shaders::star_tint;
+use crate::draw_state::BlendMode;
+use crate::framebuffer::Framebuffer;
+use crate::{project_to_screen, CelestialObject};
+
+// ============= DESTELLO DE LENTE DEL SOL =============
+// Cuando el Sol está en pantalla, la luz que rebota entre las lentes de una cámara real deja
+// un núcleo brillante, un anillo de halo y una fila de "fantasmas" sobre la recta que une el
+// Sol con el centro de la imagen. Se compone en la pantalla tras la reducción, sumando luz.
+// La visibilidad sale del buffer de profundidad de la escena: se muestrea una rejilla sobre
+// el disco del Sol y cada muestra tapada por algo más cercano (un planeta, una luna) resta.
+// El destello se apaga suavemente al acercarse el Sol al borde de la pantalla.
+
+const SAMPLE_GRID: usize = 5;           // rejilla de muestras sobre el disco (5 x 5)
+const DEPTH_TOLERANCE: f32 = 1.001;     // margen de la comparación con la profundidad invertida
+const EDGE_FADE: f32 = 0.12;            // fracción de la pantalla en la que se apaga hacia el borde
+
+const HALO_COLOR: u32 = 0x8C7050;
+
+// Fantasmas: posición sobre la recta Sol -> centro (1 = el centro), radio en píxeles,
+// color e intensidad
+const GHOSTS: [(f32, f32, u32, f32); 5] = [
+    (0.45, 14.0, 0xFFD080, 0.20),
+    (0.8, 30.0, 0x70A0FF, 0.12),
+    (1.2, 10.0, 0xFF8060, 0.22),
+    (1.55, 46.0, 0x80FFB0, 0.08),
+    (2.0, 22.0, 0xB080FF, 0.14),
+];
+
+// Fracción del disco del Sol que se ve (0 si está fuera de pantalla o detrás de la cámara).
+// `scene` es el framebuffer de la escena con su viewport
+pub fn sun_visibility(
+    scene: &Framebuffer,
+    sun: &CelestialObject,
+    camera_position: Vec3,
+    view_matrix: &Mat4,
+    projection_matrix: &Mat4,
+    viewport_matrix: &Mat4,
+) -> f32 {
+    let Some((center, radius)) = crate::picking::screen_disk(sun, view_matrix, projection_matrix, viewport_matrix) else {
+        return 0.0;
+    };
+    // Lo que tape al Sol tiene que estar más cerca que su punto más próximo a la cámara
+    let toward_camera = (camera_position - sun.transform.translation).normalize();
+    let Some(nearest) = project_to_screen(sun.transform.translation + toward_camera * sun.transform.scale, view_matrix, projection_matrix, viewport_matrix) else {
+        return 0.0;
+    };
+
+    let mut visible = 0;
+    let mut samples = 0;
+    for row in 0..SAMPLE_GRID {
+        for column in 0..SAMPLE_GRID {
+            let u = (column as f32 + 0.5) / SAMPLE_GRID as f32 * 2.0 - 1.0;
+            let v = (row as f32 + 0.5) / SAMPLE_GRID as f32 * 2.0 - 1.0;
+            if u * u + v * v > 1.0 {
+                continue;
+            }
+            samples += 1;
+            let x = center.x + u * radius * 0.8;
+            let y = center.y + v * radius * 0.8;
+            if x < 0.0 || y < 0.0 || x >= scene.width as f32 || y >= scene.height as f32 {
+                continue;
+            }
+            let depth = scene.zbuffer[y as usize * scene.width + x as usize];
+            if depth <= nearest.z * DEPTH_TOLERANCE {
+                visible += 1;
+            }
+        }
+    }
+    if samples == 0 {
+        return 0.0;
+    }
+    visible as f32 / samples as f32
+}
+
+// Dibuja el destello sobre la pantalla con el Sol en `center` (píxeles de la pantalla). El
+// núcleo toma el color de la estrella según su temperatura
+pub fn draw_lens_flare(screen: &mut Framebuffer, center: (f32, f32), sun_radius: f32, visibility: f32, temperature: f32) {
+    let (width, height) = (screen.width as f32, screen.height as f32);
+    let margin = center.0.min(center.1).min(width - center.0).min(height - center.1);
+    let edge_fade = (margin / (EDGE_FADE * width.min(height))).clamp(0.0, 1.0);
+    let strength = visibility * edge_fade * edge_fade;
+    if strength <= 0.0 {
+        return;
+    }
+
+    // Núcleo: resplandor amplio alrededor del disco
+    let core_radius = sun_radius * 3.0 + 40.0;
+    let core_color = star_tint(temperature * 1.05).to_hex();
+    splat(screen, center, core_radius, core_color, 0.6 * strength, |d| (1.0 - d).powi(3));
+
+    // Halo: anillo fino a varias veces el radio del Sol
+    let halo_radius = (sun_radius * 3.5).max(90.0);
+    splat(screen, center, halo_radius, HALO_COLOR, 0.25 * strength, |d| (-((d - 0.92) * 25.0).powi(2)).exp());
+
+    // Fantasmas: discos de borde suave al otro lado del centro de la imagen
+    let axis = (width * 0.5 - center.0, height * 0.5 - center.1);
+    for (position, radius, color, intensity) in GHOSTS {
+        let ghost = (center.0 + axis.0 * position, center.1 + axis.1 * position);
+        splat(screen, ghost, radius, color, intensity * strength, |d| (1.0 - d.powi(4)).max(0.0));
+    }
+}
+
+// Suma un disco de luz con el perfil radial `profile` (d en [0, 1] desde el centro)
+fn splat(screen: &mut Framebuffer, center: (f32, f32), radius: f32, color: u32, intensity: f32, profile: impl Fn(f32) -> f32) {
+    let min_x = (center.0 - radius).floor().max(0.0) as usize;
+    let min_y = (center.1 - radius).floor().max(0.0) as usize;
+    let max_x = ((center.0 + radius).ceil().max(0.0) as usize).min(screen.width);
+    let max_y = ((center.1 + radius).ceil().max(0.0) as usize).min(screen.height);
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let distance = (x as f32 + 0.5 - center.0).hypot(y as f32 + 0.5 - center.1) / radius;
+            if distance >= 1.0 {
+                continue;
+            }
+            let light = scale_color(color, profile(distance) * intensity);
+            let index = y * screen.width + x;
+            screen.buffer[index] = BlendMode::Additive.apply(light, screen.buffer[index]);
+        }
+    }
+}
+
+fn scale_color(color: u32, factor: f32) -> u32 {
+    let factor = factor.clamp(0.0, 1.0);
+    let channel = |shift: u32| ((((color >> shift) & 0xFF) as f32 * factor) as u32) << shift;
+    channel(16) | channel(8) | channel(0)
+}
@@ -0,0 +1,328 @@
+use std::fmt;
+use std::fs;
+use nalgebra_glm::Vec3;
+use serde::Deserialize;
+use crate::framebuffer::Framebuffer;
+use crate::text::{draw_text, wrap_text, GLYPH_SIZE};
+use crate::theme;
+use crate::CelestialObject;
+
+// ============= LECCIONES GUIADAS (TOML) =============
+// Una lección es una lista de pasos: cada uno puede enfocar la cámara en un cuerpo,
+// resaltar otros y mostrar un texto. Espacio avanza al siguiente paso.
+
+#[derive(Deserialize)]
+pub struct Lesson {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub steps: Vec<LessonStep>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LessonStep {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub text: String,
+    pub target: Option<String>,     // id del cuerpo a enfocar (la cámara no se mueve si falta)
+    #[serde(default)]
+    pub highlight: Vec<String>,     // ids de los cuerpos a resaltar
+    #[serde(default = "default_distance")]
+    pub distance: f32,              // distancia de la cámara en radios del cuerpo enfocado
+    #[serde(default = "default_elevation")]
+    pub elevation: f32,             // grados sobre el plano orbital
+    #[serde(default)]
+    pub azimuth: f32,               // grados alrededor del cuerpo
+    pub quiz: Option<Quiz>,         // pregunta opcional: el paso no avanza hasta responderla
+}
+
+// ============= PREGUNTAS (QUIZ) =============
+// quiz = { kind = "click", body = "mars" }
+// quiz = { kind = "eclipse", occluder = "moon", source = "sun" }
+#[derive(Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Quiz {
+    Click { body: String },                       // hacer clic sobre el cuerpo indicado
+    Eclipse { occluder: String, source: String }, // mover la cámara hasta que `occluder` tape a `source`
+}
+
+impl Quiz {
+    fn body_ids(&self) -> Vec<&String> {
+        match self {
+            Quiz::Click { body } => vec![body],
+            Quiz::Eclipse { occluder, source } => vec![occluder, source],
+        }
+    }
+}
+
+// Alineación mínima (ver picking::eclipse_alignment) para dar por bueno un eclipse
+const ECLIPSE_THRESHOLD: f32 = 0.9;
+
+enum QuizState {
+    Pending,
+    Wrong(String),
+    Solved,
+}
+
+#[derive(Debug)]
+pub enum LessonError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Empty,
+    UnknownBody(String),
+}
+
+impl fmt::Display for LessonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LessonError::Io(err) => write!(f, "no se pudo leer la lección: {}", err),
+            LessonError::Parse(err) => write!(f, "lección inválida: {}", err),
+            LessonError::Empty => write!(f, "la lección no tiene pasos"),
+            LessonError::UnknownBody(id) => write!(f, "la lección menciona un cuerpo inexistente: '{}'", id),
+        }
+    }
+}
+
+impl Lesson {
+    pub fn load(filename: &str) -> Result<Self, LessonError> {
+        let contents = fs::read_to_string(filename).map_err(LessonError::Io)?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, LessonError> {
+        let lesson: Lesson = toml::from_str(contents).map_err(LessonError::Parse)?;
+        if lesson.steps.is_empty() {
+            return Err(LessonError::Empty);
+        }
+        Ok(lesson)
+    }
+
+    // Todos los ids de la lección tienen que existir en la escena
+    fn check_bodies(&self, body_ids: &[String]) -> Result<(), LessonError> {
+        for step in &self.steps {
+            let quiz_ids = step.quiz.iter().flat_map(Quiz::body_ids);
+            for id in step.target.iter().chain(step.highlight.iter()).chain(quiz_ids) {
+                if !body_ids.contains(id) {
+                    return Err(LessonError::UnknownBody(id.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl LessonStep {
+    // Posición de la cámara relativa al cuerpo enfocado
+    pub fn camera_offset(&self, body_scale: f32) -> Vec3 {
+        orbit_offset(self.distance * body_scale, self.elevation, self.azimuth)
+    }
+}
+
+// Punto a `distance` de un cuerpo, `elevation` grados sobre el plano orbital y `azimuth`
+// grados a su alrededor (también lo usan los fotogramas de la visita guiada)
+pub fn orbit_offset(distance: f32, elevation: f32, azimuth: f32) -> Vec3 {
+    let elevation = elevation.to_radians();
+    let azimuth = azimuth.to_radians();
+    Vec3::new(
+        elevation.cos() * azimuth.sin(),
+        elevation.sin(),
+        elevation.cos() * azimuth.cos(),
+    ) * distance
+}
+
+fn default_distance() -> f32 {
+    6.0
+}
+
+fn default_elevation() -> f32 {
+    20.0
+}
+
+// ============= REPRODUCTOR DE LECCIONES =============
+
+const PANEL_WIDTH: usize = 760;
+const PANEL_MARGIN_BOTTOM: usize = 24;
+const PADDING: usize = 14;
+const LINE_HEIGHT: usize = GLYPH_SIZE + 5;
+
+const CORRECT_COLOR: u32 = 0x7CFC9A;
+const WRONG_COLOR: u32 = 0xFF7A6A;
+
+pub struct LessonPlayer {
+    lesson: Lesson,
+    step: usize,
+    quiz_state: QuizState,
+    first_try: bool,       // sin respuestas incorrectas en el paso actual
+    alignment: f32,        // progreso del eclipse en el paso actual
+    score: usize,          // preguntas acertadas al primer intento
+}
+
+impl LessonPlayer {
+    // Valida que todos los ids de la lección existan en la escena
+    pub fn new(lesson: Lesson, body_ids: &[String]) -> Result<Self, LessonError> {
+        lesson.check_bodies(body_ids)?;
+        Ok(LessonPlayer {
+            lesson,
+            step: 0,
+            quiz_state: QuizState::Pending,
+            first_try: true,
+            alignment: 0.0,
+            score: 0,
+        })
+    }
+
+    // Al recargar la escena: la lección en curso tiene que seguir encontrando sus cuerpos
+    pub fn check_bodies(&self, body_ids: &[String]) -> Result<(), LessonError> {
+        self.lesson.check_bodies(body_ids)
+    }
+
+    pub fn current_step(&self) -> &LessonStep {
+        &self.lesson.steps[self.step]
+    }
+
+    pub fn step_index(&self) -> usize {
+        self.step
+    }
+
+    // Texto del paso actual para el narrador accesible
+    pub fn narration(&self) -> String {
+        let step = self.current_step();
+        let title = if step.title.is_empty() { &self.lesson.title } else { &step.title };
+        format!("Lección, paso {} de {}: {}. {}", self.step + 1, self.lesson.steps.len(), title, step.text.trim())
+    }
+
+    // Hay una pregunta en este paso que aún no se ha respondido bien
+    pub fn quiz_pending(&self) -> bool {
+        self.current_step().quiz.is_some() && !matches!(self.quiz_state, QuizState::Solved)
+    }
+
+    // Avanza al siguiente paso; devuelve false cuando la lección termina.
+    // Con una pregunta pendiente el paso no cambia
+    pub fn advance(&mut self) -> bool {
+        if self.quiz_pending() {
+            return true;
+        }
+        if self.step + 1 < self.lesson.steps.len() {
+            self.step += 1;
+            self.quiz_state = QuizState::Pending;
+            self.first_try = true;
+            self.alignment = 0.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Respuesta a una pregunta de tipo "click" (None = clic en el vacío)
+    pub fn answer_click(&mut self, picked: Option<&CelestialObject>) {
+        let Some(Quiz::Click { body }) = &self.current_step().quiz else {
+            return;
+        };
+        if !self.quiz_pending() {
+            return;
+        }
+
+        match picked {
+            Some(obj) if &obj.id == body => self.solve(),
+            Some(obj) => {
+                self.first_try = false;
+                self.quiz_state = QuizState::Wrong(format!("Eso es {}. Inténtalo de nuevo.", obj.display_name()));
+            }
+            None => {
+                self.first_try = false;
+                self.quiz_state = QuizState::Wrong("Ahí no hay ningún cuerpo. Inténtalo de nuevo.".to_string());
+            }
+        }
+    }
+
+    // Ids (ocultador, fuente) si el paso actual pide alinear un eclipse
+    pub fn eclipse_bodies(&self) -> Option<(&str, &str)> {
+        match &self.current_step().quiz {
+            Some(Quiz::Eclipse { occluder, source }) => Some((occluder.as_str(), source.as_str())),
+            _ => None,
+        }
+    }
+
+    // Alineación actual del eclipse (se valida cada frame con la geometría de la cámara)
+    pub fn update_alignment(&mut self, alignment: f32) {
+        if !self.quiz_pending() {
+            return;
+        }
+        self.alignment = alignment;
+        if alignment >= ECLIPSE_THRESHOLD {
+            self.solve();
+        }
+    }
+
+    fn solve(&mut self) {
+        if self.first_try {
+            self.score += 1;
+        }
+        self.quiz_state = QuizState::Solved;
+    }
+
+    fn quiz_count(&self) -> usize {
+        self.lesson.steps.iter().filter(|step| step.quiz.is_some()).count()
+    }
+
+    // Línea de estado de la pregunta: instrucción, error o acierto
+    fn quiz_feedback(&self) -> Option<(String, u32)> {
+        let theme = theme::current();
+        let quiz = self.current_step().quiz.as_ref()?;
+        Some(match (&self.quiz_state, quiz) {
+            (QuizState::Solved, _) => ("¡Correcto!".to_string(), CORRECT_COLOR),
+            (QuizState::Wrong(message), _) => (message.clone(), WRONG_COLOR),
+            (QuizState::Pending, Quiz::Click { .. }) => ("Haz clic sobre el cuerpo.".to_string(), theme.muted),
+            (QuizState::Pending, Quiz::Eclipse { .. }) => {
+                (format!("Alineación: {:.0}%", self.alignment * 100.0), theme.muted)
+            }
+        })
+    }
+
+    // Panel inferior con el título y el texto del paso actual
+    pub fn draw(&self, screen: &mut Framebuffer) {
+        let theme = theme::current();
+        let step = self.current_step();
+        let max_chars = (PANEL_WIDTH - PADDING * 2) / GLYPH_SIZE;
+        let lines = wrap_text(&step.text, max_chars);
+        let feedback = self.quiz_feedback();
+
+        let title = if step.title.is_empty() { &self.lesson.title } else { &step.title };
+        let title_height = if title.is_empty() { 0 } else { GLYPH_SIZE * 2 + 10 };
+        let feedback_height = if feedback.is_some() { LINE_HEIGHT + 6 } else { 0 };
+        let panel_height = PADDING * 2 + title_height + (lines.len() + 1) * LINE_HEIGHT + feedback_height + 6;
+        let panel_x = screen.width.saturating_sub(PANEL_WIDTH) / 2;
+        let panel_y = screen.height.saturating_sub(panel_height + PANEL_MARGIN_BOTTOM);
+        screen.blend_rect(panel_x, panel_y, PANEL_WIDTH, panel_height, theme.panel_background, theme.hud_opacity);
+
+        let text_x = (panel_x + PADDING) as i32;
+        let mut y = (panel_y + PADDING) as i32;
+        if !title.is_empty() {
+            draw_text(screen, text_x, y, title, theme.title, 2);
+            y += title_height as i32;
+        }
+        for line in &lines {
+            draw_text(screen, text_x, y, line, theme.text, 1);
+            y += LINE_HEIGHT as i32;
+        }
+        if let Some((message, color)) = &feedback {
+            draw_text(screen, text_x, y + 6, message, *color, 1);
+            y += feedback_height as i32;
+        }
+
+        let is_last = self.step + 1 == self.lesson.steps.len();
+        let action = if self.quiz_pending() {
+            "Responde la pregunta para continuar"
+        } else if is_last {
+            "Espacio: terminar"
+        } else {
+            "Espacio: continuar"
+        };
+        let mut footer = format!("{}   ({}/{})", action, self.step + 1, self.lesson.steps.len());
+        if is_last && self.quiz_count() > 0 {
+            footer.push_str(&format!("   Aciertos al primer intento: {}/{}", self.score, self.quiz_count()));
+        }
+        draw_text(screen, text_x, y + 6, &footer, theme.muted, 1);
+    }
+}
@@ -0,0 +1,134 @@
+use nalgebra_glm::{Mat4, Vec3};
+use rayon::prelude::*;
+use crate::draw_state::BlendMode;
+use crate::framebuffer::Framebuffer;
+use crate::celestial_shaders::star_tint;
+use crate::random_system::StarClass;
+use crate::{project_to_screen, CelestialObject};
+
+// ============= RAYOS CREPUSCULARES (GOD RAYS) =============
+// Desenfoque radial en pantalla a partir de la posición proyectada del Sol:
+// 1. Máscara de luz a baja resolución: los píxeles alrededor del disco del Sol valen más cuanto
+//    más cerca están de su centro, y cero si el buffer de profundidad de la escena tiene ahí
+//    algo más cercano que el Sol (un planeta que lo tapa).
+// 2. Cada píxel de la máscara acumula muestras sobre la recta que lo une con el Sol, con un
+//    decaimiento exponencial: la luz se "arrastra" hacia fuera y los huecos entre las siluetas
+//    se convierten en haces, y las siluetas en sombras alargadas.
+// 3. El resultado se amplía con interpolación bilineal y se suma a la pantalla.
+// Sin nada delante solo queda un resplandor suave; el efecto aparece cuando un cuerpo cruza el Sol.
+
+const DOWNSCALE: usize = 4;            // la máscara y el desenfoque van a 1/4 de la pantalla
+const MASK_RADIUS: f32 = 2.0;          // radio de la máscara, en radios del disco del Sol
+const SAMPLES: usize = 32;             // muestras por píxel a lo largo del rayo
+const DENSITY: f32 = 0.9;              // fracción del camino hasta el Sol que recorre el rayo
+const DECAY: f32 = 0.97;               // atenuación de cada muestra respecto a la anterior
+const EXPOSURE: f32 = 1.2;
+const DEPTH_TOLERANCE: f32 = 1.001;    // margen de la comparación con la profundidad invertida
+const SHAFT_TEMPERATURE: f32 = 0.8;  // los rayos, algo más cálidos que la superficie de la estrella
+
+pub struct ShaftView<'a> {
+    pub camera_position: Vec3,
+    pub view_matrix: &'a Mat4,
+    pub projection_matrix: &'a Mat4,
+    pub scene_viewport: &'a Mat4,   // viewport del framebuffer de la escena
+    pub screen_viewport: &'a Mat4,  // viewport de la ventana
+}
+
+pub fn draw_light_shafts(scene: &Framebuffer, screen: &mut Framebuffer, sun: &CelestialObject, view: &ShaftView) {
+    let Some((center, radius)) = crate::picking::screen_disk(sun, view.view_matrix, view.projection_matrix, view.screen_viewport) else {
+        return;
+    };
+    // Lo que tape al Sol tiene que estar más cerca que su punto más próximo a la cámara
+    let toward_camera = (view.camera_position - sun.transform.translation).normalize();
+    let Some(nearest) = project_to_screen(sun.transform.translation + toward_camera * sun.transform.scale, view.view_matrix, view.projection_matrix, view.scene_viewport) else {
+        return;
+    };
+
+    let width = screen.width / DOWNSCALE;
+    let height = screen.height / DOWNSCALE;
+    if width == 0 || height == 0 {
+        return;
+    }
+    let sun_x = center.x / DOWNSCALE as f32;
+    let sun_y = center.y / DOWNSCALE as f32;
+    let mask_radius = (radius * MASK_RADIUS / DOWNSCALE as f32).max(1.0);
+
+    // Con el Sol fuera de la máscara visible no hay nada que desenfocar
+    if sun_x + mask_radius < 0.0 || sun_y + mask_radius < 0.0 || sun_x - mask_radius > width as f32 || sun_y - mask_radius > height as f32 {
+        return;
+    }
+
+    // 1. Máscara de luz, muestreando la profundidad de la escena (que puede tener otra resolución)
+    let scale_x = scene.width as f32 / width as f32;
+    let scale_y = scene.height as f32 / height as f32;
+    let mut mask = vec![0.0f32; width * height];
+    mask.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+        for (x, value) in row.iter_mut().enumerate() {
+            let distance = (x as f32 + 0.5 - sun_x).hypot(y as f32 + 0.5 - sun_y) / mask_radius;
+            if distance >= 1.0 {
+                continue;
+            }
+            let scene_x = ((x as f32 + 0.5) * scale_x) as usize;
+            let scene_y = ((y as f32 + 0.5) * scale_y) as usize;
+            let depth = scene.zbuffer[scene_y.min(scene.height - 1) * scene.width + scene_x.min(scene.width - 1)];
+            if depth <= nearest.z * DEPTH_TOLERANCE {
+                *value = (1.0 - distance) * (1.0 - distance);
+            }
+        }
+    });
+
+    // 2. Desenfoque radial hacia el Sol
+    let mut shafts = vec![0.0f32; width * height];
+    shafts.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+        for (x, value) in row.iter_mut().enumerate() {
+            let (start_x, start_y) = (x as f32 + 0.5, y as f32 + 0.5);
+            let step_x = (sun_x - start_x) * DENSITY / SAMPLES as f32;
+            let step_y = (sun_y - start_y) * DENSITY / SAMPLES as f32;
+            // Las muestras anteriores a la entrada del rayo en la máscara valen cero: se saltan
+            let distance = (sun_x - start_x).hypot(sun_y - start_y);
+            let first = if distance > mask_radius {
+                (((1.0 - mask_radius / distance) * SAMPLES as f32 / DENSITY).floor() as usize).min(SAMPLES)
+            } else {
+                0
+            };
+            let (mut px, mut py) = (start_x + step_x * first as f32, start_y + step_y * first as f32);
+            let mut weight = DECAY.powi(first as i32);
+            let mut sum = 0.0;
+            for _ in first..SAMPLES {
+                if px >= 0.0 && py >= 0.0 && (px as usize) < width && (py as usize) < height {
+                    sum += mask[py as usize * width + px as usize] * weight;
+                }
+                weight *= DECAY;
+                px += step_x;
+                py += step_y;
+            }
+            *value = sum / SAMPLES as f32;
+        }
+    });
+
+    // 3. Ampliación bilineal y suma sobre la pantalla, con el color de la estrella
+    let temperature = sun.shader.body.star_class().unwrap_or(StarClass::G).temperature();
+    let shaft_color = star_tint(temperature * SHAFT_TEMPERATURE).to_float();
+    let screen_width = screen.width;
+    screen.buffer.par_chunks_mut(screen_width).enumerate().for_each(|(y, row)| {
+        let fy = ((y as f32 + 0.5) / DOWNSCALE as f32 - 0.5).clamp(0.0, (height - 1) as f32);
+        let y0 = fy as usize;
+        let y1 = (y0 + 1).min(height - 1);
+        let ty = fy - y0 as f32;
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let fx = ((x as f32 + 0.5) / DOWNSCALE as f32 - 0.5).clamp(0.0, (width - 1) as f32);
+            let x0 = fx as usize;
+            let x1 = (x0 + 1).min(width - 1);
+            let tx = fx - x0 as f32;
+            let top = shafts[y0 * width + x0] * (1.0 - tx) + shafts[y0 * width + x1] * tx;
+            let bottom = shafts[y1 * width + x0] * (1.0 - tx) + shafts[y1 * width + x1] * tx;
+            let light = ((top * (1.0 - ty) + bottom * ty) * EXPOSURE).min(1.0);
+            if light <= 1.0 / 255.0 {
+                continue;
+            }
+            let channel = |value: f32, shift: u32| ((value * light * 255.0) as u32) << shift;
+            let color = channel(shaft_color.0, 16) | channel(shaft_color.1, 8) | channel(shaft_color.2, 0);
+            *pixel = BlendMode::Additive.apply(color, *pixel);
+        }
+    });
+}
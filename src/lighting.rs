@@ -0,0 +1,69 @@
+use serde::Deserialize;
+
+// ============= ATENUACIÓN DE LA LUZ DEL SOL =============
+// La luz que llega a un cuerpo cae con el cuadrado de la distancia al Sol: a la distancia de
+// referencia (la órbita de la Tierra en la escena de fábrica) la intensidad es 1, y Saturno o
+// Neptuno quedan más oscuros. Con 1/r² puro los planetas exteriores serían casi negros, así
+// que `compression` suaviza la caída: intensidad = (referencia / r)^(2 * compression).
+// La exposición automática sube la ganancia cuando la cámara mira a un cuerpo lejano y la
+// baja de nuevo al volver al sistema interior, con una transición de unos segundos.
+// Se configura en la sección [lighting] de settings.toml.
+
+// Tope de la intensidad junto al Sol para no quemar a blanco los planetas interiores
+const MAX_INTENSITY: f32 = 1.5;
+// Segundos que tarda la exposición en recorrer ~63% del camino hacia su objetivo
+const ADAPTATION_TIME: f32 = 1.2;
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct Lighting {
+    pub reference_distance: f32, // distancia al Sol con intensidad 1
+    pub compression: f32,        // 1 = 1/r² físico, 0 = sin atenuación
+    pub auto_exposure: bool,
+    pub max_exposure: f32,       // ganancia máxima de la exposición automática
+    pub light_shafts: bool,      // rayos crepusculares cuando un cuerpo tapa el Sol
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Lighting {
+            reference_distance: 250.0,
+            compression: 0.5,
+            auto_exposure: true,
+            max_exposure: 4.0,
+            light_shafts: true,
+        }
+    }
+}
+
+impl Lighting {
+    // Fracción de la luz del Sol que llega a `distance` unidades de él
+    pub fn intensity(&self, distance: f32) -> f32 {
+        let ratio = self.reference_distance / distance.max(1.0);
+        ratio.powf(2.0 * self.compression).min(MAX_INTENSITY)
+    }
+}
+
+// Ganancia aplicada a toda la iluminación del frame; nunca oscurece (mínimo 1)
+pub struct AutoExposure {
+    exposure: f32,
+}
+
+impl Default for AutoExposure {
+    fn default() -> Self {
+        AutoExposure { exposure: 1.0 }
+    }
+}
+
+impl AutoExposure {
+    // `focus_distance`: distancia al Sol del punto al que mira la cámara
+    pub fn update(&mut self, lighting: &Lighting, focus_distance: f32, dt: f32) -> f32 {
+        let target = if lighting.auto_exposure {
+            (1.0 / lighting.intensity(focus_distance)).clamp(1.0, lighting.max_exposure.max(1.0))
+        } else {
+            1.0
+        };
+        self.exposure += (target - self.exposure) * (1.0 - (-dt / ADAPTATION_TIME).exp());
+        self.exposure
+    }
+}
@@ -1,6 +1,7 @@
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
 use crate::color::Color;
+use crate::framebuffer::Framebuffer;
 
 pub fn line(a: &Vertex, b: &Vertex) -> Vec<Fragment> {
     let mut fragments = Vec::new();
@@ -39,4 +40,111 @@ pub fn line(a: &Vertex, b: &Vertex) -> Vec<Fragment> {
     }
 
     fragments
-}
\ No newline at end of file
+}
+
+// ============= LÍNEAS EN PANTALLA =============
+// Líneas de overlay (guías, órbitas, mallas de alambre) que se mezclan directamente sobre el
+// framebuffer, sin profundidad. Cada llamada elige el estilo: escalonada (Bresenham, píxeles
+// enteros) o suavizada con el algoritmo de Xiaolin Wu, que reparte cada punto de la línea
+// entre los dos píxeles que cruza según su cobertura y la usa como alfa de la mezcla.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LineStyle {
+    Aliased,
+    AntiAliased,
+}
+
+// `alpha` es la opacidad de toda la línea, en [0, 1]
+pub fn draw_line(framebuffer: &mut Framebuffer, from: (f32, f32), to: (f32, f32), color: u32, alpha: f32, style: LineStyle) {
+    match style {
+        LineStyle::Aliased => draw_line_aliased(framebuffer, from, to, color, alpha),
+        LineStyle::AntiAliased => draw_line_wu(framebuffer, from, to, color, alpha),
+    }
+}
+
+// Mezcla con recorte: las coordenadas pueden quedar fuera de la pantalla (o ser negativas)
+fn plot(framebuffer: &mut Framebuffer, x: i32, y: i32, color: u32, alpha: f32) {
+    if x >= 0 && y >= 0 && alpha > 0.0 {
+        framebuffer.blend_pixel(x as usize, y as usize, color, alpha);
+    }
+}
+
+fn draw_line_aliased(framebuffer: &mut Framebuffer, from: (f32, f32), to: (f32, f32), color: u32, alpha: f32) {
+    let (mut x0, mut y0) = (from.0.floor() as i32, from.1.floor() as i32);
+    let (x1, y1) = (to.0.floor() as i32, to.1.floor() as i32);
+
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let sx = if x0 < x1 { 1 } else { -1 };
+    let sy = if y0 < y1 { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    loop {
+        plot(framebuffer, x0, y0, color, alpha);
+        if x0 == x1 && y0 == y1 {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x0 += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y0 += sy;
+        }
+    }
+}
+
+// Xiaolin Wu: se avanza un píxel por paso sobre el eje mayor y en el eje menor se reparten
+// la parte entera y la fraccionaria de la posición exacta entre los dos píxeles vecinos.
+// Las coordenadas son de píxel con el centro en +0.5, como las del rasterizador
+fn draw_line_wu(framebuffer: &mut Framebuffer, from: (f32, f32), to: (f32, f32), color: u32, alpha: f32) {
+    let (mut x0, mut y0) = (from.0 - 0.5, from.1 - 0.5);
+    let (mut x1, mut y1) = (to.0 - 0.5, to.1 - 0.5);
+
+    // Líneas empinadas: se recorren por filas intercambiando los ejes
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+    if steep {
+        std::mem::swap(&mut x0, &mut y0);
+        std::mem::swap(&mut x1, &mut y1);
+    }
+    if x0 > x1 {
+        std::mem::swap(&mut x0, &mut x1);
+        std::mem::swap(&mut y0, &mut y1);
+    }
+
+    let dx = x1 - x0;
+    let gradient = if dx.abs() < 1e-6 { 1.0 } else { (y1 - y0) / dx };
+
+    let plot_pair = |framebuffer: &mut Framebuffer, major: i32, minor: f32, coverage: f32| {
+        let base = minor.floor();
+        let fraction = minor - base;
+        let (first, second) = (base as i32, base as i32 + 1);
+        let (lower, upper) = (alpha * coverage * (1.0 - fraction), alpha * coverage * fraction);
+        if steep {
+            plot(framebuffer, first, major, color, lower);
+            plot(framebuffer, second, major, color, upper);
+        } else {
+            plot(framebuffer, major, first, color, lower);
+            plot(framebuffer, major, second, color, upper);
+        }
+    };
+
+    // Extremos: la cobertura se reduce con la fracción del píxel que ocupa la línea
+    let start = x0.round();
+    let start_y = y0 + gradient * (start - x0);
+    plot_pair(framebuffer, start as i32, start_y, 1.0 - (x0 + 0.5 - start));
+    let end = x1.round();
+    let end_y = y1 + gradient * (end - x1);
+    if end > start {
+        plot_pair(framebuffer, end as i32, end_y, x1 + 0.5 - end);
+    }
+
+    // Tramo interior
+    let mut minor = start_y + gradient;
+    for major in (start as i32 + 1)..(end as i32) {
+        plot_pair(framebuffer, major, minor, 1.0);
+        minor += gradient;
+    }
+}
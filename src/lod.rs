@@ -0,0 +1,148 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use nalgebra_glm::{Mat4, Vec2, Vec3};
+use crate::vertex::Vertex;
+use crate::CelestialObject;
+
+// ============= NIVELES DE DETALLE DE LAS ESFERAS =============
+// Cada cuerpo elige la teselación de su esfera según el radio que ocupa en pantalla (no la
+// distancia): un asteroide lejano usa 80 triángulos y Júpiter llenando la ventana 5120.
+// Los niveles son icosaedros subdivididos, así que cada nivel contiene los vértices del
+// anterior más los puntos medios de sus aristas. Al entrar en un nivel esos puntos medios
+// parten de la arista plana del nivel anterior y se desplazan hasta la esfera a medida que
+// el cuerpo crece en pantalla (geomorphing): el contorno cambia de forma continua, sin saltos.
+
+const SUBDIVISIONS: usize = 4;                    // niveles 0..=3 (80, 320, 1280 y 5120 triángulos)
+const LEVEL_RADII: [f32; SUBDIVISIONS - 1] = [24.0, 72.0, 200.0]; // píxeles para entrar en cada nivel
+const MORPH_BAND: f32 = 0.5;                      // la transición dura hasta radio × (1 + banda)
+
+// Un nivel: la lista plana de triángulos (de tres en tres como la del OBJ) y, por vértice,
+// dónde estaba en la superficie del nivel anterior
+struct SphereLevel {
+    vertices: Vec<Vertex>,
+    coarse: Vec<Vec3>,
+}
+
+pub struct SphereLod {
+    levels: Vec<SphereLevel>,
+}
+
+// Malla indexada durante la subdivisión
+struct IndexedSphere {
+    positions: Vec<Vec3>,
+    coarse: Vec<Vec3>,
+    faces: Vec<[usize; 3]>,
+}
+
+impl IndexedSphere {
+    fn icosahedron() -> Self {
+        let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+        let positions: Vec<Vec3> = [
+            (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+            (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+            (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+        ]
+        .iter()
+        .map(|&(x, y, z)| Vec3::new(x, y, z).normalize())
+        .collect();
+        let faces = vec![
+            [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+            [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+            [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+            [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+        ];
+
+        IndexedSphere { coarse: positions.clone(), positions, faces }
+    }
+
+    // Parte cada triángulo en cuatro; los vértices que ya existían no se mueven
+    fn subdivide(&self) -> Self {
+        let mut positions = self.positions.clone();
+        let mut coarse = self.positions.clone();
+        let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut midpoint = |a: usize, b: usize| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let middle = (self.positions[a] + self.positions[b]) * 0.5;
+                positions.push(middle.normalize());
+                coarse.push(middle);
+                positions.len() - 1
+            })
+        };
+
+        let faces = self
+            .faces
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+
+        IndexedSphere { positions, coarse, faces }
+    }
+
+    fn flatten(&self) -> SphereLevel {
+        let corners = self.faces.iter().flatten();
+        SphereLevel {
+            vertices: corners.clone().map(|&index| sphere_vertex(self.positions[index])).collect(),
+            coarse: corners.map(|&index| self.coarse[index]).collect(),
+        }
+    }
+}
+
+// Vértice de la esfera unitaria: la normal es la propia dirección y las coordenadas de
+// textura son longitud y latitud
+fn sphere_vertex(position: Vec3) -> Vertex {
+    let tex_coords = Vec2::new(
+        0.5 + position.z.atan2(position.x) / std::f32::consts::TAU,
+        0.5 + position.y.clamp(-1.0, 1.0).asin() / std::f32::consts::PI,
+    );
+    Vertex::new(position, position, tex_coords)
+}
+
+impl SphereLod {
+    pub fn new() -> Self {
+        let mut mesh = IndexedSphere::icosahedron().subdivide();
+        let mut levels = vec![mesh.flatten()];
+        for _ in 1..SUBDIVISIONS {
+            mesh = mesh.subdivide();
+            levels.push(mesh.flatten());
+        }
+        SphereLod { levels }
+    }
+
+    // Malla para un cuerpo que ocupa `screen_radius` píxeles: prestada si el nivel está
+    // completo, o una copia con los puntos medios a medio camino durante la transición
+    pub fn select(&self, screen_radius: f32) -> Cow<'_, [Vertex]> {
+        let level = LEVEL_RADII.iter().take_while(|&&radius| screen_radius >= radius).count();
+        let SphereLevel { vertices, coarse } = &self.levels[level];
+        let Some(&entry_radius) = level.checked_sub(1).and_then(|index| LEVEL_RADII.get(index)) else {
+            return Cow::Borrowed(vertices);
+        };
+
+        let morph = (screen_radius - entry_radius) / (entry_radius * MORPH_BAND);
+        if morph >= 1.0 {
+            return Cow::Borrowed(vertices);
+        }
+        Cow::Owned(
+            vertices
+                .iter()
+                .zip(coarse)
+                .map(|(vertex, coarse)| {
+                    let position = coarse + (vertex.position - coarse) * morph;
+                    Vertex::new(position, position.normalize(), vertex.tex_coords)
+                })
+                .collect(),
+        )
+    }
+}
+
+// Radio aproximado del cuerpo en píxeles del framebuffer. A diferencia de
+// picking::screen_disk también responde para cuerpos detrás de la cámara o que la rodean
+// (se usa la distancia, no la profundidad), y sirve igual para la vista ortográfica.
+pub fn screen_radius(obj: &CelestialObject, camera_position: Vec3, projection_matrix: &Mat4, viewport_matrix: &Mat4) -> f32 {
+    let pixels_per_unit = projection_matrix[(1, 1)] * viewport_matrix[(1, 1)].abs();
+    let orthographic = projection_matrix[(3, 3)] != 0.0;
+    let depth = if orthographic { 1.0 } else { (obj.transform.translation - camera_position).magnitude().max(obj.transform.scale) };
+    obj.transform.scale * pixels_per_unit / depth
+}
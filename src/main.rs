@@ -1,6 +1,9 @@
-use nalgebra_glm::{Vec3, Mat4};
-use minifb::{Key, Window, WindowOptions};
-use std::time::Duration;
+use nalgebra_glm::{Vec2, Vec3, Mat4};
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use std::f32::consts::PI;
 
 mod framebuffer;
@@ -12,24 +15,196 @@ mod color;
 mod fragment;
 mod shaders;
 mod celestial_shaders;
-
-use framebuffer::Framebuffer;
+mod noise;
+mod scene;
+mod text;
+mod ui;
+mod encyclopedia;
+mod lesson;
+mod procedural;
+mod picking;
+mod random_system;
+mod exoplanets;
+mod narrator;
+mod terrain;
+mod scene_graph;
+mod atmosphere;
+mod landing;
+mod free_flight;
+mod flight_model;
+mod collision;
+mod weapons;
+mod drones;
+mod docking;
+mod ship_systems;
+mod mission;
+mod cockpit;
+mod spring;
+mod bookmarks;
+mod weather;
+mod ephemeris;
+mod comet;
+mod events;
+mod alignment;
+mod bench;
+mod lod;
+mod profiler;
+mod settings;
+mod theme;
+mod math;
+mod draw_state;
+mod render_graph;
+mod lighting;
+mod material;
+mod billboard;
+mod selftest;
+mod particles;
+mod watchdog;
+mod lens_flare;
+mod perf_history;
+mod light_shafts;
+mod nebula;
+mod image;
+mod skybox;
+mod star_catalog;
+mod corona;
+mod black_hole;
+mod cli;
+mod settings_menu;
+mod controls;
+mod tweaks;
+mod shader_params;
+mod scripting;
+mod components;
+mod systems;
+mod hot_reload;
+mod snapshot;
+mod input;
+mod replay;
+mod resolution;
+mod taa;
+mod motion;
+mod motion_blur;
+mod post;
+mod post_effects;
+mod gbuffer;
+mod outline;
+mod retro;
+mod audio;
+mod minimap;
+
+use framebuffer::{Channels, Framebuffer, Surface, NO_OBJECT};
 use vertex::Vertex;
 use obj::Obj;
-use triangle::triangle;
+use triangle::{triangle, triangle_point};
+use fragment::Fragment;
 use shaders::vertex_shader;
 use celestial_shaders::{CelestialBody, get_celestial_shader};
+use scene::{BeltConfig, BodyConfig, BodyMetadata, RingStyle, Scene};
+use encyclopedia::Encyclopedia;
+use lesson::{Lesson, LessonPlayer};
+use narrator::Narrator;
+use landing::Landing;
+use free_flight::FreeFlight;
+use collision::{Collider, Impact};
+use weapons::Weapons;
+use audio::{Audio, AudioConfig, Sound};
+use minimap::{Minimap, Viewer};
+use text::{Align, TextStyle};
+use drones::Squadron;
+use mission::{Mission, Pilot};
+use bookmarks::CameraBookmarks;
+use ephemeris::EphemerisClock;
+use comet::{Comet, CometTails};
+use events::EventLog;
+use alignment::{AlignmentFinder, FinderAction};
+use bench::Benchmark;
+use clap::Parser;
+use cli::Cli;
+use settings_menu::{MenuAction, RenderConfig, SettingsMenu};
+use controls::{Action, Controls};
+use tweaks::{ShaderTweaks, TweakOverrides, TweakPanel};
+use shader_params::ShaderParams;
+use scripting::ScriptHost;
+use components::{LightEmitter, Orbit, RingAttachment, Renderable, Shader, Spin, Transform};
+use hot_reload::{FileWatcher, Reload};
+use snapshot::Snapshot;
+use input::Input;
+use replay::{Recorder, Replay};
+use resolution::ResolutionController;
+use taa::TemporalAa;
+use motion::CameraMotion;
+use motion_blur::MotionBlur;
+use post::{PostProcess, Resample};
+use gbuffer::GBufferView;
+use picking::Target;
+use lod::SphereLod;
+use profiler::{Profiler, Stage};
+use settings::Settings;
+use watchdog::{Quality, Watchdog, WatchdogConfig};
+use perf_history::PerfSession;
+use theme::HudLayout;
+use draw_state::{CullMode, DrawState};
+use render_graph::{Pass, RenderGraph, Targets};
+use lighting::AutoExposure;
+use billboard::{Billboard, SpriteCamera, SpriteShader};
+use particles::{Emission, EmitterConfig, ParticleSystem};
+use scene_graph::{NodeId, SceneGraph};
+use procedural::{kepler_orbit_speed, ProceduralPlanet, SeededRng};
+use math::{
+    create_model_matrix, create_normal_matrix, create_orthographic_matrix, create_viewport_matrix, viewport_transform,
+    Backend, BackendMat3, BackendMat4, MathBackend,
+};
 
 
 pub struct Uniforms {
-    model_matrix: Mat4,
     view_matrix: Mat4,
     projection_matrix: Mat4,
+    viewport_matrix: Mat4,
     time: f32,
     current_shader: CelestialBody,
     light_position: Vec3,
     camera_position: Vec3,
     detail_level: f32,
+    // Luz del Sol que llega al cuerpo (atenuación con la distancia por la exposición)
+    light_intensity: f32,
+    // Matrices del modelo, calculadas una vez por llamada de dibujo y no por vértice
+    model_matrix: BackendMat4,
+    mvp_matrix: BackendMat4,
+    normal_matrix: BackendMat3,
+    // Vectores de movimiento (ver motion.rs): cámara y MVP del frame anterior para la velocidad
+    // de cada vértice (None sin TAA ni estelas) y desplazamiento subpíxel del TAA en píxeles
+    previous_view_projection: Option<Mat4>,
+    previous_mvp_matrix: Option<BackendMat4>,
+    jitter: Vec2,
+    // Objeto que dibuja la llamada, para el G-buffer (NO_OBJECT si no se puede seleccionar)
+    object_id: u32,
+    // Constantes del shader ajustables en vivo (F2, ver tweaks.rs)
+    tweaks: ShaderTweaks,
+    // Colores del shader (paleta de la escena, ver shader_params.rs)
+    params: ShaderParams,
+}
+
+impl Uniforms {
+    // Las mismas uniformes con otra matriz de modelo (anillos sobre su planeta)
+    fn with_model(&self, model_matrix: Mat4) -> Uniforms {
+        Uniforms {
+            model_matrix: Backend::mat4(&model_matrix),
+            mvp_matrix: Backend::mat4(&(self.projection_matrix * self.view_matrix * model_matrix)),
+            normal_matrix: Backend::mat3(&create_normal_matrix(&model_matrix)),
+            previous_mvp_matrix: self.previous_view_projection.map(|previous| Backend::mat4(&(previous * model_matrix))),
+            ..*self
+        }
+    }
+
+    // Modelo del frame anterior, para los cuerpos que se movieron (por defecto se toma el
+    // actual y solo cuenta el movimiento de la cámara)
+    fn with_previous_model(self, previous_model: Mat4) -> Uniforms {
+        Uniforms {
+            previous_mvp_matrix: self.previous_view_projection.map(|previous| Backend::mat4(&(previous * previous_model))),
+            ..self
+        }
+    }
 }
 
 struct Camera {
@@ -95,6 +270,19 @@ impl Camera {
         self.target.y += amount;
     }
 
+    // Colocar la cámara directamente (vista desde la superficie de un cuerpo)
+    fn place(&mut self, position: Vec3, target: Vec3, up: Vec3) {
+        self.position = position;
+        self.target = target;
+        self.up = up;
+    }
+
+    // Acercar suavemente la cámara a una posición y objetivo (factor en [0, 1] por frame)
+    fn ease_towards(&mut self, target: Vec3, position: Vec3, factor: f32) {
+        self.target += (target - self.target) * factor;
+        self.position += (position - self.position) * factor;
+    }
+
     fn zoom_in(&mut self, amount: f32) {
         let direction = (self.target - self.position).normalize();
         let current_distance = (self.position - self.target).magnitude();
@@ -138,272 +326,754 @@ impl Camera {
     }
 }
 
-fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
-    let (sin_x, cos_x) = rotation.x.sin_cos();
-    let (sin_y, cos_y) = rotation.y.sin_cos();
-    let (sin_z, cos_z) = rotation.z.sin_cos();
+// Parámetros de la proyección, modificables en tiempo de ejecución:
+// -/= cambian el campo de visión (teleobjetivo), 0 lo restablece y O alterna la vista de
+// mapa ortográfica, que encuadra lo mismo que la perspectiva a la distancia del objetivo
+#[derive(Clone, Copy, PartialEq)]
+struct Projection {
+    fov: f32,          // campo de visión vertical (grados)
+    near: f32,
+    far: f32,
+    orthographic: bool,
+    ortho_height: f32, // alto visible en la vista de mapa (unidades del mundo)
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection {
+            fov: DEFAULT_FOV,
+            near: 0.1,
+            far: 100_000.0, // los planetas exteriores orbitan a más de 1600 unidades
+            orthographic: false,
+            ortho_height: 1000.0,
+        }
+    }
+}
 
-    let rotation_matrix_x = Mat4::new(
-        1.0,  0.0,    0.0,   0.0,
-        0.0,  cos_x, -sin_x, 0.0,
-        0.0,  sin_x,  cos_x, 0.0,
-        0.0,  0.0,    0.0,   1.0,
-    );
+const DEFAULT_FOV: f32 = 45.0;
+const MIN_FOV: f32 = 10.0;
+const MAX_FOV: f32 = 100.0;
 
-    let rotation_matrix_y = Mat4::new(
-        cos_y,  0.0,  sin_y, 0.0,
-        0.0,    1.0,  0.0,   0.0,
-        -sin_y, 0.0,  cos_y, 0.0,
-        0.0,    0.0,  0.0,   1.0,
-    );
+impl Projection {
+    // factor < 1 acerca (campo más estrecho), > 1 aleja
+    fn zoom_lens(&mut self, factor: f32) {
+        self.fov = (self.fov * factor).clamp(MIN_FOV, MAX_FOV);
+    }
 
-    let rotation_matrix_z = Mat4::new(
-        cos_z, -sin_z, 0.0, 0.0,
-        sin_z,  cos_z, 0.0, 0.0,
-        0.0,    0.0,  1.0, 0.0,
-        0.0,    0.0,  0.0, 1.0,
-    );
+    // La vista de mapa muestra a la distancia del objetivo el mismo alto que la perspectiva
+    fn fit_ortho(&mut self, focus_distance: f32) {
+        self.ortho_height = 2.0 * focus_distance * (self.fov.to_radians() / 2.0).tan();
+    }
 
-    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
+    fn matrix(&self, width: f32, height: f32) -> Mat4 {
+        let aspect_ratio = width / height;
+        if !self.orthographic {
+            // Profundidad invertida en [0, 1] (ver framebuffer::FAR_DEPTH)
+            return nalgebra_glm::reversed_perspective_rh_zo(aspect_ratio, self.fov.to_radians(), self.near, self.far);
+        }
 
-    let transform_matrix = Mat4::new(
-        scale, 0.0,   0.0,   translation.x,
-        0.0,   scale, 0.0,   translation.y,
-        0.0,   0.0,   scale, translation.z,
-        0.0,   0.0,   0.0,   1.0,
-    );
+        // Ortográfica con la misma profundidad invertida
+        let half_height = self.ortho_height / 2.0;
+        let half_width = half_height * aspect_ratio;
+        create_orthographic_matrix(half_width, half_height, self.near, self.far)
+    }
+}
 
-    transform_matrix * rotation_matrix
+// Proyecta un punto del mundo a coordenadas de pantalla (None si queda detrás de la cámara
+// o delante del plano cercano: con profundidad invertida, z > w)
+fn project_to_screen(point: Vec3, view_matrix: &Mat4, projection_matrix: &Mat4, viewport_matrix: &Mat4) -> Option<Vec3> {
+    let clip = projection_matrix * view_matrix * nalgebra_glm::Vec4::new(point.x, point.y, point.z, 1.0);
+    if clip.w <= 0.0 || clip.z > clip.w {
+        return None;
+    }
+    let ndc = Vec3::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w);
+    Some(viewport_transform(viewport_matrix, ndc))
 }
 
-fn create_projection_matrix(window_width: f32, window_height: f32) -> Mat4 {
-    let fov = 45.0 * PI / 180.0;
-    let aspect_ratio = window_width / window_height;
-    let near = 0.1;
-    let far = 1000.0;
+// Reparto del vertex shader entre hilos: un vértice es muy barato, así que repartirlos de
+// uno en uno cuesta más en tareas de rayon que lo que se gana. Se agrupan en bloques (unos
+// cuantos por núcleo para equilibrar la carga) y las mallas pequeñas no se paralelizan
+const SEQUENTIAL_VERTEX_LIMIT: usize = 1024;
+const MIN_VERTEX_CHUNK: usize = 256;
+const CHUNKS_PER_THREAD: usize = 4;
+
+fn vertex_chunk_size(vertex_count: usize) -> Option<usize> {
+    let threads = rayon::current_num_threads();
+    if vertex_count < SEQUENTIAL_VERTEX_LIMIT || threads == 1 {
+        return None;
+    }
+    Some(vertex_count.div_ceil(threads * CHUNKS_PER_THREAD).max(MIN_VERTEX_CHUNK))
+}
 
-    nalgebra_glm::perspective(aspect_ratio, fov, near, far)
+// Buffers intermedios del pipeline que se conservan entre llamadas: render() se ejecuta para
+// cada cuerpo en cada frame, y crear los Vec de vértices, triángulos y fragmentos cada vez
+// era pura gestión de memoria. Se vacían al empezar y mantienen la capacidad ya reservada
+#[derive(Default)]
+struct RenderScratch {
+    vertices: Vec<Vertex>,
+    primitives: Vec<Primitive>,    // triángulos que miran a la cámara y sobreviven al descarte
+    fragments: Vec<Vec<Fragment>>, // fragmentos de cada primitiva (solo los primeros se usan)
 }
 
-// Sistema LOD de 3 niveles para máximo rendimiento
-// Retorna: (0=ultra_low, 1=low, 2=high)
-fn check_lod(object_position: Vec3, object_radius: f32, camera: &Camera) -> usize {
-    // Calcular distancia del objeto a la cámara
-    let to_object = object_position - camera.position;
-    let distance = to_object.magnitude();
-    
-    // ULTRA LOW POLY: MUY cerca (12 vértices, 20 triángulos) - MÁXIMO RENDIMIENTO
-    if distance < object_radius * 4.0 {
-        return 0; // Ultra low poly
+// Cada primitiva guarda el índice de su primer vértice en el buffer transformado
+#[derive(Clone, Copy)]
+enum Primitive {
+    Triangle(usize),
+    Point(usize), // triángulo diminuto que se dibuja como un solo fragmento
+}
+
+impl Primitive {
+    fn first_vertex(self) -> usize {
+        match self {
+            Primitive::Triangle(first) | Primitive::Point(first) => first,
+        }
     }
-    
-    // LOW POLY: Cerca-medio (482 vértices, 512 triángulos) - Buen rendimiento
-    if distance < object_radius * 12.0 {
-        return 1; // Low poly
+}
+
+// Los planetas lejanos generan cientos de triángulos más pequeños que un píxel. Si la caja
+// de un triángulo no contiene ningún centro de píxel del framebuffer no puede generar
+// fragmentos y se descarta antes de rasterizar; si contiene alguno pero su área no llega a MIN_TRIANGLE_AREA se dibuja
+// como un punto en su centroide, sin preparar la rasterización completa
+const MIN_TRIANGLE_AREA: f32 = 0.5; // píxeles²
+
+// Cuerpos de menos de un píxel: se dibujan además como un punto de luz (ver billboard.rs)
+// de este radio en píxeles de la ventana
+const DISTANT_POINT_PIXELS: f32 = 1.5;
+const DISTANT_POINT_COLOR: u32 = 0xFFF4E0;
+
+// Chispas de la corona: saltan de la superficie del Sol, la gravedad las devuelve en arco y se
+// enfrían de blanco a rojo. Rapidez en radios solares por segundo
+const CORONA_RAMP: [(f32, u32); 4] = [(0.0, 0xFFF8D0), (0.3, 0xFFB040), (0.7, 0xC03010), (1.0, 0x000000)];
+const CORONA_SPARKS: EmitterConfig = EmitterConfig {
+    spawn_rate: 400.0,
+    max_particles: 4096,
+    lifetime: (1.2, 2.2),
+    speed: (0.12, 0.35),
+    spread: 0.5,
+    size: (3.0, 1.0),
+    colors: &CORONA_RAMP,
+    max_pixels: 12.0,
+};
+const CORONA_GRAVITY: f32 = 0.45; // radios solares por segundo²
+
+// Paso de la simulación: lo que tardó de verdad el frame anterior, para que la nave, los
+// cuerpos, los drones, los láseres y las partículas vayan igual a cualquier velocidad de
+// frames, dentro de STEP_RANGE (tras un tirón o una pausa del depurador nada salta, y nunca
+// es cero). Al grabar, repetir o medir con el benchmark el paso es siempre FIXED_STEP y la
+// sesión sale igual
+const FIXED_STEP: f32 = 0.016;
+const STEP_RANGE: (f32, f32) = (0.001, 0.1);
+
+fn assemble_triangle(first: usize, a: &Vec3, b: &Vec3, c: &Vec3, double_area: f32, viewport: (usize, usize)) -> Option<Primitive> {
+    // Algún centro de píxel entre min y max, dentro de los `size` píxeles del framebuffer
+    let covers_pixel_center = |min: f32, max: f32, size: usize| {
+        let (first, last) = ((min - 0.5).ceil(), (max - 0.5).floor());
+        first <= last && last >= 0.0 && first < size as f32
+    };
+    if !covers_pixel_center(a.x.min(b.x).min(c.x), a.x.max(b.x).max(c.x), viewport.0)
+        || !covers_pixel_center(a.y.min(b.y).min(c.y), a.y.max(b.y).max(c.y), viewport.1)
+    {
+        return None;
     }
-    
-    // HIGH POLY: Lejos (482 vértices, 960 triángulos) - Mejor calidad
-    2 // High poly
+    if double_area * 0.5 < MIN_TRIANGLE_AREA {
+        return Some(Primitive::Point(first));
+    }
+    Some(Primitive::Triangle(first))
 }
 
-fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
-    Mat4::new(
-        width / 2.0, 0.0, 0.0, width / 2.0,
-        0.0, -height / 2.0, 0.0, height / 2.0,
-        0.0, 0.0, 1.0, 0.0,
-        0.0, 0.0, 0.0, 1.0
-    )
+thread_local! {
+    static RENDER_SCRATCH: RefCell<RenderScratch> = RefCell::new(RenderScratch::default());
 }
 
-fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
+fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, state: &DrawState, vertex_array: &[Vertex]) {
+    RENDER_SCRATCH.with(|scratch| render_with_scratch(framebuffer, uniforms, state, vertex_array, &mut scratch.borrow_mut()));
+}
+
+fn render_with_scratch(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    state: &DrawState,
+    vertex_array: &[Vertex],
+    scratch: &mut RenderScratch,
+) {
     use rayon::prelude::*;
+
+    let RenderScratch { vertices, primitives, fragments } = scratch;
+    let viewport = (framebuffer.width, framebuffer.height);
+    let multisample = framebuffer.multisample();
     
-    // Vertex Shader Stage (PARALELO - 2-4x más rápido en multi-core)
-    let transformed_vertices: Vec<Vertex> = vertex_array
-        .par_iter()
-        .map(|vertex| vertex_shader(vertex, uniforms))
-        .collect();
+    // Vertex Shader Stage (PARALELO por bloques; las mallas pequeñas, en secuencia)
+    profiler::measure(Stage::VertexShading, || {
+        vertices.clear();
+        match vertex_chunk_size(vertex_array.len()) {
+            None => vertices.extend(vertex_array.iter().map(|vertex| vertex_shader(vertex, uniforms))),
+            Some(chunk_size) => {
+                vertices.resize_with(vertex_array.len(), Vertex::default);
+                vertices
+                    .par_chunks_mut(chunk_size)
+                    .zip(vertex_array.par_chunks(chunk_size))
+                    .for_each(|(output, input)| {
+                        for (transformed, vertex) in output.iter_mut().zip(input) {
+                            *transformed = vertex_shader(vertex, uniforms);
+                        }
+                    });
+            }
+        }
+    });
 
     // Primitive Assembly Stage (secuencial - es muy rápido)
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            // Backface culling TEMPRANO (antes de rasterizar)
-            let v0 = &transformed_vertices[i].transformed_position;
-            let v1 = &transformed_vertices[i + 1].transformed_position;
-            let v2 = &transformed_vertices[i + 2].transformed_position;
-            
-            // Producto cruz en 2D (determina orientación)
-            let edge1_x = v1.x - v0.x;
-            let edge1_y = v1.y - v0.y;
-            let edge2_x = v2.x - v0.x;
-            let edge2_y = v2.y - v0.y;
-            let cross = edge1_x * edge2_y - edge1_y * edge2_x;
-            
-            // Si cross <= 0, el triángulo está de espaldas - SALTAR
-            if cross > 0.0 {
-                triangles.push([
-                    transformed_vertices[i].clone(),
-                    transformed_vertices[i + 1].clone(),
-                    transformed_vertices[i + 2].clone(),
-                ]);
-            }
-        }
-    }
-
-    // Rasterización y Fragment Shader (PARALELO con chunks)
-    // Procesar triángulos en paralelo y luego escribir al framebuffer
-    let fragments: Vec<_> = triangles
-        .par_iter()
-        .flat_map(|tri| {
-            let frags = triangle(&tri[0], &tri[1], &tri[2]);
-            frags.into_iter().map(|mut frag| {
-                // Aplicar shader
-                let shader_color = get_celestial_shader(uniforms.current_shader, &frag, &tri[0], uniforms);
-                frag.color = shader_color;
-                frag
-            }).collect::<Vec<_>>()
-        })
-        .collect();
+    profiler::measure(Stage::PrimitiveAssembly, || {
+        primitives.clear();
+        for i in (0..vertices.len()).step_by(3) {
+            if i + 2 < vertices.len() {
+                // Backface culling TEMPRANO (antes de rasterizar)
+                let v0 = &vertices[i].transformed_position;
+                let v1 = &vertices[i + 1].transformed_position;
+                let v2 = &vertices[i + 2].transformed_position;
+                
+                // Producto cruz en 2D (determina orientación)
+                let edge1_x = v1.x - v0.x;
+                let edge1_y = v1.y - v0.y;
+                let edge2_x = v2.x - v0.x;
+                let edge2_y = v2.y - v0.y;
+                let cross = edge1_x * edge2_y - edge1_y * edge2_x;
+                
+                // Si cross <= 0, el triángulo está de espaldas: se salta o no según el modo de descarte
+                if state.cull.keeps(cross) {
+                    primitives.extend(assemble_triangle(i, v0, v1, v2, cross.abs(), viewport));
+                }
+            }
+        }
+    });
+    profiler::count_triangles(vertices.len() / 3, primitives.len());
+
+    if fragments.len() < primitives.len() {
+        fragments.resize_with(primitives.len(), Vec::new);
+    }
+    let fragments = &mut fragments[..primitives.len()];
+    let vertices = &*vertices;
+
+    // Rasterización (PARALELO): los fragmentos de cada primitiva, aún sin color
+    profiler::measure(Stage::Rasterization, || {
+        fragments.par_iter_mut().zip(primitives.par_iter()).for_each(|(frags, &primitive)| {
+            frags.clear();
+            match primitive {
+                Primitive::Triangle(i) => triangle(&vertices[i], &vertices[i + 1], &vertices[i + 2], viewport, multisample, frags),
+                Primitive::Point(i) => frags.push(triangle_point(&vertices[i], &vertices[i + 1], &vertices[i + 2])),
+            }
+        });
+    });
+
+    // Fragment Shader (PARALELO): cada fragmento con el shader del cuerpo
+    profiler::measure(Stage::FragmentShading, || {
+        fragments.par_iter_mut().zip(primitives.par_iter()).for_each(|(frags, primitive)| {
+            let first = &vertices[primitive.first_vertex()];
+            for frag in frags.iter_mut() {
+                frag.color = get_celestial_shader(uniforms.current_shader, frag, first, uniforms);
+            }
+        });
+    });
     
     // Escribir fragmentos al framebuffer (secuencial para evitar race conditions en z-buffer)
-    for frag in fragments {
-        let x = frag.position.x as usize;
-        let y = frag.position.y as usize;
-        if x < framebuffer.width && y < framebuffer.height {
-            let color = frag.color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, frag.depth);
+    // Con vectores de movimiento, lo que escribe profundidad deja también su velocidad (la
+    // media de la de sus tres vértices); con G-buffer, su objeto, su normal y su posición
+    let motion_vectors = framebuffer.motion_vectors() && state.depth_write;
+    let surfaces = framebuffer.channels() != Channels::default() && state.depth_write;
+    profiler::measure(Stage::FramebufferWrite, || {
+        for (frags, primitive) in fragments.iter().zip(primitives.iter()) {
+            let first = primitive.first_vertex();
+            let corners = &vertices[first..first + 3];
+            let velocity = (corners[0].velocity + corners[1].velocity + corners[2].velocity) / 3.0;
+            let surface = surfaces.then(|| Surface {
+                object: uniforms.object_id,
+                normal: (corners[0].transformed_normal + corners[1].transformed_normal + corners[2].transformed_normal).normalize(),
+                world_position: (corners[0].world_position + corners[1].world_position + corners[2].world_position) / 3.0,
+            });
+            for frag in frags {
+                let x = frag.position.x as usize;
+                let y = frag.position.y as usize;
+                if x < framebuffer.width
+                    && y < framebuffer.height
+                    && framebuffer.draw_coverage(x, y, frag.depth, frag.color.to_hex(), frag.coverage, state)
+                {
+                    if motion_vectors {
+                        framebuffer.set_velocity(x, y, velocity);
+                    }
+                    if let Some(surface) = &surface {
+                        framebuffer.set_surface(x, y, surface);
+                    }
+                }
+            }
         }
-    }
+    });
 }
 
+// Cuerpo de la escena: una entidad con su id, sus metadatos y sus componentes
+// (ver components.rs); los sistemas del bucle principal están en systems.rs
+#[derive(Clone)]
 struct CelestialObject {
-    body_type: CelestialBody,
-    translation: Vec3,
-    rotation: Vec3,
-    scale: f32,
-    rotation_speed: Vec3,
-    orbit_speed: f32,
-    orbit_radius: f32,
-    orbit_center: Vec3,
-    use_large_sphere: bool,
+    id: String,
+    metadata: BodyMetadata,
+    transform: Transform,
+    spin: Spin,
+    renderable: Renderable,
+    shader: Shader,
+    orbit: Option<Orbit>,
+    rings: Option<RingAttachment>,
+    light: Option<LightEmitter>,
 }
 
 impl CelestialObject {
-    fn new(body_type: CelestialBody, translation: Vec3, scale: f32, use_large_sphere: bool) -> Self {
+    fn new(body_type: CelestialBody, translation: Vec3, scale: f32) -> Self {
         CelestialObject {
-            body_type,
-            translation,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            scale,
-            rotation_speed: Vec3::new(0.0, 0.01, 0.0),
-            orbit_speed: 0.0,
-            orbit_radius: 0.0,
-            orbit_center: Vec3::new(400.0, 300.0, 0.0),
-            use_large_sphere,
+            id: String::new(),
+            metadata: BodyMetadata::default(),
+            transform: Transform::new(translation, scale),
+            spin: Spin::default(),
+            renderable: Renderable::default(),
+            shader: Shader::new(body_type),
+            orbit: None,
+            rings: None,
+            light: body_type.is_star().then_some(LightEmitter),
         }
     }
 
+    // Construir un cuerpo a partir de su descripción en el archivo de escena
+    fn from_config(config: &BodyConfig) -> Self {
+        let [x, y, z] = config.position;
+        let [rx, ry, rz] = config.rotation_speed;
+        let [tx, ty, tz] = config.axial_tilt;
+        CelestialObject::new(config.shader, Vec3::new(x, y, z), config.scale)
+            .with_orbit(config.orbit_radius, config.orbit_speed)
+            .with_orbit_phase(config.orbit_phase)
+            .with_rotation_speed(Vec3::new(rx, ry, rz))
+            .with_axial_tilt(Vec3::new(tx.to_radians(), ty.to_radians(), tz.to_radians()))
+            .with_tidal_lock(config.tidally_locked)
+            .with_cull(config.cull)
+            .with_rings(config.rings.or(match config.shader {
+                CelestialBody::Procedural(planet) => planet.rings,
+                _ => None,
+            }))
+            .with_metadata(config.id.clone(), config.metadata.clone())
+    }
+
+    // Planeta procedural: la semilla define su aspecto, sus anillos y sus metadatos
+    fn procedural(seed: u64, scale: f32) -> Self {
+        let planet = ProceduralPlanet::from_seed(seed);
+        CelestialObject::new(CelestialBody::Procedural(planet), Vec3::new(600.0, 400.0, 0.0), scale)
+            .with_rings(planet.rings)
+            .with_metadata(format!("procedural-{}", seed), planet.metadata())
+    }
+
+    fn with_params(mut self, params: ShaderParams) -> Self {
+        self.shader.params = params;
+        self
+    }
+
+    // Sin radio el cuerpo no orbita: se queda en su posición
     fn with_orbit(mut self, radius: f32, speed: f32) -> Self {
-        self.orbit_radius = radius;
-        self.orbit_speed = speed;
+        self.orbit = (radius > 0.0).then(|| Orbit::new(radius, speed));
+        self
+    }
+
+    fn with_orbit_phase(mut self, phase: f32) -> Self {
+        if let Some(orbit) = self.orbit.as_mut() {
+            orbit.phase = phase;
+        }
+        self
+    }
+
+    // Registra el cuerpo en el grafo de escena. Con padre, la órbita es local a él:
+    // gira alrededor de su origen en lugar del centro del sistema
+    fn attach(mut self, graph: &mut SceneGraph, parent: Option<NodeId>) -> Self {
+        if parent.is_some() {
+            if let Some(orbit) = self.orbit.as_mut() {
+                orbit.center = Vec3::zeros();
+            }
+            self.transform.local_translation = Vec3::zeros();
+        }
+        self.transform.node = Some(graph.add_node(parent, nalgebra_glm::translation(&self.transform.local_translation)));
         self
     }
 
     fn with_rotation_speed(mut self, speed: Vec3) -> Self {
-        self.rotation_speed = speed;
+        self.spin.speed = speed;
         self
     }
 
-    fn update(&mut self, time: f32) {
-        // Rotación propia
-        self.rotation = self.rotation + self.rotation_speed;
+    fn with_axial_tilt(mut self, tilt: Vec3) -> Self {
+        self.transform.axial_tilt = tilt;
+        self
+    }
 
-        // Órbita
-        if self.orbit_radius > 0.0 {
-            let angle = time * self.orbit_speed;
-            self.translation.x = self.orbit_center.x + angle.cos() * self.orbit_radius;
-            self.translation.z = angle.sin() * self.orbit_radius;
+    fn with_tidal_lock(mut self, locked: bool) -> Self {
+        self.spin.tidally_locked = locked;
+        self
+    }
+
+    fn with_rings(mut self, rings: Option<RingStyle>) -> Self {
+        self.rings = rings.map(|style| RingAttachment { style });
+        self
+    }
+
+    fn with_cull(mut self, cull: CullMode) -> Self {
+        self.renderable.cull = cull;
+        self
+    }
+
+    fn with_model(mut self, model: Option<Arc<Vec<Vertex>>>) -> Self {
+        self.renderable.model = model;
+        self
+    }
+
+    fn with_metadata(mut self, id: String, metadata: BodyMetadata) -> Self {
+        self.id = id;
+        self.metadata = metadata;
+        self
+    }
+
+    // Nombre para mostrar: el de los metadatos o, si falta, el id de la escena
+    fn display_name(&self) -> &str {
+        if self.metadata.name.is_empty() {
+            &self.id
+        } else {
+            &self.metadata.name
         }
     }
 }
 
+// Cuerpos de una escena. Se construyen al arrancar y otra vez al recargarla (ver hot_reload.rs)
+struct SceneBodies {
+    celestial_objects: Vec<CelestialObject>,
+    asteroids: Vec<CelestialObject>, // rocas de los cinturones (sin etiqueta ni selección)
+    comets: Vec<Comet>,              // órbitas elípticas (sin etiqueta ni selección)
+    comet_tails: Vec<CometTails>,
+    scene_graph: SceneGraph,         // cada cuerpo es un nodo y las lunas cuelgan del de su planeta
+    scripts: ScriptHost,             // scripts Rhai de los cuerpos (ver scripting.rs)
+}
+
+impl SceneBodies {
+    fn build(scene: &Scene, models: &mut HashMap<String, Arc<Vec<Vertex>>>) -> Result<Self, String> {
+        let mut scene_graph = SceneGraph::new();
+
+        // TODOS usan esfera_chica (LOW POLY) para MEJOR RENDIMIENTO
+        // El planeta de cada luna siempre aparece antes que ella en la lista
+        let mut celestial_objects: Vec<CelestialObject> = Vec::with_capacity(scene.bodies.len());
+        let mut scripts = ScriptHost::new();
+        for config in &scene.bodies {
+            if let Some(path) = &config.script {
+                scripts.attach(celestial_objects.len(), path)?;
+            }
+            let parent_node = scene.parent_index(config).and_then(|index| celestial_objects[index].transform.node);
+            let body = CelestialObject::from_config(config)
+                .with_params(config.params().map_err(|err| err.to_string())?)
+                .with_model(load_model(models, &config.model)?);
+            celestial_objects.push(body.attach(&mut scene_graph, parent_node));
+        }
+
+        let mut asteroids = Vec::new();
+        for config in scene.belts.iter().flat_map(BeltConfig::rocks) {
+            let rock = CelestialObject::from_config(&config).with_model(load_model(models, &config.model)?);
+            asteroids.push(rock.attach(&mut scene_graph, None));
+        }
+
+        let comets: Vec<Comet> = scene.comets.iter().map(Comet::from_config).collect();
+        let comet_tails = comets.iter().enumerate().map(|(index, comet)| CometTails::new(comet, index as u64)).collect();
+
+        Ok(SceneBodies { celestial_objects, asteroids, comets, comet_tails, scene_graph, scripts })
+    }
+}
+
+// Malla importada, leída una sola vez por archivo aunque la usen muchos cuerpos
+fn load_model(models: &mut HashMap<String, Arc<Vec<Vertex>>>, path: &Option<String>) -> Result<Option<Arc<Vec<Vertex>>>, String> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    if let Some(model) = models.get(path) {
+        return Ok(Some(Arc::clone(model)));
+    }
+    let mesh = Obj::load(path).map_err(|err| format!("{}: {}", path, err))?;
+    println!("Malla {}: {}", path, mesh.stats());
+    let model = Arc::new(mesh.normalized().get_vertex_array());
+    models.insert(path.clone(), Arc::clone(&model));
+    Ok(Some(model))
+}
+
+// Fondo opcional de la escena: nebulosa y cielo (se recalculan solo cuando la cámara gira)
+// y estrellas reales del catálogo con las figuras de las constelaciones
+struct SceneBackground {
+    nebula: Option<RefCell<nebula::Nebula>>,
+    skybox: Option<RefCell<skybox::Skybox>>,
+    star_catalog: Option<star_catalog::StarCatalog>,
+}
+
+impl SceneBackground {
+    fn load(scene: &Scene) -> Result<Self, String> {
+        let skybox = match &scene.skybox {
+            Some(config) => Some(RefCell::new(skybox::Skybox::load(config).map_err(|err| format!("skybox: {}", err))?)),
+            None => None,
+        };
+        let star_catalog = match &scene.star_catalog {
+            Some(config) => Some(star_catalog::StarCatalog::load(config).map_err(|err| format!("star catalog: {}", err))?),
+            None => None,
+        };
+        Ok(SceneBackground {
+            nebula: scene.nebula.as_ref().map(|config| RefCell::new(nebula::Nebula::new(config))),
+            skybox,
+            star_catalog,
+        })
+    }
+}
+
+// Constantes de los shaders de cada cuerpo: las de fábrica con las del archivo de parámetros encima
+fn apply_shader_params(objects: &mut [CelestialObject], params: &BTreeMap<String, TweakOverrides>) {
+    for obj in objects.iter_mut() {
+        obj.shader.tweaks = match params.get(&obj.id) {
+            Some(overrides) => overrides.apply(obj.shader.body),
+            None => ShaderTweaks::for_body(obj.shader.body),
+        };
+    }
+    for id in params.keys().filter(|id| !objects.iter().any(|obj| &obj.id == *id)) {
+        eprintln!("Parámetros: no hay ningún cuerpo con id '{}'", id);
+    }
+}
+
 fn main() {
-    let window_width = 1200;
-    let window_height = 800;
-    // Supersampling dinámico: factor cambia según la distancia de la cámara
-    let mut supersample_factor = 2usize;
-    let mut framebuffer_width = window_width * supersample_factor;
-    let mut framebuffer_height = window_height * supersample_factor;
-    let frame_delay = Duration::from_millis(16);
+    // Opciones de arranque (ver cli.rs o --help)
+    let cli = Cli::parse();
 
-    let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
-    let mut window = Window::new(
-        "Solar System - Celestial Bodies Renderer",
-        window_width,
-        window_height,
-        WindowOptions::default(),
-    )
-    .unwrap();
+    // Autoprueba sin ventana: rasterizador, shaders y capacidades de la máquina
+    if cli.selftest {
+        std::process::exit(selftest::run());
+    }
 
-    window.set_position(200, 100);
-    window.update();
+    let window_width = cli.width as usize;
+    let window_height = cli.height as usize;
+    // Resolución adaptativa: el tamaño del framebuffer de la escena sigue al tiempo de frame
+    // (ver resolution.rs); --supersample o el menú de ajustes fijan un factor
+    let initial_supersample = cli.supersample.unwrap_or(1) as usize;
+    let mut framebuffer_width = window_width * initial_supersample;
+    let mut framebuffer_height = window_height * initial_supersample;
+
+    let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
+    // El framebuffer del nivel anterior se guarda al cambiar de resolución para no volver a
+    // reservarlo (el regulador suele alternar entre dos niveles vecinos)
+    let mut spare_framebuffer: Option<Framebuffer> = None;
+    // Capa con solo los discos de acreción, del tamaño de la escena (ver black_hole.rs)
+    let disc_layer = RefCell::new(Framebuffer::new(0, 0));
+    // Con --headless no hay ventana: el benchmark se dibuja igual y solo se escribe el informe
+    let mut window = (!cli.headless).then(|| {
+        let mut window = Window::new(
+            "Solar System - Celestial Bodies Renderer",
+            window_width,
+            window_height,
+            WindowOptions::default(),
+        )
+        .unwrap();
+        window.set_position(200, 100);
+        window.update();
+        window
+    });
 
     framebuffer.set_background_color(0x000011);
 
     // Cargar los modelos de esferas (rutas ajustadas a la carpeta `models/` en la raíz del proyecto)
     // Cargar modelo LOW POLY optimizado (178 vértices, 192 caras)
-    let sphere_low = Obj::load("models/Esfera_Low.obj").expect("Failed to load Esfera_Low.obj");
-    let sphere_low_vertices = sphere_low.get_vertex_array();    // Crear los cuerpos celestes con distancias orbitales bien separadas
-    // TODOS usan esfera_chica (LOW POLY) para MEJOR RENDIMIENTO
-    let mut celestial_objects = vec![
-        // Sol (centro) - esfera LOW
-        CelestialObject::new(CelestialBody::Sun, Vec3::new(600.0, 400.0, 0.0), 80.0, false)
-            .with_rotation_speed(Vec3::new(0.0, 0.005, 0.0)),
-        
-        // Mercurio (Lava Planet) - esfera LOW, muy cerca del sol
-        CelestialObject::new(CelestialBody::LavaPlanet, Vec3::new(600.0, 400.0, 0.0), 15.0, false)
-            .with_orbit(150.0, 0.47)
-            .with_rotation_speed(Vec3::new(0.0, 0.01, 0.0)),
-        
-        // Tierra - esfera LOW
-        CelestialObject::new(CelestialBody::Earth, Vec3::new(600.0, 400.0, 0.0), 28.0, false)
-            .with_orbit(250.0, 0.35)
-            .with_rotation_speed(Vec3::new(0.0, 0.02, 0.0)),
-        
-        // Marte - esfera LOW (más separado)
-        CelestialObject::new(CelestialBody::Mars, Vec3::new(600.0, 400.0, 0.0), 20.0, false)
-            .with_orbit(450.0, 0.24)
-            .with_rotation_speed(Vec3::new(0.0, 0.02, 0.0)),
-        
-        // Júpiter - esfera LOW (bien separado)
-        CelestialObject::new(CelestialBody::Jupiter, Vec3::new(600.0, 400.0, 0.0), 55.0, false)
-            .with_orbit(700.0, 0.13)
-            .with_rotation_speed(Vec3::new(0.0, 0.03, 0.0)),
-        
-        // Saturno - esfera LOW (el más lejano, muy separado)
-        CelestialObject::new(CelestialBody::Saturn, Vec3::new(600.0, 400.0, 0.0), 50.0, false)
-            .with_orbit(1000.0, 0.08)
-            .with_rotation_speed(Vec3::new(0.0, 0.025, 0.0)),
-        
-        // Urano (Ice Planet) - esfera LOW, muy lejano
-        CelestialObject::new(CelestialBody::IcePlanet, Vec3::new(600.0, 400.0, 0.0), 42.0, false)
-            .with_orbit(1300.0, 0.06)
-            .with_rotation_speed(Vec3::new(0.0, 0.022, 0.0)),
-        
-        // Neptuno (Alien Planet) - esfera LOW, el más lejano
-        CelestialObject::new(CelestialBody::AlienPlanet, Vec3::new(600.0, 400.0, 0.0), 40.0, false)
-            .with_orbit(1600.0, 0.04)
-            .with_rotation_speed(Vec3::new(0.0, 0.02, 0.0)),
-    ];
+    let sphere_low = Obj::load("models/Esfera_Low.obj").unwrap_or_else(|err| panic!("Failed to load models/Esfera_Low.obj: {}", err));
+    println!("Malla models/Esfera_Low.obj: {}", sphere_low.stats());
+    let sphere_low_vertices = sphere_low.get_vertex_array();
+    // Nave del vuelo libre, con los colores de los materiales de Air_Wing.mtl
+    let ship = Obj::load("models/airwing.obj").unwrap_or_else(|err| panic!("Failed to load models/airwing.obj: {}", err));
+    println!("Malla models/airwing.obj: {}", ship.stats());
+    let ship_vertices = ship.normalized().get_vertex_array();
+    // Esferas teseladas según el tamaño en pantalla para los cuerpos del sistema
+    let sphere_lod = SphereLod::new();
+    let accretion_disc = black_hole::accretion_disc_mesh();
+
+    // Ajustes del usuario: tema de la interfaz (colores, opacidad y colocación de los widgets)
+    // y atenuación de la luz del Sol
+    let settings = Settings::load(cli.settings.as_deref())
+        .unwrap_or_else(|err| panic!("Failed to load {}: {}", cli.settings.as_deref().unwrap_or(settings::DEFAULT_PATH), err));
+    theme::set(settings.ui);
+
+    // Teclas de la cámara, la captura y la pausa (controls.toml; se crea si no existe)
+    let controls = Controls::load(controls::DEFAULT_PATH)
+        .unwrap_or_else(|err| panic!("Failed to load {}: {}", controls::DEFAULT_PATH, err));
+    let mut paused = false;
+    let mut screenshot_requested = false;
+
+    // Menú de ajustes (Esc): calidad en vivo, guardada en [render]. --supersample sustituye
+    // al factor guardado
+    let mut settings_menu = SettingsMenu::new(RenderConfig {
+        supersample: cli.supersample.unwrap_or(settings.render.supersample),
+        ..settings.render
+    });
+
+    // Panel de ajuste de shaders (F2): controles deslizantes del cuerpo seleccionado
+    let mut tweak_panel = TweakPanel::new();
+
+    // Cargar la escena (cuerpos, órbitas y metadatos), generar un sistema aleatorio o
+    // importar un sistema real de un catálogo de exoplanetas
+    let scene = match (cli.random_system, &cli.exoplanets) {
+        (Some(seed), _) => random_system::generate(seed),
+        (None, Some(path)) => exoplanets::import(path, cli.system.as_deref())
+            .unwrap_or_else(|err| panic!("Failed to import {}: {}", path, err)),
+        (None, None) => {
+            let scene_path = cli.scene_path();
+            Scene::load(scene_path).unwrap_or_else(|err| panic!("Failed to load {}: {}", scene_path, err))
+        }
+    };
 
-    // Luna de la Tierra - esfera chica (SUPER CERCA de la Tierra)
-    let mut earth_moon = CelestialObject::new(CelestialBody::Moon, Vec3::new(600.0, 400.0, 0.0), 8.0, false)
-        .with_orbit(15.0, 1.2)  // Órbita SUPER cercana (15 unidades) - la luna está bastante cerca
-        .with_rotation_speed(Vec3::new(0.0, 0.01, 0.0));
+    // Nebulosa, cielo y estrellas de fondo opcionales de la escena (L muestra las constelaciones)
+    let SceneBackground { mut nebula, mut skybox, mut star_catalog } =
+        SceneBackground::load(&scene).unwrap_or_else(|err| panic!("Failed to load {}", err));
+    // Cadena de posproceso de la escena ([[post]]: halo, tonos, FXAA, viñeta, tubo)
+    let mut post_chain = post::chain(&scene.post);
+    let mut show_constellations = false;
+
+    // Mallas importadas (`model` en la escena), una copia por archivo aunque la usen muchos cuerpos
+    let mut models: HashMap<String, Arc<Vec<Vertex>>> = HashMap::new();
+
+    // Cuerpos, rocas de los cinturones y cometas, con el grafo de escena que los coloca
+    let SceneBodies { mut celestial_objects, mut asteroids, mut comets, mut comet_tails, mut scene_graph, mut scripts } =
+        SceneBodies::build(&scene, &mut models).unwrap_or_else(|err| panic!("Failed to load {}", err));
+
+    // Constantes de los shaders por cuerpo (--params o shader_params.toml, ver tweaks.rs)
+    let mut shader_params = tweaks::load_params(cli.params.as_deref()).unwrap_or_else(|err| {
+        panic!("Failed to load {}: {}", cli.params.as_deref().unwrap_or(tweaks::DEFAULT_PARAMS_PATH), err)
+    });
+    apply_shader_params(&mut celestial_objects, &shader_params);
+
+    // Repetición de una entrada grabada (--replay): sustituye a la ventana mientras dura
+    let mut replay = cli.replay.as_ref().map(|path| {
+        let replay = Replay::load(path).unwrap_or_else(|err| panic!("Failed to load {}: {}", path, err));
+        println!("Repetición {}: {} frames", path, replay.frame_count());
+        replay
+    });
+
+    // Partículas que no dependen del tiempo de la simulación: chispas del Sol y escape de la nave
+    // --seed hace repetibles las partículas y los planetas que se añaden con P; la repetición
+    // trae su semilla y al grabar sin --seed se elige una para guardarla con la entrada
+    let seed = cli
+        .seed
+        .or(replay.as_ref().and_then(|replay| replay.seed))
+        .or_else(|| cli.record.as_ref().map(|_| clock_seed()));
+    let session_seed = seed.unwrap_or(0);
+    let mut session_rng = seed.map(SeededRng::new);
+    let mut corona_sparks = ParticleSystem::new(CORONA_SPARKS, 0x5A2C ^ session_seed);
+    let mut engine_exhaust = ParticleSystem::new(free_flight::EXHAUST, 0xE7A4 ^ session_seed);
+    let mut explosions = ParticleSystem::new(collision::EXPLOSION, 0xB0A4 ^ session_seed);
+    let mut debris = ParticleSystem::new(weapons::DEBRIS, 0xDEB2 ^ session_seed);
+    let mut weapons = Weapons::new();
+    let mut squadron = Squadron::new(0xD404 ^ session_seed);
+
+    // Sonido (feature audio): música, motores, láseres, explosiones y clics. Sin ventana
+    // (--headless) no suena
+    let mut audio = Audio::new(AudioConfig { enabled: settings.audio.enabled && !cli.headless, ..settings.audio });
+
+    let body_ids: Vec<String> = celestial_objects.iter().map(|obj| obj.id.clone()).collect();
+
+    // Lección guiada opcional (Espacio avanza de paso)
+    let mut lesson = cli.lesson.as_ref().map(|path| {
+        Lesson::load(path)
+            .and_then(|lesson| LessonPlayer::new(lesson, &body_ids))
+            .unwrap_or_else(|err| panic!("Failed to load {}: {}", path, err))
+    });
+
+    // Cuerpo seleccionado (Tab para cambiar): muestra su panel de información
+    let mut selected: Option<usize> = None;
+    // La nave también se puede seleccionar (clic o al despegar en vuelo libre); un cuerpo
+    // seleccionado tiene prioridad
+    let mut ship_selected = false;
+
+    // Clic izquierdo (detección de flanco): selecciona cuerpos y responde preguntas
+    let mut mouse_was_down = false;
+
+    // Enciclopedia: páginas por cuerpo, se abre con Enter desde el panel de información
+    let mut encyclopedia = Encyclopedia::new();
+
+    // Narrador accesible opcional: describe la vista en texto al cambiar el foco
+    let mut narrator = cli.narrate.as_ref().map(|path| {
+        Narrator::open(path).unwrap_or_else(|err| panic!("Failed to open {}: {}", path, err))
+    });
+
+    // Efemérides opcionales: los planetas ocupan su posición real en la fecha indicada
+    let mut ephemeris_clock = cli.date.as_ref().map(|date| {
+        EphemerisClock::new(ephemeris::parse_date(date).unwrap_or_else(|err| panic!("Invalid --date: {}", err)))
+    });
+
+    // Registro de eventos (eclipses, conjunciones, perihelios, tormentas) y línea de tiempo (T)
+    let mut event_log = EventLog::new(scene.events.clone());
+
+    // Buscador de alineaciones (C): próximos momentos en que dos cuerpos se ven juntos
+    let mut finder = AlignmentFinder::new();
+
+    // Minimapa: vista cenital del sistema con la posición y el campo de visión de la cámara
+    let mut minimap = Minimap::new();
+
+    // Aterrizaje (G): la cámara camina sobre la superficie de un cuerpo rocoso
+    let mut landing: Option<Landing> = None;
+
+    // Vuelo libre (F): cámara con orientación de cuaternión, sin límite de inclinación y con alabeo
+    let mut flight: Option<FreeFlight> = None;
+    // Misión opcional de la escena: objetivos que se cumplen en orden volando
+    let mut mission = scene.mission.clone().map(Mission::new);
+
+    // Marcadores de cámara (Ctrl+1..9 guardar, 1..9 volver) y visita guiada de la escena (V)
+    let mut bookmarks = CameraBookmarks::new(&scene);
+
+    // Perfilador por etapas del pipeline (F3 muestra el desglose; --profile lo exporta a CSV)
+    let mut profiler = Profiler::new(cli.profile.as_deref())
+        .unwrap_or_else(|err| panic!("Failed to create {}: {}", cli.profile.as_deref().unwrap_or_default(), err));
+
+    // Benchmark: camino de cámara fijo, sin esperas, e informe JSON al terminar
+    let mut benchmark = cli.bench.map(|frames| Benchmark::new(frames, cli.bench_out.clone(), (window_width, window_height)));
+
+    // Vigilante de frames lentos: baja la calidad si el equipo no da abasto. Parte de la
+    // calidad que el historial de rendimiento da por buena en esta máquina (el benchmark mide
+    // siempre con calidad alta y sin vigilante). --quality manda sobre ambos
+    let starting_quality = match (cli.quality, &benchmark) {
+        (Some(level), _) => level.into(),
+        (None, Some(_)) => Quality::High,
+        (None, None) => perf_history::choose_quality(&settings.performance),
+    };
+    let mut watchdog = Watchdog::new(WatchdogConfig {
+        enabled: settings.watchdog.enabled && benchmark.is_none(),
+        ..settings.watchdog
+    })
+    .with_quality(starting_quality);
+    let mut perf_session = PerfSession::default();
+    // Regulador de la resolución interna (supersampling automático). El benchmark lo deja en la
+    // resolución de la ventana para que dos informes midan lo mismo
+    let mut resolution = ResolutionController::new(settings_menu.config().target_fps);
+    let adaptive_resolution = benchmark.is_none();
+    let mut temporal_aa = TemporalAa::new();
+    let mut camera_motion = CameraMotion::new();
+    let mut motion_blur = MotionBlur::new();
+    let mut retro_chain = retro::chain(&settings.retro);
+    let mut gbuffer_view = GBufferView::default();
+
+    // Grabación de la entrada de cada frame (--record) para repetir la sesión con --replay
+    let mut recorder = cli.record.as_ref().map(|path| {
+        Recorder::create(path, session_seed, (window_width, window_height))
+            .unwrap_or_else(|err| panic!("Failed to create {}: {}", path, err))
+    });
+
+    // Recarga en caliente de la escena (si viene de un archivo) y de los parámetros de los
+    // shaders; el benchmark mide siempre lo mismo y una repetición debe salir igual que al grabarla
+    let scene_file = (cli.random_system.is_none() && cli.exoplanets.is_none()).then(|| cli.scene_path());
+    let file_watcher = if benchmark.is_none() && replay.is_none() {
+        let mut watched = vec![(cli.params.as_deref().unwrap_or(tweaks::DEFAULT_PARAMS_PATH), Reload::Params)];
+        watched.extend(scene_file.map(|path| (path, Reload::Scene)));
+        FileWatcher::new(&watched)
+            .map_err(|err| eprintln!("Recarga en caliente desactivada: {}", err))
+            .ok()
+    } else {
+        None
+    };
 
     let mut time = 0.0f32;
+    let mut previous_frame = Instant::now();
     
     // Inicializar cámara - MUCHO más alejada para ver todo el sistema expandido con los planetas exteriores
     let mut camera = Camera::new(
@@ -411,53 +1081,523 @@ fn main() {
         Vec3::new(600.0, 400.0, 0.0)       // mirando al centro (donde está el sol)
     );
 
-    let projection_matrix = create_projection_matrix(window_width as f32, window_height as f32);
+    // Proyección (campo de visión, planos y vista de mapa): la matriz se rehace solo al cambiar
+    let mut projection = Projection::default();
+    let mut applied_projection = projection;
+    let mut projection_matrix = projection.matrix(window_width as f32, window_height as f32);
+    let screen_viewport_matrix = create_viewport_matrix(window_width as f32, window_height as f32);
+
+    // Posición del frame anterior: la velocidad de descenso activa el plasma de entrada atmosférica
+    let mut previous_camera_position = camera.position;
+
+    // Ganancia de la iluminación según la distancia al Sol del punto enfocado
+    let mut auto_exposure = AutoExposure::default();
+
+    // Buffer final a resolución de ventana: recibe la imagen (ya sin supersampling) y los overlays
+    let mut screen = Framebuffer::new(window_width, window_height);
+
+    // Selección y paneles abiertos del frame anterior: cada cambio suena como un clic
+    let mut previous_ui_state = None;
+
+    while window.as_ref().is_none_or(Window::is_open) {
+        let frame_start = Instant::now();
+        let deterministic = replay.is_some() || recorder.is_some() || benchmark.is_some();
+        let sim_dt = if deterministic { FIXED_STEP } else { (frame_start - previous_frame).as_secs_f32().clamp(STEP_RANGE.0, STEP_RANGE.1) };
+        previous_frame = frame_start;
+
+        // Archivos guardados desde el último frame: la cámara y el tiempo no se tocan
+        for reload in file_watcher.as_ref().map(FileWatcher::poll).unwrap_or_default() {
+            match reload {
+                Reload::Params => match tweaks::load_params(cli.params.as_deref()) {
+                    Ok(params) => {
+                        shader_params = params;
+                        apply_shader_params(&mut celestial_objects, &shader_params);
+                        println!("Recarga: parámetros de los shaders aplicados");
+                    }
+                    Err(err) => eprintln!("Recarga: {} (se mantienen los anteriores)", err),
+                },
+                Reload::Scene => {
+                    let Some(path) = scene_file else {
+                        continue;
+                    };
+                    // Con cualquier error se avisa y se sigue con la escena anterior
+                    let reloaded = Scene::load(path).map_err(|err| err.to_string()).and_then(|scene| {
+                        let bodies = SceneBodies::build(&scene, &mut models)?;
+                        let body_ids: Vec<String> = bodies.celestial_objects.iter().map(|obj| obj.id.clone()).collect();
+                        if let Some(player) = &lesson {
+                            player.check_bodies(&body_ids).map_err(|err| err.to_string())?;
+                        }
+                        Ok((bodies, SceneBackground::load(&scene)?, post::chain(&scene.post), scene.mission))
+                    });
+                    let (mut bodies, background, chain, mission_config) = match reloaded {
+                        Ok(reloaded) => reloaded,
+                        Err(err) => {
+                            eprintln!("Recarga: {} no se aplicó ({})", path, err);
+                            continue;
+                        }
+                    };
+                    apply_shader_params(&mut bodies.celestial_objects, &shader_params);
+
+                    // La selección y el aterrizaje siguen al cuerpo por su id; los resultados del
+                    // buscador y la página de la enciclopedia eran de la escena anterior
+                    let new_index = |index: usize| {
+                        let id = &celestial_objects.get(index)?.id;
+                        bodies.celestial_objects.iter().position(|obj| &obj.id == id)
+                    };
+                    selected = selected.and_then(new_index);
+                    landing = landing.take().and_then(|mut landed| {
+                        landed.body = new_index(landed.body)?;
+                        Some(landed)
+                    });
+                    encyclopedia.close();
+                    finder = AlignmentFinder::new();
+
+                    SceneBodies { celestial_objects, asteroids, comets, comet_tails, scene_graph, scripts } = bodies;
+                    SceneBackground { nebula, skybox, star_catalog } = background;
+                    post_chain = chain;
+                    // La misión puede haber cambiado: empieza de nuevo
+                    mission = mission_config.map(Mission::new);
+                    println!("Recarga: {} aplicada ({} cuerpos)", path, celestial_objects.len());
+                }
+            }
+        }
+
+        let selectable_count = celestial_objects.len();
+
+        // Entrada del frame: la grabada mientras dure la repetición y si no la de la ventana
+        // (con --headless no hay ninguna y solo avanza el benchmark)
+        let input = match replay.as_mut().map(Replay::next_frame) {
+            Some(Some(input)) => Some(input),
+            Some(None) => {
+                println!("Repetición terminada: los controles vuelven a la ventana");
+                replay = None;
+                window.as_ref().map(Input::from_window)
+            }
+            None => window.as_ref().map(Input::from_window),
+        };
+        if let (Some(recording), Some(input)) = (recorder.as_mut(), &input) {
+            if let Err(err) = recording.record(input) {
+                eprintln!("Grabación: no se pudo escribir ({}); se detiene", err);
+                recorder = None;
+            }
+        }
+
+        let mut clicked_at = None;
+        if let Some(input) = &input {
+            // Esc abre y cierra el menú de ajustes; al cerrarlo se guardan los cambios
+            if input.is_key_pressed(Key::Escape, KeyRepeat::No) {
+                settings_menu.toggle();
+                if let Some(render) = settings_menu.take_changes() {
+                    if let Err(err) = Settings::save_render(cli.settings.as_deref(), &render) {
+                        eprintln!("Ajustes: no se pudo guardar [render] ({})", err);
+                    }
+                }
+            }
+
+            let mouse_down = input.get_mouse_down(MouseButton::Left);
+            clicked_at = if mouse_down && !mouse_was_down && !encyclopedia.is_open() && !settings_menu.is_open() {
+                input.get_mouse_pos(MouseMode::Discard)
+            } else {
+                None
+            };
+            mouse_was_down = mouse_down;
+            // Los clics sobre el panel de ajustes mueven sus controles, no seleccionan cuerpos
+            let selected_body = selected.and_then(|index| celestial_objects.get(index)).map(|obj| obj.shader.body);
+            clicked_at = clicked_at.filter(|&(x, y)| !tweak_panel.contains(window_width, window_height, selected_body, x, y));
+
+            // F2: panel de ajuste de shaders
+            if input.is_key_pressed(Key::F2, KeyRepeat::No) {
+                tweak_panel.toggle();
+            }
+
+            // F3: desglose de tiempos por etapa (funciona con cualquier panel abierto)
+            if input.is_key_pressed(Key::F3, KeyRepeat::No) {
+                profiler.toggle();
+            }
+
+            // F4: ver los canales del G-buffer en lugar de la imagen (ver gbuffer.rs)
+            if input.is_key_pressed(Key::F4, KeyRepeat::No) {
+                gbuffer_view = gbuffer_view.next();
+            }
+
+            // F5: guardar la partida; F9: volver a la guardada (ver snapshot.rs)
+            if input.is_key_pressed(Key::F5, KeyRepeat::No) {
+                let snapshot = Snapshot::capture(time, paused, &camera, selected, &celestial_objects, ephemeris_clock.as_ref());
+                match snapshot.save(snapshot::DEFAULT_PATH) {
+                    Ok(()) => println!("Partida guardada en {}", snapshot::DEFAULT_PATH),
+                    Err(err) => eprintln!("Partida: {}", err),
+                }
+            }
+            if input.is_key_pressed(Key::F9, KeyRepeat::No) {
+                match Snapshot::load(snapshot::DEFAULT_PATH) {
+                    Ok(snapshot) => {
+                        for seed in snapshot.missing_procedural_seeds(&celestial_objects) {
+                            celestial_objects.push(CelestialObject::procedural(seed, 1.0).attach(&mut scene_graph, None));
+                        }
+                        for id in snapshot.restore_bodies(&mut celestial_objects) {
+                            eprintln!("Partida: la escena no tiene ningún cuerpo con id '{}'", id);
+                        }
+                        if let Some(clock) = &snapshot.ephemeris {
+                            ephemeris_clock = Some(clock.clone());
+                        }
+                        time = snapshot.time;
+                        paused = snapshot.paused;
+                        selected = snapshot.selected(&celestial_objects);
+                        event_log.resume_at(time);
+                        landing = None;
+                        flight = None;
+                        bookmarks.stop();
+                        snapshot.restore_camera(&mut camera);
+                        println!("Partida cargada de {}", snapshot::DEFAULT_PATH);
+                    }
+                    Err(err) => eprintln!("Partida: {}", err),
+                }
+            }
+
+            // Captura (F12) y pausa de la simulación (B), también con cualquier panel abierto
+            if controls.is_pressed(input, Action::Screenshot) {
+                screenshot_requested = true;
+            }
+            if controls.is_pressed(input, Action::Pause) {
+                paused = !paused;
+            }
+
+            if settings_menu.is_open() {
+                // Con el menú abierto las flechas cambian los ajustes en lugar de mover la cámara
+                if let Some(MenuAction::Quit) = settings_menu.handle_input(input) {
+                    break;
+                }
+            } else if tweak_panel.is_open() {
+                // Con el panel de ajustes abierto las flechas mueven sus controles; Tab sigue
+                // cambiando de cuerpo y un clic fuera del panel lo selecciona
+                if let Some(obj) = selected.and_then(|index| celestial_objects.get_mut(index)) {
+                    tweak_panel.handle_input(input, obj.shader.body, &mut obj.shader.tweaks);
+                }
+                if input.is_key_pressed(Key::Tab, KeyRepeat::No) {
+                    selected = next_selection(selected, selectable_count);
+                }
+            } else if encyclopedia.is_open() {
+                // Con la enciclopedia abierta las flechas navegan páginas en lugar de mover la cámara
+                if input.is_key_pressed(Key::Right, KeyRepeat::No) {
+                    encyclopedia.next_page(selectable_count);
+                }
+                if input.is_key_pressed(Key::Left, KeyRepeat::No) {
+                    encyclopedia.previous_page(selectable_count);
+                }
+                if input.is_key_pressed(Key::Down, KeyRepeat::Yes) {
+                    encyclopedia.scroll_by(1);
+                }
+                if input.is_key_pressed(Key::Up, KeyRepeat::Yes) {
+                    encyclopedia.scroll_by(-1);
+                }
+                if input.is_key_pressed(Key::Enter, KeyRepeat::No) || input.is_key_pressed(Key::Backspace, KeyRepeat::No) {
+                    // Al cerrar, el panel queda en la última página consultada
+                    selected = encyclopedia.page();
+                    encyclopedia.close();
+                }
+            } else if finder.is_open() {
+                // Con el buscador abierto las flechas y Enter manejan sus campos; 1-9 o un clic saltan
+                let names: Vec<&str> = celestial_objects.iter().map(CelestialObject::display_name).collect();
+                let clicked_row = clicked_at.and_then(|(x, y)| finder.result_at(window_height, x, y, &names));
+                match finder.handle_input(input, selectable_count).or(clicked_row.map(FinderAction::Jump)) {
+                    Some(FinderAction::Search) => {
+                        finder.search(&celestial_objects, &scene_graph, ephemeris_clock.as_ref(), time, camera.position);
+                    }
+                    Some(FinderAction::Jump(index)) => {
+                        if let Some(alignment) = finder.result(index) {
+                            if let Some(clock) = ephemeris_clock.as_mut() {
+                                clock.advance(alignment.time - time);
+                            }
+                            time = alignment.time;
+                            event_log.skip_to(time);
+                            landing = None;
+                            flight = None;
+                            bookmarks.stop();
+                            alignment.frame(&mut camera);
+                        }
+                    }
+                    None => {}
+                }
+                if input.is_key_pressed(Key::C, KeyRepeat::No) {
+                    finder.toggle(selected);
+                }
+            } else {
+                match (landing.as_mut(), flight.as_mut()) {
+                    (Some(landed), _) => landed.handle_input(input, &controls, &celestial_objects[landed.body]),
+                    (None, Some(flying)) => flying.handle_input(input, &controls),
+                    (None, None) if !bookmarks.is_touring() => handle_input(input, &controls, &mut camera),
+                    (None, None) => {}
+                }
+
+                // F: entrar o salir del vuelo libre (no desde la superficie)
+                if input.is_key_pressed(Key::F, KeyRepeat::No) && landing.is_none() {
+                    match flight.take() {
+                        Some(flying) => flying.land_camera(&mut camera),
+                        None => {
+                            bookmarks.stop();
+                            flight = Some(FreeFlight::from_camera(&camera));
+                            // La cámara sigue a la nave: pasa a ser el objetivo
+                            selected = None;
+                            ship_selected = true;
+                        }
+                    }
+                }
+
+                // G: aterrizar en el cuerpo seleccionado (o el más cercano) y despegar de nuevo
+                if input.is_key_pressed(Key::G, KeyRepeat::No) {
+                    match landing.take() {
+                        Some(landed) => landed.take_off(&mut camera, &celestial_objects[landed.body]),
+                        None => {
+                            landing = Landing::touch_down(&celestial_objects, selected, &camera);
+                            if landing.is_some() {
+                                flight = None;
+                                bookmarks.stop();
+                            }
+                        }
+                    }
+                }
+
+                // Tab: recorrer los cuerpos (planetas y lunas) y luego volver a "sin selección"
+                if input.is_key_pressed(Key::Tab, KeyRepeat::No) {
+                    selected = next_selection(selected, selectable_count);
+                }
+
+                // Enter: abrir la enciclopedia en el cuerpo seleccionado
+                if let Some(index) = selected {
+                    if input.is_key_pressed(Key::Enter, KeyRepeat::No) {
+                        encyclopedia.open(index);
+                    }
+                }
+
+                // P: añadir un planeta procedural aleatorio en una órbita exterior
+                if input.is_key_pressed(Key::P, KeyRepeat::No) {
+                    let seed = session_rng.as_mut().map_or_else(clock_seed, SeededRng::next_u64);
+                    let planet = spawn_procedural_planet(seed, &celestial_objects);
+                    celestial_objects.push(planet.attach(&mut scene_graph, None));
+                }
+
+                // [ y ]: más lento / más rápido el reloj de efemérides
+                if let Some(clock) = ephemeris_clock.as_mut() {
+                    if input.is_key_pressed(Key::LeftBracket, KeyRepeat::No) {
+                        clock.slower();
+                    }
+                    if input.is_key_pressed(Key::RightBracket, KeyRepeat::No) {
+                        clock.faster();
+                    }
+                }
+
+                // -/=: campo de visión (teleobjetivo), 0: restablecerlo, O: vista de mapa ortográfica
+                if input.is_key_down(Key::Equal) {
+                    projection.zoom_lens(1.0 / 1.02);
+                }
+                if input.is_key_down(Key::Minus) {
+                    projection.zoom_lens(1.02);
+                }
+                if input.is_key_pressed(Key::Key0, KeyRepeat::No) {
+                    projection.fov = DEFAULT_FOV;
+                }
+                if input.is_key_pressed(Key::O, KeyRepeat::No) {
+                    projection.orthographic = !projection.orthographic;
+                }
+
+                // L: nombres de los cuerpos; Shift+L: líneas de las constelaciones del catálogo
+                if input.is_key_pressed(Key::L, KeyRepeat::No) {
+                    if input.is_key_down(Key::LeftShift) || input.is_key_down(Key::RightShift) {
+                        show_constellations = !show_constellations;
+                    } else {
+                        theme::set(theme::Theme { labels: !theme::current().labels, ..theme::current() });
+                    }
+                }
+
+                // C: buscador de alineaciones (el cuerpo A es el seleccionado)
+                if input.is_key_pressed(Key::C, KeyRepeat::No) {
+                    finder.toggle(selected);
+                }
 
-    while window.is_open() {
-        if window.is_key_down(Key::Escape) {
-            break;
+                // T: línea de tiempo de eventos; con ella abierta, 1-9 saltan al evento elegido
+                if input.is_key_pressed(Key::T, KeyRepeat::No) {
+                    event_log.toggle_timeline();
+                }
+                if event_log.timeline_open() {
+                    let keys = [Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9];
+                    let jump = keys
+                        .iter()
+                        .position(|&key| input.is_key_pressed(key, KeyRepeat::No))
+                        .and_then(|index| event_log.jump_to(index));
+                    if let Some((event, target)) = jump {
+                        if let Some(clock) = ephemeris_clock.as_mut() {
+                            clock.advance(target - time);
+                        }
+                        time = target;
+                        if event.focus.is_some() {
+                            selected = event.focus;
+                        }
+                    }
+                }
+
+                // 1-9 (con la línea de tiempo cerrada): marcadores de cámara; V: visita guiada
+                if !event_log.timeline_open() && landing.is_none() && flight.is_none() {
+                    bookmarks.handle_input(input, &camera);
+                }
+
+                // Espacio: siguiente paso de la lección (al terminar se cierra); en vuelo libre dispara
+                if input.is_key_pressed(Key::Space, KeyRepeat::No) && flight.is_none() {
+                    if let Some(player) = lesson.as_mut() {
+                        if !player.advance() {
+                            lesson = None;
+                        }
+                    }
+                }
+            }
         }
 
-        handle_input(&window, &mut camera);
+        // En el benchmark la cámara sigue su camino fijo (ignora los controles)
+        if let Some(benchmark) = &benchmark {
+            benchmark.place_camera(&mut camera, &celestial_objects);
+        }
 
         // Calcular distancia de la cámara al objetivo
         let distance_to_target = (camera.position - camera.target).magnitude();
         
-        // Decidir factor de supersampling basado en distancia (con histéresis para evitar parpadeo)
-        let desired_supersample = if distance_to_target > 1500.0 {
-            2usize  // Lejos: alta calidad
-        } else if distance_to_target > 600.0 {
-            1usize  // Media distancia: calidad normal
-        } else {
-            1usize  // Cerca: sin supersampling (rendimiento)
+        let render_config = settings_menu.config();
+
+        // La vista de mapa sigue a la distancia del objetivo (Z/X siguen acercando)
+        if projection.orthographic {
+            projection.fit_ortho(distance_to_target);
+        }
+        if projection != applied_projection {
+            projection_matrix = projection.matrix(window_width as f32, window_height as f32);
+            applied_projection = projection;
+        }
+
+        // Objeto bajo el clic en el canal de objetos del frame anterior: se lee antes de que un
+        // cambio de resolución cambie el framebuffer por otro con un canal viejo o vacío
+        let clicked_object = clicked_at.and_then(|(mouse_x, mouse_y)| picking::pick_object(&framebuffer, mouse_x, mouse_y, window_width, window_height));
+
+        // El vigilante de frames lentos limita la escala del regulador y puede reducir la
+        // resolución; con MSAA no hace falta supersampling, ni con el modo retro, que junta
+        // los píxeles en bloques. El factor fijo del menú (o de --supersample) se respeta siempre
+        let quality = watchdog.quality();
+        let (desired_width, desired_height) = match render_config.supersample {
+            0 => {
+                let max_scale = quality.max_supersample() as f32 / quality.resolution_divisor() as f32;
+                resolution.set_target_fps(render_config.target_fps);
+                resolution.set_max_scale(if render_config.msaa || render_config.retro { max_scale.min(1.0) } else { max_scale });
+                let scale = resolution.scale();
+                ((window_width as f32 * scale).round() as usize, (window_height as f32 * scale).round() as usize)
+            }
+            factor => (
+                window_width * factor as usize / quality.resolution_divisor(),
+                window_height * factor as usize / quality.resolution_divisor(),
+            ),
         };
 
-        // Solo cambiar el framebuffer si el factor cambia (para evitar saltos)
-        if desired_supersample != supersample_factor {
-            supersample_factor = desired_supersample;
-            framebuffer_width = window_width * supersample_factor;
-            framebuffer_height = window_height * supersample_factor;
-            framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
-            framebuffer.set_background_color(0x000011);
+        // Solo cambiar el framebuffer si su tamaño cambia (para evitar saltos)
+        if (desired_width, desired_height) != (framebuffer_width, framebuffer_height) {
+            framebuffer_width = desired_width;
+            framebuffer_height = desired_height;
+            let next = spare_framebuffer
+                .take()
+                .filter(|spare| spare.width == framebuffer_width && spare.height == framebuffer_height)
+                .unwrap_or_else(|| {
+                    let mut created = Framebuffer::new(framebuffer_width, framebuffer_height);
+                    created.set_background_color(0x000011);
+                    created
+                });
+            spare_framebuffer = Some(std::mem::replace(&mut framebuffer, next));
+        }
+        framebuffer.set_multisample(render_config.msaa);
+        // Vectores de movimiento para el antialiasing temporal y el desenfoque de movimiento
+        let motion_vectors = render_config.taa || render_config.motion_blur;
+        framebuffer.set_motion_vectors(motion_vectors);
+        framebuffer.set_channels(gbuffer_view.channels());
+        if !render_config.taa {
+            temporal_aa.reset();
+        }
+        if !motion_vectors {
+            camera_motion.reset();
         }
 
-        framebuffer.clear();
+        // En pausa el tiempo de simulación no avanza (la cámara sí se mueve)
+        if !paused {
+            time += sim_dt;
+            if let Some(clock) = ephemeris_clock.as_mut() {
+                clock.advance(sim_dt);
+            }
+        }
 
-        time += 0.016;
-        
-        let view_matrix = camera.get_view_matrix();
+        let viewport_matrix = create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32);
+
+        // Sistemas: modelo del frame anterior -> giro propio -> órbitas locales -> grafo de
+        // escena -> posiciones de mundo
+        for bodies in [&mut celestial_objects, &mut asteroids] {
+            systems::remember_models(bodies);
+            systems::spin(bodies);
+            systems::orbits(bodies, time, ephemeris_clock.as_ref());
+            systems::write_graph(bodies, &mut scene_graph);
+        }
+        scene_graph.update_world_transforms();
+        for bodies in [&mut celestial_objects, &mut asteroids] {
+            systems::read_graph(bodies, &scene_graph);
+        }
+        event_log.update(time, &celestial_objects, &scene_graph, &comets, ephemeris_clock.as_ref());
+
+        // La lección lleva la cámara hacia el cuerpo enfocado en el paso actual
+        if let Some(player) = &lesson {
+            if let Some(target_id) = &player.current_step().target {
+                let body = celestial_objects
+                    .iter()
+                    .find(|obj| &obj.id == target_id)
+                    .expect("Lesson targets validated on load");
+                let desired_position = body.transform.translation + player.current_step().camera_offset(body.transform.scale);
+                camera.ease_towards(body.transform.translation, desired_position, 0.08);
+            }
+        }
+
+        // Vuelo hacia un marcador o un fotograma de la visita guiada
+        bookmarks.update(&mut camera, &celestial_objects, sim_dt);
 
-        // Actualizar posiciones
-        for obj in celestial_objects.iter_mut() {
-            obj.update(time);
+        // En la superficie la cámara acompaña al cuerpo en su giro y su órbita
+        if let Some(landed) = &landing {
+            landed.place_camera(&mut camera, &celestial_objects[landed.body]);
+        }
+        // Esferas de los cuerpos y, detrás, de las rocas: para la nave y para los láseres
+        let colliders: Vec<Collider> = celestial_objects.iter().chain(&asteroids).map(|obj| Collider::of(obj, sim_dt)).collect();
+        let (body_colliders, rock_colliders) = colliders.split_at(celestial_objects.len());
+        if let Some(flying) = flight.as_mut() {
+            flying.update(sim_dt);
+            // La nave no atraviesa los cuerpos: rebota o explota y reaparece
+            if let Some(Impact::Crash { position, velocity }) = flying.collide(&colliders) {
+                collision::explode(&mut explosions, position, velocity);
+                audio.play(Sound::Explosion, Some(position));
+            }
+            // Junto a una estación, despacio: atraca y la estación se convierte en el objetivo
+            if let Some(station) = flying.moor(&celestial_objects, body_colliders) {
+                selected = Some(station);
+                ship_selected = false;
+            }
+            flying.place_camera(&mut camera, &settings.flight, sim_dt);
         }
 
-        // Actualizar luna de la Tierra
-    earth_moon.orbit_center = celestial_objects[2].translation; // La Tierra es el índice 2 (después de Sol y Mercurio/Lava)
-        earth_moon.update(time);
+        let view_matrix = camera.get_view_matrix();
 
-        // La posición del Sol es la fuente de luz
-        let light_position = celestial_objects[0].translation;
+        // Antialiasing temporal: la escena se dibuja con un desplazamiento subpíxel distinto en
+        // cada frame y los vértices se proyectan también con la cámara del frame anterior. La
+        // interfaz (etiquetas, picking) sigue con la proyección sin desplazar
+        let view_projection = projection_matrix * view_matrix;
+        let jitter = if render_config.taa { temporal_aa.jitter() } else { Vec2::zeros() };
+        let scene_projection = taa::jittered_projection(&projection_matrix, jitter, framebuffer_width, framebuffer_height);
+        let previous_view_projection = motion_vectors.then(|| camera_motion.previous_view_projection(&view_projection));
+        let sky_motion = camera_motion.sky(&view_matrix, &projection_matrix, &viewport_matrix, jitter);
+
+        // La posición del Sol es la fuente de luz (el primer cuerpo si la escena no tiene Sol)
+        let light_position = systems::light_position(&celestial_objects);
+
+        // Scripts de la escena: lo que cambien de la órbita se ve desde el frame siguiente
+        if !paused {
+            scripts.update(&mut celestial_objects, time, sim_dt, camera.position, light_position);
+        }
+        let exposure = auto_exposure.update(&settings.lighting, (camera.target - light_position).magnitude(), sim_dt);
 
         // Nivel de detalle ULTRA AGRESIVO basado en distancia (más cerca = menos detalle para MÁXIMO rendimiento)
         let detail_level = if distance_to_target > 1500.0 {
@@ -470,243 +1610,763 @@ fn main() {
             0.3  // Muy cerca: bajo detalle
         } else {
             0.15 // ULTRA CERCA: mínimo detalle absoluto para MÁXIMO rendimiento
-        };        // Renderizar todos los cuerpos usando Esfera_Low.obj (178 vértices, 192 caras - MÁXIMO rendimiento)
-        for celestial_obj in &celestial_objects {
-            let model_matrix = create_model_matrix(
-                celestial_obj.translation,
-                celestial_obj.scale,
-                celestial_obj.rotation,
-            );
-            let uniforms = Uniforms {
-                model_matrix,
-                view_matrix,
-                projection_matrix,
-                time,
-                current_shader: celestial_obj.body_type,
-                light_position,
-                camera_position: camera.position,
-                detail_level,
+        } * quality.detail_scale() * render_config.shader_detail;
+
+        let camera_forward = (camera.target - camera.position).normalize();
+        audio.set_listener(camera.position, camera_forward.cross(&camera.up));
+
+        // Estado orbital de cada cometa (posición y actividad según la distancia al Sol)
+        let comet_states: Vec<_> = comets.iter().map(|comet| comet.state(time, light_position)).collect();
+        for (tails, comet) in comet_tails.iter_mut().zip(&comets) {
+            tails.update(comet, light_position, time);
+        }
+
+        // Misión: el objetivo actual se cumple al llegar a él, escanearlo o atracar. Quedarse sin
+        // combustible la hace fracasar; se reintenta cuando la nave vuelve al punto de despegue
+        if let (Some(mission), Some(flying)) = (mission.as_mut(), flight.as_ref()) {
+            if flying.respawned() {
+                mission.retry();
+            }
+            if flying.out_of_fuel() {
+                mission.fail("sin combustible");
+            }
+            if !flying.wrecked() {
+                let pilot = Pilot { position: flying.position(), forward: flying.forward(), docked_at: flying.docked_at() };
+                mission.update(sim_dt, pilot, &celestial_objects, &comets, &comet_states);
+            }
+        }
+
+        // Chispas desde un punto al azar de la superficie del Sol, hacia fuera
+        let sun = celestial_objects.iter().find(|obj| obj.shader.body.is_star());
+        if let Some(sun) = sun {
+            corona_sparks.emit(sim_dt, 1.0, |rng| {
+                let normal = particles::random_unit_vector(rng);
+                Emission {
+                    position: sun.transform.translation + normal * sun.transform.scale * 1.01,
+                    direction: normal,
+                    velocity: Vec3::zeros(),
+                    speed_scale: sun.transform.scale,
+                }
+            });
+            let gravity = CORONA_GRAVITY * sun.transform.scale;
+            corona_sparks.update(sim_dt, |position| (sun.transform.translation - position).normalize() * gravity);
+        }
+
+        // Escape de los motores en vuelo libre; lo ya emitido se sigue apagando al aterrizar
+        if let Some(flying) = &flight {
+            flying.emit_exhaust(&mut engine_exhaust, sim_dt);
+        }
+        audio.engine(flight.as_ref().map_or(0.0, FreeFlight::throttle));
+        engine_exhaust.update(sim_dt, |_| Vec3::zeros());
+        explosions.update(sim_dt, |_| Vec3::zeros());
+
+        // Drones enemigos, solo en vuelo libre: patrullan, persiguen a la nave, esquivan sus
+        // láseres y le disparan. Si la alcanzan, explota como al chocar
+        match flight.as_mut() {
+            Some(flying) => {
+                flying.fire(&mut weapons);
+                let player = flying.collider().map(|ship| drones::Player { position: ship.center, velocity: ship.velocity });
+                for position in squadron.update(sim_dt, body_colliders, rock_colliders, player, &weapons.bolts()) {
+                    collision::explode(&mut explosions, position, Vec3::zeros());
+                    audio.play(Sound::Explosion, Some(position));
+                }
+                let ship: Vec<Collider> = flying.collider().into_iter().collect();
+                // Cada láser gasta escudo y casco; el que rompe el casco hace explotar la nave
+                let damage = squadron.weapons.update(sim_dt, &colliders, &ship).len() as f32 * ship_systems::BOLT_DAMAGE;
+                if damage > 0.0 {
+                    let (position, velocity) = (flying.position(), flying.velocity());
+                    if flying.hit(damage) {
+                        collision::explode(&mut explosions, position, velocity);
+                        audio.play(Sound::Explosion, Some(position));
+                    }
+                }
+            }
+            None => squadron.clear(),
+        }
+        for muzzle in weapons.take_shots().into_iter().chain(squadron.weapons.take_shots()) {
+            audio.play(Sound::Laser, Some(muzzle));
+        }
+
+        // Láseres de la nave contra las rocas y los drones. Las rocas alcanzadas se parten, de la
+        // última a la primera para que quitarlas no mueva las que faltan
+        let drone_colliders = squadron.colliders();
+        let targets: Vec<Collider> = rock_colliders.iter().chain(&drone_colliders).copied().collect();
+        let mut hits = weapons.update(sim_dt, body_colliders, &targets);
+        hits.sort_unstable_by(|a, b| b.cmp(a));
+        for index in hits {
+            match index.checked_sub(rock_colliders.len()) {
+                Some(drone) => {
+                    if let Some((position, velocity)) = squadron.hit(drone) {
+                        collision::explode(&mut explosions, position, velocity);
+                        audio.play(Sound::Explosion, Some(position));
+                        weapons.score += drones::DRONE_POINTS;
+                    }
+                }
+                None => {
+                    audio.play(Sound::Explosion, Some(rock_colliders[index].center));
+                    weapons.score += weapons::shatter(&mut asteroids, index, &mut scene_graph, &mut debris, rock_colliders[index].velocity);
+                }
+            }
+        }
+        debris.update(sim_dt, |_| Vec3::zeros());
+
+        // Huracanes activos (función del tiempo): espirales de nubes sobre la Tierra
+        let hurricanes = weather::active_hurricanes(time);
+
+        // Entrada atmosférica: cielo, bruma y Sol atenuado según la profundidad en la capa
+        let atmosphere_entry = atmosphere::find_entry(&celestial_objects, camera.position, previous_camera_position, light_position, time);
+
+        // Overlays: etiquetas con el nombre de cada cuerpo y panel del cuerpo seleccionado
+        let labeled: Vec<&CelestialObject> = celestial_objects.iter().collect();
+
+        // Picking: el clic responde la pregunta de la lección o, si no hay, selecciona el cuerpo.
+        // Primero el que se vio en ese píxel en el frame anterior (canal de objetos) y, sobre el
+        // cielo, el disco más cercano (así se acierta a los cuerpos de pocos píxeles)
+        if let Some((mouse_x, mouse_y)) = clicked_at.filter(|_| !finder.is_open()) {
+            let picked = clicked_object
+                .filter(|&target| target != Target::Ship || flight.is_some())
+                .filter(|&target| !matches!(target, Target::Body(index) if index >= labeled.len()))
+                .or_else(|| {
+                    picking::pick_body(&labeled, mouse_x, mouse_y, &view_matrix, &projection_matrix, &screen_viewport_matrix).map(Target::Body)
+                });
+            let picked_body = match picked {
+                Some(Target::Body(index)) => Some(index),
+                _ => None,
             };
-            
-            // TODOS usan Esfera_Low.obj (178 vértices, 192 caras) para MÁXIMO rendimiento
-            render(&mut framebuffer, &uniforms, &sphere_low_vertices);
-        }
-
-        // Renderizar luna (SIEMPRE - sin frustum culling)
-        let moon_matrix = create_model_matrix(
-            earth_moon.translation,
-            earth_moon.scale,
-            earth_moon.rotation,
-        );
-        let moon_uniforms = Uniforms {
-            model_matrix: moon_matrix,
+            match lesson.as_mut() {
+                Some(player) if player.quiz_pending() => player.answer_click(picked_body.map(|index| labeled[index])),
+                _ => match picked {
+                    Some(Target::Body(index)) => selected = Some(index),
+                    Some(Target::Ship) => {
+                        selected = None;
+                        ship_selected = true;
+                    }
+                    None => {}
+                },
+            }
+        }
+
+        // Preguntas de eclipse: se validan con la geometría de la cámara en cada frame
+        if let Some(player) = lesson.as_mut() {
+            let eclipse = player.eclipse_bodies().and_then(|(occluder_id, source_id)| {
+                let occluder = labeled.iter().find(|obj| obj.id == occluder_id)?;
+                let source = labeled.iter().find(|obj| obj.id == source_id)?;
+                Some(picking::eclipse_alignment(camera.position, occluder, source))
+            });
+            if let Some(alignment) = eclipse {
+                player.update_alignment(alignment);
+            }
+        }
+
+        // Uniformes de una llamada de dibujo con la cámara y la luz de este frame
+        let frame_uniforms = |model_matrix: Mat4, shader: CelestialBody| Uniforms {
             view_matrix,
-            projection_matrix,
+            projection_matrix: scene_projection,
+            viewport_matrix,
             time,
-            current_shader: CelestialBody::Moon,
+            current_shader: shader,
             light_position,
             camera_position: camera.position,
             detail_level,
+            light_intensity: settings.lighting.intensity((model_matrix.column(3).xyz() - light_position).magnitude()) * exposure,
+            model_matrix: Backend::mat4(&model_matrix),
+            mvp_matrix: Backend::mat4(&(scene_projection * view_matrix * model_matrix)),
+            normal_matrix: Backend::mat3(&create_normal_matrix(&model_matrix)),
+            previous_view_projection,
+            previous_mvp_matrix: previous_view_projection.map(|previous| Backend::mat4(&(previous * model_matrix))),
+            jitter,
+            object_id: NO_OBJECT,
+            tweaks: ShaderTweaks::for_body(shader),
+            params: ShaderParams::for_body(shader),
         };
-        // La luna usa Esfera_Low.obj (máximo rendimiento)
-        render(&mut framebuffer, &moon_uniforms, &sphere_low_vertices);
 
-        // Renderizar anillos de Saturno (SIEMPRE - sin frustum culling)
-        render_saturn_rings(
-            &mut framebuffer,
-            &celestial_objects[5],
-            time,
-            view_matrix,
-            projection_matrix,
-            light_position,
-            camera.position,
-            detail_level,
-            &sphere_low_vertices,
-        );
+        // Agujeros negros: su disco y su lente son pasos aparte
+        let black_holes: Vec<&CelestialObject> = celestial_objects
+            .iter()
+            .filter(|obj| obj.shader.body == CelestialBody::BlackHole)
+            .collect();
+        let drone_matrices = squadron.model_matrices();
 
-        // Renderizar anillos del planeta Alien (índice 7)
-        render_alien_rings(
-            &mut framebuffer,
-            &celestial_objects[7],
-            time,
-            view_matrix,
-            projection_matrix,
-            light_position,
-            camera.position,
-            detail_level,
-            &sphere_low_vertices,
-        );
+        // ============= PASADAS DEL FRAME =============
+        let mut graph = RenderGraph::new();
 
-        if supersample_factor > 1 {
-            // Aplicar downsampling para anti-aliasing
-            let downsampled = downsample_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height, window_width, window_height);
-            window
-                .update_with_buffer(&downsampled, window_width, window_height)
-                .unwrap();
-        } else {
+        graph.add(Pass::Sky, |targets| targets.scene.clear());
+        if let Some(nebula) = &nebula {
+            graph.add(Pass::Sky, |targets| nebula.borrow_mut().draw(targets.scene, &view_matrix, &scene_projection));
+        }
+        if let Some(catalog) = &star_catalog {
+            graph.add(Pass::Sky, |targets| {
+                catalog.draw(targets.scene, &view_matrix, &scene_projection, show_constellations)
+            });
+        }
+
+        // Todos los cuerpos (luna y asteroides incluidos) con la esfera del nivel de detalle que
+        // corresponde a su tamaño en pantalla - sin frustum culling. Cada cuerpo descarta las
+        // caras que pide su escena, salvo con el descarte apagado en el menú de opciones
+        graph.add(Pass::Opaque, |targets| {
+            // Solo los cuerpos de la lista se pueden seleccionar: los asteroides no llevan objeto
+            let objects = (0..celestial_objects.len()).map(picking::body_object).chain(std::iter::repeat(NO_OBJECT));
+            for (celestial_obj, object_id) in celestial_objects.iter().chain(asteroids.iter()).zip(objects) {
+                let body_state = DrawState {
+                    cull: if render_config.backface_culling { celestial_obj.renderable.cull } else { CullMode::None },
+                    ..DrawState::OPAQUE
+                };
+                let model_matrix = celestial_obj.transform.model_matrix();
+                let uniforms = Uniforms {
+                    object_id,
+                    tweaks: celestial_obj.shader.tweaks,
+                    params: celestial_obj.shader.params,
+                    ..frame_uniforms(model_matrix, celestial_obj.shader.body)
+                }
+                .with_previous_model(celestial_obj.transform.previous_model_matrix());
+
+                // Malla importada (estaciones, asteroides...): sin terreno ni niveles de detalle
+                if let Some(model) = &celestial_obj.renderable.model {
+                    render(targets.scene, &uniforms, &body_state, model);
+                    continue;
+                }
+
+                // Modo terreno: cerca de un cuerpo rocoso, malla de alturas local en lugar de la esfera
+                match terrain::build_patch(celestial_obj, &model_matrix, camera.position, camera_forward) {
+                    Some(patch) => render(targets.scene, &uniforms, &body_state, &patch),
+                    None => {
+                        let radius = lod::screen_radius(celestial_obj, camera.position, &projection_matrix, &viewport_matrix);
+                        render(targets.scene, &uniforms, &body_state, &sphere_lod.select(radius * render_config.lod_detail));
+                    }
+                }
+            }
+        });
+
+        // La nave en vuelo libre, delante de la cámara de persecución (desde la cabina no se ve,
+        // ni mientras espera a reaparecer tras una explosión)
+        if let Some(flying) = flight.as_ref().filter(|flying| !flying.cockpit() && !flying.wrecked()) {
+            graph.add(Pass::Opaque, |targets| {
+                let uniforms = Uniforms {
+                    object_id: picking::SHIP_OBJECT,
+                    ..frame_uniforms(flying.ship_matrix(), CelestialBody::MeshMaterial)
+                };
+                render(targets.scene, &uniforms, &DrawState::OPAQUE, &ship_vertices);
+            });
+        }
+
+        // Los drones enemigos, con la misma malla
+        if !drone_matrices.is_empty() {
+            graph.add(Pass::Opaque, |targets| {
+                for model_matrix in &drone_matrices {
+                    let uniforms = frame_uniforms(*model_matrix, CelestialBody::MeshMaterial);
+                    render(targets.scene, &uniforms, &DrawState::OPAQUE, &ship_vertices);
+                }
+            });
+        }
+
+        // Núcleos de los cometas (la coma y las colas van con las partículas)
+        graph.add(Pass::Opaque, |targets| {
+            for (comet, state) in comets.iter().zip(&comet_states) {
+                let uniforms = frame_uniforms(comet.model_matrix(state, time), CelestialBody::Moon);
+                render(targets.scene, &uniforms, &DrawState::OPAQUE, &sphere_low_vertices);
+            }
+        });
+
+        // Cielo de fondo donde no ha quedado ninguna geometría opaca
+        if let Some(skybox) = &skybox {
+            graph.add(Pass::Opaque, |targets| skybox.borrow_mut().draw(targets.scene, &view_matrix, &scene_projection));
+        }
+
+        // Espirales de nubes de los huracanes, en el espacio del modelo de la Tierra
+        graph.add(Pass::Transparent, |targets| {
+            // Las bandas finas dejan entrever el océano bajo la espiral
+            let storm_state = DrawState::translucent(0.85);
+            for earth in celestial_objects.iter().filter(|obj| obj.shader.body == CelestialBody::Earth) {
+                let storm_uniforms = frame_uniforms(earth.transform.model_matrix(), CelestialBody::StormClouds)
+                    .with_previous_model(earth.transform.previous_model_matrix());
+                for storm in &hurricanes {
+                    render(targets.scene, &storm_uniforms, &storm_state, &weather::hurricane_mesh(storm, time));
+                }
+            }
+        });
+
+        // Anillos definidos en la escena
+        graph.add(Pass::Transparent, |targets| {
+            for (index, celestial_obj) in celestial_objects.iter().enumerate() {
+                let Some(rings) = celestial_obj.rings else {
+                    continue;
+                };
+                // Los anillos seleccionan su planeta
+                let uniforms = Uniforms {
+                    object_id: picking::body_object(index),
+                    ..frame_uniforms(celestial_obj.transform.model_matrix(), celestial_obj.shader.body)
+                };
+                match rings.style {
+                    RingStyle::Saturn => render_saturn_rings(targets.scene, celestial_obj, &uniforms, &sphere_low_vertices),
+                    RingStyle::Wide => render_alien_rings(targets.scene, celestial_obj, &uniforms, &sphere_low_vertices),
+                }
+            }
+        });
+
+        // Discos de acreción de los agujeros negros: en la escena y, solos, en la capa de la
+        // que la lente saca la luz que pasa por detrás de la sombra
+        if !black_holes.is_empty() {
+            graph.add(Pass::Transparent, |targets| {
+                let mut layer = disc_layer.borrow_mut();
+                if (layer.width, layer.height) != (targets.scene.width, targets.scene.height) {
+                    *layer = Framebuffer::new(targets.scene.width, targets.scene.height);
+                }
+                layer.clear();
+                for black_hole in &black_holes {
+                    let uniforms = frame_uniforms(black_hole.transform.model_matrix(), CelestialBody::AccretionDisc);
+                    render(targets.scene, &uniforms, &black_hole::DISC_STATE, &accretion_disc);
+                    render(&mut layer, &uniforms, &black_hole::DISC_STATE, &accretion_disc);
+                }
+            });
+        }
+
+        // Corona y protuberancias del Sol, sumando luz alrededor de la esfera
+        if let Some(sun) = sun {
+            graph.add(Pass::Particles, |targets| {
+                let sprite_camera = SpriteCamera {
+                    view_matrix: &view_matrix,
+                    projection_matrix: &scene_projection,
+                    viewport_matrix: &viewport_matrix,
+                };
+                corona::draw_corona(targets.scene, sun, &sprite_camera, time);
+                corona::draw_prominences(targets.scene, sun, &sprite_camera, time);
+            });
+        }
+
+        // Coma y colas de los cometas, chispas del Sol, escape y explosiones, polvo de las rocas y
+        // láseres, tras los cuerpos para la oclusión
+        graph.add(Pass::Particles, |targets| {
+            let sprite_camera = SpriteCamera {
+                view_matrix: &view_matrix,
+                projection_matrix: &scene_projection,
+                viewport_matrix: &viewport_matrix,
+            };
+            for ((comet, state), tails) in comets.iter().zip(&comet_states).zip(&comet_tails) {
+                tails.draw(targets.scene, &sprite_camera);
+                comet.draw_coma(targets.scene, state, &sprite_camera);
+            }
+            corona_sparks.draw(targets.scene, &sprite_camera);
+            engine_exhaust.draw(targets.scene, &sprite_camera);
+            explosions.draw(targets.scene, &sprite_camera);
+            debris.draw(targets.scene, &sprite_camera);
+            weapons.draw(targets.scene, &sprite_camera);
+            squadron.weapons.draw(targets.scene, &sprite_camera);
+        });
+
+        // Puntos de luz para los cuerpos que ocupan menos de un píxel: sin ellos los planetas
+        // lejanos desaparecen al alejar la cámara
+        graph.add(Pass::Particles, |targets| {
+            let point_pixels = DISTANT_POINT_PIXELS * framebuffer_width as f32 / window_width as f32;
+            let points: Vec<Billboard> = celestial_objects
+                .iter()
+                .filter(|obj| !obj.shader.body.is_star() && obj.shader.body != CelestialBody::BlackHole)
+                .filter(|obj| lod::screen_radius(obj, camera.position, &projection_matrix, &viewport_matrix) < 1.0)
+                .map(|obj| {
+                    let light = settings.lighting.intensity((obj.transform.translation - light_position).magnitude()) * exposure;
+                    Billboard::new(obj.transform.translation, obj.transform.scale, DISTANT_POINT_COLOR)
+                        .with_shader(SpriteShader::Point)
+                        .with_intensity(light.min(1.0))
+                        .with_pixel_range(point_pixels, point_pixels)
+                })
+                .collect();
+            let sprite_camera = SpriteCamera {
+                view_matrix: &view_matrix,
+                projection_matrix: &scene_projection,
+                viewport_matrix: &viewport_matrix,
+            };
+            billboard::draw_billboards(targets.scene, &sprite_camera, &billboard::GLOW_STATE, &points);
+        });
+
+        // Lente gravitatoria: la última de las partículas, para que también se deformen
+        if !black_holes.is_empty() {
+            graph.add(Pass::Particles, |targets| {
+                let sprite_camera = SpriteCamera {
+                    view_matrix: &view_matrix,
+                    projection_matrix: &scene_projection,
+                    viewport_matrix: &viewport_matrix,
+                };
+                let layer = disc_layer.borrow();
+                for black_hole in &black_holes {
+                    black_hole::apply_lensing(targets.scene, &layer, black_hole, &sprite_camera);
+                }
+            });
+        }
+
+        if let Some(entry) = &atmosphere_entry {
+            graph.add(Pass::Post, |targets| {
+                let sun_disk = celestial_objects
+                    .iter()
+                    .find(|obj| obj.shader.body.is_star())
+                    .and_then(|sun| picking::screen_disk(sun, &view_matrix, &scene_projection, &viewport_matrix))
+                    .map(|(center, radius)| (center.x, center.y, radius));
+                // Desde la superficie, bóveda celeste con horizonte, resplandor solar y atardecer
+                let dome = landing.as_ref().and_then(|landed| {
+                    let up = landed.up(&celestial_objects[landed.body]);
+                    atmosphere::SkyDome::new(&view_matrix, &scene_projection, &viewport_matrix, camera.position, up, light_position)
+                });
+                atmosphere::apply_sky_and_haze(targets.scene, entry, sun_disk, dome.as_ref());
+            });
+        }
+
+        // Mezcla con la historia antes de llevar la escena a la pantalla
+        if render_config.taa {
+            graph.add(Pass::Post, |targets| {
+                profiler::measure(Stage::TemporalResolve, || temporal_aa.resolve(targets.scene, &sky_motion));
+            });
+        }
+        if render_config.motion_blur {
+            graph.add(Pass::Post, |targets| {
+                profiler::measure(Stage::MotionBlur, || motion_blur.apply(targets.scene, &sky_motion));
+            });
+        }
+
+        if gbuffer_view != GBufferView::Off {
+            graph.add(Pass::Post, |targets| gbuffer_view.draw(targets.scene));
+        }
+
+        // La escena a la resolución de la ventana: promedia el supersampling o amplía la
+        // resolución reducida (ver post.rs)
+        graph.add(Pass::Post, |targets| {
+            profiler::measure(Stage::Downsampling, || Resample.process(targets.scene, targets.screen));
+        });
+
+        // Estelas de plasma: salen del punto hacia el que se mueve la cámara (no en tierra)
+        if let Some(entry) = atmosphere_entry.as_ref().filter(|_| landing.is_none()) {
+            graph.add(Pass::Post, |targets| {
+                let motion = camera.position - previous_camera_position;
+                let focus = project_to_screen(camera.position + motion * 100.0, &view_matrix, &projection_matrix, &screen_viewport_matrix)
+                    .map(|point| (point.x, point.y))
+                    .unwrap_or((window_width as f32 / 2.0, window_height as f32 / 2.0));
+                atmosphere::draw_plasma_streaks(targets.screen, entry, time, focus);
+            });
+        }
+
+        // Rayos crepusculares: desenfoque radial de la luz del Sol enmascarada por los cuerpos
+        if let Some(sun) = sun.filter(|_| settings.lighting.light_shafts) {
+            graph.add(Pass::Post, |targets| {
+                let view = light_shafts::ShaftView {
+                    camera_position: camera.position,
+                    view_matrix: &view_matrix,
+                    projection_matrix: &projection_matrix,
+                    scene_viewport: &viewport_matrix,
+                    screen_viewport: &screen_viewport_matrix,
+                };
+                light_shafts::draw_light_shafts(targets.scene, targets.screen, sun, &view);
+            });
+        }
+
+        // Destello de lente del Sol, según lo que deja ver el buffer de profundidad de la escena
+        if let Some(sun) = sun {
+            graph.add(Pass::Post, |targets| {
+                let visibility = lens_flare::sun_visibility(targets.scene, sun, camera.position, &view_matrix, &projection_matrix, &viewport_matrix);
+                if visibility <= 0.0 {
+                    return;
+                }
+                if let Some((center, radius)) = picking::screen_disk(sun, &view_matrix, &projection_matrix, &screen_viewport_matrix) {
+                    let temperature = sun.shader.body.star_class().unwrap_or(random_system::StarClass::G).temperature();
+                    lens_flare::draw_lens_flare(targets.screen, (center.x, center.y), radius, visibility, temperature);
+                }
+            });
+        }
+
+        // Contorno del objetivo seleccionado, sobre la imagen y bajo los efectos de la escena
+        let target = selected.map(Target::Body).or((ship_selected && flight.is_some()).then_some(Target::Ship));
+        if let Some(object) = target.map(Target::object) {
+            graph.add(Pass::Post, move |targets| {
+                outline::draw(targets.screen, targets.scene, object, theme::current().selection_outline);
+            });
+        }
+
+        // Posproceso de la escena ([[post]]) y modo retro sobre la imagen terminada, bajo la interfaz
+        graph.add(Pass::Post, |targets| post_chain.apply(targets.screen));
+        if render_config.retro {
+            graph.add(Pass::Post, |targets| retro_chain.apply(targets.screen));
+        }
+
+        graph.add(Pass::Ui, |targets| {
+            let screen = &mut *targets.screen;
+            let label_scene = ui::LabelScene { scene: &*targets.scene, viewport_matrix: &viewport_matrix, camera_position: camera.position };
+            ui::draw_labels(screen, &labeled, selected, &label_scene, &view_matrix, &projection_matrix, &screen_viewport_matrix);
+            // Widgets de esquina según el tema: los que comparten esquina se apilan
+            let mut hud_layout = HudLayout::new(screen);
+            if let Some(player) = &lesson {
+                for obj in labeled.iter().filter(|obj| player.current_step().highlight.contains(&obj.id)) {
+                    ui::draw_highlight(screen, obj, time, &view_matrix, &projection_matrix, &screen_viewport_matrix);
+                }
+                player.draw(screen);
+            }
+            if let Some(clock) = &ephemeris_clock {
+                clock.draw(screen, &mut hud_layout);
+            }
+            if let Some(landed) = &landing {
+                landed.draw_hud(screen, &mut hud_layout, &celestial_objects[landed.body], &controls);
+            }
+            if let Some(flying) = &flight {
+                if flying.cockpit() {
+                    let target = selected.and_then(|index| labeled.get(index)).copied();
+                    cockpit::draw_hud(screen, flying, target, &view_matrix, &projection_matrix, &screen_viewport_matrix);
+                }
+                flying.draw_hud(screen, &mut hud_layout, weapons.score, &controls);
+                if let Some(mission) = &mission {
+                    mission.draw_guidance(screen, &view_matrix, &projection_matrix, &screen_viewport_matrix);
+                    mission.draw_hud(screen, &mut hud_layout);
+                }
+            }
+            bookmarks.draw(screen, &mut hud_layout);
+            if encyclopedia.is_open() {
+                encyclopedia.draw(screen, &labeled, light_position, time, &sphere_low_vertices);
+            } else if finder.is_open() {
+                let names: Vec<&str> = labeled.iter().map(|obj| obj.display_name()).collect();
+                finder.draw(screen, &names, time);
+            } else if let Some(obj) = selected.and_then(|index| labeled.get(index)) {
+                ui::draw_info_panel(screen, &mut hud_layout, obj, camera.position);
+                if let Some(events) = weather::events(obj.shader.body, time) {
+                    weather::draw_events(screen, &mut hud_layout, &events);
+                }
+            }
+
+            if event_log.timeline_open() {
+                event_log.draw_timeline(screen, time);
+            }
+            let notice_y = event_log.draw_toasts(screen);
+            let notice_y = mission.as_mut().map_or(notice_y, |mission| mission.draw_messages(screen, notice_y));
+            watchdog.draw_notice(screen, notice_y);
+            profiler.draw(screen, &mut hud_layout);
+            let half_fov = ((projection.fov.to_radians() / 2.0).tan() * window_width as f32 / window_height as f32).atan();
+            let viewer = Viewer { position: camera.position, forward: camera_forward, up: camera.up, half_fov };
+            minimap.draw(screen, &mut hud_layout, &celestial_objects, selected, &viewer);
+            let centered = TextStyle::new(theme::current().title).with_align(Align::Center);
+            if let Some(label) = gbuffer_view.label() {
+                text::draw_styled(screen, screen.width as i32 / 2, 36, label, &centered);
+            }
+            if paused {
+                text::draw_styled(screen, screen.width as i32 / 2, 12, "PAUSA", &centered.with_scale(2));
+            }
+            if tweak_panel.is_open() {
+                let body = selected.and_then(|index| labeled.get(index)).map(|obj| (obj.display_name(), obj.shader.body, &obj.shader.tweaks));
+                tweak_panel.draw(screen, body);
+            }
+            if settings_menu.is_open() {
+                settings_menu.draw(screen);
+            }
+        });
+
+        graph.execute(&mut Targets { scene: &mut framebuffer, screen: &mut screen });
+        if motion_vectors {
+            camera_motion.advance(&view_matrix, &projection_matrix);
+        }
+
+        // Captura de la pantalla tal como se ve, con la interfaz
+        if screenshot_requested {
+            screenshot_requested = false;
+            let stamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis())
+                .unwrap_or(0);
+            let path = format!("captura-{}.png", stamp);
+            match image::save_png(&path, screen.width, screen.height, &screen.buffer) {
+                Ok(()) => println!("Captura guardada en {}", path),
+                Err(err) => eprintln!("No se pudo guardar la captura {} ({})", path, err),
+            }
+        }
+        previous_camera_position = camera.position;
+
+        let ui_state = (selected, ship_selected, settings_menu.is_open(), encyclopedia.is_open(), finder.is_open(), tweak_panel.is_open());
+        if previous_ui_state.is_some_and(|previous| previous != ui_state) {
+            audio.play(Sound::Click, None);
+        }
+        previous_ui_state = Some(ui_state);
+
+        if let Some(narrator) = narrator.as_mut() {
+            let focus = narrator::Focus {
+                selected,
+                encyclopedia_page: encyclopedia.page(),
+                lesson_step: lesson.as_ref().map(LessonPlayer::step_index),
+            };
+            let view = narrator::View {
+                camera_position: camera.position,
+                view_matrix: &view_matrix,
+                projection_matrix: &projection_matrix,
+                viewport_matrix: &screen_viewport_matrix,
+                width: window_width as f32,
+                height: window_height as f32,
+            };
+            narrator.update(focus, &labeled, lesson.as_ref(), &view);
+        }
+
+        if let Some(window) = window.as_mut() {
+            window.limit_update_rate(render_config.vsync.then(|| Duration::from_micros(16_667)));
             window
-                .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
+                .update_with_buffer(&screen.buffer, window_width, window_height)
                 .unwrap();
         }
+        profiler.end_frame();
+        if let Some(frame) = profiler.last_frame() {
+            perf_session.record(watchdog.quality(), frame.total);
+            watchdog.record(frame.total);
+        }
+        // El regulador de resolución mide el trabajo del frame, sin la espera del límite de frames
+        if adaptive_resolution {
+            resolution.record(frame_start.elapsed().as_secs_f32() * 1000.0);
+        }
+
+        if let Some(benchmark) = benchmark.as_mut() {
+            if benchmark.record(&profiler) {
+                benchmark.finish().unwrap_or_else(|err| panic!("Failed to write benchmark report: {}", err));
+                break;
+            }
+            continue;
+        }
+
+        // Límite de frames del menú de ajustes: se espera lo que sobre del presupuesto del frame
+        if render_config.frame_cap > 0 {
+            let budget = Duration::from_secs_f32(1.0 / render_config.frame_cap as f32);
+            if let Some(rest) = budget.checked_sub(frame_start.elapsed()) {
+                std::thread::sleep(rest);
+            }
+        }
+    }
 
-        std::thread::sleep(frame_delay);
+    if let Some(recording) = recorder {
+        if let Err(err) = recording.finish() {
+            eprintln!("Grabación: no se pudo terminar de escribir ({})", err);
+        }
+    }
+
+    // Tiempos de la sesión para elegir la calidad del próximo arranque
+    if benchmark.is_none() && settings.performance.history {
+        if let Err(err) = perf_session.save(&settings.performance.history_file) {
+            eprintln!("Historial de rendimiento: no se pudo escribir {} ({})", settings.performance.history_file, err);
+        }
     }
 }
 
+// Semilla de reserva cuando la sesión no tiene una (--seed o la de la repetición)
+fn clock_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+// Tab: recorre los cuerpos en orden y después vuelve a "sin selección"
+fn next_selection(selected: Option<usize>, count: usize) -> Option<usize> {
+    match selected {
+        None => Some(0),
+        Some(index) if index + 1 < count => Some(index + 1),
+        Some(_) => None,
+    }
+}
+
+// Planeta procedural en la siguiente órbita libre
+fn spawn_procedural_planet(seed: u64, existing: &[CelestialObject]) -> CelestialObject {
+    let outermost_orbit = existing.iter().filter_map(|obj| obj.orbit).map(|orbit| orbit.radius).fold(0.0, f32::max);
+    let orbit_radius = outermost_orbit + 150.0;
+    let orbit_speed = kepler_orbit_speed(orbit_radius);
+    let scale = 15.0 + (seed % 30) as f32;
+
+    CelestialObject::procedural(seed, scale)
+        .with_orbit(orbit_radius, orbit_speed)
+        .with_rotation_speed(Vec3::new(0.0, 0.015, 0.0))
+}
+
+// El shader de anillos devuelve negro fuera de las bandas: con mezcla aditiva esas zonas no
+// tapan ni el planeta ni el fondo, y sin descarte de caras también se ve la mitad trasera
+// del anillo (la que queda detrás del planeta la oculta la prueba de profundidad)
+const RING_STATE: DrawState = DrawState::ADDITIVE;
+
 fn render_saturn_rings(
     framebuffer: &mut Framebuffer,
     saturn: &CelestialObject,
-    time: f32,
-    view_matrix: Mat4,
-    projection_matrix: Mat4,
-    light_position: Vec3,
-    camera_position: Vec3,
-    detail_level: f32,
+    planet_uniforms: &Uniforms,
     vertex_arrays: &[Vertex],
 ) {
     // Renderizar anillos grandes y prominentes de Saturno
-    let ring_scale = saturn.scale * 2.5; // Anillos más grandes y visibles
-    let ring_translation = Vec3::new(saturn.translation.x, saturn.translation.y, saturn.translation.z);
-    let ring_rotation = Vec3::new(PI / 4.5, saturn.rotation.y, 0.0); // Inclinación más suave para verse mejor
+    let ring_scale = saturn.transform.scale * 2.5; // Anillos más grandes y visibles
+    let ring_translation = Vec3::new(saturn.transform.translation.x, saturn.transform.translation.y, saturn.transform.translation.z);
+    let ring_rotation = Vec3::new(PI / 4.5, saturn.transform.rotation.y, 0.0); // Inclinación más suave para verse mejor
 
-    let model_matrix = create_model_matrix(ring_translation, ring_scale, ring_rotation);
+    // Los anillos están en el ecuador: heredan la inclinación del eje del planeta
+    let model_matrix = create_model_matrix(ring_translation, ring_scale, saturn.transform.axial_tilt)
+        * create_model_matrix(Vec3::zeros(), 1.0, ring_rotation);
     let uniforms = Uniforms {
-        model_matrix,
-        view_matrix,
-        projection_matrix,
-        time,
         current_shader: CelestialBody::Ring,
-        light_position,
-        camera_position,
-        detail_level,
+        ..planet_uniforms.with_model(model_matrix)
     };
 
-    // Renderizar con el shader de anillos
-    render(framebuffer, &uniforms, vertex_arrays);
+    // Renderizar con el shader de anillos (ver RING_STATE)
+    render(framebuffer, &uniforms, &RING_STATE, vertex_arrays);
 }
 
 fn render_alien_rings(
     framebuffer: &mut Framebuffer,
     alien_planet: &CelestialObject,
-    time: f32,
-    view_matrix: Mat4,
-    projection_matrix: Mat4,
-    light_position: Vec3,
-    camera_position: Vec3,
-    detail_level: f32,
+    planet_uniforms: &Uniforms,
     vertex_arrays: &[Vertex],
 ) {
     // Renderizar anillos ENORMES del planeta alien - MUY visibles y dramáticos
-    let ring_scale = alien_planet.scale * 4.0; // Anillos ENORMES (4x el tamaño del planeta!)
-    let ring_translation = Vec3::new(alien_planet.translation.x, alien_planet.translation.y, alien_planet.translation.z);
+    let ring_scale = alien_planet.transform.scale * 4.0; // Anillos ENORMES (4x el tamaño del planeta!)
+    let ring_translation = Vec3::new(alien_planet.transform.translation.x, alien_planet.transform.translation.y, alien_planet.transform.translation.z);
     // Rotación similar a Saturno pero con más inclinación para verse mejor desde cualquier ángulo
-    let ring_rotation = Vec3::new(PI / 3.5, alien_planet.rotation.y + time * 0.001, PI / 8.0);
+    let ring_rotation = Vec3::new(PI / 3.5, alien_planet.transform.rotation.y + planet_uniforms.time * 0.001, PI / 8.0);
 
-    let model_matrix = create_model_matrix(ring_translation, ring_scale, ring_rotation);
+    let model_matrix = create_model_matrix(ring_translation, ring_scale, alien_planet.transform.axial_tilt)
+        * create_model_matrix(Vec3::zeros(), 1.0, ring_rotation);
     let uniforms = Uniforms {
-        model_matrix,
-        view_matrix,
-        projection_matrix,
-        time,
         current_shader: CelestialBody::Ring, // Usar el shader de anillos (tiene transparencia)
-        light_position,
-        camera_position,
-        detail_level,
+        ..planet_uniforms.with_model(model_matrix)
     };
 
-    // Renderizar con el shader de anillos
-    render(framebuffer, &uniforms, vertex_arrays);
-}
-
-// Función para downsample el framebuffer (anti-aliasing)
-fn downsample_buffer(high_res_buffer: &[u32], high_width: usize, high_height: usize, 
-                     low_width: usize, low_height: usize) -> Vec<u32> {
-    let mut low_res_buffer = vec![0u32; low_width * low_height];
-    let scale_x = high_width / low_width;
-    let scale_y = high_height / low_height;
-    
-    for y in 0..low_height {
-        for x in 0..low_width {
-            let mut r_sum = 0u32;
-            let mut g_sum = 0u32;
-            let mut b_sum = 0u32;
-            let mut count = 0u32;
-            
-            // Promediar los píxeles del área correspondiente
-            for dy in 0..scale_y {
-                for dx in 0..scale_x {
-                    let hx = x * scale_x + dx;
-                    let hy = y * scale_y + dy;
-                    
-                    if hx < high_width && hy < high_height {
-                        let pixel = high_res_buffer[hy * high_width + hx];
-                        r_sum += (pixel >> 16) & 0xFF;
-                        g_sum += (pixel >> 8) & 0xFF;
-                        b_sum += pixel & 0xFF;
-                        count += 1;
-                    }
-                }
-            }
-            
-            // Calcular promedio
-            if count > 0 {
-                let r = (r_sum / count) & 0xFF;
-                let g = (g_sum / count) & 0xFF;
-                let b = (b_sum / count) & 0xFF;
-                low_res_buffer[y * low_width + x] = (r << 16) | (g << 8) | b;
-            }
-        }
-    }
-    
-    low_res_buffer
+    // Renderizar con el shader de anillos (ver RING_STATE)
+    render(framebuffer, &uniforms, &RING_STATE, vertex_arrays);
 }
 
-fn handle_input(window: &Window, camera: &mut Camera) {
+fn handle_input(input: &Input, controls: &Controls, camera: &mut Camera) {
     let move_speed = 10.0;
     let rotate_speed = 0.02;
     let zoom_speed = 20.0;
     
-    // WASD: mover cámara
-    if window.is_key_down(Key::W) {
+    // WASD (o lo asignado en controls.toml): mover cámara
+    if controls.is_down(input, Action::MoveForward) {
         camera.move_forward(move_speed);
     }
-    if window.is_key_down(Key::S) {
+    if controls.is_down(input, Action::MoveBack) {
         camera.move_forward(-move_speed);
     }
-    if window.is_key_down(Key::A) {
+    if controls.is_down(input, Action::MoveLeft) {
         camera.move_right(-move_speed);
     }
-    if window.is_key_down(Key::D) {
+    if controls.is_down(input, Action::MoveRight) {
         camera.move_right(move_speed);
     }
     
     // Q/E: mover arriba/abajo
-    if window.is_key_down(Key::Q) {
+    if controls.is_down(input, Action::MoveUp) {
         camera.move_up(move_speed);
     }
-    if window.is_key_down(Key::E) {
+    if controls.is_down(input, Action::MoveDown) {
         camera.move_up(-move_speed);
     }
     
     // Flechas: orbitar alrededor del objetivo
-    if window.is_key_down(Key::Left) {
+    if controls.is_down(input, Action::OrbitLeft) {
         camera.orbit(-rotate_speed, 0.0);
     }
-    if window.is_key_down(Key::Right) {
+    if controls.is_down(input, Action::OrbitRight) {
         camera.orbit(rotate_speed, 0.0);
     }
-    if window.is_key_down(Key::Up) {
+    if controls.is_down(input, Action::OrbitUp) {
         camera.orbit(0.0, rotate_speed);
     }
-    if window.is_key_down(Key::Down) {
+    if controls.is_down(input, Action::OrbitDown) {
         camera.orbit(0.0, -rotate_speed);
     }
     
     // Z/X: zoom
-    if window.is_key_down(Key::Z) {
+    if controls.is_down(input, Action::ZoomIn) {
         camera.zoom_in(zoom_speed);
     }
-    if window.is_key_down(Key::X) {
+    if controls.is_down(input, Action::ZoomOut) {
         camera.zoom_out(zoom_speed);
     }
 }
\ No newline at end of file
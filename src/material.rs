@@ -0,0 +1,151 @@
+use nalgebra_glm::Vec3;
+use crate::celestial_shaders::CelestialBody;
+use crate::color::Color;
+
+// ============= MATERIALES Y RESPUESTA FOTOMÉTRICA =============
+// Cada cuerpo elige cómo responde su superficie a la luz del Sol: el albedo (fracción de la
+// luz difusa que devuelve, aplicada sobre el color de su textura procedural) y una BRDF.
+// - Lambert: superficies lisas o heladas; oscurecen suavemente hacia el terminador.
+// - Oren-Nayar: regolito polvoriento (Luna, Marte, lunas pequeñas). La rugosidad devuelve
+//   la luz hacia el Sol, así que el disco se ve plano y el borde apenas se oscurece.
+// - Brillo anisótropo (anillos): las partículas alineadas con la órbita brillan en una banda
+//   perpendicular a la dirección de giro y dispersan la luz hacia delante a contraluz.
+// El reflejo especular (océanos, hielo) es un lóbulo aparte que se suma a cualquier BRDF.
+// Efecto de oposición: el regolito y las partículas de los anillos se iluminan de golpe
+// cuando el Sol queda justo detrás de la cámara (ángulo de fase casi 0), porque sus granos
+// esconden sus propias sombras y retrodispersan la luz de forma coherente.
+
+#[derive(Clone, Copy, Debug)]
+pub enum Brdf {
+    Lambert,
+    OrenNayar { roughness: f32 },               // desviación de las microfacetas (radianes)
+    Sheen { strength: f32, exponent: f32 },     // anillos: necesita la tangente de la superficie
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Material {
+    pub albedo: f32,
+    pub brdf: Brdf,
+    pub ambient: f32,
+    pub specular: f32,
+    pub shininess: f32,
+    pub opposition: f32, // amplitud del pico de brillo con ángulo de fase 0 (0 = sin efecto)
+}
+
+// Anchura angular del pico de oposición: a ~5° de fase ya queda en la mitad
+const OPPOSITION_WIDTH: f32 = 0.045;
+
+impl Material {
+    const fn lambert(albedo: f32, ambient: f32, specular: f32, shininess: f32) -> Material {
+        Material { albedo, brdf: Brdf::Lambert, ambient, specular, shininess, opposition: 0.0 }
+    }
+
+    const fn dusty(albedo: f32, ambient: f32, roughness: f32, opposition: f32) -> Material {
+        Material { albedo, brdf: Brdf::OrenNayar { roughness }, ambient, specular: 0.02, shininess: 2.0, opposition }
+    }
+
+    // Todas las direcciones normalizadas y apuntando desde la superficie. `tangent` solo lo
+    // usa el brillo anisótropo (dirección de las partículas de los anillos)
+    pub fn shade(&self, base_color: Color, normal: Vec3, tangent: Option<Vec3>, light_dir: Vec3, view_dir: Vec3) -> Color {
+        let ambient = base_color * self.ambient;
+        let surge = 1.0 + self.opposition_surge(light_dir, view_dir);
+        let diffuse = base_color * (self.albedo * surge * self.diffuse_response(normal, tangent, light_dir, view_dir));
+
+        // Lóbulo especular (Phong)
+        let reflect_dir = normal * 2.0 * normal.dot(&light_dir) - light_dir;
+        let spec = reflect_dir.dot(&view_dir).max(0.0).powf(self.shininess);
+        let specular = Color::from_float(1.0, 1.0, 1.0) * (spec * self.specular);
+
+        ambient + diffuse + specular
+    }
+
+    // Término de Hapke simplificado: B0 / (1 + tan(g/2) / h), con g el ángulo de fase entre
+    // las direcciones hacia el Sol y hacia la cámara
+    fn opposition_surge(&self, light_dir: Vec3, view_dir: Vec3) -> f32 {
+        if self.opposition <= 0.0 {
+            return 0.0;
+        }
+        let phase = light_dir.dot(&view_dir).clamp(-1.0, 1.0).acos();
+        if phase >= std::f32::consts::FRAC_PI_2 {
+            return 0.0;
+        }
+        self.opposition / (1.0 + (phase * 0.5).tan() / OPPOSITION_WIDTH)
+    }
+
+    // Luz difusa devuelta hacia la cámara, ya multiplicada por el coseno de incidencia
+    fn diffuse_response(&self, normal: Vec3, tangent: Option<Vec3>, light_dir: Vec3, view_dir: Vec3) -> f32 {
+        let cos_light = normal.dot(&light_dir);
+        match self.brdf {
+            Brdf::Lambert => cos_light.max(0.0),
+            Brdf::OrenNayar { roughness } => {
+                if cos_light <= 0.0 {
+                    return 0.0;
+                }
+                let cos_view = normal.dot(&view_dir).clamp(0.0, 1.0);
+                let sigma2 = roughness * roughness;
+                let a = 1.0 - 0.5 * sigma2 / (sigma2 + 0.33);
+                let b = 0.45 * sigma2 / (sigma2 + 0.09);
+
+                // Coseno de la diferencia de acimut entre luz y vista, en el plano tangente
+                let light_plane = light_dir - normal * cos_light;
+                let view_plane = view_dir - normal * cos_view;
+                let cos_azimuth = if light_plane.norm() > 1e-4 && view_plane.norm() > 1e-4 {
+                    light_plane.normalize().dot(&view_plane.normalize()).max(0.0)
+                } else {
+                    0.0
+                };
+
+                let theta_light = cos_light.min(1.0).acos();
+                let theta_view = cos_view.acos();
+                let alpha = theta_light.max(theta_view);
+                let beta = theta_light.min(theta_view);
+                cos_light * (a + b * cos_azimuth * alpha.sin() * beta.tan())
+            }
+            Brdf::Sheen { strength, exponent } => {
+                // Plano fino: se ilumina por las dos caras
+                let lambert = cos_light.abs();
+                let Some(tangent) = tangent else {
+                    return lambert;
+                };
+                // Kajiya-Kay: máximo cuando el vector medio es perpendicular a las partículas
+                let half = (light_dir + view_dir).normalize();
+                let along = tangent.dot(&half);
+                let sheen = (1.0 - along * along).max(0.0).powf(exponent * 0.5);
+                // Dispersión hacia delante: más intensa cuando el Sol está detrás del anillo
+                let forward = (-light_dir.dot(&view_dir)).max(0.0);
+                lambert + strength * sheen * (0.5 + 0.5 * forward)
+            }
+        }
+    }
+}
+
+// Material de cada tipo de cuerpo. Los gigantes gaseosos y el Sol usan su propia
+// iluminación en el shader; aquí quedan con Lambert para quien los consulte
+pub fn body_material(body: CelestialBody) -> Material {
+    match body {
+        CelestialBody::Earth => Material::lambert(0.8, 0.25, 0.05, 4.0),
+        CelestialBody::Procedural(_) => Material::lambert(0.8, 0.25, 0.05, 4.0),
+        CelestialBody::Mars => Material { specular: 0.08, shininess: 4.0, ..Material::dusty(0.75, 0.3, 0.35, 0.3) },
+        CelestialBody::Moon => Material::dusty(0.85, 0.2, 0.6, 0.6),
+        CelestialBody::Phobos => Material::dusty(0.85, 0.18, 0.6, 0.5),
+        CelestialBody::Deimos => Material::dusty(0.85, 0.2, 0.55, 0.5),
+        CelestialBody::Callisto => Material::dusty(0.85, 0.2, 0.45, 0.4),
+        CelestialBody::Io => Material::lambert(0.85, 0.2, 0.05, 4.0),
+        CelestialBody::Europa => Material::lambert(0.9, 0.2, 0.35, 32.0),
+        CelestialBody::Ganymede => Material::lambert(0.85, 0.2, 0.05, 4.0),
+        CelestialBody::LavaPlanet => Material::lambert(0.6, 0.2, 0.1, 4.0),
+        CelestialBody::IcePlanet => Material::lambert(0.6, 0.4, 0.9, 128.0),
+        CelestialBody::AlienPlanet => Material::lambert(0.7, 0.35, 0.4, 16.0),
+        // Pintura metálica: brillo especular marcado para que se lean las aristas de la nave
+        CelestialBody::MeshMaterial | CelestialBody::Station => Material::lambert(0.85, 0.3, 0.45, 24.0),
+        CelestialBody::Ring => Material {
+            albedo: 0.7,
+            brdf: Brdf::Sheen { strength: 0.35, exponent: 24.0 },
+            ambient: 0.3,
+            specular: 0.0,
+            shininess: 1.0,
+            opposition: 0.5, // el "efecto Seeliger" de los anillos de Saturno
+        },
+        _ => Material::lambert(0.8, 0.25, 0.0, 1.0),
+    }
+}
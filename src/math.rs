@@ -0,0 +1,354 @@
+use nalgebra_glm::{Mat3, Mat4, Vec3, Vec4};
+
+// ============= MATEMÁTICA DEL PIPELINE =============
+// Construcción de las matrices de modelo, vista de pantalla, proyección ortográfica y
+// normales. Todo el renderizador pasa por aquí en lugar de escribir literales Mat4, así que
+// este es el único sitio que hay que tocar para optimizar las cuentas o cambiar de biblioteca
+// de álgebra lineal. El módulo solo depende de nalgebra-glm (y de glam con la feature
+// `glam`); benches/math.rs lo incluye tal cual para medirlo.
+
+// Traslación * escala uniforme * rotación (Z * Y * X, ángulos en radianes).
+// Se escribe directamente el resultado en lugar de multiplicar cuatro matrices 4x4
+pub fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
+    let (sin_x, cos_x) = rotation.x.sin_cos();
+    let (sin_y, cos_y) = rotation.y.sin_cos();
+    let (sin_z, cos_z) = rotation.z.sin_cos();
+
+    Mat4::new(
+        scale * cos_z * cos_y,
+        scale * (cos_z * sin_y * sin_x - sin_z * cos_x),
+        scale * (cos_z * sin_y * cos_x + sin_z * sin_x),
+        translation.x,
+        scale * sin_z * cos_y,
+        scale * (sin_z * sin_y * sin_x + cos_z * cos_x),
+        scale * (sin_z * sin_y * cos_x - cos_z * sin_x),
+        translation.y,
+        -scale * sin_y,
+        scale * cos_y * sin_x,
+        scale * cos_y * cos_x,
+        translation.z,
+        0.0, 0.0, 0.0, 1.0,
+    )
+}
+
+// NDC ([-1, 1], y hacia arriba) a píxeles (y hacia abajo); la profundidad pasa sin cambios
+pub fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
+    Mat4::new(
+        width / 2.0, 0.0, 0.0, width / 2.0,
+        0.0, -height / 2.0, 0.0, height / 2.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0
+    )
+}
+
+// Ortográfica con profundidad invertida (ver framebuffer::FAR_DEPTH): near -> 1, far -> 0
+pub fn create_orthographic_matrix(half_width: f32, half_height: f32, near: f32, far: f32) -> Mat4 {
+    let depth_range = far - near;
+    Mat4::new(
+        1.0 / half_width, 0.0,               0.0,               0.0,
+        0.0,              1.0 / half_height, 0.0,               0.0,
+        0.0,              0.0,               1.0 / depth_range, far / depth_range,
+        0.0,              0.0,               0.0,               1.0,
+    )
+}
+
+// Matriz de las normales: inversa traspuesta del bloque 3x3 del modelo, que lleva las
+// normales al espacio del mundo perpendiculares a la superficie transformada
+pub fn create_normal_matrix(model_matrix: &Mat4) -> Mat3 {
+    model_matrix
+        .fixed_view::<3, 3>(0, 0)
+        .into_owned()
+        .try_inverse()
+        .map(|inverse| inverse.transpose())
+        .unwrap_or(Mat3::identity())
+}
+
+// Aplica una matriz de viewport a un punto en NDC. La matriz solo tiene escala y traslación,
+// así que bastan seis entradas en lugar del producto 4x4 completo
+pub fn viewport_transform(viewport_matrix: &Mat4, ndc: Vec3) -> Vec3 {
+    Vec3::new(
+        viewport_matrix[(0, 0)] * ndc.x + viewport_matrix[(0, 3)],
+        viewport_matrix[(1, 1)] * ndc.y + viewport_matrix[(1, 3)],
+        viewport_matrix[(2, 2)] * ndc.z + viewport_matrix[(2, 3)],
+    )
+}
+
+// ============= BACKEND DE ÁLGEBRA LINEAL =============
+// El bucle caliente del vertex shader (MVP * posición y matriz de normales * normal) pasa por
+// este trait. Las matrices se convierten al tipo del backend una vez por llamada de dibujo
+// (Uniforms guarda BackendMat4/BackendMat3) y el resto del renderizador sigue con nalgebra.
+// Con `--features glam` se usa glam, que vectoriza los productos con SIMD.
+
+pub trait MathBackend {
+    type Mat4: Copy + Send + Sync;
+    type Mat3: Copy + Send + Sync;
+    const NAME: &'static str;
+
+    fn mat4(matrix: &Mat4) -> Self::Mat4;
+    fn mat3(matrix: &Mat3) -> Self::Mat3;
+    // Punto en coordenadas homogéneas (w = 1)
+    fn transform_point(matrix: &Self::Mat4, point: &Vec3) -> Vec4;
+    fn transform_vector(matrix: &Self::Mat3, vector: &Vec3) -> Vec3;
+}
+
+// Con glam solo queda como referencia de las pruebas
+#[cfg(any(not(feature = "glam"), test))]
+pub struct NalgebraBackend;
+
+#[cfg(any(not(feature = "glam"), test))]
+impl MathBackend for NalgebraBackend {
+    type Mat4 = Mat4;
+    type Mat3 = Mat3;
+    const NAME: &'static str = "nalgebra";
+
+    fn mat4(matrix: &Mat4) -> Mat4 {
+        *matrix
+    }
+
+    fn mat3(matrix: &Mat3) -> Mat3 {
+        *matrix
+    }
+
+    fn transform_point(matrix: &Mat4, point: &Vec3) -> Vec4 {
+        matrix * Vec4::new(point.x, point.y, point.z, 1.0)
+    }
+
+    fn transform_vector(matrix: &Mat3, vector: &Vec3) -> Vec3 {
+        matrix * vector
+    }
+}
+
+#[cfg(feature = "glam")]
+pub struct GlamBackend;
+
+// nalgebra y glam guardan las matrices por columnas, así que se copian tal cual
+#[cfg(feature = "glam")]
+impl MathBackend for GlamBackend {
+    type Mat4 = glam::Mat4;
+    type Mat3 = glam::Mat3A;
+    const NAME: &'static str = "glam (SIMD)";
+
+    fn mat4(matrix: &Mat4) -> glam::Mat4 {
+        glam::Mat4::from_cols_slice(matrix.as_slice())
+    }
+
+    fn mat3(matrix: &Mat3) -> glam::Mat3A {
+        glam::Mat3A::from_cols_slice(matrix.as_slice())
+    }
+
+    fn transform_point(matrix: &glam::Mat4, point: &Vec3) -> Vec4 {
+        let result = *matrix * glam::Vec4::new(point.x, point.y, point.z, 1.0);
+        Vec4::new(result.x, result.y, result.z, result.w)
+    }
+
+    fn transform_vector(matrix: &glam::Mat3A, vector: &Vec3) -> Vec3 {
+        let result = *matrix * glam::Vec3A::new(vector.x, vector.y, vector.z);
+        Vec3::new(result.x, result.y, result.z)
+    }
+}
+
+#[cfg(not(feature = "glam"))]
+pub type Backend = NalgebraBackend;
+#[cfg(feature = "glam")]
+pub type Backend = GlamBackend;
+
+pub type BackendMat4 = <Backend as MathBackend>::Mat4;
+pub type BackendMat3 = <Backend as MathBackend>::Mat3;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    // Composición original (una matriz por eje y una de traslación y escala)
+    fn reference_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
+        let rotation_x = nalgebra_glm::rotation(rotation.x, &Vec3::x());
+        let rotation_y = nalgebra_glm::rotation(rotation.y, &Vec3::y());
+        let rotation_z = nalgebra_glm::rotation(rotation.z, &Vec3::z());
+        let transform = nalgebra_glm::translation(&translation) * nalgebra_glm::scaling(&Vec3::repeat(scale));
+        transform * rotation_z * rotation_y * rotation_x
+    }
+
+    fn assert_close(a: &Mat4, b: &Mat4) {
+        assert!((a - b).abs().max() < EPSILON, "{} != {}", a, b);
+    }
+
+    // Entradas aleatorias repetibles (xorshift64) para comprobar cada propiedad en muchos
+    // casos, al estilo de las pruebas de propiedades; sin dependencias para que el módulo
+    // siga compilando solo en benches/math.rs
+    const RANDOM_CASES: usize = 500;
+
+    struct Cases(u64);
+
+    impl Cases {
+        fn new(seed: u64) -> Self {
+            Cases(seed | 1)
+        }
+
+        fn float(&mut self, min: f32, max: f32) -> f32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            min + (max - min) * (self.0 >> 40) as f32 / (1u64 << 24) as f32
+        }
+
+        fn vec3(&mut self, min: f32, max: f32) -> Vec3 {
+            Vec3::new(self.float(min, max), self.float(min, max), self.float(min, max))
+        }
+    }
+
+    #[test]
+    fn model_matrix_matches_composed_rotations() {
+        let cases = [
+            (Vec3::zeros(), 1.0, Vec3::zeros()),
+            (Vec3::new(600.0, 400.0, 0.0), 80.0, Vec3::new(0.4, 0.0, 0.0)),
+            (Vec3::new(-12.0, 3.5, 900.0), 2.5, Vec3::new(0.3, 1.7, -0.9)),
+            (Vec3::new(1.0, 2.0, 3.0), 0.01, Vec3::new(3.1, -2.2, 6.0)),
+        ];
+        for (translation, scale, rotation) in cases {
+            let expected = reference_model_matrix(translation, scale, rotation) / scale.max(1.0);
+            assert_close(&(create_model_matrix(translation, scale, rotation) / scale.max(1.0)), &expected);
+        }
+    }
+
+    #[test]
+    fn random_model_matrices_match_composed_rotations() {
+        let mut cases = Cases::new(0x0DE1);
+        for _ in 0..RANDOM_CASES {
+            let (translation, scale, rotation) = (cases.vec3(-5000.0, 5000.0), cases.float(0.01, 500.0), cases.vec3(-7.0, 7.0));
+            let model = create_model_matrix(translation, scale, rotation);
+            let expected = reference_model_matrix(translation, scale, rotation);
+            let block = |matrix: &Mat4| matrix.fixed_view::<3, 3>(0, 0).into_owned() / scale;
+            assert!((block(&model) - block(&expected)).abs().max() < 1e-4, "{} != {}", model, expected);
+            assert_eq!(model.fixed_view::<3, 1>(0, 3).into_owned(), translation);
+            assert_eq!(model.row(3).into_owned(), Vec4::new(0.0, 0.0, 0.0, 1.0).transpose());
+        }
+    }
+
+    #[test]
+    fn model_matrix_axes_are_orthogonal_with_length_scale() {
+        let mut cases = Cases::new(0xA1E5);
+        for _ in 0..RANDOM_CASES {
+            let scale = cases.float(0.01, 500.0);
+            let model = create_model_matrix(cases.vec3(-100.0, 100.0), scale, cases.vec3(-7.0, 7.0));
+            let axes: Vec<Vec3> = (0..3).map(|column| model.fixed_view::<3, 1>(0, column).into_owned() / scale).collect();
+            for (i, axis) in axes.iter().enumerate() {
+                assert!((axis.norm() - 1.0).abs() < 1e-4, "eje {} con longitud {}", i, axis.norm());
+                for other in &axes[i + 1..] {
+                    assert!(axis.dot(other).abs() < 1e-4);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn model_matrix_places_origin_at_translation() {
+        let translation = Vec3::new(10.0, -20.0, 30.0);
+        let origin = create_model_matrix(translation, 5.0, Vec3::new(0.7, 0.2, 1.1)) * Vec4::new(0.0, 0.0, 0.0, 1.0);
+        assert!((origin.xyz() - translation).norm() < EPSILON);
+    }
+
+    #[test]
+    fn viewport_maps_ndc_corners_to_pixels() {
+        let viewport = create_viewport_matrix(1200.0, 800.0);
+        let top_left = viewport_transform(&viewport, Vec3::new(-1.0, 1.0, 0.5));
+        let bottom_right = viewport_transform(&viewport, Vec3::new(1.0, -1.0, 0.5));
+        assert!((top_left - Vec3::new(0.0, 0.0, 0.5)).norm() < EPSILON);
+        assert!((bottom_right - Vec3::new(1200.0, 800.0, 0.5)).norm() < EPSILON);
+    }
+
+    #[test]
+    fn viewport_transform_matches_matrix_product() {
+        let viewport = create_viewport_matrix(2400.0, 1600.0);
+        let ndc = Vec3::new(0.25, -0.6, 0.9);
+        let full = viewport * Vec4::new(ndc.x, ndc.y, ndc.z, 1.0);
+        assert!((viewport_transform(&viewport, ndc) - full.xyz()).norm() < EPSILON);
+    }
+
+    #[test]
+    fn viewport_keeps_ndc_cube_on_screen() {
+        let mut cases = Cases::new(0x7E57);
+        for _ in 0..RANDOM_CASES {
+            let (width, height) = (cases.float(64.0, 4096.0), cases.float(64.0, 4096.0));
+            let viewport = create_viewport_matrix(width, height);
+            let ndc = cases.vec3(-1.0, 1.0);
+            let pixel = viewport_transform(&viewport, ndc);
+            assert!((0.0..=width).contains(&pixel.x) && (0.0..=height).contains(&pixel.y), "{} fuera de {}x{}", pixel, width, height);
+            assert_eq!(pixel.z, ndc.z);
+
+            // La y de pantalla crece hacia abajo
+            let above = viewport_transform(&viewport, ndc + Vec3::new(0.0, 0.1, 0.0));
+            assert!(above.y < pixel.y);
+            assert!((pixel - (viewport * Vec4::new(ndc.x, ndc.y, ndc.z, 1.0)).xyz()).norm() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn orthographic_depth_is_reversed() {
+        let ortho = create_orthographic_matrix(400.0, 300.0, 0.1, 5000.0);
+        let near = ortho * Vec4::new(0.0, 0.0, -0.1, 1.0);
+        let far = ortho * Vec4::new(0.0, 0.0, -5000.0, 1.0);
+        assert!((near.z - 1.0).abs() < EPSILON);
+        assert!(far.z.abs() < EPSILON);
+        assert_eq!(ortho[(3, 3)], 1.0);
+    }
+
+    #[test]
+    fn normal_matrix_is_inverse_transpose_of_model_block() {
+        let model = create_model_matrix(Vec3::new(5.0, 6.0, 7.0), 3.0, Vec3::new(0.5, -1.0, 0.25));
+        let block = model.fixed_view::<3, 3>(0, 0).into_owned();
+        let product = create_normal_matrix(&model).transpose() * block;
+        assert!((product - Mat3::identity()).abs().max() < EPSILON);
+    }
+
+    #[test]
+    fn normal_matrix_inverts_random_models() {
+        let mut cases = Cases::new(0x4041);
+        for _ in 0..RANDOM_CASES {
+            let model = create_model_matrix(cases.vec3(-1000.0, 1000.0), cases.float(0.1, 100.0), cases.vec3(-7.0, 7.0));
+            let block = model.fixed_view::<3, 3>(0, 0).into_owned();
+            assert!((create_normal_matrix(&model).transpose() * block - Mat3::identity()).abs().max() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn normal_matrix_keeps_normals_perpendicular_to_surface() {
+        let mut cases = Cases::new(0x4098);
+        for _ in 0..RANDOM_CASES {
+            let model = create_model_matrix(cases.vec3(-1000.0, 1000.0), cases.float(0.1, 100.0), cases.vec3(-7.0, 7.0));
+            let block = model.fixed_view::<3, 3>(0, 0).into_owned();
+            let normal = cases.vec3(-1.0, 1.0);
+            let tangent = normal.cross(&cases.vec3(-1.0, 1.0));
+            let world_normal = create_normal_matrix(&model) * normal;
+            let world_tangent = block * tangent;
+            assert!(world_normal.dot(&world_tangent).abs() < 1e-3 * world_normal.norm() * world_tangent.norm().max(1.0));
+            // Con escala uniforme la normal apunta igual que la dirección transformada por el modelo
+            assert!((world_normal.normalize() - (block * normal).normalize()).norm() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn normal_matrix_of_singular_model_is_identity() {
+        let model = create_model_matrix(Vec3::zeros(), 0.0, Vec3::zeros());
+        assert_eq!(create_normal_matrix(&model), Mat3::identity());
+    }
+
+    #[test]
+    fn backend_transforms_match_nalgebra() {
+        let model = create_model_matrix(Vec3::new(600.0, 400.0, -20.0), 40.0, Vec3::new(0.2, 2.4, -0.6));
+        let mvp = nalgebra_glm::reversed_perspective_rh_zo(1.5, 0.8, 0.1, 100_000.0) * model;
+        let normal_matrix = create_normal_matrix(&model);
+        let point = Vec3::new(0.3, -0.8, 0.52);
+
+        let clip = Backend::transform_point(&Backend::mat4(&mvp), &point);
+        let expected_clip = NalgebraBackend::transform_point(&NalgebraBackend::mat4(&mvp), &point);
+        assert_eq!(expected_clip, mvp * Vec4::new(point.x, point.y, point.z, 1.0));
+        assert!((clip - expected_clip).norm() < EPSILON * expected_clip.norm().max(1.0));
+
+        let normal = Backend::transform_vector(&Backend::mat3(&normal_matrix), &point);
+        let expected_normal = NalgebraBackend::transform_vector(&NalgebraBackend::mat3(&normal_matrix), &point);
+        assert_eq!(expected_normal, normal_matrix * point);
+        assert!((normal - expected_normal).norm() < EPSILON);
+    }
+}
@@ -0,0 +1,232 @@
+use nalgebra_glm::Vec3;
+use crate::celestial_shaders::CelestialBody;
+use crate::framebuffer::Framebuffer;
+use crate::line::{draw_line, LineStyle};
+use crate::random_system::StarClass;
+use crate::text::{draw_styled, draw_text, Align, TextStyle, GLYPH_SIZE};
+use crate::theme::{self, HudLayout, Widget};
+use crate::CelestialObject;
+
+// ============= MINIMAPA =============
+// Vista cenital del sistema en una esquina (widget minimap): el plano XZ visto desde arriba,
+// con +X a la derecha y +Z hacia abajo. Cada cuerpo es un punto de su color, cada órbita un
+// círculo alrededor de su centro (el padre, si es una luna) y la cámara una cuña con su campo
+// de visión horizontal. La escala se ajusta en cada frame para que quepan todos los cuerpos,
+// sus órbitas y la cámara (abajo a la derecha, la distancia del centro al borde): por mucho
+// que se acerque uno a un planeta, el mapa enseña dónde está.
+// Se dibuja en un framebuffer propio, así que lo que se sale del recuadro queda recortado.
+
+const SIZE: usize = 180;
+const PADDING: f32 = 10.0;           // píxeles libres entre lo más alejado y el borde
+const MIN_ORBIT_PIXELS: f32 = 3.0;   // las órbitas más pequeñas (lunas lejanas) no se dibujan
+const DOT_RADIUS: (f32, f32) = (1.5, 5.0);
+const WEDGE_LENGTH: f32 = 24.0;
+const MAX_HALF_FOV: f32 = 1.4;       // radianes; más abierta la cuña ya no se entiende
+const ORBIT_ALPHA: f32 = 0.3;
+const CAMERA_COLOR: u32 = 0xFFFFFF;
+
+// Cámara que se marca en el mapa
+#[derive(Clone, Copy)]
+pub struct Viewer {
+    pub position: Vec3,
+    pub forward: Vec3,
+    pub up: Vec3,
+    pub half_fov: f32, // mitad del campo de visión horizontal (radianes)
+}
+
+// Centro y escala del mapa en este frame
+#[derive(Clone, Copy, Debug)]
+pub struct MapView {
+    center: Vec3,
+    scale: f32, // píxeles por unidad del mundo
+    size: f32,
+}
+
+impl MapView {
+    // Centrado en `center`, con cada punto y su margen (un radio) dentro del recuadro
+    pub fn fit(center: Vec3, points: impl IntoIterator<Item = (Vec3, f32)>, size: f32) -> Self {
+        let extent = points
+            .into_iter()
+            .map(|(point, margin)| (point.x - center.x).abs().max((point.z - center.z).abs()) + margin)
+            .fold(1.0, f32::max);
+        MapView { center, scale: (size / 2.0 - PADDING).max(1.0) / extent, size }
+    }
+
+    // Unidades del mundo desde el centro hasta el borde del recuadro
+    pub fn reach(&self) -> f32 {
+        self.size / 2.0 / self.scale
+    }
+
+    pub fn map_point(&self, point: Vec3) -> (f32, f32) {
+        (self.size / 2.0 + (point.x - self.center.x) * self.scale, self.size / 2.0 + (point.z - self.center.z) * self.scale)
+    }
+}
+
+pub struct Minimap {
+    inset: Framebuffer,
+}
+
+impl Minimap {
+    pub fn new() -> Self {
+        Minimap { inset: Framebuffer::new(SIZE, SIZE) }
+    }
+
+    pub fn draw(&mut self, screen: &mut Framebuffer, layout: &mut HudLayout, objects: &[CelestialObject], selected: Option<usize>, viewer: &Viewer) {
+        if objects.is_empty() {
+            return;
+        }
+        let Some((x, y)) = layout.place(Widget::Minimap, SIZE, SIZE) else {
+            return;
+        };
+        let theme = theme::current();
+        let style = if theme.smooth_lines { LineStyle::AntiAliased } else { LineStyle::Aliased };
+        let inset = &mut self.inset;
+        inset.set_background_color(theme.panel_background);
+        inset.clear();
+
+        // Centrado en la estrella, que no se mueve: el mapa no baila de un frame a otro
+        let center = crate::systems::light_position(objects);
+        let orbits: Vec<(Vec3, f32)> = objects
+            .iter()
+            .filter_map(|obj| {
+                let orbit = obj.orbit?;
+                Some((obj.transform.translation - obj.transform.local_translation + orbit.center, orbit.radius))
+            })
+            .collect();
+        let bodies = objects.iter().map(|obj| (obj.transform.translation, obj.transform.scale));
+        let view = MapView::fit(center, bodies.chain(orbits.iter().copied()).chain([(viewer.position, 0.0)]), SIZE as f32);
+
+        for &(orbit_center, radius) in &orbits {
+            let radius = radius * view.scale;
+            if radius >= MIN_ORBIT_PIXELS {
+                draw_circle(inset, view.map_point(orbit_center), radius, theme.muted, style);
+            }
+        }
+        for (index, obj) in objects.iter().enumerate() {
+            let point = view.map_point(obj.transform.translation);
+            let radius = (obj.transform.scale * view.scale).clamp(DOT_RADIUS.0, DOT_RADIUS.1);
+            fill_disc(inset, point, radius, body_color(obj.shader.body));
+            if selected == Some(index) {
+                draw_circle(inset, point, radius + 3.0, theme.label_selected, style);
+            }
+        }
+
+        // Cuña de la cámara. Mirando hacia abajo la dirección de vista apenas tiene componente
+        // horizontal: entonces manda la vertical de la cámara (lo que queda arriba en pantalla)
+        let camera = view.map_point(viewer.position);
+        let heading = [viewer.forward, viewer.up]
+            .into_iter()
+            .map(|direction| (direction.x, direction.z))
+            .find(|(dx, dz)| dx.hypot(*dz) > 0.3);
+        if let Some((dx, dz)) = heading {
+            let angle = dz.atan2(dx);
+            let half_fov = viewer.half_fov.min(MAX_HALF_FOV);
+            let edge = |side: f32| {
+                let a = angle + side * half_fov;
+                (camera.0 + a.cos() * WEDGE_LENGTH, camera.1 + a.sin() * WEDGE_LENGTH)
+            };
+            let (left, right) = (edge(-1.0), edge(1.0));
+            draw_line(inset, camera, left, CAMERA_COLOR, 0.9, style);
+            draw_line(inset, camera, right, CAMERA_COLOR, 0.9, style);
+            draw_line(inset, left, right, CAMERA_COLOR, 0.4, style);
+        }
+        fill_disc(inset, camera, 2.0, CAMERA_COLOR);
+
+        let last = SIZE as f32 - 1.0;
+        for (from, to) in [((0.0, 0.0), (last, 0.0)), ((last, 0.0), (last, last)), ((last, last), (0.0, last)), ((0.0, last), (0.0, 0.0))] {
+            draw_line(inset, from, to, theme.muted, 1.0, LineStyle::Aliased);
+        }
+        draw_text(inset, 5, 5, "Mapa", theme.muted, 1);
+        // Escala: distancia del centro al borde
+        let reach = format!("{:.0} u", view.reach());
+        draw_styled(inset, SIZE as i32 - 5, (SIZE - GLYPH_SIZE - 5) as i32, &reach, &TextStyle::new(theme.muted).with_align(Align::Right));
+        screen.blit(inset, x, y);
+    }
+}
+
+fn draw_circle(screen: &mut Framebuffer, center: (f32, f32), radius: f32, color: u32, style: LineStyle) {
+    let segments = ((radius * 0.8) as usize).clamp(16, 128);
+    let point = |i: usize| {
+        let angle = i as f32 / segments as f32 * std::f32::consts::TAU;
+        (center.0 + angle.cos() * radius, center.1 + angle.sin() * radius)
+    };
+    for i in 0..segments {
+        draw_line(screen, point(i), point(i + 1), color, ORBIT_ALPHA, style);
+    }
+}
+
+// Disco relleno con el borde suavizado
+fn fill_disc(screen: &mut Framebuffer, center: (f32, f32), radius: f32, color: u32) {
+    let reach = radius.ceil() as i32 + 1;
+    let (cx, cy) = (center.0.round() as i32, center.1.round() as i32);
+    for py in cy - reach..=cy + reach {
+        for px in cx - reach..=cx + reach {
+            let distance = (px as f32 - center.0).hypot(py as f32 - center.1);
+            let coverage = (radius + 0.5 - distance).clamp(0.0, 1.0);
+            if px >= 0 && py >= 0 && coverage > 0.0 {
+                screen.blend_pixel(px as usize, py as usize, color, coverage);
+            }
+        }
+    }
+}
+
+// Color del punto de cada cuerpo: el tono que domina su superficie
+fn body_color(body: CelestialBody) -> u32 {
+    match body {
+        CelestialBody::Sun => 0xFFD24A,
+        CelestialBody::Star(class) => match class {
+            StarClass::O | StarClass::B => 0x9FB8FF,
+            StarClass::A => 0xE8EEFF,
+            StarClass::F | StarClass::G => 0xFFE48A,
+            StarClass::K => 0xFFB060,
+            StarClass::M => 0xFF7A50,
+        },
+        CelestialBody::Earth => 0x4A90E2,
+        CelestialBody::Jupiter => 0xD8B08C,
+        CelestialBody::Mars => 0xD0603A,
+        CelestialBody::Saturn | CelestialBody::Ring => 0xE6D39A,
+        CelestialBody::Moon => 0xB0B0B0,
+        CelestialBody::LavaPlanet => 0xFF6A2A,
+        CelestialBody::IcePlanet => 0xBFE8FF,
+        CelestialBody::AlienPlanet => 0x7CFC9A,
+        CelestialBody::Uranus => 0x9FE3E8,
+        CelestialBody::Neptune => 0x4A6CE0,
+        CelestialBody::Procedural(planet) => planet.biome.palette().lowland.to_hex(),
+        CelestialBody::Io => 0xE8D060,
+        CelestialBody::Europa => 0xD8CFC0,
+        CelestialBody::Ganymede => 0xA89C8C,
+        CelestialBody::Callisto => 0x7A6E64,
+        CelestialBody::Phobos | CelestialBody::Deimos => 0x8C7B6B,
+        CelestialBody::StormClouds => 0xDDDDDD,
+        // La sombra es negra: en el mapa se marca con el color del disco
+        CelestialBody::BlackHole | CelestialBody::AccretionDisc => 0xFFA040,
+        CelestialBody::MeshMaterial | CelestialBody::Station => 0x7FE0FF,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_fits_every_point_and_keeps_the_center_in_the_middle() {
+        let center = Vec3::new(400.0, 300.0, 0.0);
+        let points = [
+            (Vec3::new(1000.0, 300.0, 0.0), 50.0),  // Saturno con su radio
+            (Vec3::new(400.0, 0.0, -250.0), 28.0),  // la Tierra: la altura no cuenta
+            (Vec3::new(-900.0, 800.0, 40.0), 0.0),  // la cámara, muy lejos
+        ];
+        let view = MapView::fit(center, points, SIZE as f32);
+        assert_eq!(view.map_point(center), (SIZE as f32 / 2.0, SIZE as f32 / 2.0));
+        for (point, margin) in points {
+            let (x, y) = view.map_point(point);
+            let margin = margin * view.scale;
+            assert!(x - margin >= PADDING - 1.0e-3 && x + margin <= SIZE as f32 - PADDING + 1.0e-3, "{} {}", x, margin);
+            assert!(y - margin >= PADDING - 1.0e-3 && y + margin <= SIZE as f32 - PADDING + 1.0e-3, "{} {}", y, margin);
+        }
+        // La cámara es lo más lejano: toca el margen izquierdo
+        assert!((view.map_point(points[2].0).0 - PADDING).abs() < 1.0e-3);
+        // +Z hacia abajo
+        assert!(view.map_point(points[1].0).1 < SIZE as f32 / 2.0);
+    }
+}
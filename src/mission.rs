@@ -0,0 +1,329 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use nalgebra_glm::{Mat4, Vec3};
+use crate::cockpit::draw_edge_arrow;
+use crate::comet::{Comet, CometState};
+use crate::framebuffer::Framebuffer;
+use crate::line::{draw_line, LineStyle};
+use crate::scene::{MissionConfig, WaypointConfig, WaypointKind};
+use crate::text::{draw_styled, draw_text, text_width, Align, TextStyle, GLYPH_SIZE};
+use crate::theme::{self, HudLayout, Widget};
+use crate::{project_to_screen, CelestialObject};
+
+// ============= MISIONES DEL VUELO LIBRE =============
+// La tabla [mission] de la escena es una lista de objetivos que se cumplen en orden:
+// llegar cerca de un cuerpo o un cometa, escanearlo (apuntarle con la proa desde cerca unos
+// segundos) o atracar en una estación. Mientras se vuela, un rombo sobre el objetivo actual
+// (o una flecha en el borde si queda fuera de la vista) indica hacia dónde ir, un cartel
+// muestra el progreso y al cumplir cada objetivo aparece un mensaje en el centro. Si la
+// nave se queda sin combustible la misión fracasa y vuelve a empezar cuando la remolcan.
+
+const REACH_RANGE: f32 = 4.0; // radios del objetivo
+const SCAN_RANGE: f32 = 40.0; // los cometas son pequeños: se escanean desde lejos
+const SCAN_CONE: f32 = 0.985; // coseno del ángulo máximo entre la proa y el objetivo (~10°)
+const MESSAGE_SECONDS: f32 = 4.0;
+const MARKER_SIZE: f32 = 9.0;
+
+const MISSION_COLOR: u32 = 0x7CFC9A;
+const FAILURE_COLOR: u32 = 0xFF7A6A;
+
+const PADDING: usize = 10;
+const LINE_HEIGHT: usize = GLYPH_SIZE + 4;
+
+// Lo que la misión necesita saber de la nave
+#[derive(Clone, Copy)]
+pub struct Pilot {
+    pub position: Vec3,
+    pub forward: Vec3,
+    pub docked_at: Option<usize>, // estación en la que está atracada
+}
+
+// El objetivo actual en este frame
+#[derive(Clone, Copy)]
+struct Beacon {
+    position: Vec3,
+    radius: f32,
+    body: Option<usize>, // índice si es un cuerpo (los cometas no tienen)
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum MissionState {
+    Active,
+    Complete,
+    Failed(String), // por qué
+}
+
+pub struct Mission {
+    title: String,
+    waypoints: Vec<WaypointConfig>,
+    current: usize,
+    scan: f32,                // segundos escaneando el objetivo actual
+    beacon: Option<Beacon>,
+    distance: f32,            // de la nave a la superficie del objetivo
+    state: MissionState,
+    messages: VecDeque<(String, Instant, u32)>, // texto, cuándo apareció y color
+}
+
+impl Mission {
+    pub fn new(config: MissionConfig) -> Self {
+        let state = if config.waypoints.is_empty() { MissionState::Complete } else { MissionState::Active };
+        Mission {
+            title: config.title,
+            waypoints: config.waypoints,
+            current: 0,
+            scan: 0.0,
+            beacon: None,
+            distance: 0.0,
+            state,
+            messages: VecDeque::new(),
+        }
+    }
+
+    fn waypoint(&self) -> Option<&WaypointConfig> {
+        self.waypoints.get(self.current).filter(|_| self.state == MissionState::Active)
+    }
+
+    // Sigue a la nave: sitúa el objetivo actual y lo da por cumplido si toca
+    pub fn update(&mut self, dt: f32, pilot: Pilot, objects: &[CelestialObject], comets: &[Comet], comet_states: &[CometState]) {
+        if self.state != MissionState::Active {
+            return;
+        }
+        let Some(waypoint) = self.waypoints.get(self.current) else {
+            return;
+        };
+        self.beacon = locate(&waypoint.target, objects, comets, comet_states);
+        let Some(beacon) = self.beacon else {
+            return;
+        };
+        let offset = beacon.position - pilot.position;
+        self.distance = (offset.magnitude() - beacon.radius).max(0.0);
+
+        let range = |default: f32| waypoint.distance.unwrap_or(default) * beacon.radius;
+        let done = match waypoint.kind {
+            WaypointKind::Reach => offset.magnitude() < range(REACH_RANGE),
+            WaypointKind::Scan => {
+                let aimed = offset.try_normalize(1.0e-6).is_some_and(|direction| direction.dot(&pilot.forward) > SCAN_CONE);
+                // Al perderlo de la mira el escaneo se va descargando
+                self.scan = if aimed && offset.magnitude() < range(SCAN_RANGE) { self.scan + dt } else { (self.scan - dt).max(0.0) };
+                self.scan >= waypoint.seconds
+            }
+            WaypointKind::Dock => beacon.body.is_some() && pilot.docked_at == beacon.body,
+        };
+        if done {
+            // Sitúa ya el siguiente objetivo (y lo cumple si la nave también está en él)
+            self.complete();
+            self.update(0.0, pilot, objects, comets, comet_states);
+        }
+    }
+
+    fn complete(&mut self) {
+        let waypoint = &self.waypoints[self.current];
+        let message = if waypoint.done.is_empty() { format!("Objetivo cumplido: {}", waypoint.objective) } else { waypoint.done.clone() };
+        self.messages.push_back((message, Instant::now(), MISSION_COLOR));
+        self.current += 1;
+        self.scan = 0.0;
+        self.beacon = None;
+        if self.current == self.waypoints.len() {
+            self.state = MissionState::Complete;
+            let message = if self.title.is_empty() { "¡Misión completada!".to_string() } else { format!("¡Misión completada: {}!", self.title) };
+            self.messages.push_back((message, Instant::now(), MISSION_COLOR));
+        }
+    }
+
+    // La misión en curso fracasa (una terminada ya no)
+    pub fn fail(&mut self, reason: &str) {
+        if self.state == MissionState::Active {
+            self.state = MissionState::Failed(reason.to_string());
+            self.beacon = None;
+            self.messages.push_back((format!("Misión fallida: {}", reason), Instant::now(), FAILURE_COLOR));
+        }
+    }
+
+    // Tras fracasar, vuelve a empezar desde el primer objetivo
+    pub fn retry(&mut self) {
+        if matches!(self.state, MissionState::Failed(_)) {
+            self.state = MissionState::Active;
+            self.current = 0;
+            self.scan = 0.0;
+        }
+    }
+
+    // Rombo sobre el objetivo con su distancia, o flecha en el borde si queda fuera de la vista
+    pub fn draw_guidance(&self, screen: &mut Framebuffer, view_matrix: &Mat4, projection_matrix: &Mat4, viewport_matrix: &Mat4) {
+        let (Some(waypoint), Some(beacon)) = (self.waypoint(), self.beacon) else {
+            return;
+        };
+        let theme = theme::current();
+        let style = if theme.smooth_lines { LineStyle::AntiAliased } else { LineStyle::Aliased };
+        let label = format!("{}  {:.0} u", waypoint.objective, self.distance);
+
+        let on_screen = project_to_screen(beacon.position, view_matrix, projection_matrix, viewport_matrix)
+            .filter(|point| point.x >= 0.0 && point.y >= 0.0 && point.x < screen.width as f32 && point.y < screen.height as f32);
+        let Some(point) = on_screen else {
+            draw_edge_arrow(screen, beacon.position, &label, MISSION_COLOR, view_matrix);
+            return;
+        };
+        let corners = [(0.0, -MARKER_SIZE), (MARKER_SIZE, 0.0), (0.0, MARKER_SIZE), (-MARKER_SIZE, 0.0)];
+        for (i, (dx, dy)) in corners.iter().enumerate() {
+            let (nx, ny) = corners[(i + 1) % corners.len()];
+            draw_line(screen, (point.x + dx, point.y + dy), (point.x + nx, point.y + ny), MISSION_COLOR, 1.0, style);
+        }
+        let text_style = TextStyle::new(MISSION_COLOR).with_outline(theme.label_outline).with_align(Align::Center);
+        draw_styled(screen, point.x as i32, (point.y + MARKER_SIZE) as i32 + 6, &label, &text_style);
+    }
+
+    // Cartel con el objetivo actual y el progreso de la misión
+    pub fn draw_hud(&self, screen: &mut Framebuffer, layout: &mut HudLayout) {
+        let title = if self.title.is_empty() { "Misión" } else { &self.title };
+        let mut lines = Vec::new();
+        match self.waypoint() {
+            Some(waypoint) => {
+                lines.push(format!("Objetivo {}/{}: {}", self.current + 1, self.waypoints.len(), waypoint.objective));
+                lines.push(match (waypoint.kind, self.beacon) {
+                    (_, None) => "Objetivo fuera de la escena".to_string(),
+                    (WaypointKind::Scan, Some(_)) if self.scan > 0.0 => {
+                        format!("Escaneando: {:.0}%", (self.scan / waypoint.seconds * 100.0).min(100.0))
+                    }
+                    (WaypointKind::Scan, Some(_)) => format!("Distancia: {:.0} u (apunta la proa para escanear)", self.distance),
+                    (WaypointKind::Dock, Some(_)) => format!("Distancia: {:.0} u (llega despacio para atracar)", self.distance),
+                    (WaypointKind::Reach, Some(_)) => format!("Distancia: {:.0} u", self.distance),
+                });
+            }
+            None => match &self.state {
+                MissionState::Failed(reason) => {
+                    lines.push(format!("Fallida: {}", reason));
+                    lines.push("Se reintenta al volver al punto de despegue".to_string());
+                }
+                _ => lines.push(format!("Completada: {} objetivos", self.waypoints.len())),
+            },
+        }
+
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0).max(title.chars().count() * 2)
+            * GLYPH_SIZE
+            + PADDING * 2;
+        let height = PADDING * 2 + GLYPH_SIZE * 2 + 8 + lines.len() * LINE_HEIGHT;
+        let Some((x, panel_y)) = layout.place(Widget::Mission, width, height) else {
+            return;
+        };
+        let theme = theme::current();
+        screen.blend_rect(x, panel_y, width, height, theme.panel_background, theme.hud_opacity);
+
+        let text_x = (x + PADDING) as i32;
+        let mut y = (panel_y + PADDING) as i32;
+        draw_text(screen, text_x, y, title, theme.title, 2);
+        y += (GLYPH_SIZE * 2 + 8) as i32;
+        for line in &lines {
+            draw_text(screen, text_x, y, line, theme.text, 1);
+            y += LINE_HEIGHT as i32;
+        }
+    }
+
+    // Mensajes de los objetivos cumplidos, centrados a partir de `y`; se desvanecen al
+    // expirar. Devuelve la altura a la que puede ir el siguiente aviso
+    pub fn draw_messages(&mut self, screen: &mut Framebuffer, y: usize) -> usize {
+        let theme = theme::current();
+        let lifetime = Duration::from_secs_f32(MESSAGE_SECONDS);
+        self.messages.retain(|(_, shown, _)| shown.elapsed() < lifetime);
+
+        let mut y = y;
+        let height = GLYPH_SIZE * 2 + PADDING * 2;
+        for (message, shown, color) in &self.messages {
+            let remaining = 1.0 - shown.elapsed().as_secs_f32() / MESSAGE_SECONDS;
+            let alpha = (remaining * 4.0).min(1.0);
+            let width = text_width(message, 2) + PADDING * 2;
+            let x = screen.width.saturating_sub(width) / 2;
+            screen.blend_rect(x, y, width, height, theme.panel_background, theme.hud_opacity * alpha);
+            screen.blend_rect(x, y, 3, height, *color, alpha);
+            draw_text(screen, (x + PADDING) as i32, (y + PADDING) as i32, message, *color, 2);
+            y += height + 6;
+        }
+        y
+    }
+}
+
+// Un cuerpo por su id o, si no, un cometa por su nombre
+fn locate(target: &str, objects: &[CelestialObject], comets: &[Comet], comet_states: &[CometState]) -> Option<Beacon> {
+    if let Some(index) = objects.iter().position(|obj| obj.id == target) {
+        let obj = &objects[index];
+        return Some(Beacon { position: obj.transform.translation, radius: obj.transform.scale, body: Some(index) });
+    }
+    comets
+        .iter()
+        .zip(comet_states)
+        .find(|(comet, _)| comet.name == target)
+        .map(|(comet, state)| Beacon { position: state.position, radius: comet.scale, body: None })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::celestial_shaders::CelestialBody;
+
+    fn waypoint(kind: WaypointKind, target: &str) -> WaypointConfig {
+        WaypointConfig { kind, target: target.to_string(), objective: target.to_string(), done: String::new(), distance: None, seconds: 1.0 }
+    }
+
+    fn body(id: &str, shader: CelestialBody, position: Vec3, scale: f32) -> CelestialObject {
+        let mut obj = CelestialObject::new(shader, position, scale);
+        obj.id = id.to_string();
+        obj
+    }
+
+    #[test]
+    fn waypoints_complete_in_order() {
+        let objects = [
+            body("mars", CelestialBody::Mars, Vec3::new(1000.0, 0.0, 0.0), 20.0),
+            body("station", CelestialBody::Station, Vec3::new(0.0, 0.0, -500.0), 6.0),
+        ];
+        let config = MissionConfig {
+            title: "Prueba".to_string(),
+            waypoints: vec![waypoint(WaypointKind::Reach, "mars"), waypoint(WaypointKind::Scan, "mars"), waypoint(WaypointKind::Dock, "station")],
+        };
+        let mut mission = Mission::new(config);
+        let pilot = |position: Vec3, forward: Vec3, docked_at: Option<usize>| Pilot { position, forward, docked_at };
+
+        // Lejos de Marte no pasa nada; a menos de 4 radios se cumple el primero
+        mission.update(0.1, pilot(Vec3::zeros(), Vec3::x(), None), &objects, &[], &[]);
+        assert_eq!(mission.current, 0);
+        assert!((mission.distance - 980.0).abs() < 1.0e-3);
+        mission.update(0.1, pilot(Vec3::new(950.0, 0.0, 0.0), Vec3::x(), None), &objects, &[], &[]);
+        assert_eq!(mission.current, 1);
+
+        // Escanear: hay que apuntarle durante `seconds`; de espaldas el escaneo no avanza
+        let near = Vec3::new(900.0, 0.0, 0.0);
+        mission.update(0.5, pilot(near, -Vec3::x(), None), &objects, &[], &[]);
+        assert_eq!(mission.scan, 0.0);
+        for _ in 0..3 {
+            mission.update(0.4, pilot(near, Vec3::x(), None), &objects, &[], &[]);
+        }
+        assert_eq!(mission.current, 2);
+
+        // Atracar en otra estación no cuenta; en la del objetivo, sí, y la misión termina
+        mission.update(0.1, pilot(Vec3::zeros(), Vec3::x(), Some(0)), &objects, &[], &[]);
+        assert_eq!(mission.state, MissionState::Active);
+        mission.update(0.1, pilot(Vec3::zeros(), Vec3::x(), Some(1)), &objects, &[], &[]);
+        assert_eq!(mission.state, MissionState::Complete);
+        assert!(mission.waypoint().is_none());
+        assert_eq!(mission.messages.len(), 4);
+    }
+
+    #[test]
+    fn failed_missions_start_over_on_retry() {
+        let objects = [body("mars", CelestialBody::Mars, Vec3::new(1000.0, 0.0, 0.0), 20.0)];
+        let config = MissionConfig { title: String::new(), waypoints: vec![waypoint(WaypointKind::Reach, "mars"), waypoint(WaypointKind::Scan, "mars")] };
+        let mut mission = Mission::new(config);
+        mission.update(0.1, Pilot { position: Vec3::new(950.0, 0.0, 0.0), forward: Vec3::x(), docked_at: None }, &objects, &[], &[]);
+        assert_eq!(mission.current, 1);
+
+        mission.fail("sin combustible");
+        assert!(mission.waypoint().is_none());
+        // Fallida no avanza, y un segundo fallo no repite el mensaje
+        mission.update(5.0, Pilot { position: Vec3::new(950.0, 0.0, 0.0), forward: Vec3::x(), docked_at: None }, &objects, &[], &[]);
+        mission.fail("sin combustible");
+        assert_eq!(mission.messages.len(), 2);
+
+        mission.retry();
+        assert_eq!(mission.current, 0);
+        assert_eq!(mission.state, MissionState::Active);
+    }
+}
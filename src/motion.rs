@@ -0,0 +1,139 @@
+use nalgebra_glm::{Mat4, Vec2, Vec4};
+use crate::framebuffer::Framebuffer;
+
+// ============= VECTORES DE MOVIMIENTO =============
+// Cuántos píxeles se movió cada punto de la imagen desde el frame anterior. Lo usan el
+// antialiasing temporal (taa.rs) y el desenfoque de movimiento (motion_blur.rs).
+//
+// La geometría lo calcula al dibujarse: el vertex shader proyecta cada vértice también con
+// las matrices del frame pasado (el modelo de cada cuerpo y la cámara) y el framebuffer
+// guarda la velocidad de lo que queda delante. Donde no hay geometría (el cielo) la velocidad
+// sale de reproyectar el fondo con las dos cámaras; el cielo está en el infinito, así que solo
+// cuenta el giro de la cámara (como al dibujar las estrellas).
+
+#[derive(Default)]
+pub struct CameraMotion {
+    previous_view_projection: Option<Mat4>, // sin el desplazamiento subpíxel del TAA
+    previous_sky: Option<Mat4>,             // lo mismo solo con el giro de la cámara
+}
+
+impl CameraMotion {
+    pub fn new() -> Self {
+        CameraMotion::default()
+    }
+
+    // Cámara del frame anterior (la actual en el primero, que así no se mueve)
+    pub fn previous_view_projection(&self, view_projection: &Mat4) -> Mat4 {
+        self.previous_view_projection.unwrap_or(*view_projection)
+    }
+
+    // Sin nada que use las velocidades se olvida la cámara, para no volver con una vieja
+    pub fn reset(&mut self) {
+        self.previous_view_projection = None;
+        self.previous_sky = None;
+    }
+
+    // Movimiento del fondo en este frame. La cámara es la actual, con la proyección sin
+    // desplazar; `jitter` es el desplazamiento subpíxel con el que se dibujó la escena
+    pub fn sky(&self, view_matrix: &Mat4, projection_matrix: &Mat4, viewport_matrix: &Mat4, jitter: Vec2) -> SkyMotion {
+        let sky = sky_matrix(view_matrix, projection_matrix);
+        let previous_sky = self.previous_sky.unwrap_or(sky);
+        // Píxel de ahora -> píxel del frame anterior para un punto del fondo
+        let reprojection = (viewport_matrix * sky)
+            .try_inverse()
+            .map(|inverse| viewport_matrix * previous_sky * inverse);
+        SkyMotion { reprojection, jitter }
+    }
+
+    // Al terminar el frame, su cámara pasa a ser la anterior
+    pub fn advance(&mut self, view_matrix: &Mat4, projection_matrix: &Mat4) {
+        self.previous_view_projection = Some(projection_matrix * view_matrix);
+        self.previous_sky = Some(sky_matrix(view_matrix, projection_matrix));
+    }
+}
+
+fn sky_matrix(view_matrix: &Mat4, projection_matrix: &Mat4) -> Mat4 {
+    let mut rotation = *view_matrix;
+    rotation.fixed_view_mut::<3, 1>(0, 3).fill(0.0);
+    projection_matrix * rotation
+}
+
+#[derive(Clone, Copy)]
+pub struct SkyMotion {
+    reprojection: Option<Mat4>,
+    jitter: Vec2,
+}
+
+impl SkyMotion {
+    // Reproyección a lo largo de una fila: un paso fijo por píxel
+    pub fn row(&self, y: usize) -> SkyRow {
+        let jitter = self.jitter;
+        SkyRow {
+            y,
+            jitter,
+            steps: self.reprojection.map(|reprojection| {
+                let start = reprojection * Vec4::new(0.5 - jitter.x, y as f32 + 0.5 - jitter.y, 0.0, 1.0);
+                (start, reprojection.column(0).into_owned())
+            }),
+        }
+    }
+}
+
+pub struct SkyRow {
+    y: usize,
+    jitter: Vec2,
+    steps: Option<(Vec4, Vec4)>,
+}
+
+impl SkyRow {
+    pub fn velocity(&self, x: usize) -> Vec2 {
+        let Some((start, step)) = self.steps else {
+            return Vec2::zeros();
+        };
+        let previous = start + step * x as f32;
+        if previous.w.abs() <= f32::EPSILON {
+            return Vec2::zeros();
+        }
+        Vec2::new(
+            x as f32 + 0.5 - self.jitter.x - previous.x / previous.w,
+            self.y as f32 + 0.5 - self.jitter.y - previous.y / previous.w,
+        )
+    }
+}
+
+// Velocidad de un píxel: la de su geometría o, si no tiene, la del fondo
+pub fn pixel_velocity(scene: &Framebuffer, sky_row: &SkyRow, x: usize) -> Vec2 {
+    scene.velocity(x, sky_row.y).unwrap_or_else(|| sky_row.velocity(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::Vec3;
+    use crate::math::{create_viewport_matrix, viewport_transform};
+
+    #[test]
+    fn sky_follows_the_turn_and_not_the_translation() {
+        let viewport = create_viewport_matrix(200.0, 100.0);
+        let projection = nalgebra_glm::reversed_perspective_rh_zo(2.0, 1.0, 0.1, 1000.0);
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let before = nalgebra_glm::look_at(&Vec3::zeros(), &Vec3::new(0.0, 0.0, -1.0), &up);
+        // Gira un poco y además se desplaza: las estrellas solo notan el giro
+        let after = nalgebra_glm::look_at(&Vec3::new(50.0, 0.0, 0.0), &Vec3::new(50.1, 0.0, -1.0), &up);
+
+        let star = |view: &Mat4| {
+            let clip = sky_matrix(view, &projection) * Vec4::new(-0.2, 0.1, -1.0, 0.0);
+            viewport_transform(&viewport, clip.xyz() / clip.w)
+        };
+        let (from, to) = (star(&before), star(&after));
+
+        let mut motion = CameraMotion::new();
+        motion.advance(&before, &projection);
+        let row = motion.sky(&after, &projection, &viewport, Vec2::zeros()).row(to.y as usize);
+        let x = to.x as usize;
+        // El píxel donde está ahora la estrella se movió lo mismo que ella
+        let velocity = row.velocity(x);
+        let expected = Vec2::new(to.x - from.x, to.y - from.y);
+        assert!((velocity - expected).magnitude() < 0.5, "{:?} vs {:?}", velocity, expected);
+    }
+}
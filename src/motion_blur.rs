@@ -0,0 +1,92 @@
+use rayon::prelude::*;
+use crate::framebuffer::Framebuffer;
+use crate::motion::{pixel_velocity, SkyMotion};
+
+// ============= DESENFOQUE DE MOVIMIENTO =============
+// Con los mismos vectores de movimiento que el antialiasing temporal (ver motion.rs), cada
+// píxel promedia la imagen a lo largo del camino que recorrió durante el frame: la Luna, que
+// da la vuelta a la Tierra a velocidad 1.2, deja una estela y un barrido rápido de la cámara
+// arrastra las estrellas. Como una cámara con el obturador abierto medio frame, la estela
+// cubre la mitad del movimiento, centrada en el píxel. Lo que apenas se mueve no se toca y
+// las estelas muy largas se recortan a MAX_BLUR píxeles.
+
+const SHUTTER: f32 = 0.5;    // fracción del frame con el obturador abierto
+const SAMPLES: u32 = 8;      // lecturas a lo largo de la estela
+const MIN_BLUR: f32 = 1.0;   // estelas más cortas (en píxeles) se dejan nítidas
+const MAX_BLUR: f32 = 48.0;
+
+#[derive(Default)]
+pub struct MotionBlur {
+    blurred: Vec<u32>,
+}
+
+impl MotionBlur {
+    pub fn new() -> Self {
+        MotionBlur::default()
+    }
+
+    pub fn apply(&mut self, scene: &mut Framebuffer, sky: &SkyMotion) {
+        let (width, height) = (scene.width, scene.height);
+        self.blurred.resize(width * height, 0);
+        let frame = &*scene;
+        self.blurred.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+            let sky_row = sky.row(y);
+            for (x, output) in row.iter_mut().enumerate() {
+                let streak = pixel_velocity(frame, &sky_row, x) * SHUTTER;
+                let length = streak.magnitude();
+                if length < MIN_BLUR {
+                    *output = frame.buffer[y * width + x];
+                    continue;
+                }
+                let streak = streak * (MAX_BLUR / length).min(1.0);
+
+                let mut sum = [0u32; 3];
+                for sample in 0..SAMPLES {
+                    let t = sample as f32 / (SAMPLES - 1) as f32 - 0.5;
+                    let sample_x = (x as f32 + 0.5 + streak.x * t).clamp(0.0, (width - 1) as f32) as usize;
+                    let sample_y = (y as f32 + 0.5 + streak.y * t).clamp(0.0, (height - 1) as f32) as usize;
+                    let color = frame.buffer[sample_y * width + sample_x];
+                    sum[0] += (color >> 16) & 0xFF;
+                    sum[1] += (color >> 8) & 0xFF;
+                    sum[2] += color & 0xFF;
+                }
+                let [r, g, b] = sum.map(|channel| channel / SAMPLES);
+                *output = (r << 16) | (g << 8) | b;
+            }
+        });
+        scene.buffer.copy_from_slice(&self.blurred);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::{Mat4, Vec2};
+    use crate::math::create_viewport_matrix;
+    use crate::motion::CameraMotion;
+
+    #[test]
+    fn only_moving_pixels_are_smeared() {
+        let (width, height) = (32, 4);
+        let viewport = create_viewport_matrix(width as f32, height as f32);
+        let projection = nalgebra_glm::perspective(1.0, 1.0, 0.1, 100.0);
+        let sky = CameraMotion::new().sky(&Mat4::identity(), &projection, &viewport, Vec2::zeros());
+
+        // Una columna blanca en la fila 1 que se movió 16 píxeles a la derecha; la fila 2 no se mueve
+        let mut scene = Framebuffer::new(width, height);
+        scene.set_motion_vectors(true);
+        for y in [1, 2] {
+            scene.buffer[y * width + 16] = 0xFFFFFF;
+        }
+        for x in 0..width {
+            scene.set_velocity(x, 1, Vec2::new(16.0, 0.0));
+        }
+        let still = scene.buffer[2 * width..3 * width].to_vec();
+
+        MotionBlur::new().apply(&mut scene, &sky);
+        assert_eq!(&scene.buffer[2 * width..3 * width], &still[..]);
+        let streak = scene.buffer[width..2 * width].iter().filter(|&&pixel| pixel != 0).count();
+        assert!(streak > 1, "la columna no dejó estela");
+        assert!(scene.buffer[width + 16] < 0xFFFFFF);
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs::File;
+use std::io::{self, Write};
+use nalgebra_glm::{Mat4, Vec3};
+use crate::lesson::LessonPlayer;
+use crate::picking::screen_disk;
+use crate::CelestialObject;
+
+// ============= NARRADOR ACCESIBLE (--narrate <archivo|->) =============
+// Cada vez que cambia el foco (cuerpo seleccionado, página de la enciclopedia o paso de
+// la lección) escribe una descripción en texto plano de la vista actual. Con "-" se
+// escribe en stdout; con una ruta, en un archivo que un lector de pantalla puede seguir.
+
+// Lo que tiene el foco en este frame: al cambiar se narra la vista
+#[derive(Clone, Copy, PartialEq)]
+pub struct Focus {
+    pub selected: Option<usize>,
+    pub encyclopedia_page: Option<usize>,
+    pub lesson_step: Option<usize>,
+}
+
+// Contexto de la vista necesario para describirla
+pub struct View<'a> {
+    pub camera_position: Vec3,
+    pub view_matrix: &'a Mat4,
+    pub projection_matrix: &'a Mat4,
+    pub viewport_matrix: &'a Mat4,
+    pub width: f32,
+    pub height: f32,
+}
+
+pub struct Narrator {
+    output: Box<dyn Write>,
+    last_focus: Option<Focus>,
+}
+
+impl Narrator {
+    pub fn open(destination: &str) -> io::Result<Self> {
+        let output: Box<dyn Write> = if destination == "-" {
+            Box::new(io::stdout())
+        } else {
+            Box::new(File::create(destination)?)
+        };
+        Ok(Narrator { output, last_focus: None })
+    }
+
+    // Narra la vista solo si el foco cambió desde la última llamada
+    pub fn update(&mut self, focus: Focus, objects: &[&CelestialObject], lesson: Option<&LessonPlayer>, view: &View) {
+        if self.last_focus == Some(focus) {
+            return;
+        }
+        self.last_focus = Some(focus);
+
+        let mut lines = Vec::new();
+        if let Some(player) = lesson {
+            lines.push(player.narration());
+        }
+        if let Some(obj) = focus.encyclopedia_page.and_then(|page| objects.get(page)) {
+            lines.push(format!("Enciclopedia abierta en {}.", obj.display_name()));
+        }
+        match focus.selected.and_then(|index| objects.get(index)) {
+            Some(obj) => lines.push(describe_body(obj, view)),
+            None => lines.push("Ningún cuerpo seleccionado.".to_string()),
+        }
+        lines.push(describe_visible(objects, view));
+
+        // Un bloque por cambio de foco, separado por una línea en blanco
+        let text = lines.join("\n");
+        if writeln!(self.output, "{}\n", text).and_then(|_| self.output.flush()).is_err() {
+            eprintln!("No se pudo escribir la narración accesible");
+        }
+    }
+}
+
+fn describe_body(obj: &CelestialObject, view: &View) -> String {
+    let distance = (obj.transform.translation - view.camera_position).magnitude();
+    let mut text = format!("Seleccionado: {}, a {:.0} unidades de la cámara", obj.display_name(), distance);
+    match screen_disk(obj, view.view_matrix, view.projection_matrix, view.viewport_matrix) {
+        Some((center, _)) if is_on_screen(center, view) => {
+            text.push_str(&format!(", {}.", screen_region(center, view)));
+        }
+        _ => text.push_str(", fuera de la vista."),
+    }
+    if !obj.metadata.description.is_empty() {
+        text.push(' ');
+        text.push_str(&obj.metadata.description);
+    }
+    text
+}
+
+// Cuerpos en pantalla, del más cercano al más lejano, con su zona aproximada
+fn describe_visible(objects: &[&CelestialObject], view: &View) -> String {
+    let mut visible: Vec<(f32, String)> = objects
+        .iter()
+        .filter_map(|obj| {
+            let (center, _) = screen_disk(obj, view.view_matrix, view.projection_matrix, view.viewport_matrix)?;
+            if !is_on_screen(center, view) {
+                return None;
+            }
+            let distance = (obj.transform.translation - view.camera_position).magnitude();
+            Some((distance, format!("{} ({})", obj.display_name(), screen_region(center, view))))
+        })
+        .collect();
+
+    if visible.is_empty() {
+        return "No hay cuerpos visibles.".to_string();
+    }
+    visible.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let names: Vec<String> = visible.into_iter().map(|(_, name)| name).collect();
+    format!("Cuerpos visibles ({}): {}.", names.len(), names.join(", "))
+}
+
+fn is_on_screen(point: Vec3, view: &View) -> bool {
+    point.x >= 0.0 && point.x < view.width && point.y >= 0.0 && point.y < view.height
+}
+
+// Zona de la pantalla en una cuadrícula de 3x3 ("arriba a la izquierda", "en el centro"...)
+fn screen_region(point: Vec3, view: &View) -> &'static str {
+    let column = (point.x / view.width * 3.0).clamp(0.0, 2.0) as usize;
+    let row = (point.y / view.height * 3.0).clamp(0.0, 2.0) as usize;
+    const REGIONS: [[&str; 3]; 3] = [
+        ["arriba a la izquierda", "arriba", "arriba a la derecha"],
+        ["a la izquierda", "en el centro", "a la derecha"],
+        ["abajo a la izquierda", "abajo", "abajo a la derecha"],
+    ];
+    REGIONS[row][column]
+}
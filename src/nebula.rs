@@ -0,0 +1,213 @@
+use nalgebra_glm::{Mat4, Vec3};
+use rayon::prelude::*;
+use serde::Deserialize;
+use crate::draw_state::BlendMode;
+use crate::framebuffer::Framebuffer;
+use crate::noise::{fbm, hash3};
+
+// ============= NEBULOSA DE FONDO =============
+// Nubes de gas en el fondo de la escena, en lugar del azul casi negro del borrado. El fondo
+// está en el infinito, así que la nebulosa solo depende de la dirección de la mirada: cada
+// rayo se recorre a través de una cáscara de ruido 3D (radios 1 a 2 en torno a la cámara),
+// acumulando la luz que emite el gas y la que tapan las franjas de polvo oscuro.
+// El recorrido se hace a 1/10 de la resolución de la escena y se amplía con interpolación
+// bilineal (las nubes son de baja frecuencia y no se nota). Mientras la cámara no gire la
+// imagen pequeña se reutiliza: desplazarse por la escena no cambia el fondo.
+// En la escena, con la tabla opcional [nebula]:
+//   palette    = "emission" | "reflection" | "planetary" | "dusty"
+//   colors     = [0xFFB0C8, 0xC8325A, 0x2A0A3C]  (paleta propia: núcleo, cuerpo y borde)
+//   density    = 0.5   (fracción del cielo cubierta por gas, de 0 a 1)
+//   brightness = 1.0
+//   seed       = 7     (forma de las nubes)
+
+const DOWNSCALE: usize = 10;         // la nebulosa se calcula a 1/10 de la escena
+const STEPS: usize = 10;             // muestras por rayo a través de la cáscara
+const FREQUENCY: f32 = 1.6;          // tamaño de las nubes (más alto = nubes más pequeñas)
+const DUST_FREQUENCY: f32 = 3.1;     // las franjas de polvo son más finas que el gas
+const DUST_ABSORPTION: f32 = 2.2;
+const EMISSION: f32 = 0.9;           // luz máxima que suma el gas antes de `brightness`
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum NebulaPalette {
+    #[default]
+    Emission,   // hidrógeno ionizado: rosas y rojos
+    Reflection, // polvo que refleja estrellas jóvenes: azules
+    Planetary,  // capas de gas expulsadas por una estrella moribunda: verde azulado y magenta
+    Dusty,      // nube fría iluminada desde lejos: ámbar y marrón
+}
+
+impl NebulaPalette {
+    pub const ALL: [NebulaPalette; 4] = [
+        NebulaPalette::Emission,
+        NebulaPalette::Reflection,
+        NebulaPalette::Planetary,
+        NebulaPalette::Dusty,
+    ];
+
+    // Núcleo (gas denso), cuerpo y borde (gas tenue)
+    fn colors(self) -> [u32; 3] {
+        match self {
+            NebulaPalette::Emission => [0xFFB0C8, 0xC8325A, 0x2A0A3C],
+            NebulaPalette::Reflection => [0xC8E4FF, 0x3A6AD0, 0x0A1640],
+            NebulaPalette::Planetary => [0xB8FFE8, 0x20A8A0, 0x5A1A6A],
+            NebulaPalette::Dusty => [0xFFD8A0, 0xA0602A, 0x2A1408],
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct NebulaConfig {
+    pub palette: NebulaPalette,
+    pub colors: Option<[u32; 3]>, // reemplaza a la paleta: núcleo, cuerpo y borde
+    pub density: f32,
+    pub brightness: f32,
+    pub seed: u32,
+}
+
+impl Default for NebulaConfig {
+    fn default() -> Self {
+        NebulaConfig {
+            palette: NebulaPalette::default(),
+            colors: None,
+            density: 0.5,
+            brightness: 1.0,
+            seed: 0,
+        }
+    }
+}
+
+pub struct Nebula {
+    palette: [(f32, f32, f32); 3],
+    threshold: f32,
+    brightness: f32,
+    offset: Vec3,
+    // Imagen pequeña del último frame y la vista con la que se calculó
+    cache: Vec<(f32, f32, f32)>,
+    cache_key: Option<[f32; 13]>,
+}
+
+impl Nebula {
+    pub fn new(config: &NebulaConfig) -> Self {
+        let channels = |hex: u32| {
+            (
+                ((hex >> 16) & 0xFF) as f32 / 255.0,
+                ((hex >> 8) & 0xFF) as f32 / 255.0,
+                (hex & 0xFF) as f32 / 255.0,
+            )
+        };
+        let colors = config.colors.unwrap_or_else(|| config.palette.colors());
+        // La semilla desplaza el ruido a otra región; el desplazamiento se acota para que las
+        // coordenadas no pierdan precisión en f32 con semillas grandes
+        let offset = |axis: i32| 100.0 + 900.0 * hash3(config.seed as i32, axis, 0);
+        Nebula {
+            palette: colors.map(channels),
+            threshold: 1.0 - config.density.clamp(0.0, 1.0),
+            brightness: config.brightness.max(0.0),
+            offset: Vec3::new(offset(0), offset(1), offset(2)),
+            cache: Vec::new(),
+            cache_key: None,
+        }
+    }
+
+    // Suma la nebulosa al fondo del framebuffer (llamar justo después de borrarlo)
+    pub fn draw(&mut self, framebuffer: &mut Framebuffer, view_matrix: &Mat4, projection_matrix: &Mat4) {
+        let width = (framebuffer.width / DOWNSCALE).max(2);
+        let height = (framebuffer.height / DOWNSCALE).max(2);
+
+        // Solo importan la orientación de la cámara, el campo de visión y el tamaño
+        let key = [
+            view_matrix[(0, 0)], view_matrix[(0, 1)], view_matrix[(0, 2)],
+            view_matrix[(1, 0)], view_matrix[(1, 1)], view_matrix[(1, 2)],
+            view_matrix[(2, 0)], view_matrix[(2, 1)], view_matrix[(2, 2)],
+            projection_matrix[(0, 0)], projection_matrix[(1, 1)],
+            width as f32, height as f32,
+        ];
+        if self.cache_key != Some(key) {
+            self.render(width, height, view_matrix, projection_matrix);
+            self.cache_key = Some(key);
+        }
+
+        // Ampliación bilineal sumando sobre el color de fondo
+        let cache = &self.cache;
+        let scale_x = (width - 1) as f32 / framebuffer.width.max(2).saturating_sub(1) as f32;
+        let scale_y = (height - 1) as f32 / framebuffer.height.max(2).saturating_sub(1) as f32;
+        let full_width = framebuffer.width;
+        framebuffer.buffer.par_chunks_mut(full_width).enumerate().for_each(|(y, row)| {
+            let fy = y as f32 * scale_y;
+            let y0 = (fy as usize).min(height - 2);
+            let ty = fy - y0 as f32;
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let fx = x as f32 * scale_x;
+                let x0 = (fx as usize).min(width - 2);
+                let tx = fx - x0 as f32;
+                let sample = |dx: usize, dy: usize| cache[(y0 + dy) * width + x0 + dx];
+                let top = lerp3(sample(0, 0), sample(1, 0), tx);
+                let bottom = lerp3(sample(0, 1), sample(1, 1), tx);
+                let (r, g, b) = lerp3(top, bottom, ty);
+                let channel = |value: f32, shift: u32| ((value.min(1.0) * 255.0) as u32) << shift;
+                let light = channel(r, 16) | channel(g, 8) | channel(b, 0);
+                *pixel = BlendMode::Additive.apply(light, *pixel);
+            }
+        });
+    }
+
+    // Recorre un rayo por píxel de la imagen pequeña
+    fn render(&mut self, width: usize, height: usize, view_matrix: &Mat4, projection_matrix: &Mat4) {
+        // Los ejes de la cámara en el mundo son las filas de la rotación de la vista
+        let right = Vec3::new(view_matrix[(0, 0)], view_matrix[(0, 1)], view_matrix[(0, 2)]);
+        let up = Vec3::new(view_matrix[(1, 0)], view_matrix[(1, 1)], view_matrix[(1, 2)]);
+        let back = Vec3::new(view_matrix[(2, 0)], view_matrix[(2, 1)], view_matrix[(2, 2)]);
+        let (focal_x, focal_y) = (projection_matrix[(0, 0)], projection_matrix[(1, 1)]);
+
+        let mut cache = std::mem::take(&mut self.cache);
+        cache.resize(width * height, (0.0, 0.0, 0.0));
+        let nebula = &*self;
+        cache.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+            let ndc_y = 1.0 - 2.0 * (y as f32 + 0.5) / height as f32;
+            for (x, texel) in row.iter_mut().enumerate() {
+                let ndc_x = 2.0 * (x as f32 + 0.5) / width as f32 - 1.0;
+                let direction = (right * (ndc_x / focal_x) + up * (ndc_y / focal_y) - back).normalize();
+                *texel = nebula.march(direction);
+            }
+        });
+        self.cache = cache;
+    }
+
+    // Luz que llega por una dirección: el gas emite y el polvo absorbe lo que hay detrás
+    fn march(&self, direction: Vec3) -> (f32, f32, f32) {
+        let step = 1.0 / STEPS as f32;
+        let mut transmittance = 1.0;
+        let mut light = (0.0, 0.0, 0.0);
+        // Se recorre de cerca a lejos; el polvo cercano tapa al gas del fondo
+        for i in 0..STEPS {
+            let t = 1.0 + (i as f32 + 0.5) * step;
+            let p = direction * t * FREQUENCY + self.offset;
+            let gas = fbm(p.x, p.y, p.z, 3);
+            let density = ((gas - self.threshold * 0.35 - 0.3) / 0.25).clamp(0.0, 1.0);
+            let dust = fbm(p.x * DUST_FREQUENCY / FREQUENCY + 41.0, p.y * DUST_FREQUENCY / FREQUENCY, p.z * DUST_FREQUENCY / FREQUENCY, 2);
+            let dust = ((dust - 0.55) / 0.2).clamp(0.0, 1.0) * density;
+
+            if density > 0.0 {
+                // Gas denso con el color del núcleo y el tenue con el del borde
+                let [core, body, edge] = self.palette;
+                let color = if density > 0.5 {
+                    lerp3(body, core, (density - 0.5) * 2.0)
+                } else {
+                    lerp3(edge, body, density * 2.0)
+                };
+                let emitted = density * density * EMISSION * self.brightness * transmittance * step;
+                light.0 += color.0 * emitted;
+                light.1 += color.1 * emitted;
+                light.2 += color.2 * emitted;
+            }
+            transmittance *= (-dust * DUST_ABSORPTION * step).exp();
+        }
+        light
+    }
+}
+
+fn lerp3(a: (f32, f32, f32), b: (f32, f32, f32), t: f32) -> (f32, f32, f32) {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t, a.2 + (b.2 - a.2) * t)
+}
@@ -0,0 +1,267 @@
+// ============= RUIDO DE GRADIENTE (PERLIN MEJORADO) =============
+// Reemplaza el hash basado en sin(...) * 43758.5453, que mostraba artefactos
+// direccionales y bandas con coordenadas grandes. Todas las funciones públicas
+// devuelven valores en [0, 1] como las versiones anteriores, para que los umbrales
+// de los shaders sigan siendo válidos.
+
+// Tabla de permutación (duplicada para evitar el módulo en los índices)
+const PERMUTATION: [u8; 512] = build_permutation(0x5EED_CAFE);
+
+// Mezcla de Fisher-Yates con un LCG, evaluada en tiempo de compilación
+const fn build_permutation(seed: u32) -> [u8; 512] {
+    let mut base = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        base[i] = i as u8;
+        i += 1;
+    }
+
+    let mut state = seed;
+    let mut i = 255;
+    while i > 0 {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let j = (state >> 8) as usize % (i + 1);
+        let tmp = base[i];
+        base[i] = base[j];
+        base[j] = tmp;
+        i -= 1;
+    }
+
+    let mut table = [0u8; 512];
+    let mut i = 0;
+    while i < 512 {
+        table[i] = base[i & 255];
+        i += 1;
+    }
+    table
+}
+
+// La desviación del ruido de gradiente es menor que la del ruido de valor anterior;
+// este factor reescala la salida para conservar el contraste de los shaders existentes
+const CONTRAST: f32 = 1.3;
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+// Producto punto con uno de los 12 gradientes de las aristas del cubo
+fn grad(hash: u8, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+// Ruido de Perlin 3D con signo, aproximadamente en [-1, 1]
+pub fn perlin(x: f32, y: f32, z: f32) -> f32 {
+    let xf = x.floor();
+    let yf = y.floor();
+    let zf = z.floor();
+
+    // Posición de la celda en la tabla (se repite cada 256 unidades)
+    let xi = (xf as i32 & 255) as usize;
+    let yi = (yf as i32 & 255) as usize;
+    let zi = (zf as i32 & 255) as usize;
+
+    let x = x - xf;
+    let y = y - yf;
+    let z = z - zf;
+
+    let u = fade(x);
+    let v = fade(y);
+    let w = fade(z);
+
+    let p = &PERMUTATION;
+    let a = p[xi] as usize + yi;
+    let aa = p[a] as usize + zi;
+    let ab = p[a + 1] as usize + zi;
+    let b = p[xi + 1] as usize + yi;
+    let ba = p[b] as usize + zi;
+    let bb = p[b + 1] as usize + zi;
+
+    lerp(
+        lerp(
+            lerp(grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z), u),
+            lerp(grad(p[ab], x, y - 1.0, z), grad(p[bb], x - 1.0, y - 1.0, z), u),
+            v,
+        ),
+        lerp(
+            lerp(grad(p[aa + 1], x, y, z - 1.0), grad(p[ba + 1], x - 1.0, y, z - 1.0), u),
+            lerp(grad(p[ab + 1], x, y - 1.0, z - 1.0), grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0), u),
+            v,
+        ),
+        w,
+    )
+}
+
+// Ruido de gradiente remapeado a [0, 1]
+pub fn noise(x: f32, y: f32, z: f32) -> f32 {
+    (0.5 + 0.5 * CONTRAST * perlin(x, y, z)).clamp(0.0, 1.0)
+}
+
+// Hash entero -> [0, 1) para valores aleatorios por celda (Worley, destellos, etc.)
+pub fn hash3(x: i32, y: i32, z: i32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8DA6_B343)
+        ^ (y as u32).wrapping_mul(0xD816_3841)
+        ^ (z as u32).wrapping_mul(0xCB1A_B31F);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    h = h.wrapping_mul(0x297A_2D39);
+    h ^= h >> 15;
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
+
+// Función para ruido fractal (Fractal Brownian Motion)
+pub fn fbm(x: f32, y: f32, z: f32, octaves: u32) -> f32 {
+    let mut value = 0.0;
+    let mut amplitude = 0.5;
+    let mut frequency = 1.0;
+    let mut max_value = 0.0;
+
+    for _ in 0..octaves {
+        value += noise(x * frequency, y * frequency, z * frequency) * amplitude;
+        max_value += amplitude;
+        frequency *= 2.0;
+        amplitude *= 0.5;
+    }
+
+    // Normalizar para mantener el rango [0, 1]
+    if max_value > 0.0 {
+        value / max_value
+    } else {
+        value
+    }
+}
+
+// Turbulencia para efectos caóticos (suma de valores absolutos del ruido con signo)
+pub fn turbulence(x: f32, y: f32, z: f32, octaves: u32) -> f32 {
+    let mut value = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut max_value = 0.0;
+
+    for _ in 0..octaves {
+        let n = noise(x * frequency, y * frequency, z * frequency);
+        value += (n - 0.5).abs() * amplitude;
+        max_value += amplitude * 0.5;
+        frequency *= 2.0;
+        amplitude *= 0.5;
+    }
+
+    // Normalizar
+    if max_value > 0.0 {
+        value / max_value
+    } else {
+        value
+    }
+}
+
+// Worley/Cellular noise: diferencia entre las dos distancias más cercanas (bordes suaves)
+pub fn worley_noise(x: f32, y: f32, z: f32) -> f32 {
+    let xi = x.floor() as i32;
+    let yi = y.floor() as i32;
+    let zi = z.floor() as i32;
+
+    let mut min_dist: f32 = 100.0;
+    let mut second_min_dist: f32 = 100.0;
+
+    for i in -1..=1 {
+        for j in -1..=1 {
+            for k in -1..=1 {
+                let (cx, cy, cz) = (xi + i, yi + j, zi + k);
+
+                // Punto característico aleatorio dentro de la celda
+                let point_x = cx as f32 + hash3(cx, cy, cz);
+                let point_y = cy as f32 + hash3(cx + 1, cy, cz);
+                let point_z = cz as f32 + hash3(cx, cy + 1, cz);
+
+                let dx = point_x - x;
+                let dy = point_y - y;
+                let dz = point_z - z;
+                let dist = (dx * dx + dy * dy + dz * dz).sqrt();
+
+                if dist < min_dist {
+                    second_min_dist = min_dist;
+                    min_dist = dist;
+                } else if dist < second_min_dist {
+                    second_min_dist = dist;
+                }
+            }
+        }
+    }
+
+    (second_min_dist - min_dist).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::procedural::SeededRng;
+
+    const RANDOM_CASES: usize = 2000;
+
+    fn random_point(rng: &mut SeededRng, range: f32) -> (f32, f32, f32) {
+        let mut coordinate = || (rng.next_f32() * 2.0 - 1.0) * range;
+        (coordinate(), coordinate(), coordinate())
+    }
+
+    // Los umbrales de los shaders suponen que todo el ruido está en [0, 1]
+    #[test]
+    fn noise_functions_stay_in_unit_range() {
+        let mut rng = SeededRng::new(0x0153);
+        for case in 0..RANDOM_CASES {
+            // Coordenadas pequeñas y grandes (las de los shaders llegan a miles)
+            let range = if case % 2 == 0 { 10.0 } else { 5000.0 };
+            let (x, y, z) = random_point(&mut rng, range);
+            let octaves = 1 + (rng.next_u64() % 8) as u32;
+            for (name, value) in [
+                ("noise", noise(x, y, z)),
+                ("fbm", fbm(x, y, z, octaves)),
+                ("turbulence", turbulence(x, y, z, octaves)),
+                ("worley_noise", worley_noise(x, y, z)),
+            ] {
+                assert!((0.0..=1.0).contains(&value), "{}({}, {}, {}) = {}", name, x, y, z, value);
+            }
+            assert!((-1.5..=1.5).contains(&perlin(x, y, z)));
+        }
+    }
+
+    #[test]
+    fn hash3_is_deterministic_and_below_one() {
+        let mut rng = SeededRng::new(0x4A54);
+        for _ in 0..RANDOM_CASES {
+            let (x, y, z) = (rng.next_u64() as i32, rng.next_u64() as i32, rng.next_u64() as i32);
+            let value = hash3(x, y, z);
+            assert!((0.0..1.0).contains(&value));
+            assert_eq!(value, hash3(x, y, z));
+        }
+    }
+
+    #[test]
+    fn perlin_is_zero_on_lattice_points() {
+        for (x, y, z) in [(0, 0, 0), (3, -7, 12), (-255, 256, 1000)] {
+            assert_eq!(perlin(x as f32, y as f32, z as f32), 0.0);
+        }
+    }
+
+    #[test]
+    fn fbm_with_one_octave_is_noise() {
+        let mut rng = SeededRng::new(0xFB41);
+        for _ in 0..100 {
+            let (x, y, z) = random_point(&mut rng, 100.0);
+            assert_eq!(fbm(x, y, z, 1), noise(x, y, z));
+        }
+        assert_eq!(fbm(1.5, 2.5, 3.5, 0), 0.0);
+    }
+}
@@ -1,7 +1,88 @@
-use tobj;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
 use nalgebra_glm::{Vec2, Vec3};
+use crate::color::Color;
 use crate::vertex::Vertex;
 
+// ============= MALLAS: OBJ, STL Y PLY =============
+// `Obj::load` elige el formato por la extensión del archivo:
+// - .obj: v, vt, vn y caras `f` con índices v, v/t, v//n o v/t/n (negativos = relativos al
+//   final). Los polígonos se triangulan en abanico; o, g, s y l se ignoran.
+//   Los errores indican la línea: caras mal escritas o índices fuera de rango.
+//   `mtllib` lee la biblioteca de materiales (.mtl, junto al OBJ) y `usemtl` asigna el
+//   material a las caras siguientes: cada vértice lleva el índice del material y su color
+//   difuso (Kd), que pinta el shader MeshMaterial. Un material con luz propia (Ke) la lleva en
+//   lugar del difuso y no se ilumina (las balizas de la estación). Si falta la biblioteca o un
+//   material, las caras quedan en gris claro y el resumen lo avisa.
+// - .stl: binario o ASCII (impresión 3D). Cada faceta lleva su normal; si viene a cero se
+//   calcula con el producto vectorial.
+// - .ply: ASCII o binario (little/big endian), típico de escaneos. Se leen x/y/z, las
+//   normales nx/ny/nz y las coordenadas u/v (o s/t) si existen, y las caras como listas de
+//   índices (los polígonos se triangulan en abanico).
+// Los vértices sin normal en el archivo reciben una suavizada, promedio de las de sus caras.
+// Todas las mallas pasan al sistema de la escena girando 180° sobre X.
+
+#[derive(Debug)]
+pub enum MeshError {
+    Io(std::io::Error),
+    Syntax { line: usize, message: String },
+    MalformedFace { line: usize, message: String },
+    IndexOutOfRange { line: usize, kind: &'static str, index: i64, available: usize },
+    Empty,
+    Stl(String),
+    Ply(String),
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MeshError::Io(err) if err.kind() == std::io::ErrorKind::NotFound => write!(f, "no existe el archivo"),
+            MeshError::Io(err) => write!(f, "no se pudo leer la malla: {}", err),
+            MeshError::Syntax { line, message } => write!(f, "línea {}: {}", line, message),
+            MeshError::MalformedFace { line, message } => write!(f, "línea {}: cara mal formada: {}", line, message),
+            MeshError::IndexOutOfRange { line, kind, index, available } => write!(
+                f,
+                "línea {}: índice de {} {} fuera de rango (hay {})",
+                line, kind, index, available
+            ),
+            MeshError::Empty => write!(f, "la malla no tiene ninguna cara"),
+            MeshError::Stl(message) => write!(f, "STL no válido: {}", message),
+            MeshError::Ply(message) => write!(f, "PLY no válido: {}", message),
+        }
+    }
+}
+
+// Resumen de una malla cargada, para mostrarlo al arrancar
+pub struct MeshStats {
+    pub vertices: usize,
+    pub triangles: usize,
+    pub computed_normals: usize, // vértices sin normal en el archivo
+    pub texcoords: bool,
+    pub materials: usize,
+    pub missing_materials: Vec<String>, // bibliotecas .mtl o materiales que no se encontraron
+}
+
+impl fmt::Display for MeshStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} vértices, {} triángulos", self.vertices, self.triangles)?;
+        if self.computed_normals > 0 {
+            write!(f, ", {} vértices sin normal (calculadas)", self.computed_normals)?;
+        }
+        if !self.texcoords {
+            write!(f, ", sin coordenadas de textura")?;
+        }
+        if self.materials > 0 {
+            write!(f, ", {} materiales", self.materials)?;
+        }
+        if !self.missing_materials.is_empty() {
+            write!(f, " (no se encontró: {})", self.missing_materials.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
 pub struct Obj {
     meshes: Vec<Mesh>,
 }
@@ -11,33 +92,96 @@ struct Mesh {
     normals: Vec<Vec3>,
     texcoords: Vec<Vec2>,
     indices: Vec<u32>,
+    computed_normals: usize,
+    vertex_materials: Vec<u16>,   // por vértice: 0 sin material, n = materials[n - 1]
+    materials: Vec<MeshMaterial>,
+    missing_materials: Vec<String>,
+}
+
+impl Mesh {
+    fn new() -> Self {
+        Mesh {
+            vertices: Vec::new(),
+            normals: Vec::new(),
+            texcoords: Vec::new(),
+            indices: Vec::new(),
+            computed_normals: 0,
+            vertex_materials: Vec::new(),
+            materials: Vec::new(),
+            missing_materials: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct MeshMaterial {
+    name: String,
+    diffuse: Color,
+    emission: Option<Color>, // Ke, si no es negro
+}
+
+// Color de las caras cuyo material no está en ninguna biblioteca
+const MISSING_MATERIAL_COLOR: (f32, f32, f32) = (0.7, 0.7, 0.7);
+
+// Del sistema del archivo al de la escena
+fn to_scene(x: f32, y: f32, z: f32) -> Vec3 {
+    Vec3::new(x, -y, -z)
+}
+
+fn face_normal(a: Vec3, b: Vec3, c: Vec3) -> Vec3 {
+    let normal = (b - a).cross(&(c - a));
+    if normal.norm() > 0.0 { normal.normalize() } else { Vec3::new(0.0, 1.0, 0.0) }
 }
 
 impl Obj {
-    pub fn load(filename: &str) -> Result<Self, tobj::LoadError> {
-        let (models, _) = tobj::load_obj(filename, &tobj::LoadOptions {
-            single_index: true,
-            triangulate: true,
-            ..Default::default()
-        })?;
-
-        let meshes = models.into_iter().map(|model| {
-            let mesh = model.mesh;
-            Mesh {
-                vertices: mesh.positions.chunks(3)
-                    .map(|v| Vec3::new(v[0], -v[1], -v[2]))
-                    .collect(),
-                normals: mesh.normals.chunks(3)
-                    .map(|n| Vec3::new(n[0], -n[1], -n[2]))
-                    .collect(),
-                texcoords: mesh.texcoords.chunks(2)
-                    .map(|t| Vec2::new(t[0], 1.0 - t[1]))
-                    .collect(),
-                indices: mesh.indices,
-            }
-        }).collect();
-
-        Ok(Obj { meshes })
+    pub fn load(filename: &str) -> Result<Self, MeshError> {
+        let extension = Path::new(filename)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+        let bytes = fs::read(filename).map_err(MeshError::Io)?;
+        let mesh = match extension.as_deref() {
+            Some("stl") => parse_stl(&bytes)?,
+            Some("ply") => parse_ply(&bytes)?,
+            _ => {
+                let directory = Path::new(filename).parent().unwrap_or(Path::new(""));
+                parse_obj(&String::from_utf8_lossy(&bytes), directory)?
+            }
+        };
+        Ok(Obj { meshes: vec![mesh] })
+    }
+
+    pub fn stats(&self) -> MeshStats {
+        MeshStats {
+            vertices: self.meshes.iter().map(|mesh| mesh.vertices.len()).sum(),
+            triangles: self.meshes.iter().map(|mesh| mesh.indices.len() / 3).sum(),
+            computed_normals: self.meshes.iter().map(|mesh| mesh.computed_normals).sum(),
+            texcoords: self.meshes.iter().all(|mesh| mesh.texcoords.len() == mesh.vertices.len()),
+            materials: self.meshes.iter().map(|mesh| mesh.materials.len()).sum(),
+            missing_materials: self.meshes.iter().flat_map(|mesh| mesh.missing_materials.iter().cloned()).collect(),
+        }
+    }
+
+    // Centra la malla y la escala a radio 1, como la esfera: así `scale` es el radio del
+    // cuerpo sea cual sea la unidad del archivo (milímetros de un STL, metros de un escaneo...)
+    pub fn normalized(mut self) -> Self {
+        let mut points = self.meshes.iter().flat_map(|mesh| mesh.vertices.iter());
+        let Some(first) = points.next() else {
+            return self;
+        };
+        let (min, max) = points.fold((*first, *first), |(min, max), point| (min.inf(point), max.sup(point)));
+        let center = (min + max) * 0.5;
+        let radius = self.meshes
+            .iter()
+            .flat_map(|mesh| mesh.vertices.iter())
+            .map(|point| (point - center).norm())
+            .fold(0.0f32, f32::max);
+        let scale = if radius > 0.0 { 1.0 / radius } else { 1.0 };
+        for mesh in &mut self.meshes {
+            for point in &mut mesh.vertices {
+                *point = (*point - center) * scale;
+            }
+        }
+        self
     }
 
     pub fn get_vertex_array(&self) -> Vec<Vertex> {
@@ -46,14 +190,23 @@ impl Obj {
         for mesh in &self.meshes {
             for &index in &mesh.indices {
                 let position = mesh.vertices[index as usize];
-                let normal = mesh.normals.get(index as usize)
-                    .cloned()
-                    .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+                let normal = mesh.normals[index as usize];
                 let tex_coords = mesh.texcoords.get(index as usize)
                     .cloned()
                     .unwrap_or(Vec2::new(0.0, 0.0));
 
-                vertices.push(Vertex::new(position, normal, tex_coords));
+                let vertex = Vertex::new(position, normal, tex_coords);
+                vertices.push(match mesh.vertex_materials.get(index as usize) {
+                    Some(&material) if material > 0 => {
+                        let mesh_material = &mesh.materials[material as usize - 1];
+                        let vertex = vertex.with_material(material, mesh_material.diffuse);
+                        match mesh_material.emission {
+                            Some(emission) => vertex.with_emission(emission),
+                            None => vertex,
+                        }
+                    }
+                    _ => vertex,
+                });
             }
         }
 
@@ -61,3 +214,466 @@ impl Obj {
     }
 }
 
+// ============= OBJ =============
+
+// Índice de una esquina de cara: 1..=n desde el principio, -1..=-n desde el final
+fn resolve_index(text: &str, line: usize, kind: &'static str, available: usize) -> Result<usize, MeshError> {
+    let index: i64 = text.parse().map_err(|_| MeshError::MalformedFace {
+        line,
+        message: format!("'{}' no es un índice de {}", text, kind),
+    })?;
+    let resolved = if index < 0 { available as i64 + index } else { index - 1 };
+    if index == 0 || resolved < 0 || resolved >= available as i64 {
+        return Err(MeshError::IndexOutOfRange { line, kind, index, available });
+    }
+    Ok(resolved as usize)
+}
+
+// Colores de una biblioteca .mtl: difuso (Kd) y luz propia (Ke); el resto de parámetros se ignoran
+fn parse_mtl(text: &str) -> Vec<MeshMaterial> {
+    let mut materials: Vec<MeshMaterial> = Vec::new();
+    for line in text.lines() {
+        let mut words = line.split_whitespace();
+        match (words.next(), materials.last_mut()) {
+            (Some("newmtl"), _) => materials.push(MeshMaterial {
+                name: words.collect::<Vec<_>>().join(" "),
+                diffuse: Color::from_float(MISSING_MATERIAL_COLOR.0, MISSING_MATERIAL_COLOR.1, MISSING_MATERIAL_COLOR.2),
+                emission: None,
+            }),
+            (Some("Kd"), Some(material)) => {
+                let rgb: Vec<f32> = words.filter_map(|word| word.parse().ok()).collect();
+                if let [r, g, b] = rgb[..] {
+                    material.diffuse = Color::from_float(r, g, b);
+                }
+            }
+            (Some("Ke"), Some(material)) => {
+                let rgb: Vec<f32> = words.filter_map(|word| word.parse().ok()).collect();
+                if let [r, g, b] = rgb[..] {
+                    material.emission = (r > 0.0 || g > 0.0 || b > 0.0).then(|| Color::from_float(r, g, b));
+                }
+            }
+            _ => {}
+        }
+    }
+    materials
+}
+
+fn parse_obj(text: &str, directory: &Path) -> Result<Mesh, MeshError> {
+    let (mut positions, mut texcoords, mut normals) = (Vec::new(), Vec::new(), Vec::new());
+    let mut mesh = Mesh::new();
+    // Cada combinación (posición, textura, normal, material) es un vértice de la malla
+    let mut corners: HashMap<(usize, Option<usize>, Option<usize>, u16), u32> = HashMap::new();
+    let mut position_of: Vec<usize> = Vec::new();
+    let mut library: Vec<MeshMaterial> = Vec::new();
+    let mut current_material = 0u16;
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let mut words = raw.split_whitespace();
+        let keyword = words.next();
+        let numbers = |words: std::str::SplitWhitespace, minimum: usize| -> Result<Vec<f32>, MeshError> {
+            let values: Vec<f32> = words
+                .map(|word| word.parse::<f32>().map_err(|_| MeshError::Syntax { line, message: format!("'{}' no es un número", word) }))
+                .collect::<Result<_, _>>()?;
+            if values.len() < minimum {
+                return Err(MeshError::Syntax { line, message: format!("se esperaban al menos {} números", minimum) });
+            }
+            Ok(values)
+        };
+        match keyword {
+            Some("v") => {
+                let v = numbers(words, 3)?;
+                positions.push(to_scene(v[0], v[1], v[2]));
+            }
+            Some("vt") => {
+                let t = numbers(words, 1)?;
+                texcoords.push(Vec2::new(t[0], 1.0 - t.get(1).copied().unwrap_or(0.0)));
+            }
+            Some("vn") => {
+                let n = numbers(words, 3)?;
+                normals.push(to_scene(n[0], n[1], n[2]));
+            }
+            Some("mtllib") => {
+                let name = words.collect::<Vec<_>>().join(" ");
+                match fs::read_to_string(directory.join(&name)) {
+                    Ok(contents) => library.extend(parse_mtl(&contents)),
+                    Err(_) => mesh.missing_materials.push(name),
+                }
+            }
+            Some("usemtl") => {
+                let name = words.collect::<Vec<_>>().join(" ");
+                current_material = match mesh.materials.iter().position(|material| material.name == name) {
+                    Some(index) => index as u16 + 1,
+                    None => {
+                        let material = match library.iter().find(|material| material.name == name) {
+                            Some(material) => material.clone(),
+                            None => {
+                                if !library.is_empty() {
+                                    mesh.missing_materials.push(name.clone());
+                                }
+                                let (r, g, b) = MISSING_MATERIAL_COLOR;
+                                MeshMaterial { name, diffuse: Color::from_float(r, g, b), emission: None }
+                            }
+                        };
+                        mesh.materials.push(material);
+                        mesh.materials.len() as u16
+                    }
+                };
+            }
+            Some("f") => {
+                let mut face = Vec::new();
+                for corner in words {
+                    let mut parts = corner.split('/');
+                    let position = resolve_index(parts.next().unwrap_or_default(), line, "vértice", positions.len())?;
+                    let texcoord = match parts.next() {
+                        Some("") | None => None,
+                        Some(text) => Some(resolve_index(text, line, "coordenada de textura", texcoords.len())?),
+                    };
+                    let normal = match parts.next() {
+                        Some("") | None => None,
+                        Some(text) => Some(resolve_index(text, line, "normal", normals.len())?),
+                    };
+                    if parts.next().is_some() {
+                        return Err(MeshError::MalformedFace { line, message: format!("'{}' tiene más de tres índices", corner) });
+                    }
+                    let key = (position, texcoord, normal, current_material);
+                    let vertex = *corners.entry(key).or_insert_with(|| {
+                        mesh.vertices.push(positions[position]);
+                        mesh.vertex_materials.push(current_material);
+                        mesh.texcoords.push(texcoord.map_or(Vec2::zeros(), |t| texcoords[t]));
+                        mesh.normals.push(normal.map_or(Vec3::zeros(), |n| normals[n]));
+                        position_of.push(position);
+                        (mesh.vertices.len() - 1) as u32
+                    });
+                    face.push(vertex);
+                }
+                if face.len() < 3 {
+                    return Err(MeshError::MalformedFace { line, message: format!("{} vértices (mínimo 3)", face.len()) });
+                }
+                for i in 1..face.len() - 1 {
+                    mesh.indices.extend([face[0], face[i], face[i + 1]]);
+                }
+            }
+            _ => {} // comentarios, objetos, grupos, suavizado, líneas...
+        }
+    }
+
+    if mesh.indices.is_empty() {
+        return Err(MeshError::Empty);
+    }
+    if texcoords.is_empty() {
+        mesh.texcoords.clear();
+    }
+
+    // Normales que faltan: suavizadas por posición, para que las esquinas que comparten
+    // posición pero no coordenada de textura no dejen una arista marcada
+    let missing: Vec<usize> = (0..mesh.vertices.len()).filter(|&vertex| mesh.normals[vertex] == Vec3::zeros()).collect();
+    if !missing.is_empty() {
+        let by_position: Vec<u32> = mesh.indices.iter().map(|&vertex| position_of[vertex as usize] as u32).collect();
+        let smooth = smooth_normals(&positions, &by_position);
+        for &vertex in &missing {
+            mesh.normals[vertex] = smooth[position_of[vertex]];
+        }
+        mesh.computed_normals = missing.len();
+    }
+    Ok(mesh)
+}
+
+// ============= STL =============
+
+fn parse_stl(bytes: &[u8]) -> Result<Mesh, MeshError> {
+    // El binario tiene 80 bytes de cabecera, el número de triángulos y 50 bytes por triángulo.
+    // Muchos binarios también empiezan por "solid", así que decide el tamaño
+    if bytes.len() >= 84 {
+        let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+        if bytes.len() == 84 + count * 50 {
+            return Ok(parse_stl_binary(&bytes[84..], count));
+        }
+    }
+    let text = std::str::from_utf8(bytes).map_err(|_| MeshError::Stl("no es ASCII y el tamaño no cuadra con un binario".to_string()))?;
+    if !text.trim_start().starts_with("solid") {
+        return Err(MeshError::Stl("no empieza por 'solid' ni tiene el tamaño de un binario".to_string()));
+    }
+    parse_stl_ascii(text)
+}
+
+fn parse_stl_binary(data: &[u8], count: usize) -> Mesh {
+    let mut triangles = Vec::with_capacity(count);
+    for record in data.chunks_exact(50) {
+        let float = |index: usize| f32::from_le_bytes(record[index * 4..index * 4 + 4].try_into().unwrap());
+        let normal = to_scene(float(0), float(1), float(2));
+        let corners = [3, 6, 9].map(|start| to_scene(float(start), float(start + 1), float(start + 2)));
+        triangles.push((normal, corners));
+    }
+    stl_mesh(triangles)
+}
+
+fn parse_stl_ascii(text: &str) -> Result<Mesh, MeshError> {
+    let mut triangles = Vec::new();
+    let mut normal = Vec3::zeros();
+    let mut corners: Vec<Vec3> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        let error = |message: &str| MeshError::Stl(format!("línea {}: {}", index + 1, message));
+        let vector = |words: &mut std::str::SplitWhitespace| -> Result<Vec3, MeshError> {
+            let mut next = || words.next().and_then(|word| word.parse::<f32>().ok()).ok_or_else(|| error("se esperaban tres números"));
+            Ok(to_scene(next()?, next()?, next()?))
+        };
+        match words.next() {
+            Some("facet") => {
+                words.next(); // "normal"
+                normal = vector(&mut words)?;
+                corners.clear();
+            }
+            Some("vertex") => corners.push(vector(&mut words)?),
+            // Las facetas con más de tres vértices se triangulan en abanico
+            Some("endfacet") => {
+                if corners.len() < 3 {
+                    return Err(error("faceta con menos de tres vértices"));
+                }
+                for i in 1..corners.len() - 1 {
+                    triangles.push((normal, [corners[0], corners[i], corners[i + 1]]));
+                }
+            }
+            _ => {}
+        }
+    }
+    if triangles.is_empty() {
+        return Err(MeshError::Stl("no contiene ninguna faceta".to_string()));
+    }
+    Ok(stl_mesh(triangles))
+}
+
+// Tres vértices propios por triángulo, todos con la normal de la faceta
+fn stl_mesh(triangles: Vec<(Vec3, [Vec3; 3])>) -> Mesh {
+    let mut mesh = Mesh::new();
+    for (normal, [a, b, c]) in triangles {
+        let normal = if normal.norm() > 0.5 { normal.normalize() } else { face_normal(a, b, c) };
+        for corner in [a, b, c] {
+            mesh.indices.push(mesh.vertices.len() as u32);
+            mesh.vertices.push(corner);
+            mesh.normals.push(normal);
+        }
+    }
+    mesh
+}
+
+// ============= PLY =============
+
+#[derive(Clone, Copy)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" | "int8" => ScalarType::I8,
+            "uchar" | "uint8" => ScalarType::U8,
+            "short" | "int16" => ScalarType::I16,
+            "ushort" | "uint16" => ScalarType::U16,
+            "int" | "int32" => ScalarType::I32,
+            "uint" | "uint32" => ScalarType::U32,
+            "float" | "float32" => ScalarType::F32,
+            "double" | "float64" => ScalarType::F64,
+            _ => return None,
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            ScalarType::I8 | ScalarType::U8 => 1,
+            ScalarType::I16 | ScalarType::U16 => 2,
+            ScalarType::I32 | ScalarType::U32 | ScalarType::F32 => 4,
+            ScalarType::F64 => 8,
+        }
+    }
+}
+
+enum PropertyKind {
+    Scalar(ScalarType),
+    List { count: ScalarType, item: ScalarType },
+}
+
+struct Property {
+    name: String,
+    kind: PropertyKind,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+// Lee los valores del cuerpo del archivo en el orden de la cabecera
+enum PlyReader<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary { data: &'a [u8], position: usize, big_endian: bool },
+}
+
+impl PlyReader<'_> {
+    fn read(&mut self, kind: ScalarType) -> Result<f64, MeshError> {
+        match self {
+            PlyReader::Ascii(words) => words
+                .next()
+                .and_then(|word| word.parse::<f64>().ok())
+                .ok_or_else(|| MeshError::Ply("faltan valores o hay un número mal escrito".to_string())),
+            PlyReader::Binary { data, position, big_endian } => {
+                let size = kind.size();
+                let mut raw = [0u8; 8];
+                let value = data
+                    .get(*position..*position + size)
+                    .ok_or_else(|| MeshError::Ply("el archivo termina antes de tiempo".to_string()))?;
+                raw[..size].copy_from_slice(value);
+                *position += size;
+                if *big_endian {
+                    raw[..size].reverse();
+                }
+                Ok(match kind {
+                    ScalarType::I8 => raw[0] as i8 as f64,
+                    ScalarType::U8 => raw[0] as f64,
+                    ScalarType::I16 => i16::from_le_bytes([raw[0], raw[1]]) as f64,
+                    ScalarType::U16 => u16::from_le_bytes([raw[0], raw[1]]) as f64,
+                    ScalarType::I32 => i32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64,
+                    ScalarType::U32 => u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64,
+                    ScalarType::F32 => f32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64,
+                    ScalarType::F64 => f64::from_le_bytes(raw),
+                })
+            }
+        }
+    }
+}
+
+fn parse_ply(bytes: &[u8]) -> Result<Mesh, MeshError> {
+    let error = |message: &str| MeshError::Ply(message.to_string());
+    if !bytes.starts_with(b"ply") {
+        return Err(error("falta la firma 'ply'"));
+    }
+    // La cabecera es texto y termina en la línea "end_header"
+    let marker = bytes.windows(10).position(|window| window == b"end_header").ok_or_else(|| error("falta 'end_header'"))?;
+    let body_start = bytes[marker..].iter().position(|&byte| byte == b'\n').map_or(bytes.len(), |offset| marker + offset + 1);
+    let header = std::str::from_utf8(&bytes[..marker]).map_err(|_| error("cabecera no válida"))?;
+
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    for line in header.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["format", name, ..] => format = Some(name.to_string()),
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| error("número de elementos no válido"))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, item, name] => {
+                let kind = match (ScalarType::parse(count), ScalarType::parse(item)) {
+                    (Some(count), Some(item)) => PropertyKind::List { count, item },
+                    _ => return Err(MeshError::Ply(format!("tipo de lista desconocido en '{}'", line.trim()))),
+                };
+                let element = elements.last_mut().ok_or_else(|| error("propiedad antes de cualquier elemento"))?;
+                element.properties.push(Property { name: name.to_string(), kind });
+            }
+            ["property", kind, name] => {
+                let kind = ScalarType::parse(kind).ok_or_else(|| MeshError::Ply(format!("tipo desconocido '{}'", kind)))?;
+                let element = elements.last_mut().ok_or_else(|| error("propiedad antes de cualquier elemento"))?;
+                element.properties.push(Property { name: name.to_string(), kind: PropertyKind::Scalar(kind) });
+            }
+            _ => {} // "ply", comentarios, obj_info
+        }
+    }
+
+    let data = &bytes[body_start..];
+    let mut reader = match format.as_deref() {
+        Some("ascii") => PlyReader::Ascii(std::str::from_utf8(data).map_err(|_| error("datos ASCII no válidos"))?.split_ascii_whitespace()),
+        Some("binary_little_endian") => PlyReader::Binary { data, position: 0, big_endian: false },
+        Some("binary_big_endian") => PlyReader::Binary { data, position: 0, big_endian: true },
+        Some(other) => return Err(MeshError::Ply(format!("formato '{}' no soportado", other))),
+        None => return Err(error("falta la línea 'format'")),
+    };
+
+    let mut mesh = Mesh::new();
+    for element in &elements {
+        let named = |names: &[&str]| element.properties.iter().position(|property| names.contains(&property.name.as_str()));
+        let position = [named(&["x"]), named(&["y"]), named(&["z"])];
+        let normal = [named(&["nx"]), named(&["ny"]), named(&["nz"])];
+        let texcoord = [named(&["u", "s", "texture_u"]), named(&["v", "t", "texture_v"])];
+        let face = named(&["vertex_indices", "vertex_index"]);
+
+        for _ in 0..element.count {
+            let mut values = vec![0.0f64; element.properties.len()];
+            let mut list = Vec::new();
+            for (index, property) in element.properties.iter().enumerate() {
+                match property.kind {
+                    PropertyKind::Scalar(kind) => values[index] = reader.read(kind)?,
+                    PropertyKind::List { count, item } => {
+                        let length = reader.read(count)? as usize;
+                        let items = (0..length).map(|_| reader.read(item)).collect::<Result<Vec<_>, _>>()?;
+                        if Some(index) == face {
+                            list = items;
+                        }
+                    }
+                }
+            }
+
+            match element.name.as_str() {
+                "vertex" => {
+                    let [Some(x), Some(y), Some(z)] = position else {
+                        return Err(error("los vértices necesitan x, y y z"));
+                    };
+                    mesh.vertices.push(to_scene(values[x] as f32, values[y] as f32, values[z] as f32));
+                    if let [Some(x), Some(y), Some(z)] = normal {
+                        mesh.normals.push(to_scene(values[x] as f32, values[y] as f32, values[z] as f32));
+                    }
+                    if let [Some(u), Some(v)] = texcoord {
+                        mesh.texcoords.push(Vec2::new(values[u] as f32, 1.0 - values[v] as f32));
+                    }
+                }
+                "face" => {
+                    if list.len() < 3 {
+                        return Err(error("cara con menos de tres vértices"));
+                    }
+                    for i in 1..list.len() - 1 {
+                        mesh.indices.extend([list[0], list[i], list[i + 1]].map(|index| index as u32));
+                    }
+                }
+                _ => {} // otros elementos (aristas, materiales...) se leen y se ignoran
+            }
+        }
+    }
+
+    if mesh.indices.is_empty() {
+        return Err(error("no contiene ninguna cara"));
+    }
+    if let Some(&index) = mesh.indices.iter().find(|&&index| index as usize >= mesh.vertices.len()) {
+        return Err(MeshError::Ply(format!("la cara usa el vértice {} y solo hay {}", index, mesh.vertices.len())));
+    }
+    if mesh.normals.len() != mesh.vertices.len() {
+        mesh.normals = smooth_normals(&mesh.vertices, &mesh.indices);
+        mesh.computed_normals = mesh.vertices.len();
+    }
+    Ok(mesh)
+}
+
+// Normales por vértice: suma de las normales (sin normalizar, pesan por área) de sus caras
+fn smooth_normals(vertices: &[Vec3], indices: &[u32]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::zeros(); vertices.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| index as usize);
+        let normal = (vertices[b] - vertices[a]).cross(&(vertices[c] - vertices[a]));
+        for index in [a, b, c] {
+            normals[index] += normal;
+        }
+    }
+    normals
+        .into_iter()
+        .map(|normal| if normal.norm() > 0.0 { normal.normalize() } else { Vec3::new(0.0, 1.0, 0.0) })
+        .collect()
+}
@@ -0,0 +1,86 @@
+use rayon::prelude::*;
+use crate::framebuffer::{Framebuffer, NO_OBJECT};
+
+// ============= CONTORNO DE LA SELECCIÓN =============
+// El cuerpo seleccionado (o la nave) se rodea con una línea de color para encontrarlo en una
+// escena llena. Se detecta en el canal de objetos del G-buffer (ver gbuffer.rs): un píxel es
+// del contorno si no es del objeto pero tiene alguno del objeto a menos de WIDTH píxeles. Así
+// la línea sigue la silueta visible, también donde otro cuerpo tapa parte del seleccionado.
+// Se dibuja en la pantalla, a su resolución, leyendo los objetos de la escena (que puede
+// tener otra); el grosor no cambia con el supersampling.
+
+const WIDTH: usize = 2; // píxeles de la ventana
+
+// Contorno de `object` en `screen` según los objetos de `scene`
+pub fn draw(screen: &mut Framebuffer, scene: &Framebuffer, object: u32, color: u32) {
+    if object == NO_OBJECT || !scene.channels().object_id {
+        return;
+    }
+    let (width, height) = (screen.width, screen.height);
+    let mask: Vec<bool> = (0..width * height)
+        .into_par_iter()
+        .map(|index| {
+            let (x, y) = (index % width, index / width);
+            scene.object_id(x * scene.width / width, y * scene.height / height) == object
+        })
+        .collect();
+
+    // Solo las filas cerca del objeto (casi siempre una parte pequeña de la pantalla)
+    let Some(top) = mask.chunks(width).position(|row| row.contains(&true)) else {
+        return;
+    };
+    let bottom = mask.chunks(width).rposition(|row| row.contains(&true)).unwrap_or(top);
+    let first = top.saturating_sub(WIDTH);
+    let last = (bottom + WIDTH).min(height - 1);
+
+    screen.buffer[first * width..(last + 1) * width]
+        .par_chunks_mut(width)
+        .enumerate()
+        .for_each(|(offset, row)| {
+            let y = first + offset;
+            let rows = y.saturating_sub(WIDTH)..(y + WIDTH + 1).min(height);
+            for (x, pixel) in row.iter_mut().enumerate() {
+                if mask[y * width + x] {
+                    continue;
+                }
+                let columns = x.saturating_sub(WIDTH)..(x + WIDTH + 1).min(width);
+                if rows.clone().any(|near| mask[near * width + columns.start..near * width + columns.end].contains(&true)) {
+                    *pixel = color;
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::{Channels, Surface};
+    use nalgebra_glm::Vec3;
+
+    #[test]
+    fn outline_surrounds_the_object_at_screen_resolution() {
+        // Escena a doble resolución con un cuadrado del objeto 5 en el centro
+        let mut scene = Framebuffer::new(40, 40);
+        scene.set_channels(Channels { object_id: true, ..Channels::default() });
+        let surface = Surface { object: 5, normal: Vec3::z(), world_position: Vec3::zeros() };
+        for y in 16..24 {
+            for x in 16..24 {
+                scene.set_surface(x, y, &surface);
+            }
+        }
+        let mut screen = Framebuffer::new(20, 20);
+        draw(&mut screen, &scene, 5, 0xFFD040);
+
+        // El objeto (8..12 en pantalla) queda intacto y lo rodea una línea de WIDTH píxeles
+        assert_eq!(screen.buffer[10 * 20 + 10], 0);
+        assert_eq!(screen.buffer[10 * 20 + 7], 0xFFD040);
+        assert_eq!(screen.buffer[10 * 20 + 6], 0xFFD040);
+        assert_eq!(screen.buffer[10 * 20 + 5], 0);
+        assert_eq!(screen.buffer[0], 0);
+
+        // Otro objeto no tiene contorno
+        let mut untouched = Framebuffer::new(20, 20);
+        draw(&mut untouched, &scene, 6, 0xFFD040);
+        assert!(untouched.buffer.iter().all(|&pixel| pixel == 0));
+    }
+}
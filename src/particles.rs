@@ -0,0 +1,184 @@
+use nalgebra_glm::Vec3;
+use rayon::prelude::*;
+use crate::billboard::{draw_billboards, Billboard, SpriteCamera};
+use crate::draw_state::DrawState;
+use crate::framebuffer::Framebuffer;
+use crate::procedural::SeededRng;
+
+// ============= SISTEMA DE PARTÍCULAS =============
+// Un emisor suelta partículas a un ritmo fijo (fracciones incluidas: el resto se acumula para
+// el frame siguiente), cada una con su vida, velocidad y tamaño sorteados dentro de los rangos
+// de su EmitterConfig. En cada paso se integran con la aceleración que pida el llamador
+// (gravedad, presión de radiación...) y las que agotan su vida desaparecen. Se dibujan como
+// billboards con mezcla aditiva: el color recorre la rampa de la configuración a lo largo de
+// la vida y termina en negro, que al sumarse no cambia nada.
+// Con muchas partículas la integración y la preparación de los billboards van en paralelo.
+
+// Por debajo de este número de partículas rayon no compensa
+const PARALLEL_THRESHOLD: usize = 2048;
+
+// Suma saturada sin escribir profundidad: las partículas se tapan con los cuerpos pero no entre sí
+pub const PARTICLE_STATE: DrawState = DrawState::ADDITIVE;
+
+#[derive(Clone, Copy, Debug)]
+pub struct EmitterConfig {
+    pub spawn_rate: f32,                // partículas por segundo
+    pub max_particles: usize,
+    pub lifetime: (f32, f32),           // segundos, mínimo y máximo
+    pub speed: (f32, f32),              // unidades por segundo a lo largo de la dirección de emisión
+    pub spread: f32,                    // semiángulo del cono de emisión (radianes)
+    pub size: (f32, f32),               // radio al nacer y al morir (unidades del mundo)
+    pub colors: &'static [(f32, u32)],  // rampa de color: (fracción de la vida, color)
+    pub max_pixels: f32,                // radio máximo en pantalla
+}
+
+// Dónde y hacia dónde sale una partícula. `velocity` es la del propio emisor (la partícula la
+// hereda) y `speed_scale` multiplica la rapidez sorteada en el cono
+pub struct Emission {
+    pub position: Vec3,
+    pub direction: Vec3,
+    pub velocity: Vec3,
+    pub speed_scale: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Particle {
+    position: Vec3,
+    velocity: Vec3,
+    age: f32,
+    lifetime: f32,
+}
+
+pub struct ParticleSystem {
+    config: EmitterConfig,
+    particles: Vec<Particle>,
+    pending: f32, // partículas por emitir acumuladas de frames anteriores
+    rng: SeededRng,
+    pub intensity: f32, // brillo de todo el sistema (p. ej. la actividad de un cometa)
+}
+
+impl ParticleSystem {
+    pub fn new(config: EmitterConfig, seed: u64) -> Self {
+        ParticleSystem {
+            config,
+            particles: Vec::with_capacity(config.max_particles),
+            pending: 0.0,
+            rng: SeededRng::new(seed),
+            intensity: 1.0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.pending = 0.0;
+    }
+
+    // Suelta las partículas que tocan en `dt` segundos (el ritmo se multiplica por `rate_scale`);
+    // `emission` decide el origen de cada una. Las nuevas ya avanzan la parte del paso que
+    // les corresponde para que no salgan todas apiladas en el mismo punto
+    pub fn emit(&mut self, dt: f32, rate_scale: f32, mut emission: impl FnMut(&mut SeededRng) -> Emission) {
+        self.pending += self.config.spawn_rate * rate_scale.max(0.0) * dt;
+        let count = self.pending.floor() as usize;
+        self.pending -= count as f32;
+
+        for i in 0..count {
+            if self.particles.len() >= self.config.max_particles {
+                self.pending = 0.0;
+                break;
+            }
+            let Emission { position, direction, velocity, speed_scale } = emission(&mut self.rng);
+            let direction = cone_direction(&mut self.rng, direction, self.config.spread);
+            let speed = self.rng.range(self.config.speed.0, self.config.speed.1) * speed_scale;
+            // El emisor también se movía durante el paso: respecto a él la partícula solo ha
+            // avanzado lo que le da el cono
+            let head_start = dt * (i as f32 + self.rng.next_f32()) / count as f32;
+            self.particles.push(Particle {
+                position: position + direction * speed * head_start,
+                velocity: velocity + direction * speed,
+                age: head_start,
+                lifetime: self.rng.range(self.config.lifetime.0, self.config.lifetime.1),
+            });
+        }
+    }
+
+    // Integra un paso con la aceleración que devuelve `acceleration` en la posición de cada partícula
+    pub fn update(&mut self, dt: f32, acceleration: impl Fn(Vec3) -> Vec3 + Sync) {
+        let step = |particle: &mut Particle| {
+            particle.velocity += acceleration(particle.position) * dt;
+            particle.position += particle.velocity * dt;
+            particle.age += dt;
+        };
+        if self.particles.len() >= PARALLEL_THRESHOLD {
+            self.particles.par_iter_mut().for_each(step);
+        } else {
+            self.particles.iter_mut().for_each(step);
+        }
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+    }
+
+    pub fn draw(&self, framebuffer: &mut Framebuffer, camera: &SpriteCamera) {
+        if self.particles.is_empty() || self.intensity <= 0.0 {
+            return;
+        }
+        let sprite = |particle: &Particle| {
+            let life = (particle.age / particle.lifetime).clamp(0.0, 1.0);
+            let radius = self.config.size.0 + (self.config.size.1 - self.config.size.0) * life;
+            Billboard::new(particle.position, radius, sample_ramp(self.config.colors, life))
+                .with_intensity(self.intensity)
+                .with_pixel_range(1.0, self.config.max_pixels)
+        };
+        let sprites: Vec<Billboard> = if self.particles.len() >= PARALLEL_THRESHOLD {
+            self.particles.par_iter().map(sprite).collect()
+        } else {
+            self.particles.iter().map(sprite).collect()
+        };
+        draw_billboards(framebuffer, camera, &PARTICLE_STATE, &sprites);
+    }
+}
+
+// Dirección aleatoria dentro de un cono alrededor de `axis` (uniforme en el ángulo sólido)
+fn cone_direction(rng: &mut SeededRng, axis: Vec3, spread: f32) -> Vec3 {
+    let axis = axis.try_normalize(1.0e-6).unwrap_or_else(|| Vec3::new(0.0, 1.0, 0.0));
+    if spread <= 0.0 {
+        return axis;
+    }
+    let helper = if axis.x.abs() < 0.9 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+    let tangent = axis.cross(&helper).normalize();
+    let bitangent = axis.cross(&tangent);
+
+    let cos_theta = 1.0 - rng.next_f32() * (1.0 - spread.min(std::f32::consts::PI).cos());
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let (sin_phi, cos_phi) = (rng.next_f32() * std::f32::consts::TAU).sin_cos();
+    axis * cos_theta + (tangent * cos_phi + bitangent * sin_phi) * sin_theta
+}
+
+// Color de la rampa en `t`, interpolando entre las dos claves vecinas
+fn sample_ramp(ramp: &[(f32, u32)], t: f32) -> u32 {
+    let Some(&(_, first)) = ramp.first() else {
+        return 0;
+    };
+    let mut previous = (0.0, first);
+    for &(key, color) in ramp {
+        if t <= key {
+            let span = key - previous.0;
+            let mix = if span > 0.0 { (t - previous.0) / span } else { 1.0 };
+            return lerp_color(previous.1, color, mix);
+        }
+        previous = (key, color);
+    }
+    previous.1
+}
+
+fn lerp_color(a: u32, b: u32, t: f32) -> u32 {
+    let channel = |shift: u32| {
+        let from = ((a >> shift) & 0xFF) as f32;
+        let to = ((b >> shift) & 0xFF) as f32;
+        ((from + (to - from) * t).round() as u32) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
+
+// Dirección uniforme en la esfera (puntos al azar sobre la superficie de un cuerpo)
+pub fn random_unit_vector(rng: &mut SeededRng) -> Vec3 {
+    cone_direction(rng, Vec3::new(0.0, 1.0, 0.0), std::f32::consts::PI)
+}
@@ -0,0 +1,135 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use serde::Deserialize;
+use crate::watchdog::{Quality, QUALITIES};
+
+// ============= HISTORIAL DE RENDIMIENTO =============
+// Al cerrar la aplicación se añade al historial una fila por cada calidad usada en la sesión
+// con su tiempo medio de frame (la calidad puede cambiar a mitad de sesión si el vigilante la
+// baja). Al arrancar se elige la mejor calidad que en esta máquina ha cumplido el objetivo de
+// tiempo de frame; una calidad de la que aún no hay datos se prueba, y si va lenta el vigilante
+// la bajará y la sesión quedará registrada para la próxima vez. Las filas de otra máquina (con
+// otro número de hilos) se ignoran. Se configura en [performance]; `quality` fija la calidad.
+//
+// Formato del archivo, una fila por sesión y calidad: calidad,frames,media_ms,hilos
+
+const WARMUP_FRAMES: u32 = 30; // los primeros frames pagan la carga de la escena
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PerformanceConfig {
+    pub quality: Option<Quality>,  // sin valor se elige con el historial
+    pub history: bool,             // registrar las sesiones y usar el historial
+    pub history_file: String,
+    pub target_frame_ms: f32,      // tiempo medio de frame aceptable (incluida la espera)
+}
+
+impl Default for PerformanceConfig {
+    fn default() -> Self {
+        PerformanceConfig {
+            quality: None,
+            history: true,
+            history_file: "perf_history.csv".to_string(),
+            target_frame_ms: 40.0,
+        }
+    }
+}
+
+// Frames y milisegundos acumulados por calidad
+#[derive(Default, Clone, Copy)]
+struct Totals {
+    frames: u32,
+    total_ms: f64,
+}
+
+impl Totals {
+    fn average(self) -> Option<f32> {
+        (self.frames > 0).then(|| (self.total_ms / self.frames as f64) as f32)
+    }
+}
+
+// Calidad de partida: la fijada en los ajustes o la mejor que ha ido bien en esta máquina
+pub fn choose_quality(config: &PerformanceConfig) -> Quality {
+    if let Some(quality) = config.quality {
+        return quality;
+    }
+    if !config.history {
+        return Quality::High;
+    }
+    let history = match load(&config.history_file) {
+        Ok(history) => history,
+        Err(err) => {
+            eprintln!("Historial de rendimiento: no se pudo leer {} ({})", config.history_file, err);
+            return Quality::High;
+        }
+    };
+    QUALITIES
+        .into_iter()
+        .find(|quality| history[*quality as usize].average().is_none_or(|ms| ms <= config.target_frame_ms))
+        .unwrap_or(Quality::Minimum)
+}
+
+fn load(path: &str) -> io::Result<[Totals; QUALITIES.len()]> {
+    let mut totals = [Totals::default(); QUALITIES.len()];
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(totals),
+        Err(err) => return Err(err),
+    };
+    let threads = rayon::current_num_threads();
+    for line in contents.lines() {
+        let fields: Vec<&str> = line.trim().split(',').collect();
+        let [name, frames, average, row_threads] = fields[..] else {
+            continue;
+        };
+        let Some(quality) = QUALITIES.into_iter().find(|quality| quality.name() == name) else {
+            continue;
+        };
+        let (Ok(frames), Ok(average), Ok(row_threads)) = (frames.parse::<u32>(), average.parse::<f64>(), row_threads.parse::<usize>()) else {
+            continue;
+        };
+        if row_threads != threads {
+            continue;
+        }
+        let slot = &mut totals[quality as usize];
+        slot.frames += frames;
+        slot.total_ms += average * frames as f64;
+    }
+    Ok(totals)
+}
+
+// Tiempos de frame de la sesión en curso
+#[derive(Default)]
+pub struct PerfSession {
+    totals: [Totals; QUALITIES.len()],
+    seen: u32,
+}
+
+impl PerfSession {
+    pub fn record(&mut self, quality: Quality, frame_ms: f32) {
+        self.seen += 1;
+        if self.seen <= WARMUP_FRAMES {
+            return;
+        }
+        let slot = &mut self.totals[quality as usize];
+        slot.frames += 1;
+        slot.total_ms += frame_ms as f64;
+    }
+
+    // Añade las filas de la sesión al historial
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let rows: Vec<String> = QUALITIES
+            .into_iter()
+            .filter_map(|quality| {
+                let totals = self.totals[quality as usize];
+                let average = totals.average()?;
+                Some(format!("{},{},{:.3},{}\n", quality.name(), totals.frames, average, rayon::current_num_threads()))
+            })
+            .collect();
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(rows.concat().as_bytes())
+    }
+}
@@ -0,0 +1,132 @@
+use nalgebra_glm::{Mat4, Vec3};
+use crate::framebuffer::{Framebuffer, NO_OBJECT};
+use crate::{project_to_screen, CelestialObject};
+
+// ============= PICKING Y GEOMETRÍA DE PANTALLA =============
+
+// Disco proyectado de un cuerpo: centro en pantalla (z = profundidad) y radio en píxeles
+pub fn screen_disk(
+    obj: &CelestialObject,
+    view_matrix: &Mat4,
+    projection_matrix: &Mat4,
+    viewport_matrix: &Mat4,
+) -> Option<(Vec3, f32)> {
+    let center = project_to_screen(obj.transform.translation, view_matrix, projection_matrix, viewport_matrix)?;
+
+    // Radio: proyectar un punto del borde en la dirección "derecha" de la cámara
+    let camera_right = Vec3::new(view_matrix[(0, 0)], view_matrix[(0, 1)], view_matrix[(0, 2)]);
+    let edge = project_to_screen(obj.transform.translation + camera_right * obj.transform.scale, view_matrix, projection_matrix, viewport_matrix)?;
+    let radius = (edge.x - center.x).hypot(edge.y - center.y);
+
+    Some((center, radius))
+}
+
+// Cuerpo bajo el cursor (el más cercano a la cámara si se solapan varios: mayor z invertida)
+pub fn pick_body(
+    objects: &[&CelestialObject],
+    x: f32,
+    y: f32,
+    view_matrix: &Mat4,
+    projection_matrix: &Mat4,
+    viewport_matrix: &Mat4,
+) -> Option<usize> {
+    // Margen mínimo para poder acertar a cuerpos que se ven muy pequeños
+    const MIN_PICK_RADIUS: f32 = 8.0;
+
+    objects
+        .iter()
+        .enumerate()
+        .filter_map(|(index, obj)| {
+            let (center, radius) = screen_disk(obj, view_matrix, projection_matrix, viewport_matrix)?;
+            let distance = (x - center.x).hypot(y - center.y);
+            (distance <= radius.max(MIN_PICK_RADIUS)).then_some((index, center.z))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(index, _)| index)
+}
+
+// Objeto del G-buffer de cada cuerpo de la escena (su índice más uno: el 0 es NO_OBJECT)
+pub fn body_object(index: usize) -> u32 {
+    index as u32 + 1
+}
+
+// La nave del vuelo libre, lejos de cualquier índice de cuerpo
+pub const SHIP_OBJECT: u32 = u32::MAX;
+
+// Lo que se puede seleccionar con el ratón
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Target {
+    Body(usize),
+    Ship,
+}
+
+impl Target {
+    // Objeto del G-buffer del objetivo (para su contorno)
+    pub fn object(self) -> u32 {
+        match self {
+            Target::Body(index) => body_object(index),
+            Target::Ship => SHIP_OBJECT,
+        }
+    }
+}
+
+// Lo que quedó delante en el píxel de la ventana (x, y) según el canal de objetos de la escena
+// dibujada; None donde no había nada (el clic cae entonces en los discos de pick_body)
+pub fn pick_object(scene: &Framebuffer, x: f32, y: f32, window_width: usize, window_height: usize) -> Option<Target> {
+    if x < 0.0 || y < 0.0 || window_width == 0 || window_height == 0 {
+        return None;
+    }
+    // La escena puede tener otra resolución que la ventana
+    let scene_x = (x as usize * scene.width / window_width).min(scene.width.saturating_sub(1));
+    let scene_y = (y as usize * scene.height / window_height).min(scene.height.saturating_sub(1));
+    match scene.object_id(scene_x, scene_y) {
+        NO_OBJECT => None,
+        SHIP_OBJECT => Some(Target::Ship),
+        object => Some(Target::Body(object as usize - 1)),
+    }
+}
+
+// Fracción del disco de `source` tapada por `occluder` vista desde `eye`, relativa a la
+// máxima posible (un cuerpo pequeño nunca tapa del todo a uno grande): 1 = eclipse perfecto
+pub fn eclipse_alignment(eye: Vec3, occluder: &CelestialObject, source: &CelestialObject) -> f32 {
+    let to_occluder = occluder.transform.translation - eye;
+    let to_source = source.transform.translation - eye;
+    let occluder_distance = to_occluder.magnitude();
+    let source_distance = to_source.magnitude();
+
+    // El ocultador debe estar delante de la fuente y fuera de ambos cuerpos
+    if occluder_distance <= occluder.transform.scale || source_distance <= source.transform.scale || occluder_distance >= source_distance {
+        return 0.0;
+    }
+
+    // Radios angulares y separación entre centros (en radianes)
+    let occluder_radius = (occluder.transform.scale / occluder_distance).asin();
+    let source_radius = (source.transform.scale / source_distance).asin();
+    let separation = to_occluder.normalize().dot(&to_source.normalize()).clamp(-1.0, 1.0).acos();
+
+    let covered = circle_overlap_area(occluder_radius, source_radius, separation);
+    let max_covered = std::f32::consts::PI * occluder_radius.min(source_radius).powi(2);
+
+    if max_covered <= 0.0 {
+        0.0
+    } else {
+        (covered / max_covered).clamp(0.0, 1.0)
+    }
+}
+
+// Área de la intersección de dos círculos de radios r1, r2 con centros a distancia d
+fn circle_overlap_area(r1: f32, r2: f32, d: f32) -> f32 {
+    if d >= r1 + r2 {
+        return 0.0;
+    }
+    if d <= (r1 - r2).abs() {
+        let r = r1.min(r2);
+        return std::f32::consts::PI * r * r;
+    }
+
+    let a1 = ((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1)).clamp(-1.0, 1.0).acos();
+    let a2 = ((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2)).clamp(-1.0, 1.0).acos();
+    let triangle = 0.5 * ((-d + r1 + r2) * (d + r1 - r2) * (d - r1 + r2) * (d + r1 + r2)).max(0.0).sqrt();
+
+    r1 * r1 * a1 + r2 * r2 * a2 - triangle
+}
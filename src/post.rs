@@ -0,0 +1,288 @@
+use rayon::prelude::*;
+use serde::Deserialize;
+use crate::framebuffer::Framebuffer;
+use crate::post_effects::{Bloom, Fxaa, Tonemap, Vignette};
+use crate::retro::{Curvature, Scanlines};
+
+// ============= CADENA DE POSPROCESO =============
+// Efectos que leen una imagen terminada (color y profundidad) y escriben otra. La cadena los
+// ejecuta en orden alternando entre la imagen y un framebuffer auxiliar, así cada efecto solo
+// ve su entrada y su salida y se pueden combinar en cualquier orden. La profundidad no
+// cambia a lo largo de la cadena.
+//
+// La escena lleva su propia cadena en la lista [[post]] del archivo de escena; se aplica a la
+// pantalla después de llevar la escena a la resolución de la ventana (Resample, que también
+// es un efecto) y antes del modo retro y la interfaz:
+//
+// [[post]]
+// effect = "bloom"      # halo alrededor de lo más brillante (threshold, intensity, radius)
+// [[post]]
+// effect = "tonemap"    # curva fílmica (exposure)
+// [[post]]
+// effect = "fxaa"       # suavizado de bordes sobre la imagen final
+// [[post]]
+// effect = "vignette"   # oscurece las esquinas (strength)
+// [[post]]
+// effect = "crt"        # líneas de barrido y curvatura de tubo (scanlines, curvature)
+
+pub trait PostProcess {
+    // `output` ya tiene el tamaño que debe llenar; `input` conserva la profundidad de la escena
+    fn process(&mut self, input: &Framebuffer, output: &mut Framebuffer);
+}
+
+#[derive(Default)]
+pub struct PostChain {
+    effects: Vec<Box<dyn PostProcess>>,
+    scratch: Option<Framebuffer>, // se reserva con la primera imagen
+}
+
+impl PostChain {
+    pub fn new() -> Self {
+        PostChain::default()
+    }
+
+    pub fn with_effect(mut self, effect: impl PostProcess + 'static) -> Self {
+        self.effects.push(Box::new(effect));
+        self
+    }
+
+    // Los efectos, en el orden en que se añadieron, sobre `image` y a su resolución
+    pub fn apply(&mut self, image: &mut Framebuffer) {
+        if self.effects.is_empty() {
+            return;
+        }
+        let mut scratch = self
+            .scratch
+            .take()
+            .filter(|scratch| (scratch.width, scratch.height) == (image.width, image.height))
+            .unwrap_or_else(|| Framebuffer::new(image.width, image.height));
+        for effect in &mut self.effects {
+            effect.process(image, &mut scratch);
+            std::mem::swap(&mut image.buffer, &mut scratch.buffer);
+        }
+        self.scratch = Some(scratch);
+    }
+}
+
+// Lleva la escena al tamaño de la salida: promedia bloques enteros con supersampling y
+// repite píxeles (vecino más cercano) si la escena se dibujó a menos resolución. La
+// profundidad se copia del píxel de la escena que cae en cada uno, para los efectos de después
+pub struct Resample;
+
+impl PostProcess for Resample {
+    fn process(&mut self, input: &Framebuffer, output: &mut Framebuffer) {
+        let (width, height) = (output.width, output.height);
+        if (input.width, input.height) == (width, height) {
+            output.buffer.copy_from_slice(&input.buffer);
+        } else if input.width > width {
+            downsample(input, output);
+        } else {
+            output.buffer.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+                let source_row = (y * input.height / height) * input.width;
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    *pixel = input.buffer[source_row + x * input.width / width];
+                }
+            });
+        }
+        output.zbuffer.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+            let source_row = (y * input.height / height) * input.width;
+            for (x, depth) in row.iter_mut().enumerate() {
+                *depth = input.zbuffer[source_row + x * input.width / width];
+            }
+        });
+    }
+}
+
+// Las filas de salida se calculan en paralelo. Cada una lee solo su franja de scale_y filas
+// de origen, y cada bloque se recorre por rebanadas contiguas a partir de un desplazamiento
+// precalculado (sin comprobar límites píxel a píxel)
+fn downsample(input: &Framebuffer, output: &mut Framebuffer) {
+    let (high_width, low_width) = (input.width, output.width);
+    let scale_x = high_width / low_width;
+    let scale_y = input.height / output.height;
+    let count = (scale_x * scale_y) as u32;
+    if count == 0 {
+        output.buffer.fill(0);
+        return;
+    }
+
+    output.buffer.par_chunks_mut(low_width).enumerate().for_each(|(y, row)| {
+        let band_start = y * scale_y * high_width;
+        let band = &input.buffer[band_start..band_start + scale_y * high_width];
+
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let mut sum = [0u32; 3];
+            // Promediar los píxeles del área correspondiente
+            for dy in 0..scale_y {
+                let start = dy * high_width + x * scale_x;
+                for source in &band[start..start + scale_x] {
+                    sum[0] += (source >> 16) & 0xFF;
+                    sum[1] += (source >> 8) & 0xFF;
+                    sum[2] += source & 0xFF;
+                }
+            }
+            let [r, g, b] = sum.map(|channel| channel / count);
+            *pixel = (r << 16) | (g << 8) | b;
+        }
+    });
+}
+
+// Un efecto de la lista [[post]] de la escena
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "effect", rename_all = "lowercase", deny_unknown_fields)]
+pub enum PostEffectConfig {
+    Bloom {
+        #[serde(default = "default_bloom_threshold")]
+        threshold: f32, // luminancia (0-1) a partir de la cual algo brilla
+        #[serde(default = "default_bloom_intensity")]
+        intensity: f32,
+        #[serde(default = "default_bloom_radius")]
+        radius: usize,  // en píxeles de la ventana
+    },
+    Tonemap {
+        #[serde(default = "default_exposure")]
+        exposure: f32,
+    },
+    Fxaa,
+    Vignette {
+        #[serde(default = "default_vignette_strength")]
+        strength: f32, // oscurecimiento de las esquinas (0-1)
+    },
+    Crt {
+        #[serde(default = "default_scanlines")]
+        scanlines: f32,
+        #[serde(default = "default_curvature")]
+        curvature: f32,
+    },
+}
+
+impl PostEffectConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            PostEffectConfig::Bloom { threshold, intensity, radius } => {
+                if !(0.0..=1.0).contains(&threshold) || intensity < 0.0 || !(1..=64).contains(&radius) {
+                    return Err("bloom: threshold entre 0 y 1, intensity positiva y radius entre 1 y 64".to_string());
+                }
+            }
+            PostEffectConfig::Tonemap { exposure } => {
+                if exposure <= 0.0 {
+                    return Err("tonemap: exposure debe ser positiva".to_string());
+                }
+            }
+            PostEffectConfig::Fxaa => {}
+            PostEffectConfig::Vignette { strength } => {
+                if !(0.0..=1.0).contains(&strength) {
+                    return Err("vignette: strength debe estar entre 0 y 1".to_string());
+                }
+            }
+            PostEffectConfig::Crt { scanlines, curvature } => {
+                if !(0.0..=1.0).contains(&scanlines) || !(0.0..=0.5).contains(&curvature) {
+                    return Err("crt: scanlines entre 0 y 1 y curvature entre 0 y 0.5".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// La cadena de la escena, en el orden de la lista
+pub fn chain(effects: &[PostEffectConfig]) -> PostChain {
+    effects.iter().fold(PostChain::new(), |chain, effect| match *effect {
+        PostEffectConfig::Bloom { threshold, intensity, radius } => chain.with_effect(Bloom::new(threshold, intensity, radius)),
+        PostEffectConfig::Tonemap { exposure } => chain.with_effect(Tonemap::new(exposure)),
+        PostEffectConfig::Fxaa => chain.with_effect(Fxaa),
+        PostEffectConfig::Vignette { strength } => chain.with_effect(Vignette::new(strength)),
+        PostEffectConfig::Crt { scanlines, curvature } => chain
+            .with_effect(Scanlines::new(scanlines, 2))
+            .with_effect(Curvature::new(curvature)),
+    })
+}
+
+fn default_bloom_threshold() -> f32 {
+    0.75
+}
+
+fn default_bloom_intensity() -> f32 {
+    0.6
+}
+
+fn default_bloom_radius() -> usize {
+    8
+}
+
+fn default_exposure() -> f32 {
+    1.0
+}
+
+fn default_vignette_strength() -> f32 {
+    0.4
+}
+
+fn default_scanlines() -> f32 {
+    0.3
+}
+
+fn default_curvature() -> f32 {
+    0.06
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Suma una constante al color (para ver el orden de la cadena)
+    struct Add(u32);
+
+    impl PostProcess for Add {
+        fn process(&mut self, input: &Framebuffer, output: &mut Framebuffer) {
+            for (out, color) in output.buffer.iter_mut().zip(&input.buffer) {
+                *out = color + self.0;
+            }
+        }
+    }
+
+    struct Double;
+
+    impl PostProcess for Double {
+        fn process(&mut self, input: &Framebuffer, output: &mut Framebuffer) {
+            for (out, color) in output.buffer.iter_mut().zip(&input.buffer) {
+                *out = color * 2;
+            }
+        }
+    }
+
+    #[test]
+    fn effects_run_in_order_and_keep_the_depth() {
+        let mut image = Framebuffer::new(4, 2);
+        image.buffer.fill(1);
+        image.zbuffer.fill(0.5);
+        let mut chain = PostChain::new().with_effect(Add(2)).with_effect(Double).with_effect(Add(1));
+        chain.apply(&mut image);
+        assert!(image.buffer.iter().all(|&color| color == 7));
+        assert!(image.zbuffer.iter().all(|&depth| depth == 0.5));
+    }
+
+    #[test]
+    fn resample_averages_down_and_repeats_up() {
+        let mut scene = Framebuffer::new(4, 2);
+        scene.buffer = vec![0x000000, 0x0000FF, 0x000010, 0x000010, 0x0000FF, 0x000000, 0x000010, 0x000010];
+        let mut screen = Framebuffer::new(2, 1);
+        Resample.process(&scene, &mut screen);
+        assert_eq!(screen.buffer, vec![0x00007F, 0x000010]);
+
+        let mut large = Framebuffer::new(4, 2);
+        Resample.process(&screen, &mut large);
+        assert_eq!(large.buffer, vec![0x00007F, 0x00007F, 0x000010, 0x000010, 0x00007F, 0x00007F, 0x000010, 0x000010]);
+    }
+
+    #[test]
+    fn scene_lists_effects_in_order() {
+        #[derive(Deserialize)]
+        struct List {
+            post: Vec<PostEffectConfig>,
+        }
+        let list: List = toml::from_str("[[post]]\neffect = \"fxaa\"\n[[post]]\neffect = \"vignette\"\nstrength = 0.2\n").unwrap();
+        assert_eq!(list.post, vec![PostEffectConfig::Fxaa, PostEffectConfig::Vignette { strength: 0.2 }]);
+        assert!(toml::from_str::<List>("[[post]]\neffect = \"sepia\"\n").is_err());
+    }
+}
@@ -0,0 +1,254 @@
+use rayon::prelude::*;
+use crate::framebuffer::Framebuffer;
+use crate::post::PostProcess;
+
+// ============= EFECTOS DE POSPROCESO =============
+// Los efectos que una escena puede encadenar en su lista [[post]] (ver post.rs): halo de lo
+// brillante, curva de tonos, suavizado de bordes y viñeta. La curvatura y las líneas de
+// barrido del "crt" son las del modo retro (retro.rs).
+
+// Curva fílmica (la aproximación de Narkowicz a ACES) con una exposición delante: realza el
+// contraste de los medios tonos y comprime los brillos sin quemarlos
+pub struct Tonemap {
+    curve: [u8; 256], // la curva no depende del píxel: se calcula una vez por canal
+}
+
+impl Tonemap {
+    pub fn new(exposure: f32) -> Self {
+        let curve = std::array::from_fn(|value| {
+            let x = value as f32 / 255.0 * exposure;
+            let mapped = (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+            (mapped.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+        });
+        Tonemap { curve }
+    }
+}
+
+impl PostProcess for Tonemap {
+    fn process(&mut self, input: &Framebuffer, output: &mut Framebuffer) {
+        let curve = &self.curve;
+        output.buffer.par_iter_mut().zip(input.buffer.par_iter()).for_each(|(pixel, &color)| {
+            let [r, g, b] = channels(color).map(|channel| curve[channel as usize] as u32);
+            *pixel = (r << 16) | (g << 8) | b;
+        });
+    }
+}
+
+// Halo: lo que pasa del umbral de luminancia se desenfoca a media resolución (dos cajas
+// seguidas, que ya se parecen a una gaussiana) y se suma a la imagen
+pub struct Bloom {
+    threshold: f32,
+    intensity: f32,
+    radius: usize,
+    bright: Vec<[f32; 3]>,
+    blurred: Vec<[f32; 3]>,
+}
+
+impl Bloom {
+    pub fn new(threshold: f32, intensity: f32, radius: usize) -> Self {
+        Bloom { threshold, intensity, radius, bright: Vec::new(), blurred: Vec::new() }
+    }
+}
+
+impl PostProcess for Bloom {
+    fn process(&mut self, input: &Framebuffer, output: &mut Framebuffer) {
+        let (width, height) = (input.width, input.height);
+        let (half_width, half_height) = (width.div_ceil(2), height.div_ceil(2));
+        let threshold = self.threshold;
+
+        // Parte brillante, un píxel por cada bloque de 2x2
+        self.bright.resize(half_width * half_height, [0.0; 3]);
+        self.bright.par_chunks_mut(half_width).enumerate().for_each(|(y, row)| {
+            for (x, bright) in row.iter_mut().enumerate() {
+                let color = channels(input.buffer[(y * 2).min(height - 1) * width + (x * 2).min(width - 1)]).map(|channel| channel as f32 / 255.0);
+                let luminance = 0.2126 * color[0] + 0.7152 * color[1] + 0.0722 * color[2];
+                let excess = if luminance > threshold { (luminance - threshold) / luminance } else { 0.0 };
+                *bright = color.map(|channel| channel * excess);
+            }
+        });
+
+        let radius = self.radius.div_ceil(2).max(1);
+        for _ in 0..2 {
+            box_blur(&self.bright, &mut self.blurred, half_width, half_height, radius);
+            std::mem::swap(&mut self.bright, &mut self.blurred);
+        }
+
+        let (glow, intensity) = (&self.bright, self.intensity);
+        output.buffer.par_chunks_mut(width).zip(input.buffer.par_chunks(width)).enumerate().for_each(|(y, (row, source))| {
+            let glow_row = &glow[(y / 2) * half_width..(y / 2 + 1) * half_width];
+            for (x, (pixel, &color)) in row.iter_mut().zip(source).enumerate() {
+                let halo = glow_row[x / 2];
+                let mut mixed = channels(color);
+                for (channel, extra) in mixed.iter_mut().zip(halo) {
+                    *channel = (*channel + (extra * intensity * 255.0) as u32).min(255);
+                }
+                let [r, g, b] = mixed;
+                *pixel = (r << 16) | (g << 8) | b;
+            }
+        });
+    }
+}
+
+// Media de una caja de lado 2 * radius + 1, por filas y luego por columnas (lo que cae fuera
+// de la imagen cuenta como negro, así el halo se desvanece en los bordes)
+fn box_blur(source: &[[f32; 3]], output: &mut Vec<[f32; 3]>, width: usize, height: usize, radius: usize) {
+    let scale = 1.0 / (2 * radius + 1) as f32;
+    let window = |center: usize, length: usize| center.saturating_sub(radius)..(center + radius + 1).min(length);
+    let rows: Vec<[f32; 3]> = (0..width * height)
+        .into_par_iter()
+        .map(|index| {
+            let (x, y) = (index % width, index / width);
+            sum(window(x, width).map(|x| source[y * width + x])).map(|channel| channel * scale)
+        })
+        .collect();
+    output.resize(width * height, [0.0; 3]);
+    output.par_iter_mut().enumerate().for_each(|(index, value)| {
+        let (x, y) = (index % width, index / width);
+        *value = sum(window(y, height).map(|y| rows[y * width + x])).map(|channel| channel * scale);
+    });
+}
+
+fn sum(values: impl Iterator<Item = [f32; 3]>) -> [f32; 3] {
+    values.fold([0.0; 3], |total, value| std::array::from_fn(|channel| total[channel] + value[channel]))
+}
+
+// Suavizado de bordes sobre la imagen terminada, al estilo de FXAA pero reducido: donde el
+// contraste con los cuatro vecinos es alto se mezcla el píxel con el vecino del otro lado del
+// borde, más cuanto más aislado está (sin buscar los extremos del borde como el original)
+pub struct Fxaa;
+
+const FXAA_MIN_CONTRAST: f32 = 0.0312;
+const FXAA_RELATIVE_CONTRAST: f32 = 0.125;
+const FXAA_SUBPIXEL: f32 = 0.75;
+
+impl PostProcess for Fxaa {
+    fn process(&mut self, input: &Framebuffer, output: &mut Framebuffer) {
+        let (width, height) = (input.width, input.height);
+        let luma = |x: usize, y: usize| luminance(input.buffer[y * width + x]);
+        output.buffer.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+            let (up, down) = (y.saturating_sub(1), (y + 1).min(height - 1));
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let color = input.buffer[y * width + x];
+                let (left, right) = (x.saturating_sub(1), (x + 1).min(width - 1));
+                let center = luma(x, y);
+                let [north, south, west, east] = [luma(x, up), luma(x, down), luma(left, y), luma(right, y)];
+                let highest = center.max(north).max(south).max(west).max(east);
+                let lowest = center.min(north).min(south).min(west).min(east);
+                let contrast = highest - lowest;
+                if contrast < FXAA_MIN_CONTRAST.max(FXAA_RELATIVE_CONTRAST * highest) {
+                    *pixel = color;
+                    continue;
+                }
+
+                // Cuánto se sale el píxel de la media de sus vecinos
+                let average = (north + south + west + east) / 4.0;
+                let blend = ((average - center).abs() / contrast).clamp(0.0, 1.0);
+                let blend = blend * blend * FXAA_SUBPIXEL;
+
+                // Borde horizontal si cambia más de arriba abajo: se mezcla con el vecino de
+                // arriba o de abajo, el que más difiere
+                let horizontal = (north + south - 2.0 * center).abs() >= (west + east - 2.0 * center).abs();
+                let neighbour = if horizontal {
+                    if (north - center).abs() >= (south - center).abs() { (x, up) } else { (x, down) }
+                } else if (west - center).abs() >= (east - center).abs() {
+                    (left, y)
+                } else {
+                    (right, y)
+                };
+                *pixel = mix(color, input.buffer[neighbour.1 * width + neighbour.0], blend);
+            }
+        });
+    }
+}
+
+// Oscurece hacia las esquinas con la distancia al centro al cuadrado
+pub struct Vignette {
+    strength: f32,
+}
+
+impl Vignette {
+    pub fn new(strength: f32) -> Self {
+        Vignette { strength }
+    }
+}
+
+impl PostProcess for Vignette {
+    fn process(&mut self, input: &Framebuffer, output: &mut Framebuffer) {
+        let (width, height, strength) = (input.width, input.height, self.strength);
+        output.buffer.par_chunks_mut(width).zip(input.buffer.par_chunks(width)).enumerate().for_each(|(y, (row, source))| {
+            let v = (y as f32 + 0.5) / height as f32 * 2.0 - 1.0;
+            for (x, (pixel, &color)) in row.iter_mut().zip(source).enumerate() {
+                let u = (x as f32 + 0.5) / width as f32 * 2.0 - 1.0;
+                // 0 en el centro y 1 en las esquinas
+                let distance = (u * u + v * v) / 2.0;
+                *pixel = mix(color, 0x000000, strength * distance);
+            }
+        });
+    }
+}
+
+fn channels(color: u32) -> [u32; 3] {
+    [(color >> 16) & 0xFF, (color >> 8) & 0xFF, color & 0xFF]
+}
+
+fn luminance(color: u32) -> f32 {
+    let [r, g, b] = channels(color);
+    (0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32) / 255.0
+}
+
+fn mix(a: u32, b: u32, amount: f32) -> u32 {
+    let (a, b) = (channels(a), channels(b));
+    let [r, g, b] = std::array::from_fn(|i| (a[i] as f32 + (b[i] as f32 - a[i] as f32) * amount + 0.5) as u32);
+    (r << 16) | (g << 8) | b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(effect: &mut impl PostProcess, image: &Framebuffer) -> Framebuffer {
+        let mut output = Framebuffer::new(image.width, image.height);
+        effect.process(image, &mut output);
+        output
+    }
+
+    #[test]
+    fn fxaa_softens_a_staircase_and_keeps_flat_areas() {
+        // Diagonal blanca sobre negro: los escalones son los píxeles aislados
+        let mut image = Framebuffer::new(12, 12);
+        for y in 0..12 {
+            for x in 0..=y {
+                image.buffer[y * 12 + x] = 0xFFFFFF;
+            }
+        }
+        let output = run(&mut Fxaa, &image);
+        assert_eq!(output.buffer[11 * 12], 0xFFFFFF);
+        assert_eq!(output.buffer[11], 0x000000);
+        let softened = output.buffer.iter().filter(|&&pixel| pixel != 0 && pixel != 0xFFFFFF).count();
+        assert!(softened > 0, "la diagonal no se suavizó");
+    }
+
+    #[test]
+    fn bloom_spreads_only_what_is_bright() {
+        let mut image = Framebuffer::new(32, 32);
+        image.buffer.fill(0x202020);
+        image.buffer[16 * 32 + 16] = 0xFFFFFF;
+        let output = run(&mut Bloom::new(0.75, 1.0, 6), &image);
+        assert!(output.buffer[16 * 32 + 19] > 0x202020, "el halo no llegó a los vecinos");
+        assert_eq!(output.buffer[0], 0x202020);
+
+        // Sin nada por encima del umbral la imagen no cambia
+        let mut dim = Framebuffer::new(32, 32);
+        dim.buffer.fill(0x404040);
+        assert_eq!(run(&mut Bloom::new(0.75, 1.0, 6), &dim).buffer, dim.buffer);
+    }
+
+    #[test]
+    fn vignette_darkens_corners_only() {
+        let mut image = Framebuffer::new(40, 20);
+        image.buffer.fill(0xC0C0C0);
+        let output = run(&mut Vignette::new(0.5), &image);
+        assert!(output.buffer[10 * 40 + 20] >= 0xBF0000);
+        assert!(output.buffer[0] < 0x700000);
+    }
+}
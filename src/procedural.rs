@@ -0,0 +1,267 @@
+use std::collections::BTreeMap;
+use serde::Deserialize;
+use crate::color::Color;
+use crate::scene::{BodyMetadata, RingStyle};
+
+// ============= PLANETA PROCEDURAL (SEMILLA u64) =============
+// Todos los parámetros del planeta (océanos, paleta de bioma, nubes, anillos) salen de
+// una única semilla: la misma semilla produce siempre el mismo planeta.
+
+// Generador SplitMix64: pequeño, rápido y con buena distribución para semillas consecutivas
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Valor uniforme en [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[(self.next_u64() % items.len() as u64) as usize]
+    }
+}
+
+// Velocidad orbital según la tercera ley de Kepler (T² ∝ r³), calibrada con la
+// órbita de la Tierra en la escena (radio 250, velocidad 0.35)
+pub fn kepler_orbit_speed(orbit_radius: f32) -> f32 {
+    0.35 * (250.0 / orbit_radius.max(1.0)).powf(1.5)
+}
+
+// Nombre pronunciable derivado de una semilla (planetas, estrellas)
+pub fn generated_name(seed: u64) -> String {
+    const SYLLABLES: [&str; 16] = [
+        "ka", "ze", "ri", "to", "mar", "vel", "dra", "xo", "lun", "the", "qua", "sor", "ni", "bel", "os", "ur",
+    ];
+    let mut rng = SeededRng::new(seed ^ 0xA5A5_A5A5_A5A5_A5A5);
+    let count = 2 + (rng.next_u64() % 2) as usize;
+    let mut name: String = (0..count).map(|_| *rng.pick(&SYLLABLES)).collect();
+    if let Some(first) = name.get_mut(0..1) {
+        first.make_ascii_uppercase();
+    }
+    format!("{}-{}", name, seed % 1000)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Biome {
+    Temperate,
+    Desert,
+    Frozen,
+    Volcanic,
+    Alien,
+    Toxic,
+}
+
+// Colores de un bioma, de lo más profundo a las cumbres
+pub struct BiomePalette {
+    pub deep_ocean: Color,
+    pub shallow_ocean: Color,
+    pub lowland: Color,
+    pub highland: Color,
+    pub peak: Color,
+    pub cloud: Color,
+    pub atmosphere: Color,
+}
+
+impl Biome {
+    const ALL: [Biome; 6] = [
+        Biome::Temperate,
+        Biome::Desert,
+        Biome::Frozen,
+        Biome::Volcanic,
+        Biome::Alien,
+        Biome::Toxic,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Biome::Temperate => "templado",
+            Biome::Desert => "desértico",
+            Biome::Frozen => "helado",
+            Biome::Volcanic => "volcánico",
+            Biome::Alien => "alienígena",
+            Biome::Toxic => "tóxico",
+        }
+    }
+
+    pub fn palette(&self) -> BiomePalette {
+        match self {
+            Biome::Temperate => BiomePalette {
+                deep_ocean: Color::from_float(0.02, 0.06, 0.18),
+                shallow_ocean: Color::from_float(0.06, 0.28, 0.45),
+                lowland: Color::from_float(0.16, 0.40, 0.14),
+                highland: Color::from_float(0.45, 0.40, 0.30),
+                peak: Color::from_float(0.95, 0.95, 0.98),
+                cloud: Color::from_float(0.97, 0.97, 1.0),
+                atmosphere: Color::from_float(0.35, 0.55, 0.95),
+            },
+            Biome::Desert => BiomePalette {
+                deep_ocean: Color::from_float(0.05, 0.12, 0.20),
+                shallow_ocean: Color::from_float(0.15, 0.40, 0.42),
+                lowland: Color::from_float(0.80, 0.62, 0.36),
+                highland: Color::from_float(0.62, 0.38, 0.20),
+                peak: Color::from_float(0.88, 0.78, 0.60),
+                cloud: Color::from_float(0.95, 0.90, 0.80),
+                atmosphere: Color::from_float(0.90, 0.70, 0.45),
+            },
+            Biome::Frozen => BiomePalette {
+                deep_ocean: Color::from_float(0.04, 0.10, 0.22),
+                shallow_ocean: Color::from_float(0.30, 0.55, 0.70),
+                lowland: Color::from_float(0.78, 0.86, 0.92),
+                highland: Color::from_float(0.55, 0.65, 0.75),
+                peak: Color::from_float(1.0, 1.0, 1.0),
+                cloud: Color::from_float(0.92, 0.96, 1.0),
+                atmosphere: Color::from_float(0.60, 0.82, 1.0),
+            },
+            Biome::Volcanic => BiomePalette {
+                deep_ocean: Color::from_float(0.90, 0.25, 0.02),
+                shallow_ocean: Color::from_float(1.0, 0.55, 0.10),
+                lowland: Color::from_float(0.16, 0.12, 0.11),
+                highland: Color::from_float(0.30, 0.22, 0.20),
+                peak: Color::from_float(0.50, 0.45, 0.42),
+                cloud: Color::from_float(0.35, 0.32, 0.30),
+                atmosphere: Color::from_float(0.95, 0.40, 0.15),
+            },
+            Biome::Alien => BiomePalette {
+                deep_ocean: Color::from_float(0.10, 0.02, 0.20),
+                shallow_ocean: Color::from_float(0.30, 0.10, 0.50),
+                lowland: Color::from_float(0.55, 0.15, 0.55),
+                highland: Color::from_float(0.20, 0.60, 0.55),
+                peak: Color::from_float(0.85, 0.95, 0.70),
+                cloud: Color::from_float(0.90, 0.80, 1.0),
+                atmosphere: Color::from_float(0.75, 0.40, 0.95),
+            },
+            Biome::Toxic => BiomePalette {
+                deep_ocean: Color::from_float(0.10, 0.18, 0.04),
+                shallow_ocean: Color::from_float(0.35, 0.50, 0.08),
+                lowland: Color::from_float(0.55, 0.55, 0.20),
+                highland: Color::from_float(0.40, 0.35, 0.18),
+                peak: Color::from_float(0.75, 0.78, 0.45),
+                cloud: Color::from_float(0.80, 0.85, 0.45),
+                atmosphere: Color::from_float(0.70, 0.85, 0.25),
+            },
+        }
+    }
+}
+
+// En la escena: shader = { Procedural = 1234 }
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(from = "u64")]
+pub struct ProceduralPlanet {
+    pub seed: u64,
+    pub biome: Biome,
+    pub ocean_level: f32,      // Umbral de altura bajo el cual hay océano [0, 1]
+    pub cloud_density: f32,    // 0 = cielo despejado, 1 = cubierto
+    pub ice_caps: f32,         // Latitud a partir de la cual hay casquetes (1 = sin casquetes)
+    pub terrain_scale: f32,    // Frecuencia base del relieve
+    pub terrain_offset: [f32; 3], // Desplazamiento en el espacio de ruido (planetas distintos)
+    pub rings: Option<RingStyle>,
+}
+
+impl From<u64> for ProceduralPlanet {
+    fn from(seed: u64) -> Self {
+        ProceduralPlanet::from_seed(seed)
+    }
+}
+
+impl ProceduralPlanet {
+    pub fn from_seed(seed: u64) -> Self {
+        let mut rng = SeededRng::new(seed);
+        let biome = *rng.pick(&Biome::ALL);
+        Self::roll(seed, biome, &mut rng)
+    }
+
+    // Bioma impuesto desde fuera (p. ej. por la temperatura de un exoplaneta real);
+    // el resto de parámetros sigue saliendo de la semilla
+    pub fn with_biome(seed: u64, biome: Biome) -> Self {
+        Self::roll(seed, biome, &mut SeededRng::new(seed))
+    }
+
+    fn roll(seed: u64, biome: Biome, rng: &mut SeededRng) -> Self {
+        // Cada bioma tiene su rango típico de océanos y nubes
+        let (ocean_min, ocean_max) = match biome {
+            Biome::Desert => (0.0, 0.30),
+            Biome::Volcanic => (0.20, 0.45),
+            Biome::Frozen => (0.25, 0.55),
+            _ => (0.35, 0.65),
+        };
+        let ocean_level = rng.range(ocean_min, ocean_max);
+        let cloud_density = match biome {
+            Biome::Desert | Biome::Volcanic => rng.range(0.0, 0.35),
+            Biome::Toxic => rng.range(0.5, 0.9),
+            _ => rng.range(0.15, 0.75),
+        };
+        let ice_caps = match biome {
+            Biome::Frozen => rng.range(0.25, 0.5),
+            Biome::Volcanic => 1.0,
+            _ => rng.range(0.6, 0.95),
+        };
+        let terrain_scale = rng.range(1.5, 4.0);
+        let terrain_offset = [rng.range(-100.0, 100.0), rng.range(-100.0, 100.0), rng.range(-100.0, 100.0)];
+        let rings = if rng.chance(0.3) {
+            Some(if rng.chance(0.5) { RingStyle::Saturn } else { RingStyle::Wide })
+        } else {
+            None
+        };
+
+        ProceduralPlanet {
+            seed,
+            biome,
+            ocean_level,
+            cloud_density,
+            ice_caps,
+            terrain_scale,
+            terrain_offset,
+            rings,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        generated_name(self.seed)
+    }
+
+    // Metadatos para el panel de información y la enciclopedia
+    pub fn metadata(&self) -> BodyMetadata {
+        let mut facts = vec![format!("Océanos: ~{:.0}% de la superficie", self.ocean_level * 100.0)];
+        facts.push(format!("Nubosidad: {:.0}%", self.cloud_density * 100.0));
+        if self.rings.is_some() {
+            facts.push("Tiene un sistema de anillos.".to_string());
+        }
+        if self.ice_caps < 1.0 {
+            facts.push("Casquetes polares de hielo.".to_string());
+        }
+
+        let mut custom = BTreeMap::new();
+        custom.insert("Semilla".to_string(), self.seed.to_string());
+        custom.insert("Bioma".to_string(), self.biome.name().to_string());
+
+        BodyMetadata {
+            name: self.name(),
+            description: format!("Planeta {} generado proceduralmente a partir de una semilla.", self.biome.name()),
+            facts,
+            custom,
+            ..BodyMetadata::default()
+        }
+    }
+}
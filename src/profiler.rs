@@ -0,0 +1,240 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use crate::framebuffer::Framebuffer;
+use crate::text::{draw_text, text_width, GLYPH_SIZE};
+use crate::theme::{self, HudLayout, Widget};
+
+// ============= PERFILADOR POR ETAPAS (F3, --profile archivo.csv) =============
+// Cada etapa del pipeline suma su tiempo de pared en un contador global mientras dura el
+// frame (render() se llama muchas veces por frame: cuerpos, anillos, miniaturas...). Al
+// cerrar el frame los contadores se vacían en un historial: F3 muestra la media de los
+// últimos frames en pantalla y --profile escribe una fila por frame en un CSV.
+
+#[derive(Clone, Copy)]
+pub enum Stage {
+    VertexShading,
+    PrimitiveAssembly,
+    Rasterization,
+    FragmentShading,
+    FramebufferWrite,
+    TemporalResolve,
+    MotionBlur,
+    Downsampling,
+}
+
+pub const STAGES: [Stage; 8] = [
+    Stage::VertexShading,
+    Stage::PrimitiveAssembly,
+    Stage::Rasterization,
+    Stage::FragmentShading,
+    Stage::FramebufferWrite,
+    Stage::TemporalResolve,
+    Stage::MotionBlur,
+    Stage::Downsampling,
+];
+
+impl Stage {
+    fn label(self) -> &'static str {
+        match self {
+            Stage::VertexShading => "Vértices",
+            Stage::PrimitiveAssembly => "Ensamblado",
+            Stage::Rasterization => "Rasterizado",
+            Stage::FragmentShading => "Fragmentos",
+            Stage::FramebufferWrite => "Escritura",
+            Stage::TemporalResolve => "TAA",
+            Stage::MotionBlur => "Estelas",
+            Stage::Downsampling => "Reducción",
+        }
+    }
+
+    pub fn column(self) -> &'static str {
+        match self {
+            Stage::VertexShading => "vertex_ms",
+            Stage::PrimitiveAssembly => "assembly_ms",
+            Stage::Rasterization => "raster_ms",
+            Stage::FragmentShading => "fragment_ms",
+            Stage::FramebufferWrite => "write_ms",
+            Stage::TemporalResolve => "taa_ms",
+            Stage::MotionBlur => "blur_ms",
+            Stage::Downsampling => "downsample_ms",
+        }
+    }
+
+    fn color(self) -> u32 {
+        match self {
+            Stage::VertexShading => 0x4A9BFF,
+            Stage::PrimitiveAssembly => 0x9B6BFF,
+            Stage::Rasterization => 0xFF9B3A,
+            Stage::FragmentShading => 0xFF4A6B,
+            Stage::FramebufferWrite => 0x4AD88A,
+            Stage::TemporalResolve => 0x4AD8D8,
+            Stage::MotionBlur => 0xD84AD8,
+            Stage::Downsampling => 0xE6D24A,
+        }
+    }
+}
+
+static STAGE_NANOS: [AtomicU64; STAGES.len()] = [const { AtomicU64::new(0) }; STAGES.len()];
+static TRIANGLES_SUBMITTED: AtomicU64 = AtomicU64::new(0);
+static TRIANGLES_DRAWN: AtomicU64 = AtomicU64::new(0);
+
+// Ejecuta una etapa y suma su duración al frame en curso
+pub fn measure<T>(stage: Stage, work: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = work();
+    STAGE_NANOS[stage as usize].fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    result
+}
+
+// Triángulos enviados a render() y los que quedan tras descartar las caras traseras
+pub fn count_triangles(submitted: usize, drawn: usize) {
+    TRIANGLES_SUBMITTED.fetch_add(submitted as u64, Ordering::Relaxed);
+    TRIANGLES_DRAWN.fetch_add(drawn as u64, Ordering::Relaxed);
+}
+
+const HISTORY_FRAMES: usize = 60; // la media en pantalla cubre el último segundo
+const BAR_WIDTH: usize = 220;
+
+#[derive(Clone, Copy)]
+pub struct FrameTiming {
+    pub stages: [f32; STAGES.len()], // milisegundos por etapa
+    pub total: f32,                  // frame completo, incluida la espera
+    pub triangles: u64,
+    pub drawn: u64,
+}
+
+pub struct Profiler {
+    visible: bool,
+    history: VecDeque<FrameTiming>,
+    frame_start: Instant,
+    frame: u64,
+    csv: Option<BufWriter<File>>,
+}
+
+impl Profiler {
+    // Sin ruta solo hay cartel; con ruta se crea el CSV y se escribe su cabecera
+    pub fn new(csv_path: Option<&str>) -> io::Result<Self> {
+        let csv = match csv_path {
+            Some(path) => {
+                let mut writer = BufWriter::new(File::create(path)?);
+                let columns: Vec<&str> = STAGES.iter().map(|stage| stage.column()).collect();
+                writeln!(writer, "frame,{},frame_ms,triangles,drawn_triangles", columns.join(","))?;
+                Some(writer)
+            }
+            None => None,
+        };
+
+        Ok(Profiler {
+            visible: false,
+            history: VecDeque::with_capacity(HISTORY_FRAMES),
+            frame_start: Instant::now(),
+            frame: 0,
+            csv,
+        })
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    // Cierra el frame: recoge los contadores, los guarda y los exporta
+    pub fn end_frame(&mut self) {
+        let mut stages = [0.0; STAGES.len()];
+        for (slot, counter) in stages.iter_mut().zip(&STAGE_NANOS) {
+            *slot = counter.swap(0, Ordering::Relaxed) as f32 / 1.0e6;
+        }
+        let total = self.frame_start.elapsed().as_secs_f32() * 1000.0;
+        let triangles = TRIANGLES_SUBMITTED.swap(0, Ordering::Relaxed);
+        let drawn = TRIANGLES_DRAWN.swap(0, Ordering::Relaxed);
+        self.frame_start = Instant::now();
+        self.frame += 1;
+
+        if let Some(writer) = self.csv.as_mut() {
+            let values: Vec<String> = stages.iter().map(|ms| format!("{:.3}", ms)).collect();
+            let row = format!("{},{},{:.3},{},{}", self.frame, values.join(","), total, triangles, drawn);
+            if let Err(err) = writeln!(writer, "{}", row) {
+                eprintln!("Perfilador: no se pudo escribir el CSV ({}), se deja de exportar", err);
+                self.csv = None;
+            }
+        }
+
+        if self.history.len() == HISTORY_FRAMES {
+            self.history.pop_front();
+        }
+        self.history.push_back(FrameTiming { stages, total, triangles, drawn });
+    }
+
+    pub fn last_frame(&self) -> Option<&FrameTiming> {
+        self.history.back()
+    }
+
+    // Cartel (abajo a la derecha por defecto): una barra apilada y la media de cada etapa
+    pub fn draw(&self, screen: &mut Framebuffer, layout: &mut HudLayout) {
+        const PADDING: usize = 10;
+        const LINE_HEIGHT: usize = GLYPH_SIZE + 4;
+        const BAR_HEIGHT: usize = 10;
+
+        if !self.visible || self.history.is_empty() {
+            return;
+        }
+
+        let frames = self.history.len() as f32;
+        let mut averages = [0.0f32; STAGES.len()];
+        for timing in &self.history {
+            for (average, ms) in averages.iter_mut().zip(timing.stages) {
+                *average += ms / frames;
+            }
+        }
+        let total = self.history.iter().map(|timing| timing.total).sum::<f32>() / frames;
+        let measured: f32 = averages.iter().sum();
+
+        let title = format!("Perfil: {:.1} ms/frame ({:.0} FPS)", total, 1000.0 / total.max(0.001));
+        let lines: Vec<String> = STAGES
+            .iter()
+            .zip(averages)
+            .map(|(stage, ms)| format!("{:<12}{:>7.2} ms", stage.label(), ms))
+            .chain(std::iter::once(format!("{:<12}{:>7.2} ms", "Resto", (total - measured).max(0.0))))
+            .collect();
+        let last = self.history.back().map_or((0, 0), |timing| (timing.drawn, timing.triangles));
+        let triangles = format!("Triángulos: {} de {}", last.0, last.1);
+
+        let width = lines
+            .iter()
+            .map(|line| text_width(line, 1) + GLYPH_SIZE * 2)
+            .chain([text_width(&title, 1), text_width(&triangles, 1), BAR_WIDTH])
+            .max()
+            .unwrap_or(0)
+            + PADDING * 2;
+        let height = PADDING * 3 + LINE_HEIGHT + BAR_HEIGHT + (lines.len() + 1) * LINE_HEIGHT;
+        let Some((x, y)) = layout.place(Widget::Profiler, width, height) else {
+            return;
+        };
+        let theme = theme::current();
+        screen.blend_rect(x, y, width, height, theme.panel_background, theme.hud_opacity);
+
+        let text_x = x + PADDING;
+        let mut line_y = y + PADDING;
+        draw_text(screen, text_x as i32, line_y as i32, &title, theme.title, 1);
+        line_y += LINE_HEIGHT;
+
+        // Barra apilada: la parte de cada etapa en el frame completo
+        let mut bar_x = text_x;
+        for (stage, ms) in STAGES.iter().zip(averages) {
+            let segment = ((ms / total.max(measured).max(0.001)) * BAR_WIDTH as f32) as usize;
+            screen.blend_rect(bar_x, line_y, segment, BAR_HEIGHT, stage.color(), 1.0);
+            bar_x += segment;
+        }
+        line_y += BAR_HEIGHT + PADDING;
+
+        for (index, line) in lines.iter().enumerate() {
+            let swatch = STAGES.get(index).map_or(theme.text, |stage| stage.color());
+            screen.blend_rect(text_x, line_y + 1, GLYPH_SIZE - 2, GLYPH_SIZE - 2, swatch, 1.0);
+            draw_text(screen, (text_x + GLYPH_SIZE * 2) as i32, line_y as i32, line, theme.text, 1);
+            line_y += LINE_HEIGHT;
+        }
+        draw_text(screen, text_x as i32, line_y as i32, &triangles, theme.muted, 1);
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+use serde::Deserialize;
+use crate::celestial_shaders::CelestialBody;
+use crate::draw_state::CullMode;
+use crate::nebula::{NebulaConfig, NebulaPalette};
+use crate::procedural::{generated_name, kepler_orbit_speed, ProceduralPlanet, SeededRng};
+use crate::scene::{default_position, BeltConfig, BodyConfig, BodyMetadata, EventConfig, RingStyle, Scene};
+
+// ============= SISTEMA ESTELAR ALEATORIO (--random-system <semilla>) =============
+// Genera una escena completa a partir de una semilla: clase de la estrella, número de
+// planetas, separación de órbitas, lunas, cinturones y parámetros de cada planeta.
+
+// Clase espectral de una estrella, de la más caliente (O) a la más fría (M). Decide su color
+// de cuerpo negro, su tamaño y su actividad (manchas, llamaradas). En la escena:
+// shader = { Star = "M" }
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+pub enum StarClass {
+    O,
+    B,
+    A,
+    F,
+    G,
+    K,
+    M,
+}
+
+impl StarClass {
+    // Las enanas rojas son las más comunes; las gigantes azules, las más raras
+    fn pick(rng: &mut SeededRng) -> Self {
+        let roll = rng.next_f32();
+        if roll < 0.35 {
+            StarClass::M
+        } else if roll < 0.60 {
+            StarClass::K
+        } else if roll < 0.82 {
+            StarClass::G
+        } else if roll < 0.95 {
+            StarClass::F
+        } else if roll < 0.985 {
+            StarClass::A
+        } else if roll < 0.997 {
+            StarClass::B
+        } else {
+            StarClass::O
+        }
+    }
+
+    // Clase a partir de un tipo espectral de catálogo ("G2V", "M8", "K0IV-V"...)
+    pub fn from_spectral_type(text: &str) -> Option<Self> {
+        match text.trim().chars().next()?.to_ascii_uppercase() {
+            'M' => Some(StarClass::M),
+            'K' => Some(StarClass::K),
+            'G' => Some(StarClass::G),
+            'F' => Some(StarClass::F),
+            'A' => Some(StarClass::A),
+            'B' => Some(StarClass::B),
+            'O' => Some(StarClass::O),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StarClass::M => "M",
+            StarClass::K => "K",
+            StarClass::G => "G",
+            StarClass::F => "F",
+            StarClass::A => "A",
+            StarClass::B => "B",
+            StarClass::O => "O",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            StarClass::M => "enana roja, fría y tenue",
+            StarClass::K => "enana naranja, algo más fría que el Sol",
+            StarClass::G => "enana amarilla, parecida al Sol",
+            StarClass::F => "estrella blanco-amarilla, más caliente que el Sol",
+            StarClass::A => "estrella blanca, grande y muy luminosa",
+            StarClass::B => "estrella azul-blanca, enorme y miles de veces más luminosa que el Sol",
+            StarClass::O => "gigante azul, la clase más caliente y luminosa",
+        }
+    }
+
+    // Radio en unidades de la escena (el Sol mide 80)
+    pub fn scale(&self) -> f32 {
+        match self {
+            StarClass::M => 50.0,
+            StarClass::K => 65.0,
+            StarClass::G => 80.0,
+            StarClass::F => 95.0,
+            StarClass::A => 115.0,
+            StarClass::B => 145.0,
+            StarClass::O => 180.0,
+        }
+    }
+
+    // Factor de separación de las órbitas: estrellas más luminosas alejan sus planetas
+    pub fn orbit_spread(&self) -> f32 {
+        match self {
+            StarClass::M => 0.7,
+            StarClass::K => 0.85,
+            StarClass::G => 1.0,
+            StarClass::F => 1.15,
+            StarClass::A => 1.35,
+            StarClass::B => 1.6,
+            StarClass::O => 1.9,
+        }
+    }
+
+    // Temperatura efectiva de la superficie (kelvin); el shader la convierte en color
+    pub fn temperature(&self) -> f32 {
+        match self {
+            StarClass::M => 3200.0,
+            StarClass::K => 4500.0,
+            StarClass::G => 5800.0,
+            StarClass::F => 6700.0,
+            StarClass::A => 8800.0,
+            StarClass::B => 16000.0,
+            StarClass::O => 35000.0,
+        }
+    }
+
+    // Actividad magnética relativa a la del Sol: las enanas rojas son estrellas fulgurantes,
+    // las calientes tienen superficies más tranquilas
+    pub fn activity(&self) -> f32 {
+        match self {
+            StarClass::M => 1.6,
+            StarClass::K => 1.2,
+            StarClass::G => 1.0,
+            StarClass::F => 0.85,
+            StarClass::A => 0.7,
+            StarClass::B => 0.6,
+            StarClass::O => 0.5,
+        }
+    }
+}
+
+const GIANT_MOON_SHADERS: [CelestialBody; 5] = [
+    CelestialBody::Io,
+    CelestialBody::Europa,
+    CelestialBody::Ganymede,
+    CelestialBody::Callisto,
+    CelestialBody::Moon,
+];
+
+const ROCKY_MOON_SHADERS: [CelestialBody; 3] = [CelestialBody::Moon, CelestialBody::Phobos, CelestialBody::Deimos];
+
+const GAS_GIANT_SHADERS: [CelestialBody; 4] = [
+    CelestialBody::Jupiter,
+    CelestialBody::Saturn,
+    CelestialBody::Uranus,
+    CelestialBody::Neptune,
+];
+
+pub fn generate(seed: u64) -> Scene {
+    let mut rng = SeededRng::new(seed);
+    let star_class = StarClass::pick(&mut rng);
+    let star_scale = star_class.scale();
+    let spread = star_class.orbit_spread();
+
+    let mut star_custom = BTreeMap::new();
+    star_custom.insert("Clase espectral".to_string(), star_class.label().to_string());
+    star_custom.insert("Semilla del sistema".to_string(), seed.to_string());
+    let mut bodies = vec![BodyConfig {
+        id: "star".to_string(),
+        shader: CelestialBody::Star(star_class),
+        parent: None,
+        position: default_position(),
+        scale: star_scale,
+        orbit_radius: 0.0,
+        orbit_speed: 0.0,
+        orbit_phase: 0.0,
+        rotation_speed: [0.0, 0.005, 0.0],
+        axial_tilt: [0.0; 3],
+        tidally_locked: false,
+        rings: None,
+        model: None,
+        cull: CullMode::default(),
+        metadata: BodyMetadata {
+            name: generated_name(seed),
+            description: format!("Estrella de clase {}: {}.", star_class.label(), star_class.description()),
+            custom: star_custom,
+            ..BodyMetadata::default()
+        },
+        palette: None,
+        script: None,
+    }];
+
+    // Órbitas con separación creciente (al estilo de la ley de Titius-Bode)
+    let planet_count = 2 + (rng.next_u64() % 7) as usize;
+    let frost_line = star_scale * 6.0 * spread;
+    let mut orbit_radius = star_scale + rng.range(70.0, 120.0) * spread;
+    let mut orbits = Vec::with_capacity(planet_count);
+
+    for index in 0..planet_count {
+        let planet_seed = rng.next_u64();
+        let is_giant = orbit_radius > frost_line && rng.chance(0.6);
+
+        let (shader, scale, rings, metadata) = if is_giant {
+            let shader = *rng.pick(&GAS_GIANT_SHADERS);
+            let rings = match shader {
+                CelestialBody::Saturn => Some(RingStyle::Saturn),
+                _ if rng.chance(0.25) => Some(RingStyle::Wide),
+                _ => None,
+            };
+            let mut custom = BTreeMap::new();
+            custom.insert("Semilla".to_string(), planet_seed.to_string());
+            let metadata = BodyMetadata {
+                name: generated_name(planet_seed),
+                description: "Gigante gaseoso más allá de la línea de hielo del sistema.".to_string(),
+                custom,
+                ..BodyMetadata::default()
+            };
+            (shader, rng.range(35.0, 60.0), rings, metadata)
+        } else {
+            let planet = ProceduralPlanet::from_seed(planet_seed);
+            (CelestialBody::Procedural(planet), rng.range(12.0, 30.0), planet.rings, planet.metadata())
+        };
+
+        let planet_id = format!("planet-{}", index + 1);
+        let planet_name = metadata.name.clone();
+        bodies.push(BodyConfig {
+            id: planet_id.clone(),
+            shader,
+            parent: None,
+            position: default_position(),
+            scale,
+            orbit_radius,
+            orbit_speed: kepler_orbit_speed(orbit_radius),
+            orbit_phase: rng.range(0.0, std::f32::consts::TAU),
+            rotation_speed: [0.0, rng.range(0.005, 0.03), 0.0],
+            axial_tilt: [0.0, 0.0, rng.range(0.0, 35.0)],
+            tidally_locked: false,
+            rings,
+            model: None,
+            cull: CullMode::default(),
+            metadata,
+            palette: None,
+            script: None,
+        });
+        orbits.push((orbit_radius, scale));
+
+        // Lunas: los gigantes tienen más y de más tipos
+        let (max_moons, moon_shaders): (u64, &[CelestialBody]) = if is_giant {
+            (5, &GIANT_MOON_SHADERS)
+        } else {
+            (3, &ROCKY_MOON_SHADERS)
+        };
+        let moon_count = rng.next_u64() % max_moons;
+        let mut moon_orbit = scale * rng.range(1.8, 2.4);
+        for moon_index in 0..moon_count {
+            let moon_seed = rng.next_u64();
+            let moon_scale = if is_giant { rng.range(5.0, 9.0) } else { rng.range(2.5, 6.0) };
+            bodies.push(BodyConfig {
+                id: format!("{}-moon-{}", planet_id, moon_index + 1),
+                shader: *rng.pick(moon_shaders),
+                parent: Some(planet_id.clone()),
+                position: default_position(),
+                scale: moon_scale,
+                orbit_radius: moon_orbit,
+                orbit_speed: 2.0 * (scale * 2.0 / moon_orbit).powf(1.5),
+                orbit_phase: rng.range(0.0, std::f32::consts::TAU),
+                rotation_speed: [0.0, 0.01, 0.0],
+                axial_tilt: [0.0; 3],
+                tidally_locked: true,
+                rings: None,
+                model: None,
+                cull: CullMode::default(),
+                metadata: BodyMetadata {
+                    name: generated_name(moon_seed),
+                    description: format!("Luna de {}.", planet_name),
+                    ..BodyMetadata::default()
+                },
+                palette: None,
+                script: None,
+            });
+            moon_orbit += moon_scale * 2.0 + rng.range(8.0, 20.0);
+        }
+        let outermost = scale.max(moon_orbit);
+
+        let gap = orbit_radius * rng.range(0.3, 0.6) + outermost * 3.0;
+        orbit_radius += gap * spread;
+    }
+
+    // Cinturones de asteroides en los huecos más amplios entre órbitas
+    let belt_count = (rng.next_u64() % 3) as usize;
+    let mut gaps: Vec<(f32, f32)> = orbits
+        .windows(2)
+        .map(|pair| (pair[0].0 + pair[0].1 * 2.5, pair[1].0 - pair[1].1 * 2.5))
+        .filter(|(inner, outer)| outer - inner > 60.0)
+        .collect();
+    gaps.sort_by(|a, b| (b.1 - b.0).total_cmp(&(a.1 - a.0)));
+
+    let belts = gaps
+        .into_iter()
+        .take(belt_count)
+        .map(|(inner, outer)| {
+            let middle = (inner + outer) * 0.5;
+            let half_width = (outer - inner) * rng.range(0.15, 0.3);
+            BeltConfig {
+                inner_radius: middle - half_width,
+                outer_radius: middle + half_width,
+                count: 30 + (rng.next_u64() % 31) as usize,
+                rock_scale: rng.range(3.0, 6.0),
+                seed: rng.next_u64(),
+                model: None,
+                cull: CullMode::default(),
+            }
+        })
+        .collect();
+
+    // Algunos sistemas quedan frente a una nebulosa de color al azar
+    let nebula = rng.chance(0.6).then(|| NebulaConfig {
+        palette: *rng.pick(&NebulaPalette::ALL),
+        density: rng.range(0.4, 0.65),
+        seed: rng.next_u64() as u32,
+        ..NebulaConfig::default()
+    });
+
+    Scene { bodies, belts, comets: Vec::new(), events: EventConfig::default(), tour: Vec::new(), mission: None, nebula, skybox: None, star_catalog: None, post: Vec::new() }
+}
@@ -0,0 +1,50 @@
+use crate::framebuffer::Framebuffer;
+
+// ============= GRAFO DE PASADAS DEL FRAME =============
+// El frame se describe como una lista de pasos, cada uno dentro de una pasada con nombre.
+// Las pasadas se ejecutan siempre en el mismo orden (cielo, opacos, transparentes,
+// partículas, posproceso, interfaz) y, dentro de cada una, los pasos en el orden en que se
+// añadieron. Así una función nueva (una pasada de sombras, un prepaso de profundidad, una
+// ventana con otra cámara...) se añade en su pasada sin reordenar a mano el bucle principal.
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Pass {
+    Sky,         // fondo del framebuffer de la escena
+    Opaque,      // cuerpos, terreno y núcleos: prueban y escriben profundidad
+    Transparent, // nubes y anillos, sobre lo opaco
+    Particles,   // colas de los cometas y demás efectos sin malla
+    Post,        // cielo atmosférico, reducción del supersampling a la pantalla, estelas, efectos (post.rs)
+    Ui,          // etiquetas, carteles y paneles sobre la pantalla final
+}
+
+// Destinos de un frame: la escena (con supersampling) y la pantalla que se muestra
+pub struct Targets<'a> {
+    pub scene: &'a mut Framebuffer,
+    pub screen: &'a mut Framebuffer,
+}
+
+type Step<'a> = Box<dyn FnOnce(&mut Targets) + 'a>;
+
+// Se crea uno por frame: los pasos toman prestado el estado del frame y se consumen al ejecutarse
+#[derive(Default)]
+pub struct RenderGraph<'a> {
+    steps: Vec<(Pass, Step<'a>)>,
+}
+
+impl<'a> RenderGraph<'a> {
+    pub fn new() -> Self {
+        RenderGraph::default()
+    }
+
+    pub fn add(&mut self, pass: Pass, step: impl FnOnce(&mut Targets) + 'a) {
+        self.steps.push((pass, Box::new(step)));
+    }
+
+    pub fn execute(mut self, targets: &mut Targets) {
+        // Orden estable: los pasos de una misma pasada conservan el orden en que se añadieron
+        self.steps.sort_by_key(|(pass, _)| *pass);
+        for (_, step) in self.steps {
+            step(targets);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use minifb::Key;
+use crate::controls::{key_name, parse_key};
+use crate::input::Input;
+
+// ============= GRABACIÓN Y REPETICIÓN DE LA ENTRADA =============
+// --record archivo guarda la entrada de cada frame (teclas y ratón) y --replay archivo la
+// vuelve a dar en lugar de la ventana. La simulación avanza siempre 0.016 s por frame, así
+// que con la misma escena, las mismas opciones y la misma semilla la repetición sale igual:
+// sirve para adjuntar un error reproducible o grabar una demo. Al terminar la repetición
+// los controles vuelven a la ventana.
+//
+// El archivo es texto: una cabecera con la semilla de la sesión y el tamaño de la ventana,
+// y una línea por frame con su número y lo que cambió respecto a no tocar nada:
+//
+// seed 1234
+// size 1200 800
+// 0 mouse=600,400
+// 1 down=W pressed=W repeat=W mouse=600,400 buttons=L
+//
+// down son las teclas mantenidas, pressed las pulsadas en ese frame, repeat las pulsadas o
+// repetidas por el teclado y buttons los botones del ratón (L, M, R) con los nombres de
+// tecla de controls.toml.
+
+const BUTTON_NAMES: [&str; 3] = ["L", "M", "R"];
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Syntax { line: usize, reason: String },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "no se pudo leer la repetición: {}", err),
+            ReplayError::Syntax { line, reason } => write!(f, "repetición inválida (línea {}): {}", line, reason),
+        }
+    }
+}
+
+pub struct Recorder {
+    file: BufWriter<File>,
+    frame: usize,
+}
+
+impl Recorder {
+    pub fn create(path: &str, seed: u64, size: (usize, usize)) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "# Repetición de la entrada: cargo run --release -- --replay {}", path)?;
+        writeln!(file, "seed {}", seed)?;
+        writeln!(file, "size {} {}", size.0, size.1)?;
+        Ok(Recorder { file, frame: 0 })
+    }
+
+    pub fn record(&mut self, input: &Input) -> io::Result<()> {
+        let mut line = self.frame.to_string();
+        for (field, keys) in [("down", &input.down), ("pressed", &input.pressed), ("repeat", &input.repeated)] {
+            let names: Vec<String> = keys.iter().filter(|&&key| key != Key::Unknown).map(|&key| key_name(key)).collect();
+            if !names.is_empty() {
+                line.push_str(&format!(" {}={}", field, names.join(",")));
+            }
+        }
+        if let Some((x, y)) = input.mouse {
+            line.push_str(&format!(" mouse={},{}", x, y));
+        }
+        let buttons: Vec<&str> = BUTTON_NAMES.iter().zip(input.buttons).filter(|(_, down)| *down).map(|(name, _)| *name).collect();
+        if !buttons.is_empty() {
+            line.push_str(&format!(" buttons={}", buttons.join(",")));
+        }
+        writeln!(self.file, "{}", line)?;
+        self.frame += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+pub struct Replay {
+    pub seed: Option<u64>,
+    frames: Vec<Input>,
+    next: usize,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Self, ReplayError> {
+        let contents = fs::read_to_string(path).map_err(ReplayError::Io)?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, ReplayError> {
+        let mut seed = None;
+        let mut size = (0, 0);
+        let mut frames = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            let syntax = |reason: String| ReplayError::Syntax { line: index + 1, reason };
+            let mut words = line.split_whitespace();
+            match words.next() {
+                None => {}
+                Some(word) if word.starts_with('#') => {}
+                Some("seed") => {
+                    seed = Some(words.next().and_then(|value| value.parse().ok()).ok_or_else(|| syntax("semilla inválida".to_string()))?);
+                }
+                Some("size") => {
+                    let mut numbers = words.map(str::parse::<usize>);
+                    size = match (numbers.next(), numbers.next()) {
+                        (Some(Ok(width)), Some(Ok(height))) => (width, height),
+                        _ => return Err(syntax("se esperaba `size ANCHO ALTO`".to_string())),
+                    };
+                }
+                Some(number) => {
+                    if number.parse() != Ok(frames.len()) {
+                        return Err(syntax(format!("se esperaba el frame {}", frames.len())));
+                    }
+                    let mut input = Input { size, ..Input::default() };
+                    for field in words {
+                        parse_field(&mut input, field).map_err(syntax)?;
+                    }
+                    frames.push(input);
+                }
+            }
+        }
+        Ok(Replay { seed, frames, next: 0 })
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    // Entrada del siguiente frame, o None cuando ya no quedan
+    pub fn next_frame(&mut self) -> Option<Input> {
+        let input = self.frames.get(self.next)?.clone();
+        self.next += 1;
+        Some(input)
+    }
+}
+
+fn parse_keys(list: &str) -> Result<Vec<Key>, String> {
+    list.split(',').map(|name| parse_key(name).ok_or_else(|| format!("tecla desconocida: '{}'", name))).collect()
+}
+
+fn parse_field(input: &mut Input, field: &str) -> Result<(), String> {
+    let (name, value) = field.split_once('=').ok_or_else(|| format!("campo sin valor: '{}'", field))?;
+    match name {
+        "down" => input.down = parse_keys(value)?,
+        "pressed" => input.pressed = parse_keys(value)?,
+        "repeat" => input.repeated = parse_keys(value)?,
+        "mouse" => {
+            let position = value.split_once(',').and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)));
+            input.mouse = Some(position.ok_or_else(|| format!("posición del ratón inválida: '{}'", value))?);
+        }
+        "buttons" => {
+            for button in value.split(',') {
+                let index = BUTTON_NAMES
+                    .iter()
+                    .position(|name| *name == button)
+                    .ok_or_else(|| format!("botón desconocido: '{}'", button))?;
+                input.buttons[index] = true;
+            }
+        }
+        _ => return Err(format!("campo desconocido: '{}'", name)),
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+// ============= RESOLUCIÓN ADAPTATIVA =============
+// Con el supersampling automático la escena no se dibuja a un tamaño fijo: este regulador
+// mira el tiempo de los últimos frames y sube o baja la resolución interna para mantener el
+// objetivo de fotogramas por segundo (target_fps en [render]). Por debajo de la ventana la
+// escena se amplía al presentarla y por encima se reduce promediando (supersampling), así que
+// un equipo rápido gana anti-aliasing y uno lento sigue fluido en un vuelo rasante.
+//
+// El coste de un frame se estima proporcional a los píxeles (el cuadrado de la escala): solo
+// se sube de nivel si la estimación cabe con holgura en el objetivo, y como parte del frame no
+// depende de la resolución, el nivel nuevo no vuelve a bajar enseguida. Con el antialiasing
+// de bordes (MSAA) activado no se pasa de la resolución de la ventana: los bordes ya salen
+// suaves sin pagar cuatro veces los fragmentos.
+
+// Escalas de la resolución interna respecto a la ventana, por eje. Por encima de 1 solo
+// factores enteros, que son los que sabe promediar la reducción
+const LEVELS: [f32; 6] = [0.5, 0.625, 0.75, 0.875, 1.0, 2.0];
+const START_LEVEL: usize = 4; // la resolución de la ventana
+
+const WINDOW: usize = 20;     // frames que se promedian antes de decidir
+const OVERLOAD: f32 = 1.1;    // se baja si la media pasa del objetivo más este margen
+const HEADROOM: f32 = 0.85;   // se sube si la estimación del nivel siguiente queda por debajo
+
+pub struct ResolutionController {
+    target_ms: f32,
+    max_scale: f32, // tope del vigilante de frames lentos y del antialiasing de bordes
+    level: usize,
+    recent: Vec<f32>,
+    skip_next: bool, // el primer frame tras un cambio paga la reserva del framebuffer
+}
+
+impl ResolutionController {
+    pub fn new(target_fps: f32) -> Self {
+        ResolutionController {
+            target_ms: 1000.0 / target_fps,
+            max_scale: LEVELS[LEVELS.len() - 1],
+            level: START_LEVEL,
+            recent: Vec::with_capacity(WINDOW),
+            skip_next: false,
+        }
+    }
+
+    pub fn set_target_fps(&mut self, target_fps: f32) {
+        self.target_ms = 1000.0 / target_fps;
+    }
+
+    // Con el tope por debajo del nivel actual se baja ya, sin esperar a medir
+    pub fn set_max_scale(&mut self, max_scale: f32) {
+        self.max_scale = max_scale;
+        while self.level > 0 && LEVELS[self.level] > max_scale {
+            self.change(self.level - 1);
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        LEVELS[self.level]
+    }
+
+    // Tiempo de trabajo del último frame en milisegundos (sin la espera del límite de frames)
+    pub fn record(&mut self, frame_ms: f32) {
+        if std::mem::take(&mut self.skip_next) {
+            return;
+        }
+        self.recent.push(frame_ms);
+        if self.recent.len() < WINDOW {
+            return;
+        }
+        let average = self.recent.iter().sum::<f32>() / self.recent.len() as f32;
+        self.recent.clear();
+
+        if average > self.target_ms * OVERLOAD && self.level > 0 {
+            self.change(self.level - 1);
+        } else if let Some(&next) = LEVELS.get(self.level + 1).filter(|&&next| next <= self.max_scale) {
+            let estimate = average * (next / self.scale()).powi(2);
+            if estimate < self.target_ms * HEADROOM {
+                self.change(self.level + 1);
+            }
+        }
+    }
+
+    fn change(&mut self, level: usize) {
+        self.level = level;
+        self.recent.clear();
+        self.skip_next = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET_FPS: f32 = 60.0; // 16.7 ms
+
+    fn record_window(controller: &mut ResolutionController, frame_ms: f32) {
+        for _ in 0..WINDOW {
+            controller.record(frame_ms);
+        }
+    }
+
+    #[test]
+    fn overloaded_window_steps_down() {
+        let mut controller = ResolutionController::new(TARGET_FPS);
+        record_window(&mut controller, 16.0);
+        assert_eq!(controller.scale(), 1.0);
+        for _ in 0..WINDOW - 1 {
+            controller.record(30.0);
+        }
+        assert_eq!(controller.scale(), 1.0, "decide al completar la ventana");
+        controller.record(30.0);
+        assert_eq!(controller.scale(), LEVELS[START_LEVEL - 1]);
+    }
+
+    #[test]
+    fn steps_up_only_when_the_estimate_fits_the_headroom() {
+        let mut controller = ResolutionController::new(TARGET_FPS);
+        // De 1 a 2 la estimación es cuatro veces la media: 4 ms * 4 no cabe en 16.7 * 0.85
+        record_window(&mut controller, 4.0);
+        assert_eq!(controller.scale(), 1.0);
+        // 3 ms * 4 = 12 ms sí
+        record_window(&mut controller, 3.0);
+        assert_eq!(controller.scale(), 2.0);
+    }
+
+    #[test]
+    fn max_scale_steps_down_at_once() {
+        let mut controller = ResolutionController::new(TARGET_FPS);
+        record_window(&mut controller, 3.0);
+        assert_eq!(controller.scale(), 2.0);
+        controller.set_max_scale(1.0);
+        assert_eq!(controller.scale(), 1.0);
+        controller.set_max_scale(0.7);
+        assert_eq!(controller.scale(), 0.625);
+        // Y no vuelve a subir por encima del tope aunque sobre tiempo
+        controller.record(0.0); // el frame tras el cambio no cuenta
+        record_window(&mut controller, 0.5);
+        assert_eq!(controller.scale(), 0.625);
+    }
+
+    #[test]
+    fn first_frame_after_a_change_is_skipped() {
+        let mut controller = ResolutionController::new(TARGET_FPS);
+        record_window(&mut controller, 30.0);
+        let scale = controller.scale();
+        assert!(scale < 1.0);
+        // El frame de la reserva tarda mucho pero no cuenta; los demás ni bajan (12 ms) ni
+        // dejan subir (12 ms * (1 / 0.875)² pasa de la holgura)
+        controller.record(1000.0);
+        record_window(&mut controller, 12.0);
+        assert_eq!(controller.scale(), scale);
+    }
+}
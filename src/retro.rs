@@ -0,0 +1,258 @@
+use rayon::prelude::*;
+use serde::Deserialize;
+use crate::framebuffer::Framebuffer;
+use crate::post::{PostChain, PostProcess};
+
+// ============= MODO RETRO =============
+// Un filtro para capturas con estilo de consola vieja, que se activa en el menú de ajustes
+// (Esc) y se configura en la tabla [retro] de settings.toml. Es una cadena de posproceso
+// (ver post.rs) sobre la pantalla terminada, después de la de la escena y antes de la interfaz:
+//
+// 1. Píxeles gordos: bloques de pixel_size x pixel_size con su color medio, como una
+//    resolución interna reducida.
+// 2. Paleta: cada bloque toma el color más cercano de una paleta corta (CGA de 16 colores,
+//    PICO-8 o los 4 verdes de la Game Boy, o los de `colors`), con un tramado ordenado para
+//    que los degradados de las atmósferas no se queden en franjas.
+// 3. Líneas de barrido: la última fila de cada bloque (una de cada dos sin píxeles gordos)
+//    se oscurece.
+// 4. Curvatura de tubo: la imagen se abomba hacia las esquinas, que quedan en negro.
+
+// Tramado ordenado de Bayer 4x4, en dieciseisavos
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Palette {
+    Cga,
+    Pico8,
+    GameBoy,
+}
+
+impl Palette {
+    fn colors(self) -> &'static [u32] {
+        match self {
+            Palette::Cga => &[
+                0x000000, 0x0000AA, 0x00AA00, 0x00AAAA, 0xAA0000, 0xAA00AA, 0xAA5500, 0xAAAAAA,
+                0x555555, 0x5555FF, 0x55FF55, 0x55FFFF, 0xFF5555, 0xFF55FF, 0xFFFF55, 0xFFFFFF,
+            ],
+            Palette::Pico8 => &[
+                0x000000, 0x1D2B53, 0x7E2553, 0x008751, 0xAB5236, 0x5F574F, 0xC2C3C7, 0xFFF1E8,
+                0xFF004D, 0xFFA300, 0xFFEC27, 0x00E436, 0x29ADFF, 0x83769C, 0xFF77A8, 0xFFCCAA,
+            ],
+            Palette::GameBoy => &[0x0F380F, 0x306230, 0x8BAC0F, 0x9BBC0F],
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RetroConfig {
+    pub palette: Palette,
+    pub colors: Vec<u32>, // paleta propia (0xRRGGBB); si no está vacía reemplaza a `palette`
+    pub pixel_size: u32,  // lado de los píxeles gordos en píxeles de la ventana; 1 = sin reducir
+    pub dither: bool,
+    pub scanlines: f32,   // cuánto se oscurecen las líneas de barrido (0 = nada, 1 = negro)
+    pub curvature: f32,   // abombado del tubo (0 = pantalla plana)
+}
+
+impl Default for RetroConfig {
+    fn default() -> Self {
+        RetroConfig {
+            palette: Palette::Cga,
+            colors: Vec::new(),
+            pixel_size: 3,
+            dither: true,
+            scanlines: 0.35,
+            curvature: 0.08,
+        }
+    }
+}
+
+// La cadena del modo retro con los efectos que pide la configuración
+pub fn chain(config: &RetroConfig) -> PostChain {
+    let cell = config.pixel_size.max(1) as usize;
+    let palette = if config.colors.is_empty() { config.palette.colors().to_vec() } else { config.colors.clone() };
+    let mut chain = PostChain::new();
+    if cell > 1 {
+        chain = chain.with_effect(Pixelate { cell });
+    }
+    chain = chain.with_effect(Quantize { palette, dither: config.dither, cell });
+    if config.scanlines > 0.0 {
+        chain = chain.with_effect(Scanlines::new(config.scanlines, cell.max(2)));
+    }
+    if config.curvature > 0.0 {
+        chain = chain.with_effect(Curvature::new(config.curvature));
+    }
+    chain
+}
+
+pub struct Pixelate {
+    cell: usize,
+}
+
+impl PostProcess for Pixelate {
+    fn process(&mut self, input: &Framebuffer, output: &mut Framebuffer) {
+        let (width, cell) = (input.width, self.cell);
+        output.buffer.par_chunks_mut(width * cell).zip(input.buffer.par_chunks(width * cell)).for_each(|(band, source)| {
+            let rows = band.len() / width;
+            for left in (0..width).step_by(cell) {
+                let right = (left + cell).min(width);
+                let mut sum = [0u32; 3];
+                for row in source.chunks(width) {
+                    for &color in &row[left..right] {
+                        sum[0] += (color >> 16) & 0xFF;
+                        sum[1] += (color >> 8) & 0xFF;
+                        sum[2] += color & 0xFF;
+                    }
+                }
+                let count = (rows * (right - left)) as u32;
+                let [r, g, b] = sum.map(|channel| channel / count);
+                let average = (r << 16) | (g << 8) | b;
+                for row in band.chunks_mut(width) {
+                    row[left..right].fill(average);
+                }
+            }
+        });
+    }
+}
+
+pub struct Quantize {
+    palette: Vec<u32>,
+    dither: bool,
+    cell: usize, // el tramado va por bloques para que cada píxel gordo tome un solo color
+}
+
+impl PostProcess for Quantize {
+    fn process(&mut self, input: &Framebuffer, output: &mut Framebuffer) {
+        let palette: Vec<[i32; 3]> = self.palette.iter().map(|&color| channels(color)).collect();
+        // El tramado mueve el color como mucho media separación típica entre colores de la
+        // paleta: los degradados se reparten entre dos vecinos y el negro sigue negro
+        let spread = if self.dither { 128.0 / (palette.len() as f32).cbrt() } else { 0.0 };
+        let (width, cell) = (input.width, self.cell);
+        output.buffer.par_chunks_mut(width).zip(input.buffer.par_chunks(width)).enumerate().for_each(|(y, (row, source))| {
+            let bayer = &BAYER[(y / cell) % 4];
+            for (x, (pixel, &color)) in row.iter_mut().zip(source).enumerate() {
+                let offset = ((bayer[(x / cell) % 4] as f32 + 0.5) / 16.0 - 0.5) * spread;
+                let color = channels(color).map(|channel| channel + offset as i32);
+                let nearest = palette
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, entry)| distance(entry, &color))
+                    .map_or(0, |(index, _)| index);
+                *pixel = self.palette[nearest];
+            }
+        });
+    }
+}
+
+pub struct Scanlines {
+    strength: f32,
+    period: usize,
+}
+
+impl Scanlines {
+    // Oscurece una fila de cada `period` (la última de cada bloque)
+    pub fn new(strength: f32, period: usize) -> Self {
+        Scanlines { strength: strength.clamp(0.0, 1.0), period: period.max(1) }
+    }
+}
+
+impl PostProcess for Scanlines {
+    fn process(&mut self, input: &Framebuffer, output: &mut Framebuffer) {
+        let keep = ((1.0 - self.strength) * 256.0) as u32;
+        let (width, period) = (input.width, self.period);
+        output.buffer.par_chunks_mut(width).zip(input.buffer.par_chunks(width)).enumerate().for_each(|(y, (row, source))| {
+            if y % period != period - 1 {
+                row.copy_from_slice(source);
+                return;
+            }
+            for (pixel, &color) in row.iter_mut().zip(source) {
+                let [r, g, b] = channels(color).map(|channel| (channel as u32 * keep) >> 8);
+                *pixel = (r << 16) | (g << 8) | b;
+            }
+        });
+    }
+}
+
+pub struct Curvature {
+    amount: f32,
+}
+
+impl Curvature {
+    pub fn new(amount: f32) -> Self {
+        Curvature { amount }
+    }
+}
+
+impl PostProcess for Curvature {
+    fn process(&mut self, input: &Framebuffer, output: &mut Framebuffer) {
+        let (width, height) = (input.width, input.height);
+        let (source, amount) = (&input.buffer, self.amount);
+        output.buffer.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+            let v = (y as f32 + 0.5) / height as f32 * 2.0 - 1.0;
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let u = (x as f32 + 0.5) / width as f32 * 2.0 - 1.0;
+                // Cada punto se lee más lejos del centro cuanto más lejos está: la imagen se
+                // comprime hacia los bordes y las rectas se curvan como en el cristal del tubo
+                let bend = 1.0 + amount * (u * u + v * v);
+                let (u, v) = (u * bend, v * bend);
+                *pixel = if u.abs() < 1.0 && v.abs() < 1.0 {
+                    let source_x = ((u + 1.0) * 0.5 * width as f32) as usize;
+                    let source_y = ((v + 1.0) * 0.5 * height as f32) as usize;
+                    source[source_y.min(height - 1) * width + source_x.min(width - 1)]
+                } else {
+                    0x000000
+                };
+            }
+        });
+    }
+}
+
+fn channels(color: u32) -> [i32; 3] {
+    [((color >> 16) & 0xFF) as i32, ((color >> 8) & 0xFF) as i32, (color & 0xFF) as i32]
+}
+
+// Distancia con más peso en el verde, al que el ojo es más sensible
+fn distance(a: &[i32; 3], b: &[i32; 3]) -> i32 {
+    let (dr, dg, db) = (a[0] - b[0], a[1] - b[1], a[2] - b[2]);
+    2 * dr * dr + 4 * dg * dg + 3 * db * db
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(width: usize, height: usize) -> Framebuffer {
+        let mut image = Framebuffer::new(width, height);
+        for (index, pixel) in image.buffer.iter_mut().enumerate() {
+            let (x, y) = ((index % width) as u32, (index / width) as u32);
+            *pixel = ((x * 255 / width as u32) << 16) | ((y * 255 / height as u32) << 8) | 0x40;
+        }
+        image
+    }
+
+    #[test]
+    fn output_uses_only_the_palette_in_whole_cells() {
+        let config = RetroConfig { palette: Palette::GameBoy, scanlines: 0.0, curvature: 0.0, ..RetroConfig::default() };
+        let mut image = gradient(31, 20);
+        chain(&config).apply(&mut image);
+
+        let palette = Palette::GameBoy.colors();
+        assert!(image.buffer.iter().all(|pixel| palette.contains(pixel)));
+        // Cada bloque de 3x3 (los del borde, recortados) es de un solo color
+        for (index, &pixel) in image.buffer.iter().enumerate() {
+            let (x, y) = (index % 31, index / 31);
+            assert_eq!(pixel, image.buffer[(y / 3 * 3) * 31 + x / 3 * 3], "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn curvature_blackens_the_corners_and_keeps_the_center() {
+        let mut image = Framebuffer::new(40, 30);
+        image.buffer.fill(0xFFFFFF);
+        let mut output = Framebuffer::new(40, 30);
+        Curvature::new(0.2).process(&image, &mut output);
+        assert_eq!(output.buffer[0], 0x000000);
+        assert_eq!(output.buffer[15 * 40 + 20], 0xFFFFFF);
+    }
+}
@@ -0,0 +1,400 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use serde::Deserialize;
+use crate::celestial_shaders::CelestialBody;
+use crate::draw_state::CullMode;
+use crate::nebula::NebulaConfig;
+use crate::post::PostEffectConfig;
+use crate::skybox::SkyboxConfig;
+use crate::star_catalog::StarCatalogConfig;
+use crate::procedural::{kepler_orbit_speed, SeededRng};
+use crate::shader_params::ShaderParams;
+
+// ============= FORMATO DE ESCENA (TOML) =============
+// Describe los cuerpos del sistema (shader, tamaño, órbita, rotación) y sus metadatos
+// (nombre, descripción, datos curiosos, campos libres) para poder localizar y
+// personalizar el contenido sin recompilar.
+
+#[derive(Deserialize)]
+pub struct Scene {
+    #[serde(default)]
+    pub bodies: Vec<BodyConfig>,
+    #[serde(default)]
+    pub belts: Vec<BeltConfig>,
+    #[serde(default)]
+    pub comets: Vec<CometConfig>,
+    #[serde(default)]
+    pub events: EventConfig,
+    #[serde(default)]
+    pub tour: Vec<TourKeyframe>,
+    pub mission: Option<MissionConfig>, // objetivos del vuelo libre (ver mission.rs)
+    pub nebula: Option<NebulaConfig>, // fondo de nubes de gas (ver nebula.rs); sin tabla, fondo liso
+    pub skybox: Option<SkyboxConfig>, // panorama o cubo de la Vía Láctea (ver skybox.rs)
+    pub star_catalog: Option<StarCatalogConfig>, // estrellas reales y constelaciones (ver star_catalog.rs)
+    #[serde(default)]
+    pub post: Vec<PostEffectConfig>, // cadena de posproceso en orden (ver post.rs); vacía por defecto
+}
+
+#[derive(Deserialize, Clone)]
+pub struct BodyConfig {
+    pub id: String,
+    pub shader: CelestialBody,
+    pub parent: Option<String>, // id del cuerpo alrededor del cual orbita (lunas); debe declararse antes
+    #[serde(default = "default_position")]
+    pub position: [f32; 3],
+    pub scale: f32,
+    #[serde(default)]
+    pub orbit_radius: f32,
+    #[serde(default)]
+    pub orbit_speed: f32,
+    #[serde(default)]
+    pub orbit_phase: f32, // ángulo inicial en la órbita (radianes)
+    #[serde(default = "default_rotation_speed")]
+    pub rotation_speed: [f32; 3],
+    #[serde(default)]
+    pub axial_tilt: [f32; 3],   // inclinación del eje de giro (grados, ángulos de Euler X/Y/Z)
+    #[serde(default)]
+    pub tidally_locked: bool,   // gira una vez por órbita mostrando siempre la misma cara al padre
+    pub rings: Option<RingStyle>,
+    pub model: Option<String>,  // malla OBJ, STL o PLY en lugar de la esfera (se ajusta a radio `scale`)
+    #[serde(default)]
+    pub cull: CullMode,         // caras que no se dibujan: "back" (por defecto), "front" o "none"
+    #[serde(default)]
+    pub metadata: BodyMetadata,
+    pub palette: Option<toml::Table>, // colores del shader (ver shader_params.rs); sin tabla, los de fábrica
+    pub script: Option<String>,       // script Rhai con fn update(time, dt) (ver scripting.rs)
+}
+
+impl BodyConfig {
+    // Paleta del shader con los colores de la tabla `palette` encima de los de fábrica
+    pub fn params(&self) -> Result<ShaderParams, SceneError> {
+        ShaderParams::from_palette(self.shader, self.palette.as_ref())
+            .map_err(|reason| SceneError::InvalidPalette { body: self.id.clone(), reason })
+    }
+}
+
+// Cinturón de asteroides: rocas pequeñas repartidas entre dos radios orbitales.
+// Se dibujan pero no tienen etiqueta ni se pueden seleccionar
+#[derive(Deserialize, Clone)]
+pub struct BeltConfig {
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub count: usize,
+    #[serde(default = "default_rock_scale")]
+    pub rock_scale: f32, // tamaño máximo de cada roca
+    #[serde(default)]
+    pub seed: u64,
+    pub model: Option<String>, // malla de las rocas (OBJ, STL o PLY); sin ella, esferas
+    #[serde(default)]
+    pub cull: CullMode,        // como en los cuerpos
+}
+
+// Cometa en órbita elíptica alrededor del Sol (distancias en unidades de la escena)
+#[derive(Deserialize, Clone)]
+pub struct CometConfig {
+    pub name: String,
+    pub perihelion: f32,
+    pub aphelion: f32,
+    pub period: f32,               // tiempo de simulación para una vuelta completa
+    #[serde(default)]
+    pub phase: f32,                // fracción de la órbita recorrida al empezar (0 = perihelio)
+    #[serde(default)]
+    pub inclination: f32,          // grados respecto al plano de la escena
+    #[serde(default)]
+    pub perihelion_longitude: f32, // grados, dirección del perihelio vista desde el Sol
+    #[serde(default = "default_comet_scale")]
+    pub scale: f32,                // tamaño del núcleo
+    #[serde(default = "default_tail_length")]
+    pub tail_length: f32,          // longitud de la cola de iones en el perihelio
+}
+
+// Qué eventos registra la simulación y cómo se avisan (tabla [events], todo opcional)
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct EventConfig {
+    pub eclipses: bool,
+    pub conjunctions: bool,
+    pub perihelia: bool,
+    pub storms: bool,
+    pub toasts: bool,
+    pub toast_seconds: f32,
+    pub conjunction_angle: f32, // separación máxima vista desde el Sol (grados)
+}
+
+impl Default for EventConfig {
+    fn default() -> Self {
+        EventConfig {
+            eclipses: true,
+            conjunctions: true,
+            perihelia: true,
+            storms: true,
+            toasts: true,
+            toast_seconds: 4.0,
+            conjunction_angle: 2.0,
+        }
+    }
+}
+
+// Fotograma de la visita guiada (V): la cámara vuela hasta él en `duration` segundos y se
+// queda `hold` segundos. Con `focus` se coloca alrededor de ese cuerpo (como los pasos de
+// una lección) y lo sigue en su órbita; si no, usa `position` y `target` fijos
+#[derive(Deserialize, Clone)]
+pub struct TourKeyframe {
+    pub focus: Option<String>,
+    pub position: Option<[f32; 3]>,
+    #[serde(default = "default_position")]
+    pub target: [f32; 3],
+    #[serde(default = "default_tour_distance")]
+    pub distance: f32,  // en radios del cuerpo enfocado
+    #[serde(default = "default_tour_elevation")]
+    pub elevation: f32, // grados sobre el plano orbital
+    #[serde(default)]
+    pub azimuth: f32,   // grados alrededor del cuerpo
+    #[serde(default = "default_tour_duration")]
+    pub duration: f32,
+    #[serde(default = "default_tour_hold")]
+    pub hold: f32,
+}
+
+// Misión del vuelo libre: una lista de objetivos que se cumplen en orden
+#[derive(Deserialize, Clone)]
+pub struct MissionConfig {
+    #[serde(default)]
+    pub title: String,
+    pub waypoints: Vec<WaypointConfig>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct WaypointConfig {
+    pub kind: WaypointKind,
+    pub target: String,          // id de un cuerpo o nombre de un cometa
+    pub objective: String,       // texto del objetivo en pantalla
+    #[serde(default)]
+    pub done: String,            // mensaje al cumplirlo (uno genérico si falta)
+    pub distance: Option<f32>,   // alcance en radios del objetivo (4 para llegar, 40 para escanear)
+    #[serde(default = "default_scan_seconds")]
+    pub seconds: f32,            // tiempo apuntando al objetivo para escanearlo
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum WaypointKind {
+    Reach, // llegar cerca del objetivo
+    Scan,  // apuntarle con la proa desde cerca durante `seconds`
+    Dock,  // atracar en él (tiene que ser una estación)
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RingStyle {
+    Saturn, // Anillos clásicos (2.5x el planeta)
+    Wide,   // Anillos enormes e inclinados (4x el planeta)
+}
+
+// Metadatos mostrados en las etiquetas, el panel de información y la enciclopedia
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct BodyMetadata {
+    pub name: String,
+    pub description: String,
+    pub article: String, // Texto largo de la página de enciclopedia (párrafos separados por línea en blanco)
+    pub facts: Vec<String>,
+    pub custom: BTreeMap<String, String>,
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Empty,
+    UnknownParent { body: String, parent: String },
+    InvalidPalette { body: String, reason: String },
+    InvalidOrbit(String),
+    UnknownFocus(String),
+    InvalidKeyframe(usize),
+    InvalidPostEffect { index: usize, reason: String },
+    InvalidWaypoint { index: usize, reason: String },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "no se pudo leer la escena: {}", err),
+            SceneError::Parse(err) => write!(f, "escena inválida: {}", err),
+            SceneError::Empty => write!(f, "la escena no define ningún cuerpo"),
+            SceneError::UnknownParent { body, parent } => write!(
+                f,
+                "'{}' orbita un cuerpo inexistente o declarado después: '{}'",
+                body, parent
+            ),
+            SceneError::InvalidPalette { body, reason } => write!(f, "paleta inválida en '{}': {}", body, reason),
+            SceneError::InvalidOrbit(name) => write!(
+                f,
+                "el cometa '{}' necesita 0 < perihelion <= aphelion y period > 0",
+                name
+            ),
+            SceneError::UnknownFocus(id) => write!(f, "la visita guiada enfoca un cuerpo inexistente: '{}'", id),
+            SceneError::InvalidKeyframe(index) => write!(
+                f,
+                "el fotograma {} de la visita necesita `focus` o `position`, y duration > 0",
+                index
+            ),
+            SceneError::InvalidPostEffect { index, reason } => write!(f, "efecto {} de [[post]] inválido: {}", index, reason),
+            SceneError::InvalidWaypoint { index, reason } => write!(f, "objetivo {} de la misión inválido: {}", index, reason),
+        }
+    }
+}
+
+impl BeltConfig {
+    // Rocas del cinturón (deterministas según la semilla)
+    pub fn rocks(&self) -> Vec<BodyConfig> {
+        let mut rng = SeededRng::new(self.seed);
+        (0..self.count)
+            .map(|i| {
+                let orbit_radius = rng.range(self.inner_radius, self.outer_radius);
+                BodyConfig {
+                    id: format!("rock-{}", i),
+                    shader: CelestialBody::Moon,
+                    parent: None,
+                    position: default_position(),
+                    scale: rng.range(self.rock_scale * 0.4, self.rock_scale),
+                    orbit_radius,
+                    orbit_speed: kepler_orbit_speed(orbit_radius),
+                    orbit_phase: rng.range(0.0, std::f32::consts::TAU),
+                    rotation_speed: [rng.range(0.0, 0.03), rng.range(0.0, 0.03), 0.0],
+                    axial_tilt: [0.0; 3],
+                    tidally_locked: false,
+                    rings: None,
+                    model: self.model.clone(),
+                    cull: self.cull,
+                    metadata: BodyMetadata::default(),
+                    palette: None,
+                    script: None,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Scene {
+    pub fn load(filename: &str) -> Result<Self, SceneError> {
+        let contents = fs::read_to_string(filename).map_err(SceneError::Io)?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, SceneError> {
+        let scene: Scene = toml::from_str(contents).map_err(SceneError::Parse)?;
+
+        if scene.bodies.is_empty() {
+            return Err(SceneError::Empty);
+        }
+        // Los padres se declaran antes que sus lunas: así se actualizan en orden en un solo recorrido
+        for (index, body) in scene.bodies.iter().enumerate() {
+            if let Some(parent) = &body.parent {
+                if !scene.bodies[..index].iter().any(|other| &other.id == parent) {
+                    return Err(SceneError::UnknownParent {
+                        body: body.id.clone(),
+                        parent: parent.clone(),
+                    });
+                }
+            }
+            body.params()?;
+        }
+
+        for comet in &scene.comets {
+            if !(comet.perihelion > 0.0 && comet.perihelion <= comet.aphelion && comet.period > 0.0) {
+                return Err(SceneError::InvalidOrbit(comet.name.clone()));
+            }
+        }
+
+        for (index, keyframe) in scene.tour.iter().enumerate() {
+            if let Some(focus) = &keyframe.focus {
+                if scene.body_index(focus).is_none() {
+                    return Err(SceneError::UnknownFocus(focus.clone()));
+                }
+            } else if keyframe.position.is_none() {
+                return Err(SceneError::InvalidKeyframe(index + 1));
+            }
+            if keyframe.duration <= 0.0 {
+                return Err(SceneError::InvalidKeyframe(index + 1));
+            }
+        }
+
+        for (index, effect) in scene.post.iter().enumerate() {
+            effect.validate().map_err(|reason| SceneError::InvalidPostEffect { index: index + 1, reason })?;
+        }
+
+        if let Some(mission) = &scene.mission {
+            for (index, waypoint) in mission.waypoints.iter().enumerate() {
+                scene.check_waypoint(waypoint).map_err(|reason| SceneError::InvalidWaypoint { index: index + 1, reason })?;
+            }
+        }
+
+        Ok(scene)
+    }
+
+    // El objetivo tiene que existir, y para atracar ser una estación
+    fn check_waypoint(&self, waypoint: &WaypointConfig) -> Result<(), String> {
+        let body = self.body_index(&waypoint.target).map(|index| &self.bodies[index]);
+        let comet = self.comets.iter().any(|comet| comet.name == waypoint.target);
+        if body.is_none() && !comet {
+            return Err(format!("no hay ningún cuerpo ni cometa '{}'", waypoint.target));
+        }
+        if waypoint.kind == WaypointKind::Dock && body.is_none_or(|body| body.shader != CelestialBody::Station) {
+            return Err(format!("'{}' no es una estación", waypoint.target));
+        }
+        if waypoint.distance.is_some_and(|distance| distance <= 0.0) || waypoint.seconds <= 0.0 {
+            return Err("distance y seconds tienen que ser > 0".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn body_index(&self, id: &str) -> Option<usize> {
+        self.bodies.iter().position(|body| body.id == id)
+    }
+
+    pub fn parent_index(&self, body: &BodyConfig) -> Option<usize> {
+        body.parent.as_deref().and_then(|parent| self.body_index(parent))
+    }
+}
+
+pub fn default_position() -> [f32; 3] {
+    [600.0, 400.0, 0.0]
+}
+
+fn default_rock_scale() -> f32 {
+    4.0
+}
+
+fn default_comet_scale() -> f32 {
+    3.0
+}
+
+fn default_tail_length() -> f32 {
+    400.0
+}
+
+fn default_tour_distance() -> f32 {
+    6.0
+}
+
+fn default_tour_elevation() -> f32 {
+    20.0
+}
+
+fn default_tour_duration() -> f32 {
+    4.0
+}
+
+fn default_tour_hold() -> f32 {
+    2.0
+}
+
+fn default_scan_seconds() -> f32 {
+    3.0
+}
+
+fn default_rotation_speed() -> [f32; 3] {
+    [0.0, 0.01, 0.0]
+}
@@ -0,0 +1,77 @@
+use nalgebra_glm::{Mat4, Vec3};
+
+// ============= GRAFO DE ESCENA =============
+// Cada nodo guarda su transformación local relativa al padre. La transformación de mundo
+// (mundo del padre * local) se recalcula cada frame recorriendo el árbol desde las raíces,
+// así una luna, unos anillos o una estación se mueven con el cuerpo al que pertenecen.
+
+pub type NodeId = usize;
+
+#[derive(Clone)]
+struct SceneNode {
+    local: Mat4,
+    world: Mat4,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+#[derive(Clone)]
+pub struct SceneGraph {
+    nodes: Vec<SceneNode>,
+}
+
+impl SceneGraph {
+    pub fn new() -> Self {
+        SceneGraph { nodes: Vec::new() }
+    }
+
+    // Añade un nodo (raíz si no tiene padre); el padre debe existir ya
+    pub fn add_node(&mut self, parent: Option<NodeId>, local: Mat4) -> NodeId {
+        let id = self.nodes.len();
+        if let Some(parent) = parent {
+            self.nodes[parent].children.push(id);
+        }
+        let world = match parent {
+            Some(parent) => self.nodes[parent].world * local,
+            None => local,
+        };
+        self.nodes.push(SceneNode {
+            local,
+            world,
+            parent,
+            children: Vec::new(),
+        });
+        id
+    }
+
+    pub fn set_local(&mut self, node: NodeId, local: Mat4) {
+        self.nodes[node].local = local;
+    }
+
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.nodes[node].parent
+    }
+
+    // Origen del nodo en coordenadas de mundo
+    pub fn world_position(&self, node: NodeId) -> Vec3 {
+        let world = &self.nodes[node].world;
+        Vec3::new(world[(0, 3)], world[(1, 3)], world[(2, 3)])
+    }
+
+    // Recorrido en profundidad desde cada raíz: los padres se resuelven antes que sus hijos
+    pub fn update_world_transforms(&mut self) {
+        let mut stack: Vec<(NodeId, Mat4)> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.parent.is_none())
+            .map(|(id, _)| (id, Mat4::identity()))
+            .collect();
+
+        while let Some((id, parent_world)) = stack.pop() {
+            let world = parent_world * self.nodes[id].local;
+            self.nodes[id].world = world;
+            stack.extend(self.nodes[id].children.iter().map(|&child| (child, world)));
+        }
+    }
+}
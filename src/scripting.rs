@@ -0,0 +1,138 @@
+use std::path::PathBuf;
+use nalgebra_glm::Vec3;
+use rhai::{CallFnOptions, Dynamic, Engine, Map, Scope, AST, FLOAT};
+use crate::tweaks::Tweak;
+use crate::CelestialObject;
+
+// ============= SCRIPTS DE LA ESCENA (RHAI) =============
+// Un cuerpo de la escena puede llevar `script = "scripts/luna.rhai"` para cambiar su
+// comportamiento sin tocar el código. El script define `fn update(time, dt)`, que se llama
+// en cada frame de simulación (no en pausa) con `this` apuntando al cuerpo. `this` es un
+// mapa con lo que el script puede leer y cambiar:
+//   scale, spin                             - tamaño y giro propio (eje Y)
+//   orbit_radius, orbit_speed, orbit_phase  - órbita (solo en los cuerpos que orbitan)
+//   glow, cloud_speed, band_frequency       - constantes del shader (ver tweaks.rs)
+// y lo que solo puede leer, renovado en cada frame:
+//   id, x, y, z - identificador y posición en el mundo
+//   night       - parte de la cara visible desde la cámara que está de noche (0 a 1)
+// Cualquier otra clave que el script guarde en `this` se conserva entre frames.
+// Un error de compilación impide cargar la escena; uno durante la ejecución se avisa por
+// la consola y apaga ese script (los demás siguen).
+
+// Límite de operaciones por llamada: un bucle infinito no congela la ventana
+const MAX_OPERATIONS: u64 = 200_000;
+
+struct BodyScript {
+    body: usize, // índice en celestial_objects
+    path: String,
+    ast: AST,
+    this: Map,
+    running: bool,
+}
+
+pub struct ScriptHost {
+    engine: Engine,
+    scripts: Vec<BodyScript>,
+}
+
+impl ScriptHost {
+    pub fn new() -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        ScriptHost { engine, scripts: Vec::new() }
+    }
+
+    // Compila el script del cuerpo `body`; falla si no define `fn update(time, dt)`
+    pub fn attach(&mut self, body: usize, path: &str) -> Result<(), String> {
+        let ast = self
+            .engine
+            .compile_file(PathBuf::from(path))
+            .map_err(|err| format!("{}: {}", path, err))?;
+        if !ast.iter_functions().any(|function| function.name == "update" && function.params.len() == 2) {
+            return Err(format!("{}: falta fn update(time, dt)", path));
+        }
+        self.scripts.push(BodyScript { body, path: path.to_string(), ast, this: Map::new(), running: true });
+        Ok(())
+    }
+
+    pub fn update(&mut self, objects: &mut [CelestialObject], time: f32, dt: f32, camera_position: Vec3, light_position: Vec3) {
+        for script in self.scripts.iter_mut().filter(|script| script.running) {
+            let Some(obj) = objects.get_mut(script.body) else {
+                continue;
+            };
+            write_body(&mut script.this, obj, camera_position, light_position);
+
+            let mut this = Dynamic::from_map(std::mem::take(&mut script.this));
+            let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut this);
+            let result = self
+                .engine
+                .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &script.ast, "update", (time as FLOAT, dt as FLOAT))
+                .map_err(|err| err.to_string())
+                .and_then(|_| this.try_cast::<Map>().ok_or_else(|| "`this` dejó de ser un mapa".to_string()))
+                .and_then(|map| read_body(&map, obj).map(|_| map));
+
+            match result {
+                Ok(map) => script.this = map,
+                Err(err) => {
+                    eprintln!("Script {} ('{}') desactivado: {}", script.path, obj.id, err);
+                    script.running = false;
+                }
+            }
+        }
+    }
+}
+
+fn store(map: &mut Map, key: &str, value: f32) {
+    map.insert(key.into(), Dynamic::from_float(value as FLOAT));
+}
+
+// Número guardado en el mapa (enteros incluidos: `this.scale = 20`)
+fn number(map: &Map, key: &str) -> Result<f32, String> {
+    let entry = map.get(key).ok_or_else(|| format!("se borró `{}`", key))?;
+    entry
+        .as_float()
+        .or_else(|_| entry.as_int().map(|int| int as FLOAT))
+        .map(|value| value as f32)
+        .map_err(|kind| format!("`{}` debe ser un número, no {}", key, kind))
+}
+
+// Copia al mapa el estado del cuerpo (lo que el script ve al empezar el frame)
+fn write_body(map: &mut Map, obj: &CelestialObject, camera_position: Vec3, light_position: Vec3) {
+    map.insert("id".into(), obj.id.clone().into());
+    store(map, "x", obj.transform.translation.x);
+    store(map, "y", obj.transform.translation.y);
+    store(map, "z", obj.transform.translation.z);
+    let to_camera = (camera_position - obj.transform.translation).normalize();
+    let to_light = (light_position - obj.transform.translation).normalize();
+    store(map, "night", (1.0 - to_camera.dot(&to_light)) * 0.5);
+
+    store(map, "scale", obj.transform.scale);
+    store(map, "spin", obj.spin.speed.y);
+    store(map, "glow", obj.shader.tweaks.glow);
+    store(map, "cloud_speed", obj.shader.tweaks.cloud_speed);
+    store(map, "band_frequency", obj.shader.tweaks.band_frequency);
+    if let Some(orbit) = &obj.orbit {
+        store(map, "orbit_radius", orbit.radius);
+        store(map, "orbit_speed", orbit.speed);
+        store(map, "orbit_phase", orbit.phase);
+    }
+}
+
+// Devuelve al cuerpo lo que el script haya cambiado
+fn read_body(map: &Map, obj: &mut CelestialObject) -> Result<(), String> {
+    obj.transform.scale = number(map, "scale")?.max(0.01);
+    obj.spin.speed.y = number(map, "spin")?;
+    if let Some(orbit) = obj.orbit.as_mut() {
+        orbit.radius = number(map, "orbit_radius")?.max(0.0);
+        orbit.speed = number(map, "orbit_speed")?;
+        orbit.phase = number(map, "orbit_phase")?;
+    }
+    // Las constantes del shader que cambian se recortan al rango de su control en el panel F2
+    for (tweak, key) in [(Tweak::Glow, "glow"), (Tweak::CloudSpeed, "cloud_speed"), (Tweak::BandFrequency, "band_frequency")] {
+        let value = number(map, key)?;
+        if value != tweak.get(&obj.shader.tweaks) {
+            tweak.set(&mut obj.shader.tweaks, value);
+        }
+    }
+    Ok(())
+}
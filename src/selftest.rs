@@ -0,0 +1,345 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Instant;
+use nalgebra_glm::{Vec2, Vec3};
+use crate::celestial_shaders::CelestialBody;
+use crate::draw_state::DrawState;
+use crate::fragment::Fragment;
+use crate::framebuffer::{Framebuffer, FULL_COVERAGE, NO_OBJECT};
+use crate::lod::SphereLod;
+use crate::math::{create_normal_matrix, create_viewport_matrix, Backend, MathBackend};
+use crate::procedural::ProceduralPlanet;
+use crate::random_system::StarClass;
+use crate::triangle::triangle;
+use crate::tweaks::ShaderTweaks;
+use crate::shader_params::ShaderParams;
+use crate::vertex::Vertex;
+use crate::{render, Projection, Uniforms};
+
+// ============= AUTOPRUEBA (--selftest) =============
+// Comprueba el rasterizador y los shaders sin abrir la ventana y resume la máquina para
+// adjuntarlo a un informe de error:
+// - Triángulos de referencia: cobertura estanca (sin huecos ni píxeles repetidos en las
+//   aristas compartidas, también por muestra con antialiasing), triángulos degenerados y
+//   gigantes recortados a la pantalla.
+// - Una esfera con cada shader en un buffer pequeño: sin pánicos, con píxeles dibujados y
+//   sin profundidades NaN.
+// - Núcleos, backend de álgebra lineal, SIMD disponible y nivel de rendimiento esperado.
+// Devuelve el código de salida del proceso: 0 si todo pasa.
+
+const BUFFER_SIZE: usize = 64;
+const TIER_SIZE: usize = 256;   // la prueba de rendimiento llena un buffer más grande
+const TIER_RUNS: usize = 5;
+const BACKGROUND: u32 = 0x000011;
+
+pub fn run() -> i32 {
+    println!("Autoprueba del renderizador");
+    println!();
+
+    let mut failures = 0;
+    let mut check = |name: &str, result: Result<String, String>| match result {
+        Ok(detail) => println!("  [ok]    {:<28}{}", name, detail),
+        Err(detail) => {
+            failures += 1;
+            println!("  [FALLO] {:<28}{}", name, detail);
+        }
+    };
+
+    println!("Rasterizador");
+    check("cuadrado de dos triángulos", guarded(square_is_watertight));
+    check("abanico de ocho triángulos", guarded(fan_has_no_overlaps));
+    check("triángulo degenerado", guarded(degenerate_is_empty));
+    check("triángulo gigante", guarded(huge_triangle_is_safe));
+    check("antialiasing 4x", guarded(multisample_edges_are_shared));
+    println!();
+
+    println!("Shaders");
+    let sphere = SphereLod::new();
+    let mesh = sphere.select(BUFFER_SIZE as f32 / 2.0);
+    for (name, body) in shaders() {
+        check(name, guarded(|| shade_sphere(body, &mesh, BUFFER_SIZE)));
+    }
+    println!();
+
+    println!("Sistema");
+    let cores = std::thread::available_parallelism().map_or(1, |count| count.get());
+    println!("  núcleos:            {} (rayon usa {} hilos)", cores, rayon::current_num_threads());
+    println!("  backend:            {}", Backend::NAME);
+    let simd = simd_features();
+    println!("  SIMD:               {}", if simd.is_empty() { "ninguno detectado".to_string() } else { simd.join(", ") });
+    match guarded(|| tier_frame_time(&sphere)) {
+        Ok(millis) => println!("  rendimiento:        {:.2} ms por esfera de {}px -> {}", millis, TIER_SIZE, tier(millis)),
+        Err(detail) => check("prueba de rendimiento", Err(detail)),
+    }
+    println!();
+
+    if failures == 0 {
+        println!("Todo correcto");
+        0
+    } else {
+        println!("{} comprobaciones fallidas", failures);
+        1
+    }
+}
+
+// Convierte un pánico en un fallo con su mensaje en lugar de abortar el resto de pruebas
+fn guarded<T>(test: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(test)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|text| text.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "pánico sin mensaje".to_string());
+        Err(format!("pánico: {}", message))
+    })
+}
+
+// ============= TRIÁNGULOS DE REFERENCIA =============
+
+fn screen_vertex(x: f32, y: f32) -> Vertex {
+    Vertex::new(Vec3::new(x, y, 0.5), Vec3::new(0.0, 0.0, 1.0), Vec2::zeros())
+}
+
+fn rasterize(triangles: &[[(f32, f32); 3]]) -> Vec<Fragment> {
+    rasterize_samples(triangles, false)
+}
+
+fn rasterize_samples(triangles: &[[(f32, f32); 3]], multisample: bool) -> Vec<Fragment> {
+    let mut fragments = Vec::new();
+    for [a, b, c] in triangles {
+        triangle(&screen_vertex(a.0, a.1), &screen_vertex(b.0, b.1), &screen_vertex(c.0, c.1), (BUFFER_SIZE, BUFFER_SIZE), multisample, &mut fragments);
+    }
+    fragments
+}
+
+// Veces que se ha escrito cada píxel del buffer de prueba
+fn coverage(fragments: &[Fragment]) -> Result<Vec<u32>, String> {
+    let mut counts = vec![0u32; BUFFER_SIZE * BUFFER_SIZE];
+    for fragment in fragments {
+        if !fragment.depth.is_finite() {
+            return Err(format!("profundidad no finita en ({}, {})", fragment.position.x, fragment.position.y));
+        }
+        let (x, y) = (fragment.position.x as usize, fragment.position.y as usize);
+        if x >= BUFFER_SIZE || y >= BUFFER_SIZE {
+            return Err(format!("fragmento fuera del triángulo en ({}, {})", x, y));
+        }
+        counts[y * BUFFER_SIZE + x] += 1;
+    }
+    Ok(counts)
+}
+
+// Rectángulo con bordes fuera de la rejilla partido por la diagonal: cada centro de píxel
+// dentro del rectángulo se dibuja exactamente una vez
+fn square_is_watertight() -> Result<String, String> {
+    let (x0, y0, x1, y1) = (4.3, 5.7, 60.2, 58.1);
+    let fragments = rasterize(&[[(x0, y0), (x1, y0), (x1, y1)], [(x0, y0), (x1, y1), (x0, y1)]]);
+    let counts = coverage(&fragments)?;
+
+    let inside = |value: usize, min: f32, max: f32| value as f32 + 0.5 > min && (value as f32 + 0.5) < max;
+    for y in 0..BUFFER_SIZE {
+        for x in 0..BUFFER_SIZE {
+            let expected = u32::from(inside(x, x0, x1) && inside(y, y0, y1));
+            let count = counts[y * BUFFER_SIZE + x];
+            if count != expected {
+                return Err(format!("píxel ({}, {}) dibujado {} veces (esperado {})", x, y, count, expected));
+            }
+        }
+    }
+    Ok(format!("{} píxeles", fragments.len()))
+}
+
+// Ocho triángulos alrededor de un centro fuera de la rejilla: ninguna arista compartida
+// dibuja dos veces y el total coincide con el área del octógono
+fn fan_has_no_overlaps() -> Result<String, String> {
+    let center = (32.25, 31.6);
+    let radius = 25.0;
+    let corners: Vec<(f32, f32)> = (0..=8)
+        .map(|i| {
+            let angle = i as f32 * std::f32::consts::TAU / 8.0 + 0.1;
+            (center.0 + radius * angle.cos(), center.1 + radius * angle.sin())
+        })
+        .collect();
+    let triangles: Vec<[(f32, f32); 3]> = corners.windows(2).map(|pair| [center, pair[0], pair[1]]).collect();
+    let fragments = rasterize(&triangles);
+    let counts = coverage(&fragments)?;
+
+    if let Some(index) = counts.iter().position(|&count| count > 1) {
+        return Err(format!("píxel ({}, {}) dibujado {} veces", index % BUFFER_SIZE, index / BUFFER_SIZE, counts[index]));
+    }
+    let area = 2.0 * 2.0f32.sqrt() * radius * radius;
+    let perimeter = 8.0 * 2.0 * radius * (std::f32::consts::PI / 8.0).sin();
+    if (fragments.len() as f32 - area).abs() > perimeter / 2.0 {
+        return Err(format!("{} píxeles para un área de {:.0}", fragments.len(), area));
+    }
+    Ok(format!("{} píxeles", fragments.len()))
+}
+
+fn degenerate_is_empty() -> Result<String, String> {
+    let fragments = rasterize(&[[(3.0, 3.0), (30.0, 30.0), (60.0, 60.0)], [(10.0, 10.0), (10.0, 10.0), (40.0, 12.0)]]);
+    if fragments.is_empty() {
+        Ok("sin fragmentos".to_string())
+    } else {
+        Err(format!("{} fragmentos", fragments.len()))
+    }
+}
+
+// Con antialiasing, el mismo cuadrado: ninguna muestra se cubre dos veces, el interior queda
+// entero y los bordes oblicuos o fuera de la rejilla dan píxeles con cobertura parcial
+fn multisample_edges_are_shared() -> Result<String, String> {
+    let (x0, y0, x1, y1) = (4.3, 5.7, 60.2, 58.1);
+    let fragments = rasterize_samples(&[[(x0, y0), (x1, y0), (x1, y1)], [(x0, y0), (x1, y1), (x0, y1)]], true);
+    let mut masks = vec![0u8; BUFFER_SIZE * BUFFER_SIZE];
+    for fragment in &fragments {
+        let (x, y) = (fragment.position.x as usize, fragment.position.y as usize);
+        let mask = &mut masks[y * BUFFER_SIZE + x];
+        if *mask & fragment.coverage != 0 {
+            return Err(format!("muestra del píxel ({}, {}) cubierta dos veces", x, y));
+        }
+        *mask |= fragment.coverage;
+    }
+    let full = masks.iter().filter(|&&mask| mask == FULL_COVERAGE).count();
+    let partial = masks.iter().filter(|&&mask| mask != 0 && mask != FULL_COVERAGE).count();
+    let expected_full = (x1.floor() - x0.ceil()) as usize * (y1.floor() - y0.ceil()) as usize;
+    if full < expected_full || partial == 0 {
+        return Err(format!("{} píxeles enteros (al menos {}) y {} de borde", full, expected_full, partial));
+    }
+    Ok(format!("{} enteros, {} de borde", full, partial))
+}
+
+// Vértices proyectados casi al infinito: el triángulo se recorta y cubre el buffer entero una
+// sola vez; con infinitos o NaN, sin pánico ni desbordamiento
+fn huge_triangle_is_safe() -> Result<String, String> {
+    let fragments = rasterize(&[[(-1.0e9, -1.0e9), (1.0e9, -1.0e9), (0.0, 1.0e9)]]);
+    if let Some(index) = coverage(&fragments)?.iter().position(|&count| count != 1) {
+        return Err(format!("píxel ({}, {}) sin cubrir o repetido", index % BUFFER_SIZE, index / BUFFER_SIZE));
+    }
+    coverage(&rasterize(&[
+        [(f32::INFINITY, 0.0), (10.0, 10.0), (0.0, f32::NEG_INFINITY)],
+        [(f32::NAN, 0.0), (10.0, 10.0), (20.0, 0.0)],
+    ]))?;
+    Ok("recortado".to_string())
+}
+
+// ============= SHADERS =============
+
+fn shaders() -> Vec<(&'static str, CelestialBody)> {
+    vec![
+        ("Sol", CelestialBody::Sun),
+        ("enana roja", CelestialBody::Star(StarClass::M)),
+        ("gigante azul", CelestialBody::Star(StarClass::O)),
+        ("Tierra", CelestialBody::Earth),
+        ("Júpiter", CelestialBody::Jupiter),
+        ("Marte", CelestialBody::Mars),
+        ("Saturno", CelestialBody::Saturn),
+        ("anillos", CelestialBody::Ring),
+        ("Luna", CelestialBody::Moon),
+        ("planeta de lava", CelestialBody::LavaPlanet),
+        ("planeta helado", CelestialBody::IcePlanet),
+        ("planeta alienígena", CelestialBody::AlienPlanet),
+        ("Urano", CelestialBody::Uranus),
+        ("Neptuno", CelestialBody::Neptune),
+        ("procedural", CelestialBody::Procedural(ProceduralPlanet::from_seed(7))),
+        ("Ío", CelestialBody::Io),
+        ("Europa", CelestialBody::Europa),
+        ("Ganímedes", CelestialBody::Ganymede),
+        ("Calisto", CelestialBody::Callisto),
+        ("Fobos", CelestialBody::Phobos),
+        ("Deimos", CelestialBody::Deimos),
+        ("nubes de tormenta", CelestialBody::StormClouds),
+    ]
+}
+
+// Esfera unitaria en el origen vista de frente, con el Sol arriba a la izquierda
+fn sphere_uniforms(body: CelestialBody, size: usize) -> Uniforms {
+    let camera_position = Vec3::new(0.0, 0.0, 3.2);
+    let view_matrix = nalgebra_glm::look_at(&camera_position, &Vec3::zeros(), &Vec3::new(0.0, 1.0, 0.0));
+    let projection_matrix = Projection::default().matrix(size as f32, size as f32);
+    let model_matrix = crate::math::create_model_matrix(Vec3::zeros(), 1.0, Vec3::new(0.4, 0.8, 0.0));
+    Uniforms {
+        view_matrix,
+        projection_matrix,
+        viewport_matrix: create_viewport_matrix(size as f32, size as f32),
+        time: 12.0,
+        current_shader: body,
+        light_position: Vec3::new(-300.0, 200.0, 400.0),
+        camera_position,
+        detail_level: 1.0,
+        light_intensity: 1.0,
+        model_matrix: Backend::mat4(&model_matrix),
+        mvp_matrix: Backend::mat4(&(projection_matrix * view_matrix * model_matrix)),
+        normal_matrix: Backend::mat3(&create_normal_matrix(&model_matrix)),
+        previous_view_projection: None,
+        previous_mvp_matrix: None,
+        jitter: Vec2::zeros(),
+        object_id: NO_OBJECT,
+        tweaks: ShaderTweaks::for_body(body),
+        params: ShaderParams::for_body(body),
+    }
+}
+
+fn shade_sphere(body: CelestialBody, mesh: &[Vertex], size: usize) -> Result<String, String> {
+    let mut framebuffer = Framebuffer::new(size, size);
+    framebuffer.set_background_color(BACKGROUND);
+    framebuffer.clear();
+    render(&mut framebuffer, &sphere_uniforms(body, size), &DrawState::OPAQUE, mesh);
+
+    if let Some(index) = framebuffer.zbuffer.iter().position(|depth| depth.is_nan()) {
+        return Err(format!("profundidad NaN en ({}, {})", index % size, index / size));
+    }
+    let drawn = framebuffer.buffer.iter().filter(|&&pixel| pixel != BACKGROUND).count();
+    // Los anillos solo existen cerca del ecuador de la malla: basta con que dibujen algo
+    if drawn == 0 {
+        return Err("no dibujó ningún píxel".to_string());
+    }
+    Ok(format!("{} píxeles", drawn))
+}
+
+// ============= CAPACIDADES =============
+
+fn simd_features() -> Vec<&'static str> {
+    #[allow(unused_mut)]
+    let mut features = Vec::new();
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("sse4.1") {
+            features.push("SSE4.1");
+        }
+        if is_x86_feature_detected!("avx2") {
+            features.push("AVX2");
+        }
+        if is_x86_feature_detected!("fma") {
+            features.push("FMA");
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            features.push("NEON");
+        }
+    }
+    features
+}
+
+// Mejor tiempo de varias esferas de Júpiter (el shader más caro) llenando el buffer
+fn tier_frame_time(sphere: &SphereLod) -> Result<f64, String> {
+    let mesh = sphere.select(TIER_SIZE as f32);
+    let uniforms = sphere_uniforms(CelestialBody::Jupiter, TIER_SIZE);
+    let mut framebuffer = Framebuffer::new(TIER_SIZE, TIER_SIZE);
+    let mut best = f64::MAX;
+    for _ in 0..TIER_RUNS {
+        framebuffer.clear();
+        let start = Instant::now();
+        render(&mut framebuffer, &uniforms, &DrawState::OPAQUE, &mesh);
+        best = best.min(start.elapsed().as_secs_f64() * 1000.0);
+    }
+    Ok(best)
+}
+
+fn tier(millis: f64) -> &'static str {
+    if millis < 12.0 {
+        "alto: 60 FPS con supersampling cerca de los planetas"
+    } else if millis < 35.0 {
+        "medio: 60 FPS en vista general, menos de cerca"
+    } else {
+        "bajo: se recomienda una ventana más pequeña"
+    }
+}
@@ -0,0 +1,175 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind};
+use serde::Deserialize;
+use crate::audio::AudioConfig;
+use crate::free_flight::FlightConfig;
+use crate::lighting::Lighting;
+use crate::perf_history::PerformanceConfig;
+use crate::retro::RetroConfig;
+use crate::settings_menu::RenderConfig;
+use crate::theme::Theme;
+use crate::watchdog::WatchdogConfig;
+
+// ============= AJUSTES DEL USUARIO (settings.toml) =============
+// Preferencias que no forman parte de la escena: el tema de la interfaz ([ui]), la
+// atenuación de la luz del Sol con la exposición automática ([lighting]), el vigilante de
+// frames lentos ([watchdog]), la calidad de partida con su historial ([performance]), el
+// filtro del modo retro ([retro]), la cámara del vuelo libre ([flight]), los volúmenes del
+// sonido ([audio]) y los ajustes de calidad del menú (Esc), que se guardan en [render].
+// Si no se pasa --settings se lee settings.toml del directorio actual cuando existe; sin
+// archivo se usan los valores de fábrica.
+
+pub const DEFAULT_PATH: &str = "settings.toml";
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub ui: Theme,
+    pub lighting: Lighting,
+    pub watchdog: WatchdogConfig,
+    pub performance: PerformanceConfig,
+    pub render: RenderConfig,
+    pub retro: RetroConfig,
+    pub flight: FlightConfig,
+    pub audio: AudioConfig,
+}
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    InvalidOpacity(&'static str),
+    InvalidLabelFade,
+    InvalidLighting(&'static str),
+    InvalidWatchdog(&'static str),
+    InvalidPerformance(&'static str),
+    InvalidRender(&'static str),
+    InvalidRetro(&'static str),
+    InvalidFlight(&'static str),
+    InvalidAudio(&'static str),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SettingsError::Io(err) => write!(f, "no se pudieron leer los ajustes: {}", err),
+            SettingsError::Parse(err) => write!(f, "ajustes inválidos: {}", err),
+            SettingsError::InvalidOpacity(field) => write!(f, "[ui] {} debe estar entre 0 y 1", field),
+            SettingsError::InvalidLabelFade => write!(f, "[ui] label_fade_end debe ser mayor que label_fade_start (y este no negativo)"),
+            SettingsError::InvalidLighting(message) => write!(f, "[lighting] {}", message),
+            SettingsError::InvalidWatchdog(message) => write!(f, "[watchdog] {}", message),
+            SettingsError::InvalidPerformance(message) => write!(f, "[performance] {}", message),
+            SettingsError::InvalidRender(message) => write!(f, "[render] {}", message),
+            SettingsError::InvalidRetro(message) => write!(f, "[retro] {}", message),
+            SettingsError::InvalidFlight(message) => write!(f, "[flight] {}", message),
+            SettingsError::InvalidAudio(field) => write!(f, "[audio] {} debe estar entre 0 y 1", field),
+        }
+    }
+}
+
+impl Settings {
+    // Ruta explícita: el archivo tiene que existir. Sin ruta, settings.toml es opcional
+    pub fn load(path: Option<&str>) -> Result<Self, SettingsError> {
+        let contents = match fs::read_to_string(path.unwrap_or(DEFAULT_PATH)) {
+            Ok(contents) => contents,
+            Err(err) if path.is_none() && err.kind() == ErrorKind::NotFound => return Ok(Settings::default()),
+            Err(err) => return Err(SettingsError::Io(err)),
+        };
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, SettingsError> {
+        let settings: Settings = toml::from_str(contents).map_err(SettingsError::Parse)?;
+
+        for (field, value) in [("hud_opacity", settings.ui.hud_opacity), ("panel_opacity", settings.ui.panel_opacity)] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(SettingsError::InvalidOpacity(field));
+            }
+        }
+        if settings.ui.label_fade_start < 0.0 || settings.ui.label_fade_end <= settings.ui.label_fade_start {
+            return Err(SettingsError::InvalidLabelFade);
+        }
+        if settings.lighting.reference_distance <= 0.0 {
+            return Err(SettingsError::InvalidLighting("reference_distance debe ser positiva"));
+        }
+        if !(0.0..=1.0).contains(&settings.lighting.compression) {
+            return Err(SettingsError::InvalidLighting("compression debe estar entre 0 y 1"));
+        }
+        if settings.watchdog.threshold_ms <= 0.0 {
+            return Err(SettingsError::InvalidWatchdog("threshold_ms debe ser positivo"));
+        }
+        if settings.watchdog.consecutive_frames == 0 {
+            return Err(SettingsError::InvalidWatchdog("consecutive_frames debe ser al menos 1"));
+        }
+        if settings.performance.target_frame_ms <= 0.0 {
+            return Err(SettingsError::InvalidPerformance("target_frame_ms debe ser positivo"));
+        }
+        if settings.render.supersample > 4 {
+            return Err(SettingsError::InvalidRender("supersample debe estar entre 0 (adaptativo) y 4"));
+        }
+        if settings.render.target_fps <= 0.0 {
+            return Err(SettingsError::InvalidRender("target_fps debe ser positivo"));
+        }
+        if settings.render.lod_detail <= 0.0 || settings.render.shader_detail <= 0.0 {
+            return Err(SettingsError::InvalidRender("lod_detail y shader_detail deben ser positivos"));
+        }
+        if !(1..=16).contains(&settings.retro.pixel_size) {
+            return Err(SettingsError::InvalidRetro("pixel_size debe estar entre 1 y 16"));
+        }
+        if settings.retro.colors.len() == 1 || settings.retro.colors.iter().any(|&color| color > 0xFFFFFF) {
+            return Err(SettingsError::InvalidRetro("colors necesita al menos 2 colores 0xRRGGBB"));
+        }
+        if !(0.0..=1.0).contains(&settings.retro.scanlines) || !(0.0..=0.5).contains(&settings.retro.curvature) {
+            return Err(SettingsError::InvalidRetro("scanlines debe estar entre 0 y 1 y curvature entre 0 y 0.5"));
+        }
+        if settings.flight.chase_stiffness <= 0.0 || settings.flight.look_stiffness <= 0.0 {
+            return Err(SettingsError::InvalidFlight("chase_stiffness y look_stiffness deben ser positivas"));
+        }
+        let audio = settings.audio;
+        for (field, value) in [("master_volume", audio.master_volume), ("music_volume", audio.music_volume), ("effects_volume", audio.effects_volume)] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(SettingsError::InvalidAudio(field));
+            }
+        }
+        Ok(settings)
+    }
+
+    // Reescribe solo la tabla [render] del archivo (el resto, comentarios incluidos, se queda
+    // como estaba); si el archivo no existe se crea con esa tabla
+    pub fn save_render(path: Option<&str>, render: &RenderConfig) -> io::Result<()> {
+        let path = path.unwrap_or(DEFAULT_PATH);
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+
+        let mut kept = Vec::new();
+        let mut in_render = false;
+        for line in contents.lines() {
+            let header = line.trim_start();
+            if header.starts_with('[') {
+                in_render = header.starts_with("[render]");
+            }
+            if !in_render {
+                kept.push(line);
+            }
+        }
+        while kept.last().is_some_and(|line| line.trim().is_empty()) {
+            kept.pop();
+        }
+
+        let table = toml::to_string(render).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+        // Los comentarios que preceden a la tabla se quedan pegados a ella
+        let mut output = kept.join("\n");
+        match kept.last() {
+            Some(line) if line.trim_start().starts_with('#') => output.push('\n'),
+            Some(_) => output.push_str("\n\n"),
+            None => {}
+        }
+        output.push_str("[render]\n");
+        output.push_str(&table);
+        fs::write(path, output)
+    }
+}
@@ -0,0 +1,198 @@
+use minifb::{Key, KeyRepeat};
+use serde::{Deserialize, Serialize};
+use crate::framebuffer::Framebuffer;
+use crate::input::Input;
+use crate::text::{draw_text, text_width, GLYPH_SIZE};
+use crate::theme;
+
+// ============= MENÚ DE AJUSTES (Esc) =============
+// Ajustes de calidad que se cambian en vivo, sin reiniciar: supersampling, antialiasing de
+// bordes y temporal, desenfoque de movimiento, agresividad del nivel de detalle, detalle de
+// los shaders, descarte de caras traseras, ritmo de frames y el modo retro.
+// ↑/↓ eligen campo y ←/→ cambian su valor; al cerrar el menú (Esc) los cambios se guardan
+// en la tabla [render] de settings.toml. La última entrada cierra la aplicación.
+
+const PADDING: usize = 12;
+const LINE_HEIGHT: usize = GLYPH_SIZE + 4;
+
+const SUPERSAMPLE_STEPS: [u32; 5] = [0, 1, 2, 3, 4];
+const DETAIL_STEPS: [f32; 7] = [0.25, 0.5, 0.75, 1.0, 1.25, 1.5, 2.0];
+const FRAME_CAP_STEPS: [u32; 6] = [0, 30, 60, 90, 120, 144];
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct RenderConfig {
+    pub supersample: u32,       // 0 = resolución adaptativa (ver resolution.rs); 1-4 = factor fijo
+    pub target_fps: f32,        // objetivo de la resolución adaptativa
+    pub msaa: bool,             // antialiasing de bordes con 4 muestras (ver framebuffer.rs)
+    pub taa: bool,              // antialiasing temporal con vectores de movimiento (ver taa.rs)
+    pub motion_blur: bool,      // estelas de lo que se mueve rápido (ver motion_blur.rs)
+    pub lod_detail: f32,        // multiplica el radio en pantalla con el que se elige la esfera
+    pub shader_detail: f32,     // multiplica el nivel de detalle de los shaders (octavas del ruido)
+    pub backface_culling: bool, // descartar las caras de espaldas a la cámara en los cuerpos
+    pub vsync: bool,            // limitar la presentación a 60 Hz (minifb no expone el vsync real)
+    pub frame_cap: u32,         // fotogramas por segundo como máximo; 0 = sin límite
+    pub retro: bool,            // paleta corta y filtro de tubo (ver retro.rs y [retro])
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        RenderConfig {
+            supersample: 0,
+            target_fps: 60.0,
+            msaa: true,
+            taa: true,
+            motion_blur: false,
+            lod_detail: 1.0,
+            shader_detail: 1.0,
+            backface_culling: true,
+            vsync: false,
+            frame_cap: 60,
+            retro: false,
+        }
+    }
+}
+
+pub enum MenuAction {
+    Quit,
+}
+
+// Campos del menú, en orden de pantalla
+const FIELDS: usize = 11;
+const QUIT_FIELD: usize = FIELDS - 1;
+
+pub struct SettingsMenu {
+    open: bool,
+    field: usize,
+    config: RenderConfig,
+    saved: RenderConfig, // lo último guardado, para escribir solo si algo cambió
+}
+
+// Siguiente o anterior valor de una lista de pasos (desde el más cercano al actual)
+fn step_through<T: Copy + PartialOrd>(steps: &[T], current: T, forward: bool) -> T {
+    let index = steps.iter().position(|&step| step >= current).unwrap_or(steps.len() - 1);
+    let index = if forward {
+        (index + 1).min(steps.len() - 1)
+    } else {
+        index.saturating_sub(1)
+    };
+    steps[index]
+}
+
+impl SettingsMenu {
+    pub fn new(config: RenderConfig) -> Self {
+        SettingsMenu {
+            open: false,
+            field: 0,
+            config,
+            saved: config,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn config(&self) -> RenderConfig {
+        self.config
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.field = 0;
+    }
+
+    // Ajustes pendientes de guardar (se marcan como guardados al devolverlos)
+    pub fn take_changes(&mut self) -> Option<RenderConfig> {
+        if self.open || self.config == self.saved {
+            return None;
+        }
+        self.saved = self.config;
+        Some(self.config)
+    }
+
+    pub fn handle_input(&mut self, input: &Input) -> Option<MenuAction> {
+        if input.is_key_pressed(Key::Up, KeyRepeat::No) {
+            self.field = (self.field + FIELDS - 1) % FIELDS;
+        }
+        if input.is_key_pressed(Key::Down, KeyRepeat::No) {
+            self.field = (self.field + 1) % FIELDS;
+        }
+        if self.field == QUIT_FIELD && input.is_key_pressed(Key::Enter, KeyRepeat::No) {
+            return Some(MenuAction::Quit);
+        }
+
+        let forward = input.is_key_pressed(Key::Right, KeyRepeat::Yes);
+        if !forward && !input.is_key_pressed(Key::Left, KeyRepeat::Yes) {
+            return None;
+        }
+        let config = &mut self.config;
+        match self.field {
+            0 => config.supersample = step_through(&SUPERSAMPLE_STEPS, config.supersample, forward),
+            1 => config.msaa = !config.msaa,
+            2 => config.taa = !config.taa,
+            3 => config.motion_blur = !config.motion_blur,
+            4 => config.lod_detail = step_through(&DETAIL_STEPS, config.lod_detail, forward),
+            5 => config.shader_detail = step_through(&DETAIL_STEPS, config.shader_detail, forward),
+            6 => config.backface_culling = !config.backface_culling,
+            7 => config.vsync = !config.vsync,
+            8 => config.frame_cap = step_through(&FRAME_CAP_STEPS, config.frame_cap, forward),
+            9 => config.retro = !config.retro,
+            _ => {}
+        }
+        None
+    }
+
+    fn lines(&self) -> Vec<(String, u32)> {
+        let theme = theme::current();
+        let config = &self.config;
+        let on_off = |value: bool| if value { "sí" } else { "no" };
+        let supersample = match config.supersample {
+            0 => format!("adaptativo ({:.0} fps)", config.target_fps),
+            factor => format!("{}x", factor),
+        };
+        let frame_cap = match config.frame_cap {
+            0 => "sin límite".to_string(),
+            fps => format!("{} fps", fps),
+        };
+        let fields = [
+            format!("Supersampling:        < {} >", supersample),
+            format!("Antialiasing 4x:      < {} >", on_off(config.msaa)),
+            format!("Suavizado temporal:   < {} >", on_off(config.taa)),
+            format!("Estela de movimiento: < {} >", on_off(config.motion_blur)),
+            format!("Nivel de detalle:     < {:.2} >", config.lod_detail),
+            format!("Detalle de shaders:   < {:.2} >", config.shader_detail),
+            format!("Caras traseras fuera: < {} >", on_off(config.backface_culling)),
+            format!("Vsync (60 Hz):        < {} >", on_off(config.vsync)),
+            format!("Límite de frames:     < {} >", frame_cap),
+            format!("Modo retro:           < {} >", on_off(config.retro)),
+            "Salir de la aplicación (Enter)".to_string(),
+        ];
+
+        let mut lines = vec![("Ajustes".to_string(), theme.title)];
+        for (i, field) in fields.into_iter().enumerate() {
+            let (marker, color) = if i == self.field { ("> ", theme.accent) } else { ("  ", theme.text) };
+            lines.push((format!("{}{}", marker, field), color));
+        }
+        lines.push((String::new(), theme.text));
+        lines.push(("↑/↓ campo   ←/→ valor   Esc: guardar y cerrar".to_string(), theme.muted));
+        lines
+    }
+
+    // Panel centrado en la pantalla
+    pub fn draw(&self, screen: &mut Framebuffer) {
+        let theme = theme::current();
+        let lines = self.lines();
+        let width = lines.iter().map(|(line, _)| text_width(line, 1)).max().unwrap_or(0) + PADDING * 2;
+        let height = PADDING * 2 + lines.len() * LINE_HEIGHT;
+        let x = screen.width.saturating_sub(width) / 2;
+        let y = screen.height.saturating_sub(height) / 2;
+        screen.blend_rect(x, y, width, height, theme.panel_background, theme.panel_opacity);
+
+        let mut text_y = (y + PADDING) as i32;
+        for (line, color) in lines {
+            draw_text(screen, (x + PADDING) as i32, text_y, &line, color, 1);
+            text_y += LINE_HEIGHT as i32;
+        }
+    }
+}
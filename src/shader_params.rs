@@ -0,0 +1,281 @@
+use serde::Deserialize;
+use crate::celestial_shaders::CelestialBody;
+use crate::color::Color;
+
+// ============= PALETAS DE LOS SHADERS =============
+// Los colores de los shaders con más capas (Tierra, Marte, Júpiter, Saturno, Urano y
+// Neptuno) dejan de ser literales: cada uno tiene su estructura con los valores de fábrica,
+// que viaja en el CelestialObject y llega al shader por Uniforms. Una escena puede cambiar
+// cualquiera de ellos con una tabla `palette` en el cuerpo, así que un segundo planeta con
+// el shader de la Tierra puede tener océanos morados sin tocar el código:
+//
+// [bodies.palette]
+// ocean_deep = [0.10, 0.02, 0.20]
+// forest = [0.45, 0.12, 0.10]
+//
+// Los umbrales y velocidades que se ajustan con F2 siguen en ShaderTweaks (ver tweaks.rs).
+// Los colores se escriben como en el shader: [r, g, b] de 0.0 a 1.0.
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct EarthParams {
+    pub ocean_deep: Color,
+    pub ocean_shallow: Color,
+    pub forest: Color,
+    pub plains: Color,
+    pub desert: Color,
+    pub mountain: Color,
+    pub snow: Color, // cumbres y casquetes polares
+    pub tundra: Color,
+    pub beach: Color,
+    pub cloud: Color,
+    pub atmosphere: Color, // brillo del limbo (dispersión de Rayleigh)
+}
+
+impl Default for EarthParams {
+    fn default() -> Self {
+        EarthParams {
+            ocean_deep: Color::from_float(0.01, 0.05, 0.15),
+            ocean_shallow: Color::from_float(0.05, 0.25, 0.45),
+            forest: Color::from_float(0.13, 0.38, 0.13),
+            plains: Color::from_float(0.42, 0.48, 0.22),
+            desert: Color::from_float(0.76, 0.60, 0.35),
+            mountain: Color::from_float(0.45, 0.40, 0.35),
+            snow: Color::from_float(0.95, 0.95, 0.98),
+            tundra: Color::from_float(0.55, 0.50, 0.45),
+            beach: Color::from_float(0.88, 0.82, 0.65),
+            cloud: Color::from_float(0.98, 0.98, 1.0),
+            atmosphere: Color::from_float(0.35, 0.55, 0.95),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct MarsParams {
+    pub rust: [Color; 3], // óxido de la superficie, del más oscuro al más claro
+    pub crater: Color,
+    pub ice: Color,       // casquetes de CO2
+    pub dust: Color,      // velo de las tormentas de polvo
+    pub haze: [Color; 2], // atmósfera del limbo: aire limpio y con polvo
+}
+
+impl Default for MarsParams {
+    fn default() -> Self {
+        MarsParams {
+            rust: [
+                Color::from_float(0.6, 0.25, 0.15),
+                Color::from_float(0.7, 0.35, 0.2),
+                Color::from_float(0.8, 0.3, 0.1),
+            ],
+            crater: Color::from_float(0.3, 0.15, 0.1),
+            ice: Color::from_float(0.9, 0.95, 1.0),
+            dust: Color::from_float(0.78, 0.52, 0.32),
+            haze: [Color::from_float(0.9, 0.6, 0.4), Color::from_float(0.8, 0.5, 0.3)],
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct JupiterParams {
+    pub deep: [Color; 2],  // atmósfera profunda bajo las bandas
+    pub bands: [Color; 4], // de la zona más clara al cinturón más oscuro
+    pub vortex: Color,
+    pub storm: [Color; 3], // Gran Mancha Roja: borde, zona media y centro
+    pub white_storm: Color,
+    pub brown_storm: Color,
+    pub high_cloud: Color,
+    pub scatter: Color,
+    pub rim: Color,
+}
+
+impl Default for JupiterParams {
+    fn default() -> Self {
+        JupiterParams {
+            deep: [Color::from_float(0.82, 0.58, 0.35), Color::from_float(0.68, 0.45, 0.28)],
+            bands: [
+                Color::from_float(0.98, 0.88, 0.72),
+                Color::from_float(0.92, 0.78, 0.58),
+                Color::from_float(0.75, 0.52, 0.32),
+                Color::from_float(0.68, 0.45, 0.28),
+            ],
+            vortex: Color::from_float(0.85, 0.65, 0.45),
+            storm: [
+                Color::from_float(0.82, 0.48, 0.28),
+                Color::from_float(0.88, 0.35, 0.18),
+                Color::from_float(0.92, 0.22, 0.12),
+            ],
+            white_storm: Color::from_float(0.95, 0.85, 0.70),
+            brown_storm: Color::from_float(0.65, 0.45, 0.30),
+            high_cloud: Color::from_float(0.98, 0.90, 0.75),
+            scatter: Color::from_float(0.92, 0.78, 0.62),
+            rim: Color::from_float(0.98, 0.82, 0.62),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct SaturnParams {
+    pub deep: [Color; 2],
+    pub bands: [Color; 4], // de la zona más clara a la más oscura
+    pub turbulence: Color,
+    pub wind: Color,         // corrientes en chorro de latitudes medias
+    pub hexagon: [Color; 2], // hexágono polar y su turbulencia
+    pub high_cloud: Color,
+    pub scatter: Color,
+    pub rim: Color,
+}
+
+impl Default for SaturnParams {
+    fn default() -> Self {
+        SaturnParams {
+            deep: [Color::from_float(0.90, 0.85, 0.68), Color::from_float(0.85, 0.80, 0.63)],
+            bands: [
+                Color::from_float(0.98, 0.94, 0.78),
+                Color::from_float(0.93, 0.89, 0.73),
+                Color::from_float(0.88, 0.84, 0.68),
+                Color::from_float(0.84, 0.80, 0.65),
+            ],
+            turbulence: Color::from_float(0.91, 0.87, 0.71),
+            wind: Color::from_float(0.96, 0.92, 0.76),
+            hexagon: [Color::from_float(0.78, 0.74, 0.60), Color::from_float(0.82, 0.78, 0.64)],
+            high_cloud: Color::from_float(0.99, 0.96, 0.82),
+            scatter: Color::from_float(0.95, 0.91, 0.75),
+            rim: Color::from_float(0.99, 0.95, 0.80),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct UranusParams {
+    pub base: [Color; 2], // atmósfera profunda y clara
+    pub band: Color,
+    pub hood: Color,      // capucha polar
+    pub haze: Color,      // neblina de metano del limbo
+}
+
+impl Default for UranusParams {
+    fn default() -> Self {
+        UranusParams {
+            base: [Color::from_float(0.60, 0.82, 0.88), Color::from_float(0.72, 0.90, 0.92)],
+            band: Color::from_float(0.66, 0.86, 0.90),
+            hood: Color::from_float(0.85, 0.95, 0.96),
+            haze: Color::from_float(0.55, 0.80, 0.88),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct NeptuneParams {
+    pub base: [Color; 2], // azul profundo y medio
+    pub band: Color,
+    pub spot: Color,      // Gran Mancha Oscura
+    pub companion: Color, // nubes compañeras de la mancha
+    pub streak: Color,    // vetas de metano
+    pub rim: Color,
+}
+
+impl Default for NeptuneParams {
+    fn default() -> Self {
+        NeptuneParams {
+            base: [Color::from_float(0.12, 0.24, 0.72), Color::from_float(0.22, 0.40, 0.86)],
+            band: Color::from_float(0.16, 0.30, 0.78),
+            spot: Color::from_float(0.05, 0.10, 0.38),
+            companion: Color::from_float(0.90, 0.94, 1.0),
+            streak: Color::from_float(0.92, 0.95, 1.0),
+            rim: Color::from_float(0.35, 0.55, 0.98),
+        }
+    }
+}
+
+// Paleta de un cuerpo; Factory son los colores de fábrica de los shaders sin estructura
+#[derive(Clone, Copy, Default, Debug)]
+pub enum ShaderParams {
+    #[default]
+    Factory,
+    Earth(EarthParams),
+    Mars(MarsParams),
+    Jupiter(JupiterParams),
+    Saturn(SaturnParams),
+    Uranus(UranusParams),
+    Neptune(NeptuneParams),
+}
+
+impl ShaderParams {
+    // Colores de fábrica ya resueltos para el shader del cuerpo
+    pub fn for_body(body: CelestialBody) -> Self {
+        match body {
+            CelestialBody::Earth => ShaderParams::Earth(EarthParams::default()),
+            CelestialBody::Mars => ShaderParams::Mars(MarsParams::default()),
+            CelestialBody::Jupiter => ShaderParams::Jupiter(JupiterParams::default()),
+            CelestialBody::Saturn => ShaderParams::Saturn(SaturnParams::default()),
+            CelestialBody::Uranus => ShaderParams::Uranus(UranusParams::default()),
+            CelestialBody::Neptune => ShaderParams::Neptune(NeptuneParams::default()),
+            _ => ShaderParams::Factory,
+        }
+    }
+
+    // Tabla `palette` de la escena, leída con la estructura del shader del cuerpo
+    pub fn from_palette(body: CelestialBody, palette: Option<&toml::Table>) -> Result<Self, String> {
+        let Some(palette) = palette else {
+            return Ok(ShaderParams::for_body(body));
+        };
+        let value = toml::Value::Table(palette.clone());
+        let params = match body {
+            CelestialBody::Earth => value.try_into().map(ShaderParams::Earth),
+            CelestialBody::Mars => value.try_into().map(ShaderParams::Mars),
+            CelestialBody::Jupiter => value.try_into().map(ShaderParams::Jupiter),
+            CelestialBody::Saturn => value.try_into().map(ShaderParams::Saturn),
+            CelestialBody::Uranus => value.try_into().map(ShaderParams::Uranus),
+            CelestialBody::Neptune => value.try_into().map(ShaderParams::Neptune),
+            _ => return Err("este shader no tiene colores configurables".to_string()),
+        };
+        params.map_err(|err| err.to_string())
+    }
+
+    pub fn earth(&self) -> EarthParams {
+        match self {
+            ShaderParams::Earth(params) => *params,
+            _ => EarthParams::default(),
+        }
+    }
+
+    pub fn mars(&self) -> MarsParams {
+        match self {
+            ShaderParams::Mars(params) => *params,
+            _ => MarsParams::default(),
+        }
+    }
+
+    pub fn jupiter(&self) -> JupiterParams {
+        match self {
+            ShaderParams::Jupiter(params) => *params,
+            _ => JupiterParams::default(),
+        }
+    }
+
+    pub fn saturn(&self) -> SaturnParams {
+        match self {
+            ShaderParams::Saturn(params) => *params,
+            _ => SaturnParams::default(),
+        }
+    }
+
+    pub fn uranus(&self) -> UranusParams {
+        match self {
+            ShaderParams::Uranus(params) => *params,
+            _ => UranusParams::default(),
+        }
+    }
+
+    pub fn neptune(&self) -> NeptuneParams {
+        match self {
+            ShaderParams::Neptune(params) => *params,
+            _ => NeptuneParams::default(),
+        }
+    }
+}
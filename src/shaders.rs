@@ -1,20 +1,11 @@
-use nalgebra_glm::{Vec3, Vec4, Mat3};
+use nalgebra_glm::{Vec2, Vec3};
+use crate::math::{viewport_transform, Backend, MathBackend};
 use crate::vertex::Vertex;
 use crate::Uniforms;
 
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
-  // Transform position
-  let position = Vec4::new(
-    vertex.position.x,
-    vertex.position.y,
-    vertex.position.z,
-    1.0
-  );
-  
-  // Apply model, view, and projection transformations
-  let model_view = uniforms.view_matrix * uniforms.model_matrix;
-  let mvp = uniforms.projection_matrix * model_view;
-  let transformed = mvp * position;
+  // Apply model, view, and projection transformations (MVP precalculada por llamada de dibujo)
+  let transformed = Backend::transform_point(&uniforms.mvp_matrix, &vertex.position);
 
   // Perform perspective division
   let w = transformed.w;
@@ -24,22 +15,28 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     transformed.z / w
   );
   
-  // Convert NDC to screen coordinates
-  let screen_position = Vec3::new(
-    (ndc_position.x + 1.0) * 600.0,  // Asumiendo viewport width de 1200
-    (1.0 - ndc_position.y) * 400.0,  // Asumiendo viewport height de 800
-    ndc_position.z
-  );
+  // Convert NDC to screen coordinates (viewport del framebuffer actual, incluye supersampling)
+  let screen_position = viewport_transform(&uniforms.viewport_matrix, ndc_position);
 
-  // Transform normal
-  let model_mat3 = Mat3::new(
-    uniforms.model_matrix[0], uniforms.model_matrix[1], uniforms.model_matrix[2],
-    uniforms.model_matrix[4], uniforms.model_matrix[5], uniforms.model_matrix[6],
-    uniforms.model_matrix[8], uniforms.model_matrix[9], uniforms.model_matrix[10]
-  );
-  let normal_matrix = model_mat3.transpose().try_inverse().unwrap_or(Mat3::identity());
+  // Posición en el mundo (solo el modelo), para la luz y la vista
+  let world = Backend::transform_point(&uniforms.model_matrix, &vertex.position);
+  let world_position = Vec3::new(world.x, world.y, world.z);
+
+  // Dónde estaba el vértice en el frame anterior, con el modelo y la cámara de entonces y sin
+  // el desplazamiento subpíxel del TAA (solo si algo usa los vectores de movimiento)
+  let velocity = uniforms
+    .previous_mvp_matrix
+    .map(|previous_mvp| Backend::transform_point(&previous_mvp, &vertex.position))
+    .filter(|previous| previous.w > 0.0 && w > 0.0)
+    .map(|previous| {
+      let previous_ndc = Vec3::new(previous.x / previous.w, previous.y / previous.w, previous.z / previous.w);
+      let previous_screen = viewport_transform(&uniforms.viewport_matrix, previous_ndc);
+      Vec2::new(screen_position.x - uniforms.jitter.x - previous_screen.x, screen_position.y - uniforms.jitter.y - previous_screen.y)
+    })
+    .unwrap_or_else(Vec2::zeros);
 
-  let transformed_normal = normal_matrix * vertex.normal;
+  // Transform normal (al mundo, con la inversa traspuesta del modelo)
+  let transformed_normal = Backend::transform_vector(&uniforms.normal_matrix, &vertex.normal);
 
   // Create a new Vertex with transformed attributes
   Vertex {
@@ -47,7 +44,11 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     normal: vertex.normal,
     tex_coords: vertex.tex_coords,
     color: vertex.color,
+    material: vertex.material,
+    emissive: vertex.emissive,
     transformed_position: screen_position,
     transformed_normal,
+    world_position,
+    velocity,
   }
 }
\ No newline at end of file
@@ -0,0 +1,167 @@
+use crate::flight_model::{ShipControls, BOOST};
+use crate::framebuffer::Framebuffer;
+use crate::text::{draw_text, GLYPH_SIZE};
+use crate::theme;
+
+// ============= COMBUSTIBLE, CASCO Y ESCUDO =============
+// Los motores gastan combustible mientras empujan (el principal más que los propulsores de
+// maniobra, y el turbo multiplica el gasto); los giros no gastan. Sin combustible la nave ya
+// no puede empujar: va a la deriva hasta que la remolcan al punto de despegue, y la misión
+// en curso fracasa. Los golpes (rebotes contra los cuerpos y disparos de los drones) gastan
+// primero el escudo y después el casco; con el casco a cero la nave explota. El escudo se
+// recarga solo si pasa un rato sin recibir golpes. Atracada en una estación, la nave carga
+// combustible y repara el casco.
+
+pub const FUEL_CAPACITY: f32 = 100.0;
+pub const HULL_MAX: f32 = 100.0;
+pub const SHIELD_MAX: f32 = 50.0;
+const MAIN_BURN: f32 = 3.0;        // combustible/s con el motor principal a tope
+const RCS_BURN: f32 = 0.75;        // combustible/s por eje de maniobra a tope
+const SHIELD_RECHARGE: f32 = 10.0; // escudo/s
+const SHIELD_DELAY: f32 = 3.0;     // segundos sin golpes antes de empezar a recargar
+const REFUEL_RATE: f32 = 25.0;     // combustible/s atracada
+const REPAIR_RATE: f32 = 20.0;     // casco/s atracada
+const BUMP_DAMAGE: f32 = 0.5;      // daño por cada unidad/s que cambia la velocidad en un rebote
+pub const BOLT_DAMAGE: f32 = 20.0; // daño de un láser de dron
+
+const FUEL_COLOR: u32 = 0xFFB040;
+const HULL_COLOR: u32 = 0x7CFC9A;
+const SHIELD_COLOR: u32 = 0x7FB2FF;
+const LOW_COLOR: u32 = 0xFF5A4A;   // por debajo de LOW_LEVEL las barras se ponen en rojo
+const LOW_LEVEL: f32 = 0.25;
+
+const BAR_LABELS: [&str; 3] = ["Combustible", "Casco", "Escudo"];
+pub const BAR_HEIGHT: usize = GLYPH_SIZE + 4;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ShipSystems {
+    pub fuel: f32,
+    pub hull: f32,
+    pub shield: f32,
+    recharge_delay: f32, // segundos que faltan para que el escudo empiece a recargarse
+}
+
+impl ShipSystems {
+    pub fn new() -> Self {
+        ShipSystems { fuel: FUEL_CAPACITY, hull: HULL_MAX, shield: SHIELD_MAX, recharge_delay: 0.0 }
+    }
+
+    // Gasta el combustible de `dt` segundos con estos mandos. Sin combustible los motores no
+    // empujan: devuelve los mandos que quedan (solo los giros)
+    pub fn burn(&mut self, controls: ShipControls, dt: f32) -> ShipControls {
+        if self.fuel <= 0.0 {
+            return ShipControls { thrust: nalgebra_glm::Vec3::zeros(), boost: false, ..controls };
+        }
+        let boost = if controls.boost { BOOST } else { 1.0 };
+        let thrust = controls.thrust.map(|axis| axis.clamp(-1.0, 1.0));
+        let main = (-thrust.z).max(0.0) * MAIN_BURN;
+        let rcs = (thrust.x.abs() + thrust.y.abs() + thrust.z.max(0.0)) * RCS_BURN;
+        self.fuel = (self.fuel - (main + rcs) * boost * dt).max(0.0);
+        controls
+    }
+
+    pub fn out_of_fuel(&self) -> bool {
+        self.fuel <= 0.0
+    }
+
+    // Un golpe: lo para el escudo y lo que sobra daña el casco. Devuelve true si la nave se rompe
+    pub fn damage(&mut self, amount: f32) -> bool {
+        let absorbed = amount.min(self.shield);
+        self.shield -= absorbed;
+        self.hull = (self.hull - (amount - absorbed)).max(0.0);
+        self.recharge_delay = SHIELD_DELAY;
+        self.hull <= 0.0
+    }
+
+    // Daño de un rebote que cambió la velocidad en `speed_change` unidades/s
+    pub fn bump(&mut self, speed_change: f32) -> bool {
+        self.damage(speed_change * BUMP_DAMAGE)
+    }
+
+    // Recarga del escudo tras SHIELD_DELAY segundos sin golpes
+    pub fn update(&mut self, dt: f32) {
+        if self.recharge_delay > 0.0 {
+            self.recharge_delay = (self.recharge_delay - dt).max(0.0);
+            return;
+        }
+        self.shield = (self.shield + SHIELD_RECHARGE * dt).min(SHIELD_MAX);
+    }
+
+    // Atracada: carga combustible y repara el casco (el escudo se recarga como siempre)
+    pub fn service(&mut self, dt: f32) {
+        self.fuel = (self.fuel + REFUEL_RATE * dt).min(FUEL_CAPACITY);
+        self.hull = (self.hull + REPAIR_RATE * dt).min(HULL_MAX);
+        self.update(dt);
+    }
+
+    // Depósito lleno y casco reparado (para el cartel de la nave atracada)
+    pub fn topped_up(&self) -> bool {
+        self.fuel >= FUEL_CAPACITY && self.hull >= HULL_MAX
+    }
+
+    // Barras de combustible, casco y escudo, una por línea desde (x, y) y `width` de ancho
+    pub fn draw_bars(&self, screen: &mut Framebuffer, x: usize, y: usize, width: usize) {
+        let theme = theme::current();
+        let label_width = BAR_LABELS.iter().map(|label| label.chars().count()).max().unwrap_or(0) * GLYPH_SIZE + GLYPH_SIZE;
+        let bar_width = width.saturating_sub(label_width);
+        let levels = [(self.fuel / FUEL_CAPACITY, FUEL_COLOR), (self.hull / HULL_MAX, HULL_COLOR), (self.shield / SHIELD_MAX, SHIELD_COLOR)];
+        for (row, (label, (level, color))) in BAR_LABELS.iter().zip(levels).enumerate() {
+            let row_y = y + row * BAR_HEIGHT;
+            draw_text(screen, x as i32, row_y as i32, label, theme.text, 1);
+            let color = if level < LOW_LEVEL { LOW_COLOR } else { color };
+            let bar_x = x + label_width;
+            screen.blend_rect(bar_x, row_y, bar_width, GLYPH_SIZE, theme.muted, 0.35);
+            screen.blend_rect(bar_x, row_y, (bar_width as f32 * level.clamp(0.0, 1.0)) as usize, GLYPH_SIZE, color, 0.9);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::Vec3;
+
+    #[test]
+    fn thrust_burns_fuel_until_the_engines_stop() {
+        let mut systems = ShipSystems::new();
+        let full_throttle = ShipControls { thrust: Vec3::new(0.0, 0.0, -1.0), torque: Vec3::x(), boost: false };
+        systems.burn(full_throttle, 1.0);
+        assert!((systems.fuel - (FUEL_CAPACITY - MAIN_BURN)).abs() < 1.0e-4);
+        // Girar no gasta
+        systems.burn(ShipControls { thrust: Vec3::zeros(), ..full_throttle }, 1.0);
+        assert!((systems.fuel - (FUEL_CAPACITY - MAIN_BURN)).abs() < 1.0e-4);
+
+        // Con el depósito vacío solo quedan los giros
+        systems.fuel = 0.01;
+        systems.burn(ShipControls { boost: true, ..full_throttle }, 1.0);
+        assert!(systems.out_of_fuel());
+        let left = systems.burn(full_throttle, 1.0);
+        assert_eq!(left.thrust, Vec3::zeros());
+        assert_eq!(left.torque, Vec3::x());
+
+        // Atracada se llena de nuevo
+        for _ in 0..100 {
+            systems.service(0.1);
+        }
+        assert_eq!(systems.fuel, FUEL_CAPACITY);
+    }
+
+    #[test]
+    fn shield_absorbs_hits_and_recharges_after_a_pause() {
+        let mut systems = ShipSystems::new();
+        assert!(!systems.damage(30.0));
+        assert_eq!((systems.shield, systems.hull), (SHIELD_MAX - 30.0, HULL_MAX));
+        assert!(!systems.damage(40.0));
+        assert_eq!((systems.shield, systems.hull), (0.0, HULL_MAX - 20.0));
+
+        // Recién golpeada no recarga; pasado el retraso, sí
+        systems.update(SHIELD_DELAY * 0.5);
+        assert_eq!(systems.shield, 0.0);
+        systems.update(SHIELD_DELAY);
+        systems.update(1.0);
+        assert!((systems.shield - SHIELD_RECHARGE).abs() < 1.0e-4);
+
+        // Un golpe mayor que escudo y casco rompe la nave
+        assert!(systems.damage(SHIELD_MAX + HULL_MAX));
+    }
+}
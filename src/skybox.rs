@@ -0,0 +1,184 @@
+use std::fmt;
+use std::f32::consts::{PI, TAU};
+use nalgebra_glm::{Mat3, Mat4, Vec3};
+use rayon::prelude::*;
+use serde::Deserialize;
+use crate::draw_state::BlendMode;
+use crate::framebuffer::{Framebuffer, FAR_DEPTH};
+use crate::image::{Image, ImageError};
+use crate::math::create_model_matrix;
+
+// ============= FONDO DE CIELO (SKYBOX) =============
+// Un panorama de la Vía Láctea detrás de la escena. Se admiten dos formas de mapa:
+// - Equirectangular: una sola imagen 2:1 (longitud en horizontal, latitud en vertical).
+// - Cubo: seis caras cuadradas en el orden +X, -X, +Y, -Y, +Z, -Z (derecha, izquierda,
+//   arriba, abajo, delante, detrás), con la convención de los cubemaps de OpenGL.
+// Después de dibujar los cuerpos opacos, cada píxel que ninguna geometría ha cubierto (sigue
+// con la profundidad del plano lejano) suma el color del cielo en la dirección de su rayo.
+// Las imágenes pueden ser PNG o Radiance HDR (ver image.rs).
+// En la escena, con la tabla opcional [skybox]:
+//   panorama    = "assets/milky_way.hdr"                      (equirectangular)
+//   faces       = ["px.png", "nx.png", "py.png", "ny.png", "pz.png", "nz.png"]  (cubo)
+//   orientation = [60.2, 0.0, 0.0]  (grados, ángulos de Euler X/Y/Z como axial_tilt;
+//                                    ~60° inclina el plano galáctico respecto a la eclíptica)
+//   brightness  = 0.6
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SkyboxConfig {
+    pub panorama: Option<String>,
+    pub faces: Option<[String; 6]>,
+    pub orientation: [f32; 3],
+    pub brightness: f32,
+}
+
+impl Default for SkyboxConfig {
+    fn default() -> Self {
+        SkyboxConfig {
+            panorama: None,
+            faces: None,
+            orientation: [0.0; 3],
+            brightness: 1.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SkyboxError {
+    NoImage,
+    Image { path: String, error: ImageError },
+    FaceSize(String),
+}
+
+impl fmt::Display for SkyboxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SkyboxError::NoImage => write!(f, "[skybox] necesita `panorama` o las seis `faces`"),
+            SkyboxError::Image { path, error } => write!(f, "{}: {}", path, error),
+            SkyboxError::FaceSize(path) => write!(f, "la cara '{}' del cubo no es cuadrada o no mide lo mismo que las demás", path),
+        }
+    }
+}
+
+enum EnvironmentMap {
+    Panorama(Image),
+    Cube(Box<[Image; 6]>),
+}
+
+pub struct Skybox {
+    map: EnvironmentMap,
+    to_sky: Mat3, // del mundo al espacio del mapa (inversa de la orientación)
+    brightness: f32,
+    // Color del cielo en cada píxel para la última orientación de la cámara
+    cache: Vec<u32>,
+    cache_key: Option<[f32; 13]>,
+}
+
+impl Skybox {
+    pub fn load(config: &SkyboxConfig) -> Result<Self, SkyboxError> {
+        let load = |path: &String| Image::load(path).map_err(|error| SkyboxError::Image { path: path.clone(), error });
+        let map = match (&config.panorama, &config.faces) {
+            (Some(path), _) => EnvironmentMap::Panorama(load(path)?),
+            (None, Some(paths)) => {
+                let [px, nx, py, ny, pz, nz] = paths;
+                let faces = [load(px)?, load(nx)?, load(py)?, load(ny)?, load(pz)?, load(nz)?];
+                let size = faces[0].width;
+                if let Some(index) = faces.iter().position(|face| face.width != size || face.height != size) {
+                    return Err(SkyboxError::FaceSize(paths[index].clone()));
+                }
+                EnvironmentMap::Cube(Box::new(faces))
+            }
+            (None, None) => return Err(SkyboxError::NoImage),
+        };
+
+        // Misma convención que axial_tilt
+        let [x, y, z] = config.orientation.map(f32::to_radians);
+        let orientation = create_model_matrix(Vec3::zeros(), 1.0, Vec3::new(x, y, z));
+        Ok(Skybox {
+            map,
+            to_sky: nalgebra_glm::mat4_to_mat3(&orientation).transpose(),
+            brightness: config.brightness.max(0.0),
+            cache: Vec::new(),
+            cache_key: None,
+        })
+    }
+
+    // Color del cielo en una dirección del mundo (unitaria)
+    fn sample(&self, direction: Vec3) -> (f32, f32, f32) {
+        let d = self.to_sky * direction;
+        match &self.map {
+            EnvironmentMap::Panorama(image) => {
+                // Longitud desde -Z hacia +X (el centro del panorama mira a -Z) y latitud desde +Y
+                let u = 0.5 + d.x.atan2(-d.z) / TAU;
+                let v = d.y.clamp(-1.0, 1.0).acos() / PI;
+                image.sample(u, v, true)
+            }
+            EnvironmentMap::Cube(faces) => {
+                // Cara según el eje dominante; (sc, tc) son las coordenadas dentro de la cara
+                let (ax, ay, az) = (d.x.abs(), d.y.abs(), d.z.abs());
+                let (face, sc, tc, major) = if ax >= ay && ax >= az {
+                    if d.x > 0.0 { (0, -d.z, -d.y, ax) } else { (1, d.z, -d.y, ax) }
+                } else if ay >= az {
+                    if d.y > 0.0 { (2, d.x, d.z, ay) } else { (3, d.x, -d.z, ay) }
+                } else if d.z > 0.0 {
+                    (4, d.x, -d.y, az)
+                } else {
+                    (5, -d.x, -d.y, az)
+                };
+                faces[face].sample(0.5 * (sc / major + 1.0), 0.5 * (tc / major + 1.0), false)
+            }
+        }
+    }
+
+    // Suma el cielo en los píxeles sin geometría (llamar después de los cuerpos opacos).
+    // Como la nebulosa, el cielo solo se vuelve a muestrear cuando la cámara gira
+    pub fn draw(&mut self, framebuffer: &mut Framebuffer, view_matrix: &Mat4, projection_matrix: &Mat4) {
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        let key = [
+            view_matrix[(0, 0)], view_matrix[(0, 1)], view_matrix[(0, 2)],
+            view_matrix[(1, 0)], view_matrix[(1, 1)], view_matrix[(1, 2)],
+            view_matrix[(2, 0)], view_matrix[(2, 1)], view_matrix[(2, 2)],
+            projection_matrix[(0, 0)], projection_matrix[(1, 1)],
+            width as f32, height as f32,
+        ];
+        if self.cache_key != Some(key) {
+            self.render(width, height, view_matrix, projection_matrix);
+            self.cache_key = Some(key);
+        }
+
+        let rows = framebuffer.buffer.par_chunks_mut(width)
+            .zip(framebuffer.zbuffer.par_chunks(width))
+            .zip(self.cache.par_chunks(width));
+        rows.for_each(|((pixels, depths), sky)| {
+            for ((pixel, &depth), &light) in pixels.iter_mut().zip(depths).zip(sky) {
+                if depth == FAR_DEPTH {
+                    *pixel = BlendMode::Additive.apply(light, *pixel);
+                }
+            }
+        });
+    }
+
+    // Muestrea el mapa en la dirección del rayo de cada píxel
+    fn render(&mut self, width: usize, height: usize, view_matrix: &Mat4, projection_matrix: &Mat4) {
+        // Los ejes de la cámara en el mundo son las filas de la rotación de la vista
+        let right = Vec3::new(view_matrix[(0, 0)], view_matrix[(0, 1)], view_matrix[(0, 2)]);
+        let up = Vec3::new(view_matrix[(1, 0)], view_matrix[(1, 1)], view_matrix[(1, 2)]);
+        let back = Vec3::new(view_matrix[(2, 0)], view_matrix[(2, 1)], view_matrix[(2, 2)]);
+        let (focal_x, focal_y) = (projection_matrix[(0, 0)], projection_matrix[(1, 1)]);
+
+        let mut cache = std::mem::take(&mut self.cache);
+        cache.resize(width * height, 0);
+        let skybox = &*self;
+        cache.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+            let ndc_y = 1.0 - 2.0 * (y as f32 + 0.5) / height as f32;
+            for (x, texel) in row.iter_mut().enumerate() {
+                let ndc_x = 2.0 * (x as f32 + 0.5) / width as f32 - 1.0;
+                let direction = (right * (ndc_x / focal_x) + up * (ndc_y / focal_y) - back).normalize();
+                let (r, g, b) = skybox.sample(direction);
+                let channel = |value: f32, shift: u32| (((value * skybox.brightness).min(1.0) * 255.0) as u32) << shift;
+                *texel = channel(r, 16) | channel(g, 8) | channel(b, 0);
+            }
+        });
+        self.cache = cache;
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+use std::fs;
+use nalgebra_glm::Vec3;
+use serde::{Deserialize, Serialize};
+use crate::components::Orbit;
+use crate::ephemeris::EphemerisClock;
+use crate::{Camera, CelestialObject};
+
+// ============= PARTIDAS GUARDADAS (F5 / F9) =============
+// F5 guarda el estado de la simulación en quicksave.toml y F9 lo recupera, para seguir una
+// simulación larga o volver a un encuadre cuidado otro día: tiempo, reloj de efemérides,
+// pausa, cámara, cuerpo seleccionado y, de cada cuerpo, su posición, su giro y su órbita
+// (que un script puede haber cambiado). Los cuerpos se reconocen por su id, así que la
+// partida sirve mientras la escena los conserve; los planetas añadidos con P se vuelven a
+// crear a partir de su semilla y los ids que la escena ya no tiene se ignoran con un aviso.
+// Las rocas de los cinturones y los cometas dependen solo del tiempo y no se guardan.
+
+pub const DEFAULT_PATH: &str = "quicksave.toml";
+
+const PROCEDURAL_PREFIX: &str = "procedural-";
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "no se pudo acceder a la partida: {}", err),
+            SnapshotError::Parse(err) => write!(f, "partida inválida: {}", err),
+            SnapshotError::Serialize(err) => write!(f, "no se pudo escribir la partida: {}", err),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CameraState {
+    position: [f32; 3],
+    target: [f32; 3],
+    up: [f32; 3],
+}
+
+#[derive(Serialize, Deserialize)]
+struct OrbitState {
+    radius: f32,
+    speed: f32,
+    phase: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BodyState {
+    id: String,
+    position: [f32; 3], // relativa al padre en el grafo de escena
+    rotation: [f32; 3],
+    spin: [f32; 3],
+    scale: f32,
+    orbit: Option<OrbitState>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub time: f32,
+    pub paused: bool,
+    selected: Option<String>,
+    camera: CameraState,
+    pub ephemeris: Option<EphemerisClock>,
+    #[serde(default)]
+    bodies: Vec<BodyState>,
+}
+
+impl Snapshot {
+    pub fn capture(
+        time: f32,
+        paused: bool,
+        camera: &Camera,
+        selected: Option<usize>,
+        objects: &[CelestialObject],
+        ephemeris: Option<&EphemerisClock>,
+    ) -> Self {
+        Snapshot {
+            time,
+            paused,
+            selected: selected.and_then(|index| objects.get(index)).map(|obj| obj.id.clone()),
+            camera: CameraState {
+                position: camera.position.into(),
+                target: camera.target.into(),
+                up: camera.up.into(),
+            },
+            ephemeris: ephemeris.cloned(),
+            bodies: objects
+                .iter()
+                .map(|obj| BodyState {
+                    id: obj.id.clone(),
+                    position: obj.transform.local_translation.into(),
+                    rotation: obj.transform.rotation.into(),
+                    spin: obj.spin.speed.into(),
+                    scale: obj.transform.scale,
+                    orbit: obj.orbit.map(|orbit| OrbitState { radius: orbit.radius, speed: orbit.speed, phase: orbit.phase }),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), SnapshotError> {
+        let contents = toml::to_string(self).map_err(SnapshotError::Serialize)?;
+        fs::write(path, contents).map_err(SnapshotError::Io)
+    }
+
+    pub fn load(path: &str) -> Result<Self, SnapshotError> {
+        let contents = fs::read_to_string(path).map_err(SnapshotError::Io)?;
+        toml::from_str(&contents).map_err(SnapshotError::Parse)
+    }
+
+    // Semillas de los planetas añadidos con P que aún no están en la escena
+    pub fn missing_procedural_seeds(&self, objects: &[CelestialObject]) -> Vec<u64> {
+        self.bodies
+            .iter()
+            .filter(|body| !objects.iter().any(|obj| obj.id == body.id))
+            .filter_map(|body| body.id.strip_prefix(PROCEDURAL_PREFIX)?.parse().ok())
+            .collect()
+    }
+
+    // Devuelve a cada cuerpo su estado guardado; los ids que no están en la escena se devuelven
+    pub fn restore_bodies(&self, objects: &mut [CelestialObject]) -> Vec<&str> {
+        let mut missing = Vec::new();
+        for body in &self.bodies {
+            let Some(obj) = objects.iter_mut().find(|obj| obj.id == body.id) else {
+                missing.push(body.id.as_str());
+                continue;
+            };
+            obj.transform.local_translation = Vec3::from(body.position);
+            obj.transform.rotation = Vec3::from(body.rotation);
+            obj.spin.speed = Vec3::from(body.spin);
+            obj.transform.scale = body.scale;
+            // El centro de la órbita lo pone la escena (el del sistema o el del padre)
+            let center = obj.orbit.map(|orbit| orbit.center).unwrap_or(Orbit::new(0.0, 0.0).center);
+            obj.orbit = body.orbit.as_ref().map(|saved| Orbit { radius: saved.radius, speed: saved.speed, phase: saved.phase, center });
+        }
+        missing
+    }
+
+    pub fn selected(&self, objects: &[CelestialObject]) -> Option<usize> {
+        let id = self.selected.as_ref()?;
+        objects.iter().position(|obj| &obj.id == id)
+    }
+
+    pub fn restore_camera(&self, camera: &mut Camera) {
+        camera.position = Vec3::from(self.camera.position);
+        camera.target = Vec3::from(self.camera.target);
+        camera.up = Vec3::from(self.camera.up);
+    }
+}
@@ -0,0 +1,64 @@
+use nalgebra_glm::Vec3;
+
+// ============= MUELLE CON AMORTIGUAMIENTO CRÍTICO =============
+// Un valor que persigue a un objetivo como una masa unida a él por un muelle con el
+// amortiguamiento justo para no pasarse (crítico): llega lo antes posible sin oscilar. La
+// rigidez k fija la rapidez (frecuencia ω = √k; partiendo del reposo, tras 4/ω segundos
+// queda menos de un 10% del camino). Se integra con la solución exacta de la ecuación, así
+// que es estable con cualquier paso de tiempo y cualquier rigidez.
+
+#[derive(Clone, Copy, Debug)]
+pub struct Spring {
+    value: Vec3,
+    velocity: Vec3,
+}
+
+impl Spring {
+    pub fn new(value: Vec3) -> Self {
+        Spring { value, velocity: Vec3::zeros() }
+    }
+
+    pub fn value(&self) -> Vec3 {
+        self.value
+    }
+
+    // Avanza `dt` segundos hacia `target` y devuelve el valor nuevo
+    pub fn update(&mut self, target: Vec3, stiffness: f32, dt: f32) -> Vec3 {
+        let omega = stiffness.max(0.0).sqrt();
+        // x(t) = objetivo + (c1 + c2·t)·e^(-ωt), con c1 el desplazamiento y c2 = v0 + ω·c1
+        let offset = self.value - target;
+        let slope = self.velocity + offset * omega;
+        let decay = (-omega * dt).exp();
+        self.value = target + (offset + slope * dt) * decay;
+        self.velocity = (slope - (offset + slope * dt) * omega) * decay;
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reaches_the_target_without_overshooting() {
+        let target = Vec3::new(10.0, 0.0, -4.0);
+        let mut spring = Spring::new(Vec3::zeros());
+        let mut previous = f32::MAX;
+        for _ in 0..120 {
+            let distance = (spring.update(target, 60.0, 0.016) - target).magnitude();
+            // Amortiguamiento crítico: la distancia solo baja
+            assert!(distance <= previous, "{} > {}", distance, previous);
+            previous = distance;
+        }
+        assert!(previous < 0.01, "quedó a {}", previous);
+    }
+
+    #[test]
+    fn large_steps_stay_stable() {
+        let mut spring = Spring::new(Vec3::new(1.0, 1.0, 1.0));
+        for _ in 0..10 {
+            let value = spring.update(Vec3::zeros(), 10_000.0, 0.5);
+            assert!(value.magnitude() <= 1.0 && value.iter().all(|axis| axis.is_finite()));
+        }
+    }
+}
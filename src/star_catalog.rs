@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use nalgebra_glm::{Mat4, Vec3};
+use serde::Deserialize;
+use crate::celestial_shaders::star_tint;
+use crate::draw_state::BlendMode;
+use crate::framebuffer::Framebuffer;
+use crate::line::{draw_line, LineStyle};
+use crate::random_system::StarClass;
+
+// ============= CATÁLOGO DE ESTRELLAS REALES =============
+// Las estrellas brillantes del cielo terrestre sobre la esfera celeste, a distancia infinita:
+// cada una se proyecta solo con la orientación de la cámara y se dibuja como un punto suave
+// con el tamaño y el brillo de su magnitud y el color de su tipo espectral. Se dibujan al
+// principio del frame, así que los cuerpos las tapan. Las figuras de las constelaciones
+// (segmentos entre estrellas con nombre) se muestran u ocultan con L.
+// Las coordenadas ecuatoriales (ascensión recta, declinación) se giran a la eclíptica, que es
+// el plano XZ de la escena con el norte eclíptico en +Y y el punto vernal en +X (como las
+// efemérides), de modo que las constelaciones del zodiaco quedan alrededor del plano de las órbitas.
+// En la escena, con la tabla opcional [star_catalog]:
+//   stars           = "scenes/bright_stars.csv"   (name,constellation,ra_hours,dec_deg,magnitude,spectral)
+//   constellations  = "scenes/constellations.csv" (constellation,from,to)
+//   magnitude_limit = 4.5   (no se dibujan las más débiles)
+//   brightness      = 1.0
+
+const OBLIQUITY: f32 = 23.439;      // inclinación del ecuador sobre la eclíptica (grados)
+const STAR_COLUMNS: [&str; 6] = ["name", "constellation", "ra_hours", "dec_deg", "magnitude", "spectral"];
+const LINE_COLUMNS: [&str; 3] = ["constellation", "from", "to"];
+const REFERENCE_HEIGHT: f32 = 800.0; // los tamaños en píxeles son para una pantalla de 800 de alto
+const BASE_RADIUS: f32 = 1.4;        // radio de una estrella en el límite de magnitud
+const RADIUS_PER_MAGNITUDE: f32 = 0.45;
+const LINE_COLOR: u32 = 0x5A78B4;
+const LINE_ALPHA: f32 = 0.45;
+const STAR_WHITENESS: f32 = 0.55;    // mezcla con blanco: star_tint está saturado para los discos
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct StarCatalogConfig {
+    pub stars: String,
+    pub constellations: Option<String>,
+    pub magnitude_limit: f32,
+    pub brightness: f32,
+}
+
+impl Default for StarCatalogConfig {
+    fn default() -> Self {
+        StarCatalogConfig {
+            stars: "scenes/bright_stars.csv".to_string(),
+            constellations: Some("scenes/constellations.csv".to_string()),
+            magnitude_limit: 4.5,
+            brightness: 1.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StarCatalogError {
+    Io { path: String, error: std::io::Error },
+    MissingColumn(&'static str),
+    Row { line: usize, message: String },
+    UnknownStar { line: usize, name: String },
+    Empty,
+}
+
+impl fmt::Display for StarCatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StarCatalogError::Io { path, error } => write!(f, "no se pudo leer '{}': {}", path, error),
+            StarCatalogError::MissingColumn(column) => write!(f, "falta la columna '{}' en la cabecera", column),
+            StarCatalogError::Row { line, message } => write!(f, "línea {}: {}", line, message),
+            StarCatalogError::UnknownStar { line, name } => write!(f, "línea {}: la estrella '{}' no está en el catálogo", line, name),
+            StarCatalogError::Empty => write!(f, "el catálogo no contiene ninguna estrella"),
+        }
+    }
+}
+
+struct CatalogStar {
+    direction: Vec3, // unitaria, en el espacio de la escena
+    magnitude: f32,
+    color: (f32, f32, f32),
+}
+
+pub struct StarCatalog {
+    stars: Vec<CatalogStar>,
+    lines: Vec<(usize, usize)>, // índices de las estrellas que une cada segmento
+    magnitude_limit: f32,
+    brightness: f32,
+}
+
+impl StarCatalog {
+    pub fn load(config: &StarCatalogConfig) -> Result<Self, StarCatalogError> {
+        let read = |path: &str| fs::read_to_string(path).map_err(|error| StarCatalogError::Io { path: path.to_string(), error });
+        let (stars, names) = parse_stars(&read(&config.stars)?)?;
+        let lines = match &config.constellations {
+            Some(path) => parse_lines(&read(path)?, &names)?,
+            None => Vec::new(),
+        };
+        Ok(StarCatalog {
+            stars,
+            lines,
+            magnitude_limit: config.magnitude_limit,
+            brightness: config.brightness.max(0.0),
+        })
+    }
+
+    // Estrellas (y figuras si `show_lines`) sobre el fondo recién borrado
+    pub fn draw(&self, framebuffer: &mut Framebuffer, view_matrix: &Mat4, projection_matrix: &Mat4, show_lines: bool) {
+        let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+        let pixel_scale = height / REFERENCE_HEIGHT;
+        // Solo la rotación de la vista: las estrellas están en el infinito
+        let project = |direction: Vec3| {
+            let view = view_matrix.fixed_view::<3, 3>(0, 0) * direction;
+            if view.z > -0.05 {
+                return None; // detrás de la cámara o demasiado al borde para proyectarla
+            }
+            let ndc_x = projection_matrix[(0, 0)] * view.x / -view.z;
+            let ndc_y = projection_matrix[(1, 1)] * view.y / -view.z;
+            Some(((ndc_x + 1.0) * 0.5 * width, (1.0 - ndc_y) * 0.5 * height))
+        };
+
+        if show_lines {
+            for &(from, to) in &self.lines {
+                if let (Some(a), Some(b)) = (project(self.stars[from].direction), project(self.stars[to].direction)) {
+                    let on_screen = |p: (f32, f32)| p.0 > -width && p.0 < 2.0 * width && p.1 > -height && p.1 < 2.0 * height;
+                    if on_screen(a) || on_screen(b) {
+                        draw_line(framebuffer, a, b, LINE_COLOR, LINE_ALPHA, LineStyle::AntiAliased);
+                    }
+                }
+            }
+        }
+
+        for star in self.stars.iter().filter(|star| star.magnitude <= self.magnitude_limit) {
+            let Some((x, y)) = project(star.direction) else {
+                continue;
+            };
+            // Cada magnitud es un factor 2.5 en flujo (10^-0.4); la escala se comprime para que
+            // las débiles sigan viéndose junto a Sirio
+            let intensity = (10f32.powf(-0.15 * (star.magnitude - 1.0)) * self.brightness).min(1.6);
+            let radius = (BASE_RADIUS + RADIUS_PER_MAGNITUDE * (self.magnitude_limit - star.magnitude)) * pixel_scale;
+            draw_point(framebuffer, x, y, radius, star.color, intensity);
+        }
+    }
+}
+
+// Punto gaussiano sumado al framebuffer
+fn draw_point(framebuffer: &mut Framebuffer, x: f32, y: f32, radius: f32, color: (f32, f32, f32), intensity: f32) {
+    let reach = (radius * 2.0).ceil() as i32;
+    let (cx, cy) = (x.floor() as i32, y.floor() as i32);
+    let sigma2 = (radius * 0.6).max(0.5).powi(2);
+    for py in (cy - reach).max(0)..=(cy + reach).min(framebuffer.height as i32 - 1) {
+        for px in (cx - reach).max(0)..=(cx + reach).min(framebuffer.width as i32 - 1) {
+            let (dx, dy) = (px as f32 + 0.5 - x, py as f32 + 0.5 - y);
+            let glow = intensity * (-(dx * dx + dy * dy) / (2.0 * sigma2)).exp();
+            if glow < 1.0 / 255.0 {
+                continue;
+            }
+            let channel = |value: f32, shift: u32| (((value * glow).min(1.0) * 255.0) as u32) << shift;
+            let light = channel(color.0, 16) | channel(color.1, 8) | channel(color.2, 0);
+            let index = py as usize * framebuffer.width + px as usize;
+            framebuffer.buffer[index] = BlendMode::Additive.apply(light, framebuffer.buffer[index]);
+        }
+    }
+}
+
+// Dirección en la escena de unas coordenadas ecuatoriales
+fn celestial_direction(ra_hours: f32, dec_deg: f32) -> Vec3 {
+    let (ra, dec) = ((ra_hours * 15.0).to_radians(), dec_deg.to_radians());
+    let equatorial = Vec3::new(dec.cos() * ra.cos(), dec.cos() * ra.sin(), dec.sin());
+    // Giro alrededor del punto vernal hasta la eclíptica
+    let (sin_e, cos_e) = OBLIQUITY.to_radians().sin_cos();
+    let ecliptic = Vec3::new(
+        equatorial.x,
+        equatorial.y * cos_e + equatorial.z * sin_e,
+        -equatorial.y * sin_e + equatorial.z * cos_e,
+    );
+    // Eclíptica (x al punto vernal, z al norte) a la escena (norte en +Y, órbitas en XZ)
+    Vec3::new(ecliptic.x, ecliptic.z, -ecliptic.y)
+}
+
+// Fila de un CSV: número de línea y campos
+type CsvRow<'a> = (usize, Vec<&'a str>);
+
+// Filas de un CSV sin líneas vacías ni comentarios, con los índices de las columnas pedidas
+fn csv_rows<'a, const N: usize>(
+    contents: &'a str,
+    columns: [&'static str; N],
+) -> Result<([usize; N], Vec<CsvRow<'a>>), StarCatalogError> {
+    let mut rows = contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+    let (_, header) = rows.next().ok_or(StarCatalogError::Empty)?;
+    let header: Vec<&str> = header.split(',').map(str::trim).collect();
+    let mut indices = [0; N];
+    for (slot, name) in indices.iter_mut().zip(columns) {
+        *slot = header
+            .iter()
+            .position(|column| column.eq_ignore_ascii_case(name))
+            .ok_or(StarCatalogError::MissingColumn(name))?;
+    }
+    Ok((indices, rows.map(|(line, row)| (line, row.split(',').map(str::trim).collect())).collect()))
+}
+
+fn parse_stars(contents: &str) -> Result<(Vec<CatalogStar>, HashMap<String, usize>), StarCatalogError> {
+    let (columns, rows) = csv_rows(contents, STAR_COLUMNS)?;
+    let [name, _, ra, dec, magnitude, spectral] = [0, 1, 2, 3, 4, 5];
+    let mut stars = Vec::new();
+    let mut names = HashMap::new();
+    for (line, fields) in rows {
+        // `column` es la posición en STAR_COLUMNS, no en el fichero
+        let field = |column: usize| {
+            fields.get(columns[column]).copied().filter(|value| !value.is_empty()).ok_or_else(|| StarCatalogError::Row {
+                line,
+                message: format!("falta el valor de '{}'", STAR_COLUMNS[column]),
+            })
+        };
+        let number = |column: usize, range: std::ops::RangeInclusive<f32>| {
+            let value = field(column)?;
+            match value.parse::<f32>() {
+                Ok(number) if range.contains(&number) => Ok(number),
+                _ => Err(StarCatalogError::Row {
+                    line,
+                    message: format!("'{}' debe ser un número entre {} y {} (se leyó '{}')", STAR_COLUMNS[column], range.start(), range.end(), value),
+                }),
+            }
+        };
+
+        let spectral_type = field(spectral)?;
+        let class = StarClass::from_spectral_type(spectral_type)
+            .ok_or_else(|| StarCatalogError::Row { line, message: format!("tipo espectral desconocido '{}'", spectral_type) })?;
+        let (r, g, b) = star_tint(class.temperature()).to_float();
+        let whiten = |channel: f32| channel + (1.0 - channel) * STAR_WHITENESS;
+
+        names.insert(field(name)?.to_string(), stars.len());
+        stars.push(CatalogStar {
+            direction: celestial_direction(number(ra, 0.0..=24.0)?, number(dec, -90.0..=90.0)?),
+            magnitude: number(magnitude, -2.0..=8.0)?,
+            color: (whiten(r), whiten(g), whiten(b)),
+        });
+    }
+    if stars.is_empty() {
+        return Err(StarCatalogError::Empty);
+    }
+    Ok((stars, names))
+}
+
+fn parse_lines(contents: &str, names: &HashMap<String, usize>) -> Result<Vec<(usize, usize)>, StarCatalogError> {
+    let ([_, from, to], rows) = csv_rows(contents, LINE_COLUMNS)?;
+    let mut lines = Vec::new();
+    for (line, fields) in rows {
+        let star = |index: usize| {
+            let name = fields.get(index).copied().unwrap_or_default();
+            names.get(name).copied().ok_or_else(|| StarCatalogError::UnknownStar { line, name: name.to_string() })
+        };
+        lines.push((star(from)?, star(to)?));
+    }
+    Ok(lines)
+}
@@ -0,0 +1,71 @@
+use nalgebra_glm::Vec3;
+use crate::ephemeris::EphemerisClock;
+use crate::scene_graph::SceneGraph;
+use crate::CelestialObject;
+
+// ============= SISTEMAS =============
+// Lo que el bucle principal hace con los cuerpos en cada frame, un sistema por
+// comportamiento y cada uno sobre los componentes que usa (ver components.rs):
+// giro propio -> órbitas locales -> grafo de escena -> posiciones de mundo.
+// Las predicciones de eventos y el buscador de alineaciones repiten el mismo recorrido
+// sobre una copia de los cuerpos (events.rs), así que no se separan del bucle real.
+
+// Spin: suma el giro propio (también en pausa, como siempre)
+pub fn spin(objects: &mut [CelestialObject]) {
+    for obj in objects {
+        obj.transform.rotation += obj.spin.speed;
+    }
+}
+
+// Orbit: coloca cada cuerpo en su órbita; con efemérides, los planetas reales van al
+// ángulo que marca la fecha del reloj
+pub fn orbits(objects: &mut [CelestialObject], time: f32, clock: Option<&EphemerisClock>) {
+    for obj in objects {
+        let Some(orbit) = obj.orbit else {
+            continue;
+        };
+        let angle = clock
+            .and_then(|clock| clock.orbit_angle(&obj.id))
+            .unwrap_or_else(|| orbit.angle(time));
+        obj.transform.local_translation.x = orbit.center.x + angle.cos() * orbit.radius;
+        obj.transform.local_translation.z = orbit.center.z + angle.sin() * orbit.radius;
+        obj.spin.lock_to_orbit(&mut obj.transform, angle);
+    }
+}
+
+// Transform: lleva las posiciones locales al grafo (antes de recalcularlo)
+pub fn write_graph(objects: &[CelestialObject], graph: &mut SceneGraph) {
+    for obj in objects {
+        if let Some(node) = obj.transform.node {
+            graph.set_local(node, nalgebra_glm::translation(&obj.transform.local_translation));
+        }
+    }
+}
+
+// Transform: toma las posiciones de mundo calculadas por el grafo
+pub fn read_graph(objects: &mut [CelestialObject], graph: &SceneGraph) {
+    for obj in objects {
+        obj.transform.translation = match obj.transform.node {
+            Some(node) => graph.world_position(node),
+            None => obj.transform.local_translation,
+        };
+    }
+}
+
+// Transform: guarda la matriz de modelo del frame que se dibujó, antes de moverlo, para la
+// velocidad de sus píxeles en el siguiente
+pub fn remember_models(objects: &mut [CelestialObject]) {
+    for obj in objects {
+        obj.transform.previous_model = Some(obj.transform.model_matrix());
+    }
+}
+
+// LightEmitter: posición de la luz de la escena (el primer cuerpo si ninguno emite)
+pub fn light_position(objects: &[CelestialObject]) -> Vec3 {
+    objects
+        .iter()
+        .find(|obj| obj.light.is_some())
+        .unwrap_or(&objects[0])
+        .transform
+        .translation
+}
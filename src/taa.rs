@@ -0,0 +1,236 @@
+use nalgebra_glm::{Mat4, Vec2};
+use rayon::prelude::*;
+use crate::framebuffer::Framebuffer;
+use crate::motion::{pixel_velocity, SkyMotion};
+
+// ============= ANTIALIASING TEMPORAL (TAA) =============
+// Cada frame la proyección de la escena se desplaza una fracción de píxel distinta (una
+// secuencia de Halton de 8 posiciones), así que frames seguidos muestrean puntos distintos
+// de cada píxel. La resolución mezcla el frame nuevo con la historia acumulada y el
+// resultado converge a una imagen suavizada como con supersampling, sin sombrear más píxeles.
+//
+// Para que lo que se mueve no deje estela, la historia se lee donde estaba cada píxel en el
+// frame anterior (según su vector de movimiento, ver motion.rs) y se recorta al rango de
+// colores de los 3x3 vecinos del frame nuevo: lo que ya no está (un borde que se ha
+// descubierto, una partícula que se ha ido) no puede seguir pintándose.
+
+const JITTER_SEQUENCE: u32 = 8;
+const BLEND: f32 = 0.1; // peso del frame nuevo en la mezcla
+
+#[derive(Default)]
+pub struct TemporalAa {
+    history: Vec<[f32; 3]>, // en coma flotante: con un 10% por frame, en 8 bits se quedaría a medias
+    next: Vec<[f32; 3]>,
+    width: usize,
+    height: usize,
+    frame: u32,
+}
+
+impl TemporalAa {
+    pub fn new() -> Self {
+        TemporalAa::default()
+    }
+
+    // Desplazamiento subpíxel de este frame, en píxeles de la escena (entre -0.5 y 0.5)
+    pub fn jitter(&self) -> Vec2 {
+        let index = self.frame % JITTER_SEQUENCE + 1;
+        Vec2::new(halton(index, 2) - 0.5, halton(index, 3) - 0.5)
+    }
+
+    // Olvida la historia: al desactivarlo, para que no vuelva con una imagen vieja
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+
+    // Mezcla la escena con la historia y deja el resultado en los dos
+    pub fn resolve(&mut self, scene: &mut Framebuffer, sky: &SkyMotion) {
+        let (width, height) = (scene.width, scene.height);
+        let valid = (self.width, self.height) == (width, height) && self.history.len() == width * height;
+
+        if valid {
+            self.next.resize(width * height, [0.0; 3]);
+            let history = &self.history;
+            let frame = &*scene;
+            self.next.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+                // Rango de colores de cada columna en las filas y-1..y+1; el del vecindario 3x3
+                // de un píxel junta el de tres columnas
+                let rows = [y.saturating_sub(1), y, (y + 1).min(height - 1)];
+                let columns: Vec<(u32, u32)> = (0..width)
+                    .map(|x| {
+                        let [above, center, below] = rows.map(|row| frame.buffer[row * width + x]);
+                        (byte_min(byte_min(above, center), below), byte_max(byte_max(above, center), below))
+                    })
+                    .collect();
+                let sky_row = sky.row(y);
+
+                for (x, output) in row.iter_mut().enumerate() {
+                    let color = channels(frame.buffer[y * width + x]);
+                    let (min, max) = columns[x.saturating_sub(1)..(x + 2).min(width)]
+                        .iter()
+                        .fold((u32::MAX, 0), |(min, max), &(column_min, column_max)| (byte_min(min, column_min), byte_max(max, column_max)));
+                    // Vecindario de un solo color (el cielo negro, casi toda la imagen): el
+                    // recorte dejaría la historia en el color de ahora
+                    if min == max {
+                        *output = color;
+                        continue;
+                    }
+                    let (min, max) = (channels(min), channels(max));
+                    let velocity = pixel_velocity(frame, &sky_row, x);
+                    let previous = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) - velocity;
+                    *output = match sample(history, width, height, previous) {
+                        Some(past) => blend(past, color, min, max),
+                        None => color,
+                    };
+                }
+            });
+            std::mem::swap(&mut self.history, &mut self.next);
+            scene.buffer.par_iter_mut().zip(self.history.par_iter()).for_each(|(pixel, color)| *pixel = to_hex(color));
+        } else {
+            self.history.clear();
+            self.history.extend(scene.buffer.iter().map(|&color| channels(color)));
+            self.width = width;
+            self.height = height;
+        }
+
+        self.frame = self.frame.wrapping_add(1);
+    }
+}
+
+// La proyección con el desplazamiento subpíxel del frame: el punto se mueve `jitter` píxeles
+// en pantalla sea cual sea su profundidad (se suma un múltiplo de w a x e y)
+pub fn jittered_projection(projection: &Mat4, jitter: Vec2, width: usize, height: usize) -> Mat4 {
+    // El viewport invierte la y (ver math::create_viewport_matrix)
+    let offset = Vec2::new(2.0 * jitter.x / width as f32, -2.0 * jitter.y / height as f32);
+    let mut jittered = *projection;
+    for column in 0..4 {
+        jittered[(0, column)] += offset.x * projection[(3, column)];
+        jittered[(1, column)] += offset.y * projection[(3, column)];
+    }
+    jittered
+}
+
+// Elemento `index` de la secuencia de Halton en la base dada, en [0, 1)
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+// Lectura bilineal de la historia en una posición de píxel (None fuera de la imagen)
+fn sample(history: &[[f32; 3]], width: usize, height: usize, position: Vec2) -> Option<[f32; 3]> {
+    if position.x < 0.0 || position.y < 0.0 || position.x >= width as f32 || position.y >= height as f32 {
+        return None;
+    }
+    let x = (position.x - 0.5).max(0.0);
+    let y = (position.y - 0.5).max(0.0);
+    let (x0, y0) = (x as usize, y as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+
+    let mut result = [0.0; 3];
+    for (px, py, weight) in [(x0, y0, (1.0 - fx) * (1.0 - fy)), (x1, y0, fx * (1.0 - fy)), (x0, y1, (1.0 - fx) * fy), (x1, y1, fx * fy)] {
+        for (total, channel) in result.iter_mut().zip(history[py * width + px]) {
+            *total += channel * weight;
+        }
+    }
+    Some(result)
+}
+
+// Mínimo y máximo de cada canal de dos colores empaquetados
+fn byte_min(a: u32, b: u32) -> u32 {
+    let (a, b) = (a.to_le_bytes(), b.to_le_bytes());
+    u32::from_le_bytes(std::array::from_fn(|i| a[i].min(b[i])))
+}
+
+fn byte_max(a: u32, b: u32) -> u32 {
+    let (a, b) = (a.to_le_bytes(), b.to_le_bytes());
+    u32::from_le_bytes(std::array::from_fn(|i| a[i].max(b[i])))
+}
+
+// Historia recortada al rango del vecindario y mezclada con el color de ahora
+fn blend(past: [f32; 3], current: [f32; 3], min: [f32; 3], max: [f32; 3]) -> [f32; 3] {
+    let mut mixed = [0.0; 3];
+    for channel in 0..3 {
+        let past = past[channel].max(min[channel]).min(max[channel]);
+        mixed[channel] = past + (current[channel] - past) * BLEND;
+    }
+    mixed
+}
+
+fn to_hex(color: &[f32; 3]) -> u32 {
+    // La conversión a entero satura: basta sumar medio para redondear
+    let [r, g, b] = color.map(|channel| ((channel + 0.5) as u32).min(255));
+    (r << 16) | (g << 8) | b
+}
+
+fn channels(color: u32) -> [f32; 3] {
+    [((color >> 16) & 0xFF) as f32, ((color >> 8) & 0xFF) as f32, (color & 0xFF) as f32]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::Vec4;
+    use crate::math::{create_viewport_matrix, viewport_transform};
+    use crate::motion::CameraMotion;
+
+    const SIZE: usize = 16;
+
+    // Cámara quieta: el fondo no se mueve
+    fn still_sky() -> SkyMotion {
+        let viewport = create_viewport_matrix(SIZE as f32, SIZE as f32);
+        let projection = nalgebra_glm::perspective(1.0, 1.0, 0.1, 100.0);
+        CameraMotion::new().sky(&Mat4::identity(), &projection, &viewport, Vec2::zeros())
+    }
+
+    fn checkerboard() -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+        for (index, pixel) in framebuffer.buffer.iter_mut().enumerate() {
+            *pixel = if (index % SIZE + index / SIZE).is_multiple_of(2) { 0xFFFFFF } else { 0x203040 };
+        }
+        framebuffer
+    }
+
+    #[test]
+    fn still_image_is_kept() {
+        let mut taa = TemporalAa::new();
+        let original = checkerboard().buffer;
+        for _ in 0..4 {
+            let mut frame = checkerboard();
+            taa.resolve(&mut frame, &still_sky());
+            assert_eq!(frame.buffer, original);
+        }
+    }
+
+    #[test]
+    fn history_does_not_outlive_what_left_the_pixel() {
+        let mut taa = TemporalAa::new();
+        taa.resolve(&mut checkerboard(), &still_sky());
+
+        // Un frame vacío: el recorte al vecindario no deja rastro del tablero
+        let mut empty = Framebuffer::new(SIZE, SIZE);
+        taa.resolve(&mut empty, &still_sky());
+        assert!(empty.buffer.iter().all(|&pixel| pixel == 0));
+    }
+
+    #[test]
+    fn jitter_moves_every_depth_by_the_same_pixels() {
+        let viewport = create_viewport_matrix(SIZE as f32, SIZE as f32);
+        let projection = nalgebra_glm::reversed_perspective_rh_zo(1.0, 1.0, 0.1, 100.0);
+        let jitter = Vec2::new(0.25, -0.375);
+        let jittered = jittered_projection(&projection, jitter, SIZE, SIZE);
+        let screen = |matrix: &Mat4, z: f32| {
+            let clip = matrix * Vec4::new(0.3, -0.2, z, 1.0);
+            viewport_transform(&viewport, clip.xyz() / clip.w)
+        };
+        for z in [-0.5, -3.0, -40.0] {
+            let moved = screen(&jittered, z) - screen(&projection, z);
+            assert!((moved.x - jitter.x).abs() < 1e-4 && (moved.y - jitter.y).abs() < 1e-4, "z = {}: {:?}", z, moved);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use nalgebra_glm::{Mat4, Vec2, Vec3, Vec4};
+use crate::celestial_shaders::{surface_height, CelestialBody};
+use crate::vertex::Vertex;
+use crate::CelestialObject;
+
+// ============= MODO TERRENO (ACERCAMIENTO A LA SUPERFICIE) =============
+// Cerca de un cuerpo rocoso la esfera de baja resolución se sustituye por una malla de
+// alturas local, centrada en el punto de la superficie bajo la cámara y extendida hasta
+// el horizonte. Las alturas salen del mismo ruido que el shader del cuerpo, y los vértices
+// siguen en el espacio del modelo, así que el shader colorea la malla igual que la esfera.
+
+// Distancia (en radios del cuerpo, desde su centro) por debajo de la cual se activa
+pub const TERRAIN_DISTANCE: f32 = 3.0;
+
+const GRID_SIZE: usize = 80;         // celdas por lado de la malla
+const RELIEF: f32 = 0.035;           // altura máxima de las montañas (en radios)
+const HORIZON_MARGIN: f32 = 0.15;    // radianes extra más allá del horizonte
+const NEAR_MARGIN: f32 = 0.5;        // triángulos más cerca del plano de la cámara se descartan
+
+// Malla de triángulos (lista plana, de tres en tres como la del OBJ) que sustituye a la
+// esfera del cuerpo, o None si la cámara está lejos o el cuerpo no tiene superficie sólida
+pub fn build_patch(
+    obj: &CelestialObject,
+    model_matrix: &Mat4,
+    camera_position: Vec3,
+    camera_forward: Vec3,
+) -> Option<Vec<Vertex>> {
+    if (camera_position - obj.transform.translation).magnitude() > obj.transform.scale * TERRAIN_DISTANCE {
+        return None;
+    }
+    surface_height(obj.shader.body, Vec3::new(0.0, 1.0, 0.0))?;
+
+    // Cámara en el espacio del modelo (esfera unitaria sin rotar)
+    let inverse_model = model_matrix.try_inverse()?;
+    let local_camera = inverse_model * Vec4::new(camera_position.x, camera_position.y, camera_position.z, 1.0);
+    let local_camera = Vec3::new(local_camera.x, local_camera.y, local_camera.z);
+    let altitude = local_camera.magnitude().max(1.0001);
+    let sub_camera = local_camera / local_camera.magnitude();
+
+    // Base tangente en el punto bajo la cámara
+    let helper = if sub_camera.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+    let tangent_u = sub_camera.cross(&helper).normalize();
+    let tangent_v = sub_camera.cross(&tangent_u);
+
+    // Ángulo hasta el horizonte geométrico: más alto = parche más grande y menos denso
+    let extent = ((1.0 / altitude).acos() + HORIZON_MARGIN).min(1.4);
+
+    // Rejilla de puntos desplazados radialmente según la altura de la superficie
+    let points: Vec<Vec3> = (0..=GRID_SIZE)
+        .flat_map(|row| (0..=GRID_SIZE).map(move |column| (row, column)))
+        .map(|(row, column)| {
+            let a = (column as f32 / GRID_SIZE as f32 * 2.0 - 1.0) * extent;
+            let b = (row as f32 / GRID_SIZE as f32 * 2.0 - 1.0) * extent;
+            let direction = (sub_camera + tangent_u * a.tan() + tangent_v * b.tan()).normalize();
+            direction * ground_radius(obj.shader.body, direction)
+        })
+        .collect();
+
+    // Posiciones en el mundo para descartar lo que queda detrás de la cámara
+    let in_front = |point: &Vec3| {
+        let world = model_matrix * Vec4::new(point.x, point.y, point.z, 1.0);
+        (Vec3::new(world.x, world.y, world.z) - camera_position).dot(&camera_forward) > NEAR_MARGIN
+    };
+    let visible: Vec<bool> = points.iter().map(in_front).collect();
+
+    let stride = GRID_SIZE + 1;
+    let mut vertices = Vec::with_capacity(GRID_SIZE * GRID_SIZE * 6);
+    for row in 0..GRID_SIZE {
+        for column in 0..GRID_SIZE {
+            let i00 = row * stride + column;
+            let i01 = i00 + 1;
+            let i10 = i00 + stride;
+            let i11 = i10 + 1;
+            for [a, b, c] in [[i00, i10, i01], [i01, i10, i11]] {
+                if visible[a] && visible[b] && visible[c] {
+                    push_face(&mut vertices, points[a], points[b], points[c]);
+                }
+            }
+        }
+    }
+
+    Some(vertices)
+}
+
+// Distancia al centro (en radios) del suelo en una dirección del espacio del modelo
+pub fn ground_radius(body: CelestialBody, direction: Vec3) -> f32 {
+    1.0 + surface_height(body, direction).unwrap_or(0.0) * RELIEF
+}
+
+// Triángulo con normal plana (el rasterizador sombrea cada cara con la normal del primer vértice)
+fn push_face(vertices: &mut Vec<Vertex>, a: Vec3, b: Vec3, c: Vec3) {
+    let normal = (b - a).cross(&(c - a)).normalize();
+    for position in [a, b, c] {
+        vertices.push(Vertex::new(position, normal, Vec2::new(0.0, 0.0)));
+    }
+}
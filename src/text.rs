@@ -0,0 +1,462 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use font8x8::{BASIC_UNICODE, BLOCK_UNICODE, BOX_UNICODE, GREEK_UNICODE, LATIN_UNICODE, MISC_UNICODE};
+use crate::framebuffer::Framebuffer;
+
+// ============= TEXTO CON ATLAS DE FUENTE 8x8 =============
+// Todos los glifos (font8x8: ASCII, Latin-1, griego, cajas, bloques y varios, más flechas
+// y marcas de acento propias) se empaquetan una sola vez en un atlas con tres variantes de
+// tamaño: 8 px tal cual, 16 px suavizado con Scale2x y 24 px con Scale3x, así el texto
+// grande no sale en escalones. El texto UTF-8 se "compone" antes de dibujarse: las marcas
+// combinantes (é escrita como e + ´) se unen a su letra, las letras sin glifo propio (č, ō,
+// ő...) se dibujan como letra + marca y la puntuación tipográfica cae a su versión ASCII.
+// Cada glifo ocupa siempre una celda de GLYPH_SIZE * escala, así que el ancho de un texto
+// es su número de glifos compuestos (text_width).
+
+pub const GLYPH_SIZE: usize = 8;
+
+const ATLAS_COLUMNS: usize = 32;
+const ATLAS_SCALES: usize = 3; // variantes de 8, 16 y 24 px
+
+// Marcas combinantes con dibujo propio
+const GRAVE: char = '\u{0300}';
+const ACUTE: char = '\u{0301}';
+const CIRCUMFLEX: char = '\u{0302}';
+const TILDE: char = '\u{0303}';
+const MACRON: char = '\u{0304}';
+const DIAERESIS: char = '\u{0308}';
+const RING: char = '\u{030A}';
+const DOUBLE_ACUTE: char = '\u{030B}';
+const CARON: char = '\u{030C}';
+const CEDILLA: char = '\u{0327}';
+
+// Letras precompuestas = letra base + marca (en el mismo orden dentro de cada par de cadenas)
+const COMPOSITIONS: [(&str, &str, char); 10] = [
+    ("ÀÈÌÒÙàèìòù", "AEIOUaeiou", GRAVE),
+    ("ÁÉÍÓÚÝáéíóúýĆćŃńŚśŹź", "AEIOUYaeiouyCcNnSsZz", ACUTE),
+    ("ÂÊÎÔÛâêîôû", "AEIOUaeiou", CIRCUMFLEX),
+    ("ÃÑÕãñõ", "ANOano", TILDE),
+    ("ĀāĒēĪīŌōŪū", "AaEeIiOoUu", MACRON),
+    ("ÄËÏÖÜäëïöüÿ", "AEIOUaeiouy", DIAERESIS),
+    ("ÅåŮů", "AaUu", RING),
+    ("ŐőŰű", "OoUu", DOUBLE_ACUTE),
+    ("ČčĎďĚěŇňŘřŠšŤťŽž", "CcDdEeNnRrSsTtZz", CARON),
+    ("Çç", "Cc", CEDILLA),
+];
+
+// Glifos dibujados a mano ('#' = píxel encendido), para lo que font8x8 no trae
+const CUSTOM_GLYPHS: [(char, [&str; 8]); 15] = [
+    ('←', ["........", "..#.....", ".##.....", "#######.", ".##.....", "..#.....", "........", "........"]),
+    ('→', ["........", ".....#..", ".....##.", ".#######", ".....##.", ".....#..", "........", "........"]),
+    ('↑', ["...#....", "..###...", ".#####..", "...#....", "...#....", "...#....", "...#....", "........"]),
+    ('↓', ["...#....", "...#....", "...#....", "...#....", ".#####..", "..###...", "...#....", "........"]),
+    ('⊕', ["..###...", ".#.#.#..", "#..#..#.", "#######.", "#..#..#.", ".#.#.#..", "..###...", "........"]),
+    ('…', ["........", "........", "........", "........", "........", "........", "#..#..#.", "........"]),
+    (GRAVE, [".##.....", "...#....", "........", "........", "........", "........", "........", "........"]),
+    (ACUTE, ["....##..", "...#....", "........", "........", "........", "........", "........", "........"]),
+    (CIRCUMFLEX, ["...#....", "..#.#...", "........", "........", "........", "........", "........", "........"]),
+    (TILDE, [".##..#..", "#..##...", "........", "........", "........", "........", "........", "........"]),
+    (MACRON, [".#####..", "........", "........", "........", "........", "........", "........", "........"]),
+    (DIAERESIS, [".##.##..", "........", "........", "........", "........", "........", "........", "........"]),
+    (RING, ["..###...", "..#.#...", "..###...", "........", "........", "........", "........", "........"]),
+    (DOUBLE_ACUTE, ["..#..#..", ".#..#...", "........", "........", "........", "........", "........", "........"]),
+    (CARON, ["..#.#...", "...#....", "........", "........", "........", "........", "........", "........"]),
+];
+
+// La cedilla va debajo de la letra
+const CEDILLA_GLYPH: [&str; 8] = ["........", "........", "........", "........", "........", "........", "...#....", "..##...."];
+
+// Dónde queda el punto x de draw_styled respecto al texto
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Align {
+    #[default]
+    Left,   // x es el borde izquierdo
+    Center, // x es el centro
+    Right,  // x es el borde derecho
+}
+
+// Estilo de un texto: color, escala, alineación y efectos para que se lea sobre fondos claros
+#[derive(Clone, Copy)]
+pub struct TextStyle {
+    pub color: u32,
+    pub scale: usize,
+    pub align: Align,
+    pub alpha: f32,           // opacidad de todo el texto (sombra y contorno incluidos)
+    pub shadow: Option<u32>,  // sombra desplazada abajo a la derecha
+    pub outline: Option<u32>, // contorno de un píxel alrededor de cada glifo
+}
+
+impl TextStyle {
+    pub fn new(color: u32) -> Self {
+        TextStyle {
+            color,
+            scale: 1,
+            align: Align::Left,
+            alpha: 1.0,
+            shadow: None,
+            outline: None,
+        }
+    }
+
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    pub fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_shadow(mut self, color: u32) -> Self {
+        self.shadow = Some(color);
+        self
+    }
+
+    pub fn with_outline(mut self, color: u32) -> Self {
+        self.outline = Some(color);
+        self
+    }
+}
+
+// Una página del atlas: todos los glifos a un tamaño, en filas de ATLAS_COLUMNS celdas
+struct AtlasPage {
+    cell: usize,
+    width: usize,
+    pixels: Vec<bool>,
+}
+
+impl AtlasPage {
+    fn build(glyphs: &[[u8; 8]], scale: usize) -> Self {
+        let cell = GLYPH_SIZE * scale;
+        let width = ATLAS_COLUMNS * cell;
+        let rows = glyphs.len().div_ceil(ATLAS_COLUMNS);
+        let mut pixels = vec![false; width * rows * cell];
+
+        for (index, bits) in glyphs.iter().enumerate() {
+            let bitmap = upscale(bits, scale);
+            let (cell_x, cell_y) = ((index % ATLAS_COLUMNS) * cell, (index / ATLAS_COLUMNS) * cell);
+            for y in 0..cell {
+                for x in 0..cell {
+                    pixels[(cell_y + y) * width + cell_x + x] = bitmap[y * cell + x];
+                }
+            }
+        }
+        AtlasPage { cell, width, pixels }
+    }
+
+    // Píxeles encendidos de un glifo, relativos a la esquina de su celda
+    fn coverage(&self, index: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let (cell_x, cell_y) = ((index % ATLAS_COLUMNS) * self.cell, (index / ATLAS_COLUMNS) * self.cell);
+        (0..self.cell)
+            .flat_map(move |y| (0..self.cell).map(move |x| (x, y)))
+            .filter(move |&(x, y)| self.pixels[(cell_y + y) * self.width + cell_x + x])
+    }
+}
+
+struct FontAtlas {
+    index: HashMap<char, usize>,
+    raised: Vec<bool>, // glifos que llegan a las filas de arriba (mayúsculas): su acento sube
+    pages: Vec<AtlasPage>,
+}
+
+impl FontAtlas {
+    fn build() -> Self {
+        let tables = [&BASIC_UNICODE[..], &LATIN_UNICODE, &GREEK_UNICODE, &MISC_UNICODE, &BOX_UNICODE, &BLOCK_UNICODE];
+        let custom = CUSTOM_GLYPHS
+            .iter()
+            .map(|(c, rows)| (*c, bitmap(rows)))
+            .chain(std::iter::once((CEDILLA, bitmap(&CEDILLA_GLYPH))));
+
+        let mut index = HashMap::new();
+        let mut glyphs: Vec<[u8; 8]> = Vec::new();
+        let font = tables.into_iter().flatten().map(|glyph| (glyph.char(), glyph.byte_array()));
+        for (c, bits) in font.chain(custom) {
+            index.entry(c).or_insert_with(|| {
+                glyphs.push(bits);
+                glyphs.len() - 1
+            });
+        }
+
+        FontAtlas {
+            index,
+            raised: glyphs.iter().map(|bits| bits[0] != 0 || bits[1] != 0).collect(),
+            pages: (1..=ATLAS_SCALES).map(|scale| AtlasPage::build(&glyphs, scale)).collect(),
+        }
+    }
+
+    fn glyph(&self, c: char) -> Option<usize> {
+        self.index.get(&c).copied()
+    }
+}
+
+fn atlas() -> &'static FontAtlas {
+    static ATLAS: OnceLock<FontAtlas> = OnceLock::new();
+    ATLAS.get_or_init(FontAtlas::build)
+}
+
+fn bitmap(rows: &[&str; 8]) -> [u8; 8] {
+    // Bit 0 = píxel más a la izquierda (como font8x8)
+    rows.map(|row| row.bytes().enumerate().filter(|(_, byte)| *byte == b'#').fold(0u8, |bits, (col, _)| bits | (1 << col)))
+}
+
+// ============= ESCALADO SUAVE (Scale2x / Scale3x) =============
+// Amplía el glifo copiando el vecino que continúa una diagonal en lugar de repetir
+// bloques, así las curvas y diagonales de las letras grandes salen lisas
+
+fn upscale(bits: &[u8; 8], scale: usize) -> Vec<bool> {
+    let on = |x: i32, y: i32| (0..8).contains(&x) && (0..8).contains(&y) && bits[y as usize] & (1 << x) != 0;
+    let cell = GLYPH_SIZE * scale;
+    let mut out = vec![false; cell * cell];
+
+    for y in 0..8i32 {
+        for x in 0..8i32 {
+            let e = on(x, y);
+            let (b, d, f, h) = (on(x, y - 1), on(x - 1, y), on(x + 1, y), on(x, y + 1));
+            let block: Vec<bool> = match scale {
+                1 => vec![e],
+                2 => vec![
+                    if d == b && d != h && b != f { d } else { e },
+                    if b == f && b != d && f != h { f } else { e },
+                    if d == h && d != b && h != f { d } else { e },
+                    if h == f && d != h && b != f { f } else { e },
+                ],
+                _ => {
+                    let (a, c, g, i) = (on(x - 1, y - 1), on(x + 1, y - 1), on(x - 1, y + 1), on(x + 1, y + 1));
+                    vec![
+                        if d == b && b != f && d != h { d } else { e },
+                        if (d == b && b != f && d != h && e != c) || (b == f && b != d && f != h && e != a) { b } else { e },
+                        if b == f && b != d && f != h { f } else { e },
+                        if (d == b && b != f && d != h && e != g) || (d == h && d != b && h != f && e != a) { d } else { e },
+                        e,
+                        if (b == f && b != d && f != h && e != i) || (h == f && d != h && b != f && e != c) { f } else { e },
+                        if d == h && d != b && h != f { d } else { e },
+                        if (d == h && d != b && h != f && e != i) || (h == f && d != h && b != f && e != g) { h } else { e },
+                        if h == f && d != h && b != f { f } else { e },
+                    ]
+                }
+            };
+            for (k, value) in block.into_iter().enumerate() {
+                out[(y as usize * scale + k / scale) * cell + x as usize * scale + k % scale] = value;
+            }
+        }
+    }
+    out
+}
+
+// ============= COMPOSICIÓN DEL TEXTO =============
+
+#[derive(Clone, Copy)]
+struct ShapedGlyph {
+    base: char,
+    glyph: Option<usize>, // None = espacio
+    mark: Option<usize>,  // acento dibujado encima (o cedilla debajo)
+}
+
+fn compose(base: char, mark: char) -> Option<char> {
+    COMPOSITIONS.iter().filter(|(_, _, m)| *m == mark).find_map(|(composed, bases, _)| {
+        bases.chars().position(|b| b == base).and_then(|position| composed.chars().nth(position))
+    })
+}
+
+fn decompose(c: char) -> Option<(char, char)> {
+    COMPOSITIONS.iter().find_map(|(composed, bases, mark)| {
+        composed.chars().position(|p| p == c).and_then(|position| bases.chars().nth(position)).map(|base| (base, *mark))
+    })
+}
+
+fn is_combining(c: char) -> bool {
+    ('\u{0300}'..='\u{036F}').contains(&c)
+}
+
+// Caracteres que no ocupan sitio (espacios de ancho cero, selectores de variante, BOM)
+fn is_ignorable(c: char) -> bool {
+    matches!(c, '\u{200B}'..='\u{200F}' | '\u{FE00}'..='\u{FE0F}' | '\u{FEFF}')
+}
+
+// Puntuación tipográfica sin glifo: su equivalente ASCII
+fn fallback(c: char) -> char {
+    match c {
+        '\u{00A0}' | '\u{2007}' | '\u{202F}' => ' ',
+        '‘' | '’' | '‚' | 'ʻ' | 'ʼ' => '\'',
+        '“' | '”' | '„' => '"',
+        '‐' | '‑' | '‒' | '–' | '—' | '−' => '-',
+        '•' | '∙' => '·',
+        _ => c,
+    }
+}
+
+fn shape(text: &str) -> Vec<ShapedGlyph> {
+    let atlas = atlas();
+    let mut glyphs: Vec<ShapedGlyph> = Vec::with_capacity(text.len());
+
+    for c in text.chars() {
+        if is_combining(c) {
+            // La marca se une a la letra anterior: precompuesta si existe, si no encima
+            if let Some(last) = glyphs.last_mut() {
+                match compose(last.base, c).and_then(|composed| atlas.glyph(composed).map(|glyph| (composed, glyph))) {
+                    Some((composed, glyph)) => {
+                        last.base = composed;
+                        last.glyph = Some(glyph);
+                    }
+                    None if last.mark.is_none() => last.mark = atlas.glyph(c),
+                    None => {}
+                }
+            }
+            continue;
+        }
+        if is_ignorable(c) {
+            continue;
+        }
+
+        let c = fallback(c);
+        let shaped = if c.is_whitespace() {
+            ShapedGlyph { base: c, glyph: None, mark: None }
+        } else if let Some(glyph) = atlas.glyph(c) {
+            ShapedGlyph { base: c, glyph: Some(glyph), mark: None }
+        } else if let Some((base, mark)) = decompose(c).filter(|(base, _)| atlas.glyph(*base).is_some()) {
+            ShapedGlyph { base: c, glyph: atlas.glyph(base), mark: atlas.glyph(mark) }
+        } else {
+            ShapedGlyph { base: c, glyph: atlas.glyph('?'), mark: None }
+        };
+        glyphs.push(shaped);
+    }
+    glyphs
+}
+
+// Ancho en píxeles de un texto ya compuesto
+pub fn text_width(text: &str, scale: usize) -> usize {
+    shape(text).len() * GLYPH_SIZE * scale.max(1)
+}
+
+// Dibuja una línea de texto con la esquina superior izquierda en (x, y)
+pub fn draw_text(framebuffer: &mut Framebuffer, x: i32, y: i32, text: &str, color: u32, scale: usize) {
+    draw_styled(framebuffer, x, y, text, &TextStyle::new(color).with_scale(scale));
+}
+
+// Igual que draw_text con sombra, contorno y/o alineación; se pinta por pasadas (sombra,
+// contorno, relleno) para que el contorno de una letra no tape el relleno de la vecina
+pub fn draw_styled(framebuffer: &mut Framebuffer, x: i32, y: i32, text: &str, style: &TextStyle) {
+    let atlas = atlas();
+    let glyphs = shape(text);
+    let width = (glyphs.len() * GLYPH_SIZE * style.scale) as i32;
+    let x = match style.align {
+        Align::Left => x,
+        Align::Center => x - width / 2,
+        Align::Right => x - width,
+    };
+
+    // Escalas 1-3 usan su variante del atlas; las mayores amplían la de 8 px por bloques
+    let (page, factor) = if style.scale <= ATLAS_SCALES { (&atlas.pages[style.scale - 1], 1) } else { (&atlas.pages[0], style.scale) };
+    let advance = (GLYPH_SIZE * style.scale) as i32;
+    let raise = 2 * style.scale as i32;
+    let thickness = style.scale.div_ceil(2) as i32;
+    let cedilla = atlas.glyph(CEDILLA);
+
+    let mut paint = |offset_x: i32, offset_y: i32, spread: i32, color: u32| {
+        for (position, shaped) in glyphs.iter().enumerate() {
+            let Some(glyph) = shaped.glyph else {
+                continue;
+            };
+            let origin_x = x + position as i32 * advance + offset_x;
+            // Sobre las mayúsculas el acento sube por encima de la celda
+            let mark_y = if atlas.raised[glyph] && shaped.mark != cedilla { -raise } else { 0 };
+            let layers = std::iter::once((glyph, 0)).chain(shaped.mark.map(|mark| (mark, mark_y)));
+            for (layer, layer_y) in layers {
+                for (px, py) in page.coverage(layer) {
+                    for sy in 0..factor as i32 {
+                        for sx in 0..factor as i32 {
+                            let gx = origin_x + (px * factor) as i32 + sx;
+                            let gy = y + offset_y + layer_y + (py * factor) as i32 + sy;
+                            for dy in -spread..=spread {
+                                for dx in -spread..=spread {
+                                    let (fx, fy) = (gx + dx, gy + dy);
+                                    if fx < 0 || fy < 0 {
+                                        continue;
+                                    }
+                                    if style.alpha < 1.0 {
+                                        framebuffer.blend_pixel(fx as usize, fy as usize, color, style.alpha);
+                                    } else {
+                                        framebuffer.set_pixel(fx as usize, fy as usize, color);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    if let Some(shadow) = style.shadow {
+        paint(thickness, thickness, 0, shadow);
+    }
+    if let Some(outline) = style.outline {
+        paint(0, 0, thickness, outline);
+    }
+    paint(0, 0, 0, style.color);
+}
+
+// Parte el texto en líneas de como máximo `max_chars` glifos, respetando palabras
+pub fn wrap_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+
+    for word in text.split_whitespace() {
+        let word_len = shape(word).len();
+        let needed = if current.is_empty() { word_len } else { current_len + 1 + word_len };
+        if needed > max_chars && !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        if !current.is_empty() {
+            current.push(' ');
+            current_len += 1;
+        }
+        current.push_str(word);
+        current_len += word_len;
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Columnas con algún píxel encendido, de la primera a la última
+    fn inked_columns(framebuffer: &Framebuffer) -> (usize, usize) {
+        let columns: Vec<usize> = (0..framebuffer.width)
+            .filter(|&x| (0..framebuffer.height).any(|y| framebuffer.buffer[y * framebuffer.width + x] != 0))
+            .collect();
+        (columns[0], columns[columns.len() - 1])
+    }
+
+    #[test]
+    fn alignment_moves_the_text_around_the_anchor() {
+        // Flechas dibujadas a mano: tienen tinta en los dos extremos de la celda
+        let text = "←↑↓→";
+        let width = text_width(text, 2);
+        assert_eq!(width, 4 * GLYPH_SIZE * 2);
+        let mut spans = Vec::new();
+        for align in [Align::Left, Align::Center, Align::Right] {
+            let mut framebuffer = Framebuffer::new(200, 40);
+            draw_styled(&mut framebuffer, 100, 10, text, &TextStyle::new(0xFFFFFF).with_scale(2).with_align(align));
+            spans.push(inked_columns(&framebuffer));
+        }
+        // La tinta es la misma, desplazada medio ancho y un ancho entero
+        let (left, center, right) = (spans[0], spans[1], spans[2]);
+        assert!(left.0 >= 100 && left.1 < 100 + width);
+        assert_eq!((center.0 + width / 2, center.1 + width / 2), left);
+        assert_eq!((right.0 + width, right.1 + width), left);
+    }
+}
@@ -0,0 +1,201 @@
+use std::sync::RwLock;
+use serde::Deserialize;
+use crate::framebuffer::Framebuffer;
+
+// ============= TEMA DE LA INTERFAZ =============
+// Colores, opacidad y colocación de los elementos en pantalla, configurables en la sección
+// [ui] de settings.toml. El tema es global (todos los paneles lo leen al dibujarse) para
+// que pueda cambiarse en caliente sin pasar por cada función de dibujo.
+// Los colores se escriben como enteros hexadecimales de TOML: panel_background = 0x0A0F1E
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+// Esquina (la de fábrica del widget si no se indica) y visibilidad de un widget
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct WidgetPlacement {
+    pub corner: Option<Corner>,
+    pub visible: bool,
+}
+
+impl Default for WidgetPlacement {
+    fn default() -> Self {
+        DEFAULT_PLACEMENT
+    }
+}
+
+const DEFAULT_PLACEMENT: WidgetPlacement = WidgetPlacement { corner: None, visible: true };
+
+#[derive(Deserialize, Clone, Copy, Default, Debug)]
+#[serde(default)]
+pub struct Widgets {
+    pub info_panel: WidgetPlacement, // panel del cuerpo seleccionado
+    pub weather: WidgetPlacement,    // tormentas del cuerpo seleccionado
+    pub clock: WidgetPlacement,      // reloj de efemérides (--date)
+    pub flight: WidgetPlacement,     // cartel del vuelo libre
+    pub mission: WidgetPlacement,    // objetivo de la misión en vuelo libre
+    pub landing: WidgetPlacement,    // cartel del aterrizaje
+    pub notices: WidgetPlacement,    // avisos de marcadores y visita guiada
+    pub profiler: WidgetPlacement,   // tiempos por etapa y FPS (F3)
+    pub minimap: WidgetPlacement,    // vista cenital del sistema
+}
+
+// Widgets que se colocan en una esquina de la pantalla
+#[derive(Clone, Copy)]
+pub enum Widget {
+    InfoPanel,
+    Weather,
+    Clock,
+    Flight,
+    Mission,
+    Landing,
+    Notices,
+    Profiler,
+    Minimap,
+}
+
+impl Widget {
+    fn default_corner(self) -> Corner {
+        match self {
+            Widget::InfoPanel => Corner::TopLeft,
+            Widget::Flight | Widget::Mission | Widget::Landing => Corner::TopRight,
+            Widget::Weather | Widget::Notices => Corner::BottomLeft,
+            Widget::Clock | Widget::Profiler | Widget::Minimap => Corner::BottomRight,
+        }
+    }
+
+    fn placement(self, widgets: &Widgets) -> WidgetPlacement {
+        match self {
+            Widget::InfoPanel => widgets.info_panel,
+            Widget::Weather => widgets.weather,
+            Widget::Clock => widgets.clock,
+            Widget::Flight => widgets.flight,
+            Widget::Mission => widgets.mission,
+            Widget::Landing => widgets.landing,
+            Widget::Notices => widgets.notices,
+            Widget::Profiler => widgets.profiler,
+            Widget::Minimap => widgets.minimap,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct Theme {
+    pub panel_background: u32,
+    pub hud_opacity: f32,   // carteles y paneles sobre la escena
+    pub panel_opacity: f32, // ventanas modales (enciclopedia, buscador)
+    pub title: u32,
+    pub text: u32,
+    pub muted: u32,
+    pub accent: u32,        // campos activos y resaltados
+    pub label: u32,
+    pub label_selected: u32,
+    pub label_outline: u32,
+    pub leader: u32,
+    pub selection_outline: u32, // contorno del cuerpo o la nave seleccionados
+    pub labels: bool,       // nombres de los cuerpos (L los muestra u oculta)
+    pub label_fade_start: f32, // distancia a la cámara a la que los nombres empiezan a apagarse
+    pub label_fade_end: f32,   // y a la que ya no se ven
+    pub leader_lines: bool, // líneas guía de las etiquetas apartadas
+    pub smooth_lines: bool, // líneas suavizadas (Xiaolin Wu) en lugar de escalonadas
+    pub toasts: bool,       // avisos de eventos
+    pub widgets: Widgets,
+}
+
+const DEFAULT_THEME: Theme = Theme {
+    panel_background: 0x0A0F1E,
+    hud_opacity: 0.75,
+    panel_opacity: 0.9,
+    title: 0xFFD24A,
+    text: 0xC8D2E6,
+    muted: 0x7F8AA3,
+    accent: 0x7FE0FF,
+    label: 0xDDE6FF,
+    label_selected: 0xFFD040,
+    label_outline: 0x000000,
+    leader: 0x8090B0,
+    selection_outline: 0xFFD040,
+    labels: true,
+    label_fade_start: 2500.0,
+    label_fade_end: 6000.0,
+    leader_lines: true,
+    smooth_lines: true,
+    toasts: true,
+    widgets: Widgets {
+        info_panel: DEFAULT_PLACEMENT,
+        weather: DEFAULT_PLACEMENT,
+        clock: DEFAULT_PLACEMENT,
+        flight: DEFAULT_PLACEMENT,
+        mission: DEFAULT_PLACEMENT,
+        landing: DEFAULT_PLACEMENT,
+        notices: DEFAULT_PLACEMENT,
+        profiler: DEFAULT_PLACEMENT,
+        minimap: DEFAULT_PLACEMENT,
+    },
+};
+
+impl Default for Theme {
+    fn default() -> Self {
+        DEFAULT_THEME
+    }
+}
+
+static THEME: RwLock<Theme> = RwLock::new(DEFAULT_THEME);
+
+pub fn current() -> Theme {
+    *THEME.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub fn set(theme: Theme) {
+    *THEME.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = theme;
+}
+
+// ============= COLOCACIÓN EN ESQUINAS =============
+// Se crea una por frame: los widgets que comparten esquina se apilan (hacia abajo en las
+// esquinas superiores y hacia arriba en las inferiores) en el orden en que se dibujan.
+
+const MARGIN: usize = 10;
+
+pub struct HudLayout {
+    width: usize,
+    height: usize,
+    used: [usize; 4], // alto ocupado en cada esquina
+}
+
+impl HudLayout {
+    pub fn new(screen: &Framebuffer) -> Self {
+        HudLayout {
+            width: screen.width,
+            height: screen.height,
+            used: [0; 4],
+        }
+    }
+
+    // Esquina superior izquierda del hueco reservado, o None si el widget está oculto
+    pub fn place(&mut self, widget: Widget, width: usize, height: usize) -> Option<(usize, usize)> {
+        let placement = widget.placement(&current().widgets);
+        if !placement.visible {
+            return None;
+        }
+        let corner = placement.corner.unwrap_or(widget.default_corner());
+        let used = &mut self.used[corner as usize];
+        let x = match corner {
+            Corner::TopLeft | Corner::BottomLeft => MARGIN,
+            Corner::TopRight | Corner::BottomRight => self.width.saturating_sub(width + MARGIN),
+        };
+        let y = match corner {
+            Corner::TopLeft | Corner::TopRight => MARGIN + *used,
+            Corner::BottomLeft | Corner::BottomRight => self.height.saturating_sub(MARGIN + *used + height),
+        };
+        *used += height + MARGIN;
+        Some((x, y))
+    }
+}
@@ -3,6 +3,7 @@ use crate::fragment::Fragment;
 use crate::vertex::Vertex;
 use crate::line::line;
 use crate::color::Color;
+use crate::framebuffer::{FULL_COVERAGE, SAMPLES};
 
 pub fn _triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
   let mut fragments = Vec::new();
@@ -15,52 +16,131 @@ pub fn _triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
   fragments
 }
 
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
-  let mut fragments = Vec::new();
-  let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
+// Bits fraccionarios de las coordenadas de pantalla en punto fijo (28.4: 1/16 de píxel)
+const SUBPIXEL_BITS: u32 = 4;
+const SUBPIXEL_ONE: i64 = 1 << SUBPIXEL_BITS;
+const SUBPIXEL_HALF: i64 = SUBPIXEL_ONE / 2;
 
-  let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+// Posiciones de las muestras del antialiasing respecto al centro del píxel, en 1/16 de píxel:
+// la rejilla rotada de 4 muestras de las GPU, que separa bien los bordes casi horizontales y
+// casi verticales. Caen en la rejilla de punto fijo, así que la prueba sigue siendo exacta
+const SAMPLE_OFFSETS: [(i64, i64); SAMPLES] = [(-2, -6), (6, -2), (-6, 2), (2, 6)];
 
-  // Early rejection: si el bounding box es muy pequeño o inválido, saltar
-  if max_x < min_x || max_y < min_y {
-    return fragments;
+// Añade los fragmentos del triángulo a `fragments` (el llamador reutiliza el Vec entre frames).
+// Rasterizado estanco: los vértices se ajustan a una rejilla de 1/16 de píxel y las funciones
+// de arista se evalúan con enteros, así que dos triángulos que comparten arista calculan
+// exactamente el mismo valor en cada centro de píxel. La regla superior-izquierda decide a
+// quién pertenece un píxel que cae justo sobre la arista: ni se dibuja dos veces ni queda
+// una grieta entre ambos.
+//
+// Con `multisample` cada píxel se prueba en las muestras de SAMPLE_OFFSETS en lugar de en su
+// centro y el fragmento lleva qué muestras cubre (ver framebuffer.rs); el color se calcula
+// igual una sola vez por píxel.
+//
+// Solo se recorren los píxeles de `viewport` (ancho y alto del framebuffer). Un triángulo que
+// sale de la banda de guarda (un planeta que llena la pantalla en un vuelo rasante) se recorta
+// antes contra ella y se rasteriza en abanico: las coordenadas en punto fijo siguen pequeñas
+// y el triángulo no desaparece por grande.
+pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, viewport: (usize, usize), multisample: bool, fragments: &mut Vec<Fragment>) {
+  let corners = [v1.transformed_position, v2.transformed_position, v3.transformed_position];
+  if corners.iter().any(|corner| corner.x.is_nan() || corner.y.is_nan() || corner.z.is_nan()) {
+    return;
   }
+  let corners = corners.map(|corner| Vec3::new(
+    corner.x.clamp(-MAX_SCREEN_COORD, MAX_SCREEN_COORD),
+    corner.y.clamp(-MAX_SCREEN_COORD, MAX_SCREEN_COORD),
+    corner.z,
+  ));
 
-  // Limitar el bounding box a un tamaño razonable para evitar procesar triángulos gigantes
-  let box_width = max_x - min_x;
-  let box_height = max_y - min_y;
-  if box_width > 5000 || box_height > 5000 {
-    return fragments; // Triángulo demasiado grande, probablemente fuera de pantalla
+  let guard_band = GuardBand::around(viewport);
+  if corners.iter().all(|corner| guard_band.contains(corner)) {
+    rasterize(corners, v1.transformed_normal, viewport, multisample, fragments);
+    return;
+  }
+  let polygon = guard_band.clip(&corners);
+  for i in 1..polygon.len().saturating_sub(1) {
+    rasterize([polygon[0], polygon[i], polygon[i + 1]], v1.transformed_normal, viewport, multisample, fragments);
   }
+}
 
-  let light_dir = Vec3::new(0.0, 0.0, -1.0);
+fn rasterize(corners: [Vec3; 3], flat_normal: Vec3, viewport: (usize, usize), multisample: bool, fragments: &mut Vec<Fragment>) {
+  let [a, mut b, mut c] = corners;
+  let (fa, mut fb, mut fc) = (to_fixed(&a), to_fixed(&b), to_fixed(&c));
+
+  // Área (doble) en punto fijo; el triángulo degenerado en la rejilla no cubre ningún píxel
+  let mut triangle_area = edge_function_fixed(fa, fb, fc);
+  if triangle_area == 0 {
+    return;
+  }
+  // Ambos sentidos de giro llegan aquí (sin culling): se ordenan para que el interior sea positivo
+  if triangle_area < 0 {
+    std::mem::swap(&mut b, &mut c);
+    std::mem::swap(&mut fb, &mut fc);
+    triangle_area = -triangle_area;
+  }
+
+  // Bounding box de los centros de píxel que puede cubrir, recortado al framebuffer. Las
+  // muestras están a menos de medio píxel del centro: basta un píxel más por cada lado
+  let margin = i64::from(multisample);
+  let min_x = ((fa.0.min(fb.0).min(fc.0) - SUBPIXEL_HALF + SUBPIXEL_ONE - 1).div_euclid(SUBPIXEL_ONE) - margin).max(0);
+  let min_y = ((fa.1.min(fb.1).min(fc.1) - SUBPIXEL_HALF + SUBPIXEL_ONE - 1).div_euclid(SUBPIXEL_ONE) - margin).max(0);
+  let max_x = ((fa.0.max(fb.0).max(fc.0) - SUBPIXEL_HALF).div_euclid(SUBPIXEL_ONE) + margin).min(viewport.0 as i64 - 1);
+  let max_y = ((fa.1.max(fb.1).max(fc.1) - SUBPIXEL_HALF).div_euclid(SUBPIXEL_ONE) + margin).min(viewport.1 as i64 - 1);
 
-  let triangle_area = edge_function(&a, &b, &c);
-  
-  // Early rejection: si el área es casi cero, el triángulo es degenerado
-  if triangle_area.abs() < 0.0001 {
-    return fragments;
+  // Early rejection: ningún centro de píxel dentro del bounding box (o fuera de pantalla)
+  if max_x < min_x || max_y < min_y {
+    return;
   }
+  let box_width = max_x - min_x;
+  let box_height = max_y - min_y;
+
+  let light_dir = Vec3::new(0.0, 0.0, -1.0);
 
   // Pre-reservar espacio aproximado basado en el área del bounding box
   let estimated_fragments = ((box_width * box_height) / 4) as usize;
   fragments.reserve(estimated_fragments.min(1000));
 
+  // Aristas opuestas a cada vértice. Un píxel exactamente sobre una arista solo cuenta si
+  // es superior o izquierda: en las demás se exige valor estrictamente positivo (sesgo -1)
+  let edges = [(fb, fc), (fc, fa), (fa, fb)];
+  let bias = edges.map(|(from, to)| if is_top_left(from, to) { 0 } else { -1 });
+
+  // Funciones de arista en el primer centro de píxel y su incremento por columna y por fila
+  let origin = (min_x * SUBPIXEL_ONE + SUBPIXEL_HALF, min_y * SUBPIXEL_ONE + SUBPIXEL_HALF);
+  let mut row = edges.map(|(from, to)| edge_function_fixed(from, to, origin));
+  let step_x = edges.map(|(from, to)| (to.1 - from.1) * SUBPIXEL_ONE);
+  let step_y = edges.map(|(from, to)| (from.0 - to.0) * SUBPIXEL_ONE);
+
+  // Diferencia de cada función de arista entre el centro y cada muestra
+  let sample_steps = SAMPLE_OFFSETS.map(|(dx, dy)| edges.map(|(from, to)| (to.1 - from.1) * dx + (from.0 - to.0) * dy));
+  // Lo más que se aleja cada función de arista del centro en alguna muestra: con el centro
+  // más lejos que eso de las tres aristas el píxel es entero (o vacío) sin probar muestras
+  let reach: [i64; 3] = std::array::from_fn(|i| sample_steps.iter().map(|steps| steps[i].abs()).max().unwrap_or(0));
+  // Un píxel de borde se sombrea en su centro aunque caiga fuera del triángulo: la
+  // profundidad extrapolada se limita a la de los vértices
+  let (min_depth, max_depth) = (a.z.min(b.z).min(c.z), a.z.max(b.z).max(c.z));
+
+  let inverse_area = 1.0 / triangle_area as f32;
+
   // Iterate over each pixel in the bounding box
   for y in min_y..=max_y {
+    let mut w = row;
     for x in min_x..=max_x {
-      let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+      // Check if the point is inside the triangle (el centro o, con antialiasing, cada muestra)
+      let inside = |offsets: [i64; 3]| (0..3).all(|i| w[i] + offsets[i] + bias[i] >= 0);
+      let coverage = if !multisample || (0..3).all(|i| w[i] + bias[i] >= reach[i]) {
+        if inside([0; 3]) { FULL_COVERAGE } else { 0 }
+      } else if (0..3).any(|i| w[i] + bias[i] < -reach[i]) {
+        0
+      } else {
+        (0..SAMPLES).filter(|&sample| inside(sample_steps[sample])).fold(0u8, |mask, sample| mask | 1 << sample)
+      };
+      if coverage != 0 {
+        // Barycentric coordinates
+        let (w1, w2, w3) = (w[0] as f32 * inverse_area, w[1] as f32 * inverse_area, w[2] as f32 * inverse_area);
 
-      // Calculate barycentric coordinates
-      let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
-
-      // Check if the point is inside the triangle
-      if w1 >= 0.0 && w1 <= 1.0 && 
-         w2 >= 0.0 && w2 <= 1.0 &&
-         w3 >= 0.0 && w3 <= 1.0 {
         // Interpolate normal
-        // let normal = v1.transformed_normal * w1 + v2.transformed_normal * w2 + v3.transformed_normal * w3;
-        let normal = v1.transformed_normal;
+        let normal = flat_normal;
         let normal = normal.normalize();
 
         // Calculate lighting intensity
@@ -70,35 +150,311 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
         let base_color = Color::new(100, 100, 100); // Medium gray
         let lit_color = base_color * intensity;
 
-        // Interpolate depth
-        // let depth = a.z * w1 + b.z * w2 + c.z * w3;
-        let depth = a.z;
+        // Interpolate depth (z ya dividida por w: lineal en pantalla)
+        let depth = (a.z * w1 + b.z * w2 + c.z * w3).clamp(min_depth, max_depth);
 
-        fragments.push(Fragment::new(x as f32, y as f32, lit_color, depth));
+        fragments.push(Fragment::new(x as f32, y as f32, lit_color, depth).with_coverage(coverage));
+      }
+      for i in 0..3 {
+        w[i] += step_x[i];
       }
     }
+    for i in 0..3 {
+      row[i] += step_y[i];
+    }
   }
+}
 
-  fragments
+// Triángulo más pequeño que un píxel (ver assemble_triangle): un único fragmento en el
+// píxel de su centroide, con la profundidad del centroide
+pub fn triangle_point(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Fragment {
+  let centroid = (v1.transformed_position + v2.transformed_position + v3.transformed_position) / 3.0;
+  Fragment::new(centroid.x.floor().max(0.0), centroid.y.floor().max(0.0), Color::black(), centroid.z)
 }
 
-fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
-    let min_x = v1.x.min(v2.x).min(v3.x).floor() as i32;
-    let min_y = v1.y.min(v2.y).min(v3.y).floor() as i32;
-    let max_x = v1.x.max(v2.x).max(v3.x).ceil() as i32;
-    let max_y = v1.y.max(v2.y).max(v3.y).ceil() as i32;
+// Límite de las coordenadas antes de recortar: un vértice casi en el plano de la cámara puede
+// proyectarse a millones de píxeles (o a infinito)
+const MAX_SCREEN_COORD: f32 = 1.0e7;
+
+// Margen alrededor del framebuffer dentro del que un triángulo se rasteriza sin recortar
+// (píxeles). Recortar cuesta más que recorrer un bounding box ya limitado a la pantalla, así
+// que solo se recortan los triángulos que salen de este margen
+const GUARD_BAND: f32 = 2048.0;
+
+// Rectángulo de la banda de guarda en coordenadas de pantalla
+struct GuardBand {
+  min: (f32, f32),
+  max: (f32, f32),
+}
+
+impl GuardBand {
+  fn around(viewport: (usize, usize)) -> Self {
+    GuardBand {
+      min: (-GUARD_BAND, -GUARD_BAND),
+      max: (viewport.0 as f32 + GUARD_BAND, viewport.1 as f32 + GUARD_BAND),
+    }
+  }
+
+  fn contains(&self, point: &Vec3) -> bool {
+    point.x >= self.min.0 && point.x <= self.max.0 && point.y >= self.min.1 && point.y <= self.max.1
+  }
+
+  // Sutherland-Hodgman contra los cuatro lados. La profundidad ya está dividida por w y es
+  // lineal en pantalla, así que se interpola con el mismo parámetro que x e y
+  fn clip(&self, corners: &[Vec3; 3]) -> Vec<Vec3> {
+    let mut polygon = corners.to_vec();
+    // Eje (0 = x, 1 = y), posición del lado y hacia dónde queda el interior
+    let sides = [(0, self.min.0, 1.0), (0, self.max.0, -1.0), (1, self.min.1, 1.0), (1, self.max.1, -1.0)];
+    for (axis, limit, direction) in sides {
+      // Distancia con signo al lado: positiva o cero dentro
+      let distance = |point: &Vec3| (point[axis] - limit) * direction;
+      let input = std::mem::take(&mut polygon);
+      for (i, current) in input.iter().enumerate() {
+        let previous = &input[(i + input.len() - 1) % input.len()];
+        let (d_current, d_previous) = (distance(current), distance(previous));
+        if (d_current >= 0.0) != (d_previous >= 0.0) {
+          let t = d_previous / (d_previous - d_current);
+          polygon.push(previous + (current - previous) * t);
+        }
+        if d_current >= 0.0 {
+          polygon.push(*current);
+        }
+      }
+    }
+    polygon
+  }
+}
 
-    (min_x, min_y, max_x, max_y)
+// Posición de pantalla a punto fijo 28.4
+fn to_fixed(position: &Vec3) -> (i64, i64) {
+  let fixed = |value: f32| (value * SUBPIXEL_ONE as f32).round() as i64;
+  (fixed(position.x), fixed(position.y))
 }
 
-fn barycentric_coordinates(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3, area: f32) -> (f32, f32, f32) {
-    let w1 = edge_function(b, c, p) / area;
-    let w2 = edge_function(c, a, p) / area;
-    let w3 = edge_function(a, b, p) / area;
+// Función de arista en enteros: exacta y sin redondeo
+fn edge_function_fixed(a: (i64, i64), b: (i64, i64), c: (i64, i64)) -> i64 {
+  (c.0 - a.0) * (b.1 - a.1) - (c.1 - a.1) * (b.0 - a.0)
+}
 
-    (w1, w2, w3)
+// Con el interior a la izquierda de la arista (área positiva, y hacia abajo): superior si es
+// horizontal y avanza hacia -x, izquierda si baja por la pantalla
+fn is_top_left(from: (i64, i64), to: (i64, i64)) -> bool {
+  let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+  (dy == 0 && dx < 0) || dy > 0
 }
 
-fn edge_function(a: &Vec3, b: &Vec3, c: &Vec3) -> f32 {
-    (c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+  use nalgebra_glm::Vec2;
+  use super::*;
+  use crate::procedural::SeededRng;
+
+  const RANDOM_CASES: usize = 300;
+  const VIEWPORT: (usize, usize) = (64, 64);
+
+  fn screen_vertex(x: f32, y: f32, depth: f32) -> Vertex {
+    Vertex::new(Vec3::new(x, y, depth), Vec3::new(0.0, 0.0, 1.0), Vec2::zeros())
+  }
+
+  fn random_point(rng: &mut SeededRng) -> (f32, f32) {
+    (rng.next_f32() * 64.0 - 8.0, rng.next_f32() * 64.0 - 8.0)
+  }
+
+  fn random_fixed(rng: &mut SeededRng) -> (i64, i64) {
+    ((rng.next_u64() % 2048) as i64 - 1024, (rng.next_u64() % 2048) as i64 - 1024)
+  }
+
+  #[test]
+  fn edge_function_is_twice_the_signed_area() {
+    let (a, b, c) = ((0, 0), (0, 160), (160, 0));
+    assert_eq!(edge_function_fixed(a, b, c), 160 * 160);
+    assert_eq!(edge_function_fixed(a, c, b), -160 * 160);
+    assert_eq!(edge_function_fixed(a, b, (0, 80)), 0);
+  }
+
+  #[test]
+  fn edge_functions_are_cyclic_and_change_sign_with_the_winding() {
+    let mut rng = SeededRng::new(0xED6E);
+    for _ in 0..RANDOM_CASES {
+      let (a, b, c) = (random_fixed(&mut rng), random_fixed(&mut rng), random_fixed(&mut rng));
+      let area = edge_function_fixed(a, b, c);
+      assert_eq!(edge_function_fixed(b, c, a), area);
+      assert_eq!(edge_function_fixed(c, a, b), area);
+      assert_eq!(edge_function_fixed(a, c, b), -area);
+
+      // Las tres aristas evaluadas en cualquier punto suman el área (coordenadas baricéntricas)
+      let p = random_fixed(&mut rng);
+      assert_eq!(edge_function_fixed(b, c, p) + edge_function_fixed(c, a, p) + edge_function_fixed(a, b, p), area);
+    }
+  }
+
+  #[test]
+  fn top_left_rule_gives_each_edge_to_one_side() {
+    let mut rng = SeededRng::new(0x70F1);
+    for _ in 0..RANDOM_CASES {
+      let (from, to) = (random_fixed(&mut rng), random_fixed(&mut rng));
+      if from != to {
+        assert_ne!(is_top_left(from, to), is_top_left(to, from), "{:?} -> {:?}", from, to);
+      }
+    }
+  }
+
+  #[test]
+  fn barycentric_weights_sum_to_one_and_reproduce_the_pixel_center() {
+    // Con profundidad 1 en un vértice y 0 en los otros dos, la de cada fragmento es el peso de
+    // ese vértice tal como lo calcula rasterize: tres pasadas dan los tres pesos de cada píxel
+    let mut rng = SeededRng::new(0xBA41);
+    let mut checked = 0;
+    for _ in 0..RANDOM_CASES {
+      // Vértices en la rejilla de 1/16 de píxel: el rasterizador no los mueve
+      let corners = [(); 3].map(|_| {
+        let (x, y) = random_point(&mut rng);
+        ((x * 16.0).round() / 16.0, (y * 16.0).round() / 16.0)
+      });
+      let weights: [Vec<Fragment>; 3] = std::array::from_fn(|vertex| {
+        let [a, b, c] = std::array::from_fn(|i| screen_vertex(corners[i].0, corners[i].1, if i == vertex { 1.0 } else { 0.0 }));
+        let mut fragments = Vec::new();
+        triangle(&a, &b, &c, VIEWPORT, false, &mut fragments);
+        fragments
+      });
+      assert!(weights[0].len() == weights[1].len() && weights[1].len() == weights[2].len());
+      for ((first, second), third) in weights[0].iter().zip(&weights[1]).zip(&weights[2]) {
+        assert!(first.position == second.position && second.position == third.position);
+        let (w1, w2, w3) = (first.depth, second.depth, third.depth);
+        assert!((w1 + w2 + w3 - 1.0).abs() < 1e-5, "pesos {} {} {}", w1, w2, w3);
+        let x = corners[0].0 * w1 + corners[1].0 * w2 + corners[2].0 * w3;
+        let y = corners[0].1 * w1 + corners[1].1 * w2 + corners[2].1 * w3;
+        let center = first.position + Vec2::new(0.5, 0.5);
+        assert!((x - center.x).abs() < 1e-3 && (y - center.y).abs() < 1e-3, "({}, {}) en lugar de {:?}", x, y, center);
+      }
+      checked += weights[0].len();
+    }
+    assert!(checked > 0);
+  }
+
+  #[test]
+  fn interpolated_depth_follows_the_triangle_plane() {
+    // Profundidad lineal en pantalla y distinta en cada vértice: en cada centro de píxel
+    // cubierto el fragmento debe dar exactamente el plano, no solo algo entre los vértices
+    let plane = |x: f32, y: f32| 0.5 + 0.004 * x - 0.003 * y;
+    let mut rng = SeededRng::new(0xDE97);
+    let mut fragments = Vec::new();
+    let mut checked = 0;
+    for _ in 0..RANDOM_CASES {
+      // Vértices en la rejilla de 1/16 de píxel: el rasterizador no los mueve
+      let [a, b, c] = [(); 3].map(|_| {
+        let (x, y) = random_point(&mut rng);
+        let (x, y) = ((x * 16.0).round() / 16.0, (y * 16.0).round() / 16.0);
+        screen_vertex(x, y, plane(x, y))
+      });
+      fragments.clear();
+      triangle(&a, &b, &c, VIEWPORT, false, &mut fragments);
+      for fragment in &fragments {
+        let expected = plane(fragment.position.x + 0.5, fragment.position.y + 0.5);
+        assert!((fragment.depth - expected).abs() < 1e-4, "profundidad {} en lugar de {}", fragment.depth, expected);
+      }
+      checked += fragments.len();
+    }
+    assert!(checked > 0);
+  }
+
+  #[test]
+  fn fragments_stay_inside_the_bounding_box() {
+    let mut rng = SeededRng::new(0xB0C5);
+    let mut fragments = Vec::new();
+    for _ in 0..RANDOM_CASES {
+      let points = [(); 3].map(|_| random_point(&mut rng));
+      let [a, b, c] = points.map(|(x, y)| screen_vertex(x, y, 0.5));
+      fragments.clear();
+      triangle(&a, &b, &c, VIEWPORT, false, &mut fragments);
+      let (min_x, max_x) = points.iter().fold((f32::MAX, f32::MIN), |(min, max), point| (min.min(point.0), max.max(point.0)));
+      let (min_y, max_y) = points.iter().fold((f32::MAX, f32::MIN), |(min, max), point| (min.min(point.1), max.max(point.1)));
+      for fragment in &fragments {
+        let center = fragment.position + Vec2::new(0.5, 0.5);
+        assert!(center.x >= min_x && center.x <= max_x && center.y >= min_y && center.y <= max_y);
+      }
+    }
+  }
+
+  #[test]
+  fn degenerate_triangle_has_no_fragments() {
+    let mut fragments = Vec::new();
+    triangle(&screen_vertex(0.0, 0.0, 0.5), &screen_vertex(10.0, 10.0, 0.5), &screen_vertex(20.0, 20.0, 0.5), VIEWPORT, false, &mut fragments);
+    assert!(fragments.is_empty());
+  }
+
+  #[test]
+  fn fragments_stay_inside_the_viewport() {
+    let mut rng = SeededRng::new(0x6A4D);
+    let mut fragments = Vec::new();
+    for _ in 0..RANDOM_CASES {
+      let [a, b, c] = [(); 3].map(|_| screen_vertex(rng.range(-1.0e5, 1.0e5), rng.range(-1.0e5, 1.0e5), 0.5));
+      fragments.clear();
+      triangle(&a, &b, &c, VIEWPORT, false, &mut fragments);
+      for fragment in &fragments {
+        assert!(fragment.position.x < VIEWPORT.0 as f32 && fragment.position.y < VIEWPORT.1 as f32);
+        assert!(fragment.position.x >= 0.0 && fragment.position.y >= 0.0);
+      }
+    }
+  }
+
+  #[test]
+  fn clipped_triangles_keep_their_coverage() {
+    let mut fragments = Vec::new();
+    // Mucho más grande que la banda de guarda: cubre todo el viewport una sola vez
+    triangle(&screen_vertex(-1.0e6, -1.0e6, 0.5), &screen_vertex(1.0e6, -1.0e6, 0.5), &screen_vertex(0.0, 1.0e6, 0.5), VIEWPORT, false, &mut fragments);
+    assert_eq!(fragments.len(), VIEWPORT.0 * VIEWPORT.1);
+
+    // Medio plano bajo la recta y = x + 0.5: los píxeles con y > x
+    fragments.clear();
+    triangle(&screen_vertex(-1.0e6, -1.0e6 + 0.5, 0.5), &screen_vertex(1.0e6, 1.0e6 + 0.5, 0.5), &screen_vertex(-1.0e6, 1.0e6, 0.5), VIEWPORT, false, &mut fragments);
+    assert_eq!(fragments.len(), VIEWPORT.0 * (VIEWPORT.0 - 1) / 2);
+    assert!(fragments.iter().all(|fragment| fragment.position.y > fragment.position.x));
+  }
+
+  #[test]
+  fn guard_band_clip_keeps_the_inside() {
+    let guard_band = GuardBand::around(VIEWPORT);
+    let inside = [Vec3::new(0.0, 0.0, 0.1), Vec3::new(10.0, 0.0, 0.2), Vec3::new(0.0, 10.0, 0.3)];
+    assert_eq!(guard_band.clip(&inside), inside.to_vec());
+
+    let polygon = guard_band.clip(&[Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0e6, 0.0, 1.0), Vec3::new(0.0, 1.0e6, 1.0)]);
+    assert!(polygon.len() >= 3);
+    for point in &polygon {
+      assert!(guard_band.contains(point), "{:?}", point);
+      // La profundidad sigue el plano del triángulo: z = (x + y) / 1e6
+      assert!((point.z - (point.x + point.y) / 1.0e6).abs() < 1e-4);
+    }
+  }
+
+  #[test]
+  fn shared_edge_samples_are_covered_once() {
+    let mut rng = SeededRng::new(0x3A5A);
+    let mut fragments = Vec::new();
+    for _ in 0..RANDOM_CASES {
+      let (x0, y0) = (rng.range(-4.0, 20.0), rng.range(-4.0, 20.0));
+      let (x1, y1) = (x0 + rng.range(2.0, 40.0), y0 + rng.range(2.0, 40.0));
+      let [a, b, c, d] = [(x0, y0), (x1, y0), (x1, y1), (x0, y1)].map(|(x, y)| screen_vertex(x, y, 0.5));
+      fragments.clear();
+      triangle(&a, &b, &c, VIEWPORT, true, &mut fragments);
+      triangle(&a, &c, &d, VIEWPORT, true, &mut fragments);
+
+      let mut masks = vec![0u8; VIEWPORT.0 * VIEWPORT.1];
+      for fragment in &fragments {
+        let mask = &mut masks[fragment.position.y as usize * VIEWPORT.0 + fragment.position.x as usize];
+        assert_eq!(*mask & fragment.coverage, 0, "muestra cubierta dos veces");
+        *mask |= fragment.coverage;
+      }
+      // Cada muestra dentro del rectángulo (y no justo en su borde) queda cubierta
+      for (index, mask) in masks.iter().enumerate() {
+        let (x, y) = ((index % VIEWPORT.0) as f32 + 0.5, (index / VIEWPORT.0) as f32 + 0.5);
+        for (sample, (dx, dy)) in SAMPLE_OFFSETS.iter().enumerate() {
+          let (sx, sy) = (x + *dx as f32 / 16.0, y + *dy as f32 / 16.0);
+          if sx > x0 + 0.1 && sx < x1 - 0.1 && sy > y0 + 0.1 && sy < y1 - 0.1 {
+            assert_ne!(mask & (1 << sample), 0, "muestra sin cubrir en ({}, {})", sx, sy);
+          }
+        }
+      }
+    }
+  }
+}
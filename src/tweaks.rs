@@ -0,0 +1,378 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode};
+use nalgebra_glm::Vec3;
+use serde::Deserialize;
+use crate::celestial_shaders::CelestialBody;
+use crate::framebuffer::Framebuffer;
+use crate::input::Input;
+use crate::text::{draw_text, GLYPH_SIZE};
+use crate::theme;
+
+// ============= AJUSTE EN VIVO DE LOS SHADERS (F2) =============
+// Controles deslizantes para las constantes principales del shader del cuerpo seleccionado:
+// frecuencia de las bandas, posición y tamaño de la gran tormenta, umbrales de tierra y de
+// nubes. Los valores viven en cada CelestialObject y llegan al shader por Uniforms, así que
+// el cambio se ve en el mismo frame sin recompilar. Se arrastran con el ratón o se eligen
+// con ↑/↓ y se mueven con ←/→; Retroceso devuelve el campo a su valor de fábrica.
+// El panel no guarda nada: los valores que se quieran conservar se escriben en el archivo
+// de parámetros (--params, shader_params.toml), una tabla por id de cuerpo, que se lee al
+// arrancar y otra vez cada vez que cambia (ver hot_reload.rs).
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ShaderTweaks {
+    pub band_frequency: f32,  // bandas de los gigantes gaseosos y helados
+    pub storm_longitude: f32, // giro de la gran tormenta alrededor del eje (grados)
+    pub storm_latitude: f32,  // desplazamiento de la tormenta hacia el norte (unidades del radio)
+    pub storm_size: f32,      // multiplica el radio de la tormenta
+    pub land_threshold: f32,  // cuánto ruido de continente hace falta para que haya tierra
+    pub cloud_threshold: f32, // cobertura de nubes: más bajo = más nubes
+    pub cloud_speed: f32,     // multiplica la velocidad de las nubes
+    pub glow: f32,            // multiplica la bioluminiscencia del planeta alienígena
+}
+
+impl ShaderTweaks {
+    // Los valores con los que se escribió cada shader
+    pub fn for_body(body: CelestialBody) -> Self {
+        let base = ShaderTweaks {
+            band_frequency: 0.0,
+            storm_longitude: 0.0,
+            storm_latitude: 0.0,
+            storm_size: 1.0,
+            land_threshold: 0.48,
+            cloud_threshold: 0.45,
+            cloud_speed: 1.0,
+            glow: 1.0,
+        };
+        match body {
+            CelestialBody::Jupiter => ShaderTweaks { band_frequency: 14.0, cloud_threshold: 0.55, ..base },
+            CelestialBody::Saturn => ShaderTweaks { band_frequency: 9.0, ..base },
+            CelestialBody::Uranus => ShaderTweaks { band_frequency: 7.0, ..base },
+            CelestialBody::Neptune => ShaderTweaks { band_frequency: 10.0, ..base },
+            _ => base,
+        }
+    }
+
+    // Centro de una tormenta escrito en el shader, girado y desplazado según los ajustes
+    pub fn place_storm(&self, center: Vec3) -> Vec3 {
+        let (sin, cos) = self.storm_longitude.to_radians().sin_cos();
+        Vec3::new(
+            center.x * cos + center.z * sin,
+            center.y + self.storm_latitude,
+            center.z * cos - center.x * sin,
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Tweak {
+    BandFrequency,
+    StormLongitude,
+    StormLatitude,
+    StormSize,
+    LandThreshold,
+    CloudThreshold,
+    CloudSpeed,
+    Glow,
+}
+
+impl Tweak {
+    fn label(self) -> &'static str {
+        match self {
+            Tweak::BandFrequency => "Frecuencia de bandas",
+            Tweak::StormLongitude => "Tormenta: longitud",
+            Tweak::StormLatitude => "Tormenta: latitud",
+            Tweak::StormSize => "Tormenta: tamaño",
+            Tweak::LandThreshold => "Umbral de tierra",
+            Tweak::CloudThreshold => "Umbral de nubes",
+            Tweak::CloudSpeed => "Velocidad de nubes",
+            Tweak::Glow => "Bioluminiscencia",
+        }
+    }
+
+    fn range(self) -> (f32, f32) {
+        match self {
+            Tweak::BandFrequency => (2.0, 30.0),
+            Tweak::StormLongitude => (-180.0, 180.0),
+            Tweak::StormLatitude => (-0.5, 0.5),
+            Tweak::StormSize => (0.25, 2.5),
+            Tweak::LandThreshold => (0.3, 0.7),
+            Tweak::CloudThreshold => (0.2, 0.8),
+            Tweak::CloudSpeed => (0.0, 5.0),
+            Tweak::Glow => (0.0, 3.0),
+        }
+    }
+
+    pub fn get(self, tweaks: &ShaderTweaks) -> f32 {
+        match self {
+            Tweak::BandFrequency => tweaks.band_frequency,
+            Tweak::StormLongitude => tweaks.storm_longitude,
+            Tweak::StormLatitude => tweaks.storm_latitude,
+            Tweak::StormSize => tweaks.storm_size,
+            Tweak::LandThreshold => tweaks.land_threshold,
+            Tweak::CloudThreshold => tweaks.cloud_threshold,
+            Tweak::CloudSpeed => tweaks.cloud_speed,
+            Tweak::Glow => tweaks.glow,
+        }
+    }
+
+    pub fn set(self, tweaks: &mut ShaderTweaks, value: f32) {
+        let (min, max) = self.range();
+        let value = value.clamp(min, max);
+        match self {
+            Tweak::BandFrequency => tweaks.band_frequency = value,
+            Tweak::StormLongitude => tweaks.storm_longitude = value,
+            Tweak::StormLatitude => tweaks.storm_latitude = value,
+            Tweak::StormSize => tweaks.storm_size = value,
+            Tweak::LandThreshold => tweaks.land_threshold = value,
+            Tweak::CloudThreshold => tweaks.cloud_threshold = value,
+            Tweak::CloudSpeed => tweaks.cloud_speed = value,
+            Tweak::Glow => tweaks.glow = value,
+        }
+    }
+}
+
+// ============= ARCHIVO DE PARÁMETROS =============
+// [jupiter]
+// band_frequency = 16.0
+// storm_longitude = 40.0
+// Los campos que falten conservan el valor de fábrica del shader del cuerpo.
+
+pub const DEFAULT_PARAMS_PATH: &str = "shader_params.toml";
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct TweakOverrides {
+    pub band_frequency: Option<f32>,
+    pub storm_longitude: Option<f32>,
+    pub storm_latitude: Option<f32>,
+    pub storm_size: Option<f32>,
+    pub land_threshold: Option<f32>,
+    pub cloud_threshold: Option<f32>,
+    pub cloud_speed: Option<f32>,
+    pub glow: Option<f32>,
+}
+
+impl TweakOverrides {
+    // Valores de fábrica del cuerpo con los del archivo encima (recortados al rango del control)
+    pub fn apply(&self, body: CelestialBody) -> ShaderTweaks {
+        let mut tweaks = ShaderTweaks::for_body(body);
+        let fields = [
+            (Tweak::BandFrequency, self.band_frequency),
+            (Tweak::StormLongitude, self.storm_longitude),
+            (Tweak::StormLatitude, self.storm_latitude),
+            (Tweak::StormSize, self.storm_size),
+            (Tweak::LandThreshold, self.land_threshold),
+            (Tweak::CloudThreshold, self.cloud_threshold),
+            (Tweak::CloudSpeed, self.cloud_speed),
+            (Tweak::Glow, self.glow),
+        ];
+        for (tweak, value) in fields {
+            if let Some(value) = value {
+                tweak.set(&mut tweaks, value);
+            }
+        }
+        tweaks
+    }
+}
+
+#[derive(Debug)]
+pub enum ParamsError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+}
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamsError::Io(err) => write!(f, "no se pudieron leer los parámetros: {}", err),
+            ParamsError::Parse(err) => write!(f, "parámetros inválidos: {}", err),
+        }
+    }
+}
+
+// Ajustes por id de cuerpo. Ruta explícita: el archivo tiene que existir; sin ruta,
+// shader_params.toml es opcional
+pub fn load_params(path: Option<&str>) -> Result<BTreeMap<String, TweakOverrides>, ParamsError> {
+    let contents = match fs::read_to_string(path.unwrap_or(DEFAULT_PARAMS_PATH)) {
+        Ok(contents) => contents,
+        Err(err) if path.is_none() && err.kind() == ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(ParamsError::Io(err)),
+    };
+    toml::from_str(&contents).map_err(ParamsError::Parse)
+}
+
+// Controles que tiene sentido mostrar para cada shader
+pub fn tweaks_for(body: CelestialBody) -> &'static [Tweak] {
+    match body {
+        CelestialBody::Jupiter => &[
+            Tweak::BandFrequency,
+            Tweak::StormLongitude,
+            Tweak::StormLatitude,
+            Tweak::StormSize,
+            Tweak::CloudThreshold,
+        ],
+        CelestialBody::Neptune => &[Tweak::BandFrequency, Tweak::StormLongitude, Tweak::StormLatitude, Tweak::StormSize],
+        CelestialBody::Saturn | CelestialBody::Uranus => &[Tweak::BandFrequency],
+        CelestialBody::Earth => &[Tweak::LandThreshold, Tweak::CloudThreshold, Tweak::CloudSpeed],
+        CelestialBody::AlienPlanet => &[Tweak::Glow],
+        _ => &[],
+    }
+}
+
+const PANEL_WIDTH: usize = 300;
+const PADDING: usize = 12;
+const MARGIN: usize = 10;
+const ROW_HEIGHT: usize = GLYPH_SIZE * 3 + 4; // etiqueta, barra y separación
+const TRACK_HEIGHT: usize = 6;
+const KEY_STEPS: f32 = 100.0; // pasos de ←/→ en todo el rango
+
+// Barra de un control en pantalla
+struct SliderRect {
+    x: usize,
+    y: usize,
+    width: usize,
+}
+
+pub struct TweakPanel {
+    open: bool,
+    field: usize,
+    dragging: Option<usize>, // control que se arrastra con el botón izquierdo pulsado
+}
+
+impl TweakPanel {
+    pub fn new() -> Self {
+        TweakPanel { open: false, field: 0, dragging: None }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.field = 0;
+        self.dragging = None;
+    }
+
+    // Rectángulo del panel (x, y, ancho, alto), pegado al borde derecho y centrado en vertical
+    fn rect(screen_width: usize, screen_height: usize, count: usize) -> (usize, usize, usize, usize) {
+        let height = PADDING * 2 + GLYPH_SIZE * 2 + 8 + count.max(1) * ROW_HEIGHT + GLYPH_SIZE + 4;
+        let x = screen_width.saturating_sub(PANEL_WIDTH + MARGIN);
+        let y = screen_height.saturating_sub(height) / 2;
+        (x, y, PANEL_WIDTH, height)
+    }
+
+    fn slider(screen_width: usize, screen_height: usize, count: usize, index: usize) -> SliderRect {
+        let (x, y, width, _) = Self::rect(screen_width, screen_height, count);
+        SliderRect {
+            x: x + PADDING,
+            y: y + PADDING + GLYPH_SIZE * 2 + 8 + index * ROW_HEIGHT + GLYPH_SIZE + 4,
+            width: width - PADDING * 2,
+        }
+    }
+
+    // ¿Cae el punto sobre el panel? (los clics ahí no seleccionan cuerpos)
+    pub fn contains(&self, screen_width: usize, screen_height: usize, body: Option<CelestialBody>, x: f32, y: f32) -> bool {
+        if !self.open {
+            return false;
+        }
+        let count = body.map_or(0, |body| tweaks_for(body).len());
+        let (px, py, width, height) = Self::rect(screen_width, screen_height, count);
+        x >= px as f32 && x < (px + width) as f32 && y >= py as f32 && y < (py + height) as f32
+    }
+
+    // Teclado y ratón sobre los ajustes del cuerpo seleccionado
+    pub fn handle_input(&mut self, input: &Input, body: CelestialBody, tweaks: &mut ShaderTweaks) {
+        let fields = tweaks_for(body);
+        if fields.is_empty() {
+            self.dragging = None;
+            return;
+        }
+        self.field = self.field.min(fields.len() - 1);
+
+        if input.is_key_pressed(Key::Up, KeyRepeat::No) {
+            self.field = (self.field + fields.len() - 1) % fields.len();
+        }
+        if input.is_key_pressed(Key::Down, KeyRepeat::No) {
+            self.field = (self.field + 1) % fields.len();
+        }
+        let tweak = fields[self.field];
+        let (min, max) = tweak.range();
+        let step = (max - min) / KEY_STEPS;
+        if input.is_key_pressed(Key::Right, KeyRepeat::Yes) {
+            tweak.set(tweaks, tweak.get(tweaks) + step);
+        }
+        if input.is_key_pressed(Key::Left, KeyRepeat::Yes) {
+            tweak.set(tweaks, tweak.get(tweaks) - step);
+        }
+        if input.is_key_pressed(Key::Backspace, KeyRepeat::No) {
+            tweak.set(tweaks, tweak.get(&ShaderTweaks::for_body(body)));
+        }
+
+        // Ratón: al pulsar sobre una barra se engancha y la sigue hasta soltar el botón
+        let (width, height) = input.get_size();
+        let mouse = input.get_mouse_pos(MouseMode::Clamp);
+        if !input.get_mouse_down(MouseButton::Left) {
+            self.dragging = None;
+            return;
+        }
+        let Some((mouse_x, mouse_y)) = mouse else {
+            return;
+        };
+        if self.dragging.is_none() {
+            self.dragging = (0..fields.len()).find(|&index| {
+                let slider = Self::slider(width, height, fields.len(), index);
+                let top = slider.y as f32 - 4.0;
+                let bottom = (slider.y + TRACK_HEIGHT) as f32 + 4.0;
+                mouse_x >= slider.x as f32 && mouse_x < (slider.x + slider.width) as f32 && mouse_y >= top && mouse_y < bottom
+            });
+        }
+        if let Some(index) = self.dragging {
+            let slider = Self::slider(width, height, fields.len(), index);
+            let t = ((mouse_x - slider.x as f32) / slider.width as f32).clamp(0.0, 1.0);
+            let (min, max) = fields[index].range();
+            fields[index].set(tweaks, min + (max - min) * t);
+            self.field = index;
+        }
+    }
+
+    pub fn draw(&self, screen: &mut Framebuffer, body: Option<(&str, CelestialBody, &ShaderTweaks)>) {
+        let theme = theme::current();
+        let fields = body.map_or(&[][..], |(_, body, _)| tweaks_for(body));
+        let (x, y, width, height) = Self::rect(screen.width, screen.height, fields.len());
+        screen.blend_rect(x, y, width, height, theme.panel_background, theme.panel_opacity);
+
+        let text_x = (x + PADDING) as i32;
+        let title = body.map_or("Shader", |(name, _, _)| name);
+        draw_text(screen, text_x, (y + PADDING) as i32, title, theme.title, 2);
+
+        let Some((_, body_type, tweaks)) = body.filter(|_| !fields.is_empty()) else {
+            let message = if body.is_some() { "Este shader no tiene ajustes" } else { "Selecciona un cuerpo (Tab o clic)" };
+            draw_text(screen, text_x, (y + PADDING + GLYPH_SIZE * 2 + 8) as i32, message, theme.muted, 1);
+            return;
+        };
+
+        let defaults = ShaderTweaks::for_body(body_type);
+        for (index, tweak) in fields.iter().enumerate() {
+            let slider = Self::slider(screen.width, screen.height, fields.len(), index);
+            let value = tweak.get(tweaks);
+            let color = if index == self.field { theme.accent } else { theme.text };
+            let modified = if value != tweak.get(&defaults) { "*" } else { "" };
+            let label = format!("{}: {:.2}{}", tweak.label(), value, modified);
+            draw_text(screen, slider.x as i32, (slider.y - GLYPH_SIZE - 4) as i32, &label, color, 1);
+
+            let (min, max) = tweak.range();
+            let filled = (((value - min) / (max - min)).clamp(0.0, 1.0) * slider.width as f32) as usize;
+            screen.blend_rect(slider.x, slider.y, slider.width, TRACK_HEIGHT, theme.muted, 0.5);
+            screen.blend_rect(slider.x, slider.y, filled, TRACK_HEIGHT, color, 1.0);
+            let knob_x = (slider.x + filled).saturating_sub(2);
+            screen.blend_rect(knob_x, slider.y.saturating_sub(3), 4, TRACK_HEIGHT + 6, theme.title, 1.0);
+        }
+
+        let hint_y = (y + height - PADDING - GLYPH_SIZE) as i32;
+        draw_text(screen, text_x, hint_y, "↑/↓ ←/→ ratón  Retroceso: fábrica", theme.muted, 1);
+    }
+}
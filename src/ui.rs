@@ -0,0 +1,361 @@
+use nalgebra_glm::{Mat4, Vec2, Vec3};
+use crate::celestial_shaders::CelestialBody;
+use crate::framebuffer::Framebuffer;
+use crate::line::{draw_line, LineStyle};
+use crate::picking::screen_disk;
+use crate::text::{draw_styled, draw_text, text_width, wrap_text, TextStyle, GLYPH_SIZE};
+use crate::theme::{self, HudLayout, Widget};
+use crate::{project_to_screen, CelestialObject};
+
+// ============= ETIQUETAS DE CUERPOS =============
+// Pasada de colocación en pantalla tras la proyección: las etiquetas se ordenan por
+// prioridad (la del cuerpo seleccionado primero y después las de los cuerpos que se ven
+// más grandes) y cada una ocupa el primer hueco libre alrededor de su cuerpo. Si hay que
+// apartarla, una línea guía la une al cuerpo; si no cabe en ningún sitio, se oculta.
+// Los cuerpos tapados por otro (según el buffer de profundidad de la escena) no llevan
+// nombre, y los lejanos lo van apagando entre label_fade_start y label_fade_end del tema;
+// el del cuerpo seleccionado se ve siempre.
+
+const LABEL_GAP: f32 = 6.0;                       // separación entre el disco y la etiqueta
+const LEADER_DISTANCES: [f32; 2] = [22.0, 44.0];  // apartados (píxeles más allá del disco)
+// El punto que se compara con la profundidad está algo por delante de la superficie, para
+// que las nubes y la atmósfera del propio cuerpo no lo tapen
+const OCCLUSION_MARGIN: f32 = 1.15;               // radios desde el centro
+const DEPTH_TOLERANCE: f32 = 1.001;               // margen de la comparación con la profundidad invertida
+const MIN_LABEL_ALPHA: f32 = 0.05;                // más apagada ya no se dibuja
+
+// Lo que las etiquetas leen de la escena: su profundidad (a la resolución interna, con su
+// viewport) y desde dónde mira la cámara
+pub struct LabelScene<'a> {
+    pub scene: &'a Framebuffer,
+    pub viewport_matrix: &'a Mat4,
+    pub camera_position: Vec3,
+}
+
+// Rectángulo de pantalla (x, y, ancho, alto)
+type Rect = (f32, f32, f32, f32);
+
+struct LabelCandidate<'a> {
+    text: &'a str,
+    center: (f32, f32),
+    radius: f32,
+    color: u32,
+    alpha: f32,
+    priority: (bool, f32),
+}
+
+// ¿Tapa otra cosa de la escena el lado del cuerpo que mira a la cámara?
+fn occluded(obj: &CelestialObject, depth: &LabelScene, view_matrix: &Mat4, projection_matrix: &Mat4) -> bool {
+    let toward_camera = (depth.camera_position - obj.transform.translation).try_normalize(1.0e-6).unwrap_or_else(Vec3::z);
+    let front = obj.transform.translation + toward_camera * obj.transform.scale * OCCLUSION_MARGIN;
+    let Some(point) = project_to_screen(front, view_matrix, projection_matrix, depth.viewport_matrix) else {
+        return false;
+    };
+    let scene = depth.scene;
+    if point.x < 0.0 || point.y < 0.0 || point.x >= scene.width as f32 || point.y >= scene.height as f32 {
+        return false;
+    }
+    // Profundidad invertida: lo que está más cerca tiene más profundidad
+    scene.zbuffer[point.y as usize * scene.width + point.x as usize] > point.z * DEPTH_TOLERANCE
+}
+
+// Opacidad del nombre según la distancia a la cámara
+fn distance_fade(distance: f32, start: f32, end: f32) -> f32 {
+    (1.0 - (distance - start) / (end - start)).clamp(0.0, 1.0)
+}
+
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+}
+
+// Posiciones a probar, en orden: pegada al disco (derecha, izquierda, arriba, abajo) y
+// después apartada en ocho direcciones (con línea guía)
+fn label_slots(center: (f32, f32), radius: f32, width: f32, height: f32) -> Vec<(Rect, bool)> {
+    let (x, y) = center;
+    let edge = radius + LABEL_GAP;
+    let mut slots = vec![
+        ((x + edge, y - height / 2.0, width, height), false),
+        ((x - edge - width, y - height / 2.0, width, height), false),
+        ((x - width / 2.0, y - edge - height, width, height), false),
+        ((x - width / 2.0, y + edge, width, height), false),
+    ];
+    for distance in LEADER_DISTANCES {
+        for step in 0..8 {
+            let angle = step as f32 * std::f32::consts::FRAC_PI_4;
+            let (dx, dy) = (angle.cos(), -angle.sin());
+            let (anchor_x, anchor_y) = (x + dx * (edge + distance), y + dy * (edge + distance));
+            // El rectángulo crece hacia fuera del cuerpo desde el extremo de la línea
+            let rect_x = if dx > 0.1 { anchor_x } else if dx < -0.1 { anchor_x - width } else { anchor_x - width / 2.0 };
+            let rect_y = if dy > 0.1 { anchor_y } else if dy < -0.1 { anchor_y - height } else { anchor_y - height / 2.0 };
+            slots.push(((rect_x, rect_y, width, height), true));
+        }
+    }
+    slots
+}
+
+pub fn draw_labels(
+    screen: &mut Framebuffer,
+    objects: &[&CelestialObject],
+    selected: Option<usize>,
+    depth: &LabelScene,
+    view_matrix: &Mat4,
+    projection_matrix: &Mat4,
+    viewport_matrix: &Mat4,
+) {
+    let theme = theme::current();
+    if !theme.labels {
+        return;
+    }
+
+    let mut candidates: Vec<LabelCandidate> = objects
+        .iter()
+        .enumerate()
+        .filter_map(|(index, obj)| {
+            let is_selected = selected == Some(index);
+            let distance = (obj.transform.translation - depth.camera_position).magnitude();
+            let alpha = if is_selected { 1.0 } else { distance_fade(distance, theme.label_fade_start, theme.label_fade_end) };
+            if alpha < MIN_LABEL_ALPHA || (!is_selected && occluded(obj, depth, view_matrix, projection_matrix)) {
+                return None;
+            }
+            let (center, radius) = screen_disk(obj, view_matrix, projection_matrix, viewport_matrix)?;
+            Some(LabelCandidate {
+                text: obj.display_name(),
+                center: (center.x, center.y),
+                radius: radius.min(screen.width as f32),
+                color: if is_selected { theme.label_selected } else { theme.label },
+                alpha,
+                priority: (is_selected, radius),
+            })
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.priority.0.cmp(&a.priority.0).then(b.priority.1.total_cmp(&a.priority.1)));
+
+    let bounds: Rect = (0.0, 0.0, screen.width as f32, screen.height as f32);
+    let inside = |rect: &Rect| rect.0 >= bounds.0 && rect.1 >= bounds.1 && rect.0 + rect.2 <= bounds.2 && rect.1 + rect.3 <= bounds.3;
+    let mut placed: Vec<Rect> = Vec::with_capacity(candidates.len());
+
+    for candidate in &candidates {
+        // Margen de un píxel alrededor del texto para el contorno
+        let width = text_width(candidate.text, 1) as f32 + 2.0;
+        let height = GLYPH_SIZE as f32 + 2.0;
+        let slot = label_slots(candidate.center, candidate.radius, width, height)
+            .into_iter()
+            .find(|(rect, _)| inside(rect) && !placed.iter().any(|other| overlaps(rect, other)));
+        let Some((rect, displaced)) = slot else {
+            continue;
+        };
+        placed.push(rect);
+
+        if displaced && theme.leader_lines {
+            draw_leader(screen, candidate.center, candidate.radius, &rect, theme.leader, candidate.alpha, theme.smooth_lines);
+        }
+        // Contorno oscuro para que el nombre se lea también delante del Sol o de un planeta claro
+        draw_styled(
+            screen,
+            rect.0 as i32 + 1,
+            rect.1 as i32 + 1,
+            candidate.text,
+            &TextStyle::new(candidate.color).with_outline(theme.label_outline).with_alpha(candidate.alpha),
+        );
+    }
+}
+
+// Línea guía desde el borde del disco hasta el punto más cercano de la etiqueta
+fn draw_leader(screen: &mut Framebuffer, center: (f32, f32), radius: f32, rect: &Rect, color: u32, alpha: f32, smooth: bool) {
+    let end_x = center.0.clamp(rect.0, rect.0 + rect.2);
+    let end_y = center.1.clamp(rect.1, rect.1 + rect.3);
+    let (dx, dy) = (end_x - center.0, end_y - center.1);
+    let length = dx.hypot(dy);
+    if length <= radius {
+        return;
+    }
+    let (ux, uy) = (dx / length, dy / length);
+    let start = radius + 2.0;
+    if start >= length {
+        return;
+    }
+    let from = (center.0 + ux * start, center.1 + uy * start);
+    let style = if smooth { LineStyle::AntiAliased } else { LineStyle::Aliased };
+    draw_line(screen, from, (end_x, end_y), color, 0.7 * alpha, style);
+}
+
+// ============= RESALTADO DE CUERPOS =============
+// Anillo pulsante alrededor del disco proyectado del cuerpo (lecciones guiadas)
+pub fn draw_highlight(
+    screen: &mut Framebuffer,
+    obj: &CelestialObject,
+    time: f32,
+    view_matrix: &Mat4,
+    projection_matrix: &Mat4,
+    viewport_matrix: &Mat4,
+) {
+    let Some((center, radius)) = screen_disk(obj, view_matrix, projection_matrix, viewport_matrix) else {
+        return;
+    };
+    let radius = radius + 6.0;
+    let color = theme::current().accent;
+
+    let alpha = 0.55 + 0.45 * (time * 4.0).sin();
+    let segments = ((radius * 6.0) as usize).clamp(32, 2048);
+    for i in 0..segments {
+        let angle = i as f32 / segments as f32 * std::f32::consts::TAU;
+        for thickness in 0..2 {
+            let r = radius + thickness as f32;
+            let x = center.x + angle.cos() * r;
+            let y = center.y + angle.sin() * r;
+            if x >= 0.0 && y >= 0.0 {
+                screen.blend_pixel(x as usize, y as usize, color, alpha);
+            }
+        }
+    }
+}
+
+// ============= PANEL DE INFORMACIÓN =============
+// Nombre, datos en vivo (tamaño, órbita, giro, distancia a la cámara y shader), descripción,
+// datos curiosos y campos personalizados del cuerpo seleccionado
+
+const FRAME_TIME: f32 = 0.016; // el giro propio se aplica una vez por frame
+
+// Datos del cuerpo en este frame, como pares (campo, valor). La órbita se mide sobre el
+// plano XZ desde su centro (el del padre si es una luna) y los periodos en segundos de la
+// simulación
+pub fn live_data(obj: &CelestialObject, camera_position: Vec3) -> Vec<(&'static str, String)> {
+    let shader = match obj.shader.body {
+        CelestialBody::Star(class) => format!("Star ({:?})", class),
+        body => body.name().to_string(),
+    };
+    let mut data = vec![("Radio", format!("{:.1}", obj.transform.scale))];
+    match obj.orbit {
+        Some(orbit) => {
+            let local = obj.transform.local_translation;
+            let radius = Vec2::new(local.x - orbit.center.x, local.z - orbit.center.z).magnitude();
+            data.push(("Órbita", format!("{:.1}", radius)));
+            data.push(("Vel. angular", format!("{:.2} °/s", orbit.speed.to_degrees())));
+        }
+        None => data.push(("Órbita", "sin órbita".to_string())),
+    }
+    let spin = obj.spin.speed.magnitude();
+    let rotation = if obj.spin.tidally_locked {
+        "acoplada a la órbita".to_string()
+    } else if spin > 0.0 {
+        format!("{:.1} s", std::f32::consts::TAU / spin * FRAME_TIME)
+    } else {
+        "sin giro".to_string()
+    };
+    data.push(("Rotación", rotation));
+    data.push(("Distancia", format!("{:.0}", (obj.transform.translation - camera_position).magnitude())));
+    data.push(("Shader", shader));
+    data
+}
+
+pub fn draw_info_panel(screen: &mut Framebuffer, layout: &mut HudLayout, obj: &CelestialObject, camera_position: Vec3) {
+    let theme = theme::current();
+    let metadata = &obj.metadata;
+    let panel_width = 360;
+    let line_height = GLYPH_SIZE + 4;
+    let max_chars = (panel_width - 24) / GLYPH_SIZE;
+
+    let mut lines: Vec<(String, u32)> = Vec::new();
+    let data = live_data(obj, camera_position);
+    let key_width = data.iter().map(|(key, _)| key.chars().count()).max().unwrap_or(0);
+    for (key, value) in data {
+        let padding = " ".repeat(key_width - key.chars().count());
+        lines.push((format!("{}{}  {}", key, padding, value), theme.accent));
+    }
+    lines.push((String::new(), theme.text));
+    for line in wrap_text(&metadata.description, max_chars) {
+        lines.push((line, theme.text));
+    }
+    if !metadata.facts.is_empty() {
+        lines.push((String::new(), theme.text));
+        for fact in &metadata.facts {
+            for (i, line) in wrap_text(fact, max_chars - 2).into_iter().enumerate() {
+                let prefix = if i == 0 { "- " } else { "  " };
+                lines.push((format!("{}{}", prefix, line), theme.text));
+            }
+        }
+    }
+    if !metadata.custom.is_empty() {
+        lines.push((String::new(), theme.text));
+        for (key, value) in &metadata.custom {
+            for line in wrap_text(&format!("{}: {}", key, value), max_chars) {
+                lines.push((line, theme.muted));
+            }
+        }
+    }
+
+    let title_height = GLYPH_SIZE * 2 + 8;
+    let panel_height = 12 + title_height + lines.len() * line_height + line_height + 8;
+    let Some((panel_x, panel_y)) = layout.place(Widget::InfoPanel, panel_width, panel_height) else {
+        return;
+    };
+    screen.blend_rect(panel_x, panel_y, panel_width, panel_height, theme.panel_background, theme.hud_opacity);
+
+    let text_x = (panel_x + 12) as i32;
+    let mut y = (panel_y + 12) as i32;
+    draw_text(screen, text_x, y, obj.display_name(), theme.title, 2);
+    y += title_height as i32;
+
+    for (line, color) in &lines {
+        draw_text(screen, text_x, y, line, *color, 1);
+        y += line_height as i32;
+    }
+
+    draw_text(screen, text_x, y + 4, "Tab: siguiente cuerpo   Enter: enciclopedia", theme.muted, 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn live_data_follows_the_body_and_the_camera() {
+        let mut moon = CelestialObject::new(CelestialBody::Moon, Vec3::new(0.0, 0.0, 0.0), 3.0)
+            .with_orbit(20.0, std::f32::consts::PI)
+            .with_rotation_speed(Vec3::new(0.0, 0.1, 0.0));
+        moon.transform.local_translation = Vec3::new(400.0, 300.0, 20.0);
+        moon.transform.translation = Vec3::new(0.0, 0.0, 10.0);
+        let value = |data: &[(&str, String)], key: &str| data.iter().find(|(field, _)| *field == key).unwrap().1.clone();
+
+        let data = live_data(&moon, Vec3::zeros());
+        assert_eq!(value(&data, "Radio"), "3.0");
+        assert_eq!(value(&data, "Órbita"), "20.0");
+        assert_eq!(value(&data, "Vel. angular"), "180.00 °/s");
+        assert_eq!(value(&data, "Rotación"), "1.0 s");
+        assert_eq!(value(&data, "Distancia"), "10");
+        assert_eq!(value(&data, "Shader"), "Moon");
+
+        // La cámara se aleja y la órbita cambia en vivo
+        moon.orbit.as_mut().unwrap().radius = 35.0;
+        moon.transform.local_translation = Vec3::new(400.0, 300.0, 35.0);
+        let data = live_data(&moon, Vec3::new(0.0, 0.0, 110.0));
+        assert_eq!(value(&data, "Órbita"), "35.0");
+        assert_eq!(value(&data, "Distancia"), "100");
+    }
+
+    #[test]
+    fn labels_hide_behind_nearer_geometry_and_fade_with_distance() {
+        let planet = CelestialObject::new(CelestialBody::Mars, Vec3::zeros(), 10.0);
+        let camera_position = Vec3::new(0.0, 0.0, 100.0);
+        let view_matrix = nalgebra_glm::look_at(&camera_position, &Vec3::zeros(), &Vec3::y());
+        let projection_matrix = nalgebra_glm::reversed_perspective_rh_zo(1.0, 0.8, 0.1, 10_000.0);
+        let viewport_matrix = crate::math::create_viewport_matrix(64.0, 64.0);
+        let mut scene = Framebuffer::new(64, 64);
+
+        // Cielo vacío, o el propio planeta (su superficie queda detrás del punto de prueba)
+        let surface = project_to_screen(Vec3::new(0.0, 0.0, 10.0), &view_matrix, &projection_matrix, &viewport_matrix).unwrap();
+        scene.zbuffer.fill(surface.z);
+        let depth = LabelScene { scene: &scene, viewport_matrix: &viewport_matrix, camera_position };
+        assert!(!occluded(&planet, &depth, &view_matrix, &projection_matrix));
+
+        // Una luna entre la cámara y el planeta lo tapa
+        let moon = project_to_screen(Vec3::new(0.0, 0.0, 50.0), &view_matrix, &projection_matrix, &viewport_matrix).unwrap();
+        scene.zbuffer.fill(moon.z);
+        let depth = LabelScene { scene: &scene, viewport_matrix: &viewport_matrix, camera_position };
+        assert!(occluded(&planet, &depth, &view_matrix, &projection_matrix));
+
+        assert_eq!(distance_fade(1000.0, 2500.0, 6000.0), 1.0);
+        assert!((distance_fade(4250.0, 2500.0, 6000.0) - 0.5).abs() < 1.0e-6);
+        assert_eq!(distance_fade(7000.0, 2500.0, 6000.0), 0.0);
+    }
+}
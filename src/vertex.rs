@@ -7,8 +7,12 @@ pub struct Vertex {
   pub normal: Vec3,
   pub tex_coords: Vec2,
   pub color: Color,
-  pub transformed_position: Vec3,
-  pub transformed_normal: Vec3,
+  pub material: u16, // índice del material de la malla (0 sin materiales); color lleva su difuso
+  pub emissive: bool, // el material tiene luz propia (Ke del .mtl): color lleva esa luz
+  pub transformed_position: Vec3, // pantalla (x, y en píxeles, z profundidad)
+  pub transformed_normal: Vec3,   // mundo
+  pub world_position: Vec3,       // mundo: la iluminación usa esta y no la de pantalla
+  pub velocity: Vec2,             // píxeles que se movió en pantalla desde el frame anterior (ver motion.rs)
 }
 
 impl Vertex {
@@ -18,8 +22,12 @@ impl Vertex {
       normal,
       tex_coords,
       color: Color::black(),
+      material: 0,
+      emissive: false,
       transformed_position: position,
       transformed_normal: normal,
+      world_position: position,
+      velocity: Vec2::new(0.0, 0.0),
     }
   }
 
@@ -29,11 +37,29 @@ impl Vertex {
       normal: Vec3::new(0.0, 0.0, 0.0),
       tex_coords: Vec2::new(0.0, 0.0),
       color,
+      material: 0,
+      emissive: false,
       transformed_position: Vec3::new(0.0, 0.0, 0.0),
       transformed_normal: Vec3::new(0.0, 0.0, 0.0),
+      world_position: Vec3::new(0.0, 0.0, 0.0),
+      velocity: Vec2::new(0.0, 0.0),
     }
   }
 
+  // Material de la cara a la que pertenece el vértice y su color difuso
+  pub fn with_material(mut self, material: u16, color: Color) -> Self {
+    self.material = material;
+    self.color = color;
+    self
+  }
+
+  // Material con luz propia: el color es el de la luz y no se ilumina
+  pub fn with_emission(mut self, color: Color) -> Self {
+    self.color = color;
+    self.emissive = true;
+    self
+  }
+
   pub fn set_transformed(&mut self, position: Vec3, normal: Vec3) {
     self.transformed_position = position;
     self.transformed_normal = normal;
@@ -47,8 +73,12 @@ impl Default for Vertex {
       normal: Vec3::new(0.0, 1.0, 0.0),
       tex_coords: Vec2::new(0.0, 0.0),
       color: Color::black(),
+      material: 0,
+      emissive: false,
       transformed_position: Vec3::new(0.0, 0.0, 0.0),
       transformed_normal: Vec3::new(0.0, 1.0, 0.0),
+      world_position: Vec3::new(0.0, 0.0, 0.0),
+      velocity: Vec2::new(0.0, 0.0),
     }
   }
 }
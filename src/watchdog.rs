@@ -0,0 +1,171 @@
+use std::time::{Duration, Instant};
+use serde::Deserialize;
+use crate::framebuffer::Framebuffer;
+use crate::text::{draw_text, text_width, GLYPH_SIZE};
+use crate::theme;
+
+// ============= VIGILANTE DE FRAMES LENTOS =============
+// En equipos modestos un frame de cerca de un planeta con supersampling puede tardar cientos
+// de milisegundos y la aplicación se convierte en un pase de diapositivas. Si varios frames
+// seguidos superan el umbral, el vigilante baja un escalón de calidad (supersampling, octavas
+// del ruido de los shaders y, en el último, la resolución interna) y lo avisa en pantalla.
+// No vuelve a subir solo: la calidad se recupera al reiniciar (la de partida la decide el
+// historial de rendimiento, ver perf_history.rs). Se configura en [watchdog].
+
+const NOTICE_SECONDS: f32 = 4.0;
+const PADDING: usize = 12;
+const LINE_HEIGHT: usize = GLYPH_SIZE + 4;
+const NOTICE_COLOR: u32 = 0xFFB347;
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct WatchdogConfig {
+    pub enabled: bool,
+    pub threshold_ms: f32,        // un frame más largo cuenta como lento
+    pub consecutive_frames: u32,  // frames lentos seguidos antes de bajar la calidad
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        WatchdogConfig {
+            enabled: true,
+            threshold_ms: 100.0,
+            consecutive_frames: 5,
+        }
+    }
+}
+
+// Escalones de calidad, de mejor a peor (en settings.toml: "high", "medium", "low", "minimum")
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Quality {
+    High,
+    Medium,
+    Low,
+    Minimum,
+}
+
+pub const QUALITIES: [Quality; 4] = [Quality::High, Quality::Medium, Quality::Low, Quality::Minimum];
+
+impl Quality {
+    pub fn name(self) -> &'static str {
+        match self {
+            Quality::High => "high",
+            Quality::Medium => "medium",
+            Quality::Low => "low",
+            Quality::Minimum => "minimum",
+        }
+    }
+
+    // Factor de supersampling máximo (el de cada frame lo decide la resolución adaptativa)
+    pub fn max_supersample(self) -> usize {
+        match self {
+            Quality::High => 2,
+            _ => 1,
+        }
+    }
+
+    // Multiplica el nivel de detalle que reciben los shaders (octavas del ruido)
+    pub fn detail_scale(self) -> f32 {
+        match self {
+            Quality::High | Quality::Medium => 1.0,
+            Quality::Low => 0.6,
+            Quality::Minimum => 0.4,
+        }
+    }
+
+    // La escena se dibuja a 1/n de la resolución de la ventana y se amplía al presentarla
+    pub fn resolution_divisor(self) -> usize {
+        match self {
+            Quality::Minimum => 2,
+            _ => 1,
+        }
+    }
+
+    fn lower(self) -> Option<Quality> {
+        match self {
+            Quality::High => Some(Quality::Medium),
+            Quality::Medium => Some(Quality::Low),
+            Quality::Low => Some(Quality::Minimum),
+            Quality::Minimum => None,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Quality::High => "calidad alta",
+            Quality::Medium => "sin supersampling",
+            Quality::Low => "sin supersampling y menos detalle en las superficies",
+            Quality::Minimum => "media resolución y detalle mínimo",
+        }
+    }
+}
+
+pub struct Watchdog {
+    config: WatchdogConfig,
+    quality: Quality,
+    slow_frames: u32,
+    notice: Option<(String, Instant)>,
+}
+
+impl Watchdog {
+    pub fn new(config: WatchdogConfig) -> Self {
+        Watchdog {
+            config,
+            quality: Quality::High,
+            slow_frames: 0,
+            notice: None,
+        }
+    }
+
+    // Calidad de partida (la elegida por el historial de rendimiento o por los ajustes)
+    pub fn with_quality(mut self, quality: Quality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    pub fn quality(&self) -> Quality {
+        self.quality
+    }
+
+    // Duración del último frame en milisegundos; baja un escalón tras la racha de frames lentos
+    pub fn record(&mut self, frame_ms: f32) {
+        if !self.config.enabled {
+            return;
+        }
+        if frame_ms <= self.config.threshold_ms {
+            self.slow_frames = 0;
+            return;
+        }
+        self.slow_frames += 1;
+        if self.slow_frames < self.config.consecutive_frames {
+            return;
+        }
+        // La cuenta empieza de cero con la nueva calidad (el primer frame tras el cambio
+        // también paga la reserva del framebuffer)
+        self.slow_frames = 0;
+        if let Some(lower) = self.quality.lower() {
+            self.quality = lower;
+            let text = format!("Frames lentos (> {:.0} ms): {}", self.config.threshold_ms, lower.description());
+            self.notice = Some((text, Instant::now()));
+        }
+    }
+
+    // Aviso centrado a la altura `y` (debajo de los avisos de eventos); se desvanece al final
+    pub fn draw_notice(&mut self, screen: &mut Framebuffer, y: usize) {
+        let lifetime = Duration::from_secs_f32(NOTICE_SECONDS);
+        self.notice = self.notice.take().filter(|(_, shown)| shown.elapsed() < lifetime);
+        let theme = theme::current();
+        let Some((text, shown)) = self.notice.as_ref().filter(|_| theme.toasts) else {
+            return;
+        };
+
+        let remaining = 1.0 - shown.elapsed().as_secs_f32() / NOTICE_SECONDS;
+        let alpha = (remaining * 4.0).min(1.0);
+        let width = text_width(text, 1) + PADDING * 2;
+        let x = screen.width.saturating_sub(width) / 2;
+        screen.blend_rect(x, y, width, LINE_HEIGHT + PADDING, theme.panel_background, theme.hud_opacity * alpha);
+        screen.blend_rect(x, y, 3, LINE_HEIGHT + PADDING, NOTICE_COLOR, alpha);
+        draw_text(screen, (x + PADDING) as i32, (y + PADDING / 2 + 2) as i32, text, NOTICE_COLOR, 1);
+    }
+}
@@ -0,0 +1,255 @@
+use nalgebra_glm::Vec3;
+use crate::billboard::{draw_billboards, Billboard, SpriteCamera};
+use crate::collision::{ray_sphere, Collider};
+use crate::components::Orbit;
+use crate::framebuffer::Framebuffer;
+use crate::particles::{Emission, EmitterConfig, ParticleSystem, PARTICLE_STATE};
+use crate::scene_graph::SceneGraph;
+use crate::CelestialObject;
+
+// ============= LÁSERES Y ASTEROIDES DESTRUCTIBLES =============
+// En vuelo libre Espacio dispara rayos láser desde los cañones de las alas, alternando uno y
+// otro. Cada rayo es un proyectil que avanza en línea recta (con la velocidad de la nave
+// sumada) hasta que se apaga; en cada paso su recorrido se comprueba contra las esferas de
+// los cuerpos (ver collision.rs). Los planetas solo lo detienen; una roca del cinturón se
+// parte en dos fragmentos más pequeños que siguen su órbita, o desaparece si ya es pequeña,
+// con una nube de polvo. Cada roca alcanzada suma puntos en el cartel del vuelo libre.
+
+pub const BOLT_SPEED: f32 = 900.0; // unidades/s respecto a la nave
+const BOLT_LIFETIME: f32 = 1.2;   // segundos
+const BOLT_LENGTH: f32 = 6.0;     // largo de la estela dibujada
+const BOLT_RADIUS: f32 = 0.6;
+const BOLT_COLOR: u32 = 0xFF5040; // los del jugador; los drones llevan otro (ver drones.rs)
+const FIRE_INTERVAL: f32 = 0.12;  // segundos entre disparos
+const STREAK_SPRITES: usize = 6;
+
+// Al partirse, cada fragmento tiene este tamaño relativo; por debajo de MIN_FRAGMENT la roca
+// desaparece
+const SPLIT_SCALE: f32 = 0.6;
+const MIN_FRAGMENT: f32 = 1.2;
+pub const SPLIT_POINTS: u32 = 10;
+pub const DESTROY_POINTS: u32 = 25;
+
+const DEBRIS_RAMP: [(f32, u32); 4] = [(0.0, 0xFFE0B0), (0.15, 0xB08860), (0.6, 0x504438), (1.0, 0x000000)];
+const BURST_TIME: f32 = 0.05;
+
+// Polvo y chispas de una roca alcanzada (la rapidez se escala con el tamaño de la roca)
+pub const DEBRIS: EmitterConfig = EmitterConfig {
+    spawn_rate: 3000.0, // × BURST_TIME = 150 partículas por roca
+    max_particles: 2048,
+    lifetime: (0.5, 1.4),
+    speed: (2.0, 8.0),
+    spread: std::f32::consts::PI,
+    size: (0.6, 1.6),
+    colors: &DEBRIS_RAMP,
+    max_pixels: 16.0,
+};
+
+#[derive(Clone, Copy, Debug)]
+struct Bolt {
+    position: Vec3,
+    velocity: Vec3,
+    age: f32,
+}
+
+pub struct Weapons {
+    bolts: Vec<Bolt>,
+    color: u32,
+    cooldown: f32, // segundos hasta el siguiente disparo
+    shots: usize,  // para alternar los cañones
+    fired: Vec<Vec3>, // cañones que han disparado desde la última take_shots (para el sonido)
+    pub score: u32,
+}
+
+impl Weapons {
+    pub fn new() -> Self {
+        Weapons { bolts: Vec::new(), color: BOLT_COLOR, cooldown: 0.0, shots: 0, fired: Vec::new(), score: 0 }
+    }
+
+    pub fn with_color(mut self, color: u32) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn clear(&mut self) {
+        self.bolts.clear();
+        self.fired.clear();
+    }
+
+    // Dispara por el siguiente cañón si ya se ha recargado
+    pub fn fire(&mut self, muzzles: &[Vec3], direction: Vec3, ship_velocity: Vec3) {
+        if self.cooldown > 0.0 || muzzles.is_empty() {
+            return;
+        }
+        let muzzle = muzzles[self.shots % muzzles.len()];
+        self.shots += 1;
+        self.cooldown = FIRE_INTERVAL;
+        self.shoot(muzzle, direction, ship_velocity);
+    }
+
+    // Un rayo desde `muzzle`, sin mirar la recarga (cada dron lleva la suya)
+    pub fn shoot(&mut self, muzzle: Vec3, direction: Vec3, ship_velocity: Vec3) {
+        self.bolts.push(Bolt { position: muzzle, velocity: ship_velocity + direction.normalize() * BOLT_SPEED, age: 0.0 });
+        self.fired.push(muzzle);
+    }
+
+    // Desde dónde se ha disparado desde la última llamada
+    pub fn take_shots(&mut self) -> Vec<Vec3> {
+        std::mem::take(&mut self.fired)
+    }
+
+    // Posición y velocidad de cada rayo en vuelo
+    pub fn bolts(&self) -> Vec<(Vec3, Vec3)> {
+        self.bolts.iter().map(|bolt| (bolt.position, bolt.velocity)).collect()
+    }
+
+    // Avanza los rayos `dt` segundos. Los que tocan un cuerpo de `obstacles` se apagan; los
+    // que tocan un blanco de `targets` (rocas, drones o la nave), también, y se devuelven los
+    // índices de los blancos alcanzados (cada uno una sola vez)
+    pub fn update(&mut self, dt: f32, obstacles: &[Collider], targets: &[Collider]) -> Vec<usize> {
+        self.cooldown = (self.cooldown - dt).max(0.0);
+        let mut hits = Vec::new();
+        self.bolts.retain_mut(|bolt| {
+            let travel = bolt.velocity * dt;
+            let first_hit = |colliders: &[Collider]| {
+                colliders
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, collider)| {
+                        ray_sphere(bolt.position, travel, collider.center, collider.radius + BOLT_RADIUS).map(|time| (index, time))
+                    })
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            };
+            let obstacle = first_hit(obstacles);
+            let target = first_hit(targets);
+            match (target, obstacle) {
+                (Some((index, target_time)), obstacle) if obstacle.is_none_or(|(_, time)| target_time < time) => {
+                    if !hits.contains(&index) {
+                        hits.push(index);
+                    }
+                    return false;
+                }
+                (_, Some(_)) => return false,
+                _ => {}
+            }
+            bolt.position += travel;
+            bolt.age += dt;
+            bolt.age < BOLT_LIFETIME
+        });
+        hits
+    }
+
+    // Cada rayo es una estela corta de puntos brillantes que se apaga hacia atrás
+    pub fn draw(&self, framebuffer: &mut Framebuffer, camera: &SpriteCamera) {
+        let sprites: Vec<Billboard> = self
+            .bolts
+            .iter()
+            .flat_map(|bolt| {
+                let back = -bolt.velocity.normalize() * BOLT_LENGTH;
+                (0..STREAK_SPRITES).map(move |i| {
+                    let t = i as f32 / (STREAK_SPRITES - 1) as f32;
+                    Billboard::new(bolt.position + back * t, BOLT_RADIUS, self.color)
+                        .with_intensity(1.0 - t * 0.7)
+                        .with_pixel_range(1.5, 10.0)
+                })
+            })
+            .collect();
+        draw_billboards(framebuffer, camera, &PARTICLE_STATE, &sprites);
+    }
+}
+
+// Destruye la roca `index` de la lista: la parte en dos fragmentos o la quita, suelta el polvo
+// y devuelve los puntos ganados
+pub fn shatter(rocks: &mut Vec<CelestialObject>, index: usize, graph: &mut SceneGraph, debris: &mut ParticleSystem, velocity: Vec3) -> u32 {
+    let rock = rocks.swap_remove(index);
+    let scale = rock.transform.scale;
+    debris.emit(BURST_TIME, 1.0, |_| Emission {
+        position: rock.transform.translation,
+        direction: Vec3::y(),
+        velocity,
+        speed_scale: scale,
+    });
+
+    let fragment_scale = scale * SPLIT_SCALE;
+    let Some(orbit) = rock.orbit.filter(|_| fragment_scale >= MIN_FRAGMENT) else {
+        return DESTROY_POINTS;
+    };
+    // Los fragmentos quedan uno delante y otro detrás en la órbita, algo separados del radio
+    let spread = fragment_scale / orbit.radius.max(1.0);
+    for (side, suffix) in [(-1.0, "a"), (1.0, "b")] {
+        let mut fragment = rock.clone();
+        fragment.id = format!("{}{}", rock.id, suffix);
+        fragment.transform.scale = fragment_scale;
+        fragment.orbit = Some(Orbit {
+            radius: orbit.radius + side * fragment_scale * 0.5,
+            phase: orbit.phase + side * spread,
+            ..orbit
+        });
+        // El primero se queda el nodo de la roca en el grafo de escena; el segundo necesita otro
+        if side > 0.0 {
+            fragment = fragment.attach(graph, None);
+        }
+        rocks.push(fragment);
+    }
+    SPLIT_POINTS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rock(center: Vec3, radius: f32) -> Collider {
+        Collider { center, radius, velocity: Vec3::zeros(), fatal: false }
+    }
+
+    #[test]
+    fn bolts_hit_the_nearest_rock_and_stop_at_planets() {
+        let mut weapons = Weapons::new();
+        weapons.fire(&[Vec3::zeros()], Vec3::new(0.0, 0.0, -1.0), Vec3::zeros());
+        // Recargando: el segundo disparo no sale
+        weapons.fire(&[Vec3::zeros()], Vec3::new(0.0, 0.0, -1.0), Vec3::zeros());
+        assert_eq!(weapons.bolts.len(), 1);
+
+        // En 0.1 s recorre 90 unidades: alcanza la roca 1 (la más cercana) sin tocar la 0
+        let rocks = [rock(Vec3::new(0.0, 0.0, -80.0), 2.0), rock(Vec3::new(0.0, 0.0, -40.0), 2.0)];
+        assert_eq!(weapons.update(0.1, &[], &rocks), vec![1]);
+        assert!(weapons.bolts.is_empty());
+
+        // Un planeta delante de la roca la protege
+        weapons.update(FIRE_INTERVAL, &[], &[]);
+        weapons.fire(&[Vec3::zeros()], Vec3::new(0.0, 0.0, -1.0), Vec3::zeros());
+        let planet = [rock(Vec3::new(0.0, 0.0, -20.0), 5.0)];
+        assert!(weapons.update(0.1, &planet, &rocks).is_empty());
+        assert!(weapons.bolts.is_empty());
+
+        // Sin nada delante, el rayo se apaga al acabar su vida
+        weapons.update(FIRE_INTERVAL, &[], &[]);
+        weapons.fire(&[Vec3::zeros()], Vec3::new(0.0, 1.0, 0.0), Vec3::zeros());
+        for _ in 0..100 {
+            weapons.update(0.016, &[], &rocks);
+        }
+        assert!(weapons.bolts.is_empty());
+    }
+
+    #[test]
+    fn big_rocks_split_and_small_ones_vanish() {
+        use crate::celestial_shaders::CelestialBody;
+        let mut graph = SceneGraph::new();
+        let mut debris = ParticleSystem::new(DEBRIS, 1);
+        let rock = |scale: f32, graph: &mut SceneGraph| {
+            CelestialObject::new(CelestialBody::Moon, Vec3::zeros(), scale).with_orbit(500.0, 0.01).attach(graph, None)
+        };
+        let mut rocks = vec![rock(4.0, &mut graph), rock(1.5, &mut graph)];
+
+        assert_eq!(shatter(&mut rocks, 0, &mut graph, &mut debris, Vec3::zeros()), SPLIT_POINTS);
+        assert_eq!(rocks.len(), 3);
+        let fragments: Vec<_> = rocks.iter().filter(|rock| rock.transform.scale < 4.0 && rock.transform.scale > 1.5).collect();
+        assert_eq!(fragments.len(), 2);
+        assert_ne!(fragments[0].transform.node, fragments[1].transform.node);
+        assert_ne!(fragments[0].orbit.unwrap().phase, fragments[1].orbit.unwrap().phase);
+
+        let small = rocks.iter().position(|rock| rock.transform.scale == 1.5).unwrap();
+        assert_eq!(shatter(&mut rocks, small, &mut graph, &mut debris, Vec3::zeros()), DESTROY_POINTS);
+        assert_eq!(rocks.len(), 2);
+    }
+}
@@ -0,0 +1,358 @@
+use std::f32::consts::TAU;
+use nalgebra_glm::{Vec2, Vec3};
+use crate::celestial_shaders::{surface_height, CelestialBody};
+use crate::framebuffer::Framebuffer;
+use crate::noise::{fbm, hash3};
+use crate::text::{draw_text, GLYPH_SIZE};
+use crate::theme::{self, HudLayout, Widget};
+use crate::vertex::Vertex;
+
+// ============= CLIMA: HURACANES EN LA TIERRA Y POLVO EN MARTE =============
+// Los huracanes se forman sobre el océano tropical, derivan hacia el oeste y hacia el polo
+// y se disipan en unos días simulados (antes si tocan tierra). Cada uno es una función pura
+// del tiempo sembrada por su turno de formación, así no hay estado que guardar. Se dibujan
+// como una malla de nubes en espiral, con ojo, justo por encima de la superficie.
+
+// Un día terrestre en unidades de tiempo de la simulación (una vuelta de la Tierra a 0.02 rad/frame)
+pub const DAY: f32 = 5.0;
+
+const SPAWN_INTERVAL_DAYS: f32 = 5.0;   // un turno de formación cada 5 días
+const MIN_LIFETIME_DAYS: f32 = 8.0;
+const MAX_LIFETIME_DAYS: f32 = 14.0;
+const SPAWN_ATTEMPTS: i32 = 4;           // longitudes probadas hasta dar con océano
+const CLOUD_HEIGHT: f32 = 1.02;          // capa de nubes (en radios, sobre el relieve)
+const RINGS: usize = 16;                 // resolución radial de la malla
+const SECTORS: usize = 48;               // resolución angular de la malla
+const MIN_DENSITY: f32 = 0.3;            // por debajo el triángulo no se dibuja (cielo despejado)
+
+const NAMES: [&str; 21] = [
+    "Ana", "Bill", "Claudette", "Danny", "Elsa", "Fred", "Grace", "Henri", "Ida", "Julián", "Kate",
+    "Larry", "Mindy", "Nicolás", "Odette", "Peter", "Rosa", "Sam", "Teresa", "Víctor", "Wanda",
+];
+
+#[derive(Clone, Copy)]
+pub struct Hurricane {
+    pub name: &'static str,
+    pub center: Vec3,      // centro en la esfera unitaria del modelo de la Tierra
+    pub radius: f32,       // radio angular de la espiral (radianes)
+    pub intensity: f32,    // [0, 1]: crece, alcanza su pico y se disipa
+    pub hemisphere: f32,   // 1 norte, -1 sur: sentido de giro de la espiral
+    pub age_days: f32,
+    pub lifetime_days: f32,
+}
+
+impl Hurricane {
+    // Categoría Saffir-Simpson aproximada (1 a 5) según la intensidad actual
+    pub fn category(&self) -> u8 {
+        1 + (self.intensity * 4.99) as u8
+    }
+
+    pub fn latitude(&self) -> f32 {
+        self.center.y.asin().to_degrees()
+    }
+
+    pub fn longitude(&self) -> f32 {
+        self.center.z.atan2(self.center.x).to_degrees()
+    }
+}
+
+// Huracanes activos en un instante (los turnos recientes que siguen con vida)
+pub fn active_hurricanes(time: f32) -> Vec<Hurricane> {
+    let current = (time / (SPAWN_INTERVAL_DAYS * DAY)).floor() as i32;
+    let oldest = current - (MAX_LIFETIME_DAYS / SPAWN_INTERVAL_DAYS).ceil() as i32 - 1;
+    (oldest..=current).filter_map(|slot| hurricane(slot, time)).collect()
+}
+
+fn hurricane(slot: i32, time: f32) -> Option<Hurricane> {
+    // No todos los turnos forman un huracán
+    if hash3(slot, 41, 7) < 0.25 {
+        return None;
+    }
+    let birth = (slot as f32 + hash3(slot, 1, 2) * 0.6) * SPAWN_INTERVAL_DAYS * DAY;
+    let lifetime_days = MIN_LIFETIME_DAYS + hash3(slot, 3, 4) * (MAX_LIFETIME_DAYS - MIN_LIFETIME_DAYS);
+    let age_days = (time - birth) / DAY;
+    if !(0.0..lifetime_days).contains(&age_days) {
+        return None;
+    }
+
+    // Génesis entre 8° y 20° de latitud, sobre el océano
+    let hemisphere = if hash3(slot, 5, 6) < 0.5 { 1.0 } else { -1.0 };
+    let genesis_latitude = (8.0 + hash3(slot, 7, 8) * 12.0).to_radians() * hemisphere;
+    let genesis_longitude = (0..SPAWN_ATTEMPTS)
+        .map(|attempt| hash3(slot, 9, attempt) * TAU)
+        .find(|&longitude| is_ocean(direction(genesis_latitude, longitude)))?;
+
+    // Deriva hacia el oeste con los alisios y hacia el polo, cada vez más deprisa
+    let progress = age_days / lifetime_days;
+    let latitude = genesis_latitude + (age_days * 0.9 * (1.0 + progress)).to_radians() * hemisphere;
+    let longitude = genesis_longitude - (age_days * 3.5).to_radians();
+    let center = direction(latitude.clamp(-1.2, 1.2), longitude);
+
+    // Se intensifica los primeros días y decae al final; sobre tierra pierde fuerza
+    let peak = 0.5 + hash3(slot, 11, 12) * 0.5;
+    let growth = (age_days / 2.5).min(1.0);
+    let decay = ((lifetime_days - age_days) / 3.0).min(1.0);
+    let landfall = if is_ocean(center) { 1.0 } else { 0.45 };
+    let intensity = peak * growth * decay * landfall;
+
+    Some(Hurricane {
+        name: NAMES[slot.rem_euclid(NAMES.len() as i32) as usize],
+        center,
+        radius: (6.0 + intensity * 8.0).to_radians(),
+        intensity,
+        hemisphere,
+        age_days,
+        lifetime_days,
+    })
+}
+
+fn direction(latitude: f32, longitude: f32) -> Vec3 {
+    Vec3::new(latitude.cos() * longitude.cos(), latitude.sin(), latitude.cos() * longitude.sin())
+}
+
+fn is_ocean(direction: Vec3) -> bool {
+    surface_height(CelestialBody::Earth, direction) == Some(0.0)
+}
+
+// Densidad de nubes de la espiral en coordenadas polares alrededor del ojo
+// (r = distancia normalizada al radio, theta = ángulo alrededor del centro)
+fn spiral_density(storm: &Hurricane, r: f32, theta: f32, time: f32) -> f32 {
+    if r >= 1.0 {
+        return 0.0;
+    }
+    // Dos brazos en espiral logarítmica que giran con el tiempo (al revés en el sur)
+    let arms = (0.5 + 0.5 * (2.0 * (theta * storm.hemisphere + 2.2 * (r + 0.05).ln()) + time * 0.8).cos()).powi(2);
+    let envelope = 1.0 - r * r;
+    let eyewall = (-((r - 0.14) / 0.08).powi(2)).exp();
+    let eye = ((r - 0.05) / 0.05).clamp(0.0, 1.0);
+    let ragged = 0.75 + 0.5 * fbm(r * 6.0 + 3.0, theta.sin() * 2.0, theta.cos() * 2.0 + time * 0.05, 2);
+    ((eyewall + envelope * (0.1 + 0.9 * arms)) * eye * ragged).clamp(0.0, 1.0) * (0.4 + 0.6 * storm.intensity)
+}
+
+// Malla de nubes de un huracán en el espacio del modelo de la Tierra. La densidad va en
+// tex_coords.x para que el shader de nubes de tormenta la use
+pub fn hurricane_mesh(storm: &Hurricane, time: f32) -> Vec<Vertex> {
+    let helper = if storm.center.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+    let tangent_u = storm.center.cross(&helper).normalize();
+    let tangent_v = storm.center.cross(&tangent_u);
+
+    let point = |ring: usize, sector: usize| {
+        let r = ring as f32 / RINGS as f32;
+        let theta = sector as f32 / SECTORS as f32 * TAU;
+        let offset = (tangent_u * theta.cos() + tangent_v * theta.sin()) * (r * storm.radius).tan();
+        (storm.center + offset).normalize() * CLOUD_HEIGHT
+    };
+
+    let mut vertices = Vec::new();
+    for ring in 0..RINGS {
+        for sector in 0..SECTORS {
+            // Densidad en el centro de la celda: las celdas despejadas no se dibujan
+            let r = (ring as f32 + 0.5) / RINGS as f32;
+            let theta = (sector as f32 + 0.5) / SECTORS as f32 * TAU;
+            let density = spiral_density(storm, r, theta, time);
+            if density < MIN_DENSITY {
+                continue;
+            }
+
+            let p00 = point(ring, sector);
+            let p01 = point(ring + 1, sector);
+            let p10 = point(ring, sector + 1);
+            let p11 = point(ring + 1, sector + 1);
+            for [a, b, c] in [[p00, p10, p01], [p01, p10, p11]] {
+                let normal = (b - a).cross(&(c - a));
+                if normal.magnitude() > 1.0e-9 {
+                    for position in [a, b, c] {
+                        vertices.push(Vertex::new(position, normal.normalize(), Vec2::new(density, 0.0)));
+                    }
+                }
+            }
+        }
+    }
+    vertices
+}
+
+// ============= MARTE: TORMENTAS DE POLVO =============
+// Tormentas regionales que nacen, crecen y derivan hacia el este en pocos soles, y de vez
+// en cuando una tormenta global que envuelve el planeta durante semanas. También son una
+// función pura del tiempo; el shader de Marte las usa para tapar la superficie con polvo.
+
+const REGIONAL_INTERVAL_DAYS: f32 = 3.0;
+const REGIONAL_MIN_LIFETIME_DAYS: f32 = 4.0;
+const REGIONAL_MAX_LIFETIME_DAYS: f32 = 9.0;
+const DUST_YEAR_DAYS: f32 = 60.0;        // ciclo estacional: como mucho una tormenta global por año
+const GLOBAL_GROWTH_DAYS: f32 = 6.0;
+const GLOBAL_PEAK_DAYS: f32 = 12.0;
+const GLOBAL_DECAY_DAYS: f32 = 10.0;
+
+#[derive(Clone, Copy)]
+pub struct DustStorm {
+    pub center: Vec3,      // centro en la esfera unitaria del modelo de Marte
+    pub radius: f32,       // radio angular (radianes)
+    pub intensity: f32,    // opacidad del polvo [0, 1]
+    pub age_days: f32,
+    pub lifetime_days: f32,
+}
+
+impl DustStorm {
+    pub fn latitude(&self) -> f32 {
+        self.center.y.asin().to_degrees()
+    }
+
+    pub fn longitude(&self) -> f32 {
+        self.center.z.atan2(self.center.x).to_degrees()
+    }
+}
+
+// Tormentas regionales activas en un instante
+pub fn active_dust_storms(time: f32) -> Vec<DustStorm> {
+    let current = (time / (REGIONAL_INTERVAL_DAYS * DAY)).floor() as i32;
+    let oldest = current - (REGIONAL_MAX_LIFETIME_DAYS / REGIONAL_INTERVAL_DAYS).ceil() as i32 - 1;
+    (oldest..=current).filter_map(|slot| dust_storm(slot, time)).collect()
+}
+
+fn dust_storm(slot: i32, time: f32) -> Option<DustStorm> {
+    if hash3(slot, 53, 19) < 0.3 {
+        return None;
+    }
+    let birth = (slot as f32 + hash3(slot, 21, 22) * 0.8) * REGIONAL_INTERVAL_DAYS * DAY;
+    let lifetime_days = REGIONAL_MIN_LIFETIME_DAYS
+        + hash3(slot, 23, 24) * (REGIONAL_MAX_LIFETIME_DAYS - REGIONAL_MIN_LIFETIME_DAYS);
+    let age_days = (time - birth) / DAY;
+    if !(0.0..lifetime_days).contains(&age_days) {
+        return None;
+    }
+
+    // Nacen en latitudes medias y los vientos las llevan hacia el este
+    let latitude = ((hash3(slot, 25, 26) * 2.0 - 1.0) * 50.0).to_radians();
+    let longitude = hash3(slot, 27, 28) * TAU + (age_days * 5.0).to_radians();
+
+    // Crecen mientras se intensifican y se deshacen al final
+    let progress = age_days / lifetime_days;
+    let max_radius = 20.0 + hash3(slot, 29, 30) * 20.0;
+    let growth = (age_days / 1.5).min(1.0);
+    let decay = ((lifetime_days - age_days) / 2.0).min(1.0);
+
+    Some(DustStorm {
+        center: direction(latitude, longitude),
+        radius: (8.0 + (max_radius - 8.0) * progress.sqrt()).to_radians(),
+        intensity: (0.5 + hash3(slot, 31, 32) * 0.5) * growth * decay,
+        age_days,
+        lifetime_days,
+    })
+}
+
+// Opacidad de la tormenta global en curso [0, 1] (0 si este año no hay)
+pub fn global_dust_level(time: f32) -> f32 {
+    let year = (time / (DUST_YEAR_DAYS * DAY)).floor() as i32;
+    if hash3(year, 61, 37) < 0.4 {
+        return 0.0;
+    }
+    let onset = hash3(year, 63, 39) * (DUST_YEAR_DAYS - GLOBAL_GROWTH_DAYS - GLOBAL_PEAK_DAYS - GLOBAL_DECAY_DAYS);
+    let day = time / DAY - year as f32 * DUST_YEAR_DAYS - onset;
+    if day < 0.0 {
+        0.0
+    } else if day < GLOBAL_GROWTH_DAYS {
+        day / GLOBAL_GROWTH_DAYS
+    } else if day < GLOBAL_GROWTH_DAYS + GLOBAL_PEAK_DAYS {
+        1.0
+    } else {
+        (1.0 - (day - GLOBAL_GROWTH_DAYS - GLOBAL_PEAK_DAYS) / GLOBAL_DECAY_DAYS).max(0.0)
+    }
+}
+
+// Cobertura de polvo [0, 1] en un punto de la superficie de Marte (espacio del modelo)
+pub fn dust_coverage(pos: Vec3, time: f32) -> f32 {
+    // Nubes de polvo con textura algodonosa que se desplaza con el viento
+    let billow = fbm(pos.x * 5.0 + time * 0.08, pos.y * 5.0, pos.z * 5.0 - time * 0.05, 3);
+    let global = global_dust_level(time) * (0.75 + 0.25 * billow);
+
+    let regional = active_dust_storms(time)
+        .iter()
+        .map(|storm| {
+            let angle = pos.normalize().dot(&storm.center).clamp(-1.0, 1.0).acos();
+            let falloff = (1.0 - angle / storm.radius).clamp(0.0, 1.0);
+            storm.intensity * (falloff * 2.0).min(1.0) * (0.6 + 0.6 * billow)
+        })
+        .fold(0.0, f32::max);
+
+    regional.max(global).min(1.0)
+}
+
+// ============= PANEL DE EVENTOS =============
+
+// Eventos meteorológicos de un cuerpo (None si no tiene clima simulado)
+pub fn events(body: CelestialBody, time: f32) -> Option<Vec<String>> {
+    let mut lines: Vec<String> = match body {
+        CelestialBody::Earth => active_hurricanes(time)
+            .iter()
+            .map(|storm| {
+                format!(
+                    "Huracán {} - cat. {} - {} - día {:.0} de {:.0}",
+                    storm.name,
+                    storm.category(),
+                    format_position(storm.latitude(), storm.longitude()),
+                    storm.age_days.floor() + 1.0,
+                    storm.lifetime_days.ceil(),
+                )
+            })
+            .collect(),
+        CelestialBody::Mars => {
+            let mut lines = Vec::new();
+            let global = global_dust_level(time);
+            if global > 0.0 {
+                lines.push(format!("Tormenta de polvo global - opacidad {:.0}%", global * 100.0));
+            }
+            lines.extend(active_dust_storms(time).iter().map(|storm| {
+                format!(
+                    "Tormenta regional - {} - {:.0}° de radio - sol {:.0} de {:.0}",
+                    format_position(storm.latitude(), storm.longitude()),
+                    storm.radius.to_degrees(),
+                    storm.age_days.floor() + 1.0,
+                    storm.lifetime_days.ceil(),
+                )
+            }));
+            lines
+        }
+        _ => return None,
+    };
+    if lines.is_empty() {
+        let calm = if body == CelestialBody::Earth { "Sin huracanes activos" } else { "Sin tormentas de polvo" };
+        lines.push(calm.to_string());
+    }
+    Some(lines)
+}
+
+fn format_position(latitude: f32, longitude: f32) -> String {
+    format!(
+        "{:.0}°{} {:.0}°{}",
+        latitude.abs(),
+        if latitude >= 0.0 { "N" } else { "S" },
+        longitude.abs(),
+        if longitude >= 0.0 { "E" } else { "O" },
+    )
+}
+
+// Panel de eventos meteorológicos (se muestra con el cuerpo seleccionado)
+pub fn draw_events(screen: &mut Framebuffer, layout: &mut HudLayout, lines: &[String]) {
+    const PADDING: usize = 12;
+    const LINE_HEIGHT: usize = GLYPH_SIZE + 4;
+
+    let title = "Eventos meteorológicos";
+    let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0).max(title.chars().count()) * GLYPH_SIZE
+        + PADDING * 2;
+    let height = PADDING * 2 + LINE_HEIGHT * (lines.len() + 1) + 4;
+    let Some((x, y)) = layout.place(Widget::Weather, width, height) else {
+        return;
+    };
+    let theme = theme::current();
+    screen.blend_rect(x, y, width, height, theme.panel_background, theme.hud_opacity);
+
+    let text_x = (x + PADDING) as i32;
+    let mut text_y = (y + PADDING) as i32;
+    draw_text(screen, text_x, text_y, title, theme.title, 1);
+    text_y += (LINE_HEIGHT + 4) as i32;
+    for line in lines {
+        draw_text(screen, text_x, text_y, line, theme.text, 1);
+        text_y += LINE_HEIGHT as i32;
+    }
+}